@@ -1,16 +1,27 @@
-use std::path::Path;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
 
 use actix_web::cookie::SameSite;
+use anyhow::anyhow;
 use config::{Config, FileFormat, FileSourceFile};
-use deadpool_redis::{Config as RedisConfig, Pool as RedisPool};
+use deadpool_redis::{
+    Config as RedisConfig, ConnectionAddr as RedisConnectionAddr,
+    ConnectionInfo as RedisConnectionInfo, Pool as RedisPool, PoolConfig as RedisPoolConfig,
+    RedisConnectionInfo as RedisConnectionOptions,
+};
 use enum_display::EnumDisplay;
+use ipnet::IpNet;
 use log::LevelFilter;
 use secrecy::{ExposeSecret as _, SecretString};
 use serde::{Deserialize as _, Deserializer};
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
 use sqlx::{ConnectOptions as _, PgPool};
 
-use use_cases::settings::{AuthorizationSettings, PasswordSettings};
+use domain::events::DomainEvent;
+use domain::models::user::UserPermissionCode;
+use use_cases::settings::{
+    AuthorizationSettings, PasswordSettings, RetentionSettings, SettingsError,
+};
 
 /// 設定ファイルディレクトリ・パス
 pub const SETTINGS_DIR_NAME: &str = "settings";
@@ -25,18 +36,22 @@ pub const ENV_APP_ENVIRONMENT_DEFAULT: &str = "development";
 pub enum AppEnvironment {
     /// 開発環境
     Development,
+    /// テスト環境
+    Test,
+    /// ステージング環境
+    Staging,
     /// 運用環境
     Production,
 }
 
-impl From<String> for AppEnvironment {
+impl TryFrom<String> for AppEnvironment {
+    type Error = anyhow::Error;
+
     /// アプリの動作環境を表現する文字列から、アプリの動作環境を判定する。
     ///
-    /// アプリの動作環境を表現する文字列が`development`の場合は開発環境、
-    /// `production`の場合は運用環境と判定する。
-    ///
-    /// 上記以外の場合、開発環境と判定する。
-    /// なお、大文字と小文字は無視する。
+    /// アプリの動作環境を表現する文字列が`development`または`develop`の場合は開発環境、
+    /// `test`の場合はテスト環境、`staging`の場合はステージング環境、`production`の場合は
+    /// 運用環境と判定する。なお、大文字と小文字は無視する。
     ///
     /// # 引数
     ///
@@ -44,11 +59,18 @@ impl From<String> for AppEnvironment {
     ///
     /// # 戻り値
     ///
-    /// アプリの動作環境
-    fn from(value: String) -> Self {
+    /// アプリの動作環境、上記以外の場合はエラー
+    fn try_from(value: String) -> Result<Self, Self::Error> {
         match value.to_lowercase().as_str() {
-            "production" => Self::Production,
-            _ => Self::Development,
+            "development" | "develop" => Ok(Self::Development),
+            "test" => Ok(Self::Test),
+            "staging" => Ok(Self::Staging),
+            "production" => Ok(Self::Production),
+            _ => Err(anyhow!(
+                "アプリの動作環境が不正です。`{}`には`development`、`test`、`staging`または`production`を指定してください。({})",
+                ENV_APP_ENVIRONMENT,
+                value
+            )),
         }
     }
 }
@@ -68,18 +90,611 @@ pub struct AppSettings {
     pub authorization: AuthorizationSettings,
     /// ロギング設定
     pub logging: LoggingSettings,
+    /// 保持期間設定
+    #[serde(default)]
+    pub retention: RetentionSettings,
+    /// トレーシング設定
+    #[serde(default)]
+    pub tracing: TracingSettings,
+    /// Eメール設定
+    ///
+    /// 指定した場合は、SMTPサーバーを介して、ユーザーに通知するEメールを送信する。
+    /// 指定しない場合は、Eメールを送信しない。
+    #[serde(default)]
+    pub email: Option<EmailSettings>,
+    /// 初期管理者設定
+    ///
+    /// 指定した場合は、アプリケーションの起動時に、初期管理者を登録する。
+    #[serde(default)]
+    pub initial_admin: Option<InitialAdminSettings>,
+    /// Webhook設定のリスト
+    ///
+    /// 指定した場合は、購読するイベントが発生したときに、Webhookの送信先にイベントを通知する。
+    #[serde(default)]
+    pub webhooks: Vec<WebhookSettings>,
+}
+
+impl AppSettings {
+    /// アプリケーション設定を検証する。
+    ///
+    /// それぞれの設定を検証して、見つかったすべての違反を1つのエラーにまとめて返す。
+    /// 違反した設定のキーには、設定のセクションを付与する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        error.merge("http_server", self.http_server.validate());
+        error.merge("database", self.database.validate());
+        error.merge("redis", self.redis.validate());
+        error.merge("password", self.password.validate());
+        error.merge("authorization", self.authorization.validate());
+        error.merge("tracing", self.tracing.validate());
+        if let Some(email) = &self.email {
+            error.merge("email", email.validate());
+        }
+        if let Some(initial_admin) = &self.initial_admin {
+            error.merge("initial_admin", initial_admin.validate());
+        }
+        for (index, webhook) in self.webhooks.iter().enumerate() {
+            error.merge(&format!("webhooks[{}]", index), webhook.validate());
+        }
+
+        error.into_result()
+    }
 }
 
 /// HTTPサーバー設定
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct HttpServerSettings {
+    /// リスニングホスト
+    ///
+    /// IPアドレスまたはホスト名を指定する。
+    #[serde(default = "default_http_server_host")]
+    pub host: String,
     /// リスニングポート番号
     pub port: u16,
+    /// APIのパスに付与するプレフィックス
+    ///
+    /// `/`で始まり`/`で終わらないパスを指定する。空文字を指定した場合は、プレフィックスを付与しない。
+    #[serde(default = "default_http_server_api_prefix")]
+    pub api_prefix: String,
+    /// プレフィックスを付与していない以前のパスへのリクエストを、プレフィックスを付与したパスに
+    /// 308 Permanent Redirectでリダイレクトするか示すフラグ
+    ///
+    /// デフォルトは`false`で、以前のパスへのリクエストには404 Not Foundを返す。
+    #[serde(default)]
+    pub redirect_unprefixed_paths: bool,
+    /// ユーザーがサインアップできるか示すフラグ
+    ///
+    /// デフォルトは`true`で、`false`の場合はサインアップのリクエストに403 Forbiddenを返す。
+    #[serde(default = "default_http_server_registration_enabled")]
+    pub registration_enabled: bool,
+    /// ユーザーがサインアップで登録できるユーザー権限コードのリスト
+    ///
+    /// デフォルトは一般ユーザーのみで、管理者としてアクセスしていないリクエストが、リストにない
+    /// ユーザー権限コードでサインアップした場合は403 Forbiddenを返す。
+    #[serde(default = "default_http_server_sign_up_permission_codes")]
+    pub sign_up_permission_codes: Vec<i16>,
+    /// 管理者が一括登録できるユーザーの最大数
+    ///
+    /// デフォルトは`1000`で、最大数を超えるユーザーの一括登録のリクエストには400 Bad Requestを返す。
+    #[serde(default = "default_http_server_max_import_users")]
+    pub max_import_users: usize,
+    /// JSON形式のリクエストボディの最大サイズ（バイト）
+    ///
+    /// デフォルトは`16384`で、最大サイズを超えるリクエストボディには413 Payload Too Largeを返す。
+    /// ユーザーの一括登録は、最大サイズに一括登録できるユーザーの最大数を乗じたサイズまで受け付ける。
+    #[serde(default = "default_http_server_max_json_payload_bytes")]
+    pub max_json_payload_bytes: usize,
+    /// 未知のフィールドを含むリクエストボディを拒否するか示すフラグ
+    ///
+    /// デフォルトは`true`で、未知のフィールドを含むリクエストボディに400 Bad Requestを返す。
+    /// クライアントが余分なフィールドを送信している場合に、クライアントを移行するまで`false`にして、
+    /// サインアップ、サインイン及びユーザーの一括登録で、未知のフィールドを無視する。
+    #[serde(default = "default_http_server_strict_request_bodies")]
+    pub strict_request_bodies: bool,
+    /// `Idempotency-Key`ヘッダを指定したリクエストと、そのレスポンスを記録する期間（秒）
+    ///
+    /// デフォルトは`86400`で、期間内に同じ冪等キーで再送信されたリクエストには、記録したレスポンスを返す。
+    #[serde(default = "default_http_server_idempotency_key_seconds")]
+    pub idempotency_key_seconds: u64,
+    /// メンテナンス中に返すレスポンスの`Retry-After`ヘッダに設定する秒数
+    ///
+    /// デフォルトは`300`
+    #[serde(default = "default_http_server_maintenance_retry_after_seconds")]
+    pub maintenance_retry_after_seconds: u64,
+    /// 管理者向けのエンドポイントへのアクセスを許可するIPアドレスの範囲（CIDR表記）のリスト
+    ///
+    /// デフォルトは空で、空の場合はアクセス元のIPアドレスを確認しない。
+    /// 範囲外のIPアドレスからのリクエストには403 Forbiddenを返す。
+    #[serde(default)]
+    pub admin_ip_allowlist: Vec<String>,
+    /// 信頼するリバースプロキシのIPアドレスまたはIPアドレスの範囲（CIDR表記）のリスト
+    ///
+    /// デフォルトは空で、`X-Forwarded-For`及び`Forwarded`ヘッダを無視して、接続元のIPアドレスを
+    /// アクセス元とする。接続元が信頼するプロキシの場合のみ、これらのヘッダからアクセス元を判定する。
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
     /// アクセス及びリフレッシュトークンを保存するクッキーに付与するSameSite属性
     #[serde(deserialize_with = "deserialize_same_site")]
     pub same_site: SameSite,
     /// アクセス及びリフレッシュトークンを保存するクッキーにSecure属性を付けるか示すフラグ
     pub secure: bool,
+    /// アクセス及びリフレッシュトークンを保存するクッキーに付与するPath属性
+    ///
+    /// デフォルトは`/`
+    #[serde(default = "default_http_server_cookie_path")]
+    pub cookie_path: String,
+    /// アクセス及びリフレッシュトークンを保存するクッキーに付与するDomain属性
+    ///
+    /// 指定しない場合は、Domain属性を付与しない。
+    #[serde(default)]
+    pub cookie_domain: Option<String>,
+    /// アクセス及びリフレッシュトークンを保存するクッキーの名前に付与するプレフィックス
+    ///
+    /// デフォルトは空文字で、クッキーの名前は`access`と`refresh`になる。
+    #[serde(default)]
+    pub cookie_name_prefix: String,
+    /// アクセス及びリフレッシュトークンを保存するクッキーの名前に、`__Host-`プレフィックスを付与するか示すフラグ
+    ///
+    /// `__Host-`プレフィックスを付与したクッキーは、Secure属性が必要で、Path属性が`/`、
+    /// かつDomain属性を付与してはならない。
+    #[serde(default)]
+    pub use_host_prefix: bool,
+    /// TLS設定
+    #[serde(default)]
+    pub tls: Option<TlsSettings>,
+    /// ワーカースレッド数
+    ///
+    /// デフォルトは利用可能な論理CPU数
+    #[serde(default = "default_http_server_workers")]
+    pub workers: usize,
+    /// キープアライブ時間（秒）
+    ///
+    /// 0を指定した場合は、キープアライブを無効にする。
+    #[serde(default = "default_http_server_keep_alive_seconds")]
+    pub keep_alive_seconds: u64,
+    /// クライアントがリクエストヘッダを送信し終えるまでのタイムアウト時間（秒）
+    ///
+    /// 0を指定した場合は、タイムアウトを無効にする。
+    #[serde(default = "default_http_server_client_request_timeout_seconds")]
+    pub client_request_timeout_seconds: u64,
+    /// HTTPサーバーを停止するときに、ワーカーが処理中のリクエストを完了するまで待機する時間（秒）
+    #[serde(default = "default_http_server_shutdown_timeout_seconds")]
+    pub shutdown_timeout_seconds: u64,
+    /// レスポンスボディを圧縮するか示すフラグ
+    ///
+    /// デフォルトは`false`で、`true`の場合はリクエストの`Accept-Encoding`ヘッダに応じて、
+    /// レスポンスボディをgzip、brotliまたはzstdで圧縮する。
+    #[serde(default)]
+    pub compression: bool,
+    /// メトリクス設定
+    #[serde(default)]
+    pub metrics: MetricsSettings,
+    /// APIドキュメント設定
+    #[serde(default)]
+    pub api_docs: ApiDocsSettings,
+    /// アクセスログ設定
+    #[serde(default)]
+    pub access_log: AccessLogSettings,
+}
+
+/// メトリクス設定
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct MetricsSettings {
+    /// `/metrics`エンドポイントを公開するか示すフラグ
+    #[serde(default = "default_metrics_enabled")]
+    pub enabled: bool,
+    /// `/metrics`エンドポイントにアクセスを許可するIPアドレスのリスト
+    ///
+    /// デフォルトはループバックアドレスのみ
+    #[serde(default = "default_metrics_allowed_ips")]
+    pub allowed_ips: Vec<IpAddr>,
+    /// リポジトリのメソッドの呼び出しを計測するか示すフラグ
+    ///
+    /// デフォルトは`true`で、ユーザーリポジトリとトークンリポジトリのメソッドの処理時間と成否を、
+    /// スパンと`repository_call_duration_seconds`メトリクスに記録する。`false`の場合は計測しない。
+    #[serde(default = "default_metrics_instrument_repositories")]
+    pub instrument_repositories: bool,
+}
+
+impl Default for MetricsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: default_metrics_enabled(),
+            allowed_ips: default_metrics_allowed_ips(),
+            instrument_repositories: default_metrics_instrument_repositories(),
+        }
+    }
+}
+
+impl MetricsSettings {
+    /// `/metrics`エンドポイントへのアクセスを許可するIPアドレスであるか確認する。
+    ///
+    /// IPv4射影IPv6アドレスは、IPv4アドレスとして確認する。
+    ///
+    /// # 引数
+    ///
+    /// * `ip` - アクセス元のIPアドレス
+    ///
+    /// # 戻り値
+    ///
+    /// アクセスを許可する場合は`true`、それ以外の場合は`false`
+    pub fn is_allowed(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.allowed_ips
+            .iter()
+            .any(|allowed| allowed.to_canonical() == ip)
+    }
+}
+
+fn default_metrics_enabled() -> bool {
+    true
+}
+
+fn default_metrics_allowed_ips() -> Vec<IpAddr> {
+    vec![
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(Ipv6Addr::LOCALHOST),
+    ]
+}
+
+fn default_metrics_instrument_repositories() -> bool {
+    true
+}
+
+/// APIドキュメント設定
+///
+/// OpenAPIドキュメントは、常に`/api-docs/openapi.json`で公開する。
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct ApiDocsSettings {
+    /// `/api-docs`でSwagger UIを公開するか示すフラグ
+    ///
+    /// デフォルトは`false`
+    #[serde(default)]
+    pub swagger_ui: bool,
+}
+
+/// アクセスログ設定
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct AccessLogSettings {
+    /// ヘルスチェック及びレディネスチェックへのリクエストを、アクセスログに記録しないか示すフラグ
+    ///
+    /// デフォルトは`false`
+    #[serde(default)]
+    pub exclude_health_check: bool,
+}
+
+/// TLS設定
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct TlsSettings {
+    /// HTTPサーバーがTLSでリクエストを待ち受けるか示すフラグ
+    pub enabled: bool,
+    /// PEM形式のサーバー証明書（チェーン）ファイルのパス
+    pub cert_path: PathBuf,
+    /// PEM形式の秘密鍵ファイルのパス
+    pub key_path: PathBuf,
+}
+
+/// HTTPサーバーがリッスンするデフォルトのホスト
+pub const DEFAULT_HTTP_SERVER_HOST: &str = "127.0.0.1";
+
+fn default_http_server_host() -> String {
+    String::from(DEFAULT_HTTP_SERVER_HOST)
+}
+
+/// APIのパスに付与するデフォルトのプレフィックス
+pub const DEFAULT_HTTP_SERVER_API_PREFIX: &str = "/api/v1";
+
+fn default_http_server_api_prefix() -> String {
+    String::from(DEFAULT_HTTP_SERVER_API_PREFIX)
+}
+
+/// アクセス及びリフレッシュトークンを保存するクッキーに付与するデフォルトのPath属性
+pub const DEFAULT_HTTP_SERVER_COOKIE_PATH: &str = "/";
+
+fn default_http_server_cookie_path() -> String {
+    String::from(DEFAULT_HTTP_SERVER_COOKIE_PATH)
+}
+
+fn default_http_server_registration_enabled() -> bool {
+    true
+}
+
+fn default_http_server_strict_request_bodies() -> bool {
+    true
+}
+
+fn default_http_server_sign_up_permission_codes() -> Vec<i16> {
+    vec![UserPermissionCode::General as i16]
+}
+
+/// 管理者が一括登録できるユーザーのデフォルトの最大数
+pub const DEFAULT_HTTP_SERVER_MAX_IMPORT_USERS: usize = 1000;
+
+fn default_http_server_max_import_users() -> usize {
+    DEFAULT_HTTP_SERVER_MAX_IMPORT_USERS
+}
+
+/// JSON形式のリクエストボディのデフォルトの最大サイズ（バイト）
+pub const DEFAULT_HTTP_SERVER_MAX_JSON_PAYLOAD_BYTES: usize = 16 * 1024;
+
+fn default_http_server_max_json_payload_bytes() -> usize {
+    DEFAULT_HTTP_SERVER_MAX_JSON_PAYLOAD_BYTES
+}
+
+/// リクエストとレスポンスを冪等キーで記録するデフォルトの期間（秒）
+pub const DEFAULT_HTTP_SERVER_IDEMPOTENCY_KEY_SECONDS: u64 = 86400;
+
+fn default_http_server_idempotency_key_seconds() -> u64 {
+    DEFAULT_HTTP_SERVER_IDEMPOTENCY_KEY_SECONDS
+}
+
+/// メンテナンス中に返すレスポンスの`Retry-After`ヘッダに設定するデフォルトの秒数
+pub const DEFAULT_HTTP_SERVER_MAINTENANCE_RETRY_AFTER_SECONDS: u64 = 300;
+
+fn default_http_server_maintenance_retry_after_seconds() -> u64 {
+    DEFAULT_HTTP_SERVER_MAINTENANCE_RETRY_AFTER_SECONDS
+}
+
+/// HTTPサーバーのデフォルトのキープアライブ時間（秒）
+pub const DEFAULT_HTTP_SERVER_KEEP_ALIVE_SECONDS: u64 = 5;
+
+/// HTTPサーバーのデフォルトのクライアントリクエストタイムアウト時間（秒）
+pub const DEFAULT_HTTP_SERVER_CLIENT_REQUEST_TIMEOUT_SECONDS: u64 = 5;
+
+/// HTTPサーバーのデフォルトのシャットダウンタイムアウト時間（秒）
+pub const DEFAULT_HTTP_SERVER_SHUTDOWN_TIMEOUT_SECONDS: u64 = 30;
+
+fn default_http_server_workers() -> usize {
+    std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+}
+
+fn default_http_server_keep_alive_seconds() -> u64 {
+    DEFAULT_HTTP_SERVER_KEEP_ALIVE_SECONDS
+}
+
+fn default_http_server_client_request_timeout_seconds() -> u64 {
+    DEFAULT_HTTP_SERVER_CLIENT_REQUEST_TIMEOUT_SECONDS
+}
+
+fn default_http_server_shutdown_timeout_seconds() -> u64 {
+    DEFAULT_HTTP_SERVER_SHUTDOWN_TIMEOUT_SECONDS
+}
+
+impl HttpServerSettings {
+    /// HTTPサーバー設定を検証する。
+    ///
+    /// リスニングホストが、IPアドレスまたはホスト名として妥当であるか確認する。
+    /// また、APIのパスのプレフィックスが妥当であるか、`__Host-`プレフィックスを付与するクッキーの属性が
+    /// 妥当であるか、ワーカースレッド数と一括登録できるユーザーの最大数が1以上であるか確認する。
+    /// さらに、管理者向けのエンドポイントへのアクセスを許可するIPアドレスの範囲が、CIDR表記であるか確認して、
+    /// 信頼するプロキシが、IPアドレスまたはCIDR表記であるか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if self.host.parse::<IpAddr>().is_err() && !is_valid_host_name(&self.host) {
+            error.push(
+                "host",
+                format!(
+                    "HTTPサーバーのリスニングホストが、IPアドレスまたはホスト名として妥当ではありません。({})",
+                    self.host
+                ),
+            );
+        }
+        if !self.api_prefix.is_empty()
+            && (!self.api_prefix.starts_with('/') || self.api_prefix.ends_with('/'))
+        {
+            error.push(
+                "api_prefix",
+                format!(
+                    "APIのパスのプレフィックスは、`/`で始まり`/`で終わらないパスを指定してください。({})",
+                    self.api_prefix
+                ),
+            );
+        }
+        if self.use_host_prefix {
+            if !self.secure {
+                error.push(
+                    "secure",
+                    "`__Host-`プレフィックスを付与するクッキーには、Secure属性を付与する必要があります。",
+                );
+            }
+            if self.cookie_path != "/" {
+                error.push(
+                    "cookie_path",
+                    format!(
+                        "`__Host-`プレフィックスを付与するクッキーのPath属性は、`/`でなければなりません。({})",
+                        self.cookie_path
+                    ),
+                );
+            }
+            if let Some(domain) = &self.cookie_domain {
+                error.push(
+                    "cookie_domain",
+                    format!(
+                        "`__Host-`プレフィックスを付与するクッキーには、Domain属性を付与できません。({})",
+                        domain
+                    ),
+                );
+            }
+        }
+        if self.workers == 0 {
+            error.push(
+                "workers",
+                "HTTPサーバーのワーカースレッド数には1以上を指定してください。",
+            );
+        }
+        if self.max_import_users == 0 {
+            error.push(
+                "max_import_users",
+                "一括登録できるユーザーの最大数には1以上を指定してください。",
+            );
+        }
+        if self.max_json_payload_bytes == 0 {
+            error.push(
+                "max_json_payload_bytes",
+                "JSON形式のリクエストボディの最大サイズには1以上を指定してください。",
+            );
+        }
+        if self.idempotency_key_seconds == 0 {
+            error.push(
+                "idempotency_key_seconds",
+                "冪等キーでリクエストを記録する期間には1以上を指定してください。",
+            );
+        }
+        for code in &self.sign_up_permission_codes {
+            if UserPermissionCode::try_from(*code).is_err() {
+                error.push(
+                    "sign_up_permission_codes",
+                    format!(
+                        "サインアップで登録できるユーザー権限コードが範囲外です。({})",
+                        code
+                    ),
+                );
+            }
+        }
+        for range in &self.admin_ip_allowlist {
+            if range.parse::<IpNet>().is_err() {
+                error.push(
+                    "admin_ip_allowlist",
+                    format!(
+                        "管理者向けのエンドポイントへのアクセスを許可するIPアドレスの範囲は、CIDR表記で指定してください。({})",
+                        range
+                    ),
+                );
+            }
+        }
+        for proxy in &self.trusted_proxies {
+            if parse_ip_range(proxy).is_none() {
+                error.push(
+                    "trusted_proxies",
+                    format!(
+                        "信頼するプロキシは、IPアドレスまたはCIDR表記のIPアドレスの範囲で指定してください。({})",
+                        proxy
+                    ),
+                );
+            }
+        }
+
+        error.into_result()
+    }
+
+    /// ユーザーがサインアップで、ユーザー権限を持つユーザーを登録できるか確認する。
+    ///
+    /// # 引数
+    ///
+    /// * `code` - ユーザー権限コード
+    ///
+    /// # 戻り値
+    ///
+    /// 登録できる場合は`true`
+    pub fn allows_sign_up_with(&self, code: UserPermissionCode) -> bool {
+        self.sign_up_permission_codes.contains(&(code as i16))
+    }
+
+    /// 管理者向けのエンドポイントへのアクセスを許可するIPアドレスの範囲を返す。
+    ///
+    /// # 戻り値
+    ///
+    /// IPアドレスの範囲のリスト、CIDR表記として妥当ではない範囲を含む場合はエラー
+    pub fn admin_ip_ranges(&self) -> anyhow::Result<Vec<IpNet>> {
+        self.admin_ip_allowlist
+            .iter()
+            .map(|range| {
+                range.parse::<IpNet>().map_err(|_| {
+                    anyhow!(
+                        "管理者向けのエンドポイントへのアクセスを許可するIPアドレスの範囲が、CIDR表記として妥当ではありません。({})",
+                        range
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// 信頼するリバースプロキシのIPアドレスの範囲を返す。
+    ///
+    /// IPアドレスを指定した場合は、そのIPアドレスのみを含む範囲とする。
+    ///
+    /// # 戻り値
+    ///
+    /// IPアドレスの範囲のリスト、IPアドレスまたはCIDR表記として妥当ではない値を含む場合はエラー
+    pub fn trusted_proxy_ranges(&self) -> anyhow::Result<Vec<IpNet>> {
+        self.trusted_proxies
+            .iter()
+            .map(|proxy| {
+                parse_ip_range(proxy).ok_or_else(|| {
+                    anyhow!(
+                        "信頼するプロキシが、IPアドレスまたはCIDR表記として妥当ではありません。({})",
+                        proxy
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// 有効なTLS設定を返す。
+    ///
+    /// # 戻り値
+    ///
+    /// TLSが有効な場合はTLS設定、それ以外の場合は`None`
+    pub fn enabled_tls(&self) -> Option<&TlsSettings> {
+        self.tls.as_ref().filter(|tls| tls.enabled)
+    }
+
+    /// HTTPサーバーがリクエストを待ち受けるアドレスを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// `<host>:<port>`形式のアドレス
+    /// ホストがIPv6アドレスの場合は`[<host>]:<port>`形式のアドレス
+    pub fn address(&self) -> String {
+        match self.host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(_)) => format!("[{}]:{}", self.host, self.port),
+            _ => format!("{}:{}", self.host, self.port),
+        }
+    }
+}
+
+/// IPアドレスまたはCIDR表記のIPアドレスの範囲を解析する。
+///
+/// # 引数
+///
+/// * `value` - IPアドレスまたはCIDR表記のIPアドレスの範囲
+///
+/// # 戻り値
+///
+/// IPアドレスの範囲、解析できない場合は`None`
+fn parse_ip_range(value: &str) -> Option<IpNet> {
+    value
+        .parse::<IpNet>()
+        .ok()
+        .or_else(|| value.parse::<IpAddr>().ok().map(IpNet::from))
+}
+
+/// ホスト名として妥当であるか確認する。
+///
+/// ホスト名は、ドットで区切られたラベルで構成され、全体で253文字以下でなければならない。
+/// また、それぞれのラベルは1文字以上63文字以下の英数字またはハイフンで構成され、
+/// ハイフンで始まったり、終わったりしてはならない。
+///
+/// # 引数
+///
+/// * `host` - ホスト名
+///
+/// # 戻り値
+///
+/// ホスト名として妥当な場合は`true`、それ以外の場合は`false`
+fn is_valid_host_name(host: &str) -> bool {
+    if host.is_empty() || 253 < host.len() {
+        return false;
+    }
+    host.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
 }
 
 fn deserialize_same_site<'de, D>(deserializer: D) -> Result<SameSite, D::Error>
@@ -117,9 +732,122 @@ pub struct DatabaseSettings {
     pub connection_timeout_seconds: u64,
     /// ログに記録するSQLステートメントの最小レベル
     pub log_statements: LevelFilter,
+    /// 接続プールが保持する最大接続数
+    ///
+    /// デフォルトは`10`
+    #[serde(default = "default_database_max_connections")]
+    pub max_connections: u32,
+    /// 接続プールが保持する最小接続数
+    ///
+    /// デフォルトは`0`
+    #[serde(default)]
+    pub min_connections: u32,
+    /// 接続プールがアイドル状態の接続を閉じるまでの時間（秒）
+    ///
+    /// デフォルトは`600`で、0を指定した場合は、アイドル状態の接続を閉じない。
+    #[serde(default = "default_database_idle_timeout_seconds")]
+    pub idle_timeout_seconds: u64,
+    /// SQLステートメントのタイムアウト時間（ミリ秒）
+    ///
+    /// デフォルトは`30000`で、0を指定した場合は、タイムアウトを無効にする。
+    #[serde(default = "default_database_statement_timeout_ms")]
+    pub statement_timeout_ms: u64,
+    /// ユーザーリポジトリがSQLステートメントの完了を待機する時間（ミリ秒）
+    ///
+    /// デフォルトは`10000`で、0を指定した場合は、タイムアウトを無効にする。データベースが応答しない
+    /// 場合でも、接続プールの接続を占有し続けないように、アプリケーション側でSQLステートメントの
+    /// 実行を取り消す。
+    #[serde(default = "default_database_query_timeout_ms")]
+    pub query_timeout_ms: u64,
+    /// データベースに接続するときに通知するアプリケーション名
+    ///
+    /// デフォルトは`actix-web-example`
+    #[serde(default = "default_database_application_name")]
+    pub application_name: String,
+    /// アプリケーションの起動時に、データベースをマイグレートするか示すフラグ
+    ///
+    /// デフォルトは`false`
+    #[serde(default)]
+    pub migrate_on_startup: bool,
+    /// アプリケーションの起動時に、データベースに接続できるか確認するか示すフラグ
+    ///
+    /// デフォルトは`false`で、最初にデータベースを使用するときに接続する。`true`の場合は、
+    /// HTTPサーバーがリクエストを待ち受ける前に、接続プールが保持する最小接続数（最低1つ）の接続を
+    /// 作成して`SELECT 1`を実行し、接続できない場合はアプリケーションの起動を中止する。
+    #[serde(default)]
+    pub fail_fast: bool,
+}
+
+/// 接続プールが保持するデフォルトの最大接続数
+pub const DEFAULT_DATABASE_MAX_CONNECTIONS: u32 = 10;
+
+/// 接続プールがアイドル状態の接続を閉じるまでのデフォルトの時間（秒）
+pub const DEFAULT_DATABASE_IDLE_TIMEOUT_SECONDS: u64 = 600;
+
+/// SQLステートメントのデフォルトのタイムアウト時間（ミリ秒）
+pub const DEFAULT_DATABASE_STATEMENT_TIMEOUT_MS: u64 = 30_000;
+
+/// ユーザーリポジトリがSQLステートメントの完了を待機するデフォルトの時間（ミリ秒）
+pub const DEFAULT_DATABASE_QUERY_TIMEOUT_MS: u64 = 10_000;
+
+/// データベースに接続するときに通知するデフォルトのアプリケーション名
+pub const DEFAULT_DATABASE_APPLICATION_NAME: &str = "actix-web-example";
+
+fn default_database_max_connections() -> u32 {
+    DEFAULT_DATABASE_MAX_CONNECTIONS
+}
+
+fn default_database_idle_timeout_seconds() -> u64 {
+    DEFAULT_DATABASE_IDLE_TIMEOUT_SECONDS
+}
+
+fn default_database_statement_timeout_ms() -> u64 {
+    DEFAULT_DATABASE_STATEMENT_TIMEOUT_MS
+}
+
+fn default_database_query_timeout_ms() -> u64 {
+    DEFAULT_DATABASE_QUERY_TIMEOUT_MS
+}
+
+fn default_database_application_name() -> String {
+    String::from(DEFAULT_DATABASE_APPLICATION_NAME)
 }
 
 impl DatabaseSettings {
+    /// データベース設定を検証する。
+    ///
+    /// ユーザー名、ホスト及びデータベース名が空でないか、接続タイムアウト秒が1以上であるか確認する。
+    /// また、接続プールの最大接続数が1以上で、最小接続数が最大接続数以下であるか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        for (field, value) in [
+            ("user", &self.user),
+            ("host", &self.host),
+            ("name", &self.name),
+        ] {
+            if value.is_empty() {
+                error.push(field, "空でない文字列を指定してください。");
+            }
+        }
+        if self.connection_timeout_seconds == 0 {
+            error.push("connection_timeout_seconds", "1以上を指定してください。");
+        }
+        if self.max_connections == 0 {
+            error.push("max_connections", "1以上を指定してください。");
+        }
+        if self.max_connections < self.min_connections {
+            error.push(
+                "min_connections",
+                format!(
+                    "接続プールの最小接続数は、最大接続数以下を指定してください。(min_connections: {}, max_connections: {})",
+                    self.min_connections, self.max_connections
+                ),
+            );
+        }
+
+        error.into_result()
+    }
+
     /// データベースを指定しない接続オプションを取得する。
     ///
     /// # 戻り値
@@ -136,6 +864,8 @@ impl DatabaseSettings {
             .port(self.port)
             .host(&self.host)
             .ssl_mode(ssl_mode)
+            .application_name(&self.application_name)
+            .options([("statement_timeout", self.statement_timeout_ms)])
     }
 
     /// データベース接続オプションを取得する。
@@ -148,17 +878,44 @@ impl DatabaseSettings {
         options.log_statements(self.log_statements)
     }
 
+    /// ユーザーリポジトリがSQLステートメントの完了を待機する時間を取得する。
+    ///
+    /// # 戻り値
+    ///
+    /// SQLステートメントの完了を待機する時間、タイムアウトを無効にする場合は`None`
+    pub fn query_timeout(&self) -> Option<std::time::Duration> {
+        match self.query_timeout_ms {
+            0 => None,
+            milliseconds => Some(std::time::Duration::from_millis(milliseconds)),
+        }
+    }
+
     /// PostgreSQL接続プールを取得する。
     ///
     /// # 戻り値
     ///
     /// PostgreSQL接続プール
     pub fn connection_pool(&self) -> PgPool {
+        self.pool_options().connect_lazy_with(self.with_db())
+    }
+
+    /// PostgreSQL接続プールのオプションを取得する。
+    ///
+    /// # 戻り値
+    ///
+    /// PostgreSQL接続プールのオプション
+    pub fn pool_options(&self) -> PgPoolOptions {
+        let idle_timeout = match self.idle_timeout_seconds {
+            0 => None,
+            seconds => Some(std::time::Duration::from_secs(seconds)),
+        };
         PgPoolOptions::new()
             .acquire_timeout(std::time::Duration::from_secs(
                 self.connection_timeout_seconds,
             ))
-            .connect_lazy_with(self.with_db())
+            .max_connections(self.max_connections)
+            .min_connections(self.min_connections)
+            .idle_timeout(idle_timeout)
     }
 }
 
@@ -169,23 +926,153 @@ pub struct RedisSettings {
     pub port: u16,
     /// ホスト
     pub host: String,
+    /// パスワード
+    ///
+    /// 指定しない場合は、認証しない。
+    #[serde(default)]
+    pub password: Option<SecretString>,
+    /// データベース番号
+    ///
+    /// デフォルトは`0`
+    #[serde(default)]
+    pub db: u8,
+    /// TLSで接続するか示すフラグ
+    ///
+    /// デフォルトは`false`
+    #[serde(default)]
+    pub tls: bool,
+    /// 接続プールが保持する最大接続数
+    ///
+    /// 指定しない場合は、論理CPU数の4倍
+    #[serde(default)]
+    pub pool_max_size: Option<usize>,
+    /// アプリケーションの起動時に、Redisに接続できるか確認するか示すフラグ
+    ///
+    /// デフォルトは`false`で、最初にRedisを使用するときに接続する。`true`の場合は、HTTPサーバーが
+    /// リクエストを待ち受ける前に、接続プールから接続を取得して`PING`コマンドを送信し、接続できない
+    /// 場合はアプリケーションの起動を中止する。
+    #[serde(default)]
+    pub fail_fast: bool,
 }
 
 impl RedisSettings {
+    /// Redis設定を検証する。
+    ///
+    /// ホストが空でないか、接続プールの最大接続数が1以上であるか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if self.host.is_empty() {
+            error.push("host", "空でない文字列を指定してください。");
+        }
+        if self.pool_max_size == Some(0) {
+            error.push("pool_max_size", "1以上を指定してください。");
+        }
+
+        error.into_result()
+    }
+
+    /// Redis接続情報を取得する。
+    ///
+    /// # 戻り値
+    ///
+    /// Redis接続情報
+    pub fn connection_info(&self) -> RedisConnectionInfo {
+        let addr = match self.tls {
+            true => RedisConnectionAddr::TcpTls {
+                host: self.host.clone(),
+                port: self.port,
+                insecure: false,
+            },
+            false => RedisConnectionAddr::Tcp(self.host.clone(), self.port),
+        };
+        RedisConnectionInfo {
+            addr,
+            redis: RedisConnectionOptions {
+                db: self.db as i64,
+                username: None,
+                password: self
+                    .password
+                    .as_ref()
+                    .map(|password| password.expose_secret().to_string()),
+            },
+        }
+    }
+
     /// Redis接続プールを取得する。
     ///
+    /// 接続プールを構築できなかった場合は、パスワードを除いた接続パラメーターを含むエラーを返す。
+    ///
     /// # 戻り値
     ///
     /// Redis接続プール
     pub fn connection_pool(&self) -> anyhow::Result<RedisPool> {
-        let url = format!("redis://{}:{}", &self.host, self.port);
         let config = RedisConfig {
-            url: Some(url),
-            connection: None,
-            pool: None,
+            url: None,
+            connection: Some(self.connection_info()),
+            pool: self.pool_max_size.map(RedisPoolConfig::new),
         };
 
-        Ok(config.create_pool(None)?)
+        config.create_pool(None).map_err(|e| {
+            anyhow!(
+                "Redis接続プールを構築できませんでした。ホスト、ポート番号、データベース番号、TLS及びパスワードの設定を確認してください。(host: {}, port: {}, db: {}, tls: {}, password: {}): {}",
+                self.host,
+                self.port,
+                self.db,
+                self.tls,
+                match self.password {
+                    Some(_) => "<redacted>",
+                    None => "<none>",
+                },
+                e
+            )
+        })
+    }
+}
+
+/// 初期管理者設定
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct InitialAdminSettings {
+    /// Eメールアドレス
+    pub email: String,
+    /// パスワード
+    pub password: SecretString,
+    /// 苗字
+    pub family_name: String,
+    /// 名前
+    pub given_name: String,
+    /// 郵便番号
+    pub postal_code: String,
+    /// 住所
+    pub address: String,
+    /// 固定電話番号
+    #[serde(default)]
+    pub fixed_phone_number: Option<String>,
+    /// 携帯電話番号
+    #[serde(default)]
+    pub mobile_phone_number: Option<String>,
+}
+
+impl InitialAdminSettings {
+    /// 初期管理者設定を検証する。
+    ///
+    /// Eメールアドレスとパスワードが空でないか、固定電話番号または携帯電話番号を指定しているか確認する。
+    /// それぞれの値の形式は、初期管理者を登録するときに検証する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if self.email.is_empty() {
+            error.push("email", "空でない文字列を指定してください。");
+        }
+        if self.password.expose_secret().is_empty() {
+            error.push("password", "空でない文字列を指定してください。");
+        }
+        if self.fixed_phone_number.is_none() && self.mobile_phone_number.is_none() {
+            error.push(
+                "mobile_phone_number",
+                "固定電話番号または携帯電話番号を指定してください。",
+            );
+        }
+
+        error.into_result()
     }
 }
 
@@ -194,17 +1081,205 @@ impl RedisSettings {
 pub struct LoggingSettings {
     /// ログレベル
     pub level: log::Level,
+    /// ログの出力形式
+    ///
+    /// デフォルトは`pretty`
+    #[serde(default)]
+    pub format: LogFormat,
 }
 
-/// アプリケーション設定を取得する。
-///
-/// # 引数
-///
-/// * `app_env` - アプリケーションの動作環境
-/// * `settings_dir` - アプリケーション設定ファイルを格納しているディレクトリのパス
-///
-/// # 戻り値
-///
+/// ログの出力形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Bunyan形式
+    #[default]
+    Pretty,
+    /// 1行に1つのイベントを出力するJSON形式
+    ///
+    /// イベントのフィールドと、イベントを囲むスパンのフィールドをフラットに出力する。
+    Json,
+}
+
+/// トレーシング設定
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct TracingSettings {
+    /// トレースをエクスポートするOTLPコレクターのエンドポイント（例: `http://localhost:4317`）
+    ///
+    /// 指定した場合は、スパンをOpenTelemetryのトレースとしてOTLP（gRPC）でエクスポートする。
+    #[serde(default)]
+    pub otlp_endpoint: Option<String>,
+}
+
+impl TracingSettings {
+    /// トレーシング設定を検証する。
+    ///
+    /// OTLPコレクターのエンドポイントを指定した場合は、`http://`または`https://`で始まるか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if let Some(endpoint) = &self.otlp_endpoint {
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                error.push(
+                    "otlp_endpoint",
+                    "`http://`または`https://`で始まるURLを指定してください。",
+                );
+            }
+        }
+
+        error.into_result()
+    }
+}
+
+/// Eメール設定
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct EmailSettings {
+    /// SMTPサーバーのホスト名
+    pub host: String,
+    /// SMTPサーバーのポート番号
+    pub port: u16,
+    /// SMTPサーバーの認証に使用するユーザー名
+    #[serde(default)]
+    pub username: Option<String>,
+    /// SMTPサーバーの認証に使用するパスワード
+    #[serde(default)]
+    pub password: Option<SecretString>,
+    /// 送信元のEメールアドレス（例: `Actix Web Example <no-reply@example.com>`）
+    pub from: String,
+    /// STARTTLSでSMTPサーバーとの通信を暗号化するか示すフラグ
+    #[serde(default)]
+    pub starttls: bool,
+}
+
+impl EmailSettings {
+    /// Eメール設定を検証する。
+    ///
+    /// ホスト名と送信元のEメールアドレスが空でないか、ポート番号が1以上か、ユーザー名とパスワードを
+    /// 両方とも指定しているか、または両方とも指定していないか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if self.host.is_empty() {
+            error.push("host", "空でない文字列を指定してください。");
+        }
+        if self.port == 0 {
+            error.push("port", "1以上を指定してください。");
+        }
+        if self.username.is_some() != self.password.is_some() {
+            error.push(
+                "password",
+                "ユーザー名とパスワードは両方とも指定するか、両方とも指定しないでください。",
+            );
+        }
+        if self.from.is_empty() {
+            error.push("from", "空でない文字列を指定してください。");
+        }
+
+        error.into_result()
+    }
+}
+
+/// Webhook設定
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct WebhookSettings {
+    /// イベントを通知する送信先のURL
+    pub url: String,
+    /// リクエストボディの署名に使用する秘密鍵
+    pub secret: SecretString,
+    /// 購読するイベントの種類を示す名前のリスト（例: `user_registered`、`account_locked`）
+    pub events: Vec<String>,
+    /// 送信に失敗した場合に再送信する回数を含めた、最大の送信回数
+    ///
+    /// デフォルトは`5`
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    /// 最初に再送信するまでの待機時間（ミリ秒）
+    ///
+    /// デフォルトは`1000`で、再送信するたびに待機時間を2倍にする。
+    #[serde(default = "default_webhook_initial_backoff_millis")]
+    pub initial_backoff_millis: u64,
+    /// 送信先の応答を待機する時間（秒）
+    ///
+    /// デフォルトは`10`
+    #[serde(default = "default_webhook_timeout_seconds")]
+    pub timeout_seconds: u64,
+}
+
+impl WebhookSettings {
+    /// Webhook設定を検証する。
+    ///
+    /// URLが`http://`または`https://`で始まるか、秘密鍵が空でないか、購読するイベントを1つ以上
+    /// 指定して、それぞれが既知のイベントの種類か、最大の送信回数と応答を待機する時間が1以上か
+    /// 確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if !self.url.starts_with("http://") && !self.url.starts_with("https://") {
+            error.push(
+                "url",
+                "`http://`または`https://`で始まるURLを指定してください。",
+            );
+        }
+        if self.secret.expose_secret().is_empty() {
+            error.push("secret", "空でない文字列を指定してください。");
+        }
+        if self.events.is_empty() {
+            error.push("events", "1つ以上のイベントを指定してください。");
+        }
+        for event in self.events.iter() {
+            if !DomainEvent::NAMES.contains(&event.as_str()) {
+                error.push(
+                    "events",
+                    format!(
+                        "`{}`は不明なイベントです。{}のいずれかを指定してください。",
+                        event,
+                        DomainEvent::NAMES.join("、")
+                    ),
+                );
+            }
+        }
+        if self.max_attempts == 0 {
+            error.push("max_attempts", "1以上を指定してください。");
+        }
+        if self.timeout_seconds == 0 {
+            error.push("timeout_seconds", "1以上を指定してください。");
+        }
+
+        error.into_result()
+    }
+
+    /// イベントを購読しているか確認する。
+    ///
+    /// # 引数
+    ///
+    /// * `event` - イベントの種類を示す名前
+    ///
+    /// # 戻り値
+    ///
+    /// イベントを購読している場合は`true`
+    pub fn subscribes(&self, event: &str) -> bool {
+        self.events.iter().any(|e| e == event)
+    }
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    5
+}
+
+fn default_webhook_initial_backoff_millis() -> u64 {
+    1000
+}
+
+fn default_webhook_timeout_seconds() -> u64 {
+    10
+}
+
+/// アプリケーション設定を取得する。
+///
+/// # 引数
+///
+/// * `app_env` - アプリケーションの動作環境
+/// * `settings_dir` - アプリケーション設定ファイルを格納しているディレクトリのパス
+///
+/// # 戻り値
+///
 /// アプリケーション設定
 pub fn read_app_settings<P: AsRef<Path>>(
     app_env: AppEnvironment,
@@ -233,6 +1308,8 @@ pub fn read_app_settings<P: AsRef<Path>>(
                 .separator("__"),
         )
         .build()?;
+    // ファイルに記録されたシークレットを読み込み
+    let settings = resolve_secret_files(settings, |name| std::env::var(name).ok())?;
 
     // アプリケーション設定を読み込み
     settings
@@ -240,6 +1317,96 @@ pub fn read_app_settings<P: AsRef<Path>>(
         .map_err(|e| e.into())
 }
 
+/// ファイルから読み込むことができるシークレットの設定キーと、シークレットを記録したファイルのパスを
+/// 指定する環境変数の名前
+const SECRET_SETTINGS: [(&str, &[&str]); 8] = [
+    (
+        "database.password",
+        &[
+            "POSTGRES_DATABASE__PASSWORD_FILE",
+            "APP_DATABASE__PASSWORD_FILE",
+        ],
+    ),
+    ("redis.password", &["APP_REDIS__PASSWORD_FILE"]),
+    (
+        "initial_admin.password",
+        &["APP_INITIAL_ADMIN__PASSWORD_FILE"],
+    ),
+    ("password.pepper", &["APP_PASSWORD__PEPPER_FILE"]),
+    (
+        "authorization.jwt_token_secret",
+        &["APP_AUTHORIZATION__JWT_TOKEN_SECRET_FILE"],
+    ),
+    (
+        "authorization.totp_secret_key",
+        &["APP_AUTHORIZATION__TOTP_SECRET_KEY_FILE"],
+    ),
+    (
+        "authorization.introspection_credential",
+        &["APP_AUTHORIZATION__INTROSPECTION_CREDENTIAL_FILE"],
+    ),
+    ("email.password", &["APP_EMAIL__PASSWORD_FILE"]),
+];
+
+/// シークレットを記録したファイルのパスを指定する設定のキー
+const SECRET_FILE_KEY: &str = "file";
+
+/// ファイルに記録されたシークレットを読み込み、設定を上書きする。
+///
+/// シークレットを記録したファイルのパスは、`*_FILE`環境変数、または設定ファイルで`{ file: <path> }`形式で
+/// 指定する。両方指定された場合は、環境変数を優先する。ファイルから読み込んだシークレットは、前後の
+/// 空白文字を取り除く。
+///
+/// # 引数
+///
+/// * `settings` - 設定
+/// * `env` - 環境変数の名前から値を取得する関数
+///
+/// # 戻り値
+///
+/// シークレットを上書きした設定
+fn resolve_secret_files<F>(settings: Config, env: F) -> anyhow::Result<Config>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut overrides = vec![];
+    for (key, env_names) in SECRET_SETTINGS {
+        let path = match env_names.iter().find_map(|name| env(name)) {
+            Some(path) => Some(path),
+            None => settings
+                .get_table(key)
+                .ok()
+                .and_then(|table| table.get(SECRET_FILE_KEY).cloned())
+                .map(|value| value.into_string())
+                .transpose()
+                .map_err(|e| {
+                    anyhow!("設定`{}`のシークレットファイルのパスが不正です。{}", key, e)
+                })?,
+        };
+        if let Some(path) = path {
+            let secret = std::fs::read_to_string(&path).map_err(|e| {
+                anyhow!(
+                    "設定`{}`のシークレットファイルを読み込めません。({}) {}",
+                    key,
+                    path,
+                    e
+                )
+            })?;
+            overrides.push((key, secret.trim().to_string()));
+        }
+    }
+    if overrides.is_empty() {
+        return Ok(settings);
+    }
+
+    let mut builder = Config::builder().add_source(settings);
+    for (key, secret) in overrides {
+        builder = builder.set_override(key, secret)?;
+    }
+
+    Ok(builder.build()?)
+}
+
 /// `Config`がロードする設定ファイルのパスを構築する。
 ///
 /// # 引数
@@ -264,23 +1431,106 @@ pub mod tests {
     use log::LevelFilter;
     use secrecy::ExposeSecret;
 
-    use crate::settings::{read_app_settings, AppEnvironment, DatabaseSettings, SETTINGS_DIR_NAME};
+    use config::{Config, FileFormat};
+    use ipnet::IpNet;
+
+    use domain::models::user::UserPermissionCode;
+
+    use crate::settings::{
+        read_app_settings, resolve_secret_files, AppEnvironment, DatabaseSettings, EmailSettings,
+        HttpServerSettings, RedisConnectionAddr, RedisSettings, WebhookSettings,
+        DEFAULT_DATABASE_APPLICATION_NAME, DEFAULT_DATABASE_IDLE_TIMEOUT_SECONDS,
+        DEFAULT_DATABASE_MAX_CONNECTIONS, DEFAULT_DATABASE_QUERY_TIMEOUT_MS,
+        DEFAULT_DATABASE_STATEMENT_TIMEOUT_MS, DEFAULT_HTTP_SERVER_API_PREFIX,
+        DEFAULT_HTTP_SERVER_CLIENT_REQUEST_TIMEOUT_SECONDS, DEFAULT_HTTP_SERVER_COOKIE_PATH,
+        DEFAULT_HTTP_SERVER_HOST, DEFAULT_HTTP_SERVER_KEEP_ALIVE_SECONDS,
+        DEFAULT_HTTP_SERVER_MAX_IMPORT_USERS, DEFAULT_HTTP_SERVER_MAX_JSON_PAYLOAD_BYTES,
+        DEFAULT_HTTP_SERVER_SHUTDOWN_TIMEOUT_SECONDS, ENV_APP_ENVIRONMENT, SETTINGS_DIR_NAME,
+    };
+
+    /// シークレットを記録したファイルを作成する。
+    fn create_secret_file(name: &str, contents: &str) -> anyhow::Result<std::path::PathBuf> {
+        let dir = std::env::temp_dir().join(format!("awe-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(name);
+        std::fs::write(&path, contents)?;
+
+        Ok(path)
+    }
+
+    /// シークレットの設定を含む設定を構築する。
+    fn secret_settings(yaml: &str) -> anyhow::Result<Config> {
+        Ok(Config::builder()
+            .add_source(config::File::from_str(yaml, FileFormat::Yaml))
+            .build()?)
+    }
+
+    /// 設定ファイルと環境変数で指定したファイルからシークレットを読み込み、インラインで指定した
+    /// シークレットはそのまま読み込めることを確認
+    #[test]
+    fn secrets_can_be_read_from_files() -> anyhow::Result<()> {
+        let pepper = create_secret_file("pepper", "  file-pepper\n")?;
+        let db_password = create_secret_file("db-password", "file-db-password\n")?;
+        let yaml = format!(
+            "database:\n  password: inline-db-password\npassword:\n  pepper:\n    file: {}\nauthorization:\n  jwt_token_secret: inline-secret\n",
+            pepper.display()
+        );
+        let settings = secret_settings(&yaml)?;
+        let db_password = db_password.display().to_string();
+
+        let settings = resolve_secret_files(settings, |name| {
+            (name == "POSTGRES_DATABASE__PASSWORD_FILE").then(|| db_password.clone())
+        })?;
+
+        assert_eq!("file-pepper", settings.get_string("password.pepper")?);
+        assert_eq!(
+            "file-db-password",
+            settings.get_string("database.password")?
+        );
+        assert_eq!(
+            "inline-secret",
+            settings.get_string("authorization.jwt_token_secret")?
+        );
+
+        Ok(())
+    }
+
+    /// シークレットを記録したファイルが存在しない場合に、設定のキーを含むエラーになることを確認
+    #[test]
+    fn missing_secret_file_produces_error_naming_the_field() -> anyhow::Result<()> {
+        let settings = secret_settings("password:\n  pepper:\n    file: /nonexistent/pepper\n")?;
+
+        let result = resolve_secret_files(settings, |_| None);
 
-    /// 文字列からアプリの動作環境を正しく判定できることを確認
+        let message = result.unwrap_err().to_string();
+        assert!(message.contains("password.pepper"), "{}", message);
+        assert!(message.contains("/nonexistent/pepper"), "{}", message);
+
+        Ok(())
+    }
+
+    /// 文字列からアプリの動作環境を正しく判定して、不明な文字列の場合はエラーになることを確認
     #[test]
     fn can_retrieve_app_environment_from_strings() {
         let candidates = [
+            (AppEnvironment::Development, "development"),
             (AppEnvironment::Development, "develop"),
             (AppEnvironment::Development, "DEVELOP"),
+            (AppEnvironment::Test, "test"),
+            (AppEnvironment::Staging, "staging"),
+            (AppEnvironment::Staging, "STAGING"),
             (AppEnvironment::Production, "production"),
             (AppEnvironment::Production, "PRODUCTION"),
-            (AppEnvironment::Development, ""),
-            (AppEnvironment::Development, "foobar"),
         ];
         for (expected, candidate) in candidates {
-            let environment: AppEnvironment = candidate.to_string().into();
+            let environment = AppEnvironment::try_from(candidate.to_string()).unwrap();
             assert_eq!(expected, environment);
         }
+        for candidate in ["", "foobar", "prodcution"] {
+            let result = AppEnvironment::try_from(candidate.to_string());
+            let message = result.unwrap_err().to_string();
+            assert!(message.contains(ENV_APP_ENVIRONMENT), "{}", message);
+        }
     }
 
     /// 開発環境のアプリケーション設定を正しくロードできることを確認
@@ -294,6 +1544,7 @@ pub mod tests {
 
         let settings_dir = crate_dir.join("..").join(SETTINGS_DIR_NAME);
         let app_settings = read_app_settings(AppEnvironment::Development, settings_dir)?;
+        assert_eq!(DEFAULT_HTTP_SERVER_HOST, app_settings.http_server.host);
         assert_eq!(8000, app_settings.http_server.port);
         assert_eq!(
             "very-long-and-complex-string",
@@ -301,6 +1552,19 @@ pub mod tests {
         );
         validate_database_settings(&app_settings.database);
         assert!(!app_settings.database.require_ssl); // SSL接続を要求しない
+        validate_database_pool_settings(
+            &app_settings.database,
+            DEFAULT_DATABASE_MAX_CONNECTIONS,
+            0,
+            Some(DEFAULT_DATABASE_IDLE_TIMEOUT_SECONDS),
+            DEFAULT_DATABASE_STATEMENT_TIMEOUT_MS,
+        );
+        assert_eq!(
+            Some(std::time::Duration::from_millis(
+                DEFAULT_DATABASE_QUERY_TIMEOUT_MS
+            )),
+            app_settings.database.query_timeout()
+        );
         assert_eq!(LevelFilter::Trace, app_settings.database.log_statements);
         assert_eq!(log::Level::Debug, app_settings.logging.level);
 
@@ -318,6 +1582,7 @@ pub mod tests {
 
         let settings_dir = crate_dir.join("..").join(SETTINGS_DIR_NAME);
         let app_settings = read_app_settings(AppEnvironment::Production, settings_dir)?;
+        assert_eq!(DEFAULT_HTTP_SERVER_HOST, app_settings.http_server.host);
         assert_eq!(443, app_settings.http_server.port);
         assert_eq!(
             "very-long-and-complex-string",
@@ -325,12 +1590,106 @@ pub mod tests {
         );
         validate_database_settings(&app_settings.database);
         assert!(app_settings.database.require_ssl); // SSL接続を要求
+        validate_database_pool_settings(&app_settings.database, 20, 2, Some(300), 10_000);
         assert_eq!(LevelFilter::Error, app_settings.database.log_statements);
         assert_eq!(log::Level::Info, app_settings.logging.level);
 
         Ok(())
     }
 
+    /// アプリケーション設定を検証したとき、すべての違反を報告することを確認
+    ///
+    /// ワークスペースディレクトリ内の`.env`ファイルが存在することを想定している。
+    #[test]
+    fn app_settings_validation_reports_all_violations() -> anyhow::Result<()> {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let env_file = crate_dir.join("..").join(".env");
+        dotenvx::from_path(env_file)?;
+
+        let settings_dir = crate_dir.join("..").join(SETTINGS_DIR_NAME);
+        let mut app_settings = read_app_settings(AppEnvironment::Development, settings_dir)?;
+        assert!(app_settings.validate().is_ok());
+
+        app_settings.http_server.workers = 0;
+        app_settings.database.connection_timeout_seconds = 0;
+        app_settings.authorization.refresh_token_seconds =
+            app_settings.authorization.access_token_seconds;
+        app_settings.tracing.otlp_endpoint = Some(String::from("localhost:4317"));
+        app_settings.email = Some(EmailSettings {
+            host: String::from("localhost"),
+            port: 25,
+            username: Some(String::from("awe")),
+            password: None,
+            from: String::new(),
+            starttls: false,
+        });
+        let error = app_settings.validate().unwrap_err();
+
+        let fields = error
+            .violations
+            .iter()
+            .map(|v| v.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "http_server.workers",
+                "database.connection_timeout_seconds",
+                "authorization.refresh_token_seconds",
+                "tracing.otlp_endpoint",
+                "email.password",
+                "email.from",
+            ],
+            fields
+        );
+        let message = error.to_string();
+        for field in fields {
+            assert!(message.contains(field), "{}", message);
+        }
+
+        Ok(())
+    }
+
+    /// ステージング環境のアプリケーション設定を正しくロードできることを確認
+    ///
+    /// ワークスペースディレクトリ内の`.env`ファイルが存在することを想定している。
+    #[test]
+    fn can_retrieve_app_settings_for_staging() -> anyhow::Result<()> {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let env_file = crate_dir.join("..").join(".env");
+        dotenvx::from_path(env_file)?;
+
+        let settings_dir = crate_dir.join("..").join(SETTINGS_DIR_NAME);
+        let app_settings = read_app_settings(AppEnvironment::Staging, settings_dir)?;
+        assert_eq!(8443, app_settings.http_server.port);
+        assert!(app_settings.http_server.secure);
+        validate_database_settings(&app_settings.database);
+        assert!(app_settings.database.require_ssl); // SSL接続を要求
+        assert_eq!(LevelFilter::Warn, app_settings.database.log_statements);
+        assert_eq!(log::Level::Info, app_settings.logging.level);
+
+        Ok(())
+    }
+
+    /// テスト環境のアプリケーション設定を正しくロードできることを確認
+    ///
+    /// ワークスペースディレクトリ内の`.env`ファイルが存在することを想定している。
+    #[test]
+    fn can_retrieve_app_settings_for_test() -> anyhow::Result<()> {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let env_file = crate_dir.join("..").join(".env");
+        dotenvx::from_path(env_file)?;
+
+        let settings_dir = crate_dir.join("..").join(SETTINGS_DIR_NAME);
+        let app_settings = read_app_settings(AppEnvironment::Test, settings_dir)?;
+        assert_eq!(8000, app_settings.http_server.port);
+        validate_database_settings(&app_settings.database);
+        assert!(!app_settings.database.require_ssl); // SSL接続を要求しない
+        assert_eq!(LevelFilter::Warn, app_settings.database.log_statements);
+        assert_eq!(log::Level::Warn, app_settings.logging.level);
+
+        Ok(())
+    }
+
     /// データベース設定を正しくロードできていることを確認
     fn validate_database_settings(settings: &DatabaseSettings) {
         assert_eq!("awe", settings.user);
@@ -340,4 +1699,679 @@ pub mod tests {
         assert_eq!("awe", settings.name);
         assert_eq!(3, settings.connection_timeout_seconds);
     }
+
+    /// データベース接続プールの設定を接続プール及び接続オプションに適用していることを確認
+    fn validate_database_pool_settings(
+        settings: &DatabaseSettings,
+        max_connections: u32,
+        min_connections: u32,
+        idle_timeout_seconds: Option<u64>,
+        statement_timeout_ms: u64,
+    ) {
+        let pool_options = settings.pool_options();
+        assert_eq!(max_connections, pool_options.get_max_connections());
+        assert_eq!(min_connections, pool_options.get_min_connections());
+        assert_eq!(
+            idle_timeout_seconds.map(std::time::Duration::from_secs),
+            pool_options.get_idle_timeout()
+        );
+        assert_eq!(
+            std::time::Duration::from_secs(settings.connection_timeout_seconds),
+            pool_options.get_acquire_timeout()
+        );
+
+        let connect_options = settings.with_db();
+        assert_eq!(
+            Some(DEFAULT_DATABASE_APPLICATION_NAME),
+            connect_options.get_application_name()
+        );
+        let expected = format!("-c statement_timeout={}", statement_timeout_ms);
+        assert_eq!(Some(expected.as_str()), connect_options.get_options());
+    }
+
+    /// 接続プールの最小接続数が最大接続数を超える場合に、データベース設定が不正になることを確認
+    ///
+    /// ワークスペースディレクトリ内の`.env`ファイルが存在することを想定している。
+    #[test]
+    fn database_settings_reject_min_connections_greater_than_max() -> anyhow::Result<()> {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let env_file = crate_dir.join("..").join(".env");
+        dotenvx::from_path(env_file)?;
+
+        let settings_dir = crate_dir.join("..").join(SETTINGS_DIR_NAME);
+        let mut settings = read_app_settings(AppEnvironment::Development, settings_dir)?.database;
+        settings.min_connections = settings.max_connections;
+        assert!(settings.validate().is_ok());
+
+        settings.min_connections = settings.max_connections + 1;
+        let error = settings.validate().unwrap_err();
+        assert_eq!(1, error.violations.len());
+        assert_eq!("min_connections", error.violations[0].field);
+
+        Ok(())
+    }
+
+    /// ユーザーリポジトリがSQLステートメントの完了を待機する時間に0を指定した場合に、タイムアウトが
+    /// 無効になることを確認
+    ///
+    /// ワークスペースディレクトリ内の`.env`ファイルが存在することを想定している。
+    #[test]
+    fn zero_query_timeout_disables_timeout() -> anyhow::Result<()> {
+        let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let env_file = crate_dir.join("..").join(".env");
+        dotenvx::from_path(env_file)?;
+
+        let settings_dir = crate_dir.join("..").join(SETTINGS_DIR_NAME);
+        let mut settings = read_app_settings(AppEnvironment::Development, settings_dir)?.database;
+        settings.query_timeout_ms = 500;
+        assert_eq!(
+            Some(std::time::Duration::from_millis(500)),
+            settings.query_timeout()
+        );
+
+        settings.query_timeout_ms = 0;
+        assert!(settings.query_timeout().is_none());
+
+        Ok(())
+    }
+
+    /// YAML形式の文字列から、Redis設定を読み込む。
+    fn redis_settings(yaml: &str) -> anyhow::Result<RedisSettings> {
+        let settings = Config::builder()
+            .add_source(config::File::from_str(yaml, FileFormat::Yaml))
+            .build()?;
+
+        settings.get::<RedisSettings>("redis").map_err(|e| e.into())
+    }
+
+    /// Redis設定を省略した場合に、認証せず、データベース番号0に、TLSを使用しないで接続することを確認
+    #[test]
+    fn redis_settings_use_defaults() -> anyhow::Result<()> {
+        let settings = redis_settings("redis:\n  host: localhost\n  port: 6379\n")?;
+
+        assert!(settings.password.is_none());
+        assert_eq!(0, settings.db);
+        assert!(!settings.tls);
+        assert!(settings.pool_max_size.is_none());
+        assert!(settings.validate().is_ok());
+        let info = settings.connection_info();
+        assert!(matches!(
+            info.addr,
+            RedisConnectionAddr::Tcp(ref host, 6379) if host == "localhost"
+        ));
+        assert_eq!(0, info.redis.db);
+        assert!(info.redis.password.is_none());
+        assert!(settings.connection_pool().is_ok());
+
+        Ok(())
+    }
+
+    /// Redisのパスワード、データベース番号、TLS及び接続プールの最大接続数を読み込めることを確認
+    #[test]
+    fn redis_settings_can_be_deserialized() -> anyhow::Result<()> {
+        let yaml = "redis:\n  host: redis.example.com\n  port: 6380\n  password: redis-pass\n  db: 3\n  tls: true\n  pool_max_size: 8\n";
+        let settings = redis_settings(yaml)?;
+
+        assert_eq!(
+            "redis-pass",
+            settings.password.as_ref().unwrap().expose_secret()
+        );
+        assert_eq!(3, settings.db);
+        assert!(settings.tls);
+        assert_eq!(Some(8), settings.pool_max_size);
+        assert!(!format!("{:?}", settings).contains("redis-pass"));
+        let info = settings.connection_info();
+        assert!(matches!(
+            info.addr,
+            RedisConnectionAddr::TcpTls { ref host, port: 6380, insecure: false }
+                if host == "redis.example.com"
+        ));
+        assert_eq!(3, info.redis.db);
+        assert_eq!(Some("redis-pass"), info.redis.password.as_deref());
+        let pool = settings.connection_pool()?;
+        assert_eq!(8, pool.status().max_size);
+
+        Ok(())
+    }
+
+    /// Redis接続プールの最大接続数に0を指定した場合に、Redis設定が不正になることを確認
+    #[test]
+    fn redis_settings_reject_zero_pool_max_size() -> anyhow::Result<()> {
+        let settings =
+            redis_settings("redis:\n  host: localhost\n  port: 6379\n  pool_max_size: 0\n")?;
+
+        let error = settings.validate().unwrap_err();
+
+        assert_eq!(1, error.violations.len());
+        assert_eq!("pool_max_size", error.violations[0].field);
+
+        Ok(())
+    }
+
+    /// YAML形式の文字列と環境変数を模したマップから、HTTPサーバー設定を読み込む。
+    fn http_server_settings(
+        yaml: &str,
+        env: &[(&str, &str)],
+    ) -> anyhow::Result<HttpServerSettings> {
+        let env = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let settings = Config::builder()
+            .add_source(config::File::from_str(yaml, FileFormat::Yaml))
+            .add_source(
+                config::Environment::with_prefix("APP")
+                    .prefix_separator("_")
+                    .separator("__")
+                    .source(Some(env)),
+            )
+            .build()?;
+
+        settings
+            .get::<HttpServerSettings>("http_server")
+            .map_err(|e| e.into())
+    }
+
+    const HTTP_SERVER_YAML: &str = r#"
+http_server:
+  port: 8000
+  same_site: strict
+  secure: true
+"#;
+
+    /// リスニングホストが指定されていない場合、デフォルトのホストが設定されることを確認
+    #[test]
+    fn http_server_host_defaults_to_loopback_address() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+
+        assert_eq!(DEFAULT_HTTP_SERVER_HOST, settings.host);
+        assert_eq!("127.0.0.1:8000", settings.address());
+        assert!(settings.validate().is_ok());
+
+        Ok(())
+    }
+
+    /// APIのパスのプレフィックスが指定されていない場合、デフォルトのプレフィックスが設定されることを確認
+    #[test]
+    fn api_prefix_defaults_to_api_v1() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+
+        assert_eq!(DEFAULT_HTTP_SERVER_API_PREFIX, settings.api_prefix);
+        assert!(!settings.redirect_unprefixed_paths);
+
+        Ok(())
+    }
+
+    /// 妥当なAPIのパスのプレフィックスが検証に成功することを確認
+    #[test]
+    fn valid_api_prefixes_pass_validation() -> anyhow::Result<()> {
+        for prefix in ["", "/api", "/api/v2"] {
+            let yaml = format!("{}  api_prefix: \"{}\"\n", HTTP_SERVER_YAML, prefix);
+            let settings = http_server_settings(&yaml, &[])?;
+            assert_eq!(prefix, settings.api_prefix);
+            assert!(settings.validate().is_ok(), "{}", prefix);
+        }
+
+        Ok(())
+    }
+
+    /// 妥当でないAPIのパスのプレフィックスが検証に失敗することを確認
+    #[test]
+    fn invalid_api_prefixes_fail_validation() -> anyhow::Result<()> {
+        for prefix in ["/", "api/v1", "/api/v1/"] {
+            let yaml = format!("{}  api_prefix: \"{}\"\n", HTTP_SERVER_YAML, prefix);
+            let settings = http_server_settings(&yaml, &[])?;
+            assert!(settings.validate().is_err(), "{}", prefix);
+        }
+
+        Ok(())
+    }
+
+    /// クッキー設定が指定されていない場合、デフォルト値が設定されることを確認
+    #[test]
+    fn cookie_settings_default_values() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+
+        assert_eq!(DEFAULT_HTTP_SERVER_COOKIE_PATH, settings.cookie_path);
+        assert!(settings.cookie_domain.is_none());
+        assert!(settings.cookie_name_prefix.is_empty());
+
+        Ok(())
+    }
+
+    /// クッキー設定を読み込めることを確認
+    #[test]
+    fn can_retrieve_cookie_settings() -> anyhow::Result<()> {
+        let yaml = format!(
+            "{}  cookie_path: /api\n  cookie_domain: example.com\n  cookie_name_prefix: awe_\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+
+        assert_eq!("/api", settings.cookie_path);
+        assert_eq!(Some("example.com"), settings.cookie_domain.as_deref());
+        assert_eq!("awe_", settings.cookie_name_prefix);
+
+        Ok(())
+    }
+
+    /// サインアップを許可するか示すフラグのデフォルトが`true`で、環境変数で上書きできることを確認
+    #[test]
+    fn registration_enabled_can_be_overridden_by_environment_variable() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+        assert!(settings.registration_enabled);
+
+        let settings = http_server_settings(
+            HTTP_SERVER_YAML,
+            &[("APP_HTTP_SERVER__REGISTRATION_ENABLED", "false")],
+        )?;
+        assert!(!settings.registration_enabled);
+
+        Ok(())
+    }
+
+    /// `__Host-`プレフィックスを付与する場合に、クッキーの属性が妥当であれば検証に成功することを確認
+    #[test]
+    fn host_prefix_with_valid_cookie_attributes_passes_validation() -> anyhow::Result<()> {
+        let yaml = format!("{}  use_host_prefix: true\n", HTTP_SERVER_YAML);
+        let settings = http_server_settings(&yaml, &[])?;
+
+        assert!(settings.use_host_prefix);
+        assert!(settings.validate().is_ok());
+
+        Ok(())
+    }
+
+    /// `__Host-`プレフィックスを付与する場合に、クッキーの属性が矛盾していれば検証に失敗することを確認
+    #[test]
+    fn host_prefix_with_contradictory_cookie_attributes_fails_validation() -> anyhow::Result<()> {
+        let cases = [
+            ("", "false"),
+            ("  cookie_domain: example.com\n", "true"),
+            ("  cookie_path: /api\n", "true"),
+        ];
+        for (case, secure) in cases {
+            let yaml = format!("{}  use_host_prefix: true\n{}", HTTP_SERVER_YAML, case);
+            let settings = http_server_settings(&yaml, &[("APP_HTTP_SERVER__SECURE", secure)])?;
+            assert!(settings.validate().is_err(), "{} secure={}", case, secure);
+        }
+
+        Ok(())
+    }
+
+    /// ワーカースレッド数やタイムアウト時間が指定されていない場合、デフォルト値が設定されることを確認
+    #[test]
+    fn http_server_tuning_settings_default_values() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+
+        assert!(0 < settings.workers);
+        assert_eq!(
+            DEFAULT_HTTP_SERVER_KEEP_ALIVE_SECONDS,
+            settings.keep_alive_seconds
+        );
+        assert_eq!(
+            DEFAULT_HTTP_SERVER_CLIENT_REQUEST_TIMEOUT_SECONDS,
+            settings.client_request_timeout_seconds
+        );
+        assert_eq!(
+            DEFAULT_HTTP_SERVER_SHUTDOWN_TIMEOUT_SECONDS,
+            settings.shutdown_timeout_seconds
+        );
+
+        Ok(())
+    }
+
+    /// ワーカースレッド数やタイムアウト時間を読み込めることを確認
+    #[test]
+    fn can_retrieve_http_server_tuning_settings() -> anyhow::Result<()> {
+        let yaml = format!(
+            "{}  workers: 4\n  keep_alive_seconds: 0\n  client_request_timeout_seconds: 10\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(
+            &yaml,
+            &[("APP_HTTP_SERVER__SHUTDOWN_TIMEOUT_SECONDS", "60")],
+        )?;
+
+        assert_eq!(4, settings.workers);
+        assert_eq!(0, settings.keep_alive_seconds);
+        assert_eq!(10, settings.client_request_timeout_seconds);
+        assert_eq!(60, settings.shutdown_timeout_seconds);
+        assert!(settings.validate().is_ok());
+
+        Ok(())
+    }
+
+    /// ワーカースレッド数に0を指定した場合、検証に失敗することを確認
+    #[test]
+    fn zero_workers_fail_validation() -> anyhow::Result<()> {
+        let yaml = format!("{}  workers: 0\n", HTTP_SERVER_YAML);
+        let settings = http_server_settings(&yaml, &[])?;
+
+        assert!(settings.validate().is_err());
+
+        Ok(())
+    }
+
+    /// 一括登録できるユーザーの最大数を指定しない場合はデフォルト値を使用し、0を指定した場合は
+    /// 検証に失敗することを確認
+    #[test]
+    fn max_import_users_defaults_and_zero_fails_validation() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+        assert_eq!(
+            DEFAULT_HTTP_SERVER_MAX_IMPORT_USERS,
+            settings.max_import_users
+        );
+
+        let yaml = format!("{}  max_import_users: 0\n", HTTP_SERVER_YAML);
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_err());
+
+        Ok(())
+    }
+
+    /// JSON形式のリクエストボディの最大サイズを指定しない場合はデフォルト値を使用し、0を指定した
+    /// 場合は検証に失敗することを確認
+    #[test]
+    fn max_json_payload_bytes_defaults_and_zero_fails_validation() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+        assert_eq!(
+            DEFAULT_HTTP_SERVER_MAX_JSON_PAYLOAD_BYTES,
+            settings.max_json_payload_bytes
+        );
+
+        let yaml = format!("{}  max_json_payload_bytes: 0\n", HTTP_SERVER_YAML);
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_err());
+
+        Ok(())
+    }
+
+    /// サインアップで登録できるユーザー権限のデフォルトが一般ユーザーのみで、範囲外のユーザー権限
+    /// コードを指定した場合は検証に失敗することを確認
+    #[test]
+    fn sign_up_permission_codes_default_to_general_and_are_validated() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+        assert!(settings.allows_sign_up_with(UserPermissionCode::General));
+        assert!(!settings.allows_sign_up_with(UserPermissionCode::Admin));
+
+        let yaml = format!(
+            "{}  sign_up_permission_codes:\n    - 1\n    - 2\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_ok());
+        assert!(settings.allows_sign_up_with(UserPermissionCode::Admin));
+
+        let yaml = format!(
+            "{}  sign_up_permission_codes:\n    - 2\n    - 9\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_err());
+
+        Ok(())
+    }
+
+    /// 管理者向けのエンドポイントへのアクセスを許可するIPアドレスの範囲を読み込めて、CIDR表記として
+    /// 妥当ではない範囲を含む場合は検証に失敗することを確認
+    #[test]
+    fn can_retrieve_admin_ip_allowlist_and_malformed_range_fails_validation() -> anyhow::Result<()>
+    {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+        assert!(settings.admin_ip_allowlist.is_empty());
+
+        let yaml = format!(
+            "{}  admin_ip_allowlist:\n    - 10.0.0.0/8\n    - 2001:db8::/32\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_ok());
+        assert_eq!(
+            vec!["10.0.0.0/8".parse::<IpNet>()?, "2001:db8::/32".parse()?],
+            settings.admin_ip_ranges()?
+        );
+
+        let yaml = format!(
+            "{}  admin_ip_allowlist:\n    - 10.0.0.0/8\n    - 192.168.1.300/24\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_err());
+        assert!(settings.admin_ip_ranges().is_err());
+
+        Ok(())
+    }
+
+    /// 信頼するプロキシには、IPアドレスとCIDR表記のIPアドレスの範囲を指定でき、それ以外を指定した
+    /// 場合は検証に失敗することを確認
+    #[test]
+    fn can_retrieve_trusted_proxies_and_malformed_proxy_fails_validation() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+        assert!(settings.trusted_proxy_ranges()?.is_empty());
+
+        let yaml = format!(
+            "{}  trusted_proxies:\n    - 127.0.0.1\n    - 10.0.0.0/8\n    - \"::1\"\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_ok());
+        assert_eq!(
+            vec![
+                "127.0.0.1/32".parse::<IpNet>()?,
+                "10.0.0.0/8".parse()?,
+                "::1/128".parse()?
+            ],
+            settings.trusted_proxy_ranges()?
+        );
+
+        let yaml = format!("{}  trusted_proxies:\n    - nginx\n", HTTP_SERVER_YAML);
+        let settings = http_server_settings(&yaml, &[])?;
+        assert!(settings.validate().is_err());
+        assert!(settings.trusted_proxy_ranges().is_err());
+
+        Ok(())
+    }
+
+    /// メトリクス設定が指定されていない場合、ループバックアドレスのみに`/metrics`を公開することを確認
+    #[test]
+    fn metrics_are_exposed_only_to_loopback_addresses_by_default() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+
+        assert!(settings.metrics.enabled);
+        assert!(settings.metrics.is_allowed("127.0.0.1".parse()?));
+        assert!(settings.metrics.is_allowed("::1".parse()?));
+        assert!(settings.metrics.is_allowed("::ffff:127.0.0.1".parse()?));
+        assert!(!settings.metrics.is_allowed("192.168.1.1".parse()?));
+        assert!(settings.metrics.instrument_repositories);
+
+        Ok(())
+    }
+
+    /// メトリクス設定を読み込めることを確認
+    #[test]
+    fn can_retrieve_metrics_settings() -> anyhow::Result<()> {
+        let yaml = format!(
+            "{}  metrics:\n    enabled: false\n    allowed_ips:\n      - 10.0.0.1\n    instrument_repositories: false\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+
+        assert!(!settings.metrics.enabled);
+        assert!(settings.metrics.is_allowed("10.0.0.1".parse()?));
+        assert!(!settings.metrics.is_allowed("127.0.0.1".parse()?));
+        assert!(!settings.metrics.instrument_repositories);
+
+        Ok(())
+    }
+
+    /// APIドキュメント設定が指定されていない場合、Swagger UIが無効になることを確認
+    #[test]
+    fn swagger_ui_is_disabled_by_default() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+
+        assert!(!settings.api_docs.swagger_ui);
+
+        Ok(())
+    }
+
+    /// APIドキュメント設定を読み込めることを確認
+    #[test]
+    fn can_retrieve_api_docs_settings() -> anyhow::Result<()> {
+        let yaml = format!("{}  api_docs:\n    swagger_ui: true\n", HTTP_SERVER_YAML);
+        let settings = http_server_settings(&yaml, &[])?;
+
+        assert!(settings.api_docs.swagger_ui);
+
+        Ok(())
+    }
+
+    /// TLS設定が指定されていない場合、TLSが無効になることを確認
+    #[test]
+    fn tls_is_disabled_when_tls_settings_are_omitted() -> anyhow::Result<()> {
+        let settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+
+        assert!(settings.tls.is_none());
+        assert!(settings.enabled_tls().is_none());
+
+        Ok(())
+    }
+
+    /// TLS設定を読み込めることを確認
+    #[test]
+    fn can_retrieve_tls_settings() -> anyhow::Result<()> {
+        let yaml = format!(
+            "{}  tls:\n    enabled: true\n    cert_path: /etc/awe/cert.pem\n    key_path: /etc/awe/key.pem\n",
+            HTTP_SERVER_YAML
+        );
+        let settings = http_server_settings(&yaml, &[])?;
+
+        let tls = settings.enabled_tls().unwrap();
+        assert_eq!(Path::new("/etc/awe/cert.pem"), tls.cert_path);
+        assert_eq!(Path::new("/etc/awe/key.pem"), tls.key_path);
+
+        // 環境変数でTLSを無効化
+        let settings = http_server_settings(&yaml, &[("APP_HTTP_SERVER__TLS__ENABLED", "false")])?;
+        assert!(settings.tls.is_some());
+        assert!(settings.enabled_tls().is_none());
+
+        Ok(())
+    }
+
+    /// 環境変数でリスニングホストを上書きできることを確認
+    #[test]
+    fn http_server_host_can_be_overridden_by_environment_variable() -> anyhow::Result<()> {
+        let settings =
+            http_server_settings(HTTP_SERVER_YAML, &[("APP_HTTP_SERVER__HOST", "0.0.0.0")])?;
+
+        assert_eq!("0.0.0.0", settings.host);
+        assert_eq!("0.0.0.0:8000", settings.address());
+
+        Ok(())
+    }
+
+    /// 妥当なリスニングホストを検証できることを確認
+    #[test]
+    fn valid_http_server_hosts_pass_validation() -> anyhow::Result<()> {
+        let candidates = [
+            ("0.0.0.0", "0.0.0.0:8000"),
+            ("192.168.1.1", "192.168.1.1:8000"),
+            ("::", "[::]:8000"),
+            ("::1", "[::1]:8000"),
+            ("localhost", "localhost:8000"),
+            ("api.example.com", "api.example.com:8000"),
+            ("app-server-01", "app-server-01:8000"),
+        ];
+        for (host, expected) in candidates {
+            let mut settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+            settings.host = host.to_string();
+            assert!(settings.validate().is_ok(), "{}", host);
+            assert_eq!(expected, settings.address());
+        }
+
+        Ok(())
+    }
+
+    /// 妥当でないリスニングホストを検証できることを確認
+    #[test]
+    fn invalid_http_server_hosts_fail_validation() -> anyhow::Result<()> {
+        let long_label = "a".repeat(64);
+        let candidates = [
+            "",
+            " ",
+            "-example.com",
+            "example-.com",
+            "example..com",
+            "exa_mple.com",
+            "http://example.com",
+            "example.com:8000",
+            long_label.as_str(),
+        ];
+        for host in candidates {
+            let mut settings = http_server_settings(HTTP_SERVER_YAML, &[])?;
+            settings.host = host.to_string();
+            assert!(settings.validate().is_err(), "`{}`", host);
+        }
+
+        Ok(())
+    }
+
+    /// Webhook設定を読み込み、省略した項目にデフォルト値を設定することを確認
+    #[test]
+    fn can_retrieve_webhook_settings_with_defaults() -> anyhow::Result<()> {
+        let yaml = r#"
+            url: https://crm.example.com/hooks
+            secret: webhook-secret
+            events:
+              - user_registered
+              - account_locked
+        "#;
+        let settings: WebhookSettings = Config::builder()
+            .add_source(config::File::from_str(yaml, FileFormat::Yaml))
+            .build()?
+            .try_deserialize()?;
+
+        assert!(settings.validate().is_ok());
+        assert_eq!("https://crm.example.com/hooks", settings.url);
+        assert_eq!("webhook-secret", settings.secret.expose_secret());
+        assert!(settings.subscribes("user_registered"));
+        assert!(settings.subscribes("account_locked"));
+        assert!(!settings.subscribes("user_signed_in"));
+        assert_eq!(5, settings.max_attempts);
+        assert_eq!(1000, settings.initial_backoff_millis);
+        assert_eq!(10, settings.timeout_seconds);
+
+        Ok(())
+    }
+
+    /// 妥当でないWebhook設定を検証できることを確認
+    #[test]
+    fn invalid_webhook_settings_fail_validation() -> anyhow::Result<()> {
+        let yaml = r#"
+            url: crm.example.com/hooks
+            secret: ""
+            events:
+              - user_deleted
+            max_attempts: 0
+            timeout_seconds: 0
+        "#;
+        let settings: WebhookSettings = Config::builder()
+            .add_source(config::File::from_str(yaml, FileFormat::Yaml))
+            .build()?
+            .try_deserialize()?;
+
+        let error = settings.validate().unwrap_err();
+
+        let fields = error
+            .violations
+            .iter()
+            .map(|v| v.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec!["url", "secret", "events", "max_attempts", "timeout_seconds"],
+            fields
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+#[cfg(any(test, feature = "test-util"))]
+use std::sync::Mutex;
+
+#[cfg(any(test, feature = "test-util"))]
+use time::Duration;
+use time::OffsetDateTime;
+
+/// 時計
+///
+/// 現在の日時を取得する。サインインの失敗回数をカウントする期間やトークンの有効期限など、
+/// 現在の日時に依存する処理を、テストで実際に時間を経過させずに検証できるように、トレイトとして定義する。
+pub trait Clock: std::fmt::Debug + Sync + Send {
+    /// 現在の日時を世界標準時で返す。
+    fn now_utc(&self) -> OffsetDateTime;
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now_utc(&self) -> OffsetDateTime {
+        (**self).now_utc()
+    }
+}
+
+/// システムの時計
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        OffsetDateTime::now_utc()
+    }
+}
+
+/// 日時を固定した時計
+///
+/// 常に構築したときに指定した日時を返す。テストで使用する。
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug, Clone, Copy)]
+pub struct FixedClock(pub OffsetDateTime);
+
+#[cfg(any(test, feature = "test-util"))]
+impl Clock for FixedClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        self.0
+    }
+}
+
+/// 日時を進める時計
+///
+/// 現在の日時を返すたびに、構築したときに指定した間隔だけ日時を進める。また、`advance`で
+/// 任意の時間だけ日時を進められる。テストで使用する。
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug)]
+pub struct SteppingClock {
+    /// 次に返す日時
+    now: Mutex<OffsetDateTime>,
+    /// 日時を返すたびに進める間隔
+    step: Duration,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl SteppingClock {
+    /// 日時を進める時計を構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `start` - 最初に返す日時
+    /// * `step` - 日時を返すたびに進める間隔
+    ///
+    /// # 戻り値
+    ///
+    /// 日時を進める時計
+    pub fn new(start: OffsetDateTime, step: Duration) -> Self {
+        Self {
+            now: Mutex::new(start),
+            step,
+        }
+    }
+
+    /// 日時を進める。
+    ///
+    /// # 引数
+    ///
+    /// * `duration` - 進める時間
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl Clock for SteppingClock {
+    fn now_utc(&self) -> OffsetDateTime {
+        let mut now = self.now.lock().unwrap();
+        let current = *now;
+        *now += self.step;
+
+        current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    /// 日時を進める時計が、日時を返すたびに指定した間隔だけ日時を進めることを確認
+    #[test]
+    fn stepping_clock_advances_by_step_and_duration() {
+        let clock = SteppingClock::new(datetime!(2024-05-01 12:00:00 UTC), Duration::seconds(1));
+
+        assert_eq!(datetime!(2024-05-01 12:00:00 UTC), clock.now_utc());
+        assert_eq!(datetime!(2024-05-01 12:00:01 UTC), clock.now_utc());
+        clock.advance(Duration::minutes(1));
+        assert_eq!(datetime!(2024-05-01 12:01:02 UTC), clock.now_utc());
+        // 共有した時計も同じ日時を返す
+        let shared: Arc<dyn Clock> = Arc::new(FixedClock(datetime!(2024-05-01 12:00:00 UTC)));
+        assert_eq!(datetime!(2024-05-01 12:00:00 UTC), shared.now_utc());
+    }
+}
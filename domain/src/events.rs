@@ -0,0 +1,157 @@
+use std::sync::Arc;
+#[cfg(any(test, feature = "test-util"))]
+use std::sync::Mutex;
+
+use async_trait::async_trait;
+
+use crate::models::primitives::EmailAddress;
+use crate::models::user::UserId;
+use crate::repositories::user::SignInAttempt;
+use crate::DomainResult;
+
+/// ドメインイベント
+///
+/// ユースケースが、主な処理を完了した後に発行する。Eメールの送信や監査ログの記録などの副作用は、
+/// ユースケースではなく、イベントを受け取ったイベントハンドラーが処理する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainEvent {
+    /// ユーザーを登録した。
+    UserRegistered {
+        /// ユーザーID
+        user_id: UserId,
+        /// Eメールアドレス
+        email: EmailAddress,
+    },
+    /// ユーザーがサインインした。
+    UserSignedIn {
+        /// ユーザーID
+        user_id: UserId,
+    },
+    /// サインインに連続して失敗したため、ユーザーのアカウントをロックした。
+    AccountLocked {
+        /// ユーザーID
+        user_id: UserId,
+        /// Eメールアドレス
+        email: EmailAddress,
+        /// 最後にサインインに失敗したアクセス元
+        attempt: SignInAttempt,
+    },
+    /// ユーザーがパスワードを変更した。
+    PasswordChanged {
+        /// ユーザーID
+        user_id: UserId,
+    },
+}
+
+impl DomainEvent {
+    /// すべてのイベントの種類を示す名前
+    pub const NAMES: [&'static str; 4] = [
+        "user_registered",
+        "user_signed_in",
+        "account_locked",
+        "password_changed",
+    ];
+
+    /// イベントの種類を示す名前を返す。
+    ///
+    /// # 戻り値
+    ///
+    /// イベントの種類を示す名前
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UserRegistered { .. } => "user_registered",
+            Self::UserSignedIn { .. } => "user_signed_in",
+            Self::AccountLocked { .. } => "account_locked",
+            Self::PasswordChanged { .. } => "password_changed",
+        }
+    }
+
+    /// イベントの対象となったユーザーのユーザーIDを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーID
+    pub fn user_id(&self) -> UserId {
+        match self {
+            Self::UserRegistered { user_id, .. }
+            | Self::UserSignedIn { user_id }
+            | Self::AccountLocked { user_id, .. }
+            | Self::PasswordChanged { user_id } => *user_id,
+        }
+    }
+}
+
+/// イベント発行者
+///
+/// ユースケースが、ドメインイベントを発行するために使用する。イベントハンドラーの処理は
+/// ベストエフォートであるため、発行者はイベントハンドラーのエラーを返さない。
+#[async_trait]
+pub trait EventPublisher: std::fmt::Debug + Sync + Send {
+    /// ドメインイベントを発行する。
+    ///
+    /// # 引数
+    ///
+    /// * `event` - ドメインイベント
+    async fn publish(&self, event: DomainEvent);
+}
+
+#[async_trait]
+impl<T: EventPublisher + ?Sized> EventPublisher for Arc<T> {
+    async fn publish(&self, event: DomainEvent) {
+        (**self).publish(event).await
+    }
+}
+
+/// イベントハンドラー
+///
+/// イベント発行者に登録して、発行されたドメインイベントを処理する。
+#[async_trait]
+pub trait EventHandler: std::fmt::Debug + Sync + Send {
+    /// ドメインイベントを処理する。
+    ///
+    /// # 引数
+    ///
+    /// * `event` - ドメインイベント
+    async fn handle(&self, event: &DomainEvent) -> DomainResult<()>;
+}
+
+/// イベントを発行しないイベント発行者
+///
+/// イベントハンドラーを登録しない場合に使用する。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopEventPublisher;
+
+#[async_trait]
+impl EventPublisher for NoopEventPublisher {
+    async fn publish(&self, _event: DomainEvent) {}
+}
+
+/// 発行されたイベントを記録するイベント発行者
+///
+/// ユースケースが発行したイベントを検証するために、テストで使用する。
+#[cfg(any(test, feature = "test-util"))]
+#[derive(Debug, Default)]
+pub struct RecordingEventPublisher {
+    /// 発行された順番に格納したドメインイベント
+    events: Mutex<Vec<DomainEvent>>,
+}
+
+#[cfg(any(test, feature = "test-util"))]
+impl RecordingEventPublisher {
+    /// 発行された順番に、記録したドメインイベントを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// ドメインイベントを格納したベクタ
+    pub fn events(&self) -> Vec<DomainEvent> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+#[cfg(any(test, feature = "test-util"))]
+#[async_trait]
+impl EventPublisher for RecordingEventPublisher {
+    async fn publish(&self, event: DomainEvent) {
+        self.events.lock().unwrap().push(event);
+    }
+}
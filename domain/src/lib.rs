@@ -1,3 +1,5 @@
+pub mod clock;
+pub mod events;
 pub mod models;
 pub mod repositories;
 
@@ -6,6 +8,8 @@ use std::borrow::Cow;
 use time::macros::offset;
 use time::OffsetDateTime;
 
+use crate::clock::Clock;
+
 /// ドメインエラー
 #[derive(Debug, thiserror::Error)]
 pub enum DomainError {
@@ -32,26 +36,50 @@ pub enum DomainError {
     Repository(anyhow::Error),
 }
 
+impl DomainError {
+    /// リポジトリの処理がタイムアウトしたことを示すエラーであるか確認する。
+    ///
+    /// # 戻り値
+    ///
+    /// リポジトリの処理がタイムアウトした場合は`true`、それ以外の場合は`false`
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::Repository(e) if e.is::<RepositoryTimeout>())
+    }
+}
+
 /// ドメイン層の結果型
 pub type DomainResult<T> = Result<T, DomainError>;
 
+/// リポジトリの処理のタイムアウト
+///
+/// データベースがロックの競合などで応答しないため、リポジトリの処理が制限時間内に完了しなかった
+/// ことを表現する。リポジトリエラー（`DomainError::Repository`）に格納して返す。
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("リポジトリの処理がタイムアウトしました。")]
+pub struct RepositoryTimeout;
+
 /// 現在の日時を日本標準時で返す。
 ///
-/// 世界標準時で取得した現在の日時を、+9時間オフセットした日時を返す。
+/// 時計から世界標準時で取得した現在の日時を、+9時間オフセットした日時を返す。
+///
+/// # 引数
+///
+/// * `clock` - 時計
 ///
 /// # 戻り値
 ///
 /// 日本標準時の現在日時
-pub fn now_jst() -> OffsetDateTime {
-    OffsetDateTime::now_utc().to_offset(offset!(+9))
+pub fn now_jst(clock: &impl Clock) -> OffsetDateTime {
+    clock.now_utc().to_offset(offset!(+9))
 }
 
 #[cfg(test)]
 mod tests {
-    use time::macros::offset;
+    use time::macros::{datetime, offset};
     use time::{Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time};
 
-    use super::now_jst;
+    use super::{now_jst, DomainError, RepositoryTimeout};
+    use crate::clock::{FixedClock, SystemClock};
 
     const DATE_TIME_DIFF_ALLOWABLE_SECONDS: i64 = 60;
 
@@ -59,7 +87,7 @@ mod tests {
     #[test]
     fn retrieve_current_date_time_at_jst() {
         let utc = OffsetDateTime::now_utc();
-        let jst = now_jst();
+        let jst = now_jst(&SystemClock);
         let allowable_diff = Duration::seconds(DATE_TIME_DIFF_ALLOWABLE_SECONDS);
 
         // オフセットを確認
@@ -74,6 +102,25 @@ mod tests {
         assert!(primitive_jst - primitive_utc < allowable_diff + Duration::hours(9));
     }
 
+    /// 時計が返す日時を、日本標準時に変換して返すことを確認
+    #[test]
+    fn now_jst_converts_clock_date_time_to_jst() {
+        let clock = FixedClock(datetime!(2024-05-01 15:00:00 UTC));
+
+        let jst = now_jst(&clock);
+
+        assert_eq!(offset!(+9), jst.offset());
+        assert_eq!(datetime!(2024-05-02 00:00:00 +9), jst);
+    }
+
+    /// リポジトリの処理のタイムアウトを格納したリポジトリエラーのみを、タイムアウトと判定することを確認
+    #[test]
+    fn repository_timeout_is_detected() {
+        assert!(DomainError::Repository(RepositoryTimeout.into()).is_timeout());
+        assert!(!DomainError::Repository(anyhow::anyhow!("error")).is_timeout());
+        assert!(!DomainError::Unexpected(RepositoryTimeout.into()).is_timeout());
+    }
+
     struct DateTimeComponents {
         year: i32,
         month: Month,
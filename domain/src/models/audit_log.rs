@@ -0,0 +1,145 @@
+use time::OffsetDateTime;
+
+use crate::models::primitives::EntityId;
+use crate::models::user::UserId;
+use crate::repositories::audit_log::AuditLogInput;
+use crate::DomainError;
+
+/// 監査ログID
+pub type AuditLogId = EntityId<AuditLog>;
+
+/// 監査ログ
+///
+/// 管理者がユーザーに対して実行した操作と、ユーザーがパスワードを変更したことを記録する。操作を
+/// 実行した管理者や、操作の対象となったユーザーのレコードを物理的に削除しても、監査ログは削除しない。
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    /// 監査ログID
+    pub id: AuditLogId,
+    /// 操作を実行した管理者のユーザーID
+    pub actor_user_id: UserId,
+    /// 操作
+    pub action: AuditAction,
+    /// 操作の対象となったユーザーのユーザーID
+    pub target_user_id: Option<UserId>,
+    /// 操作を実行した日時
+    pub occurred_at: OffsetDateTime,
+    /// 操作を要求したリクエストのリクエストID
+    pub request_id: Option<String>,
+    /// 操作ごとの詳細
+    pub details: serde_json::Value,
+}
+
+/// 監査ログに記録する操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AuditAction {
+    /// ユーザーのユーザー権限を変更した。
+    ChangeUserPermission,
+    /// ユーザーのアカウントを削除した。
+    DeleteUser,
+    /// ユーザーを一括して登録した。
+    ImportUser,
+    /// パスワードを生成してユーザーを登録した。
+    CreateUser,
+    /// ユーザーのパスワードを変更した。
+    UpdatePassword,
+}
+
+impl AuditAction {
+    /// 操作を示す名前を返す。
+    ///
+    /// # 戻り値
+    ///
+    /// 操作を示す名前
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ChangeUserPermission => "change_user_permission",
+            Self::DeleteUser => "delete_user",
+            Self::ImportUser => "import_user",
+            Self::CreateUser => "create_user",
+            Self::UpdatePassword => "update_password",
+        }
+    }
+}
+
+impl std::fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl TryFrom<&str> for AuditAction {
+    type Error = DomainError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "change_user_permission" => Ok(Self::ChangeUserPermission),
+            "delete_user" => Ok(Self::DeleteUser),
+            "import_user" => Ok(Self::ImportUser),
+            "create_user" => Ok(Self::CreateUser),
+            "update_password" => Ok(Self::UpdatePassword),
+            _ => Err(DomainError::Validation(
+                "監査ログの操作が範囲外です。".into(),
+            )),
+        }
+    }
+}
+
+/// 監査ログに記録する操作者
+///
+/// 操作を実行した管理者と、操作を要求したリクエストを識別する。
+#[derive(Debug, Clone)]
+pub struct AuditActor {
+    /// 操作を実行した管理者のユーザーID
+    pub user_id: UserId,
+    /// 操作を要求したリクエストのリクエストID
+    pub request_id: Option<String>,
+}
+
+impl AuditActor {
+    /// 操作者が実行した操作を記録する監査ログを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `action` - 操作
+    /// * `target_user_id` - 操作の対象となったユーザーのユーザーID
+    /// * `details` - 操作ごとの詳細
+    ///
+    /// # 戻り値
+    ///
+    /// 記録する監査ログ
+    pub fn entry(
+        &self,
+        action: AuditAction,
+        target_user_id: Option<UserId>,
+        details: serde_json::Value,
+    ) -> AuditLogInput {
+        AuditLogInput {
+            actor_user_id: self.user_id,
+            action,
+            target_user_id,
+            request_id: self.request_id.clone(),
+            details,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 操作を示す名前から、操作に変換できることを確認
+    #[test]
+    fn audit_action_round_trips_through_name() {
+        for action in [
+            AuditAction::ChangeUserPermission,
+            AuditAction::DeleteUser,
+            AuditAction::ImportUser,
+            AuditAction::CreateUser,
+            AuditAction::UpdatePassword,
+        ] {
+            assert_eq!(action, AuditAction::try_from(action.as_str()).unwrap());
+        }
+        assert!(AuditAction::try_from("lock_user").is_err());
+    }
+}
@@ -1,2 +1,4 @@
+pub mod audit_log;
 pub mod primitives;
+pub mod query;
 pub mod user;
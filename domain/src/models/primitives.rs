@@ -23,7 +23,7 @@ pub struct EntityId<T> {
     _phantom: PhantomData<T>,
 }
 
-impl<'a, T> TryFrom<&'a str> for EntityId<T> {
+impl<T> TryFrom<&str> for EntityId<T> {
     type Error = DomainError;
 
     fn try_from(s: &str) -> Result<Self, Self::Error> {
@@ -86,6 +86,55 @@ impl<T> EntityId<T> {
     }
 }
 
+/// エンティティIDを、UUIDの文字列としてシリアライズする。
+impl<T> serde::Serialize for EntityId<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+/// UUIDの文字列から、エンティティIDをデシリアライズする。
+impl<'de, T> serde::Deserialize<'de> for EntityId<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Uuid::deserialize(deserializer).map(Self::new)
+    }
+}
+
+/// エンティティIDを、PostgreSQLの`UUID`型として扱う。
+#[cfg(feature = "sqlx")]
+impl<T> sqlx::Type<sqlx::Postgres> for EntityId<T> {
+    fn type_info() -> sqlx::postgres::PgTypeInfo {
+        <Uuid as sqlx::Type<sqlx::Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+        <Uuid as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'q, T> sqlx::Encode<'q, sqlx::Postgres> for EntityId<T> {
+    fn encode_by_ref(
+        &self,
+        buf: &mut sqlx::postgres::PgArgumentBuffer,
+    ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        <Uuid as sqlx::Encode<'q, sqlx::Postgres>>::encode_by_ref(&self.value, buf)
+    }
+}
+
+#[cfg(feature = "sqlx")]
+impl<'r, T> sqlx::Decode<'r, sqlx::Postgres> for EntityId<T> {
+    fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        <Uuid as sqlx::Decode<'r, sqlx::Postgres>>::decode(value).map(Self::new)
+    }
+}
+
 /// コード
 ///
 /// ジェネリック引数`T1`はコードテーブルの型を指定する。
@@ -131,6 +180,49 @@ impl<T1, T2: Clone + Copy> NumericCode<T1, T2> {
     }
 }
 
+/// ドメインプリミティブのメタデータ
+///
+/// `StringPrimitive`、`IntegerPrimitive`及び`OptionalStringPrimitive`導出マクロが実装する
+/// `metadata`関数が返す、ドメインプリミティブの名前と制約を表現する。
+/// OpenAPIのスキーマや、フィールドごとのエラーレスポンスを構築するときに使用する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrimitiveMetadata {
+    /// ドメインプリミティブの名前
+    pub name: &'static str,
+    /// 文字列の最小文字数
+    pub min_len: Option<usize>,
+    /// 文字列の最大文字数
+    pub max_len: Option<usize>,
+    /// 文字列がマッチする正規表現
+    pub pattern: Option<&'static str>,
+    /// 整数の最小値
+    pub min: Option<i64>,
+    /// 整数の最大値
+    pub max: Option<i64>,
+}
+
+impl PrimitiveMetadata {
+    /// 制約を持たないドメインプリミティブのメタデータを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `name` - ドメインプリミティブの名前
+    ///
+    /// # 戻り値
+    ///
+    /// ドメインプリミティブのメタデータ
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            min_len: None,
+            max_len: None,
+            pattern: None,
+            min: None,
+            max: None,
+        }
+    }
+}
+
 /// Eメールアドレスの長さ
 ///
 /// Eメールアドレスの文字数の最小値は規定されていないため、"a@a.jp"のようなアドレスを想定して6文字とした。
@@ -155,7 +247,7 @@ pub struct EmailAddress {
 ///
 /// 未加工なパスワードは、次を満たさなければならない。
 ///
-/// * 8文字以上
+/// * 8文字以上128文字以下
 /// * 大文字、小文字のアルファベットをそれぞれ1つ以上含む
 /// * 数字を1つ以上含む
 /// * 次の記号を1つ以上含む
@@ -167,9 +259,35 @@ pub struct RawPassword {
 }
 
 impl RawPassword {
+    /// デフォルトのパスワードポリシーを満たしているか検証して、未加工なパスワードを構築する。
+    ///
+    /// 最大文字数はパスワード設定で変更できるため、利用者が入力したパスワードは設定から取得した
+    /// パスワードポリシーを指定して[`RawPassword::with_policy`]で構築すること。
+    ///
+    /// # 引数
+    ///
+    /// * `value` - パスワード
+    ///
+    /// # 戻り値
+    ///
+    /// 未加工なパスワード
     pub fn new(value: SecretString) -> DomainResult<Self> {
+        Self::with_policy(value, &PASSWORD_POLICY)
+    }
+
+    /// パスワードポリシーを満たしているか検証して、未加工なパスワードを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `value` - パスワード
+    /// * `policy` - パスワードポリシー
+    ///
+    /// # 戻り値
+    ///
+    /// 未加工なパスワード
+    pub fn with_policy(value: SecretString, policy: &PasswordPolicy) -> DomainResult<Self> {
         let value = value.expose_secret().trim();
-        validate_plain_password(value)?;
+        validate_plain_password(value, policy)?;
         let value =
             SecretString::from_str(value).map_err(|e| DomainError::Unexpected(anyhow!(e)))?;
 
@@ -179,18 +297,59 @@ impl RawPassword {
 
 /// パスワードの最小文字数
 const PASSWORD_MIN_LENGTH: usize = 8;
+/// パスワードのデフォルトの最大文字数
+///
+/// 長大なパスワードのハッシュ化でCPUを消費しないように、文字数の上限を設ける。
+pub const PASSWORD_MAX_LENGTH: usize = 128;
 /// パスワードに含めるシンボルの候補
 const PASSWORD_SYMBOLS_CANDIDATES: &str = r#"~`!@#$%^&*()_-+={[}]|\:;"'<,>.?/"#;
 /// パスワードに同じ文字が存在することを許容する最大数
 /// 指定された数だけ同じ文字をパスワードに含めることを許可
 const PASSWORD_MAX_NUMBER_OF_CHAR_APPEARANCES: u64 = 3;
 
+/// パスワードポリシー
+///
+/// 未加工なパスワードが満たさなければならない規則を表現する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    /// パスワードの最小文字数
+    pub min_length: usize,
+    /// パスワードの最大文字数
+    pub max_length: usize,
+    /// パスワードに含めるシンボルの候補
+    pub symbols: &'static str,
+    /// パスワードに同じ文字が存在することを許容する最大数
+    pub max_char_appearances: u64,
+}
+
+/// 未加工なパスワードを構築するときに検証するパスワードポリシー
+pub const PASSWORD_POLICY: PasswordPolicy = PasswordPolicy {
+    min_length: PASSWORD_MIN_LENGTH,
+    max_length: PASSWORD_MAX_LENGTH,
+    symbols: PASSWORD_SYMBOLS_CANDIDATES,
+    max_char_appearances: PASSWORD_MAX_NUMBER_OF_CHAR_APPEARANCES,
+};
+
 /// パスワードがドメインルールを満たしているか確認する。
-fn validate_plain_password(s: &str) -> DomainResult<()> {
+fn validate_plain_password(s: &str, policy: &PasswordPolicy) -> DomainResult<()> {
+    // 他の規則を確認する前に、パスワードが長すぎないか確認
+    if policy.max_length < s.chars().count() {
+        return Err(DomainError::DomainRule(
+            format!(
+                "パスワードは{}文字以下で指定してください。",
+                policy.max_length
+            )
+            .into(),
+        ));
+    }
     // パスワードの文字数を確認
-    if s.len() < PASSWORD_MIN_LENGTH {
+    if s.len() < policy.min_length {
         return Err(DomainError::DomainRule(
-            format!("パスワードは少なくとも{PASSWORD_MIN_LENGTH}文字以上指定してください。").into(),
+            format!(
+                "パスワードは少なくとも{}文字以上指定してください。",
+                policy.min_length
+            )
+            .into(),
         ));
     }
     // 大文字のアルファベットが含まれるか確認
@@ -212,11 +371,11 @@ fn validate_plain_password(s: &str) -> DomainResult<()> {
         ));
     }
     // シンボルが含まれるか確認
-    if !s.chars().any(|ch| PASSWORD_SYMBOLS_CANDIDATES.contains(ch)) {
+    if !s.chars().any(|ch| policy.symbols.contains(ch)) {
         return Err(DomainError::DomainRule(
             format!(
                 "パスワードは記号({})を1文字以上含めなくてはなりません。",
-                PASSWORD_SYMBOLS_CANDIDATES
+                policy.symbols
             )
             .into(),
         ));
@@ -227,9 +386,13 @@ fn validate_plain_password(s: &str) -> DomainResult<()> {
         *number_of_chars.entry(ch).or_insert(0) += 1;
     });
     let max_number_of_appearances = number_of_chars.values().max().unwrap();
-    if PASSWORD_MAX_NUMBER_OF_CHAR_APPEARANCES < *max_number_of_appearances {
+    if policy.max_char_appearances < *max_number_of_appearances {
         return Err(DomainError::DomainRule(
-            format!("パスワードは同じ文字を{PASSWORD_MAX_NUMBER_OF_CHAR_APPEARANCES}個より多く含めることはできません。").into()
+            format!(
+                "パスワードは同じ文字を{}個より多く含めることはできません。",
+                policy.max_char_appearances
+            )
+            .into(),
         ));
     }
 
@@ -327,6 +490,21 @@ pub struct NumberOfSignInFailures {
     pub value: i16,
 }
 
+impl NumberOfSignInFailures {
+    /// サインイン失敗回数に1を加える。
+    ///
+    /// 最大値に達している場合は、最大値のままにする。
+    ///
+    /// # 戻り値
+    ///
+    /// 1を加えたサインイン失敗回数
+    pub fn saturating_increment(&self) -> Self {
+        Self {
+            value: self.value.saturating_add(1),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +531,27 @@ mod tests {
         }
     }
 
+    /// エンティティIDを、UUIDの文字列としてシリアライズ及びデシリアライズできることを確認
+    #[test]
+    fn entity_id_round_trips_through_serde_as_uuid_string() -> anyhow::Result<()> {
+        let expected = "27db4b5f-1ff8-4691-ba07-f54b56884241";
+        let entity_id: EntityId<i32> = expected.try_into()?;
+
+        let json = serde_json::to_string(&entity_id)?;
+        assert_eq!(format!(r#""{}""#, expected), json);
+        let deserialized: EntityId<i32> = serde_json::from_str(&json)?;
+        assert_eq!(entity_id, deserialized);
+
+        Ok(())
+    }
+
+    /// UUIDの形式でない文字列から、エンティティIDをデシリアライズできないことを確認
+    #[test]
+    fn entity_id_can_not_be_deserialized_from_invalid_string() {
+        let result = serde_json::from_str::<EntityId<i32>>(r#""invalid uuid v4 string""#);
+        assert!(result.is_err());
+    }
+
     /// Eメールアドレスとして妥当な文字列から、Eメール・アドレスを構築できることを確認
     #[test]
     fn construct_email_address_from_valid_strings() {
@@ -518,4 +717,20 @@ mod tests {
             );
         }
     }
+
+    /// サインイン失敗回数を、最大値を超えないようにインクリメントできることを確認
+    #[test]
+    fn number_of_sign_in_failures_is_saturating_incremented() {
+        let number_of_failures = NumberOfSignInFailures::new(0).unwrap();
+        assert_eq!(1, number_of_failures.saturating_increment().value);
+
+        let number_of_failures = NumberOfSignInFailures::new(i16::MAX).unwrap();
+        assert_eq!(i16::MAX, number_of_failures.saturating_increment().value);
+    }
+
+    /// 負のサインイン失敗回数を構築できないことを確認
+    #[test]
+    fn can_not_construct_negative_number_of_sign_in_failures() {
+        assert!(NumberOfSignInFailures::new(-1).is_err());
+    }
 }
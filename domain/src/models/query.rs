@@ -0,0 +1,173 @@
+use crate::{DomainError, DomainResult};
+
+/// ページ
+///
+/// リストを並び順で先頭から`offset`だけ読み飛ばし、最大で`limit`だけ取得する範囲を表現する。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Pagination {
+    /// 取得する最大数
+    ///
+    /// `None`の場合は、すべて取得する。
+    pub limit: Option<u32>,
+    /// 並び順で先頭から読み飛ばす数
+    pub offset: u32,
+}
+
+impl Pagination {
+    /// ページを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `limit` - 取得する最大数、`None`の場合はすべて取得
+    /// * `offset` - 並び順で先頭から読み飛ばす数
+    ///
+    /// # 戻り値
+    ///
+    /// ページ
+    pub fn new(limit: Option<u32>, offset: u32) -> Self {
+        Self { limit, offset }
+    }
+
+    /// 取得する最大数が、1以上`max`以下であるか検証する。
+    ///
+    /// # 引数
+    ///
+    /// * `max` - 取得できる最大数の上限
+    ///
+    /// # 戻り値
+    ///
+    /// 検証に成功した場合はページ
+    pub fn validate(self, max: u32) -> DomainResult<Self> {
+        match self.limit {
+            Some(limit) if !(1..=max).contains(&limit) => Err(DomainError::Validation(
+                format!("取得する数は、1以上{}以下です。", max).into(),
+            )),
+            _ => Ok(self),
+        }
+    }
+
+    /// 取得する最大数を、1以上`max`以下に収めたページを返す。
+    ///
+    /// 取得する最大数を指定していない場合は、`max`を取得する最大数にする。
+    ///
+    /// # 引数
+    ///
+    /// * `max` - 取得できる最大数の上限
+    ///
+    /// # 戻り値
+    ///
+    /// ページ
+    pub fn clamped(self, max: u32) -> Self {
+        let max = max.max(1);
+
+        Self {
+            limit: Some(self.limit.map_or(max, |limit| limit.clamp(1, max))),
+            offset: self.offset,
+        }
+    }
+
+    /// イテレーターから、ページの範囲の要素を取り出すイテレーターを返す。
+    ///
+    /// # 引数
+    ///
+    /// * `iter` - 並び順に要素を返すイテレーター
+    ///
+    /// # 戻り値
+    ///
+    /// ページの範囲の要素を返すイテレーター
+    pub fn apply<I: Iterator>(&self, iter: I) -> std::iter::Take<std::iter::Skip<I>> {
+        iter.skip(self.offset as usize)
+            .take(self.limit.map_or(usize::MAX, |limit| limit as usize))
+    }
+}
+
+/// 並べ替える方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortDirection {
+    /// 昇順
+    #[default]
+    Asc,
+    /// 降順
+    Desc,
+}
+
+impl TryFrom<&str> for SortDirection {
+    type Error = DomainError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "asc" => Ok(Self::Asc),
+            "desc" => Ok(Self::Desc),
+            _ => Err(DomainError::Validation(
+                "並べ替える方向は、ascまたはdescです。".into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 取得する最大数が範囲外の場合は、検証エラーになることを確認
+    #[test]
+    fn pagination_is_validated() {
+        assert!(Pagination::new(None, 0).validate(1000).is_ok());
+        assert!(Pagination::new(Some(1), 10).validate(1000).is_ok());
+        assert!(Pagination::new(Some(1000), 0).validate(1000).is_ok());
+        for limit in [0, 1001] {
+            let result = Pagination::new(Some(limit), 0).validate(1000);
+            assert!(
+                matches!(result, Err(DomainError::Validation(_))),
+                "{}",
+                limit
+            );
+        }
+    }
+
+    /// 取得する最大数を、1以上上限以下に収めることを確認
+    #[test]
+    fn pagination_is_clamped() {
+        assert_eq!(
+            Pagination::new(Some(100), 5),
+            Pagination::new(None, 5).clamped(100)
+        );
+        assert_eq!(
+            Pagination::new(Some(1), 0),
+            Pagination::new(Some(0), 0).clamped(100)
+        );
+        assert_eq!(
+            Pagination::new(Some(50), 0),
+            Pagination::new(Some(50), 0).clamped(100)
+        );
+        assert_eq!(
+            Pagination::new(Some(100), 0),
+            Pagination::new(Some(1001), 0).clamped(100)
+        );
+    }
+
+    /// イテレーターから、ページの範囲の要素を取り出すことを確認
+    #[test]
+    fn pagination_is_applied_to_iterator() {
+        let page = |limit, offset| {
+            Pagination::new(limit, offset)
+                .apply(1..=5)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec![1, 2, 3, 4, 5], page(None, 0));
+        assert_eq!(vec![3, 4], page(Some(2), 2));
+        assert!(page(Some(2), 5).is_empty());
+    }
+
+    /// 並べ替える方向を文字列から変換できることを確認
+    #[test]
+    fn sort_direction_can_be_parsed() {
+        assert_eq!(SortDirection::Asc, SortDirection::try_from("asc").unwrap());
+        assert_eq!(
+            SortDirection::Desc,
+            SortDirection::try_from("desc").unwrap()
+        );
+        assert!(SortDirection::try_from("up").is_err());
+    }
+}
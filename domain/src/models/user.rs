@@ -47,10 +47,13 @@ pub struct User {
     /// 住所
     pub address: Address,
     /// 固定電話番号
+    #[builder(default)]
     pub fixed_phone_number: OptionalFixedPhoneNumber,
     /// 携帯電話番号
+    #[builder(default)]
     pub mobile_phone_number: OptionalMobilePhoneNumber,
     /// 備考
+    #[builder(default)]
     pub remarks: OptionalRemarks,
     /// 最終サインイン日時
     pub last_sign_in_at: Option<OffsetDateTime>,
@@ -62,6 +65,11 @@ pub struct User {
     pub created_at: OffsetDateTime,
     /// 更新日時
     pub updated_at: OffsetDateTime,
+    /// バージョン
+    ///
+    /// プロフィール、Eメールアドレスまたはユーザー権限を変更するたびに1増やす。
+    /// サインインの試行の記録など、ユーザーが編集しない列の変更では変更しない。
+    pub version: i32,
 }
 
 pub trait UserValidator {
@@ -110,6 +118,12 @@ impl TryFrom<i16> for UserPermissionCode {
     }
 }
 
+impl From<UserPermissionCode> for i16 {
+    fn from(value: UserPermissionCode) -> Self {
+        value as i16
+    }
+}
+
 impl TryFrom<&str> for UserPermissionCode {
     type Error = DomainError;
 
@@ -163,6 +177,7 @@ pub struct UserPermissionName {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::SystemClock;
     use crate::now_jst;
 
     /// ユーザーを構築できることを確認
@@ -194,7 +209,7 @@ mod tests {
             ),
         ];
         let remarks = OptionalRemarks::try_from("remarks").unwrap();
-        let dt = now_jst();
+        let dt = now_jst(&SystemClock);
         for (fixed_phone_number, mobile_phone_number) in phone_number_pairs {
             let user = UserBuilder::new()
                 .id(id)
@@ -212,6 +227,7 @@ mod tests {
                 .number_of_sign_in_failures(NumberOfSignInFailures::new(0).unwrap())
                 .created_at(dt)
                 .updated_at(dt)
+                .version(1)
                 .build();
 
             assert!(
@@ -237,7 +253,7 @@ mod tests {
         let given_name = super::GivenName::new("bar").unwrap();
         let postal_code = PostalCode::new("012-3456").unwrap();
         let address = Address::new("foo bar baz qux").unwrap();
-        let dt = now_jst();
+        let dt = now_jst(&SystemClock);
         let user = UserBuilder::new()
             .id(id)
             .email(email.clone())
@@ -247,11 +263,9 @@ mod tests {
             .given_name(given_name.clone())
             .postal_code(postal_code.clone())
             .address(address.clone())
-            .fixed_phone_number(OptionalFixedPhoneNumber::none())
-            .mobile_phone_number(OptionalMobilePhoneNumber::none())
-            .remarks(OptionalRemarks::none())
             .created_at(dt)
             .updated_at(dt)
+            .version(1)
             .build();
         assert!(user.is_err());
     }
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::models::audit_log::{AuditAction, AuditLog};
+use crate::models::user::UserId;
+use crate::DomainResult;
+
+/// 監査ログリポジトリ
+///
+/// 管理者が実行した操作を記録した監査ログを取得する。
+///
+/// 監査ログと操作による変更が食い違わないように、監査ログは、操作による変更と同じトランザクション
+/// で記録する。このため、監査ログを記録するメソッドは、操作による変更を記録するリポジトリが
+/// 提供する（例: [`crate::repositories::user::UserRepository::update_permission`]）。
+#[async_trait]
+pub trait AuditLogRepository: std::fmt::Debug + Sync + Send {
+    /// 監査ログのリストを、操作を実行した日時の新しい順に取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `filter` - 監査ログリストの絞り込み条件
+    ///
+    /// # 戻り値
+    ///
+    /// 監査ログを格納したベクタ
+    async fn list(&self, filter: AuditLogFilter) -> DomainResult<Vec<AuditLog>>;
+}
+
+/// 共有された監査ログリポジトリ
+///
+/// `Arc<dyn AuditLogRepository>`を監査ログリポジトリとして、ユースケースに渡せるようにする。
+#[async_trait]
+impl<T: AuditLogRepository + ?Sized> AuditLogRepository for Arc<T> {
+    async fn list(&self, filter: AuditLogFilter) -> DomainResult<Vec<AuditLog>> {
+        (**self).list(filter).await
+    }
+}
+
+/// 記録する監査ログ
+///
+/// 監査ログIDと操作を実行した日時は、監査ログを記録するときに設定する。
+#[derive(Debug, Clone)]
+pub struct AuditLogInput {
+    /// 操作を実行した管理者のユーザーID
+    pub actor_user_id: UserId,
+    /// 操作
+    pub action: AuditAction,
+    /// 操作の対象となったユーザーのユーザーID
+    pub target_user_id: Option<UserId>,
+    /// 操作を要求したリクエストのリクエストID
+    pub request_id: Option<String>,
+    /// 操作ごとの詳細
+    pub details: serde_json::Value,
+}
+
+/// 監査ログリストの絞り込み条件
+///
+/// `None`の条件では絞り込まない。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AuditLogFilter {
+    /// 操作を実行した管理者のユーザーID
+    pub actor_user_id: Option<UserId>,
+    /// 操作の対象となったユーザーのユーザーID
+    pub target_user_id: Option<UserId>,
+    /// 取得する監査ログの最大数
+    ///
+    /// `None`の場合は、すべての監査ログを取得する。
+    pub limit: Option<u32>,
+    /// 操作を実行した日時の新しい順で、先頭から読み飛ばす監査ログの数
+    pub offset: u32,
+}
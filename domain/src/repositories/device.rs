@@ -0,0 +1,34 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::models::user::UserId;
+use crate::DomainResult;
+
+/// デバイスリポジトリ
+///
+/// ユーザーがサインインしたデバイスを記録する。
+#[async_trait]
+pub trait DeviceRepository: std::fmt::Debug + Sync + Send {
+    /// ユーザーがサインインしたデバイスを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `device` - デバイスを識別する文字列（例: `User-Agent`ヘッダの値）
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーがはじめてサインインしたデバイスの場合は`true`、それ以外の場合は`false`
+    async fn register_device(&self, user_id: UserId, device: &str) -> DomainResult<bool>;
+}
+
+/// 共有されたデバイスリポジトリ
+///
+/// `Arc<dyn DeviceRepository>`をデバイスリポジトリとして、ユースケースに渡せるようにする。
+#[async_trait]
+impl<T: DeviceRepository + ?Sized> DeviceRepository for Arc<T> {
+    async fn register_device(&self, user_id: UserId, device: &str) -> DomainResult<bool> {
+        (**self).register_device(user_id, device).await
+    }
+}
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+
+use crate::DomainResult;
+
+/// 冪等キーで記録したリクエスト
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IdempotencyRecord {
+    /// リクエストを処理している
+    Processing {
+        /// リクエストボディのハッシュ値
+        request_hash: String,
+    },
+    /// リクエストの処理が完了した
+    Completed {
+        /// リクエストボディのハッシュ値
+        request_hash: String,
+        /// リクエストに返したレスポンス
+        response: IdempotentResponse,
+    },
+}
+
+impl IdempotencyRecord {
+    /// リクエストボディのハッシュ値を返す。
+    ///
+    /// # 戻り値
+    ///
+    /// リクエストボディのハッシュ値
+    pub fn request_hash(&self) -> &str {
+        match self {
+            Self::Processing { request_hash } => request_hash,
+            Self::Completed { request_hash, .. } => request_hash,
+        }
+    }
+}
+
+/// 冪等キーで記録したレスポンス
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdempotentResponse {
+    /// HTTPステータスコード
+    pub status_code: u16,
+    /// JSON形式のレスポンスボディ
+    pub body: String,
+}
+
+/// 冪等キーリポジトリ
+///
+/// クライアントがリクエストを再送信したときに、同じ処理を繰り返さずに、最初のリクエストに返した
+/// レスポンスを返せるように、冪等キーごとにリクエストとレスポンスを記録する。
+#[async_trait]
+pub trait IdempotencyRepository: Sync + Send {
+    /// 冪等キーを予約して、リクエストを処理していることを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `key` - 冪等キー
+    /// * `request_hash` - リクエストボディのハッシュ値
+    /// * `ttl` - 記録を保持する期間（秒）
+    ///
+    /// # 戻り値
+    ///
+    /// 冪等キーを予約できた場合は`None`、冪等キーがすでに記録されている場合は記録
+    async fn reserve(
+        &self,
+        key: &str,
+        request_hash: &str,
+        ttl: u64,
+    ) -> DomainResult<Option<IdempotencyRecord>>;
+
+    /// 予約した冪等キーに、リクエストに返したレスポンスを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `key` - 冪等キー
+    /// * `request_hash` - リクエストボディのハッシュ値
+    /// * `response` - リクエストに返したレスポンス
+    /// * `ttl` - 記録を保持する期間（秒）
+    async fn complete(
+        &self,
+        key: &str,
+        request_hash: &str,
+        response: &IdempotentResponse,
+        ttl: u64,
+    ) -> DomainResult<()>;
+
+    /// 予約した冪等キーを解放して、同じ冪等キーでリクエストを再送信できるようにする。
+    ///
+    /// # 引数
+    ///
+    /// * `key` - 冪等キー
+    async fn release(&self, key: &str) -> DomainResult<()>;
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+
+use crate::models::audit_log::{AuditLog, AuditLogId};
+use crate::repositories::audit_log::{AuditLogFilter, AuditLogInput, AuditLogRepository};
+use crate::DomainResult;
+
+/// メモリ監査ログリポジトリ
+///
+/// PostgreSQLに接続せずに、メモリユーザーリポジトリがユーザーを変更したときに記録した監査ログを
+/// 取得する監査ログリポジトリで、テストで使用する。
+/// [`crate::repositories::memory::user::InMemoryUserRepository::audit_log_repository`]で構築する。
+///
+/// 複製したリポジトリは、同じ監査ログを共有する。
+#[derive(Debug, Clone)]
+pub struct InMemoryAuditLogRepository {
+    /// 記録した順番に格納した監査ログ
+    audit_logs: Arc<RwLock<Vec<AuditLog>>>,
+}
+
+impl InMemoryAuditLogRepository {
+    /// メモリ監査ログリポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `audit_logs` - メモリユーザーリポジトリと共有する監査ログ
+    ///
+    /// # 戻り値
+    ///
+    /// メモリ監査ログリポジトリ
+    pub(super) fn new(audit_logs: Arc<RwLock<Vec<AuditLog>>>) -> Self {
+        Self { audit_logs }
+    }
+}
+
+#[async_trait]
+impl AuditLogRepository for InMemoryAuditLogRepository {
+    async fn list(&self, filter: AuditLogFilter) -> DomainResult<Vec<AuditLog>> {
+        Ok(self
+            .audit_logs
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|log| {
+                filter
+                    .actor_user_id
+                    .is_none_or(|user_id| log.actor_user_id == user_id)
+                    && filter
+                        .target_user_id
+                        .is_none_or(|user_id| log.target_user_id == Some(user_id))
+            })
+            .skip(filter.offset as usize)
+            .take(filter.limit.map_or(usize::MAX, |limit| limit as usize))
+            .cloned()
+            .collect())
+    }
+}
+
+/// 記録する監査ログから、記録した日時を設定した監査ログを構築する。
+pub(super) fn audit_log(input: AuditLogInput) -> AuditLog {
+    AuditLog {
+        id: AuditLogId::default(),
+        actor_user_id: input.actor_user_id,
+        action: input.action,
+        target_user_id: input.target_user_id,
+        occurred_at: OffsetDateTime::now_utc(),
+        request_id: input.request_id,
+        details: input.details,
+    }
+}
@@ -0,0 +1,4 @@
+pub mod audit_log;
+pub mod token;
+pub mod user;
+pub mod user_permission;
@@ -0,0 +1,677 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use secrecy::{ExposeSecret as _, SecretString};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::user::{UserId, UserPermissionCode};
+use crate::repositories::token::{
+    AccessToken, ActiveSession, PendingEmailChange, SessionToken, TokenContent, TokenPairWithTtl,
+    TokenRepository, TokenType,
+};
+use crate::{DomainError, DomainResult};
+
+/// メモリトークンリポジトリ
+///
+/// Redisに接続せずに、トークンをメモリに保存するトークンリポジトリで、テストで使用する。
+/// Redisトークンリポジトリと同様に、生存期間を過ぎたトークンは取得できない。
+#[derive(Debug, Default)]
+pub struct InMemoryTokenRepository {
+    /// トークンを保存する状態
+    state: Mutex<State>,
+}
+
+/// メモリトークンリポジトリの状態
+#[derive(Debug, Default)]
+struct State {
+    /// キーと、キーに保存した値
+    entries: HashMap<String, Entry>,
+    /// ユーザーIDと、ユーザーに発行したアクセストークン及びリフレッシュトークンのキー
+    user_tokens: HashMap<Uuid, HashSet<String>>,
+}
+
+/// キーに保存した値と、値の有効期限
+#[derive(Debug, Clone)]
+struct Entry {
+    value: Value,
+    expires_at: Instant,
+}
+
+/// キーに保存する値
+#[derive(Debug, Clone)]
+enum Value {
+    /// アクセストークンまたはリフレッシュトークンの内容
+    Token(TokenContent),
+    /// ユーザーID
+    UserId(UserId),
+    /// 保留しているEメールアドレスの変更
+    EmailChange(PendingEmailChange),
+    /// セッション
+    Session(SessionEntry),
+}
+
+/// セッションに保存する、トークンを発行した日時、トークンのキー及びデバイス
+#[derive(Debug, Clone)]
+struct SessionEntry {
+    issued_at: OffsetDateTime,
+    access_key: String,
+    refresh_key: String,
+    device: Option<String>,
+}
+
+impl InMemoryTokenRepository {
+    /// メモリトークンリポジトリを構築する。
+    ///
+    /// # 戻り値
+    ///
+    /// メモリトークンリポジトリ
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 状態をロックする。
+    fn lock(&self) -> DomainResult<MutexGuard<'_, State>> {
+        self.state
+            .lock()
+            .map_err(|_| DomainError::Repository(anyhow!("{}", LOCK_ERROR)))
+    }
+}
+
+impl State {
+    /// キーに値を保存する。
+    fn store(&mut self, key: String, value: Value, ttl: u64) {
+        let expires_at = Instant::now() + Duration::from_secs(ttl);
+        self.entries.insert(key, Entry { value, expires_at });
+    }
+
+    /// キーに保存した値を取得する。
+    ///
+    /// 有効期限が切れた値は削除して、`None`を返す。
+    fn retrieve(&mut self, key: &str) -> Option<Value> {
+        let entry = self.entries.get(key)?;
+        if entry.expires_at <= Instant::now() {
+            self.entries.remove(key);
+            return None;
+        }
+
+        Some(entry.value.clone())
+    }
+
+    /// キーに保存した値の有効期限を取得する。
+    fn expiration(&mut self, key: &str) -> Option<OffsetDateTime> {
+        self.retrieve(key)?;
+        let remaining = self.entries[key].expires_at - Instant::now();
+
+        Some(OffsetDateTime::now_utc() + remaining)
+    }
+
+    /// キーに保存した値を取得して削除する。
+    fn consume(&mut self, key: &str) -> Option<Value> {
+        let value = self.retrieve(key);
+        self.entries.remove(key);
+
+        value
+    }
+}
+
+#[async_trait]
+impl TokenRepository for InMemoryTokenRepository {
+    async fn register_token_pair<'a>(
+        &self,
+        user_id: UserId,
+        token_pair: TokenPairWithTtl<'a>,
+        user_permission_code: UserPermissionCode,
+    ) -> DomainResult<()> {
+        let mut state = self.lock()?;
+        let access_key = generate_key(TOKEN_KEY_PREFIX, token_pair.access.secret());
+        let refresh_key = generate_key(TOKEN_KEY_PREFIX, token_pair.refresh.secret());
+        let session_id = Uuid::new_v4().simple().to_string()[..SESSION_ID_LENGTH].to_string();
+        let session = SessionEntry {
+            issued_at: OffsetDateTime::now_utc(),
+            access_key,
+            refresh_key,
+            device: token_pair.device.map(String::from),
+        };
+        let session_key = generate_session_key(user_id, &session_id);
+        state.store(
+            session_key.clone(),
+            Value::Session(session),
+            token_pair.refresh_ttl,
+        );
+        state
+            .user_tokens
+            .entry(user_id.value)
+            .or_default()
+            .insert(session_key);
+        let tokens = [
+            (
+                token_pair.access.secret(),
+                TokenType::Access,
+                token_pair.access_ttl,
+            ),
+            (
+                token_pair.refresh.secret(),
+                TokenType::Refresh,
+                token_pair.refresh_ttl,
+            ),
+        ];
+        for (token, token_type, ttl) in tokens {
+            let key = generate_key(TOKEN_KEY_PREFIX, token);
+            let content = TokenContent {
+                user_id,
+                token_type,
+                user_permission_code,
+            };
+            state.store(key.clone(), Value::Token(content), ttl);
+            state
+                .user_tokens
+                .entry(user_id.value)
+                .or_default()
+                .insert(key);
+        }
+
+        Ok(())
+    }
+
+    async fn retrieve_token_content(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<TokenContent>> {
+        let mut state = self.lock()?;
+
+        match state.retrieve(&generate_key(TOKEN_KEY_PREFIX, token)) {
+            Some(Value::Token(content)) => Ok(Some(content)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn register_email_verification_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let key = generate_key(EMAIL_VERIFICATION_KEY_PREFIX, token);
+        self.lock()?.store(key, Value::UserId(user_id), ttl);
+
+        Ok(())
+    }
+
+    async fn consume_email_verification_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        let key = generate_key(EMAIL_VERIFICATION_KEY_PREFIX, token);
+
+        match self.lock()?.consume(&key) {
+            Some(Value::UserId(user_id)) => Ok(Some(user_id)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn register_email_change_token(
+        &self,
+        email_change: &PendingEmailChange,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let key = generate_key(EMAIL_CHANGE_KEY_PREFIX, token);
+        self.lock()?
+            .store(key, Value::EmailChange(email_change.clone()), ttl);
+
+        Ok(())
+    }
+
+    async fn consume_email_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<PendingEmailChange>> {
+        let key = generate_key(EMAIL_CHANGE_KEY_PREFIX, token);
+
+        match self.lock()?.consume(&key) {
+            Some(Value::EmailChange(email_change)) => Ok(Some(email_change)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn register_mfa_pending_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut state = self.lock()?;
+        let key = generate_key(MFA_PENDING_KEY_PREFIX, token);
+        state.store(key.clone(), Value::UserId(user_id), ttl);
+        state
+            .user_tokens
+            .entry(user_id.value)
+            .or_default()
+            .insert(key);
+
+        Ok(())
+    }
+
+    async fn consume_mfa_pending_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        let key = generate_key(MFA_PENDING_KEY_PREFIX, token);
+
+        match self.lock()?.consume(&key) {
+            Some(Value::UserId(user_id)) => Ok(Some(user_id)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn revoke_mfa_pending_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        let mut guard = self.lock()?;
+        let state = &mut *guard;
+        let Some(user_tokens) = state.user_tokens.get_mut(&user_id.value) else {
+            return Ok(());
+        };
+        let keys = user_tokens
+            .iter()
+            .filter(|key| key.starts_with(MFA_PENDING_KEY_PREFIX))
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            user_tokens.remove(&key);
+            state.entries.remove(&key);
+        }
+
+        Ok(())
+    }
+
+    async fn register_password_change_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let key = generate_key(PASSWORD_CHANGE_KEY_PREFIX, token);
+        self.lock()?.store(key, Value::UserId(user_id), ttl);
+
+        Ok(())
+    }
+
+    async fn consume_password_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        let key = generate_key(PASSWORD_CHANGE_KEY_PREFIX, token);
+
+        match self.lock()?.consume(&key) {
+            Some(Value::UserId(user_id)) => Ok(Some(user_id)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn register_reauthentication(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut state = self.lock()?;
+        let key = generate_key(REAUTHENTICATION_KEY_PREFIX, access_token.secret());
+        state.store(key.clone(), Value::UserId(user_id), ttl);
+        state
+            .user_tokens
+            .entry(user_id.value)
+            .or_default()
+            .insert(key);
+
+        Ok(())
+    }
+
+    async fn reauthenticated_user(
+        &self,
+        access_token: &AccessToken,
+    ) -> DomainResult<Option<UserId>> {
+        let key = generate_key(REAUTHENTICATION_KEY_PREFIX, access_token.secret());
+
+        match self.lock()?.retrieve(&key) {
+            Some(Value::UserId(user_id)) => Ok(Some(user_id)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn user_sessions(
+        &self,
+        user_id: UserId,
+        current_access_token: &AccessToken,
+    ) -> DomainResult<Vec<ActiveSession>> {
+        let mut state = self.lock()?;
+        let current_access_key = generate_key(TOKEN_KEY_PREFIX, current_access_token.secret());
+        let session_keys = state
+            .user_tokens
+            .get(&user_id.value)
+            .map(|keys| {
+                keys.iter()
+                    .filter(|key| key.starts_with(SESSION_KEY_PREFIX))
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut sessions = vec![];
+        for key in session_keys {
+            let Some(Value::Session(session)) = state.retrieve(&key) else {
+                continue;
+            };
+            let tokens = [
+                (TokenType::Access, &session.access_key),
+                (TokenType::Refresh, &session.refresh_key),
+            ]
+            .into_iter()
+            .filter_map(|(token_type, key)| {
+                state.expiration(key).map(|expiration| SessionToken {
+                    token_type,
+                    expiration,
+                })
+            })
+            .collect();
+            sessions.push(ActiveSession {
+                id: key.rsplit(':').next().unwrap().to_string(),
+                issued_at: session.issued_at,
+                tokens,
+                device: session.device,
+                current: session.access_key == current_access_key,
+            });
+        }
+
+        Ok(sessions)
+    }
+
+    async fn revoke_session(&self, user_id: UserId, session_id: &str) -> DomainResult<bool> {
+        let mut state = self.lock()?;
+        let session_key = generate_session_key(user_id, session_id);
+        let Some(Value::Session(session)) = state.consume(&session_key) else {
+            return Ok(false);
+        };
+        let reauthentication_key =
+            session
+                .access_key
+                .replacen(TOKEN_KEY_PREFIX, REAUTHENTICATION_KEY_PREFIX, 1);
+        let keys = [
+            session_key,
+            session.access_key,
+            session.refresh_key,
+            reauthentication_key,
+        ];
+        for key in keys {
+            state.entries.remove(&key);
+            if let Some(user_tokens) = state.user_tokens.get_mut(&user_id.value) {
+                user_tokens.remove(&key);
+            }
+        }
+
+        Ok(true)
+    }
+
+    async fn revoke_user_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        let mut state = self.lock()?;
+        let keys = state.user_tokens.remove(&user_id.value).unwrap_or_default();
+        for key in keys {
+            state.entries.remove(&key);
+        }
+
+        Ok(())
+    }
+}
+
+/// トークンを保存するキーを生成する。
+fn generate_key(prefix: &str, token: &SecretString) -> String {
+    format!("{}:{}", prefix, token.expose_secret())
+}
+
+/// セッションを保存するキーを生成する。
+fn generate_session_key(user_id: UserId, session_id: &str) -> String {
+    format!("{}:{}:{}", SESSION_KEY_PREFIX, user_id.value, session_id)
+}
+
+const TOKEN_KEY_PREFIX: &str = "token";
+const EMAIL_VERIFICATION_KEY_PREFIX: &str = "email_verification";
+const EMAIL_CHANGE_KEY_PREFIX: &str = "email_change";
+const MFA_PENDING_KEY_PREFIX: &str = "mfa_pending";
+const PASSWORD_CHANGE_KEY_PREFIX: &str = "password_change";
+const REAUTHENTICATION_KEY_PREFIX: &str = "reauthentication";
+const SESSION_KEY_PREFIX: &str = "session";
+/// セッションIDの文字数
+const SESSION_ID_LENGTH: usize = 16;
+
+const LOCK_ERROR: &str = "メモリトークンリポジトリをロックできませんでした。";
+
+#[cfg(test)]
+mod tests {
+    use crate::models::primitives::EmailAddress;
+    use crate::repositories::token::RefreshToken;
+
+    use super::*;
+
+    fn secret(value: &str) -> SecretString {
+        SecretString::new(String::from(value))
+    }
+
+    fn token_pair(access: &str, refresh: &str) -> (AccessToken, RefreshToken) {
+        (
+            AccessToken::new(secret(access)),
+            RefreshToken::new(secret(refresh)),
+        )
+    }
+
+    /// 登録したアクセストークンとリフレッシュトークンの内容を取得できることを確認
+    #[tokio::test]
+    async fn can_register_and_retrieve_token_pair() -> anyhow::Result<()> {
+        let repo = InMemoryTokenRepository::new();
+        let user_id = UserId::default();
+        let (access, refresh) = token_pair("access", "refresh");
+        let tokens = TokenPairWithTtl {
+            access: &access,
+            access_ttl: 60,
+            refresh: &refresh,
+            refresh_ttl: 0,
+            device: None,
+        };
+
+        repo.register_token_pair(user_id, tokens, UserPermissionCode::General)
+            .await?;
+
+        let content = repo.retrieve_token_content(access.secret()).await?.unwrap();
+        assert_eq!(user_id, content.user_id);
+        assert_eq!(TokenType::Access, content.token_type);
+        assert_eq!(UserPermissionCode::General, content.user_permission_code);
+        // 生存期間を過ぎたトークンは取得できない
+        assert!(repo
+            .retrieve_token_content(refresh.secret())
+            .await?
+            .is_none());
+        assert!(repo
+            .retrieve_token_content(&secret("unknown"))
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    /// 1度しか使用できないトークンは、2回目に消費できないことを確認
+    #[tokio::test]
+    async fn one_time_tokens_can_be_consumed_only_once() -> anyhow::Result<()> {
+        let repo = InMemoryTokenRepository::new();
+        let user_id = UserId::default();
+        let token = secret("one-time");
+        let email_change = PendingEmailChange {
+            user_id,
+            email: EmailAddress::new("foo@example.com")?,
+        };
+
+        repo.register_email_verification_token(user_id, &token, 60)
+            .await?;
+        repo.register_email_change_token(&email_change, &token, 60)
+            .await?;
+        repo.register_mfa_pending_token(user_id, &token, 60).await?;
+        repo.register_password_change_token(user_id, &token, 60)
+            .await?;
+
+        assert_eq!(
+            Some(user_id),
+            repo.consume_email_verification_token(&token).await?
+        );
+        assert!(repo
+            .consume_email_verification_token(&token)
+            .await?
+            .is_none());
+        assert_eq!(
+            Some(email_change),
+            repo.consume_email_change_token(&token).await?
+        );
+        assert!(repo.consume_email_change_token(&token).await?.is_none());
+        assert_eq!(Some(user_id), repo.consume_mfa_pending_token(&token).await?);
+        assert!(repo.consume_mfa_pending_token(&token).await?.is_none());
+        assert_eq!(
+            Some(user_id),
+            repo.consume_password_change_token(&token).await?
+        );
+        assert!(repo.consume_password_change_token(&token).await?.is_none());
+
+        Ok(())
+    }
+
+    /// 2要素認証を待機しているサインインを示すトークンを無効にできることを確認
+    #[tokio::test]
+    async fn can_revoke_mfa_pending_tokens() -> anyhow::Result<()> {
+        let repo = InMemoryTokenRepository::new();
+        let user_id = UserId::default();
+        let other_user_id = UserId::default();
+        let (access, refresh) = token_pair("access", "refresh");
+        let tokens = TokenPairWithTtl {
+            access: &access,
+            access_ttl: 60,
+            refresh: &refresh,
+            refresh_ttl: 60,
+            device: None,
+        };
+        repo.register_token_pair(user_id, tokens, UserPermissionCode::General)
+            .await?;
+        repo.register_mfa_pending_token(user_id, &secret("mfa1"), 60)
+            .await?;
+        repo.register_mfa_pending_token(user_id, &secret("mfa2"), 60)
+            .await?;
+        repo.register_mfa_pending_token(other_user_id, &secret("mfa3"), 60)
+            .await?;
+
+        repo.revoke_mfa_pending_tokens(user_id).await?;
+
+        assert!(repo
+            .consume_mfa_pending_token(&secret("mfa1"))
+            .await?
+            .is_none());
+        assert!(repo
+            .consume_mfa_pending_token(&secret("mfa2"))
+            .await?
+            .is_none());
+        // 他のユーザーのトークンと、アクセストークンは無効にしない
+        assert_eq!(
+            Some(other_user_id),
+            repo.consume_mfa_pending_token(&secret("mfa3")).await?
+        );
+        assert!(repo
+            .retrieve_token_content(access.secret())
+            .await?
+            .is_some());
+
+        Ok(())
+    }
+
+    /// セッションを取得して、1つのセッションを無効にできることを確認
+    #[tokio::test]
+    async fn can_list_and_revoke_sessions() -> anyhow::Result<()> {
+        let repo = InMemoryTokenRepository::new();
+        let user_id = UserId::default();
+        let pairs = [
+            (token_pair("access1", "refresh1"), Some("browser")),
+            (token_pair("access2", "refresh2"), None),
+        ];
+        for ((access, refresh), device) in &pairs {
+            let tokens = TokenPairWithTtl {
+                access,
+                access_ttl: 60,
+                refresh,
+                refresh_ttl: 120,
+                device: *device,
+            };
+            repo.register_token_pair(user_id, tokens, UserPermissionCode::General)
+                .await?;
+        }
+        let ((current_access, _), _) = &pairs[0];
+
+        let sessions = repo.user_sessions(user_id, current_access).await?;
+        assert_eq!(2, sessions.len());
+        let current = sessions.iter().find(|session| session.current).unwrap();
+        assert_eq!(Some("browser"), current.device.as_deref());
+        assert_eq!(2, current.tokens.len());
+        let other = sessions.iter().find(|session| !session.current).unwrap();
+        assert!(other.device.is_none());
+
+        assert!(repo.revoke_session(user_id, &other.id).await?);
+        assert!(!repo.revoke_session(user_id, &other.id).await?);
+        assert!(!repo.revoke_session(UserId::default(), &current.id).await?);
+        let ((other_access, other_refresh), _) = &pairs[1];
+        assert!(repo
+            .retrieve_token_content(other_access.secret())
+            .await?
+            .is_none());
+        assert!(repo
+            .retrieve_token_content(other_refresh.secret())
+            .await?
+            .is_none());
+        assert!(repo
+            .retrieve_token_content(current_access.secret())
+            .await?
+            .is_some());
+        let sessions = repo.user_sessions(user_id, current_access).await?;
+        assert_eq!(1, sessions.len());
+
+        Ok(())
+    }
+
+    /// ユーザーに発行したトークンをすべて無効にできることを確認
+    #[tokio::test]
+    async fn can_revoke_user_tokens() -> anyhow::Result<()> {
+        let repo = InMemoryTokenRepository::new();
+        let user_id = UserId::default();
+        let (access, refresh) = token_pair("access", "refresh");
+        let tokens = TokenPairWithTtl {
+            access: &access,
+            access_ttl: 60,
+            refresh: &refresh,
+            refresh_ttl: 60,
+            device: None,
+        };
+        repo.register_token_pair(user_id, tokens, UserPermissionCode::Admin)
+            .await?;
+        repo.register_mfa_pending_token(user_id, &secret("mfa"), 60)
+            .await?;
+
+        repo.revoke_user_tokens(user_id).await?;
+
+        assert!(repo
+            .retrieve_token_content(access.secret())
+            .await?
+            .is_none());
+        assert!(repo
+            .retrieve_token_content(refresh.secret())
+            .await?
+            .is_none());
+        assert!(repo
+            .consume_mfa_pending_token(&secret("mfa"))
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1164 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use futures_core::Stream;
+use time::OffsetDateTime;
+use tokio::sync::RwLock;
+
+use crate::models::audit_log::AuditLog;
+use crate::models::primitives::*;
+use crate::models::query::{Pagination, SortDirection};
+use crate::models::user::{User, UserId, UserPermission, UserPermissionCode, UserPermissionName};
+use crate::repositories::audit_log::AuditLogInput;
+use crate::repositories::memory::audit_log::{audit_log, InMemoryAuditLogRepository};
+use crate::repositories::user::{
+    GuardedChange, LockedUser, SignInAttempt, SignInRecord, SignUpInput, SignUpOutput,
+    UpdateProfileInput, UserCredential, UserListFilter, UserListOrder, UserRepository, UserStream,
+};
+use crate::{DomainError, DomainResult};
+
+/// メモリユーザーリポジトリ
+///
+/// PostgreSQLに接続せずに、ユーザーをメモリに保存するユーザーリポジトリで、テストで使用する。
+/// 各メソッドは、PostgreSQLユーザーリポジトリのクエリと同じ条件で、同じ列を更新する。
+/// 特に、サインインの失敗の記録、サインイン失敗回数のインクリメント及びサインインに失敗した履歴の
+/// 消去は、削除したユーザーを対象にしないなど、クエリの動作をそのまま再現する。
+///
+/// ユーザーを変更したときに記録する監査ログもメモリに保存して、`audit_log_repository`メソッドが
+/// 返すメモリ監査ログリポジトリで取得できる。
+///
+/// 複製したリポジトリは、同じユーザーと監査ログを共有する。
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryUserRepository {
+    /// ユーザーIDと、ユーザーのレコード
+    users: Arc<RwLock<HashMap<UserId, UserRecord>>>,
+    /// 記録した順番に格納した監査ログ
+    audit_logs: Arc<RwLock<Vec<AuditLog>>>,
+}
+
+/// ユーザーのレコード
+///
+/// `User`に含まれない、`users`テーブルの列を合わせて保存する。
+#[derive(Debug, Clone)]
+struct UserRecord {
+    /// ユーザー
+    user: User,
+    /// ユーザーのPHCパスワード文字列
+    password: PhcPassword,
+    /// ユーザーのアカウントをロックした日時
+    locked_at: Option<OffsetDateTime>,
+    /// ユーザーのEメールアドレスを確認した日時
+    email_verified_at: Option<OffsetDateTime>,
+    /// ユーザーがパスワードを変更しなければならないかを示すフラグ
+    password_change_required: bool,
+    /// ユーザーを削除した日時
+    deleted_at: Option<OffsetDateTime>,
+    /// 最後にサインインに失敗したアクセス元
+    last_failed_sign_in_attempt: SignInAttempt,
+}
+
+impl UserRecord {
+    /// ユーザーを削除していないか確認する。
+    fn is_alive(&self) -> bool {
+        self.deleted_at.is_none()
+    }
+
+    /// ユーザーのクレデンシャルを返す。
+    fn credential(&self) -> UserCredential {
+        UserCredential {
+            user_id: self.user.id,
+            email: self.user.email.clone(),
+            password: self.password.clone(),
+            active: self.user.active,
+            user_permission_code: self.user.user_permission.code,
+            attempted_at: self.user.sign_in_attempted_at,
+            number_of_failures: self.user.number_of_sign_in_failures,
+            locked_at: self.locked_at,
+            email_verified_at: self.email_verified_at,
+            password_change_required: self.password_change_required,
+        }
+    }
+
+    /// サインインに失敗した回数を設定する。
+    fn set_number_of_sign_in_failures(&mut self, value: i16) {
+        self.user.number_of_sign_in_failures = NumberOfSignInFailures::new(value).unwrap();
+    }
+}
+
+impl InMemoryUserRepository {
+    /// メモリユーザーリポジトリを構築する。
+    ///
+    /// # 戻り値
+    ///
+    /// メモリユーザーリポジトリ
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// このリポジトリが記録した監査ログを取得する、メモリ監査ログリポジトリを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// メモリ監査ログリポジトリ
+    pub fn audit_log_repository(&self) -> InMemoryAuditLogRepository {
+        InMemoryAuditLogRepository::new(self.audit_logs.clone())
+    }
+
+    /// ユーザーが最初にサインインに失敗した日時を変更する。
+    ///
+    /// サインインの失敗回数をカウントする期間が経過した状況を再現するために使用する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `attempted_at` - ユーザーが最初にサインインに失敗した日時
+    pub async fn set_sign_in_attempted_at(
+        &self,
+        user_id: UserId,
+        attempted_at: Option<OffsetDateTime>,
+    ) {
+        if let Some(record) = self.users.write().await.get_mut(&user_id) {
+            record.user.sign_in_attempted_at = attempted_at;
+        }
+    }
+
+    /// ユーザーのアカウントをロックした日時を変更する。
+    ///
+    /// アカウントを自動的にアンロックするまでの時間が経過した状況を再現するために使用する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `locked_at` - ユーザーのアカウントをロックした日時
+    pub async fn set_locked_at(&self, user_id: UserId, locked_at: Option<OffsetDateTime>) {
+        if let Some(record) = self.users.write().await.get_mut(&user_id) {
+            record.locked_at = locked_at;
+        }
+    }
+
+    /// ユーザーを削除した日時を変更する。
+    ///
+    /// 削除したユーザーを保持する期間が経過した状況を再現するために使用する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `deleted_at` - ユーザーを削除した日時
+    pub async fn set_deleted_at(&self, user_id: UserId, deleted_at: Option<OffsetDateTime>) {
+        if let Some(record) = self.users.write().await.get_mut(&user_id) {
+            record.deleted_at = deleted_at;
+        }
+    }
+
+    /// 最後にサインインに失敗したアクセス元を返す。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// 最後にサインインに失敗したアクセス元、ユーザーが存在しない場合は`None`
+    pub async fn last_failed_sign_in_attempt(&self, user_id: UserId) -> Option<SignInAttempt> {
+        self.users
+            .read()
+            .await
+            .get(&user_id)
+            .map(|record| record.last_failed_sign_in_attempt.clone())
+    }
+
+    /// ユーザーを更新して、更新後のユーザーのクレデンシャルを返す。
+    async fn update_credential(
+        &self,
+        user_id: UserId,
+        f: impl FnOnce(&mut UserRecord) + Send,
+    ) -> Option<UserCredential> {
+        let mut users = self.users.write().await;
+        let record = users.get_mut(&user_id).filter(|r| r.is_alive())?;
+        f(record);
+
+        Some(record.credential())
+    }
+}
+
+/// 絞り込み条件に一致する、削除していないユーザーを並び順に従って返す。
+///
+/// 検索文字列を指定した場合は、苗字、名前またはEメールアドレスに、検索文字列を大文字と小文字を
+/// 区別せずに含むユーザーに絞り込む。
+fn list_users(
+    users: &HashMap<UserId, UserRecord>,
+    search: Option<&str>,
+    filter: UserListFilter,
+) -> Vec<User> {
+    let search = search.map(str::to_lowercase);
+    let mut users = users
+        .values()
+        .filter(|r| r.is_alive())
+        .filter(|r| filter.active.is_none_or(|active| r.user.active == active))
+        .filter(|r| {
+            filter
+                .user_permission_code
+                .is_none_or(|code| r.user.user_permission.code == code)
+        })
+        .filter(|r| {
+            search.as_deref().is_none_or(|search| {
+                [
+                    &r.user.family_name.value,
+                    &r.user.given_name.value,
+                    &r.user.email.value,
+                ]
+                .iter()
+                .any(|value| value.to_lowercase().contains(search))
+            })
+        })
+        .map(|r| r.user.clone())
+        .collect::<Vec<_>>();
+    users.sort_by(|a, b| compare_users(a, b, filter.order));
+
+    filter.pagination.apply(users.into_iter()).collect()
+}
+
+/// ユーザーが、削除していない最後の管理者であるか確認する。
+fn is_last_admin(users: &HashMap<UserId, UserRecord>, user_id: UserId) -> bool {
+    let is_admin =
+        |r: &UserRecord| r.is_alive() && r.user.user_permission.code == UserPermissionCode::Admin;
+    users.get(&user_id).is_some_and(is_admin) && users.values().filter(|r| is_admin(r)).count() <= 1
+}
+
+/// PostgreSQLユーザーリポジトリの`ORDER BY`句と同じ順番になるように、ユーザーを比較する。
+fn compare_users(a: &User, b: &User, order: UserListOrder) -> Ordering {
+    let created = |a: &User, b: &User| (a.created_at, a.id.value).cmp(&(b.created_at, b.id.value));
+    let directed = |ordering: Ordering| match order.direction() {
+        SortDirection::Asc => ordering,
+        SortDirection::Desc => ordering.reverse(),
+    };
+    match order {
+        UserListOrder::CreatedAt(_) => directed(created(a, b)),
+        UserListOrder::Email(_) => directed(a.email.value.cmp(&b.email.value)),
+        // サインインしたことがないユーザーは、並べる方向に関わらず最後に並べる
+        UserListOrder::LastSignInAt(_) => match (a.last_sign_in_at, b.last_sign_in_at) {
+            (Some(x), Some(y)) => directed(x.cmp(&y)).then_with(|| created(a, b)),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => created(a, b),
+        },
+    }
+}
+
+/// ユーザー権限コードから、ユーザー権限を構築する。
+fn user_permission(code: UserPermissionCode) -> UserPermission {
+    let name = match code {
+        UserPermissionCode::Admin => ADMIN_PERMISSION_NAME,
+        UserPermissionCode::General => GENERAL_PERMISSION_NAME,
+    };
+
+    UserPermission::new(code, UserPermissionName::new(name).unwrap())
+}
+
+/// Eメールアドレスが、他のユーザーに登録されているか確認する。
+///
+/// 削除したユーザーのEメールアドレスは墓標に置き換えているため、削除したユーザーも対象にする。
+fn ensure_unique_email(
+    users: &HashMap<UserId, UserRecord>,
+    user_id: UserId,
+    email: &EmailAddress,
+) -> DomainResult<()> {
+    if users
+        .values()
+        .any(|r| r.user.id != user_id && r.user.email == *email)
+    {
+        return Err(DomainError::Repository(anyhow!(
+            "{}",
+            DUPLICATE_EMAIL_ERROR
+        )));
+    }
+
+    Ok(())
+}
+
+/// ユーザーを登録する。
+fn insert_user(
+    users: &mut HashMap<UserId, UserRecord>,
+    input: SignUpInput,
+) -> DomainResult<SignUpOutput> {
+    if users.contains_key(&input.id) {
+        return Err(DomainError::Repository(anyhow!("{}", DUPLICATE_ID_ERROR)));
+    }
+    ensure_unique_email(users, input.id, &input.email)?;
+
+    let password_change_required = input.password_change_required;
+    let now = OffsetDateTime::now_utc();
+    let user = User {
+        id: input.id,
+        email: input.email,
+        active: input.active,
+        user_permission: user_permission(input.user_permission_code),
+        family_name: input.family_name,
+        given_name: input.given_name,
+        postal_code: input.postal_code,
+        address: input.address,
+        fixed_phone_number: input.fixed_phone_number,
+        mobile_phone_number: input.mobile_phone_number,
+        remarks: input.remarks,
+        last_sign_in_at: None,
+        sign_in_attempted_at: None,
+        number_of_sign_in_failures: NumberOfSignInFailures::new(0).unwrap(),
+        created_at: now,
+        updated_at: now,
+        version: 1,
+    };
+    let output = SignUpOutput {
+        id: user.id,
+        email: user.email.clone(),
+        active: user.active,
+        user_permission_code: user.user_permission.code,
+        family_name: user.family_name.clone(),
+        given_name: user.given_name.clone(),
+        postal_code: user.postal_code.clone(),
+        address: user.address.clone(),
+        fixed_phone_number: user.fixed_phone_number.clone(),
+        mobile_phone_number: user.mobile_phone_number.clone(),
+        remarks: user.remarks.clone(),
+        created_at: user.created_at,
+        updated_at: user.updated_at,
+    };
+    users.insert(
+        user.id,
+        UserRecord {
+            user,
+            password: input.password,
+            locked_at: None,
+            email_verified_at: None,
+            password_change_required,
+            deleted_at: None,
+            last_failed_sign_in_attempt: SignInAttempt::default(),
+        },
+    );
+
+    Ok(output)
+}
+
+#[async_trait]
+impl UserRepository for InMemoryUserRepository {
+    async fn list(&self, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        Ok(list_users(&*self.users.read().await, None, filter))
+    }
+
+    async fn search(&self, search: &str, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        Ok(list_users(&*self.users.read().await, Some(search), filter))
+    }
+
+    /// ユーザーを1人ずつ取得するストリームを返す。
+    ///
+    /// ストリームを最初に読み込んだときに、絞り込み条件に一致するユーザーを取得する。
+    fn stream(&self, filter: UserListFilter) -> UserStream {
+        let users = self.users.clone();
+
+        Box::pin(UserListStream::Pending(Box::pin(async move {
+            list_users(&*users.read().await, None, filter)
+        })))
+    }
+
+    async fn by_id(&self, user_id: UserId) -> DomainResult<Option<User>> {
+        Ok(self
+            .users
+            .read()
+            .await
+            .get(&user_id)
+            .filter(|r| r.is_alive())
+            .map(|r| r.user.clone()))
+    }
+
+    async fn by_email(&self, email: EmailAddress) -> DomainResult<Option<User>> {
+        Ok(self
+            .users
+            .read()
+            .await
+            .values()
+            .find(|r| r.is_alive() && r.user.email == email)
+            .map(|r| r.user.clone()))
+    }
+
+    async fn user_credential(&self, email: EmailAddress) -> DomainResult<Option<UserCredential>> {
+        Ok(self
+            .users
+            .read()
+            .await
+            .values()
+            .find(|r| r.is_alive() && r.user.email == email)
+            .map(|r| r.credential()))
+    }
+
+    async fn credential_by_id(&self, user_id: UserId) -> DomainResult<Option<UserCredential>> {
+        Ok(self
+            .users
+            .read()
+            .await
+            .get(&user_id)
+            .filter(|r| r.is_alive())
+            .map(|r| r.credential()))
+    }
+
+    async fn update_last_sign_in(&self, user_id: UserId) -> DomainResult<Option<SignInRecord>> {
+        let mut users = self.users.write().await;
+        let Some(record) = users.get_mut(&user_id).filter(|r| r.is_alive()) else {
+            return Ok(None);
+        };
+        let previous_sign_in_at = record
+            .user
+            .last_sign_in_at
+            .replace(OffsetDateTime::now_utc());
+        record.user.sign_in_attempted_at = None;
+        record.set_number_of_sign_in_failures(0);
+
+        Ok(Some(SignInRecord {
+            credential: record.credential(),
+            previous_sign_in_at,
+        }))
+    }
+
+    async fn record_first_sign_in_failed(
+        &self,
+        user_id: UserId,
+        attempt: &SignInAttempt,
+    ) -> DomainResult<Option<UserCredential>> {
+        Ok(self
+            .update_credential(user_id, |r| {
+                r.user.sign_in_attempted_at = Some(OffsetDateTime::now_utc());
+                r.set_number_of_sign_in_failures(1);
+                r.last_failed_sign_in_attempt = attempt.clone();
+            })
+            .await)
+    }
+
+    async fn increment_number_of_sign_in_failures(
+        &self,
+        user_id: UserId,
+        attempt: &SignInAttempt,
+    ) -> DomainResult<Option<UserCredential>> {
+        Ok(self
+            .update_credential(user_id, |r| {
+                r.user.number_of_sign_in_failures =
+                    r.user.number_of_sign_in_failures.saturating_increment();
+                r.last_failed_sign_in_attempt = attempt.clone();
+            })
+            .await)
+    }
+
+    async fn lock_user_account(&self, user_id: UserId) -> DomainResult<()> {
+        self.update_credential(user_id, |r| {
+            r.user.active = false;
+            r.locked_at = Some(OffsetDateTime::now_utc());
+        })
+        .await;
+
+        Ok(())
+    }
+
+    async fn unlock_user_account(&self, user_id: UserId) -> DomainResult<()> {
+        if let Some(record) = self
+            .users
+            .write()
+            .await
+            .get_mut(&user_id)
+            .filter(|r| r.is_alive())
+        {
+            record.user.active = true;
+            record.locked_at = None;
+        }
+
+        Ok(())
+    }
+
+    async fn unlock_expired_accounts(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        let mut count = 0;
+        for record in self.users.write().await.values_mut() {
+            let expired = record.locked_at.is_some_and(|locked_at| locked_at < before);
+            if !record.user.active && expired && record.is_alive() {
+                record.user.active = true;
+                record.locked_at = None;
+                record.user.sign_in_attempted_at = None;
+                record.set_number_of_sign_in_failures(0);
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn list_locked(&self, pagination: Pagination) -> DomainResult<Vec<LockedUser>> {
+        let mut users = self
+            .users
+            .read()
+            .await
+            .values()
+            .filter(|r| !r.user.active && r.is_alive())
+            .filter_map(|r| {
+                r.locked_at.map(|locked_at| LockedUser {
+                    user: r.user.clone(),
+                    locked_at,
+                })
+            })
+            .collect::<Vec<_>>();
+        users.sort_by_key(|u| (u.locked_at, u.user.created_at, u.user.id.value));
+
+        Ok(pagination.apply(users.into_iter()).collect())
+    }
+
+    async fn clear_sign_in_failed_history(
+        &self,
+        user_id: UserId,
+    ) -> DomainResult<Option<UserCredential>> {
+        Ok(self
+            .update_credential(user_id, |r| {
+                r.user.sign_in_attempted_at = None;
+                r.set_number_of_sign_in_failures(0);
+            })
+            .await)
+    }
+
+    async fn verify_email(&self, user_id: UserId) -> DomainResult<bool> {
+        Ok(self
+            .update_credential(user_id, |r| {
+                r.email_verified_at = r.email_verified_at.or(Some(OffsetDateTime::now_utc()));
+            })
+            .await
+            .is_some())
+    }
+
+    async fn update_email(&self, user_id: UserId, email: EmailAddress) -> DomainResult<bool> {
+        let mut users = self.users.write().await;
+        if !users.get(&user_id).is_some_and(|r| r.is_alive()) {
+            return Ok(false);
+        }
+        ensure_unique_email(&users, user_id, &email)?;
+        let record = users.get_mut(&user_id).unwrap();
+        let now = OffsetDateTime::now_utc();
+        record.user.email = email;
+        record.email_verified_at = Some(now);
+        record.user.version += 1;
+        record.user.updated_at = now;
+
+        Ok(true)
+    }
+
+    async fn update_password(
+        &self,
+        user_id: UserId,
+        password: PhcPassword,
+        audit: AuditLogInput,
+    ) -> DomainResult<()> {
+        let mut users = self.users.write().await;
+        let Some(record) = users.get_mut(&user_id).filter(|r| r.is_alive()) else {
+            return Ok(());
+        };
+        record.password = password;
+        record.user.sign_in_attempted_at = None;
+        record.set_number_of_sign_in_failures(0);
+        record.password_change_required = false;
+        record.user.version += 1;
+        record.user.updated_at = OffsetDateTime::now_utc();
+        self.audit_logs.write().await.push(audit_log(audit));
+
+        Ok(())
+    }
+
+    async fn count_admins(&self) -> DomainResult<u64> {
+        Ok(self
+            .users
+            .read()
+            .await
+            .values()
+            .filter(|r| r.is_alive() && r.user.user_permission.code == UserPermissionCode::Admin)
+            .count() as u64)
+    }
+
+    async fn update_permission(
+        &self,
+        user_id: UserId,
+        user_permission_code: UserPermissionCode,
+        audit: AuditLogInput,
+    ) -> DomainResult<GuardedChange> {
+        let mut users = self.users.write().await;
+        if user_permission_code != UserPermissionCode::Admin && is_last_admin(&users, user_id) {
+            return Ok(GuardedChange::LastAdmin);
+        }
+        let record = match users.get_mut(&user_id).filter(|r| r.is_alive()) {
+            Some(record) => record,
+            None => return Ok(GuardedChange::NotFound),
+        };
+        record.user.user_permission = user_permission(user_permission_code);
+        record.user.version += 1;
+        record.user.updated_at = OffsetDateTime::now_utc();
+        self.audit_logs.write().await.push(audit_log(audit));
+
+        Ok(GuardedChange::Changed)
+    }
+
+    async fn update_profile(
+        &self,
+        user_id: UserId,
+        version: i32,
+        profile: UpdateProfileInput,
+    ) -> DomainResult<Option<User>> {
+        let mut users = self.users.write().await;
+        let record = match users
+            .get_mut(&user_id)
+            .filter(|r| r.is_alive() && r.user.version == version)
+        {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        let user = &mut record.user;
+        user.family_name = profile.family_name;
+        user.given_name = profile.given_name;
+        user.postal_code = profile.postal_code;
+        user.address = profile.address;
+        user.fixed_phone_number = profile.fixed_phone_number;
+        user.mobile_phone_number = profile.mobile_phone_number;
+        user.remarks = profile.remarks;
+        user.version += 1;
+        user.updated_at = OffsetDateTime::now_utc();
+
+        Ok(Some(user.clone()))
+    }
+
+    /// ユーザーのアカウントを削除する。
+    ///
+    /// `User`は苗字などを必須とするため、個人情報はメモリに残るが、削除したユーザーは取得できない。
+    /// TOTPの秘密鍵は、このリポジトリで管理しないため削除しない。
+    async fn soft_delete(
+        &self,
+        user_id: UserId,
+        audit: Option<AuditLogInput>,
+    ) -> DomainResult<GuardedChange> {
+        let mut users = self.users.write().await;
+        if is_last_admin(&users, user_id) {
+            return Ok(GuardedChange::LastAdmin);
+        }
+        let record = match users.get_mut(&user_id).filter(|r| r.is_alive()) {
+            Some(record) => record,
+            None => return Ok(GuardedChange::NotFound),
+        };
+        let now = OffsetDateTime::now_utc();
+        record.user.email = EmailAddress::new(format!(
+            "{}{}{}",
+            DELETED_EMAIL_PREFIX, user_id.value, DELETED_EMAIL_SUFFIX
+        ))
+        .unwrap();
+        record.user.active = false;
+        record.locked_at = None;
+        record.email_verified_at = None;
+        record.deleted_at = Some(now);
+        record.user.updated_at = now;
+        if let Some(audit) = audit {
+            self.audit_logs.write().await.push(audit_log(audit));
+        }
+
+        Ok(GuardedChange::Changed)
+    }
+
+    async fn purge_sign_in_history_older_than(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        let mut count = 0;
+        for record in self.users.write().await.values_mut() {
+            let expired = record
+                .user
+                .sign_in_attempted_at
+                .is_some_and(|attempted_at| attempted_at < before);
+            if expired && record.locked_at.is_none() {
+                record.user.sign_in_attempted_at = None;
+                record.set_number_of_sign_in_failures(0);
+                record.last_failed_sign_in_attempt = SignInAttempt::default();
+                count += 1;
+            }
+        }
+
+        Ok(count)
+    }
+
+    async fn purge_deleted_before(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        let mut users = self.users.write().await;
+        let count = users.len();
+        users.retain(|_, r| r.deleted_at.is_none_or(|deleted_at| before <= deleted_at));
+
+        Ok((count - users.len()) as u64)
+    }
+
+    async fn create(&self, user: SignUpInput) -> DomainResult<SignUpOutput> {
+        insert_user(&mut *self.users.write().await, user)
+    }
+
+    /// 複数のユーザーを登録する。
+    ///
+    /// ユーザーを複製したマップに登録して、`atomic`が`true`で登録に失敗したユーザーがいる場合は、
+    /// 複製したマップと、登録したユーザーの監査ログを破棄する。
+    async fn create_many(
+        &self,
+        users: Vec<SignUpInput>,
+        atomic: bool,
+        audit: AuditLogInput,
+    ) -> DomainResult<Vec<DomainResult<SignUpOutput>>> {
+        let mut guard = self.users.write().await;
+        let mut staged = guard.clone();
+        let results = users
+            .into_iter()
+            .map(|user| insert_user(&mut staged, user))
+            .collect::<Vec<_>>();
+        if !(atomic && results.iter().any(|result| result.is_err())) {
+            *guard = staged;
+            let mut audit_logs = self.audit_logs.write().await;
+            for user in results.iter().flatten() {
+                audit_logs.push(audit_log(AuditLogInput {
+                    target_user_id: Some(user.id),
+                    ..audit.clone()
+                }));
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+/// 絞り込み条件に一致するユーザーを1人ずつ返すストリーム
+enum UserListStream {
+    /// ユーザーを取得している
+    Pending(Pin<Box<dyn Future<Output = Vec<User>> + Send>>),
+    /// 取得したユーザーを返している
+    Ready(std::vec::IntoIter<User>),
+}
+
+impl Stream for UserListStream {
+    type Item = DomainResult<User>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if let UserListStream::Pending(future) = this {
+            match future.as_mut().poll(cx) {
+                Poll::Ready(users) => *this = UserListStream::Ready(users.into_iter()),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        match this {
+            UserListStream::Ready(users) => Poll::Ready(users.next().map(Ok)),
+            UserListStream::Pending(_) => unreachable!(),
+        }
+    }
+}
+
+const ADMIN_PERMISSION_NAME: &str = "管理者";
+const GENERAL_PERMISSION_NAME: &str = "一般";
+
+/// 削除したユーザーのEメールアドレスの墓標のプレフィックス
+const DELETED_EMAIL_PREFIX: &str = "deleted-";
+/// 削除したユーザーのEメールアドレスの墓標のサフィックス
+const DELETED_EMAIL_SUFFIX: &str = "@deleted.invalid";
+
+/// PostgreSQLの一意制約違反と同様に、制約の名前を含める
+const DUPLICATE_EMAIL_ERROR: &str =
+    r#"duplicate key value violates unique constraint "ak_users_email""#;
+const DUPLICATE_ID_ERROR: &str = r#"duplicate key value violates unique constraint "pk_users""#;
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+    use time::Duration;
+
+    use super::*;
+    use crate::models::audit_log::AuditAction;
+    use crate::repositories::audit_log::{AuditLogFilter, AuditLogRepository as _};
+    use crate::repositories::user::SignUpInputBuilder;
+
+    /// PHC文字列
+    /// cspell: disable-next-line
+    const RAW_PHC_PASSWORD: &str = "$argon2id$v=19$m=65536,t=2,p=1$gZiV/M1gPc22ElAH/Jh1Hw$CWOrkoo7oJBQ/iyh7uJ0LO2aLEfrHwTWllSAxT0zRno";
+
+    fn sign_up_input(email: &str) -> SignUpInput {
+        SignUpInputBuilder::new()
+            .id(UserId::default())
+            .email(EmailAddress::new(email).unwrap())
+            .password(PhcPassword::new(SecretString::new(RAW_PHC_PASSWORD.into())).unwrap())
+            .active(true)
+            .user_permission_code(UserPermissionCode::General)
+            .family_name(FamilyName::new("foo").unwrap())
+            .given_name(GivenName::new("bar").unwrap())
+            .postal_code(PostalCode::new("012-3456").unwrap())
+            .address(Address::new("foo bar baz qux").unwrap())
+            .fixed_phone_number(OptionalFixedPhoneNumber::try_from("03-1234-5678").unwrap())
+            .build()
+            .unwrap()
+    }
+
+    fn import_audit(actor_user_id: UserId) -> AuditLogInput {
+        AuditLogInput {
+            actor_user_id,
+            action: AuditAction::ImportUser,
+            target_user_id: None,
+            request_id: None,
+            details: serde_json::json!({}),
+        }
+    }
+
+    /// サインインの失敗の記録、インクリメント及び消去が、クエリと同じ列を更新することを確認
+    #[tokio::test]
+    async fn sign_in_failures_are_recorded_incremented_and_cleared() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let user = repo.create(sign_up_input("foo@example.com")).await?;
+
+        let credential = repo
+            .record_first_sign_in_failed(user.id, &SignInAttempt::default())
+            .await?
+            .unwrap();
+        assert!(credential.attempted_at.is_some());
+        assert_eq!(1, credential.number_of_failures.value);
+
+        let incremented = repo
+            .increment_number_of_sign_in_failures(user.id, &SignInAttempt::default())
+            .await?
+            .unwrap();
+        assert_eq!(credential.attempted_at, incremented.attempted_at);
+        assert_eq!(2, incremented.number_of_failures.value);
+
+        // 最初のサインインの失敗として記録し直すと、失敗回数は1に戻る
+        let recorded = repo
+            .record_first_sign_in_failed(user.id, &SignInAttempt::default())
+            .await?
+            .unwrap();
+        assert_eq!(1, recorded.number_of_failures.value);
+
+        let cleared = repo.clear_sign_in_failed_history(user.id).await?.unwrap();
+        assert!(cleared.attempted_at.is_none());
+        assert_eq!(0, cleared.number_of_failures.value);
+        assert!(repo
+            .record_first_sign_in_failed(UserId::default(), &SignInAttempt::default())
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    /// ロックした日時が指定した日時より過去のアカウントのみアンロックすることを確認
+    #[tokio::test]
+    async fn unlock_expired_accounts_unlocks_only_expired_accounts() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let expired = repo.create(sign_up_input("foo@example.com")).await?;
+        let locked = repo.create(sign_up_input("bar@example.com")).await?;
+        for user_id in [expired.id, locked.id] {
+            repo.record_first_sign_in_failed(user_id, &SignInAttempt::default())
+                .await?;
+            repo.lock_user_account(user_id).await?;
+        }
+        let now = OffsetDateTime::now_utc();
+        repo.set_locked_at(expired.id, Some(now - Duration::hours(2)))
+            .await;
+
+        let count = repo
+            .unlock_expired_accounts(now - Duration::hours(1))
+            .await?;
+
+        assert_eq!(1, count);
+        let email = EmailAddress::new("foo@example.com")?;
+        let credential = repo.user_credential(email).await?.unwrap();
+        assert!(credential.active);
+        assert!(credential.locked_at.is_none());
+        assert_eq!(0, credential.number_of_failures.value);
+        let email = EmailAddress::new("bar@example.com")?;
+        let credential = repo.user_credential(email).await?.unwrap();
+        assert!(!credential.active);
+        assert_eq!(1, credential.number_of_failures.value);
+
+        Ok(())
+    }
+
+    /// 最初にサインインに失敗した日時が指定した日時より過去の、アカウントをロックしていない
+    /// ユーザーのサインインに失敗した履歴のみ消去することを確認
+    #[tokio::test]
+    async fn purge_sign_in_history_purges_only_expired_unlocked_history() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let expired = repo.create(sign_up_input("foo@example.com")).await?;
+        let recent = repo.create(sign_up_input("bar@example.com")).await?;
+        let locked = repo.create(sign_up_input("baz@example.com")).await?;
+        let attempt = SignInAttempt {
+            client_ip: Some("192.0.2.1".parse()?),
+            user_agent: Some(String::from("test-agent")),
+        };
+        for user_id in [expired.id, recent.id, locked.id] {
+            repo.record_first_sign_in_failed(user_id, &attempt).await?;
+        }
+        repo.lock_user_account(locked.id).await?;
+        let now = OffsetDateTime::now_utc();
+        for user_id in [expired.id, locked.id] {
+            repo.set_sign_in_attempted_at(user_id, Some(now - Duration::days(2)))
+                .await;
+        }
+
+        let count = repo
+            .purge_sign_in_history_older_than(now - Duration::days(1))
+            .await?;
+
+        assert_eq!(1, count);
+        let credential = repo.credential_by_id(expired.id).await?.unwrap();
+        assert!(credential.attempted_at.is_none());
+        assert_eq!(0, credential.number_of_failures.value);
+        assert_eq!(
+            Some(SignInAttempt::default()),
+            repo.last_failed_sign_in_attempt(expired.id).await
+        );
+        for user_id in [recent.id, locked.id] {
+            let credential = repo.credential_by_id(user_id).await?.unwrap();
+            assert!(credential.attempted_at.is_some());
+            assert_eq!(1, credential.number_of_failures.value);
+        }
+
+        Ok(())
+    }
+
+    /// 削除した日時が指定した日時より過去のユーザーのみ物理的に削除することを確認
+    #[tokio::test]
+    async fn purge_deleted_before_purges_only_expired_deleted_users() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let expired = repo.create(sign_up_input("foo@example.com")).await?;
+        let recent = repo.create(sign_up_input("bar@example.com")).await?;
+        let alive = repo.create(sign_up_input("baz@example.com")).await?;
+        for user_id in [expired.id, recent.id] {
+            repo.soft_delete(user_id, None).await?;
+        }
+        let now = OffsetDateTime::now_utc();
+        repo.set_deleted_at(expired.id, Some(now - Duration::days(2)))
+            .await;
+
+        let count = repo.purge_deleted_before(now - Duration::days(1)).await?;
+
+        assert_eq!(1, count);
+        let users = repo.users.read().await;
+        assert!(!users.contains_key(&expired.id));
+        assert!(users.contains_key(&recent.id));
+        assert!(users.contains_key(&alive.id));
+
+        Ok(())
+    }
+
+    /// 削除したユーザーは、最後にサインインした日時を更新しないことを確認
+    #[tokio::test]
+    async fn last_sign_in_of_deleted_user_is_not_updated() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let user = repo.create(sign_up_input("foo@example.com")).await?;
+        repo.soft_delete(user.id, None).await?;
+
+        assert!(repo.update_last_sign_in(user.id).await?.is_none());
+        let last_sign_in_at = repo.users.read().await[&user.id].user.last_sign_in_at;
+        assert!(last_sign_in_at.is_none());
+
+        Ok(())
+    }
+
+    /// 最後の管理者のアカウントは削除しないことを確認
+    #[tokio::test]
+    async fn last_admin_is_not_deleted() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let mut admins = vec![];
+        for email in ["foo@example.com", "bar@example.com"] {
+            let mut input = sign_up_input(email);
+            input.user_permission_code = UserPermissionCode::Admin;
+            admins.push(repo.create(input).await?);
+        }
+
+        assert_eq!(
+            GuardedChange::Changed,
+            repo.soft_delete(admins[0].id, None).await?
+        );
+        assert_eq!(
+            GuardedChange::LastAdmin,
+            repo.soft_delete(admins[1].id, None).await?
+        );
+        assert!(repo.by_id(admins[1].id).await?.is_some());
+        assert_eq!(1, repo.count_admins().await?);
+
+        Ok(())
+    }
+
+    /// 最後の管理者を管理者以外に変更しないことを確認
+    #[tokio::test]
+    async fn last_admin_is_not_demoted() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let mut input = sign_up_input("foo@example.com");
+        input.user_permission_code = UserPermissionCode::Admin;
+        let admin = repo.create(input).await?;
+        let audit = || import_audit(admin.id);
+
+        assert_eq!(
+            GuardedChange::LastAdmin,
+            repo.update_permission(admin.id, UserPermissionCode::General, audit())
+                .await?
+        );
+        assert_eq!(
+            GuardedChange::Changed,
+            repo.update_permission(admin.id, UserPermissionCode::Admin, audit())
+                .await?
+        );
+        assert_eq!(1, repo.count_admins().await?);
+
+        Ok(())
+    }
+
+    /// 削除したユーザーは、サインインの失敗を記録しないことを確認
+    #[tokio::test]
+    async fn sign_in_failures_of_deleted_user_are_not_recorded() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let user = repo.create(sign_up_input("foo@example.com")).await?;
+        repo.soft_delete(user.id, None).await?;
+
+        assert!(repo
+            .record_first_sign_in_failed(user.id, &SignInAttempt::default())
+            .await?
+            .is_none());
+        assert!(repo
+            .increment_number_of_sign_in_failures(user.id, &SignInAttempt::default())
+            .await?
+            .is_none());
+        assert!(repo.clear_sign_in_failed_history(user.id).await?.is_none());
+        let attempted_at = repo.users.read().await[&user.id].user.sign_in_attempted_at;
+        assert!(attempted_at.is_none());
+
+        Ok(())
+    }
+
+    /// アカウントをロックしたユーザーのみを、ロックした日時の順に取得できることを確認
+    #[tokio::test]
+    async fn list_locked_returns_only_locked_users_in_locked_order() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let first = repo.create(sign_up_input("foo@example.com")).await?;
+        let second = repo.create(sign_up_input("bar@example.com")).await?;
+        repo.create(sign_up_input("baz@example.com")).await?;
+        for user_id in [second.id, first.id] {
+            repo.record_first_sign_in_failed(user_id, &SignInAttempt::default())
+                .await?;
+            repo.lock_user_account(user_id).await?;
+        }
+        let now = OffsetDateTime::now_utc();
+        repo.set_locked_at(first.id, Some(now - Duration::hours(1)))
+            .await;
+
+        let locked = repo.list_locked(Pagination::default()).await?;
+
+        let ids = locked.iter().map(|u| u.user.id).collect::<Vec<_>>();
+        assert_eq!(vec![first.id, second.id], ids);
+        assert_eq!(1, locked[0].user.number_of_sign_in_failures.value);
+        let locked = repo.list_locked(Pagination::new(Some(1), 1)).await?;
+        assert_eq!(1, locked.len());
+        assert_eq!(second.id, locked[0].user.id);
+
+        Ok(())
+    }
+
+    /// 同じEメールアドレスのユーザーを登録できず、一括登録では`atomic`に従ってロールバックして、
+    /// 登録を確定したユーザーの監査ログのみを記録することを確認
+    #[tokio::test]
+    async fn duplicate_email_is_rejected_and_create_many_respects_atomic() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let admin = repo.create(sign_up_input("foo@example.com")).await?;
+
+        let e = repo
+            .create(sign_up_input("foo@example.com"))
+            .await
+            .err()
+            .unwrap();
+        assert!(e.to_string().contains("ak_users_email"));
+
+        let users = vec![
+            sign_up_input("bar@example.com"),
+            sign_up_input("foo@example.com"),
+        ];
+        let results = repo
+            .create_many(users.clone(), true, import_audit(admin.id))
+            .await?;
+        assert!(results[0].is_ok() && results[1].is_err());
+        assert_eq!(1, repo.list(UserListFilter::default()).await?.len());
+        let audit_log_repo = repo.audit_log_repository();
+        assert!(audit_log_repo
+            .list(AuditLogFilter::default())
+            .await?
+            .is_empty());
+
+        let results = repo
+            .create_many(users, false, import_audit(admin.id))
+            .await?;
+        assert!(results[0].is_ok() && results[1].is_err());
+        assert_eq!(2, repo.list(UserListFilter::default()).await?.len());
+        let audit_logs = audit_log_repo.list(AuditLogFilter::default()).await?;
+        assert_eq!(1, audit_logs.len());
+        assert_eq!(admin.id, audit_logs[0].actor_user_id);
+        assert_eq!(
+            Some(results[0].as_ref().unwrap().id),
+            audit_logs[0].target_user_id
+        );
+
+        Ok(())
+    }
+
+    /// 並び順に従って並べたユーザーのリストから、指定した範囲のユーザーを取得できることを確認
+    #[tokio::test]
+    async fn list_applies_order_and_pagination() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        for email in ["b@example.com", "c@example.com", "a@example.com"] {
+            repo.create(sign_up_input(email)).await?;
+        }
+        let c = repo
+            .by_email(EmailAddress::new("c@example.com")?)
+            .await?
+            .unwrap();
+        repo.update_last_sign_in(c.id).await?;
+        let emails =
+            |users: Vec<User>| users.into_iter().map(|u| u.email.value).collect::<Vec<_>>();
+
+        let filter = UserListFilter {
+            order: UserListOrder::Email(SortDirection::Desc),
+            pagination: Pagination::new(Some(2), 1),
+            ..Default::default()
+        };
+        assert_eq!(
+            vec!["b@example.com", "a@example.com"],
+            emails(repo.list(filter).await?)
+        );
+        // サインインしたことがないユーザーは、降順でも最後に並べる
+        let filter = UserListFilter {
+            order: UserListOrder::LastSignInAt(SortDirection::Desc),
+            ..Default::default()
+        };
+        assert_eq!(
+            vec!["c@example.com", "b@example.com", "a@example.com"],
+            emails(repo.list(filter).await?)
+        );
+
+        Ok(())
+    }
+
+    /// 削除したユーザーは取得できず、同じEメールアドレスで再度登録できることを確認
+    #[tokio::test]
+    async fn soft_deleted_user_is_hidden_and_email_is_released() -> anyhow::Result<()> {
+        let repo = InMemoryUserRepository::new();
+        let user = repo.create(sign_up_input("foo@example.com")).await?;
+
+        assert_eq!(
+            GuardedChange::Changed,
+            repo.soft_delete(user.id, None).await?
+        );
+
+        assert_eq!(
+            GuardedChange::NotFound,
+            repo.soft_delete(user.id, None).await?
+        );
+        assert!(repo.by_id(user.id).await?.is_none());
+        assert!(repo.by_email(user.email.clone()).await?.is_none());
+        assert!(repo.user_credential(user.email.clone()).await?.is_none());
+        assert!(repo.list(UserListFilter::default()).await?.is_empty());
+        assert!(repo.create(sign_up_input("foo@example.com")).await.is_ok());
+
+        Ok(())
+    }
+}
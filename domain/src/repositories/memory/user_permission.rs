@@ -0,0 +1,78 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::RwLock;
+
+use crate::models::user::{UserPermission, UserPermissionCode, UserPermissionName};
+use crate::repositories::user_permission::UserPermissionRepository;
+use crate::DomainResult;
+
+/// メモリユーザー権限リポジトリ
+///
+/// PostgreSQLに接続せずに、ユーザー権限をメモリに保存するユーザー権限リポジトリで、テストで使用する。
+/// 構築したリポジトリは、マイグレーションで`user_permissions`テーブルに登録するユーザー権限を持つ。
+///
+/// 複製したリポジトリは、同じユーザー権限を共有する。
+#[derive(Debug, Clone)]
+pub struct InMemoryUserPermissionRepository {
+    /// ユーザー権限コードの数値と、ユーザー権限
+    permissions: Arc<RwLock<BTreeMap<i16, UserPermission>>>,
+}
+
+impl Default for InMemoryUserPermissionRepository {
+    fn default() -> Self {
+        let permissions = [
+            (UserPermissionCode::Admin, ADMIN_PERMISSION_NAME),
+            (UserPermissionCode::General, GENERAL_PERMISSION_NAME),
+        ]
+        .into_iter()
+        .map(|(code, name)| {
+            let name = UserPermissionName::new(name).unwrap();
+            (code as i16, UserPermission::new(code, name))
+        })
+        .collect();
+
+        Self {
+            permissions: Arc::new(RwLock::new(permissions)),
+        }
+    }
+}
+
+impl InMemoryUserPermissionRepository {
+    /// メモリユーザー権限リポジトリを構築する。
+    ///
+    /// # 戻り値
+    ///
+    /// メモリユーザー権限リポジトリ
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// ユーザー権限名を変更する。
+    ///
+    /// # 引数
+    ///
+    /// * `code` - ユーザー権限コード
+    /// * `name` - 変更後のユーザー権限名
+    pub async fn rename(&self, code: UserPermissionCode, name: UserPermissionName) {
+        self.permissions
+            .write()
+            .await
+            .insert(code as i16, UserPermission::new(code, name));
+    }
+}
+
+#[async_trait]
+impl UserPermissionRepository for InMemoryUserPermissionRepository {
+    async fn list(&self) -> DomainResult<Vec<UserPermission>> {
+        Ok(self.permissions.read().await.values().cloned().collect())
+    }
+
+    async fn by_code(&self, code: UserPermissionCode) -> DomainResult<Option<UserPermission>> {
+        Ok(self.permissions.read().await.get(&(code as i16)).cloned())
+    }
+}
+
+const ADMIN_PERMISSION_NAME: &str = "管理者";
+const GENERAL_PERMISSION_NAME: &str = "一般";
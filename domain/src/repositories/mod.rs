@@ -1,2 +1,9 @@
+pub mod audit_log;
+pub mod device;
+pub mod idempotency;
+#[cfg(any(test, feature = "test-util"))]
+pub mod memory;
 pub mod token;
+pub mod totp;
 pub mod user;
+pub mod user_permission;
@@ -1,13 +1,17 @@
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use enum_display::EnumDisplay;
-use secrecy::SecretString;
+use secrecy::{ExposeSecret as _, SecretString};
+use time::OffsetDateTime;
 
+use crate::models::primitives::EmailAddress;
 use crate::models::user::{UserId, UserPermissionCode};
 use crate::{DomainError, DomainResult};
 
 /// トークンリポジトリ
 #[async_trait]
-pub trait TokenRepository: Sync + Send {
+pub trait TokenRepository: std::fmt::Debug + Sync + Send {
     /// アクセストークンとリフレッシュトークンを登録する。
     ///
     /// # 引数
@@ -24,6 +28,9 @@ pub trait TokenRepository: Sync + Send {
 
     /// トークンからユーザーIDとトークンの種類を取得する。
     ///
+    /// リクエストで送信されたトークンがアクセストークンとリフレッシュトークンのどちらであるかは、
+    /// 取得したトークンの種類で判断するため、トークンを型で区別しない。
+    ///
     /// # 引数
     ///
     /// * `token` - トークン
@@ -35,18 +42,454 @@ pub trait TokenRepository: Sync + Send {
         &self,
         token: &SecretString,
     ) -> DomainResult<Option<TokenContent>>;
+
+    /// Eメールアドレスを確認するトークンを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - Eメールアドレスを確認するユーザーのユーザーID
+    /// * `token` - Eメールアドレスを確認するトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_email_verification_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()>;
+
+    /// Eメールアドレスを確認するトークンを消費する。
+    ///
+    /// トークンは1度しか使用できないため、取得したトークンは削除する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - Eメールアドレスを確認するトークン
+    ///
+    /// # 戻り値
+    ///
+    /// Eメールアドレスを確認するユーザーのユーザーID、トークンが登録されていない場合、または
+    /// 有効期限が切れている場合は`None`
+    async fn consume_email_verification_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>>;
+
+    /// Eメールアドレスの変更を確定するトークンを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `email_change` - 保留しているEメールアドレスの変更
+    /// * `token` - Eメールアドレスの変更を確定するトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_email_change_token(
+        &self,
+        email_change: &PendingEmailChange,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()>;
+
+    /// Eメールアドレスの変更を確定するトークンを消費する。
+    ///
+    /// トークンは1度しか使用できないため、取得したトークンは削除する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - Eメールアドレスの変更を確定するトークン
+    ///
+    /// # 戻り値
+    ///
+    /// 保留しているEメールアドレスの変更、トークンが登録されていない場合、または有効期限が
+    /// 切れている場合は`None`
+    async fn consume_email_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<PendingEmailChange>>;
+
+    /// 2要素認証を待機しているサインインを示すトークンを登録する。
+    ///
+    /// ユーザーに発行したアクセストークンとリフレッシュトークンをすべて無効にした場合は、
+    /// 2要素認証を待機しているサインインも無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - サインインするユーザーのユーザーID
+    /// * `token` - 2要素認証を待機しているサインインを示すトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_mfa_pending_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()>;
+
+    /// 2要素認証を待機しているサインインを示すトークンを消費する。
+    ///
+    /// トークンは1度しか使用できないため、取得したトークンは削除する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - 2要素認証を待機しているサインインを示すトークン
+    ///
+    /// # 戻り値
+    ///
+    /// サインインするユーザーのユーザーID、トークンが登録されていない場合、または有効期限が
+    /// 切れている場合は`None`
+    async fn consume_mfa_pending_token(&self, token: &SecretString)
+        -> DomainResult<Option<UserId>>;
+
+    /// ユーザーの2要素認証を待機しているサインインを示すトークンをすべて無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    async fn revoke_mfa_pending_tokens(&self, user_id: UserId) -> DomainResult<()>;
+
+    /// パスワードの変更を要求されているユーザーが、パスワードを変更するためのトークンを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - パスワードを変更するユーザーのユーザーID
+    /// * `token` - パスワードを変更するためのトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_password_change_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()>;
+
+    /// パスワードを変更するためのトークンを消費する。
+    ///
+    /// トークンは1度しか使用できないため、取得したトークンは削除する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - パスワードを変更するためのトークン
+    ///
+    /// # 戻り値
+    ///
+    /// パスワードを変更するユーザーのユーザーID、トークンが登録されていない場合、または有効期限が
+    /// 切れている場合は`None`
+    async fn consume_password_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>>;
+
+    /// アクセストークンを発行したユーザーが、パスワードで再認証したことを登録する。
+    ///
+    /// ユーザーに発行したアクセストークンとリフレッシュトークンをすべて無効にした場合は、
+    /// 再認証したことも無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - 再認証したユーザーのユーザーID
+    /// * `access_token` - 再認証したユーザーのアクセストークン
+    /// * `ttl` - 再認証したことの生存期間（秒）
+    async fn register_reauthentication(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+        ttl: u64,
+    ) -> DomainResult<()>;
+
+    /// アクセストークンで再認証したユーザーのユーザーIDを取得する。
+    ///
+    /// 再認証したことは、生存期間が過ぎるまで何度でも取得できる。
+    ///
+    /// # 引数
+    ///
+    /// * `access_token` - アクセストークン
+    ///
+    /// # 戻り値
+    ///
+    /// 再認証したユーザーのユーザーID、再認証していない場合、または生存期間が過ぎている場合は`None`
+    async fn reauthenticated_user(
+        &self,
+        access_token: &AccessToken,
+    ) -> DomainResult<Option<UserId>>;
+
+    /// ユーザーがサインインしているセッションを取得する。
+    ///
+    /// セッションは、サインインで発行したアクセストークンとリフレッシュトークンの組で、
+    /// トークンそのものは含まない。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `current_access_token` - リクエストで送信されたアクセストークン
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーがサインインしているセッション
+    async fn user_sessions(
+        &self,
+        user_id: UserId,
+        current_access_token: &AccessToken,
+    ) -> DomainResult<Vec<ActiveSession>>;
+
+    /// ユーザーがサインインしているセッションのアクセストークンとリフレッシュトークンを無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `session_id` - セッションID
+    ///
+    /// # 戻り値
+    ///
+    /// セッションを無効にした場合は`true`、ユーザーのセッションが見つからない場合は`false`
+    async fn revoke_session(&self, user_id: UserId, session_id: &str) -> DomainResult<bool>;
+
+    /// ユーザーに発行したアクセストークンとリフレッシュトークンをすべて無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    async fn revoke_user_tokens(&self, user_id: UserId) -> DomainResult<()>;
+}
+
+/// 共有されたトークンリポジトリ
+///
+/// `Arc<dyn TokenRepository>`をトークンリポジトリとして、ユースケースに渡せるようにする。
+#[async_trait]
+impl<T: TokenRepository + ?Sized> TokenRepository for Arc<T> {
+    async fn register_token_pair<'a>(
+        &self,
+        user_id: UserId,
+        tokens: TokenPairWithTtl<'a>,
+        user_permission_code: UserPermissionCode,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_token_pair(user_id, tokens, user_permission_code)
+            .await
+    }
+
+    async fn retrieve_token_content(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<TokenContent>> {
+        (**self).retrieve_token_content(token).await
+    }
+
+    async fn register_email_verification_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_email_verification_token(user_id, token, ttl)
+            .await
+    }
+
+    async fn consume_email_verification_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        (**self).consume_email_verification_token(token).await
+    }
+
+    async fn register_email_change_token(
+        &self,
+        email_change: &PendingEmailChange,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_email_change_token(email_change, token, ttl)
+            .await
+    }
+
+    async fn consume_email_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<PendingEmailChange>> {
+        (**self).consume_email_change_token(token).await
+    }
+
+    async fn register_mfa_pending_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_mfa_pending_token(user_id, token, ttl)
+            .await
+    }
+
+    async fn consume_mfa_pending_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        (**self).consume_mfa_pending_token(token).await
+    }
+
+    async fn revoke_mfa_pending_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        (**self).revoke_mfa_pending_tokens(user_id).await
+    }
+
+    async fn register_password_change_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_password_change_token(user_id, token, ttl)
+            .await
+    }
+
+    async fn consume_password_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        (**self).consume_password_change_token(token).await
+    }
+
+    async fn register_reauthentication(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_reauthentication(user_id, access_token, ttl)
+            .await
+    }
+
+    async fn reauthenticated_user(
+        &self,
+        access_token: &AccessToken,
+    ) -> DomainResult<Option<UserId>> {
+        (**self).reauthenticated_user(access_token).await
+    }
+
+    async fn user_sessions(
+        &self,
+        user_id: UserId,
+        current_access_token: &AccessToken,
+    ) -> DomainResult<Vec<ActiveSession>> {
+        (**self).user_sessions(user_id, current_access_token).await
+    }
+
+    async fn revoke_session(&self, user_id: UserId, session_id: &str) -> DomainResult<bool> {
+        (**self).revoke_session(user_id, session_id).await
+    }
+
+    async fn revoke_user_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        (**self).revoke_user_tokens(user_id).await
+    }
+}
+
+/// 保留しているEメールアドレスの変更
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingEmailChange {
+    /// Eメールアドレスを変更するユーザーのユーザーID
+    pub user_id: UserId,
+    /// 変更後のEメールアドレス
+    pub email: EmailAddress,
 }
 
 /// アクセストークン及びリフレッシュトークンとそれぞれの生存期間
 pub struct TokenPairWithTtl<'a> {
     /// アクセストークン
-    pub access: &'a SecretString,
+    pub access: &'a AccessToken,
     /// アクセストークンの生存期間（秒）
     pub access_ttl: u64,
     /// リフレッシュトークン
-    pub refresh: &'a SecretString,
+    pub refresh: &'a RefreshToken,
     /// リフレッシュトークンの生存期間（秒）
     pub refresh_ttl: u64,
+    /// サインインしたデバイスを識別する文字列（`User-Agent`ヘッダの値）
+    pub device: Option<&'a str>,
+}
+
+/// アクセストークン
+///
+/// リフレッシュトークンと取り違えないように、アクセストークンを型で区別する。
+#[derive(Debug, Clone)]
+pub struct AccessToken(SecretString);
+
+impl AccessToken {
+    /// アクセストークンを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `value` - アクセストークンの文字列
+    ///
+    /// # 戻り値
+    ///
+    /// アクセストークン
+    pub fn new(value: SecretString) -> Self {
+        Self(value)
+    }
+
+    /// アクセストークンの文字列を返す。
+    pub fn expose(&self) -> &str {
+        self.0.expose_secret()
+    }
+
+    /// アクセストークンを秘密の文字列として返す。
+    pub fn secret(&self) -> &SecretString {
+        &self.0
+    }
+}
+
+/// リフレッシュトークン
+///
+/// アクセストークンと取り違えないように、リフレッシュトークンを型で区別する。
+#[derive(Debug, Clone)]
+pub struct RefreshToken(SecretString);
+
+impl RefreshToken {
+    /// リフレッシュトークンを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `value` - リフレッシュトークンの文字列
+    ///
+    /// # 戻り値
+    ///
+    /// リフレッシュトークン
+    pub fn new(value: SecretString) -> Self {
+        Self(value)
+    }
+
+    /// リフレッシュトークンの文字列を返す。
+    pub fn expose(&self) -> &str {
+        self.0.expose_secret()
+    }
+
+    /// リフレッシュトークンを秘密の文字列として返す。
+    pub fn secret(&self) -> &SecretString {
+        &self.0
+    }
+}
+
+/// ユーザーがサインインしているセッション
+///
+/// サインインで発行したアクセストークンとリフレッシュトークンの組を表現する。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveSession {
+    /// セッションID
+    pub id: String,
+    /// トークンを発行した日時
+    pub issued_at: OffsetDateTime,
+    /// 有効なトークンの種類と有効期限
+    pub tokens: Vec<SessionToken>,
+    /// サインインしたデバイスを識別する文字列
+    pub device: Option<String>,
+    /// リクエストで送信されたアクセストークンのセッションであるか示すフラグ
+    pub current: bool,
+}
+
+/// セッションの有効なトークン
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionToken {
+    /// トークンの種類
+    pub token_type: TokenType,
+    /// トークンの有効期限
+    pub expiration: OffsetDateTime,
 }
 
 /// トークンコンテンツ
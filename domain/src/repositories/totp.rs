@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use time::OffsetDateTime;
+
+use crate::models::user::UserId;
+use crate::DomainResult;
+
+/// TOTPリポジトリ
+///
+/// 2要素認証に使用する、ユーザーのTOTPの秘密鍵を記録する。
+#[async_trait]
+pub trait TotpRepository: std::fmt::Debug + Sync + Send {
+    /// ユーザーのTOTPの秘密鍵を取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーのTOTPの秘密鍵、登録されていない場合は`None`
+    async fn totp_secret(&self, user_id: UserId) -> DomainResult<Option<UserTotpSecret>>;
+
+    /// ユーザーのTOTPの秘密鍵を登録する。
+    ///
+    /// すでに秘密鍵が登録されている場合は、秘密鍵を置き換えて、2要素認証を無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `encrypted_secret` - 暗号化したTOTPの秘密鍵
+    async fn register_totp_secret(
+        &self,
+        user_id: UserId,
+        encrypted_secret: Vec<u8>,
+    ) -> DomainResult<()>;
+
+    /// ユーザーの2要素認証を有効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// 秘密鍵が登録されている場合は`true`、それ以外の場合は`false`
+    async fn enable_totp(&self, user_id: UserId) -> DomainResult<bool>;
+
+    /// ユーザーのTOTPの秘密鍵を削除して、2要素認証を無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// 秘密鍵が登録されていた場合は`true`、それ以外の場合は`false`
+    async fn delete_totp_secret(&self, user_id: UserId) -> DomainResult<bool>;
+
+    /// TOTPコードを使用したタイムステップを記録する。
+    ///
+    /// 同じTOTPコードを再利用されないように、最後に使用したタイムステップより後のタイムステップ
+    /// の場合のみ記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `step` - TOTPコードが一致したタイムステップ
+    ///
+    /// # 戻り値
+    ///
+    /// 記録した場合は`true`、最後に使用したタイムステップ以前のタイムステップの場合は`false`
+    async fn record_used_totp_step(&self, user_id: UserId, step: u64) -> DomainResult<bool>;
+}
+
+/// 共有されたTOTPリポジトリ
+///
+/// `Arc<dyn TotpRepository>`をTOTPリポジトリとして、ユースケースに渡せるようにする。
+#[async_trait]
+impl<T: TotpRepository + ?Sized> TotpRepository for Arc<T> {
+    async fn totp_secret(&self, user_id: UserId) -> DomainResult<Option<UserTotpSecret>> {
+        (**self).totp_secret(user_id).await
+    }
+
+    async fn register_totp_secret(
+        &self,
+        user_id: UserId,
+        encrypted_secret: Vec<u8>,
+    ) -> DomainResult<()> {
+        (**self)
+            .register_totp_secret(user_id, encrypted_secret)
+            .await
+    }
+
+    async fn enable_totp(&self, user_id: UserId) -> DomainResult<bool> {
+        (**self).enable_totp(user_id).await
+    }
+
+    async fn delete_totp_secret(&self, user_id: UserId) -> DomainResult<bool> {
+        (**self).delete_totp_secret(user_id).await
+    }
+
+    async fn record_used_totp_step(&self, user_id: UserId, step: u64) -> DomainResult<bool> {
+        (**self).record_used_totp_step(user_id, step).await
+    }
+}
+
+/// ユーザーのTOTPの秘密鍵
+#[derive(Debug, Clone)]
+pub struct UserTotpSecret {
+    /// ユーザーID
+    pub user_id: UserId,
+    /// 暗号化したTOTPの秘密鍵
+    pub encrypted_secret: Vec<u8>,
+    /// 2要素認証を有効にした日時
+    pub enabled_at: Option<OffsetDateTime>,
+    /// 最後にTOTPコードを使用したタイムステップ
+    pub last_used_step: Option<u64>,
+}
+
+impl UserTotpSecret {
+    /// 2要素認証が有効か確認する。
+    ///
+    /// # 戻り値
+    ///
+    /// 2要素認証が有効な場合は`true`、それ以外の場合は`false`
+    pub fn is_enabled(&self) -> bool {
+        self.enabled_at.is_some()
+    }
+}
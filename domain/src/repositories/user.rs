@@ -1,20 +1,60 @@
+use std::net::IpAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
 use async_trait::async_trait;
+use futures_core::Stream;
 use macros::Builder;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
 
 use crate::models::primitives::*;
+use crate::models::query::{Pagination, SortDirection};
 use crate::models::user::{User, UserId, UserPermissionCode, UserValidator};
-use crate::DomainResult;
+use crate::repositories::audit_log::AuditLogInput;
+use crate::{DomainError, DomainResult};
 
 /// ユーザーリポジトリ
 #[async_trait]
-pub trait UserRepository: Sync + Send {
+pub trait UserRepository: std::fmt::Debug + Sync + Send {
     /// ユーザーのリストを取得する。
     ///
+    /// # 引数
+    ///
+    /// * `filter` - ユーザーリストの絞り込み条件
+    ///
     /// # 戻り値
     ///
     /// ユーザーを格納したベクタ
-    async fn list(&self) -> DomainResult<Vec<User>>;
+    async fn list(&self, filter: UserListFilter) -> DomainResult<Vec<User>>;
+
+    /// ユーザーを検索する。
+    ///
+    /// 苗字、名前またはEメールアドレスに、検索文字列を大文字と小文字を区別せずに含むユーザーを、
+    /// 絞り込み条件、並び順及びページに従って取得する。検索文字列に含まれる`%`や`_`などの文字は、
+    /// ワイルドカードとして扱わない。
+    ///
+    /// # 引数
+    ///
+    /// * `search` - 検索文字列
+    /// * `filter` - ユーザーリストの絞り込み条件
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーを格納したベクタ
+    async fn search(&self, search: &str, filter: UserListFilter) -> DomainResult<Vec<User>>;
+
+    /// ユーザーを1人ずつ取得するストリームを返す。
+    ///
+    /// すべてのユーザーをメモリに読み込まないように、ユーザーはストリームを読み込むたびに取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `filter` - ユーザーリストの絞り込み条件
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーを1人ずつ返すストリーム
+    fn stream(&self, filter: UserListFilter) -> UserStream;
 
     /// ユーザーを取得する。
     ///
@@ -23,6 +63,17 @@ pub trait UserRepository: Sync + Send {
     /// ユーザー
     async fn by_id(&self, user_id: UserId) -> DomainResult<Option<User>>;
 
+    /// Eメールアドレスからユーザーを取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `email` - ユーザーのEメールアドレス
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー
+    async fn by_email(&self, email: EmailAddress) -> DomainResult<Option<User>>;
+
     /// ユーザーのクレデンシャルを取得する。
     ///
     /// # 引数
@@ -34,49 +85,71 @@ pub trait UserRepository: Sync + Send {
     /// ユーザーのクレデンシャル
     async fn user_credential(&self, email: EmailAddress) -> DomainResult<Option<UserCredential>>;
 
+    /// ユーザーIDでユーザーのクレデンシャルを取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーのクレデンシャル
+    async fn credential_by_id(&self, user_id: UserId) -> DomainResult<Option<UserCredential>>;
+
     /// ユーザが最後にサインインした日時を更新する。
     ///
     /// サインインした日時を現在の日時、最初にサインインに失敗した日時をNULL、そしてサインイン失敗回数を0にする。
+    /// 同時にサインインした場合でも、それぞれのサインインが直前のサインインの日時を取得できるように、
+    /// 更新する前の日時の取得と更新は不可分に実行する。
     ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
     ///
-    /// # ユーザーが最後にサインインした日時
-    async fn update_last_sign_in(&self, user_id: UserId) -> DomainResult<Option<UserCredential>>;
+    /// # 戻り値
+    ///
+    /// 更新後のユーザーのクレデンシャルと、更新する前にユーザーが最後にサインインした日時、
+    /// ユーザーが存在しない場合、または削除されている場合は`None`
+    async fn update_last_sign_in(&self, user_id: UserId) -> DomainResult<Option<SignInRecord>>;
 
-    /// 最初にサインインに失敗した日時を保存する。
+    /// 最初にサインインに失敗した日時と、サインインに失敗したアクセス元を保存する。
     ///
     /// サインインに失敗した回数は1になる。
     ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
+    /// * `attempt` - サインインに失敗したアクセス元
     ///
     /// # 戻り値
     ///
-    /// ユーザーのクレデンシャル
+    /// ユーザーのクレデンシャル、ユーザーが存在しない場合、または削除されている場合は`None`
     async fn record_first_sign_in_failed(
         &self,
         user_id: UserId,
+        attempt: &SignInAttempt,
     ) -> DomainResult<Option<UserCredential>>;
 
-    /// サインイン失敗回数をインクリメントする。
+    /// サインイン失敗回数をインクリメントして、サインインに失敗したアクセス元を保存する。
     ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
+    /// * `attempt` - サインインに失敗したアクセス元
     ///
     /// # 戻り値
     ///
-    /// ユーザーのクレデンシャル
+    /// ユーザーのクレデンシャル、ユーザーが存在しない場合、または削除されている場合は`None`
     async fn increment_number_of_sign_in_failures(
         &self,
         user_id: UserId,
+        attempt: &SignInAttempt,
     ) -> DomainResult<Option<UserCredential>>;
 
     /// ユーザーのアカウントをロックする。
     ///
+    /// アクティブフラグを`false`にして、アカウントをロックした日時を現在の日時にする。
+    ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
@@ -84,11 +157,40 @@ pub trait UserRepository: Sync + Send {
 
     /// ユーザーのアカウントをアンロックする。
     ///
+    /// アクティブフラグを`true`にして、アカウントをロックした日時をNULLにする。
+    ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
     async fn unlock_user_account(&self, user_id: UserId) -> DomainResult<()>;
 
+    /// ロックした日時が指定した日時よりも過去のアカウントをアンロックする。
+    ///
+    /// アンロックしたアカウントは、最初にサインインに失敗した日時をNULL、サインイン失敗回数を0にする。
+    ///
+    /// # 引数
+    ///
+    /// * `before` - この日時よりも前にロックしたアカウントをアンロックする
+    ///
+    /// # 戻り値
+    ///
+    /// アンロックしたアカウントの数
+    async fn unlock_expired_accounts(&self, before: OffsetDateTime) -> DomainResult<u64>;
+
+    /// アカウントをロックしたユーザーのリストを取得する。
+    ///
+    /// アクティブフラグが`false`で、アカウントをロックした日時が記録されている、削除していない
+    /// ユーザーを、アカウントをロックした日時の順に取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `pagination` - アカウントをロックした日時の順で取得するページ
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントをロックしたユーザーを格納したベクタ
+    async fn list_locked(&self, pagination: Pagination) -> DomainResult<Vec<LockedUser>>;
+
     /// 最初にサインインに失敗した日時をNULL、サインイン失敗回数を0にする。
     ///
     /// # 引数
@@ -97,12 +199,158 @@ pub trait UserRepository: Sync + Send {
     ///
     /// # 戻り値
     ///
-    /// ユーザーのクレデンシャル
+    /// ユーザーのクレデンシャル、ユーザーが存在しない場合、または削除されている場合は`None`
     async fn clear_sign_in_failed_history(
         &self,
         user_id: UserId,
     ) -> DomainResult<Option<UserCredential>>;
 
+    /// ユーザーのEメールアドレスを確認したことを記録する。
+    ///
+    /// Eメールアドレスを確認した日時が記録されていない場合のみ、現在の日時を記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーが存在する場合は`true`、それ以外の場合は`false`
+    async fn verify_email(&self, user_id: UserId) -> DomainResult<bool>;
+
+    /// ユーザーのEメールアドレスを変更する。
+    ///
+    /// 変更後のEメールアドレスは確認済みとして、現在の日時を確認した日時として記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `email` - 変更後のEメールアドレス
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーが存在する場合は`true`、それ以外の場合は`false`
+    async fn update_email(&self, user_id: UserId, email: EmailAddress) -> DomainResult<bool>;
+
+    /// ユーザーのパスワードを変更する。
+    ///
+    /// パスワードを変更するとともに、サインインに失敗した履歴と、パスワードの変更を要求していることを
+    /// 消去する。
+    /// パスワードを変更した場合は、同じトランザクションで監査ログを記録する。
+    /// ユーザーが存在しない、または削除されている場合は何もしない。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `password` - 変更後のパスワードをハッシュ化したPHC文字列
+    /// * `audit` - 記録する監査ログ
+    async fn update_password(
+        &self,
+        user_id: UserId,
+        password: PhcPassword,
+        audit: AuditLogInput,
+    ) -> DomainResult<()>;
+
+    /// 管理者権限を持つユーザーの数を取得する。
+    ///
+    /// アカウントがロックされているユーザーも数える。
+    ///
+    /// # 戻り値
+    ///
+    /// 管理者権限を持つユーザーの数
+    async fn count_admins(&self) -> DomainResult<u64>;
+
+    /// ユーザーのユーザー権限を変更する。
+    ///
+    /// ユーザー権限を変更した場合は、同じトランザクションで監査ログを記録する。
+    /// 管理者がいなくならないように、最後の管理者を管理者以外に変更しない。管理者の数の確認と
+    /// ユーザー権限の変更は不可分に実行する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `user_permission_code` - 変更後のユーザー権限コード
+    /// * `audit` - 記録する監査ログ
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限を変更した結果
+    async fn update_permission(
+        &self,
+        user_id: UserId,
+        user_permission_code: UserPermissionCode,
+        audit: AuditLogInput,
+    ) -> DomainResult<GuardedChange>;
+
+    /// ユーザーのプロフィールを更新する。
+    ///
+    /// ユーザーのバージョンが指定したバージョンと一致する場合のみ更新して、バージョンを1増やす。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `version` - 更新前のユーザーのバージョン
+    /// * `profile` - 更新後のプロフィール
+    ///
+    /// # 戻り値
+    ///
+    /// 更新したユーザー、ユーザーが存在しないかバージョンが一致しない場合は`None`
+    async fn update_profile(
+        &self,
+        user_id: UserId,
+        version: i32,
+        profile: UpdateProfileInput,
+    ) -> DomainResult<Option<User>>;
+
+    /// ユーザーのアカウントを削除して、個人情報を消去する。
+    ///
+    /// ユーザーのレコードは残すが、苗字、名前、郵便番号、住所、電話番号及び備考を消去して、
+    /// Eメールアドレスを一意な墓標に置き換える。削除したユーザーは、`list`、`by_id`、`by_email`
+    /// 及び`user_credential`で取得できない。
+    /// 監査ログを指定した場合は、アカウントを削除したときに、同じトランザクションで監査ログを記録する。
+    /// 管理者がいなくならないように、最後の管理者のアカウントは削除しない。管理者の数の確認と
+    /// アカウントの削除は不可分に実行する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `audit` - 記録する監査ログ、記録しない場合は`None`
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントを削除した結果
+    async fn soft_delete(
+        &self,
+        user_id: UserId,
+        audit: Option<AuditLogInput>,
+    ) -> DomainResult<GuardedChange>;
+
+    /// 最初にサインインに失敗した日時が指定した日時よりも過去のサインインに失敗した履歴を消去する。
+    ///
+    /// アカウントをロックしていないユーザーの、最初にサインインに失敗した日時をNULL、サインイン
+    /// 失敗回数を0にして、最後にサインインに失敗したアクセス元を消去する。アカウントをロックした
+    /// ユーザーの履歴は、アカウントをアンロックするときに消去するため、消去しない。
+    ///
+    /// # 引数
+    ///
+    /// * `before` - この日時よりも前に最初にサインインに失敗した履歴を消去する
+    ///
+    /// # 戻り値
+    ///
+    /// サインインに失敗した履歴を消去したユーザーの数
+    async fn purge_sign_in_history_older_than(&self, before: OffsetDateTime) -> DomainResult<u64>;
+
+    /// 削除した日時が指定した日時よりも過去のユーザーのレコードを物理的に削除する。
+    ///
+    /// # 引数
+    ///
+    /// * `before` - この日時よりも前に削除したユーザーのレコードを物理的に削除する
+    ///
+    /// # 戻り値
+    ///
+    /// 物理的に削除したユーザーの数
+    async fn purge_deleted_before(&self, before: OffsetDateTime) -> DomainResult<u64>;
+
     /// ユーザーを登録する。
     ///
     /// # 引数
@@ -113,8 +361,251 @@ pub trait UserRepository: Sync + Send {
     ///
     /// * 登録したユーザー
     async fn create(&self, user: SignUpInput) -> DomainResult<SignUpOutput>;
+
+    /// 複数のユーザーを1つのトランザクションで登録する。
+    ///
+    /// ユーザーごとにセーブポイントを設定するため、登録に失敗したユーザーがいても、他のユーザーの
+    /// 登録を継続する。`atomic`が`true`の場合は、1人でも登録に失敗したユーザーがいれば、
+    /// トランザクションをロールバックして、すべてのユーザーを登録しない。
+    /// 登録したユーザーごとに、操作の対象に登録したユーザーのユーザーIDを設定した監査ログを、
+    /// ユーザーと同じセーブポイントで記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `users` - 登録するユーザーのリスト
+    /// * `atomic` - 1人でも登録に失敗した場合に、すべてのユーザーを登録しないか示すフラグ
+    /// * `audit` - ユーザーごとに記録する監査ログ
+    ///
+    /// # 戻り値
+    ///
+    /// 引数と同じ順番で、ユーザーごとに登録したユーザーまたはエラーを格納したリスト
+    async fn create_many(
+        &self,
+        users: Vec<SignUpInput>,
+        atomic: bool,
+        audit: AuditLogInput,
+    ) -> DomainResult<Vec<DomainResult<SignUpOutput>>>;
 }
 
+/// 共有されたユーザーリポジトリ
+///
+/// `Arc<dyn UserRepository>`をユーザーリポジトリとして、ユースケースに渡せるようにする。
+#[async_trait]
+impl<T: UserRepository + ?Sized> UserRepository for Arc<T> {
+    async fn list(&self, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        (**self).list(filter).await
+    }
+
+    async fn search(&self, search: &str, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        (**self).search(search, filter).await
+    }
+
+    fn stream(&self, filter: UserListFilter) -> UserStream {
+        (**self).stream(filter)
+    }
+
+    async fn by_id(&self, user_id: UserId) -> DomainResult<Option<User>> {
+        (**self).by_id(user_id).await
+    }
+
+    async fn by_email(&self, email: EmailAddress) -> DomainResult<Option<User>> {
+        (**self).by_email(email).await
+    }
+
+    async fn user_credential(&self, email: EmailAddress) -> DomainResult<Option<UserCredential>> {
+        (**self).user_credential(email).await
+    }
+
+    async fn credential_by_id(&self, user_id: UserId) -> DomainResult<Option<UserCredential>> {
+        (**self).credential_by_id(user_id).await
+    }
+
+    async fn update_last_sign_in(&self, user_id: UserId) -> DomainResult<Option<SignInRecord>> {
+        (**self).update_last_sign_in(user_id).await
+    }
+
+    async fn record_first_sign_in_failed(
+        &self,
+        user_id: UserId,
+        attempt: &SignInAttempt,
+    ) -> DomainResult<Option<UserCredential>> {
+        (**self).record_first_sign_in_failed(user_id, attempt).await
+    }
+
+    async fn increment_number_of_sign_in_failures(
+        &self,
+        user_id: UserId,
+        attempt: &SignInAttempt,
+    ) -> DomainResult<Option<UserCredential>> {
+        (**self)
+            .increment_number_of_sign_in_failures(user_id, attempt)
+            .await
+    }
+
+    async fn lock_user_account(&self, user_id: UserId) -> DomainResult<()> {
+        (**self).lock_user_account(user_id).await
+    }
+
+    async fn unlock_user_account(&self, user_id: UserId) -> DomainResult<()> {
+        (**self).unlock_user_account(user_id).await
+    }
+
+    async fn unlock_expired_accounts(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        (**self).unlock_expired_accounts(before).await
+    }
+
+    async fn list_locked(&self, pagination: Pagination) -> DomainResult<Vec<LockedUser>> {
+        (**self).list_locked(pagination).await
+    }
+
+    async fn clear_sign_in_failed_history(
+        &self,
+        user_id: UserId,
+    ) -> DomainResult<Option<UserCredential>> {
+        (**self).clear_sign_in_failed_history(user_id).await
+    }
+
+    async fn verify_email(&self, user_id: UserId) -> DomainResult<bool> {
+        (**self).verify_email(user_id).await
+    }
+
+    async fn update_email(&self, user_id: UserId, email: EmailAddress) -> DomainResult<bool> {
+        (**self).update_email(user_id, email).await
+    }
+
+    async fn update_password(
+        &self,
+        user_id: UserId,
+        password: PhcPassword,
+        audit: AuditLogInput,
+    ) -> DomainResult<()> {
+        (**self).update_password(user_id, password, audit).await
+    }
+
+    async fn count_admins(&self) -> DomainResult<u64> {
+        (**self).count_admins().await
+    }
+
+    async fn update_permission(
+        &self,
+        user_id: UserId,
+        user_permission_code: UserPermissionCode,
+        audit: AuditLogInput,
+    ) -> DomainResult<GuardedChange> {
+        (**self)
+            .update_permission(user_id, user_permission_code, audit)
+            .await
+    }
+
+    async fn update_profile(
+        &self,
+        user_id: UserId,
+        version: i32,
+        profile: UpdateProfileInput,
+    ) -> DomainResult<Option<User>> {
+        (**self).update_profile(user_id, version, profile).await
+    }
+
+    async fn soft_delete(
+        &self,
+        user_id: UserId,
+        audit: Option<AuditLogInput>,
+    ) -> DomainResult<GuardedChange> {
+        (**self).soft_delete(user_id, audit).await
+    }
+
+    async fn purge_sign_in_history_older_than(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        (**self).purge_sign_in_history_older_than(before).await
+    }
+
+    async fn purge_deleted_before(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        (**self).purge_deleted_before(before).await
+    }
+
+    async fn create(&self, user: SignUpInput) -> DomainResult<SignUpOutput> {
+        (**self).create(user).await
+    }
+
+    async fn create_many(
+        &self,
+        users: Vec<SignUpInput>,
+        atomic: bool,
+        audit: AuditLogInput,
+    ) -> DomainResult<Vec<DomainResult<SignUpOutput>>> {
+        (**self).create_many(users, atomic, audit).await
+    }
+}
+
+/// ユーザーリストの絞り込み条件
+///
+/// `None`の条件では絞り込まない。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserListFilter {
+    /// アクティブフラグ
+    pub active: Option<bool>,
+    /// ユーザー権限コード
+    pub user_permission_code: Option<UserPermissionCode>,
+    /// 並び順
+    pub order: UserListOrder,
+    /// 並び順で取得するページ
+    pub pagination: Pagination,
+}
+
+/// ユーザーリストの並び順
+///
+/// 並び順が同じユーザーは、作成日時の順に並べる。最終サインイン日時で並べる場合、サインインしたことがない
+/// ユーザーは、並べる方向に関わらず最後に並べる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserListOrder {
+    /// 作成日時
+    CreatedAt(SortDirection),
+    /// Eメールアドレス
+    Email(SortDirection),
+    /// 最終サインイン日時
+    LastSignInAt(SortDirection),
+}
+
+impl Default for UserListOrder {
+    fn default() -> Self {
+        Self::CreatedAt(SortDirection::Asc)
+    }
+}
+
+impl UserListOrder {
+    /// 並べ替えるキーと方向から、ユーザーリストの並び順を構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `sort` - 並べ替えるキー（`email`、`createdAt`または`lastSignInAt`）
+    /// * `direction` - 並べ替える方向
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーリストの並び順
+    pub fn new(sort: &str, direction: SortDirection) -> DomainResult<Self> {
+        match sort {
+            "createdAt" => Ok(Self::CreatedAt(direction)),
+            "email" => Ok(Self::Email(direction)),
+            "lastSignInAt" => Ok(Self::LastSignInAt(direction)),
+            _ => Err(DomainError::Validation(
+                "並べ替えるキーは、email、createdAtまたはlastSignInAtです。".into(),
+            )),
+        }
+    }
+
+    /// 並べ替える方向を返す。
+    pub fn direction(&self) -> SortDirection {
+        match *self {
+            Self::CreatedAt(direction) | Self::Email(direction) | Self::LastSignInAt(direction) => {
+                direction
+            }
+        }
+    }
+}
+
+/// ユーザーを1人ずつ返すストリーム
+pub type UserStream = Pin<Box<dyn Stream<Item = DomainResult<User>> + Send>>;
+
 /// サインアップするユーザー
 #[derive(Debug, Clone, Builder)]
 #[builder_validation(func = "validate_user")]
@@ -138,11 +629,17 @@ pub struct SignUpInput {
     /// 住所
     pub address: Address,
     /// 固定電話番号
+    #[builder(default)]
     pub fixed_phone_number: OptionalFixedPhoneNumber,
     /// 携帯電話番号
+    #[builder(default)]
     pub mobile_phone_number: OptionalMobilePhoneNumber,
     /// 備考
+    #[builder(default)]
     pub remarks: OptionalRemarks,
+    /// 最初にサインインしたときにパスワードの変更を要求するか
+    #[builder(default)]
+    pub password_change_required: bool,
 }
 
 impl UserValidator for SignUpInput {
@@ -154,6 +651,38 @@ impl UserValidator for SignUpInput {
     }
 }
 
+/// 更新するユーザーのプロフィール
+#[derive(Debug, Clone, Builder)]
+#[builder_validation(func = "validate_user")]
+pub struct UpdateProfileInput {
+    /// 苗字
+    pub family_name: FamilyName,
+    /// 名前
+    pub given_name: GivenName,
+    /// 郵便番号
+    pub postal_code: PostalCode,
+    /// 住所
+    pub address: Address,
+    /// 固定電話番号
+    #[builder(default)]
+    pub fixed_phone_number: OptionalFixedPhoneNumber,
+    /// 携帯電話番号
+    #[builder(default)]
+    pub mobile_phone_number: OptionalMobilePhoneNumber,
+    /// 備考
+    #[builder(default)]
+    pub remarks: OptionalRemarks,
+}
+
+impl UserValidator for UpdateProfileInput {
+    fn fixed_phone_number(&self) -> &OptionalFixedPhoneNumber {
+        &self.fixed_phone_number
+    }
+    fn mobile_phone_number(&self) -> &OptionalMobilePhoneNumber {
+        &self.mobile_phone_number
+    }
+}
+
 /// サインアップしたユーザー
 pub struct SignUpOutput {
     /// ユーザーID
@@ -184,6 +713,35 @@ pub struct SignUpOutput {
     pub updated_at: OffsetDateTime,
 }
 
+/// ユーザーが最後にサインインした日時を更新した結果
+#[derive(Debug, Clone)]
+pub struct SignInRecord {
+    /// 更新後のユーザークレデンシャル
+    pub credential: UserCredential,
+    /// 更新する前に、ユーザーが最後にサインインした日時
+    pub previous_sign_in_at: Option<OffsetDateTime>,
+}
+
+/// アカウントをロックしたユーザー
+#[derive(Debug, Clone)]
+pub struct LockedUser {
+    /// ユーザー
+    pub user: User,
+    /// ユーザーのアカウントをロックした日時
+    pub locked_at: OffsetDateTime,
+}
+
+/// サインインを試行したアクセス元
+///
+/// HTTPリクエストを介さずにサインインする場合など、判定できない項目は`None`にする。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SignInAttempt {
+    /// アクセス元のIPアドレス
+    pub client_ip: Option<IpAddr>,
+    /// アクセス元のユーザーエージェント
+    pub user_agent: Option<String>,
+}
+
 /// ユーザークレデンシャル
 #[derive(Debug, Clone)]
 pub struct UserCredential {
@@ -200,5 +758,210 @@ pub struct UserCredential {
     /// ユーザーが最初にサインインの試行に失敗した日時
     pub attempted_at: Option<OffsetDateTime>,
     /// ユーザーが最初にサインインの試行に失敗した日時から、サインインに失敗した回数
-    pub number_of_failures: i16,
+    pub number_of_failures: NumberOfSignInFailures,
+    /// ユーザーのアカウントをロックした日時
+    pub locked_at: Option<OffsetDateTime>,
+    /// ユーザーのEメールアドレスを確認した日時
+    pub email_verified_at: Option<OffsetDateTime>,
+    /// ユーザーがパスワードを変更しなければならないかを示すフラグ
+    pub password_change_required: bool,
+}
+
+impl UserCredential {
+    /// サインインに失敗したときに、サインインに失敗した履歴に対して実施する処理を返す。
+    ///
+    /// 最初にサインインに失敗した日時が記録されていない場合、または最初にサインインに失敗した日時から
+    /// 失敗回数をカウントする期間を超えて経過している場合は、最初のサインインの失敗として記録する。
+    /// それ以外の場合は、サインイン失敗回数をインクリメントする。
+    /// 記録またはインクリメントした後の失敗回数が、アカウントをロックするまでの失敗回数に達した場合は、
+    /// アカウントをロックする。
+    ///
+    /// # 引数
+    ///
+    /// * `now` - 現在の日時
+    /// * `window` - サインインの失敗回数をカウントする期間
+    /// * `max_failures` - アカウントをロックするまでの失敗回数
+    ///
+    /// # 戻り値
+    ///
+    /// サインインに失敗した履歴に対して実施する処理
+    pub fn next_failure_action(
+        &self,
+        now: OffsetDateTime,
+        window: Duration,
+        max_failures: i16,
+    ) -> FailureAction {
+        let is_first = match self.attempted_at {
+            Some(attempted_at) => attempted_at + window < now,
+            None => true,
+        };
+        let number_of_failures = if is_first {
+            NumberOfSignInFailures::new(1).unwrap()
+        } else {
+            self.number_of_failures.saturating_increment()
+        };
+        let locks = max_failures <= number_of_failures.value;
+
+        match (is_first, locks) {
+            (true, false) => FailureAction::RecordFirst,
+            (true, true) => FailureAction::RecordFirstAndLock,
+            (false, false) => FailureAction::Increment,
+            (false, true) => FailureAction::IncrementAndLock,
+        }
+    }
+}
+
+/// 最後の管理者がいなくならないように保護した、ユーザーの変更の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GuardedChange {
+    /// ユーザーを変更した
+    Changed,
+    /// 削除していないユーザーが存在しないため、変更しなかった
+    NotFound,
+    /// ユーザーが最後の管理者であるため、変更しなかった
+    LastAdmin,
+}
+
+/// サインインに失敗した履歴に対して実施する処理
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureAction {
+    /// 最初のサインインの失敗として記録
+    RecordFirst,
+    /// 最初のサインインの失敗として記録して、アカウントをロック
+    ///
+    /// アカウントをロックするまでの失敗回数が1の場合のみ発生する。
+    RecordFirstAndLock,
+    /// サインイン失敗回数をインクリメント
+    Increment,
+    /// サインイン失敗回数をインクリメントして、アカウントをロック
+    IncrementAndLock,
+}
+
+impl FailureAction {
+    /// 最初のサインインの失敗として記録するか確認する。
+    ///
+    /// # 戻り値
+    ///
+    /// 最初のサインインの失敗として記録する場合は`true`、サインイン失敗回数をインクリメントする
+    /// 場合は`false`
+    pub fn records_first(&self) -> bool {
+        matches!(self, Self::RecordFirst | Self::RecordFirstAndLock)
+    }
+
+    /// アカウントをロックするか確認する。
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントをロックする場合は`true`
+    pub fn locks(&self) -> bool {
+        matches!(self, Self::RecordFirstAndLock | Self::IncrementAndLock)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+
+    use super::*;
+
+    const RAW_PHC_PASSWORD: &str = "$argon2id$v=19$m=65536,t=2,p=1$gZiV/M1gPc22ElAH/Jh1Hw$CWOrkoo7oJBQ/iyh7uJ0LO2aLEfrHwTWllSAxT0zRno";
+
+    /// サインインの失敗回数をカウントする期間
+    const WINDOW: Duration = Duration::seconds(300);
+
+    /// アカウントをロックするまでの失敗回数
+    const MAX_FAILURES: i16 = 3;
+
+    /// サインインに失敗した履歴を指定して、ユーザークレデンシャルを構築する。
+    fn credential(attempted_at: Option<OffsetDateTime>, number_of_failures: i16) -> UserCredential {
+        UserCredential {
+            user_id: UserId::default(),
+            email: EmailAddress::new("foo@example.com").unwrap(),
+            password: PhcPassword::new(SecretString::new(String::from(RAW_PHC_PASSWORD))).unwrap(),
+            active: true,
+            user_permission_code: UserPermissionCode::General,
+            attempted_at,
+            number_of_failures: NumberOfSignInFailures::new(number_of_failures).unwrap(),
+            locked_at: None,
+            email_verified_at: None,
+            password_change_required: false,
+        }
+    }
+
+    /// サインインに失敗した履歴がない場合は、最初のサインインの失敗として記録することを確認
+    #[test]
+    fn first_failure_is_recorded_when_no_history() {
+        let now = OffsetDateTime::now_utc();
+
+        let action = credential(None, 0).next_failure_action(now, WINDOW, MAX_FAILURES);
+
+        assert_eq!(FailureAction::RecordFirst, action);
+        assert!(action.records_first());
+        assert!(!action.locks());
+    }
+
+    /// 失敗回数をカウントする期間内は、期間の終わりちょうどを含めてインクリメントすることを確認
+    #[test]
+    fn failure_within_window_is_incremented() {
+        let now = OffsetDateTime::now_utc();
+
+        for elapsed in [Duration::ZERO, WINDOW - Duration::nanoseconds(1), WINDOW] {
+            let action =
+                credential(Some(now - elapsed), 1).next_failure_action(now, WINDOW, MAX_FAILURES);
+            assert_eq!(FailureAction::Increment, action, "elapsed: {}", elapsed);
+        }
+    }
+
+    /// 失敗回数をカウントする期間を超えて経過した場合は、失敗回数にかかわらず最初のサインインの失敗
+    /// として記録し直すことを確認
+    #[test]
+    fn failure_after_window_is_recorded_as_first() {
+        let now = OffsetDateTime::now_utc();
+        let attempted_at = now - WINDOW - Duration::nanoseconds(1);
+
+        for number_of_failures in [1, 2, 3, 10] {
+            let action = credential(Some(attempted_at), number_of_failures).next_failure_action(
+                now,
+                WINDOW,
+                MAX_FAILURES,
+            );
+            assert_eq!(FailureAction::RecordFirst, action);
+        }
+    }
+
+    /// インクリメントした後の失敗回数が、アカウントをロックするまでの失敗回数ちょうどに達したときに
+    /// ロックすることを確認
+    #[test]
+    fn account_is_locked_when_failures_reach_threshold() {
+        let now = OffsetDateTime::now_utc();
+        let expected = [
+            (1, FailureAction::Increment),
+            (2, FailureAction::IncrementAndLock),
+            (3, FailureAction::IncrementAndLock),
+        ];
+
+        for (number_of_failures, action) in expected {
+            let credential = credential(Some(now), number_of_failures);
+            assert_eq!(
+                action,
+                credential.next_failure_action(now, WINDOW, MAX_FAILURES),
+                "number of failures: {}",
+                number_of_failures
+            );
+        }
+    }
+
+    /// アカウントをロックするまでの失敗回数が1の場合は、最初のサインインの失敗でロックすることを確認
+    #[test]
+    fn first_failure_locks_when_threshold_is_one() {
+        let now = OffsetDateTime::now_utc();
+
+        let action = credential(None, 0).next_failure_action(now, WINDOW, 1);
+
+        assert_eq!(FailureAction::RecordFirstAndLock, action);
+        assert!(action.records_first());
+        assert!(action.locks());
+        let action = credential(Some(now), 1).next_failure_action(now, WINDOW, 1);
+        assert_eq!(FailureAction::IncrementAndLock, action);
+    }
 }
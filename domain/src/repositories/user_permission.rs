@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::models::user::{UserPermission, UserPermissionCode};
+use crate::DomainResult;
+
+/// ユーザー権限リポジトリ
+#[async_trait]
+pub trait UserPermissionRepository: std::fmt::Debug + Sync + Send {
+    /// ユーザー権限をユーザー権限コードの順に取得する。
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限のリスト
+    async fn list(&self) -> DomainResult<Vec<UserPermission>>;
+
+    /// ユーザー権限コードからユーザー権限を取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `code` - ユーザー権限コード
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限、登録されていない場合は`None`
+    async fn by_code(&self, code: UserPermissionCode) -> DomainResult<Option<UserPermission>>;
+}
+
+/// 共有されたユーザー権限リポジトリ
+///
+/// `Arc<dyn UserPermissionRepository>`をユーザー権限リポジトリとして、ユースケースに渡せるようにする。
+#[async_trait]
+impl<T: UserPermissionRepository + ?Sized> UserPermissionRepository for Arc<T> {
+    async fn list(&self) -> DomainResult<Vec<UserPermission>> {
+        (**self).list().await
+    }
+
+    async fn by_code(&self, code: UserPermissionCode) -> DomainResult<Option<UserPermission>> {
+        (**self).by_code(code).await
+    }
+}
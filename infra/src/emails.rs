@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport as _, Message, Tokio1Executor};
+use secrecy::ExposeSecret as _;
+
+use configurations::settings::EmailSettings;
+use domain::models::primitives::EmailAddress;
+use use_cases::notifications::EmailSender;
+use use_cases::{UseCaseError, UseCaseResult};
+
+/// SMTPサーバーを介してEメールを送信するEメール送信者
+#[derive(Debug, Clone)]
+pub struct SmtpEmailSender {
+    /// SMTPトランスポート
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    /// 送信元
+    from: Mailbox,
+}
+
+impl SmtpEmailSender {
+    /// Eメール設定から、SMTPサーバーを介してEメールを送信するEメール送信者を構築する。
+    ///
+    /// SMTPサーバーには、Eメールを送信するときに接続する。
+    /// SMTPサーバーとの接続をプールするため、Tokioランタイム内で呼び出す必要がある。
+    ///
+    /// # 引数
+    ///
+    /// * `settings` - Eメール設定
+    ///
+    /// # 戻り値
+    ///
+    /// Eメール送信者
+    pub fn new(settings: &EmailSettings) -> anyhow::Result<Self> {
+        let mut builder = if settings.starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)?
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+        }
+        .port(settings.port);
+        if let (Some(username), Some(password)) = (&settings.username, &settings.password) {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                password.expose_secret().clone(),
+            ));
+        }
+        let from = settings
+            .from
+            .parse::<Mailbox>()
+            .map_err(|e| anyhow::anyhow!("{} {}", FROM_ERROR, e))?;
+
+        Ok(Self {
+            transport: builder.build(),
+            from,
+        })
+    }
+}
+
+#[async_trait]
+impl EmailSender for SmtpEmailSender {
+    /// Eメールを送信する。
+    ///
+    /// # 引数
+    ///
+    /// * `to` - 宛先のEメールアドレス
+    /// * `subject` - 件名
+    /// * `body` - 本文
+    async fn send(&self, to: EmailAddress, subject: String, body: String) -> UseCaseResult<()> {
+        let to = to.value.parse::<Mailbox>().map_err(|e| {
+            tracing::error!("{} {} ({}:{})", BUILD_ERROR, e, file!(), line!());
+            UseCaseError::unexpected(BUILD_ERROR)
+        })?;
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(body)
+            .map_err(|e| {
+                tracing::error!("{} {} ({}:{})", BUILD_ERROR, e, file!(), line!());
+                UseCaseError::unexpected(BUILD_ERROR)
+            })?;
+        self.transport.send(message).await.map_err(|e| {
+            tracing::error!("{} {} ({}:{})", SEND_ERROR, e, file!(), line!());
+            UseCaseError::unexpected(SEND_ERROR)
+        })?;
+
+        Ok(())
+    }
+}
+
+const FROM_ERROR: &str = "送信元のEメールアドレスが不正です。";
+const BUILD_ERROR: &str = "Eメールを構築するときにエラーが発生しました。";
+const SEND_ERROR: &str = "Eメールを送信するときにエラーが発生しました。";
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+
+    use super::*;
+
+    fn email_settings() -> EmailSettings {
+        EmailSettings {
+            host: String::from("localhost"),
+            port: 1025,
+            username: Some(String::from("awe")),
+            password: Some(SecretString::new(String::from("awe-pass"))),
+            from: String::from("Actix Web Example <no-reply@example.com>"),
+            starttls: false,
+        }
+    }
+
+    /// Eメール設定からEメール送信者を構築できることを確認
+    #[tokio::test]
+    async fn can_construct_smtp_email_sender() -> anyhow::Result<()> {
+        let sender = SmtpEmailSender::new(&email_settings())?;
+
+        assert_eq!("no-reply@example.com", sender.from.email.to_string());
+
+        Ok(())
+    }
+
+    /// 送信元のEメールアドレスが不正な場合は、Eメール送信者を構築できないことを確認
+    #[tokio::test]
+    async fn invalid_from_address_fails_to_construct_smtp_email_sender() {
+        let settings = EmailSettings {
+            from: String::from("no-reply"),
+            ..email_settings()
+        };
+
+        assert!(SmtpEmailSender::new(&settings).is_err());
+    }
+}
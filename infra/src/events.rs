@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use domain::events::{DomainEvent, EventHandler, EventPublisher};
+use domain::DomainResult;
+
+/// プロセス内イベント発行者
+///
+/// 発行されたドメインイベントを、登録した順番にイベントハンドラーに渡して、すべてのイベント
+/// ハンドラーの処理が完了するまで待機する。イベントハンドラーの処理はベストエフォートであるため、
+/// イベントハンドラーがエラーを返した場合は、エラーを記録して、次のイベントハンドラーに
+/// イベントを渡す。
+/// 時間がかかる処理をするイベントハンドラーは、ユースケースを待機させないように、バックグラウンドで
+/// 処理すること。
+#[derive(Debug, Clone, Default)]
+pub struct InProcessEventPublisher {
+    /// イベントハンドラー
+    handlers: Vec<Arc<dyn EventHandler>>,
+}
+
+impl InProcessEventPublisher {
+    /// イベントハンドラーを登録していないプロセス内イベント発行者を構築する。
+    ///
+    /// # 戻り値
+    ///
+    /// プロセス内イベント発行者
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// イベントハンドラーを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `handler` - イベントハンドラー
+    ///
+    /// # 戻り値
+    ///
+    /// プロセス内イベント発行者
+    pub fn with_handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+}
+
+#[async_trait]
+impl EventPublisher for InProcessEventPublisher {
+    async fn publish(&self, event: DomainEvent) {
+        for handler in self.handlers.iter() {
+            if let Err(e) = handler.handle(&event).await {
+                tracing::error!(
+                    event = event.name(),
+                    user.id = %event.user_id(),
+                    "{} ({}:{})",
+                    e,
+                    file!(),
+                    line!()
+                );
+            }
+        }
+    }
+}
+
+/// ドメインイベントを記録するイベントハンドラー
+///
+/// 発行されたドメインイベントの種類と、対象となったユーザーのユーザーIDを、情報レベルで記録する。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LoggingEventHandler;
+
+#[async_trait]
+impl EventHandler for LoggingEventHandler {
+    async fn handle(&self, event: &DomainEvent) -> DomainResult<()> {
+        tracing::info!(
+            event = event.name(),
+            user.id = %event.user_id(),
+            "domain event was published"
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use domain::models::user::UserId;
+    use domain::DomainError;
+
+    use super::*;
+
+    /// 処理したイベントを記録するイベントハンドラー
+    #[derive(Debug, Default)]
+    struct RecordingEventHandler {
+        events: Mutex<Vec<DomainEvent>>,
+    }
+
+    #[async_trait]
+    impl EventHandler for RecordingEventHandler {
+        async fn handle(&self, event: &DomainEvent) -> DomainResult<()> {
+            self.events.lock().unwrap().push(event.clone());
+
+            Ok(())
+        }
+    }
+
+    /// 常にエラーを返すイベントハンドラー
+    #[derive(Debug)]
+    struct FailingEventHandler;
+
+    #[async_trait]
+    impl EventHandler for FailingEventHandler {
+        async fn handle(&self, _event: &DomainEvent) -> DomainResult<()> {
+            Err(DomainError::Unexpected(anyhow::anyhow!("failed")))
+        }
+    }
+
+    /// 前のイベントハンドラーがエラーを返しても、登録したすべてのイベントハンドラーにイベントを
+    /// 渡すことを確認
+    #[tokio::test]
+    async fn publish_fans_out_to_all_handlers_even_if_handler_fails() {
+        let first = Arc::new(RecordingEventHandler::default());
+        let second = Arc::new(RecordingEventHandler::default());
+        let publisher = InProcessEventPublisher::new()
+            .with_handler(first.clone())
+            .with_handler(Arc::new(FailingEventHandler))
+            .with_handler(second.clone());
+        let event = DomainEvent::UserSignedIn {
+            user_id: UserId::default(),
+        };
+
+        publisher.publish(event.clone()).await;
+
+        assert_eq!(vec![event.clone()], *first.events.lock().unwrap());
+        assert_eq!(vec![event], *second.events.lock().unwrap());
+    }
+}
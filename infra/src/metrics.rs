@@ -0,0 +1,282 @@
+use deadpool_redis::Pool as RedisPool;
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+use sqlx::PgPool;
+
+use use_cases::metrics::SignInMetrics;
+
+/// メトリクス
+///
+/// Prometheusのテキスト形式で公開するメトリクスを保持する。
+/// クローンしたメトリクスは、同じメトリクスを共有する。
+#[derive(Clone)]
+pub struct Metrics {
+    /// メトリクスを登録したレジストリ
+    registry: Registry,
+    /// HTTPリクエスト数
+    http_requests_total: IntCounterVec,
+    /// HTTPリクエストの処理時間（秒）
+    http_request_duration_seconds: HistogramVec,
+    /// リポジトリのメソッドの処理時間（秒）
+    repository_call_duration_seconds: HistogramVec,
+    /// サインインの試行回数
+    sign_ins_total: IntCounterVec,
+    /// アカウントをロックした回数
+    account_lockouts_total: IntCounter,
+    /// PostgreSQL接続プールの接続数
+    pg_pool_connections: IntGauge,
+    /// PostgreSQL接続プールのアイドル状態の接続数
+    pg_pool_idle_connections: IntGauge,
+    /// Redis接続プールの最大接続数
+    redis_pool_max_connections: IntGauge,
+    /// Redis接続プールの接続数
+    redis_pool_connections: IntGauge,
+    /// Redis接続プールの利用可能な接続数
+    redis_pool_available_connections: IntGauge,
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Metrics {
+    /// メトリクスを構築する。
+    ///
+    /// # 戻り値
+    ///
+    /// メトリクス
+    pub fn new() -> Self {
+        let registry = Registry::new();
+        // メトリクスの名前とラベルは固定であるため、構築と登録に失敗することはない
+        let http_requests_total = IntCounterVec::new(
+            Opts::new("http_requests_total", "Number of HTTP requests"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latencies in seconds",
+            ),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let repository_call_duration_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "repository_call_duration_seconds",
+                "Repository call latencies in seconds",
+            ),
+            &["repository", "method", "outcome"],
+        )
+        .unwrap();
+        let sign_ins_total = IntCounterVec::new(
+            Opts::new("sign_ins_total", "Number of sign-in attempts"),
+            &["result"],
+        )
+        .unwrap();
+        let account_lockouts_total = IntCounter::new(
+            "account_lockouts_total",
+            "Number of user accounts locked due to sign-in failures",
+        )
+        .unwrap();
+        let pg_pool_connections = IntGauge::new(
+            "pg_pool_connections",
+            "Number of connections in the PostgreSQL connection pool",
+        )
+        .unwrap();
+        let pg_pool_idle_connections = IntGauge::new(
+            "pg_pool_idle_connections",
+            "Number of idle connections in the PostgreSQL connection pool",
+        )
+        .unwrap();
+        let redis_pool_max_connections = IntGauge::new(
+            "redis_pool_max_connections",
+            "Maximum number of connections in the Redis connection pool",
+        )
+        .unwrap();
+        let redis_pool_connections = IntGauge::new(
+            "redis_pool_connections",
+            "Number of connections in the Redis connection pool",
+        )
+        .unwrap();
+        let redis_pool_available_connections = IntGauge::new(
+            "redis_pool_available_connections",
+            "Number of available connections in the Redis connection pool",
+        )
+        .unwrap();
+
+        registry
+            .register(Box::new(http_requests_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(http_request_duration_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(repository_call_duration_seconds.clone()))
+            .unwrap();
+        registry.register(Box::new(sign_ins_total.clone())).unwrap();
+        registry
+            .register(Box::new(account_lockouts_total.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(pg_pool_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(pg_pool_idle_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(redis_pool_max_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(redis_pool_connections.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(redis_pool_available_connections.clone()))
+            .unwrap();
+
+        Self {
+            registry,
+            http_requests_total,
+            http_request_duration_seconds,
+            repository_call_duration_seconds,
+            sign_ins_total,
+            account_lockouts_total,
+            pg_pool_connections,
+            pg_pool_idle_connections,
+            redis_pool_max_connections,
+            redis_pool_connections,
+            redis_pool_available_connections,
+        }
+    }
+
+    /// HTTPリクエストを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `method` - HTTPメソッド
+    /// * `route` - リクエストにマッチしたルートのパターン
+    /// * `status` - HTTPステータスコード
+    /// * `seconds` - リクエストの処理時間（秒）
+    pub fn observe_http_request(&self, method: &str, route: &str, status: u16, seconds: f64) {
+        let status = status.to_string();
+        let labels = [method, route, status.as_str()];
+        self.http_requests_total.with_label_values(&labels).inc();
+        self.http_request_duration_seconds
+            .with_label_values(&labels)
+            .observe(seconds);
+    }
+
+    /// リポジトリのメソッドの呼び出しを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `repository` - リポジトリ名
+    /// * `method` - 呼び出したメソッド名
+    /// * `outcome` - 成否（`success`または`failure`）
+    /// * `seconds` - メソッドの処理時間（秒）
+    pub fn observe_repository_call(
+        &self,
+        repository: &str,
+        method: &str,
+        outcome: &str,
+        seconds: f64,
+    ) {
+        self.repository_call_duration_seconds
+            .with_label_values(&[repository, method, outcome])
+            .observe(seconds);
+    }
+
+    /// 接続プールの状態を記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `pg_pool` - PostgreSQL接続プール
+    /// * `redis_pool` - Redis接続プール
+    pub fn observe_pools(&self, pg_pool: &PgPool, redis_pool: &RedisPool) {
+        self.pg_pool_connections.set(pg_pool.size() as i64);
+        self.pg_pool_idle_connections.set(pg_pool.num_idle() as i64);
+        let status = redis_pool.status();
+        self.redis_pool_max_connections.set(status.max_size as i64);
+        self.redis_pool_connections.set(status.size as i64);
+        self.redis_pool_available_connections
+            .set(status.available as i64);
+    }
+
+    /// メトリクスをPrometheusのテキスト形式で出力する。
+    ///
+    /// # 戻り値
+    ///
+    /// Prometheusのテキスト形式で表現したメトリクス
+    pub fn render(&self) -> anyhow::Result<String> {
+        let encoder = TextEncoder::new();
+        encoder
+            .encode_to_string(&self.registry.gather())
+            .map_err(|e| e.into())
+    }
+}
+
+impl SignInMetrics for Metrics {
+    fn sign_in_succeeded(&self) {
+        self.sign_ins_total.with_label_values(&["success"]).inc();
+    }
+
+    fn sign_in_failed(&self) {
+        self.sign_ins_total.with_label_values(&["failure"]).inc();
+    }
+
+    fn account_locked(&self) {
+        self.account_lockouts_total.inc();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 記録したHTTPリクエストとサインインを、Prometheusのテキスト形式で出力できることを確認
+    #[test]
+    fn can_render_recorded_metrics() -> anyhow::Result<()> {
+        let metrics = Metrics::new();
+        metrics.observe_http_request("GET", "/health-check", 200, 0.01);
+        metrics.observe_http_request("GET", "/health-check", 200, 0.02);
+        metrics.sign_in_succeeded();
+        metrics.sign_in_failed();
+        metrics.sign_in_failed();
+        metrics.account_locked();
+
+        let text = metrics.render()?;
+        assert!(text
+            .contains(r#"http_requests_total{method="GET",route="/health-check",status="200"} 2"#));
+        assert!(text.contains(
+            r#"http_request_duration_seconds_count{method="GET",route="/health-check",status="200"} 2"#
+        ));
+        assert!(text.contains(r#"sign_ins_total{result="success"} 1"#));
+        assert!(text.contains(r#"sign_ins_total{result="failure"} 2"#));
+        assert!(text.contains("account_lockouts_total 1"));
+
+        Ok(())
+    }
+
+    /// クローンしたメトリクスが、同じメトリクスを共有することを確認
+    #[test]
+    fn cloned_metrics_share_the_same_counters() -> anyhow::Result<()> {
+        let metrics = Metrics::new();
+        let cloned = metrics.clone();
+        cloned.sign_in_failed();
+
+        assert!(metrics
+            .render()?
+            .contains(r#"sign_ins_total{result="failure"} 1"#));
+
+        Ok(())
+    }
+}
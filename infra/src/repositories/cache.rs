@@ -0,0 +1,168 @@
+use std::sync::{Arc, RwLock};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+
+use domain::models::user::{UserPermission, UserPermissionCode};
+use domain::repositories::user_permission::UserPermissionRepository;
+use domain::{DomainError, DomainResult};
+
+/// ユーザー権限のキャッシュ
+///
+/// `user_permissions`テーブルはほとんど変更されないため、アプリケーションの起動時に読み込んで
+/// リクエストコンテキストで共有する。複製したキャッシュは、同じユーザー権限を共有する。
+#[derive(Debug, Clone, Default)]
+pub struct UserPermissionCache {
+    /// ユーザー権限コードの順に並べたユーザー権限
+    permissions: Arc<RwLock<Vec<UserPermission>>>,
+}
+
+impl UserPermissionCache {
+    /// キャッシュしたユーザー権限を取得する。
+    fn permissions(&self) -> DomainResult<Vec<UserPermission>> {
+        self.permissions
+            .read()
+            .map(|permissions| permissions.clone())
+            .map_err(|_| DomainError::Unexpected(anyhow!("{}", LOCK_ERROR)))
+    }
+
+    /// キャッシュしたユーザー権限を置き換える。
+    fn replace(&self, permissions: Vec<UserPermission>) -> DomainResult<()> {
+        let mut cached = self
+            .permissions
+            .write()
+            .map_err(|_| DomainError::Unexpected(anyhow!("{}", LOCK_ERROR)))?;
+        *cached = permissions;
+
+        Ok(())
+    }
+}
+
+/// キャッシュしたユーザー権限リポジトリ
+///
+/// ユーザー権限をキャッシュから取得する。キャッシュが空の場合、またはキャッシュに存在しない
+/// ユーザー権限コードを指定した場合は、リポジトリからユーザー権限を読み込み直す。
+#[derive(Debug, Clone)]
+pub struct CachedUserPermissionRepository<R> {
+    /// ユーザー権限を読み込むリポジトリ
+    repository: R,
+    /// ユーザー権限のキャッシュ
+    cache: UserPermissionCache,
+}
+
+impl<R: UserPermissionRepository> CachedUserPermissionRepository<R> {
+    /// キャッシュしたユーザー権限リポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `repository` - ユーザー権限を読み込むリポジトリ
+    /// * `cache` - ユーザー権限のキャッシュ
+    ///
+    /// # 戻り値
+    ///
+    /// キャッシュしたユーザー権限リポジトリ
+    pub fn new(repository: R, cache: UserPermissionCache) -> Self {
+        Self { repository, cache }
+    }
+
+    /// リポジトリからユーザー権限を読み込んで、キャッシュを置き換える。
+    ///
+    /// # 戻り値
+    ///
+    /// 読み込んだユーザー権限のリスト
+    pub async fn refresh(&self) -> DomainResult<Vec<UserPermission>> {
+        let permissions = self.repository.list().await?;
+        self.cache.replace(permissions.clone())?;
+
+        Ok(permissions)
+    }
+}
+
+#[async_trait]
+impl<R: UserPermissionRepository> UserPermissionRepository for CachedUserPermissionRepository<R> {
+    /// ユーザー権限をユーザー権限コードの順に取得する。
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限のリスト
+    async fn list(&self) -> DomainResult<Vec<UserPermission>> {
+        let permissions = self.cache.permissions()?;
+        if !permissions.is_empty() {
+            return Ok(permissions);
+        }
+
+        self.refresh().await
+    }
+
+    /// ユーザー権限コードからユーザー権限を取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `code` - ユーザー権限コード
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限
+    async fn by_code(&self, code: UserPermissionCode) -> DomainResult<Option<UserPermission>> {
+        let find = |permissions: Vec<UserPermission>| {
+            permissions
+                .into_iter()
+                .find(|permission| permission.code == code)
+        };
+        if let Some(permission) = find(self.cache.permissions()?) {
+            return Ok(Some(permission));
+        }
+
+        Ok(find(self.refresh().await?))
+    }
+}
+
+const LOCK_ERROR: &str = "ユーザー権限のキャッシュをロックできませんでした。";
+
+#[cfg(test)]
+mod tests {
+    use domain::models::user::UserPermissionName;
+    use domain::repositories::memory::user_permission::InMemoryUserPermissionRepository;
+
+    use super::*;
+
+    /// ユーザー権限名を変更しても、キャッシュを読み込み直すまでは変更前の名前を返すことを確認
+    #[tokio::test]
+    async fn renamed_permission_is_reflected_after_refresh() -> anyhow::Result<()> {
+        let source = InMemoryUserPermissionRepository::new();
+        let repo = CachedUserPermissionRepository::new(source.clone(), Default::default());
+        let admin = repo.by_code(UserPermissionCode::Admin).await?.unwrap();
+        assert_eq!("管理者", admin.name.value);
+        assert_eq!(2, repo.list().await?.len());
+
+        source
+            .rename(
+                UserPermissionCode::Admin,
+                UserPermissionName::new("システム管理者")?,
+            )
+            .await;
+        let admin = repo.by_code(UserPermissionCode::Admin).await?.unwrap();
+        assert_eq!("管理者", admin.name.value);
+
+        repo.refresh().await?;
+        let admin = repo.by_code(UserPermissionCode::Admin).await?.unwrap();
+        assert_eq!("システム管理者", admin.name.value);
+
+        Ok(())
+    }
+
+    /// 複製したキャッシュは、同じユーザー権限を共有することを確認
+    #[tokio::test]
+    async fn cloned_cache_shares_permissions() -> anyhow::Result<()> {
+        let cache = UserPermissionCache::default();
+        let repo = CachedUserPermissionRepository::new(
+            InMemoryUserPermissionRepository::new(),
+            cache.clone(),
+        );
+        repo.refresh().await?;
+
+        assert_eq!(2, cache.permissions()?.len());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,834 @@
+use std::future::Future;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use secrecy::SecretString;
+use time::OffsetDateTime;
+use tracing::Instrument as _;
+
+use domain::models::primitives::*;
+use domain::models::query::Pagination;
+use domain::models::user::{User, UserId, UserPermissionCode};
+use domain::repositories::audit_log::AuditLogInput;
+use domain::repositories::token::{
+    AccessToken, ActiveSession, PendingEmailChange, TokenContent, TokenPairWithTtl, TokenRepository,
+};
+use domain::repositories::user::{
+    GuardedChange, LockedUser, SignInAttempt, SignInRecord, SignUpInput, SignUpOutput,
+    UpdateProfileInput, UserCredential, UserListFilter, UserRepository, UserStream,
+};
+use domain::DomainResult;
+
+use crate::metrics::Metrics;
+
+/// 計測するユーザーリポジトリ
+///
+/// ユーザーリポジトリの呼び出しをスパンで囲むデコレーターで、呼び出したメソッド名、処理時間及び
+/// 成否をスパンに記録する。メトリクスを設定した場合は、処理時間をメトリクスにも記録する。
+/// ラップしたユーザーリポジトリの処理は変更しない。
+#[derive(Debug, Clone)]
+pub struct InstrumentedUserRepository<R> {
+    /// ラップしたユーザーリポジトリ
+    repository: R,
+    /// リポジトリの処理時間を記録するメトリクス
+    metrics: Option<Metrics>,
+}
+
+impl<R: UserRepository> InstrumentedUserRepository<R> {
+    /// 計測するユーザーリポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `repository` - ラップするユーザーリポジトリ
+    ///
+    /// # 戻り値
+    ///
+    /// 計測するユーザーリポジトリ
+    pub fn new(repository: R) -> Self {
+        Self {
+            repository,
+            metrics: None,
+        }
+    }
+
+    /// リポジトリの処理時間を記録するメトリクスを設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `metrics` - メトリクス
+    ///
+    /// # 戻り値
+    ///
+    /// 計測するユーザーリポジトリ
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// ユーザーリポジトリのメソッドの呼び出しを計測する。
+    async fn call<T>(
+        &self,
+        method: &'static str,
+        call: impl Future<Output = DomainResult<T>>,
+    ) -> DomainResult<T> {
+        instrument(USER_REPOSITORY, method, self.metrics.as_ref(), call).await
+    }
+}
+
+#[async_trait]
+impl<R: UserRepository> UserRepository for InstrumentedUserRepository<R> {
+    async fn list(&self, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        self.call("list", self.repository.list(filter)).await
+    }
+
+    async fn search(&self, search: &str, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        self.call("search", self.repository.search(search, filter))
+            .await
+    }
+
+    /// ストリームはユーザーを取得する前に返されるため、ストリームを作成する処理のみをスパンで囲み、
+    /// 処理時間は記録しない。
+    fn stream(&self, filter: UserListFilter) -> UserStream {
+        tracing::info_span!(
+            "repository",
+            repository.name = USER_REPOSITORY,
+            repository.method = "stream"
+        )
+        .in_scope(|| self.repository.stream(filter))
+    }
+
+    async fn by_id(&self, user_id: UserId) -> DomainResult<Option<User>> {
+        self.call("by_id", self.repository.by_id(user_id)).await
+    }
+
+    async fn by_email(&self, email: EmailAddress) -> DomainResult<Option<User>> {
+        self.call("by_email", self.repository.by_email(email)).await
+    }
+
+    async fn user_credential(&self, email: EmailAddress) -> DomainResult<Option<UserCredential>> {
+        self.call("user_credential", self.repository.user_credential(email))
+            .await
+    }
+
+    async fn credential_by_id(&self, user_id: UserId) -> DomainResult<Option<UserCredential>> {
+        self.call(
+            "credential_by_id",
+            self.repository.credential_by_id(user_id),
+        )
+        .await
+    }
+
+    async fn update_last_sign_in(&self, user_id: UserId) -> DomainResult<Option<SignInRecord>> {
+        self.call(
+            "update_last_sign_in",
+            self.repository.update_last_sign_in(user_id),
+        )
+        .await
+    }
+
+    async fn record_first_sign_in_failed(
+        &self,
+        user_id: UserId,
+        attempt: &SignInAttempt,
+    ) -> DomainResult<Option<UserCredential>> {
+        self.call(
+            "record_first_sign_in_failed",
+            self.repository
+                .record_first_sign_in_failed(user_id, attempt),
+        )
+        .await
+    }
+
+    async fn increment_number_of_sign_in_failures(
+        &self,
+        user_id: UserId,
+        attempt: &SignInAttempt,
+    ) -> DomainResult<Option<UserCredential>> {
+        self.call(
+            "increment_number_of_sign_in_failures",
+            self.repository
+                .increment_number_of_sign_in_failures(user_id, attempt),
+        )
+        .await
+    }
+
+    async fn lock_user_account(&self, user_id: UserId) -> DomainResult<()> {
+        self.call(
+            "lock_user_account",
+            self.repository.lock_user_account(user_id),
+        )
+        .await
+    }
+
+    async fn unlock_user_account(&self, user_id: UserId) -> DomainResult<()> {
+        self.call(
+            "unlock_user_account",
+            self.repository.unlock_user_account(user_id),
+        )
+        .await
+    }
+
+    async fn unlock_expired_accounts(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        self.call(
+            "unlock_expired_accounts",
+            self.repository.unlock_expired_accounts(before),
+        )
+        .await
+    }
+
+    async fn list_locked(&self, pagination: Pagination) -> DomainResult<Vec<LockedUser>> {
+        self.call("list_locked", self.repository.list_locked(pagination))
+            .await
+    }
+
+    async fn clear_sign_in_failed_history(
+        &self,
+        user_id: UserId,
+    ) -> DomainResult<Option<UserCredential>> {
+        self.call(
+            "clear_sign_in_failed_history",
+            self.repository.clear_sign_in_failed_history(user_id),
+        )
+        .await
+    }
+
+    async fn verify_email(&self, user_id: UserId) -> DomainResult<bool> {
+        self.call("verify_email", self.repository.verify_email(user_id))
+            .await
+    }
+
+    async fn update_email(&self, user_id: UserId, email: EmailAddress) -> DomainResult<bool> {
+        self.call("update_email", self.repository.update_email(user_id, email))
+            .await
+    }
+
+    async fn update_password(
+        &self,
+        user_id: UserId,
+        password: PhcPassword,
+        audit: AuditLogInput,
+    ) -> DomainResult<()> {
+        self.call(
+            "update_password",
+            self.repository.update_password(user_id, password, audit),
+        )
+        .await
+    }
+
+    async fn count_admins(&self) -> DomainResult<u64> {
+        self.call("count_admins", self.repository.count_admins())
+            .await
+    }
+
+    async fn update_permission(
+        &self,
+        user_id: UserId,
+        user_permission_code: UserPermissionCode,
+        audit: AuditLogInput,
+    ) -> DomainResult<GuardedChange> {
+        self.call(
+            "update_permission",
+            self.repository
+                .update_permission(user_id, user_permission_code, audit),
+        )
+        .await
+    }
+
+    async fn update_profile(
+        &self,
+        user_id: UserId,
+        version: i32,
+        profile: UpdateProfileInput,
+    ) -> DomainResult<Option<User>> {
+        self.call(
+            "update_profile",
+            self.repository.update_profile(user_id, version, profile),
+        )
+        .await
+    }
+
+    async fn soft_delete(
+        &self,
+        user_id: UserId,
+        audit: Option<AuditLogInput>,
+    ) -> DomainResult<GuardedChange> {
+        self.call("soft_delete", self.repository.soft_delete(user_id, audit))
+            .await
+    }
+
+    async fn purge_sign_in_history_older_than(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        self.call(
+            "purge_sign_in_history_older_than",
+            self.repository.purge_sign_in_history_older_than(before),
+        )
+        .await
+    }
+
+    async fn purge_deleted_before(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        self.call(
+            "purge_deleted_before",
+            self.repository.purge_deleted_before(before),
+        )
+        .await
+    }
+
+    async fn create(&self, user: SignUpInput) -> DomainResult<SignUpOutput> {
+        self.call("create", self.repository.create(user)).await
+    }
+
+    async fn create_many(
+        &self,
+        users: Vec<SignUpInput>,
+        atomic: bool,
+        audit: AuditLogInput,
+    ) -> DomainResult<Vec<DomainResult<SignUpOutput>>> {
+        self.call(
+            "create_many",
+            self.repository.create_many(users, atomic, audit),
+        )
+        .await
+    }
+}
+
+/// 計測するトークンリポジトリ
+///
+/// トークンリポジトリの呼び出しをスパンで囲むデコレーターで、呼び出したメソッド名、処理時間及び
+/// 成否をスパンに記録する。メトリクスを設定した場合は、処理時間をメトリクスにも記録する。
+/// ラップしたトークンリポジトリの処理は変更しない。
+#[derive(Debug, Clone)]
+pub struct InstrumentedTokenRepository<R> {
+    /// ラップしたトークンリポジトリ
+    repository: R,
+    /// リポジトリの処理時間を記録するメトリクス
+    metrics: Option<Metrics>,
+}
+
+impl<R: TokenRepository> InstrumentedTokenRepository<R> {
+    /// 計測するトークンリポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `repository` - ラップするトークンリポジトリ
+    ///
+    /// # 戻り値
+    ///
+    /// 計測するトークンリポジトリ
+    pub fn new(repository: R) -> Self {
+        Self {
+            repository,
+            metrics: None,
+        }
+    }
+
+    /// リポジトリの処理時間を記録するメトリクスを設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `metrics` - メトリクス
+    ///
+    /// # 戻り値
+    ///
+    /// 計測するトークンリポジトリ
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// トークンリポジトリのメソッドの呼び出しを計測する。
+    async fn call<T>(
+        &self,
+        method: &'static str,
+        call: impl Future<Output = DomainResult<T>>,
+    ) -> DomainResult<T> {
+        instrument(TOKEN_REPOSITORY, method, self.metrics.as_ref(), call).await
+    }
+}
+
+#[async_trait]
+impl<R: TokenRepository> TokenRepository for InstrumentedTokenRepository<R> {
+    async fn register_token_pair<'a>(
+        &self,
+        user_id: UserId,
+        tokens: TokenPairWithTtl<'a>,
+        user_permission_code: UserPermissionCode,
+    ) -> DomainResult<()> {
+        self.call(
+            "register_token_pair",
+            self.repository
+                .register_token_pair(user_id, tokens, user_permission_code),
+        )
+        .await
+    }
+
+    async fn retrieve_token_content(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<TokenContent>> {
+        self.call(
+            "retrieve_token_content",
+            self.repository.retrieve_token_content(token),
+        )
+        .await
+    }
+
+    async fn register_email_verification_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        self.call(
+            "register_email_verification_token",
+            self.repository
+                .register_email_verification_token(user_id, token, ttl),
+        )
+        .await
+    }
+
+    async fn consume_email_verification_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        self.call(
+            "consume_email_verification_token",
+            self.repository.consume_email_verification_token(token),
+        )
+        .await
+    }
+
+    async fn register_email_change_token(
+        &self,
+        email_change: &PendingEmailChange,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        self.call(
+            "register_email_change_token",
+            self.repository
+                .register_email_change_token(email_change, token, ttl),
+        )
+        .await
+    }
+
+    async fn consume_email_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<PendingEmailChange>> {
+        self.call(
+            "consume_email_change_token",
+            self.repository.consume_email_change_token(token),
+        )
+        .await
+    }
+
+    async fn register_mfa_pending_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        self.call(
+            "register_mfa_pending_token",
+            self.repository
+                .register_mfa_pending_token(user_id, token, ttl),
+        )
+        .await
+    }
+
+    async fn consume_mfa_pending_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        self.call(
+            "consume_mfa_pending_token",
+            self.repository.consume_mfa_pending_token(token),
+        )
+        .await
+    }
+
+    async fn revoke_mfa_pending_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        self.call(
+            "revoke_mfa_pending_tokens",
+            self.repository.revoke_mfa_pending_tokens(user_id),
+        )
+        .await
+    }
+
+    async fn register_password_change_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        self.call(
+            "register_password_change_token",
+            self.repository
+                .register_password_change_token(user_id, token, ttl),
+        )
+        .await
+    }
+
+    async fn consume_password_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        self.call(
+            "consume_password_change_token",
+            self.repository.consume_password_change_token(token),
+        )
+        .await
+    }
+
+    async fn register_reauthentication(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        self.call(
+            "register_reauthentication",
+            self.repository
+                .register_reauthentication(user_id, access_token, ttl),
+        )
+        .await
+    }
+
+    async fn reauthenticated_user(
+        &self,
+        access_token: &AccessToken,
+    ) -> DomainResult<Option<UserId>> {
+        self.call(
+            "reauthenticated_user",
+            self.repository.reauthenticated_user(access_token),
+        )
+        .await
+    }
+
+    async fn user_sessions(
+        &self,
+        user_id: UserId,
+        current_access_token: &AccessToken,
+    ) -> DomainResult<Vec<ActiveSession>> {
+        self.call(
+            "user_sessions",
+            self.repository.user_sessions(user_id, current_access_token),
+        )
+        .await
+    }
+
+    async fn revoke_session(&self, user_id: UserId, session_id: &str) -> DomainResult<bool> {
+        self.call(
+            "revoke_session",
+            self.repository.revoke_session(user_id, session_id),
+        )
+        .await
+    }
+
+    async fn revoke_user_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        self.call(
+            "revoke_user_tokens",
+            self.repository.revoke_user_tokens(user_id),
+        )
+        .await
+    }
+}
+
+/// リポジトリのメソッドの呼び出しを計測する。
+///
+/// 呼び出しをスパンで囲み、処理が完了した後で、処理時間（ミリ秒）と成否をスパンに記録する。
+/// メトリクスが指定された場合は、処理時間（秒）をメトリクスにも記録する。
+///
+/// # 引数
+///
+/// * `repository` - リポジトリ名
+/// * `method` - 呼び出したメソッド名
+/// * `metrics` - メトリクス
+/// * `call` - リポジトリのメソッドの呼び出し
+///
+/// # 戻り値
+///
+/// リポジトリのメソッドの呼び出し結果
+async fn instrument<T>(
+    repository: &'static str,
+    method: &'static str,
+    metrics: Option<&Metrics>,
+    call: impl Future<Output = DomainResult<T>>,
+) -> DomainResult<T> {
+    let span = tracing::info_span!(
+        "repository",
+        repository.name = repository,
+        repository.method = method,
+        repository.duration_ms = tracing::field::Empty,
+        repository.outcome = tracing::field::Empty,
+    );
+    let started_at = Instant::now();
+    let result = call.instrument(span.clone()).await;
+    let elapsed = started_at.elapsed();
+    let outcome = match result {
+        Ok(_) => OUTCOME_SUCCESS,
+        Err(_) => OUTCOME_FAILURE,
+    };
+    span.record("repository.duration_ms", elapsed.as_millis() as u64);
+    span.record("repository.outcome", outcome);
+    if let Some(metrics) = metrics {
+        metrics.observe_repository_call(repository, method, outcome, elapsed.as_secs_f64());
+    }
+
+    result
+}
+
+/// ユーザーリポジトリのリポジトリ名
+const USER_REPOSITORY: &str = "user";
+
+/// トークンリポジトリのリポジトリ名
+const TOKEN_REPOSITORY: &str = "token";
+
+/// 処理に成功したことを示す成否
+const OUTCOME_SUCCESS: &str = "success";
+
+/// 処理に失敗したことを示す成否
+const OUTCOME_FAILURE: &str = "failure";
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use actix_web::rt::time::sleep;
+    use anyhow::anyhow;
+
+    use domain::repositories::memory::user::InMemoryUserRepository;
+    use domain::repositories::user::SignUpInputBuilder;
+    use domain::DomainError;
+
+    use super::*;
+
+    /// PHC文字列
+    /// cspell: disable-next-line
+    const RAW_PHC_PASSWORD: &str = "$argon2id$v=19$m=65536,t=2,p=1$gZiV/M1gPc22ElAH/Jh1Hw$CWOrkoo7oJBQ/iyh7uJ0LO2aLEfrHwTWllSAxT0zRno";
+
+    /// 遅延
+    const DELAY: Duration = Duration::from_millis(20);
+
+    /// 遅延してから結果を返すトークンリポジトリ
+    ///
+    /// トークンの内容の取得は遅延してから`None`を返し、ユーザーのトークンの失効は遅延してから
+    /// エラーを返す。
+    #[derive(Debug)]
+    struct DelayedTokenRepository;
+
+    #[async_trait]
+    impl TokenRepository for DelayedTokenRepository {
+        async fn register_token_pair<'a>(
+            &self,
+            _user_id: UserId,
+            _tokens: TokenPairWithTtl<'a>,
+            _user_permission_code: UserPermissionCode,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn retrieve_token_content(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<TokenContent>> {
+            sleep(DELAY).await;
+            Ok(None)
+        }
+
+        async fn register_email_verification_token(
+            &self,
+            _user_id: UserId,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_email_verification_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn register_email_change_token(
+            &self,
+            _email_change: &PendingEmailChange,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_email_change_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<PendingEmailChange>> {
+            unimplemented!()
+        }
+
+        async fn register_mfa_pending_token(
+            &self,
+            _user_id: UserId,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_mfa_pending_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn revoke_mfa_pending_tokens(&self, _user_id: UserId) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn register_password_change_token(
+            &self,
+            _user_id: UserId,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_password_change_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn register_reauthentication(
+            &self,
+            _user_id: UserId,
+            _access_token: &AccessToken,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn reauthenticated_user(
+            &self,
+            _access_token: &AccessToken,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn user_sessions(
+            &self,
+            _user_id: UserId,
+            _current_access_token: &AccessToken,
+        ) -> DomainResult<Vec<ActiveSession>> {
+            unimplemented!()
+        }
+
+        async fn revoke_session(&self, _user_id: UserId, _session_id: &str) -> DomainResult<bool> {
+            unimplemented!()
+        }
+
+        async fn revoke_user_tokens(&self, _user_id: UserId) -> DomainResult<()> {
+            sleep(DELAY).await;
+            Err(DomainError::Repository(anyhow!("failed to revoke tokens")))
+        }
+    }
+
+    /// サインアップするユーザーを構築する。
+    fn sign_up_input() -> anyhow::Result<SignUpInput> {
+        Ok(SignUpInputBuilder::new()
+            .id(UserId::default())
+            .email(EmailAddress::new("foo@example.com")?)
+            .password(PhcPassword::new(SecretString::new(
+                RAW_PHC_PASSWORD.into(),
+            ))?)
+            .active(true)
+            .user_permission_code(UserPermissionCode::General)
+            .family_name(FamilyName::new("foo")?)
+            .given_name(GivenName::new("bar")?)
+            .postal_code(PostalCode::new("012-3456")?)
+            .address(Address::new("foo bar baz qux")?)
+            .fixed_phone_number(OptionalFixedPhoneNumber::try_from("03-1234-5678")?)
+            .build()
+            .unwrap())
+    }
+
+    /// メトリクスに記録されたリポジトリの処理時間の合計（秒）を返す。
+    fn duration_sum(text: &str, labels: &str) -> Option<f64> {
+        let prefix = format!("repository_call_duration_seconds_sum{{{}}} ", labels);
+        text.lines()
+            .find_map(|line| line.strip_prefix(&prefix))
+            .and_then(|value| value.parse().ok())
+    }
+
+    /// ラップしたユーザーリポジトリに処理を委譲することを確認
+    #[tokio::test]
+    async fn instrumented_repository_delegates_to_wrapped_repository() -> anyhow::Result<()> {
+        let source = InMemoryUserRepository::new();
+        let repo = InstrumentedUserRepository::new(source.clone());
+
+        let output = repo.create(sign_up_input()?).await?;
+
+        let user = source.by_id(output.id).await?.unwrap();
+        assert_eq!("foo@example.com", user.email.value);
+        assert!(repo.by_id(output.id).await?.is_some());
+
+        Ok(())
+    }
+
+    /// ユーザーリポジトリの呼び出しを、メソッド名と成否ごとにメトリクスに記録することを確認
+    #[tokio::test]
+    async fn user_repository_calls_are_recorded_with_method_and_outcome() -> anyhow::Result<()> {
+        let metrics = Metrics::new();
+        let repo = InstrumentedUserRepository::new(InMemoryUserRepository::new())
+            .with_metrics(metrics.clone());
+
+        let output = repo.create(sign_up_input()?).await?;
+        assert!(repo.create(sign_up_input()?).await.is_err());
+        repo.by_id(output.id).await?;
+
+        let text = metrics.render()?;
+        assert!(text.contains(
+            r#"repository_call_duration_seconds_count{method="create",outcome="success",repository="user"} 1"#
+        ));
+        assert!(text.contains(
+            r#"repository_call_duration_seconds_count{method="create",outcome="failure",repository="user"} 1"#
+        ));
+        assert!(text.contains(
+            r#"repository_call_duration_seconds_count{method="by_id",outcome="success",repository="user"} 1"#
+        ));
+
+        Ok(())
+    }
+
+    /// トークンリポジトリの処理時間と成否を、メトリクスに記録することを確認
+    #[actix_web::test]
+    async fn token_repository_calls_are_recorded_with_duration_and_outcome() -> anyhow::Result<()> {
+        let metrics = Metrics::new();
+        let repo =
+            InstrumentedTokenRepository::new(DelayedTokenRepository).with_metrics(metrics.clone());
+        let token = SecretString::new(String::from("token"));
+
+        assert!(repo.retrieve_token_content(&token).await?.is_none());
+        assert!(repo.revoke_user_tokens(UserId::default()).await.is_err());
+
+        let text = metrics.render()?;
+        let success = duration_sum(
+            &text,
+            r#"method="retrieve_token_content",outcome="success",repository="token""#,
+        )
+        .unwrap();
+        assert!(DELAY.as_secs_f64() <= success);
+        let failure = duration_sum(
+            &text,
+            r#"method="revoke_user_tokens",outcome="failure",repository="token""#,
+        )
+        .unwrap();
+        assert!(DELAY.as_secs_f64() <= failure);
+
+        Ok(())
+    }
+}
@@ -1,2 +1,4 @@
+pub mod cache;
+pub mod instrumented;
 pub mod postgres;
 pub mod redis;
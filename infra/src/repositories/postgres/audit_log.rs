@@ -0,0 +1,132 @@
+use async_trait::async_trait;
+use sqlx::types::Json;
+use sqlx::Postgres;
+use time::OffsetDateTime;
+
+use domain::models::audit_log::{AuditAction, AuditLog, AuditLogId};
+use domain::models::user::UserId;
+use domain::repositories::audit_log::{AuditLogFilter, AuditLogInput, AuditLogRepository};
+use domain::{DomainError, DomainResult};
+
+use crate::repositories::postgres::{PgRepository, QueryTimeoutExt as _};
+
+/// PostgreSQL監査ログリポジトリ
+pub type PgAuditLogRepository = PgRepository<AuditLog>;
+
+type PgQueryAs<'q, T> = sqlx::query::QueryAs<'q, sqlx::Postgres, T, sqlx::postgres::PgArguments>;
+type PgQuery<'q> = sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>;
+
+#[async_trait]
+impl AuditLogRepository for PgAuditLogRepository {
+    /// 監査ログのリストを、操作を実行した日時の新しい順に取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `filter` - 監査ログリストの絞り込み条件
+    ///
+    /// # 戻り値
+    ///
+    /// 監査ログを格納したベクタ
+    async fn list(&self, filter: AuditLogFilter) -> DomainResult<Vec<AuditLog>> {
+        list_audit_logs_query(filter)
+            .fetch_all(&self.pool)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?
+            .into_iter()
+            .map(AuditLog::try_from)
+            .collect()
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub struct AuditLogRow {
+    pub id: AuditLogId,
+    pub actor_user_id: UserId,
+    pub action: String,
+    pub target_user_id: Option<UserId>,
+    pub occurred_at: OffsetDateTime,
+    pub request_id: Option<String>,
+    pub details: Json<serde_json::Value>,
+}
+
+impl TryFrom<AuditLogRow> for AuditLog {
+    type Error = DomainError;
+
+    fn try_from(row: AuditLogRow) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: row.id,
+            actor_user_id: row.actor_user_id,
+            action: AuditAction::try_from(row.action.as_str())?,
+            target_user_id: row.target_user_id,
+            occurred_at: row.occurred_at,
+            request_id: row.request_id,
+            details: row.details.0,
+        })
+    }
+}
+
+/// 監査ログのリストを取得するクエリを生成する。
+///
+/// # 引数
+///
+/// * `filter` - 監査ログリストの絞り込み条件
+///
+/// # 戻り値
+///
+/// 監査ログのリストを取得するクエリ
+pub fn list_audit_logs_query<'q>(filter: AuditLogFilter) -> PgQueryAs<'q, AuditLogRow> {
+    sqlx::query_as::<Postgres, AuditLogRow>(
+        r#"
+        SELECT
+            id, actor_user_id, action, target_user_id, occurred_at, request_id, details
+        FROM
+            admin_audit_logs
+        WHERE
+            ($1::UUID IS NULL OR actor_user_id = $1)
+            AND ($2::UUID IS NULL OR target_user_id = $2)
+        ORDER BY
+            occurred_at DESC, id
+        LIMIT $3
+        OFFSET $4
+        "#,
+    )
+    .bind(filter.actor_user_id)
+    .bind(filter.target_user_id)
+    .bind(filter.limit.map(i64::from))
+    .bind(i64::from(filter.offset))
+}
+
+/// 監査ログを記録するクエリを生成する。
+///
+/// 操作を実行した日時には、操作による変更と同じ日時を記録するように、`STATEMENT_TIMESTAMP()`
+/// ではなく、トランザクションを開始した日時を示す`CURRENT_TIMESTAMP`を使用する。
+///
+/// # 引数
+///
+/// * `audit` - 記録する監査ログ
+///
+/// # 戻り値
+///
+/// 監査ログを記録するクエリ
+pub fn insert_audit_log_query<'q>(audit: AuditLogInput) -> PgQuery<'q> {
+    sqlx::query::<Postgres>(
+        r#"
+        INSERT INTO admin_audit_logs (
+            id, actor_user_id, action, target_user_id, occurred_at, request_id, details
+        )
+        VALUES (
+            $1, $2, $3, $4, CURRENT_TIMESTAMP, $5, $6
+        )
+        "#,
+    )
+    .bind(AuditLogId::default())
+    .bind(audit.actor_user_id)
+    .bind(audit.action.as_str())
+    .bind(audit.target_user_id)
+    .bind(audit.request_id)
+    .bind(Json(audit.details))
+}
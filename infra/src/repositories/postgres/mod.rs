@@ -1,10 +1,16 @@
+pub mod audit_log;
+pub mod totp;
 pub mod user;
+pub mod user_permission;
 
+use std::future::Future;
 use std::marker::PhantomData;
+use std::time::Duration;
 
+use futures_util::future::BoxFuture;
 use sqlx::{PgPool, Postgres, Transaction};
 
-use domain::{DomainError, DomainResult};
+use domain::{DomainError, DomainResult, RepositoryTimeout};
 
 /// PostgreSQLトランザクション型
 pub type PgTransaction<'c> = Transaction<'c, Postgres>;
@@ -14,6 +20,10 @@ pub type PgTransaction<'c> = Transaction<'c, Postgres>;
 pub struct PgRepository<T> {
     /// PostgreSQL接続プール
     pub pool: PgPool,
+    /// SQLステートメントの完了を待機する時間
+    ///
+    /// `None`の場合は、SQLステートメントが完了するまで待機する。
+    query_timeout: Option<Duration>,
     /// マーカー
     _phantom: PhantomData<T>,
 }
@@ -31,10 +41,25 @@ impl<T> PgRepository<T> {
     pub fn new(pool: PgPool) -> Self {
         Self {
             pool,
+            query_timeout: None,
             _phantom: Default::default(),
         }
     }
 
+    /// SQLステートメントの完了を待機する時間を設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `query_timeout` - SQLステートメントの完了を待機する時間、`None`の場合は完了するまで待機
+    ///
+    /// # 戻り値
+    ///
+    /// PostgreSQLリポジトリ
+    pub fn with_query_timeout(mut self, query_timeout: Option<Duration>) -> Self {
+        self.query_timeout = query_timeout;
+        self
+    }
+
     /// デフォルトのトランザクション分離レベルとアクセスモードで、トランザクションを開始する。
     ///
     /// # 戻り値
@@ -60,20 +85,12 @@ impl<T> PgRepository<T> {
         &self,
         isolation_level: IsolationLevel,
     ) -> DomainResult<PgTransaction<'c>> {
-        // トランザクションを開始
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| DomainError::Repository(e.into()))?;
-        // トランザクション分離モデルを設定
-        let sql = format!("SET TRANSACTION ISOLATION LEVEL {}", isolation_level);
-        sqlx::query(&sql)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| DomainError::Repository(e.into()))?;
-
-        Ok(tx)
+        self.begin_with_characteristics(TransactionCharacteristics {
+            isolation_level,
+            access_mode: None,
+            deferrable: None,
+        })
+        .await
     }
 
     /// 指定したザクション分離レベルとアクセスモードで、トランザクションを開始する。
@@ -91,27 +108,20 @@ impl<T> PgRepository<T> {
         isolation_level: IsolationLevel,
         access_mode: AccessMode,
     ) -> DomainResult<PgTransaction<'c>> {
-        // トランザクションを開始
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| DomainError::Repository(e.into()))?;
-        // トランザクション分離モデルを設定
-        let sql = format!(
-            "SET TRANSACTION ISOLATION LEVEL {} {}",
-            isolation_level, access_mode
-        );
-        sqlx::query(&sql)
-            .execute(&mut *tx)
-            .await
-            .map_err(|e| DomainError::Repository(e.into()))?;
-
-        Ok(tx)
+        self.begin_with_characteristics(TransactionCharacteristics {
+            isolation_level,
+            access_mode: Some(access_mode),
+            deferrable: None,
+        })
+        .await
     }
 
     /// トランザクションを開始する。
     ///
+    /// デフェラブルは、シリアライザブルかつ読み込み専用のトランザクションでのみ指定できる。
+    /// それ以外の組み合わせでデフェラブルを指定した場合は、トランザクションを開始せずに予期しない
+    /// エラーを返す。
+    ///
     /// # 引数
     ///
     /// * `isolation_level` - トランザクション分離レベル
@@ -132,34 +142,191 @@ impl<T> PgRepository<T> {
             && (isolation_level != IsolationLevel::Serializable
                 || access_mode != AccessMode::ReadOnly)
         {
-            panic!("Deferrable transaction can execute in serializable and read only");
+            tracing::error!(
+                "{} ({} {}) ({}:{})",
+                INVALID_DEFERRABLE_TRANSACTION,
+                isolation_level,
+                access_mode,
+                file!(),
+                line!()
+            );
+            return Err(DomainError::Unexpected(anyhow::anyhow!(
+                INVALID_DEFERRABLE_TRANSACTION
+            )));
         }
 
-        // トランザクションを開始
-        let mut tx = self
-            .pool
-            .begin()
-            .await
-            .map_err(|e| DomainError::Repository(e.into()))?;
-        // トランザクション分離モデルを設定
-        let deferrable_token = if deferrable {
-            "DEFERRABLE"
-        } else {
-            "NOT DEFERRABLE"
-        };
-        let sql = format!(
-            "SET TRANSACTION ISOLATION LEVEL {} {} {}",
-            isolation_level, access_mode, deferrable_token
-        );
-        sqlx::query(&sql)
-            .execute(&mut *tx)
+        self.begin_with_characteristics(TransactionCharacteristics {
+            isolation_level,
+            access_mode: Some(access_mode),
+            deferrable: Some(deferrable),
+        })
+        .await
+    }
+
+    /// デフォルトのトランザクション分離レベルとアクセスモードで開始したトランザクションで、
+    /// クロージャーを実行する。
+    ///
+    /// クロージャーが`Ok`を返した場合はトランザクションをコミットして、`Err`を返した場合は
+    /// トランザクションをロールバックする。
+    ///
+    /// # 引数
+    ///
+    /// * `f` - トランザクションを受け取り、トランザクション内で実行する処理を返すクロージャー
+    ///
+    /// # 戻り値
+    ///
+    /// クロージャーが返した値
+    pub async fn with_transaction<F, R>(&self, f: F) -> DomainResult<R>
+    where
+        F: for<'t> FnOnce(&'t mut PgTransaction<'static>) -> BoxFuture<'t, DomainResult<R>>,
+    {
+        let tx = self.begin().await?;
+
+        run_in_transaction(tx, f).await
+    }
+
+    /// 指定したトランザクション分離レベルとデフォルトのアクセスモードで開始したトランザクションで、
+    /// クロージャーを実行する。
+    ///
+    /// クロージャーが`Ok`を返した場合はトランザクションをコミットして、`Err`を返した場合は
+    /// トランザクションをロールバックする。
+    ///
+    /// # 引数
+    ///
+    /// * `isolation_level` - トランザクション分離レベル
+    /// * `f` - トランザクションを受け取り、トランザクション内で実行する処理を返すクロージャー
+    ///
+    /// # 戻り値
+    ///
+    /// クロージャーが返した値
+    pub async fn with_transaction_level<F, R>(
+        &self,
+        isolation_level: IsolationLevel,
+        f: F,
+    ) -> DomainResult<R>
+    where
+        F: for<'t> FnOnce(&'t mut PgTransaction<'static>) -> BoxFuture<'t, DomainResult<R>>,
+    {
+        let tx = self.begin_with_level(isolation_level).await?;
+
+        run_in_transaction(tx, f).await
+    }
+
+    /// 指定した特性で、トランザクションを開始する。
+    ///
+    /// トランザクションの特性を指定した1つの`BEGIN`ステートメントでトランザクションを開始するため、
+    /// トランザクションを開始するまでの往復は1回で、トランザクション内のすべてのステートメントに
+    /// 特性が適用される。
+    ///
+    /// # 引数
+    ///
+    /// * `characteristics` - トランザクションの特性
+    ///
+    /// # 戻り値
+    ///
+    /// トランザクション
+    async fn begin_with_characteristics<'c>(
+        &self,
+        characteristics: TransactionCharacteristics,
+    ) -> DomainResult<PgTransaction<'c>> {
+        self.pool
+            .begin_with(characteristics.begin_sql())
             .await
-            .map_err(|e| DomainError::Repository(e.into()))?;
+            .map_err(|e| DomainError::Repository(e.into()))
+    }
+}
+
+/// トランザクションの特性
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TransactionCharacteristics {
+    /// トランザクション分離レベル
+    isolation_level: IsolationLevel,
+    /// トランザクションのアクセスモード
+    ///
+    /// `None`の場合は、デフォルトのアクセスモード
+    access_mode: Option<AccessMode>,
+    /// トランザクションがコミットされるまでチェックを延期(defer)するか
+    ///
+    /// `None`の場合は、デフォルトのデフェラブル
+    deferrable: Option<bool>,
+}
+
+impl TransactionCharacteristics {
+    /// トランザクションの特性を指定して、トランザクションを開始する`BEGIN`ステートメントを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// `BEGIN`ステートメント
+    fn begin_sql(&self) -> String {
+        let mut sql = format!("BEGIN ISOLATION LEVEL {}", self.isolation_level);
+        if let Some(access_mode) = self.access_mode {
+            sql.push_str(&format!(" {}", access_mode));
+        }
+        match self.deferrable {
+            Some(true) => sql.push_str(" DEFERRABLE"),
+            Some(false) => sql.push_str(" NOT DEFERRABLE"),
+            None => {}
+        }
 
-        Ok(tx)
+        sql
     }
 }
 
+/// SQLステートメントの実行にタイムアウトを設定する拡張トレイト
+pub(crate) trait QueryTimeoutExt<T>:
+    Future<Output = Result<T, sqlx::Error>> + Sized
+{
+    /// SQLステートメントの完了を待機する時間を設定して、SQLステートメントを実行する。
+    ///
+    /// 待機する時間が経過した場合は、SQLステートメントの実行を取り消す。待機する時間が経過した
+    /// 場合、またはPostgreSQLが`statement_timeout`でSQLステートメントを取り消した場合は、
+    /// リポジトリの処理がタイムアウトしたことを示すリポジトリエラーを返す。
+    ///
+    /// # 引数
+    ///
+    /// * `timeout` - SQLステートメントの完了を待機する時間、`None`の場合は完了するまで待機
+    ///
+    /// # 戻り値
+    ///
+    /// SQLステートメントの実行結果
+    async fn with_timeout(self, timeout: Option<Duration>) -> DomainResult<Result<T, sqlx::Error>> {
+        let result = match timeout {
+            Some(timeout) => actix_web::rt::time::timeout(timeout, self)
+                .await
+                .map_err(|_| {
+                    tracing::error!("{} ({}:{})", RepositoryTimeout, file!(), line!());
+                    DomainError::Repository(RepositoryTimeout.into())
+                })?,
+            None => self.await,
+        };
+        match result {
+            Err(e) if is_query_canceled(&e) => {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                Err(DomainError::Repository(RepositoryTimeout.into()))
+            }
+            result => Ok(result),
+        }
+    }
+}
+
+impl<T, F: Future<Output = Result<T, sqlx::Error>>> QueryTimeoutExt<T> for F {}
+
+/// PostgreSQLがSQLステートメントを取り消したことを示すエラーであるか確認する。
+///
+/// # 引数
+///
+/// * `error` - SQLステートメントを実行したときのエラー
+///
+/// # 戻り値
+///
+/// SQLステートメントが取り消された場合は`true`、それ以外の場合は`false`
+fn is_query_canceled(error: &sqlx::Error) -> bool {
+    error
+        .as_database_error()
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == QUERY_CANCELED)
+}
+
 /// トランザクションをコミットする。
 ///
 /// # 引数
@@ -173,18 +340,47 @@ pub async fn commit_transaction(tx: PgTransaction<'_>) -> DomainResult<()> {
 
 /// トランザクションをロールバックする。
 ///
-/// FIXME: ロールバックをトランザクションのドロップでするなど、この関数を呼び出さない場合は削除する。
-///
 /// # 引数
 ///
 /// * `tx` - トランザクション
-#[allow(dead_code)]
-async fn rollback_transaction(tx: PgTransaction<'_>) -> DomainResult<()> {
-    tx.commit()
+pub async fn rollback_transaction(tx: PgTransaction<'_>) -> DomainResult<()> {
+    tx.rollback()
         .await
         .map_err(|e| DomainError::Repository(e.into()))
 }
 
+/// トランザクションでクロージャーを実行して、クロージャーの結果に応じてトランザクションを
+/// コミットまたはロールバックする。
+///
+/// ロールバックに失敗した場合は、ロールバックのエラーを記録して、クロージャーが返したエラーを返す。
+/// ロールバックに失敗した接続は、接続プールに返却されるときに破棄される。
+///
+/// # 引数
+///
+/// * `tx` - トランザクション
+/// * `f` - トランザクションを受け取り、トランザクション内で実行する処理を返すクロージャー
+///
+/// # 戻り値
+///
+/// クロージャーが返した値
+async fn run_in_transaction<F, R>(mut tx: PgTransaction<'static>, f: F) -> DomainResult<R>
+where
+    F: for<'t> FnOnce(&'t mut PgTransaction<'static>) -> BoxFuture<'t, DomainResult<R>>,
+{
+    match f(&mut tx).await {
+        Ok(value) => {
+            commit_transaction(tx).await?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback_error) = rollback_transaction(tx).await {
+                tracing::error!("{} ({}:{})", rollback_error, file!(), line!());
+            }
+            Err(e)
+        }
+    }
+}
+
 /// トランザクション分離レベル
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum IsolationLevel {
@@ -231,3 +427,111 @@ impl std::fmt::Display for AccessMode {
         }
     }
 }
+
+/// SQLステートメントが取り消されたことを示すSQLSTATE
+const QUERY_CANCELED: &str = "57014";
+
+const INVALID_DEFERRABLE_TRANSACTION: &str =
+    "デフェラブルは、シリアライザブルかつ読み込み専用のトランザクションでのみ指定できます。";
+
+#[cfg(test)]
+mod tests {
+    use actix_web::rt::time::sleep;
+
+    use super::*;
+
+    /// SQLステートメントが待機する時間内に完了しない場合は、リポジトリの処理がタイムアウトしたことを
+    /// 示すリポジトリエラーを返すことを確認
+    #[actix_web::test]
+    async fn query_exceeding_timeout_returns_repository_timeout() {
+        let query = async {
+            sleep(Duration::from_millis(200)).await;
+            Ok::<_, sqlx::Error>(())
+        };
+
+        let result = query.with_timeout(Some(Duration::from_millis(10))).await;
+
+        assert!(result.unwrap_err().is_timeout());
+    }
+
+    /// シリアライザブルかつ読み込み専用でないトランザクションにデフェラブルを指定した場合は、
+    /// データベースに接続せずに、予期しないエラーを返すことを確認
+    #[actix_web::test]
+    async fn deferrable_requires_serializable_and_read_only() {
+        // 接続しない接続プール
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let repo = PgRepository::<i32>::new(pool);
+
+        for (isolation_level, access_mode) in [
+            (IsolationLevel::Serializable, AccessMode::ReadWrite),
+            (IsolationLevel::RepeatableRead, AccessMode::ReadOnly),
+            (IsolationLevel::ReadCommit, AccessMode::ReadWrite),
+        ] {
+            let result = repo
+                .begin_with_full(isolation_level, access_mode, true)
+                .await;
+
+            let e = result.err().unwrap();
+            assert!(
+                matches!(e, DomainError::Unexpected(_)),
+                "{} {}: {:?}",
+                isolation_level,
+                access_mode,
+                e
+            );
+            assert_eq!(INVALID_DEFERRABLE_TRANSACTION, e.to_string());
+        }
+    }
+
+    /// トランザクションの特性から、`BEGIN`ステートメントを生成できることを確認
+    #[test]
+    fn begin_sql_contains_specified_characteristics() {
+        let characteristics = [
+            (
+                TransactionCharacteristics {
+                    isolation_level: IsolationLevel::RepeatableRead,
+                    access_mode: None,
+                    deferrable: None,
+                },
+                "BEGIN ISOLATION LEVEL REPEATABLE READ",
+            ),
+            (
+                TransactionCharacteristics {
+                    isolation_level: IsolationLevel::ReadCommit,
+                    access_mode: Some(AccessMode::ReadWrite),
+                    deferrable: None,
+                },
+                "BEGIN ISOLATION LEVEL READ COMMITTED READ WRITE",
+            ),
+            (
+                TransactionCharacteristics {
+                    isolation_level: IsolationLevel::Serializable,
+                    access_mode: Some(AccessMode::ReadOnly),
+                    deferrable: Some(true),
+                },
+                "BEGIN ISOLATION LEVEL SERIALIZABLE READ ONLY DEFERRABLE",
+            ),
+            (
+                TransactionCharacteristics {
+                    isolation_level: IsolationLevel::Serializable,
+                    access_mode: Some(AccessMode::ReadWrite),
+                    deferrable: Some(false),
+                },
+                "BEGIN ISOLATION LEVEL SERIALIZABLE READ WRITE NOT DEFERRABLE",
+            ),
+        ];
+        for (characteristics, expected) in characteristics {
+            assert_eq!(expected, characteristics.begin_sql());
+        }
+    }
+
+    /// SQLステートメントが待機する時間内に完了した場合は、SQLステートメントの実行結果を返すことを確認
+    #[actix_web::test]
+    async fn query_within_timeout_returns_result() {
+        let query = async { Ok::<_, sqlx::Error>(1) };
+
+        let result = query.with_timeout(Some(Duration::from_secs(1))).await;
+
+        assert_eq!(1, result.unwrap().unwrap());
+    }
+}
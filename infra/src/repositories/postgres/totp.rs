@@ -0,0 +1,281 @@
+use async_trait::async_trait;
+use sqlx::Postgres;
+use time::OffsetDateTime;
+
+use domain::models::user::UserId;
+use domain::repositories::totp::{TotpRepository, UserTotpSecret};
+use domain::{DomainError, DomainResult};
+
+use crate::repositories::postgres::{commit_transaction, PgRepository};
+
+/// PostgreSQL TOTPリポジトリ
+pub type PgTotpRepository = PgRepository<UserTotpSecret>;
+
+type PgQueryAs<'q, T> = sqlx::query::QueryAs<'q, sqlx::Postgres, T, sqlx::postgres::PgArguments>;
+type PgQuery<'q> = sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>;
+
+#[async_trait]
+impl TotpRepository for PgTotpRepository {
+    /// ユーザーのTOTPの秘密鍵を取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーのTOTPの秘密鍵
+    async fn totp_secret(&self, user_id: UserId) -> DomainResult<Option<UserTotpSecret>> {
+        let row = totp_secret_query(user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+
+        Ok(row.map(|row| row.into()))
+    }
+
+    /// ユーザーのTOTPの秘密鍵を登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `encrypted_secret` - 暗号化したTOTPの秘密鍵
+    async fn register_totp_secret(
+        &self,
+        user_id: UserId,
+        encrypted_secret: Vec<u8>,
+    ) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        register_totp_secret_query(user_id, encrypted_secret)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+
+        commit_transaction(tx).await
+    }
+
+    /// ユーザーの2要素認証を有効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// 秘密鍵が登録されている場合は`true`、それ以外の場合は`false`
+    async fn enable_totp(&self, user_id: UserId) -> DomainResult<bool> {
+        let mut tx = self.begin().await?;
+        let result = enable_totp_query(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(0 < result.rows_affected())
+    }
+
+    /// ユーザーのTOTPの秘密鍵を削除して、2要素認証を無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// 秘密鍵が登録されていた場合は`true`、それ以外の場合は`false`
+    async fn delete_totp_secret(&self, user_id: UserId) -> DomainResult<bool> {
+        let mut tx = self.begin().await?;
+        let result = delete_totp_secret_query(user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(0 < result.rows_affected())
+    }
+
+    /// TOTPコードを使用したタイムステップを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `step` - TOTPコードが一致したタイムステップ
+    ///
+    /// # 戻り値
+    ///
+    /// 記録した場合は`true`、最後に使用したタイムステップ以前のタイムステップの場合は`false`
+    async fn record_used_totp_step(&self, user_id: UserId, step: u64) -> DomainResult<bool> {
+        let mut tx = self.begin().await?;
+        let result = record_used_totp_step_query(user_id, step)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(0 < result.rows_affected())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub struct UserTotpSecretRow {
+    pub user_id: UserId,
+    pub encrypted_secret: Vec<u8>,
+    pub enabled_at: Option<OffsetDateTime>,
+    pub last_used_step: Option<i64>,
+}
+
+impl From<UserTotpSecretRow> for UserTotpSecret {
+    fn from(row: UserTotpSecretRow) -> Self {
+        Self {
+            user_id: row.user_id,
+            encrypted_secret: row.encrypted_secret,
+            enabled_at: row.enabled_at,
+            last_used_step: row.last_used_step.map(|step| step as u64),
+        }
+    }
+}
+
+/// ユーザーのTOTPの秘密鍵を取得するクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// ユーザーのTOTPの秘密鍵を取得するクエリ
+pub fn totp_secret_query<'q>(user_id: UserId) -> PgQueryAs<'q, UserTotpSecretRow> {
+    sqlx::query_as::<Postgres, UserTotpSecretRow>(
+        r#"
+        SELECT
+            user_id, encrypted_secret, enabled_at, last_used_step
+        FROM
+            user_totp_secrets
+        WHERE
+            user_id = $1
+        "#,
+    )
+    .bind(user_id)
+}
+
+/// ユーザーのTOTPの秘密鍵を登録するクエリを生成する。
+///
+/// すでに秘密鍵が登録されている場合は、秘密鍵を置き換えて、2要素認証を無効にする。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+/// * `encrypted_secret` - 暗号化したTOTPの秘密鍵
+///
+/// # 戻り値
+///
+/// ユーザーのTOTPの秘密鍵を登録するクエリ
+pub fn register_totp_secret_query<'q>(user_id: UserId, encrypted_secret: Vec<u8>) -> PgQuery<'q> {
+    sqlx::query::<Postgres>(
+        r#"
+        INSERT INTO user_totp_secrets (
+            user_id, encrypted_secret, enabled_at, last_used_step, created_at, updated_at
+        )
+        VALUES (
+            $1, $2, NULL, NULL, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+        )
+        ON CONFLICT (user_id) DO UPDATE
+        SET
+            encrypted_secret = EXCLUDED.encrypted_secret,
+            enabled_at = NULL,
+            last_used_step = NULL,
+            updated_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(user_id)
+    .bind(encrypted_secret)
+}
+
+/// ユーザーの2要素認証を有効にするクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// ユーザーの2要素認証を有効にするクエリ
+pub fn enable_totp_query<'q>(user_id: UserId) -> PgQuery<'q> {
+    sqlx::query::<Postgres>(
+        r#"
+        UPDATE
+            user_totp_secrets
+        SET
+            enabled_at = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            user_id = $1
+        "#,
+    )
+    .bind(user_id)
+}
+
+/// ユーザーのTOTPの秘密鍵を削除するクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// ユーザーのTOTPの秘密鍵を削除するクエリ
+pub fn delete_totp_secret_query<'q>(user_id: UserId) -> PgQuery<'q> {
+    sqlx::query::<Postgres>(
+        r#"
+        DELETE FROM
+            user_totp_secrets
+        WHERE
+            user_id = $1
+        "#,
+    )
+    .bind(user_id)
+}
+
+/// TOTPコードを使用したタイムステップを記録するクエリを生成する。
+///
+/// 最後に使用したタイムステップより後のタイムステップの場合のみ記録する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+/// * `step` - TOTPコードが一致したタイムステップ
+///
+/// # 戻り値
+///
+/// TOTPコードを使用したタイムステップを記録するクエリ
+pub fn record_used_totp_step_query<'q>(user_id: UserId, step: u64) -> PgQuery<'q> {
+    sqlx::query::<Postgres>(
+        r#"
+        UPDATE
+            user_totp_secrets
+        SET
+            last_used_step = $2,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            user_id = $1
+            AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(step as i64)
+}
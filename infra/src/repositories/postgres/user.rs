@@ -1,40 +1,115 @@
 use async_trait::async_trait;
+use futures_util::stream::BoxStream;
+use futures_util::{StreamExt as _, TryStreamExt as _};
 use secrecy::{ExposeSecret, SecretString};
-use sqlx::Postgres;
+use sqlx::postgres::PgRow;
+use sqlx::{Acquire as _, Postgres};
 use time::OffsetDateTime;
-use uuid::Uuid;
 
 use domain::models::primitives::*;
+use domain::models::query::{Pagination, SortDirection};
 use domain::models::user::{User, UserId, UserPermission, UserPermissionCode, UserPermissionName};
-use domain::repositories::user::{SignUpInput, SignUpOutput, UserCredential, UserRepository};
+use domain::repositories::audit_log::AuditLogInput;
+use domain::repositories::user::{
+    GuardedChange, LockedUser, SignInAttempt, SignInRecord, SignUpInput, SignUpOutput,
+    UpdateProfileInput, UserCredential, UserListFilter, UserListOrder, UserRepository, UserStream,
+};
 use domain::{DomainError, DomainResult};
 
-use crate::repositories::postgres::{commit_transaction, PgRepository};
+use crate::repositories::postgres::audit_log::insert_audit_log_query;
+use crate::repositories::postgres::totp::delete_totp_secret_query;
+use crate::repositories::postgres::{
+    commit_transaction, PgRepository, PgTransaction, QueryTimeoutExt as _,
+};
 
 /// PostgreSQLユーザーリポジトリ
 pub type PgUserRepository = PgRepository<User>;
 
-type PgQueryAs<'q, T> = sqlx::query::QueryAs<'q, sqlx::Postgres, T, sqlx::postgres::PgArguments>;
+type PgQueryMap<'q, F> = sqlx::query::Map<'q, sqlx::Postgres, F, sqlx::postgres::PgArguments>;
 type PgQuery<'q> = sqlx::query::Query<'q, sqlx::Postgres, sqlx::postgres::PgArguments>;
+type PgQueryScalar<'q, T> =
+    sqlx::query::QueryScalar<'q, sqlx::Postgres, T, sqlx::postgres::PgArguments>;
 
 #[async_trait]
 impl UserRepository for PgUserRepository {
     /// ユーザーのリストを取得する。
     ///
+    /// # 引数
+    ///
+    /// * `filter` - ユーザーリストの絞り込み条件
+    ///
     /// # 戻り値
     ///
     /// ユーザーを格納したベクタ
-    async fn list(&self) -> DomainResult<Vec<User>> {
-        Ok(list_users_query()
-            .fetch_all(&self.pool)
-            .await
+    async fn list(&self, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        list_users_rows(&self.pool, filter, None)
+            .try_collect::<Vec<_>>()
+            .with_timeout(self.query_timeout)
+            .await?
             .map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
                 DomainError::Repository(e.into())
             })?
             .into_iter()
-            .map(|r| r.into())
-            .collect::<_>())
+            .map(User::try_from)
+            .collect::<_>()
+    }
+
+    /// ユーザーを検索する。
+    ///
+    /// # 引数
+    ///
+    /// * `search` - 検索文字列
+    /// * `filter` - ユーザーリストの絞り込み条件
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーを格納したベクタ
+    async fn search(&self, search: &str, filter: UserListFilter) -> DomainResult<Vec<User>> {
+        list_users_rows(&self.pool, filter, Some(contains_pattern(search)))
+            .try_collect::<Vec<_>>()
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?
+            .into_iter()
+            .map(User::try_from)
+            .collect::<_>()
+    }
+
+    /// ユーザーを1人ずつ取得するストリームを返す。
+    ///
+    /// ストリームは接続プールを所有するため、リポジトリよりも長く生存できる。
+    /// 行の取得またはユーザーの構築に失敗した場合は、エラーを返した後にストリームを終了する。
+    ///
+    /// # 引数
+    ///
+    /// * `filter` - ユーザーリストの絞り込み条件
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーを1人ずつ返すストリーム
+    fn stream(&self, filter: UserListFilter) -> UserStream {
+        let pool = self.pool.clone();
+
+        Box::pin(async_stream::stream! {
+            let mut rows = list_users_rows(&pool, filter, None);
+            while let Some(row) = rows.next().await {
+                let user = row
+                    .map_err(|e| {
+                        tracing::error!("{} ({}:{})", e, file!(), line!());
+                        DomainError::Repository(e.into())
+                    })
+                    .and_then(User::try_from);
+                let failed = user.is_err();
+                yield user;
+                if failed {
+                    break;
+                }
+            }
+        })
     }
 
     /// ユーザーを取得する。
@@ -43,14 +118,38 @@ impl UserRepository for PgUserRepository {
     ///
     /// ユーザー
     async fn by_id(&self, user_id: UserId) -> DomainResult<Option<User>> {
-        Ok(user_by_id_query(user_id)
+        user_by_id_query(user_id)
             .fetch_optional(&self.pool)
-            .await
+            .with_timeout(self.query_timeout)
+            .await?
             .map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
                 DomainError::Repository(e.into())
             })?
-            .map(|r| r.into()))
+            .map(User::try_from)
+            .transpose()
+    }
+
+    /// Eメールアドレスからユーザーを取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `email` - ユーザーのEメールアドレス
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー
+    async fn by_email(&self, email: EmailAddress) -> DomainResult<Option<User>> {
+        user_by_email_query(email)
+            .fetch_optional(&self.pool)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?
+            .map(User::try_from)
+            .transpose()
     }
 
     /// ユーザーのクレデンシャルを取得する。
@@ -65,7 +164,8 @@ impl UserRepository for PgUserRepository {
     async fn user_credential(&self, email: EmailAddress) -> DomainResult<Option<UserCredential>> {
         user_credential_query(email)
             .fetch_optional(&self.pool)
-            .await
+            .with_timeout(self.query_timeout)
+            .await?
             .map(|r| r.map(|r| r.into()))
             .map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
@@ -73,56 +173,93 @@ impl UserRepository for PgUserRepository {
             })
     }
 
-    /// ユーザーが最後にサインインした日時を更新する。
-    ///
-    /// サインインした日時を現在の日時、最初にサインインに失敗した日時をNULL、そしてサインイン失敗回数を0にする。
+    /// ユーザーIDでユーザーのクレデンシャルを取得する。
     ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
-    async fn update_last_sign_in(&self, user_id: UserId) -> DomainResult<Option<UserCredential>> {
-        let mut tx = self.begin().await?;
-        let row = update_last_sign_in_at_query(user_id)
-            .fetch_optional(&mut *tx)
-            .await
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーのクレデンシャル
+    async fn credential_by_id(&self, user_id: UserId) -> DomainResult<Option<UserCredential>> {
+        credential_by_id_query(user_id)
+            .fetch_optional(&self.pool)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map(|r| r.map(|r| r.into()))
             .map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
                 DomainError::Repository(e.into())
-            })?;
-        commit_transaction(tx).await?;
+            })
+    }
+
+    /// ユーザーが最後にサインインした日時を更新する。
+    ///
+    /// サインインした日時を現在の日時、最初にサインインに失敗した日時をNULL、そしてサインイン失敗回数を0にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    async fn update_last_sign_in(&self, user_id: UserId) -> DomainResult<Option<SignInRecord>> {
+        let query_timeout = self.query_timeout;
+        let row = self
+            .with_transaction(move |tx| {
+                Box::pin(async move {
+                    update_last_sign_in_at_query(user_id)
+                        .fetch_optional(&mut **tx)
+                        .with_timeout(query_timeout)
+                        .await?
+                        .map_err(|e| {
+                            tracing::error!("{} ({}:{})", e, file!(), line!());
+                            DomainError::Repository(e.into())
+                        })
+                })
+            })
+            .await?;
 
         Ok(row.map(|r| r.into()))
     }
 
-    /// 最初にサインインに失敗した日時を保存する。
+    /// 最初にサインインに失敗した日時と、サインインに失敗したアクセス元を保存する。
     ///
     /// サインインに失敗した回数は1になる。
     ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
+    /// * `attempt` - サインインに失敗したアクセス元
     async fn record_first_sign_in_failed(
         &self,
         user_id: UserId,
+        attempt: &SignInAttempt,
     ) -> DomainResult<Option<UserCredential>> {
-        let mut tx = self.begin().await?;
-        let row = record_first_sign_in_failed_query(user_id)
-            .fetch_optional(&mut *tx)
-            .await
-            .map_err(|e| {
-                tracing::error!("{} ({}:{})", e, file!(), line!());
-                DomainError::Repository(e.into())
-            })?;
-        commit_transaction(tx).await?;
+        let query_timeout = self.query_timeout;
+        let attempt = attempt.clone();
+        let row = self
+            .with_transaction(move |tx| {
+                Box::pin(async move {
+                    record_first_sign_in_failed_query(user_id, &attempt)
+                        .fetch_optional(&mut **tx)
+                        .with_timeout(query_timeout)
+                        .await?
+                        .map_err(|e| {
+                            tracing::error!("{} ({}:{})", e, file!(), line!());
+                            DomainError::Repository(e.into())
+                        })
+                })
+            })
+            .await?;
 
         Ok(row.map(|r| r.into()))
     }
 
-    /// サインイン失敗回数をインクリメントする。
+    /// サインイン失敗回数をインクリメントして、サインインに失敗したアクセス元を保存する。
     ///
     /// # 引数
     ///
     /// * `user_id` - ユーザーID
+    /// * `attempt` - サインインに失敗したアクセス元
     ///
     /// # 戻り値
     ///
@@ -130,16 +267,24 @@ impl UserRepository for PgUserRepository {
     async fn increment_number_of_sign_in_failures(
         &self,
         user_id: UserId,
+        attempt: &SignInAttempt,
     ) -> DomainResult<Option<UserCredential>> {
-        let mut tx = self.begin().await?;
-        let row = increment_number_of_sign_in_failures_query(user_id)
-            .fetch_optional(&mut *tx)
-            .await
-            .map_err(|e| {
-                tracing::error!("{} ({}:{})", e, file!(), line!());
-                DomainError::Repository(e.into())
-            })?;
-        commit_transaction(tx).await?;
+        let query_timeout = self.query_timeout;
+        let attempt = attempt.clone();
+        let row = self
+            .with_transaction(move |tx| {
+                Box::pin(async move {
+                    increment_number_of_sign_in_failures_query(user_id, &attempt)
+                        .fetch_optional(&mut **tx)
+                        .with_timeout(query_timeout)
+                        .await?
+                        .map_err(|e| {
+                            tracing::error!("{} ({}:{})", e, file!(), line!());
+                            DomainError::Repository(e.into())
+                        })
+                })
+            })
+            .await?;
 
         Ok(row.map(|r| r.into()))
     }
@@ -151,9 +296,10 @@ impl UserRepository for PgUserRepository {
     /// * `user_id` - ユーザーID
     async fn lock_user_account(&self, user_id: UserId) -> DomainResult<()> {
         let mut tx = self.begin().await?;
-        let _ = set_active_query(user_id, false)
+        let _ = lock_user_account_query(user_id)
             .fetch_optional(&mut *tx)
-            .await
+            .with_timeout(self.query_timeout)
+            .await?
             .map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
                 DomainError::Repository(e.into())
@@ -170,9 +316,10 @@ impl UserRepository for PgUserRepository {
     /// * `user_id` - ユーザーID
     async fn unlock_user_account(&self, user_id: UserId) -> DomainResult<()> {
         let mut tx = self.begin().await?;
-        let _ = set_active_query(user_id, true)
+        let _ = unlock_user_account_query(user_id)
             .fetch_optional(&mut *tx)
-            .await
+            .with_timeout(self.query_timeout)
+            .await?
             .map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
                 DomainError::Repository(e.into())
@@ -182,6 +329,53 @@ impl UserRepository for PgUserRepository {
         Ok(())
     }
 
+    /// ロックした日時が指定した日時よりも過去のアカウントをアンロックする。
+    ///
+    /// # 引数
+    ///
+    /// * `before` - この日時よりも前にロックしたアカウントをアンロックする
+    ///
+    /// # 戻り値
+    ///
+    /// アンロックしたアカウントの数
+    async fn unlock_expired_accounts(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        let mut tx = self.begin().await?;
+        let result = unlock_expired_accounts_query(before)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// アカウントをロックしたユーザーのリストを取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `pagination` - アカウントをロックした日時の順で取得するページ
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントをロックしたユーザーを格納したベクタ
+    async fn list_locked(&self, pagination: Pagination) -> DomainResult<Vec<LockedUser>> {
+        list_locked_users_query(pagination)
+            .fetch_all(&self.pool)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?
+            .into_iter()
+            .map(LockedUser::try_from)
+            .collect::<_>()
+    }
+
     /// 最初にサインインに失敗した日時をNULL、サインイン失敗回数を0にする。
     ///
     /// # 引数
@@ -194,7 +388,8 @@ impl UserRepository for PgUserRepository {
         let mut tx = self.begin().await?;
         let row = clear_sign_in_failed_history_query(user_id)
             .fetch_optional(&mut *tx)
-            .await
+            .with_timeout(self.query_timeout)
+            .await?
             .map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
                 DomainError::Repository(e.into())
@@ -204,29 +399,363 @@ impl UserRepository for PgUserRepository {
         Ok(row.map(|r| r.into()))
     }
 
+    /// ユーザーのEメールアドレスを確認したことを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーが存在する場合は`true`、それ以外の場合は`false`
+    async fn verify_email(&self, user_id: UserId) -> DomainResult<bool> {
+        let mut tx = self.begin().await?;
+        let result = verify_email_query(user_id)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(0 < result.rows_affected())
+    }
+
+    /// ユーザーのEメールアドレスを変更する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `email` - 変更後のEメールアドレス
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーが存在する場合は`true`、それ以外の場合は`false`
+    async fn update_email(&self, user_id: UserId, email: EmailAddress) -> DomainResult<bool> {
+        let mut tx = self.begin().await?;
+        let result = update_email_query(user_id, email)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(0 < result.rows_affected())
+    }
+
+    /// ユーザーのパスワードを変更する。
+    ///
+    /// パスワードを変更するとともに、サインインに失敗した履歴と、パスワードの変更を要求していることを
+    /// 消去する。パスワードを変更した場合は、同じトランザクションで監査ログを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `password` - 変更後のパスワードをハッシュ化したPHC文字列
+    /// * `audit` - 記録する監査ログ
+    async fn update_password(
+        &self,
+        user_id: UserId,
+        password: PhcPassword,
+        audit: AuditLogInput,
+    ) -> DomainResult<()> {
+        let mut tx = self.begin().await?;
+        let result = update_password_query(user_id, password)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        if result.rows_affected() == 0 {
+            return Ok(());
+        }
+        insert_audit_log_query(audit)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(())
+    }
+
+    /// 管理者権限を持つユーザーの数を取得する。
+    ///
+    /// # 戻り値
+    ///
+    /// 管理者権限を持つユーザーの数
+    async fn count_admins(&self) -> DomainResult<u64> {
+        let count = count_admins_query()
+            .fetch_one(&self.pool)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+
+        Ok(count as u64)
+    }
+
+    /// ユーザーのユーザー権限を変更する。
+    ///
+    /// ユーザー権限を変更した場合は、同じトランザクションで監査ログを記録する。
+    /// 管理者以外に変更する場合は、管理者の行をロックしてから管理者の数を確認するため、同時に
+    /// 別の管理者のユーザー権限を変更した場合でも、管理者がいなくならない。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `user_permission_code` - 変更後のユーザー権限コード
+    /// * `audit` - 記録する監査ログ
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限を変更した結果
+    async fn update_permission(
+        &self,
+        user_id: UserId,
+        user_permission_code: UserPermissionCode,
+        audit: AuditLogInput,
+    ) -> DomainResult<GuardedChange> {
+        let mut tx = self.begin().await?;
+        if user_permission_code != UserPermissionCode::Admin
+            && is_last_admin(self, &mut tx, user_id).await?
+        {
+            return Ok(GuardedChange::LastAdmin);
+        }
+        let result = update_permission_query(user_id, user_permission_code)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        if result.rows_affected() == 0 {
+            return Ok(GuardedChange::NotFound);
+        }
+        insert_audit_log_query(audit)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        Ok(GuardedChange::Changed)
+    }
+
+    /// ユーザーのプロフィールを更新する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `version` - 更新前のユーザーのバージョン
+    /// * `profile` - 更新後のプロフィール
+    ///
+    /// # 戻り値
+    ///
+    /// 更新したユーザー、ユーザーが存在しないかバージョンが一致しない場合は`None`
+    async fn update_profile(
+        &self,
+        user_id: UserId,
+        version: i32,
+        profile: UpdateProfileInput,
+    ) -> DomainResult<Option<User>> {
+        let mut tx = self.begin().await?;
+        let row = update_profile_query(user_id, version, profile)
+            .fetch_optional(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        commit_transaction(tx).await?;
+
+        row.map(User::try_from).transpose()
+    }
+
+    /// ユーザーのアカウントを削除して、個人情報を消去する。
+    ///
+    /// ユーザーのTOTPの秘密鍵の削除と、監査ログの記録も、同じトランザクションで実行する。
+    /// 管理者の行をロックしてから管理者の数を確認するため、同時に別の管理者のアカウントを削除した
+    /// 場合でも、管理者がいなくならない。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `audit` - 記録する監査ログ、記録しない場合は`None`
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントを削除した結果
+    async fn soft_delete(
+        &self,
+        user_id: UserId,
+        audit: Option<AuditLogInput>,
+    ) -> DomainResult<GuardedChange> {
+        let mut tx = self.begin().await?;
+        if is_last_admin(self, &mut tx, user_id).await? {
+            return Ok(GuardedChange::LastAdmin);
+        }
+        let result = soft_delete_user_query(user_id)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        if result.rows_affected() == 0 {
+            return Ok(GuardedChange::NotFound);
+        }
+        delete_totp_secret_query(user_id)
+            .execute(&mut *tx)
+            .with_timeout(self.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        if let Some(audit) = audit {
+            insert_audit_log_query(audit)
+                .execute(&mut *tx)
+                .with_timeout(self.query_timeout)
+                .await?
+                .map_err(|e| {
+                    tracing::error!("{} ({}:{})", e, file!(), line!());
+                    DomainError::Repository(e.into())
+                })?;
+        }
+        commit_transaction(tx).await?;
+
+        Ok(GuardedChange::Changed)
+    }
+
+    /// 最初にサインインに失敗した日時が指定した日時よりも過去のサインインに失敗した履歴を消去する。
+    ///
+    /// 長時間ロックを保持しないように、`PURGE_BATCH_SIZE`件ずつ消去する。
+    async fn purge_sign_in_history_older_than(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        execute_in_batches(self, || {
+            purge_sign_in_history_query(before, PURGE_BATCH_SIZE)
+        })
+        .await
+    }
+
+    /// 削除した日時が指定した日時よりも過去のユーザーのレコードを物理的に削除する。
+    ///
+    /// 長時間ロックを保持しないように、`PURGE_BATCH_SIZE`件ずつ削除する。ユーザーのTOTPの
+    /// 秘密鍵は、外部キー制約によって削除される。
+    async fn purge_deleted_before(&self, before: OffsetDateTime) -> DomainResult<u64> {
+        execute_in_batches(self, || purge_deleted_users_query(before, PURGE_BATCH_SIZE)).await
+    }
+
     /// ユーザーを登録する。
     ///
     /// ユーザーを登録するとき、ユーザーの作成日時と更新日時は何らかの日時を設定する。
     /// 登録後に返されるユーザーの作成日時と更新日時の作成日時と更新日時には、データベースに登録
     /// した日時が設定されている。
     async fn create(&self, user: SignUpInput) -> DomainResult<SignUpOutput> {
+        let query_timeout = self.query_timeout;
+        let inserted_user = self
+            .with_transaction(move |tx| {
+                Box::pin(async move {
+                    insert_user_query(user)
+                        .fetch_one(&mut **tx)
+                        .with_timeout(query_timeout)
+                        .await?
+                        .map_err(|e| {
+                            tracing::error!("{} ({}:{})", e, file!(), line!());
+                            DomainError::Repository(e.into())
+                        })
+                })
+            })
+            .await?;
+
+        Ok(inserted_user.into())
+    }
+
+    /// 複数のユーザーを1つのトランザクションで登録する。
+    ///
+    /// ユーザーの監査ログは、ユーザーと同じセーブポイントで記録するため、ユーザーの登録を取り消した
+    /// 場合は、監査ログも記録しない。
+    async fn create_many(
+        &self,
+        users: Vec<SignUpInput>,
+        atomic: bool,
+        audit: AuditLogInput,
+    ) -> DomainResult<Vec<DomainResult<SignUpOutput>>> {
         let mut tx = self.begin().await?;
-        let inserted_user = insert_user_query(user)
-            .fetch_one(&mut *tx)
-            .await
-            .map_err(|e| {
+        let mut results = Vec::with_capacity(users.len());
+        for user in users {
+            // 登録に失敗したユーザーの変更のみを取り消すため、セーブポイントを設定
+            let mut savepoint = tx.begin().await.map_err(|e| {
                 tracing::error!("{} ({}:{})", e, file!(), line!());
                 DomainError::Repository(e.into())
             })?;
-        commit_transaction(tx).await?;
+            match insert_user_query(user)
+                .fetch_one(&mut *savepoint)
+                .with_timeout(self.query_timeout)
+                .await?
+            {
+                Ok(inserted_user) => {
+                    insert_audit_log_query(AuditLogInput {
+                        target_user_id: Some(inserted_user.id),
+                        ..audit.clone()
+                    })
+                    .execute(&mut *savepoint)
+                    .with_timeout(self.query_timeout)
+                    .await?
+                    .map_err(|e| {
+                        tracing::error!("{} ({}:{})", e, file!(), line!());
+                        DomainError::Repository(e.into())
+                    })?;
+                    commit_transaction(savepoint).await?;
+                    results.push(Ok(inserted_user.into()));
+                }
+                Err(e) => {
+                    savepoint.rollback().await.map_err(|e| {
+                        tracing::error!("{} ({}:{})", e, file!(), line!());
+                        DomainError::Repository(e.into())
+                    })?;
+                    results.push(Err(DomainError::Repository(e.into())));
+                }
+            }
+        }
+        if atomic && results.iter().any(|result| result.is_err()) {
+            tx.rollback().await.map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+        } else {
+            commit_transaction(tx).await?;
+        }
 
-        Ok(inserted_user.into())
+        Ok(results)
     }
 }
 
-#[derive(sqlx::FromRow)]
+/// ユーザーの行
+///
+/// 個人情報の列は、ユーザーを削除したときに消去するためNULLを許容する。ユーザーを取得するクエリは
+/// 削除していないユーザーのみを取得するため、`query_as!`マクロで列名に`!`を付けて、NULLを許容しない
+/// フィールドに格納する。
 pub struct RetrievedUserRow {
-    pub id: Uuid,
+    pub id: UserId,
     pub email: String,
     pub password: String,
     pub active: bool,
@@ -244,164 +773,725 @@ pub struct RetrievedUserRow {
     pub number_of_sign_in_failures: i16,
     pub created_at: OffsetDateTime,
     pub updated_at: OffsetDateTime,
+    pub version: i32,
 }
 
-impl From<RetrievedUserRow> for User {
-    fn from(row: RetrievedUserRow) -> Self {
-        Self {
-            id: UserId::new(row.id),
-            email: EmailAddress::new(row.email).unwrap(),
+/// データベースから取得した行からユーザーを構築する。
+///
+/// データベースに記録された値がドメインプリミティブの検証を満たさない場合は、パニックせずに
+/// リポジトリエラーを返す。
+impl TryFrom<RetrievedUserRow> for User {
+    type Error = DomainError;
+
+    fn try_from(row: RetrievedUserRow) -> Result<Self, Self::Error> {
+        let user_id = row.id;
+        build_user(row).map_err(|e| {
+            tracing::error!(
+                "can not build the user `{}` from the row: {} ({}:{})",
+                user_id,
+                e,
+                file!(),
+                line!()
+            );
+            DomainError::Repository(e.into())
+        })
+    }
+}
+
+fn build_user(row: RetrievedUserRow) -> DomainResult<User> {
+    Ok(User {
+        id: row.id,
+        email: EmailAddress::new(row.email)?,
+        active: row.active,
+        user_permission: UserPermission::new(
+            UserPermissionCode::try_from(row.user_permission_code)?,
+            UserPermissionName::new(row.user_permission_name)?,
+        ),
+        family_name: FamilyName::new(row.family_name)?,
+        given_name: GivenName::new(row.given_name)?,
+        postal_code: PostalCode::new(row.postal_code)?,
+        address: Address::new(row.address)?,
+        fixed_phone_number: OptionalFixedPhoneNumber::try_from(row.fixed_phone_number)?,
+        mobile_phone_number: OptionalMobilePhoneNumber::try_from(row.mobile_phone_number)?,
+        remarks: OptionalRemarks::try_from(row.remarks)?,
+        last_sign_in_at: row.last_sign_in_at,
+        sign_in_attempted_at: row.sign_in_attempted_at,
+        number_of_sign_in_failures: NumberOfSignInFailures::new(row.number_of_sign_in_failures)?,
+        created_at: row.created_at,
+        updated_at: row.updated_at,
+        version: row.version,
+    })
+}
+
+/// ユーザーのリストを取得するクエリを実行して、ユーザーの行を1行ずつ返すストリームを返す。
+///
+/// `query_as!`マクロはコンパイル時にSQLを検査するため、並び順ごとに`ORDER BY`句を埋め込んだSQLを
+/// 選択する。`ORDER BY`句は、並び順ごとに定めた文字列から選択するため、ユーザーの入力を含まない。
+///
+/// # 引数
+///
+/// * `executor` - クエリを実行するエグゼキューター
+/// * `filter` - ユーザーリストの絞り込み条件
+/// * `pattern` - `contains_pattern`で生成した検索パターン、ユーザーを検索しない場合は`None`
+///
+/// # 戻り値
+///
+/// ユーザーの行を1行ずつ返すストリーム
+pub fn list_users_rows<'e, E>(
+    executor: E,
+    filter: UserListFilter,
+    pattern: Option<String>,
+) -> BoxStream<'e, Result<RetrievedUserRow, sqlx::Error>>
+where
+    E: sqlx::Executor<'e, Database = Postgres> + 'e,
+{
+    let active = filter.active;
+    let user_permission_code = filter.user_permission_code.map(|code| code as i16);
+    let limit = filter.pagination.limit.map(i64::from);
+    let offset = i64::from(filter.pagination.offset);
+
+    // 検索しない場合に、トライグラムのインデックスを使用しない実行計画を作成するように、条件を付けない
+    macro_rules! list_users_ordered_by {
+        ($order_by:literal) => {
+            match pattern {
+                Some(pattern) => sqlx::query_as!(
+                    RetrievedUserRow,
+                    r#"
+        SELECT
+            u.id AS "id: _", u.email, u.password, u.active, u.user_permission_code,
+            p.name AS user_permission_name, u.family_name AS "family_name!",
+            u.given_name AS "given_name!", u.postal_code AS "postal_code!",
+            u.address AS "address!", u.fixed_phone_number, u.mobile_phone_number, u.remarks,
+            u.last_sign_in_at, u.sign_in_attempted_at, u.number_of_sign_in_failures,
+            u.created_at, u.updated_at, u.version
+        FROM users u
+        INNER JOIN user_permissions p ON u.user_permission_code = p.code
+        WHERE
+            u.deleted_at IS NULL
+            AND ($1::BOOLEAN IS NULL OR u.active = $1)
+            AND ($2::SMALLINT IS NULL OR u.user_permission_code = $2)
+            AND (
+                u.family_name ILIKE $5 ESCAPE '\'
+                OR u.given_name ILIKE $5 ESCAPE '\'
+                OR u.email ILIKE $5 ESCAPE '\'
+            )
+        ORDER BY "#
+                        + $order_by
+                        + r#"
+        LIMIT $3
+        OFFSET $4
+        "#,
+                    active,
+                    user_permission_code,
+                    limit,
+                    offset,
+                    pattern,
+                )
+                .fetch(executor),
+                None => sqlx::query_as!(
+                    RetrievedUserRow,
+                    r#"
+        SELECT
+            u.id AS "id: _", u.email, u.password, u.active, u.user_permission_code,
+            p.name AS user_permission_name, u.family_name AS "family_name!",
+            u.given_name AS "given_name!", u.postal_code AS "postal_code!",
+            u.address AS "address!", u.fixed_phone_number, u.mobile_phone_number, u.remarks,
+            u.last_sign_in_at, u.sign_in_attempted_at, u.number_of_sign_in_failures,
+            u.created_at, u.updated_at, u.version
+        FROM users u
+        INNER JOIN user_permissions p ON u.user_permission_code = p.code
+        WHERE
+            u.deleted_at IS NULL
+            AND ($1::BOOLEAN IS NULL OR u.active = $1)
+            AND ($2::SMALLINT IS NULL OR u.user_permission_code = $2)
+        ORDER BY "#
+                        + $order_by
+                        + r#"
+        LIMIT $3
+        OFFSET $4
+        "#,
+                    active,
+                    user_permission_code,
+                    limit,
+                    offset,
+                )
+                .fetch(executor),
+            }
+        };
+    }
+
+    match filter.order {
+        UserListOrder::CreatedAt(SortDirection::Asc) => {
+            list_users_ordered_by!("u.created_at ASC, u.id ASC")
+        }
+        UserListOrder::CreatedAt(SortDirection::Desc) => {
+            list_users_ordered_by!("u.created_at DESC, u.id DESC")
+        }
+        UserListOrder::Email(SortDirection::Asc) => list_users_ordered_by!("u.email ASC"),
+        UserListOrder::Email(SortDirection::Desc) => list_users_ordered_by!("u.email DESC"),
+        UserListOrder::LastSignInAt(SortDirection::Asc) => {
+            list_users_ordered_by!("u.last_sign_in_at ASC NULLS LAST, u.created_at ASC, u.id ASC")
+        }
+        UserListOrder::LastSignInAt(SortDirection::Desc) => {
+            list_users_ordered_by!("u.last_sign_in_at DESC NULLS LAST, u.created_at ASC, u.id ASC")
+        }
+    }
+}
+
+/// `LIKE`のパターンで特別な意味を持つ`\`、`%`及び`_`をエスケープして、文字列を含むパターンを生成する。
+///
+/// # 引数
+///
+/// * `value` - 検索文字列
+///
+/// # 戻り値
+///
+/// 検索文字列を含む値に一致するパターン
+pub fn contains_pattern(value: &str) -> String {
+    let mut pattern = String::with_capacity(value.len() + 2);
+    pattern.push('%');
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern.push('%');
+
+    pattern
+}
+
+/// ユーザーIDを元にユーザーを取得するクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// ユーザーIDを元にユーザーを取得するクエリ
+pub fn user_by_id_query<'q>(
+    user_id: UserId,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<RetrievedUserRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        RetrievedUserRow,
+        r#"
+        SELECT
+            u.id AS "id: _", u.email, u.password, u.active, u.user_permission_code,
+            p.name AS user_permission_name, u.family_name AS "family_name!",
+            u.given_name AS "given_name!", u.postal_code AS "postal_code!",
+            u.address AS "address!", u.fixed_phone_number, u.mobile_phone_number, u.remarks,
+            u.last_sign_in_at, u.sign_in_attempted_at, u.number_of_sign_in_failures,
+            u.created_at, u.updated_at, u.version
+        FROM users u
+        INNER JOIN user_permissions p ON u.user_permission_code = p.code
+        WHERE u.id = $1 AND u.deleted_at IS NULL
+        "#,
+        user_id as _,
+    )
+}
+
+/// Eメールアドレスを元にユーザーを取得するクエリを生成する。
+///
+/// # 引数
+///
+/// * `email` - ユーザーのEメールアドレス
+///
+/// # 戻り値
+///
+/// Eメールアドレスを元にユーザーを取得するクエリ
+pub fn user_by_email_query<'q>(
+    email: EmailAddress,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<RetrievedUserRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        RetrievedUserRow,
+        r#"
+        SELECT
+            u.id AS "id: _", u.email, u.password, u.active, u.user_permission_code,
+            p.name AS user_permission_name, u.family_name AS "family_name!",
+            u.given_name AS "given_name!", u.postal_code AS "postal_code!",
+            u.address AS "address!", u.fixed_phone_number, u.mobile_phone_number, u.remarks,
+            u.last_sign_in_at, u.sign_in_attempted_at, u.number_of_sign_in_failures,
+            u.created_at, u.updated_at, u.version
+        FROM users u
+        INNER JOIN user_permissions p ON u.user_permission_code = p.code
+        WHERE u.email = $1 AND u.deleted_at IS NULL
+        "#,
+        email.value,
+    )
+}
+
+/// アカウントをロックしたユーザーの行
+///
+/// `query_as!`マクロは構造体を入れ子にできないため、ユーザーの列とアカウントをロックした日時を
+/// 同じ構造体に格納する。
+pub struct LockedUserRow {
+    pub id: UserId,
+    pub email: String,
+    pub password: String,
+    pub active: bool,
+    pub user_permission_code: i16,
+    pub user_permission_name: String,
+    pub family_name: String,
+    pub given_name: String,
+    pub postal_code: String,
+    pub address: String,
+    pub fixed_phone_number: Option<String>,
+    pub mobile_phone_number: Option<String>,
+    pub remarks: Option<String>,
+    pub last_sign_in_at: Option<OffsetDateTime>,
+    pub sign_in_attempted_at: Option<OffsetDateTime>,
+    pub number_of_sign_in_failures: i16,
+    pub created_at: OffsetDateTime,
+    pub updated_at: OffsetDateTime,
+    pub version: i32,
+    pub locked_at: OffsetDateTime,
+}
+
+impl TryFrom<LockedUserRow> for LockedUser {
+    type Error = DomainError;
+
+    fn try_from(row: LockedUserRow) -> Result<Self, Self::Error> {
+        let user = RetrievedUserRow {
+            id: row.id,
+            email: row.email,
+            password: row.password,
             active: row.active,
-            user_permission: UserPermission::new(
-                UserPermissionCode::try_from(row.user_permission_code).unwrap(),
-                UserPermissionName::new(row.user_permission_name).unwrap(),
-            ),
-            family_name: FamilyName::new(row.family_name).unwrap(),
-            given_name: GivenName::new(row.given_name).unwrap(),
-            postal_code: PostalCode::new(row.postal_code).unwrap(),
-            address: Address::new(row.address).unwrap(),
-            fixed_phone_number: OptionalFixedPhoneNumber::try_from(row.fixed_phone_number).unwrap(),
-            mobile_phone_number: OptionalMobilePhoneNumber::try_from(row.mobile_phone_number)
-                .unwrap(),
-            remarks: OptionalRemarks::try_from(row.remarks).unwrap(),
+            user_permission_code: row.user_permission_code,
+            user_permission_name: row.user_permission_name,
+            family_name: row.family_name,
+            given_name: row.given_name,
+            postal_code: row.postal_code,
+            address: row.address,
+            fixed_phone_number: row.fixed_phone_number,
+            mobile_phone_number: row.mobile_phone_number,
+            remarks: row.remarks,
             last_sign_in_at: row.last_sign_in_at,
             sign_in_attempted_at: row.sign_in_attempted_at,
-            number_of_sign_in_failures: NumberOfSignInFailures::new(row.number_of_sign_in_failures)
-                .unwrap(),
+            number_of_sign_in_failures: row.number_of_sign_in_failures,
             created_at: row.created_at,
             updated_at: row.updated_at,
+            version: row.version,
+        };
+
+        Ok(Self {
+            user: user.try_into()?,
+            locked_at: row.locked_at,
+        })
+    }
+}
+
+/// アカウントをロックしたユーザーのリストを取得するクエリを生成する。
+///
+/// アクティブフラグが`false`で、アカウントをロックした日時が記録されている、削除していない
+/// ユーザーを、アカウントをロックした日時の順に取得する。
+///
+/// # 引数
+///
+/// * `pagination` - アカウントをロックした日時の順で取得するページ
+///
+/// # 戻り値
+///
+/// アカウントをロックしたユーザーのリストを取得するクエリ
+pub fn list_locked_users_query<'q>(
+    pagination: Pagination,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<LockedUserRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        LockedUserRow,
+        r#"
+        SELECT
+            u.id AS "id: _", u.email, u.password, u.active, u.user_permission_code,
+            p.name AS user_permission_name, u.family_name AS "family_name!",
+            u.given_name AS "given_name!", u.postal_code AS "postal_code!",
+            u.address AS "address!", u.fixed_phone_number, u.mobile_phone_number, u.remarks,
+            u.last_sign_in_at, u.sign_in_attempted_at, u.number_of_sign_in_failures,
+            u.created_at, u.updated_at, u.version,
+            u.locked_at AS "locked_at!"
+        FROM users u
+        INNER JOIN user_permissions p ON u.user_permission_code = p.code
+        WHERE
+            u.active = FALSE
+            AND u.locked_at IS NOT NULL
+            AND u.deleted_at IS NULL
+        ORDER BY u.locked_at ASC, u.created_at ASC, u.id ASC
+        LIMIT $1
+        OFFSET $2
+        "#,
+        pagination.limit.map(i64::from),
+        i64::from(pagination.offset),
+    )
+}
+
+/// ユーザークレデンシャルの行
+///
+/// `query_as!`マクロは列名をフィールド名に対応付けるため、クエリで列に別名を付けて、フィールド名と
+/// 一致させる。
+pub struct UserCredentialRow {
+    pub user_id: UserId,
+    pub email: String,
+    pub password: String,
+    pub active: bool,
+    pub user_permission_code: i16,
+    pub attempted_at: Option<OffsetDateTime>,
+    pub number_of_failures: i16,
+    pub locked_at: Option<OffsetDateTime>,
+    pub email_verified_at: Option<OffsetDateTime>,
+    pub password_change_required: bool,
+}
+
+impl From<UserCredentialRow> for UserCredential {
+    fn from(row: UserCredentialRow) -> Self {
+        Self {
+            user_id: row.user_id,
+            email: EmailAddress::new(row.email).unwrap(),
+            password: PhcPassword::new(SecretString::new(row.password)).unwrap(),
+            active: row.active,
+            user_permission_code: UserPermissionCode::try_from(row.user_permission_code).unwrap(),
+            attempted_at: row.attempted_at,
+            number_of_failures: NumberOfSignInFailures::new(row.number_of_failures).unwrap(),
+            locked_at: row.locked_at,
+            email_verified_at: row.email_verified_at,
+            password_change_required: row.password_change_required,
         }
     }
 }
 
-/// ユーザーのリストを取得するクエリを生成する。
+/// ユーザーが最後にサインインした日時を更新した結果の行
+///
+/// `query_as!`マクロは構造体を入れ子にできないため、ユーザークレデンシャルの列と更新する前に
+/// 最後にサインインした日時を同じ構造体に格納する。
+pub struct SignInRecordRow {
+    pub user_id: UserId,
+    pub email: String,
+    pub password: String,
+    pub active: bool,
+    pub user_permission_code: i16,
+    pub attempted_at: Option<OffsetDateTime>,
+    pub number_of_failures: i16,
+    pub locked_at: Option<OffsetDateTime>,
+    pub email_verified_at: Option<OffsetDateTime>,
+    pub password_change_required: bool,
+    pub previous_sign_in_at: Option<OffsetDateTime>,
+}
+
+impl From<SignInRecordRow> for SignInRecord {
+    fn from(row: SignInRecordRow) -> Self {
+        let credential = UserCredentialRow {
+            user_id: row.user_id,
+            email: row.email,
+            password: row.password,
+            active: row.active,
+            user_permission_code: row.user_permission_code,
+            attempted_at: row.attempted_at,
+            number_of_failures: row.number_of_failures,
+            locked_at: row.locked_at,
+            email_verified_at: row.email_verified_at,
+            password_change_required: row.password_change_required,
+        };
+
+        Self {
+            credential: credential.into(),
+            previous_sign_in_at: row.previous_sign_in_at,
+        }
+    }
+}
+
+/// ユーザークレデンシャルを取得するクエリを生成する。
+///
+/// # 引数
+///
+/// * `email` - ユーザークレデンシャルを取得するユーザーのEメール・アドレス
+///
+/// # 戻り値
+///
+/// ユーザークレデンシャルを取得するクエリ
+pub fn user_credential_query<'q>(
+    email: EmailAddress,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<UserCredentialRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        UserCredentialRow,
+        r#"
+        SELECT
+            id AS "user_id: _", email, password, active, user_permission_code,
+            sign_in_attempted_at AS attempted_at, number_of_sign_in_failures AS number_of_failures,
+            locked_at, email_verified_at, password_change_required
+        FROM
+            users
+        WHERE
+            email = $1
+            AND deleted_at IS NULL
+        "#,
+        email.value,
+    )
+}
+
+/// ユーザーIDでユーザークレデンシャルを取得するクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザークレデンシャルを取得するユーザーのユーザーID
+///
+/// # 戻り値
+///
+/// ユーザークレデンシャルを取得するクエリ
+pub fn credential_by_id_query<'q>(
+    user_id: UserId,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<UserCredentialRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        UserCredentialRow,
+        r#"
+        SELECT
+            id AS "user_id: _", email, password, active, user_permission_code,
+            sign_in_attempted_at AS attempted_at, number_of_sign_in_failures AS number_of_failures,
+            locked_at, email_verified_at, password_change_required
+        FROM
+            users
+        WHERE
+            id = $1
+            AND deleted_at IS NULL
+        "#,
+        user_id as _,
+    )
+}
+
+/// サインインした日時を現在の日時、最初にサインインに失敗した日時をNULL、そしてサインイン失敗回数を0にするクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - 最後にサインインした日時を更新するユーザー
+///
+/// # 戻り値
+///
+/// 更新後のユーザークレデンシャルと、更新する前に最後にサインインした日時を返すクエリ
+pub fn update_last_sign_in_at_query<'q>(
+    user_id: UserId,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<SignInRecordRow, sqlx::Error> + Send> {
+    // 更新する前の最後にサインインした日時を、行をロックして取得するため、同時にサインインした場合は、
+    // 後から更新するサインインが、先に更新したサインインの日時を取得する
+    sqlx::query_as!(
+        SignInRecordRow,
+        r#"
+        UPDATE
+            users u
+        SET
+            last_sign_in_at = CURRENT_TIMESTAMP,
+            sign_in_attempted_at = NULL,
+            number_of_sign_in_failures = 0
+        FROM (
+            SELECT id, last_sign_in_at
+            FROM users
+            WHERE id = $1 AND deleted_at IS NULL
+            FOR UPDATE
+        ) previous
+        WHERE
+            u.id = previous.id
+        RETURNING
+            u.id AS "user_id: _", u.email, u.password, u.active, u.user_permission_code,
+            u.sign_in_attempted_at AS attempted_at,
+            u.number_of_sign_in_failures AS number_of_failures, u.locked_at,
+            u.email_verified_at, u.password_change_required,
+            previous.last_sign_in_at AS previous_sign_in_at
+        "#,
+        user_id as _,
+    )
+}
+
+/// 最初にサインインに失敗したことを保存するクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+/// * `attempt` - サインインに失敗したアクセス元
 ///
 /// # 戻り値
 ///
-/// ユーザーの一覧を取得するクエリ
-pub fn list_users_query<'q>() -> PgQueryAs<'q, RetrievedUserRow> {
-    sqlx::query_as::<Postgres, RetrievedUserRow>(
+/// 最初にサインインに失敗したことを保存するクエリ
+pub fn record_first_sign_in_failed_query<'q>(
+    user_id: UserId,
+    attempt: &SignInAttempt,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<UserCredentialRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        UserCredentialRow,
         r#"
-        SELECT
-            u.id, u.email, u.password, u.active, u.user_permission_code, p.name
-            user_permission_name, u.family_name, u.given_name, u.postal_code, u.address,
-            u.fixed_phone_number, u.mobile_phone_number, u.remarks, u.last_sign_in_at,
-            u.sign_in_attempted_at, u.number_of_sign_in_failures, u.created_at,
-            u.updated_at
-        FROM users u
-        INNER JOIN user_permissions p ON u.user_permission_code = p.code
-        ORDER BY created_at
-    "#,
+        UPDATE
+            users
+        SET
+            sign_in_attempted_at = CURRENT_TIMESTAMP,
+            number_of_sign_in_failures = 1,
+            last_failed_sign_in_ip_address = $2,
+            last_failed_sign_in_user_agent = $3
+        WHERE
+            id = $1
+            AND deleted_at IS NULL
+        RETURNING
+            id AS "user_id: _", email, password, active, user_permission_code,
+            sign_in_attempted_at AS attempted_at, number_of_sign_in_failures AS number_of_failures,
+            locked_at, email_verified_at, password_change_required
+        "#,
+        user_id as _,
+        attempt.client_ip.map(|ip| ip.to_string()),
+        attempt.user_agent.clone(),
     )
 }
 
-/// ユーザーIDを元にユーザーを取得するクエリを生成する。
+/// サインイン失敗回数をインクリメントするクエリを生成する。
 ///
 /// # 引数
 ///
 /// * `user_id` - ユーザーID
+/// * `attempt` - サインインに失敗したアクセス元
 ///
 /// # 戻り値
 ///
-/// ユーザーIDを元にユーザーを取得するクエリ
-pub fn user_by_id_query<'q>(user_id: UserId) -> PgQueryAs<'q, RetrievedUserRow> {
-    sqlx::query_as::<Postgres, RetrievedUserRow>(
+/// サインイン失敗回数をインクリメントするクエリ
+pub fn increment_number_of_sign_in_failures_query<'q>(
+    user_id: UserId,
+    attempt: &SignInAttempt,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<UserCredentialRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        UserCredentialRow,
         r#"
-        SELECT
-            u.id, u.email, u.password, u.active, u.user_permission_code, p.name
-            user_permission_name, u.family_name, u.given_name, u.postal_code, u.address,
-            u.fixed_phone_number, u.mobile_phone_number, u.remarks, u.last_sign_in_at,
-            u.sign_in_attempted_at, u.number_of_sign_in_failures, u.created_at,
-            u.updated_at
-        FROM users u
-        INNER JOIN user_permissions p ON u.user_permission_code = p.code
-        WHERE u.id = $1
+        UPDATE
+            users
+        SET
+            number_of_sign_in_failures = number_of_sign_in_failures + 1,
+            last_failed_sign_in_ip_address = $2,
+            last_failed_sign_in_user_agent = $3
+        WHERE
+            id = $1
+            AND deleted_at IS NULL
+        RETURNING
+            id AS "user_id: _", email, password, active, user_permission_code,
+            sign_in_attempted_at AS attempted_at, number_of_sign_in_failures AS number_of_failures,
+            locked_at, email_verified_at, password_change_required
         "#,
+        user_id as _,
+        attempt.client_ip.map(|ip| ip.to_string()),
+        attempt.user_agent.clone(),
     )
-    .bind(user_id.value)
 }
 
-#[derive(sqlx::FromRow)]
-pub struct UserCredentialRow {
-    #[sqlx(rename = "id")]
-    pub user_id: Uuid,
-    pub email: String,
-    pub password: String,
-    pub active: bool,
-    pub user_permission_code: i16,
-    #[sqlx(rename = "sign_in_attempted_at")]
-    pub attempted_at: Option<OffsetDateTime>,
-    #[sqlx(rename = "number_of_sign_in_failures")]
-    pub number_of_failures: i16,
+/// ユーザーのアカウントをロックするクエリを生成する。
+///
+/// アクティブフラグを`false`、アカウントをロックした日時を現在の日時にする。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// ユーザーのアカウントをロックするクエリ
+pub fn lock_user_account_query<'q>(user_id: UserId) -> PgQuery<'q> {
+    sqlx::query!(
+        r#"
+        UPDATE
+            users
+        SET
+            active = FALSE,
+            locked_at = CURRENT_TIMESTAMP
+        WHERE
+            id = $1
+            AND deleted_at IS NULL
+        "#,
+        user_id as _,
+    )
 }
 
-impl From<UserCredentialRow> for UserCredential {
-    fn from(row: UserCredentialRow) -> Self {
-        Self {
-            user_id: UserId::new(row.user_id),
-            email: EmailAddress::new(row.email).unwrap(),
-            password: PhcPassword::new(SecretString::new(row.password)).unwrap(),
-            active: row.active,
-            user_permission_code: UserPermissionCode::try_from(row.user_permission_code).unwrap(),
-            attempted_at: row.attempted_at,
-            number_of_failures: row.number_of_failures,
-        }
-    }
+/// ユーザーのアカウントをアンロックするクエリを生成する。
+///
+/// アクティブフラグを`true`、アカウントをロックした日時をNULLにする。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// ユーザーのアカウントをアンロックするクエリ
+pub fn unlock_user_account_query<'q>(user_id: UserId) -> PgQuery<'q> {
+    sqlx::query!(
+        r#"
+        UPDATE
+            users
+        SET
+            active = TRUE,
+            locked_at = NULL
+        WHERE
+            id = $1
+            AND deleted_at IS NULL
+        "#,
+        user_id as _,
+    )
 }
 
-/// ユーザークレデンシャルを取得するクエリを生成する。
+/// ロックした日時が指定した日時よりも過去のアカウントをアンロックするクエリを生成する。
+///
+/// アンロックしたアカウントは、最初にサインインに失敗した日時をNULL、サインイン失敗回数を0にする。
+/// ロックした日時が記録されていない非アクティブなアカウントは、アンロックしない。
 ///
 /// # 引数
 ///
-/// * `email` - ユーザークレデンシャルを取得するユーザーのEメール・アドレス
+/// * `before` - この日時よりも前にロックしたアカウントをアンロックする
 ///
 /// # 戻り値
 ///
-/// ユーザークレデンシャルを取得するクエリ
-pub fn user_credential_query<'q>(email: EmailAddress) -> PgQueryAs<'q, UserCredentialRow> {
-    sqlx::query_as::<Postgres, UserCredentialRow>(
+/// ロックした日時が指定した日時よりも過去のアカウントをアンロックするクエリ
+pub fn unlock_expired_accounts_query<'q>(before: OffsetDateTime) -> PgQuery<'q> {
+    sqlx::query!(
         r#"
-        SELECT
-            id, email, password, active, user_permission_code,
-            sign_in_attempted_at, number_of_sign_in_failures
-        FROM
+        UPDATE
             users
+        SET
+            active = TRUE,
+            locked_at = NULL,
+            sign_in_attempted_at = NULL,
+            number_of_sign_in_failures = 0
         WHERE
-            email = $1
+            active = FALSE
+            AND locked_at IS NOT NULL
+            AND locked_at < $1
+            AND deleted_at IS NULL
         "#,
+        before,
     )
-    .bind(email.value)
 }
 
-/// サインインした日時を現在の日時、最初にサインインに失敗した日時をNULL、そしてサインイン失敗回数を0にするクエリを生成する。
+/// 最初にサインインに失敗した日時をNULL、サインイン失敗回数を0にするクエリを生成する。
 ///
 /// # 引数
 ///
-/// * `user_id` - 最後にサインインした日時を更新するユーザー
+/// * `user_id` - ユーザーID
 ///
 /// # 戻り値
 ///
-/// 更新日時
-pub fn update_last_sign_in_at_query<'q>(user_id: UserId) -> PgQueryAs<'q, UserCredentialRow> {
-    sqlx::query_as::<Postgres, UserCredentialRow>(
+/// 最初にサインインに失敗したことを保存するクエリ
+pub fn clear_sign_in_failed_history_query<'q>(
+    user_id: UserId,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<UserCredentialRow, sqlx::Error> + Send> {
+    sqlx::query_as!(
+        UserCredentialRow,
         r#"
         UPDATE
             users
         SET
-            last_sign_in_at = CURRENT_TIMESTAMP,
             sign_in_attempted_at = NULL,
             number_of_sign_in_failures = 0
         WHERE
             id = $1
+            AND deleted_at IS NULL
         RETURNING
-            id, email, password, active, user_permission_code,
-            sign_in_attempted_at, number_of_sign_in_failures
+            id AS "user_id: _", email, password, active, user_permission_code,
+            sign_in_attempted_at AS attempted_at, number_of_sign_in_failures AS number_of_failures,
+            locked_at, email_verified_at, password_change_required
         "#,
+        user_id as _,
     )
-    .bind(user_id.value)
 }
 
-/// 最初にサインインに失敗したことを保存するクエリを生成する。
+/// ユーザーのEメールアドレスを確認したことを記録するクエリを生成する。
+///
+/// Eメールアドレスを確認した日時が記録されていない場合のみ、現在の日時を記録する。
 ///
 /// # 引数
 ///
@@ -409,108 +1499,423 @@ pub fn update_last_sign_in_at_query<'q>(user_id: UserId) -> PgQueryAs<'q, UserCr
 ///
 /// # 戻り値
 ///
-/// 最初にサインインに失敗したことを保存するクエリ
-pub fn record_first_sign_in_failed_query<'q>(user_id: UserId) -> PgQueryAs<'q, UserCredentialRow> {
-    sqlx::query_as::<Postgres, UserCredentialRow>(
+/// ユーザーのEメールアドレスを確認したことを記録するクエリ
+pub fn verify_email_query<'q>(user_id: UserId) -> PgQuery<'q> {
+    sqlx::query!(
         r#"
         UPDATE
             users
         SET
-            sign_in_attempted_at = CURRENT_TIMESTAMP,
-            number_of_sign_in_failures = 1
+            email_verified_at = COALESCE(email_verified_at, CURRENT_TIMESTAMP)
         WHERE
             id = $1
-        RETURNING
-            id, email, password, active, user_permission_code,
-            sign_in_attempted_at, number_of_sign_in_failures
+            AND deleted_at IS NULL
         "#,
+        user_id as _,
     )
-    .bind(user_id.value)
 }
 
-/// サインイン失敗回数をインクリメントするクエリを生成する。
+/// ユーザーのEメールアドレスを変更するクエリを生成する。
+///
+/// 変更後のEメールアドレスは確認済みとして、現在の日時を確認した日時として記録する。
 ///
 /// # 引数
 ///
 /// * `user_id` - ユーザーID
+/// * `email` - 変更後のEメールアドレス
 ///
 /// # 戻り値
 ///
-/// サインイン失敗回数をインクリメントするクエリ
-pub fn increment_number_of_sign_in_failures_query<'q>(
+/// ユーザーのEメールアドレスを変更するクエリ
+pub fn update_email_query<'q>(user_id: UserId, email: EmailAddress) -> PgQuery<'q> {
+    sqlx::query!(
+        r#"
+        UPDATE
+            users
+        SET
+            email = $2,
+            email_verified_at = CURRENT_TIMESTAMP,
+            version = version + 1,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            id = $1
+            AND deleted_at IS NULL
+        "#,
+        user_id as _,
+        email.value,
+    )
+}
+
+/// ユーザーのパスワードを変更するクエリを生成する。
+///
+/// パスワードを変更するとともに、サインインに失敗した履歴と、パスワードの変更を要求していることを
+/// 消去する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+/// * `password` - 変更後のパスワードをハッシュ化したPHC文字列
+///
+/// # 戻り値
+///
+/// ユーザーのパスワードを変更するクエリ
+pub fn update_password_query<'q>(user_id: UserId, password: PhcPassword) -> PgQuery<'q> {
+    sqlx::query!(
+        r#"
+        UPDATE
+            users
+        SET
+            password = $2,
+            sign_in_attempted_at = NULL,
+            number_of_sign_in_failures = 0,
+            password_change_required = FALSE,
+            version = version + 1,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE
+            id = $1
+            AND deleted_at IS NULL
+        "#,
+        user_id as _,
+        password.value.expose_secret().to_string(),
+    )
+}
+
+/// ユーザーのプロフィールを更新するクエリを生成する。
+///
+/// ユーザーのバージョンが一致する場合のみ更新して、バージョンを1増やす。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+/// * `version` - 更新前のユーザーのバージョン
+/// * `profile` - 更新後のプロフィール
+///
+/// # 戻り値
+///
+/// ユーザーのプロフィールを更新して、更新したユーザーを返すクエリ
+pub fn update_profile_query<'q>(
+    user_id: UserId,
+    version: i32,
+    profile: UpdateProfileInput,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<RetrievedUserRow, sqlx::Error> + Send> {
+    // 共通テーブル式の列はNULLを許容するか推論されないため、すべての列に`!`を付ける
+    sqlx::query_as!(
+        RetrievedUserRow,
+        r#"
+        WITH u AS (
+            UPDATE
+                users
+            SET
+                family_name = $3,
+                given_name = $4,
+                postal_code = $5,
+                address = $6,
+                fixed_phone_number = $7,
+                mobile_phone_number = $8,
+                remarks = $9,
+                version = version + 1,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE
+                id = $1
+                AND version = $2
+                AND deleted_at IS NULL
+            RETURNING *
+        )
+        SELECT
+            u.id AS "id!: _", u.email AS "email!", u.password AS "password!",
+            u.active AS "active!", u.user_permission_code AS "user_permission_code!",
+            p.name AS "user_permission_name!", u.family_name AS "family_name!",
+            u.given_name AS "given_name!", u.postal_code AS "postal_code!",
+            u.address AS "address!", u.fixed_phone_number, u.mobile_phone_number, u.remarks,
+            u.last_sign_in_at, u.sign_in_attempted_at,
+            u.number_of_sign_in_failures AS "number_of_sign_in_failures!",
+            u.created_at AS "created_at!", u.updated_at AS "updated_at!", u.version AS "version!"
+        FROM u
+        INNER JOIN user_permissions p ON u.user_permission_code = p.code
+        "#,
+        user_id as _,
+        version,
+        profile.family_name.value,
+        profile.given_name.value,
+        profile.postal_code.value,
+        profile.address.value,
+        profile.fixed_phone_number.owned_value(),
+        profile.mobile_phone_number.owned_value(),
+        profile.remarks.owned_value(),
+    )
+}
+
+/// 管理者権限を持つユーザーの数を取得するクエリを生成する。
+///
+/// # 戻り値
+///
+/// 管理者権限を持つユーザーの数を取得するクエリ
+pub fn count_admins_query<'q>() -> PgQueryScalar<'q, i64> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT
+            COUNT(*) AS "count!"
+        FROM
+            users
+        WHERE
+            user_permission_code = $1
+            AND deleted_at IS NULL
+        "#,
+        UserPermissionCode::Admin as i16,
+    )
+}
+
+/// 削除していない管理者の行をロックして、管理者のユーザーIDを取得するクエリを生成する。
+///
+/// 同時に実行したトランザクションがデッドロックしないように、ユーザーIDの順に行をロックする。
+///
+/// # 戻り値
+///
+/// 削除していない管理者の行をロックして、管理者のユーザーIDを取得するクエリ
+pub fn lock_admins_query<'q>() -> PgQueryScalar<'q, UserId> {
+    sqlx::query_scalar!(
+        r#"
+        SELECT
+            id AS "id: UserId"
+        FROM
+            users
+        WHERE
+            user_permission_code = $1
+            AND deleted_at IS NULL
+        ORDER BY
+            id
+        FOR UPDATE
+        "#,
+        UserPermissionCode::Admin as i16,
+    )
+}
+
+/// ユーザーのユーザー権限を変更するクエリを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+/// * `user_permission_code` - 変更後のユーザー権限コード
+///
+/// # 戻り値
+///
+/// ユーザーのユーザー権限を変更するクエリ
+pub fn update_permission_query<'q>(
     user_id: UserId,
-) -> PgQueryAs<'q, UserCredentialRow> {
-    sqlx::query_as::<Postgres, UserCredentialRow>(
+    user_permission_code: UserPermissionCode,
+) -> PgQuery<'q> {
+    sqlx::query!(
         r#"
         UPDATE
             users
         SET
-            number_of_sign_in_failures = number_of_sign_in_failures + 1
+            user_permission_code = $2,
+            version = version + 1,
+            updated_at = CURRENT_TIMESTAMP
         WHERE
             id = $1
-        RETURNING
-            id, email, password, active, user_permission_code,
-            sign_in_attempted_at, number_of_sign_in_failures
+            AND deleted_at IS NULL
         "#,
+        user_id as _,
+        user_permission_code as i16,
     )
-    .bind(user_id.value)
 }
 
-/// アクティブフラグを更新するクエリを生成する。
+/// ユーザーのアカウントを削除して、個人情報を消去するクエリを生成する。
+///
+/// Eメールアドレスは、一意性を保つためユーザーIDを含む墓標に置き換える。これにより、削除したユーザーが
+/// 使用していたEメールアドレスで、再度サインアップできる。
 ///
 /// # 引数
 ///
 /// * `user_id` - ユーザーID
-/// * `active` - アクティブフラグ
 ///
 /// # 戻り値
 ///
-/// アクティブフラグを更新するクエリ
-pub fn set_active_query<'q>(user_id: UserId, active: bool) -> PgQuery<'q> {
-    sqlx::query::<Postgres>(
+/// ユーザーのアカウントを削除して、個人情報を消去するクエリ
+pub fn soft_delete_user_query<'q>(user_id: UserId) -> PgQuery<'q> {
+    sqlx::query!(
         r#"
         UPDATE
             users
         SET
-            active = $1
+            email = $2 || id || $3,
+            active = FALSE,
+            family_name = NULL,
+            given_name = NULL,
+            postal_code = NULL,
+            address = NULL,
+            fixed_phone_number = NULL,
+            mobile_phone_number = NULL,
+            remarks = NULL,
+            locked_at = NULL,
+            email_verified_at = NULL,
+            deleted_at = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
         WHERE
-            id = $2
+            id = $1
+            AND deleted_at IS NULL
         "#,
+        user_id as _,
+        DELETED_EMAIL_PREFIX,
+        DELETED_EMAIL_SUFFIX,
     )
-    .bind(active)
-    .bind(user_id.value)
 }
 
-/// 最初にサインインに失敗した日時をNULL、サインイン失敗回数を0にするクエリを生成する。
+/// ユーザーが、削除していない最後の管理者であるか確認する。
+///
+/// 管理者の数を確認してからユーザーを変更するまでに、他のトランザクションが管理者を削除または
+/// 変更しないように、削除していない管理者の行をすべてロックする。ロックはトランザクションが
+/// 終了するまで保持される。
 ///
 /// # 引数
 ///
+/// * `repository` - PostgreSQLユーザーリポジトリ
+/// * `tx` - ユーザーを変更するトランザクション
 /// * `user_id` - ユーザーID
 ///
 /// # 戻り値
 ///
-/// 最初にサインインに失敗したことを保存するクエリ
-pub fn clear_sign_in_failed_history_query<'q>(user_id: UserId) -> PgQueryAs<'q, UserCredentialRow> {
-    sqlx::query_as::<Postgres, UserCredentialRow>(
+/// ユーザーが最後の管理者である場合は`true`、それ以外の場合は`false`
+async fn is_last_admin(
+    repository: &PgUserRepository,
+    tx: &mut PgTransaction<'_>,
+    user_id: UserId,
+) -> DomainResult<bool> {
+    let admins = lock_admins_query()
+        .fetch_all(&mut **tx)
+        .with_timeout(repository.query_timeout)
+        .await?
+        .map_err(|e| {
+            tracing::error!("{} ({}:{})", e, file!(), line!());
+            DomainError::Repository(e.into())
+        })?;
+
+    Ok(admins.contains(&user_id) && admins.len() <= 1)
+}
+
+/// 処理した行数がバッチサイズ未満になるまで、クエリを繰り返し実行する。
+///
+/// それぞれのクエリは、1つのステートメントで完了するため、個別にコミットされる。
+///
+/// # 引数
+///
+/// * `repository` - PostgreSQLユーザーリポジトリ
+/// * `query` - 最大`PURGE_BATCH_SIZE`行を処理するクエリを生成する関数
+///
+/// # 戻り値
+///
+/// クエリが処理した行数の合計
+async fn execute_in_batches<'q, F>(repository: &PgUserRepository, query: F) -> DomainResult<u64>
+where
+    F: Fn() -> PgQuery<'q> + Send,
+{
+    let mut total = 0;
+    loop {
+        let rows_affected = query()
+            .execute(&repository.pool)
+            .with_timeout(repository.query_timeout)
+            .await?
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?
+            .rows_affected();
+        total += rows_affected;
+        if rows_affected < PURGE_BATCH_SIZE as u64 {
+            return Ok(total);
+        }
+    }
+}
+
+/// 最初にサインインに失敗した日時が指定した日時よりも過去の、アカウントをロックしていない
+/// ユーザーのサインインに失敗した履歴を消去するクエリを生成する。
+///
+/// 他のトランザクションがロックしている行は読み飛ばす。
+///
+/// # 引数
+///
+/// * `before` - この日時よりも前に最初にサインインに失敗した履歴を消去する
+/// * `limit` - 1回のクエリで履歴を消去するユーザーの最大数
+///
+/// # 戻り値
+///
+/// サインインに失敗した履歴を消去するクエリ
+pub fn purge_sign_in_history_query<'q>(before: OffsetDateTime, limit: i64) -> PgQuery<'q> {
+    sqlx::query!(
         r#"
         UPDATE
             users
         SET
             sign_in_attempted_at = NULL,
-            number_of_sign_in_failures = 0
+            number_of_sign_in_failures = 0,
+            last_failed_sign_in_ip_address = NULL,
+            last_failed_sign_in_user_agent = NULL
         WHERE
-            id = $1
-        RETURNING
-            id, email, password, active, user_permission_code,
-            sign_in_attempted_at, number_of_sign_in_failures
+            id IN (
+                SELECT
+                    id
+                FROM
+                    users
+                WHERE
+                    sign_in_attempted_at < $1
+                    AND locked_at IS NULL
+                LIMIT
+                    $2
+                FOR UPDATE SKIP LOCKED
+            )
+        "#,
+        before,
+        limit,
+    )
+}
+
+/// 削除した日時が指定した日時よりも過去のユーザーのレコードを物理的に削除するクエリを生成する。
+///
+/// 他のトランザクションがロックしている行は読み飛ばす。
+///
+/// # 引数
+///
+/// * `before` - この日時よりも前に削除したユーザーのレコードを物理的に削除する
+/// * `limit` - 1回のクエリで物理的に削除するユーザーの最大数
+///
+/// # 戻り値
+///
+/// ユーザーのレコードを物理的に削除するクエリ
+pub fn purge_deleted_users_query<'q>(before: OffsetDateTime, limit: i64) -> PgQuery<'q> {
+    sqlx::query!(
+        r#"
+        DELETE FROM
+            users
+        WHERE
+            id IN (
+                SELECT
+                    id
+                FROM
+                    users
+                WHERE
+                    deleted_at < $1
+                LIMIT
+                    $2
+                FOR UPDATE SKIP LOCKED
+            )
         "#,
+        before,
+        limit,
     )
-    .bind(user_id.value)
 }
 
-#[derive(Debug, Clone, sqlx::FromRow)]
+/// 古いレコードを消去または削除するときに、1回のクエリで処理する最大行数
+pub const PURGE_BATCH_SIZE: i64 = 1_000;
+/// 削除したユーザーのEメールアドレスの墓標のプレフィックス
+pub const DELETED_EMAIL_PREFIX: &str = "deleted-";
+/// 削除したユーザーのEメールアドレスの墓標のサフィックス
+pub const DELETED_EMAIL_SUFFIX: &str = "@deleted.invalid";
+
+/// データベースに登録したユーザーの行
+#[derive(Debug, Clone)]
 pub struct InsertedUserRow {
-    pub id: Uuid,
+    pub id: UserId,
     pub email: String,
     pub password: String,
     pub active: bool,
@@ -529,7 +1934,7 @@ pub struct InsertedUserRow {
 impl From<InsertedUserRow> for SignUpOutput {
     fn from(row: InsertedUserRow) -> Self {
         Self {
-            id: UserId::new(row.id),
+            id: row.id,
             email: EmailAddress::new(row.email).unwrap(),
             active: row.active,
             user_permission_code: UserPermissionCode::try_from(row.user_permission_code).unwrap(),
@@ -556,36 +1961,100 @@ impl From<InsertedUserRow> for SignUpOutput {
 /// # 戻り値
 ///
 /// ユーザーをデータベースに登録するクエリ
-pub fn insert_user_query<'q>(user: SignUpInput) -> PgQueryAs<'q, InsertedUserRow> {
+pub fn insert_user_query<'q>(
+    user: SignUpInput,
+) -> PgQueryMap<'q, impl FnMut(PgRow) -> Result<InsertedUserRow, sqlx::Error> + Send> {
     let password = user.password.value.expose_secret().to_string();
     let fixed_phone_number = user.fixed_phone_number.owned_value();
     let mobile_phone_number = user.mobile_phone_number.owned_value();
     let remarks = user.remarks.owned_value();
 
-    sqlx::query_as::<Postgres, InsertedUserRow>(
+    sqlx::query_as!(
+        InsertedUserRow,
         r#"
         INSERT INTO users (
             id, email, password, active, user_permission_code, family_name, given_name,
             postal_code, address, fixed_phone_number, mobile_phone_number,
-            remarks, created_at, updated_at
+            remarks, password_change_required, created_at, updated_at
         )
         VALUES (
-            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12,
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13,
             STATEMENT_TIMESTAMP(), STATEMENT_TIMESTAMP()
         )
-        RETURNING *
+        RETURNING
+            id AS "id: _", email, password, active, user_permission_code,
+            family_name AS "family_name!", given_name AS "given_name!",
+            postal_code AS "postal_code!", address AS "address!", fixed_phone_number,
+            mobile_phone_number, remarks, created_at, updated_at
         "#,
+        user.id as _,
+        user.email.value,
+        password,
+        user.active,
+        user.user_permission_code as i16,
+        user.family_name.value,
+        user.given_name.value,
+        user.postal_code.value,
+        user.address.value,
+        fixed_phone_number,
+        mobile_phone_number,
+        remarks,
+        user.password_change_required,
     )
-    .bind(user.id.value)
-    .bind(user.email.value)
-    .bind(password)
-    .bind(user.active)
-    .bind(user.user_permission_code as i16)
-    .bind(user.family_name.value)
-    .bind(user.given_name.value)
-    .bind(user.postal_code.value)
-    .bind(user.address.value)
-    .bind(fixed_phone_number)
-    .bind(mobile_phone_number)
-    .bind(remarks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn retrieved_user_row(email: &str) -> RetrievedUserRow {
+        let now = OffsetDateTime::now_utc();
+        RetrievedUserRow {
+            id: UserId::default(),
+            email: String::from(email),
+            password: String::from("$argon2id$v=19$m=12288,t=3,p=1$c2FsdA$aGFzaA"),
+            active: true,
+            user_permission_code: UserPermissionCode::General as i16,
+            user_permission_name: String::from("一般"),
+            family_name: String::from("山田"),
+            given_name: String::from("太郎"),
+            postal_code: String::from("012-3456"),
+            address: String::from("東京都新宿区西新宿2-8-1"),
+            fixed_phone_number: None,
+            mobile_phone_number: None,
+            remarks: None,
+            last_sign_in_at: None,
+            sign_in_attempted_at: None,
+            number_of_sign_in_failures: 0,
+            created_at: now,
+            updated_at: now,
+            version: 1,
+        }
+    }
+
+    /// データベースから取得した行から、ユーザーを構築できることを確認
+    #[test]
+    fn user_can_be_built_from_valid_row() {
+        let row = retrieved_user_row("foo@example.com");
+        let user_id = row.id;
+
+        let user = User::try_from(row).unwrap();
+
+        assert_eq!(user_id, user.id);
+        assert_eq!("foo@example.com", user.email.value);
+    }
+
+    /// ドメインプリミティブの検証を満たさない行からユーザーを構築した場合は、パニックせずに
+    /// リポジトリエラーを返すことを確認
+    #[test]
+    fn invalid_row_returns_repository_error() {
+        let mut row = retrieved_user_row("not-an-email");
+        let result = User::try_from(row);
+        assert!(matches!(result, Err(DomainError::Repository(_))));
+
+        row = retrieved_user_row("foo@example.com");
+        row.user_permission_code = 99;
+        let result = User::try_from(row);
+        assert!(matches!(result, Err(DomainError::Repository(_))));
+    }
 }
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use sqlx::Postgres;
+
+use domain::models::user::{UserPermission, UserPermissionCode, UserPermissionName};
+use domain::repositories::user_permission::UserPermissionRepository;
+use domain::{DomainError, DomainResult};
+
+use crate::repositories::postgres::PgRepository;
+
+/// PostgreSQLユーザー権限リポジトリ
+pub type PgUserPermissionRepository = PgRepository<UserPermission>;
+
+type PgQueryAs<'q, T> = sqlx::query::QueryAs<'q, sqlx::Postgres, T, sqlx::postgres::PgArguments>;
+
+#[async_trait]
+impl UserPermissionRepository for PgUserPermissionRepository {
+    /// ユーザー権限をユーザー権限コードの順に取得する。
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限のリスト
+    async fn list(&self) -> DomainResult<Vec<UserPermission>> {
+        let rows = list_user_permissions_query()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+
+        rows.into_iter().map(UserPermission::try_from).collect()
+    }
+
+    /// ユーザー権限コードからユーザー権限を取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `code` - ユーザー権限コード
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー権限
+    async fn by_code(&self, code: UserPermissionCode) -> DomainResult<Option<UserPermission>> {
+        let row = user_permission_by_code_query(code)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                DomainError::Repository(e.into())
+            })?;
+
+        row.map(UserPermission::try_from).transpose()
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub struct UserPermissionRow {
+    pub code: i16,
+    pub name: String,
+}
+
+impl TryFrom<UserPermissionRow> for UserPermission {
+    type Error = DomainError;
+
+    fn try_from(row: UserPermissionRow) -> Result<Self, Self::Error> {
+        Ok(Self::new(
+            UserPermissionCode::try_from(row.code)?,
+            UserPermissionName::new(row.name)?,
+        ))
+    }
+}
+
+/// ユーザー権限をユーザー権限コードの順に取得するクエリを生成する。
+///
+/// # 戻り値
+///
+/// ユーザー権限のリストを取得するクエリ
+pub fn list_user_permissions_query<'q>() -> PgQueryAs<'q, UserPermissionRow> {
+    sqlx::query_as::<Postgres, UserPermissionRow>(
+        r#"
+        SELECT
+            code, name
+        FROM
+            user_permissions
+        ORDER BY
+            code
+        "#,
+    )
+}
+
+/// ユーザー権限コードからユーザー権限を取得するクエリを生成する。
+///
+/// # 引数
+///
+/// * `code` - ユーザー権限コード
+///
+/// # 戻り値
+///
+/// ユーザー権限を取得するクエリ
+pub fn user_permission_by_code_query<'q>(
+    code: UserPermissionCode,
+) -> PgQueryAs<'q, UserPermissionRow> {
+    sqlx::query_as::<Postgres, UserPermissionRow>(
+        r#"
+        SELECT
+            code, name
+        FROM
+            user_permissions
+        WHERE
+            code = $1
+        "#,
+    )
+    .bind(code as i16)
+}
@@ -0,0 +1,102 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use deadpool_redis::{Connection as RedisConnection, Pool as RedisPool};
+use redis::AsyncCommands;
+use sha2::{Digest, Sha256};
+
+use domain::models::user::UserId;
+use domain::repositories::device::DeviceRepository;
+use domain::{DomainError, DomainResult};
+
+/// Redisデバイスリポジトリ
+///
+/// ユーザーがサインインしたデバイスを識別する文字列をハッシュ化して、ユーザーごとのセットに保存する。
+#[derive(Debug)]
+pub struct RedisDeviceRepository {
+    /// Redis接続プール
+    pool: RedisPool,
+}
+
+impl RedisDeviceRepository {
+    /// Redisデバイスリポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `pool` - Redis接続プール
+    ///
+    /// # 戻り値
+    ///
+    /// Redisデバイスリポジトリ
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+
+    /// Redisに接続する。
+    ///
+    /// # 戻り値
+    ///
+    /// Redis接続
+    async fn connection(&self) -> DomainResult<RedisConnection> {
+        self.pool.get().await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", CONNECTION_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", CONNECTION_ERROR))
+        })
+    }
+}
+
+#[async_trait]
+impl DeviceRepository for RedisDeviceRepository {
+    /// ユーザーがサインインしたデバイスを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `device` - デバイスを識別する文字列
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーがはじめてサインインしたデバイスの場合は`true`、それ以外の場合は`false`
+    async fn register_device(&self, user_id: UserId, device: &str) -> DomainResult<bool> {
+        let mut conn = self.connection().await?;
+        let added: u64 = conn
+            .sadd(generate_key(user_id), generate_member(device))
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", STORE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", STORE_ERROR))
+            })?;
+
+        Ok(0 < added)
+    }
+}
+
+/// ユーザーがサインインしたデバイスを保存するセットのキーを生成する。
+fn generate_key(user_id: UserId) -> String {
+    format!("devices:{}", user_id.value)
+}
+
+/// デバイスを識別する文字列をハッシュ化して、セットに保存するメンバーを生成する。
+fn generate_member(device: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(device.as_bytes());
+
+    format!("{:x}", hasher.finalize())
+}
+
+const CONNECTION_ERROR: &str = "Redisに接続するときにエラーが発生しました。";
+const STORE_ERROR: &str = "Redisにデバイスを保存するときにエラーが発生しました。";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 同じデバイスを識別する文字列から、同じメンバーを生成することを確認
+    #[test]
+    fn same_device_generates_same_member() {
+        let device = "Mozilla/5.0 (X11; Linux x86_64)";
+
+        assert_eq!(generate_member(device), generate_member(device));
+        assert_ne!(generate_member(device), generate_member("curl/8.0.0"));
+        assert_eq!(64, generate_member(device).len());
+    }
+}
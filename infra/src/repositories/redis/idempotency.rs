@@ -0,0 +1,243 @@
+use anyhow::anyhow;
+use async_trait::async_trait;
+use deadpool_redis::{Connection as RedisConnection, Pool as RedisPool};
+use redis::{AsyncCommands, ExistenceCheck, SetExpiry, SetOptions};
+
+use domain::repositories::idempotency::{
+    IdempotencyRecord, IdempotencyRepository, IdempotentResponse,
+};
+use domain::{DomainError, DomainResult};
+
+/// Redis冪等キーリポジトリ
+///
+/// 冪等キーごとに、リクエストボディのハッシュ値と、リクエストに返したレスポンスをJSON形式で保存する。
+pub struct RedisIdempotencyRepository {
+    /// Redis接続プール
+    pool: RedisPool,
+}
+
+impl RedisIdempotencyRepository {
+    /// Redis冪等キーリポジトリを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `pool` - Redis接続プール
+    ///
+    /// # 戻り値
+    ///
+    /// Redis冪等キーリポジトリ
+    pub fn new(pool: RedisPool) -> Self {
+        Self { pool }
+    }
+
+    /// Redisに接続する。
+    ///
+    /// # 戻り値
+    ///
+    /// Redis接続
+    async fn connection(&self) -> DomainResult<RedisConnection> {
+        self.pool.get().await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", CONNECTION_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", CONNECTION_ERROR))
+        })
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for RedisIdempotencyRepository {
+    /// 冪等キーを予約して、リクエストを処理していることを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `key` - 冪等キー
+    /// * `request_hash` - リクエストボディのハッシュ値
+    /// * `ttl` - 記録を保持する期間（秒）
+    ///
+    /// # 戻り値
+    ///
+    /// 冪等キーを予約できた場合は`None`、冪等キーがすでに記録されている場合は記録
+    async fn reserve(
+        &self,
+        key: &str,
+        request_hash: &str,
+        ttl: u64,
+    ) -> DomainResult<Option<IdempotencyRecord>> {
+        let mut conn = self.connection().await?;
+        let key = generate_key(key);
+        let value = StoredRecord::from(&IdempotencyRecord::Processing {
+            request_hash: request_hash.to_string(),
+        })
+        .to_json()?;
+        let options = SetOptions::default()
+            .conditional_set(ExistenceCheck::NX)
+            .with_expiration(SetExpiry::EX(ttl as usize));
+        loop {
+            let reserved: Option<String> =
+                conn.set_options(&key, &value, options).await.map_err(|e| {
+                    tracing::error!("{} {}({}:{})", STORE_ERROR, e, file!(), line!());
+                    DomainError::Repository(anyhow!("{}", STORE_ERROR))
+                })?;
+            if reserved.is_some() {
+                return Ok(None);
+            }
+            let stored: Option<String> = conn.get(&key).await.map_err(|e| {
+                tracing::error!("{} {}({}:{})", RETRIEVE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", RETRIEVE_ERROR))
+            })?;
+            // 予約に失敗してから取得するまでに記録が期限切れになった場合は、もう一度予約する
+            if let Some(stored) = stored {
+                return StoredRecord::from_json(&stored).map(|record| Some(record.into()));
+            }
+        }
+    }
+
+    /// 予約した冪等キーに、リクエストに返したレスポンスを記録する。
+    ///
+    /// # 引数
+    ///
+    /// * `key` - 冪等キー
+    /// * `request_hash` - リクエストボディのハッシュ値
+    /// * `response` - リクエストに返したレスポンス
+    /// * `ttl` - 記録を保持する期間（秒）
+    async fn complete(
+        &self,
+        key: &str,
+        request_hash: &str,
+        response: &IdempotentResponse,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let value = StoredRecord::from(&IdempotencyRecord::Completed {
+            request_hash: request_hash.to_string(),
+            response: response.clone(),
+        })
+        .to_json()?;
+        conn.set_ex(generate_key(key), value, ttl)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", STORE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", STORE_ERROR))
+            })
+    }
+
+    /// 予約した冪等キーを解放して、同じ冪等キーでリクエストを再送信できるようにする。
+    ///
+    /// # 引数
+    ///
+    /// * `key` - 冪等キー
+    async fn release(&self, key: &str) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        conn.del(generate_key(key)).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", DELETE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", DELETE_ERROR))
+        })
+    }
+}
+
+/// Redisに保存する冪等キーの記録
+///
+/// レスポンスを記録していない場合は、リクエストを処理していることを示す。
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct StoredRecord {
+    /// リクエストボディのハッシュ値
+    request_hash: String,
+    /// HTTPステータスコード
+    status_code: Option<u16>,
+    /// JSON形式のレスポンスボディ
+    body: Option<String>,
+}
+
+impl StoredRecord {
+    /// JSON形式の文字列に変換する。
+    fn to_json(&self) -> DomainResult<String> {
+        serde_json::to_string(self).map_err(|e| {
+            tracing::error!("{} {}({}:{})", SERIALIZE_ERROR, e, file!(), line!());
+            DomainError::Unexpected(anyhow!("{}", SERIALIZE_ERROR))
+        })
+    }
+
+    /// JSON形式の文字列から変換する。
+    fn from_json(value: &str) -> DomainResult<Self> {
+        serde_json::from_str(value).map_err(|e| {
+            tracing::error!("{} {}({}:{})", DESERIALIZE_ERROR, e, file!(), line!());
+            DomainError::Unexpected(anyhow!("{}", DESERIALIZE_ERROR))
+        })
+    }
+}
+
+impl From<&IdempotencyRecord> for StoredRecord {
+    fn from(value: &IdempotencyRecord) -> Self {
+        match value {
+            IdempotencyRecord::Processing { request_hash } => Self {
+                request_hash: request_hash.clone(),
+                status_code: None,
+                body: None,
+            },
+            IdempotencyRecord::Completed {
+                request_hash,
+                response,
+            } => Self {
+                request_hash: request_hash.clone(),
+                status_code: Some(response.status_code),
+                body: Some(response.body.clone()),
+            },
+        }
+    }
+}
+
+impl From<StoredRecord> for IdempotencyRecord {
+    fn from(value: StoredRecord) -> Self {
+        match (value.status_code, value.body) {
+            (Some(status_code), Some(body)) => Self::Completed {
+                request_hash: value.request_hash,
+                response: IdempotentResponse { status_code, body },
+            },
+            _ => Self::Processing {
+                request_hash: value.request_hash,
+            },
+        }
+    }
+}
+
+/// 冪等キーの記録を保存するキーを生成する。
+fn generate_key(key: &str) -> String {
+    format!("{}:{}", IDEMPOTENCY_KEY_PREFIX, key)
+}
+
+const IDEMPOTENCY_KEY_PREFIX: &str = "idempotency";
+
+const CONNECTION_ERROR: &str = "Redisに接続するときにエラーが発生しました。";
+const STORE_ERROR: &str = "Redisに冪等キーを保存するときにエラーが発生しました。";
+const RETRIEVE_ERROR: &str = "Redisから冪等キーを取得するときにエラーが発生しました。";
+const DELETE_ERROR: &str = "Redisから冪等キーを削除するときにエラーが発生しました。";
+const SERIALIZE_ERROR: &str = "冪等キーの記録をJSONに変換するときにエラーが発生しました。";
+const DESERIALIZE_ERROR: &str = "Redisに保存された冪等キーの記録を解析できませんでした。";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 冪等キーの記録を、Redisに保存する形式に変換して元に戻せることを確認
+    #[test]
+    fn record_round_trips_through_stored_record() -> anyhow::Result<()> {
+        let records = [
+            IdempotencyRecord::Processing {
+                request_hash: String::from("hash"),
+            },
+            IdempotencyRecord::Completed {
+                request_hash: String::from("hash"),
+                response: IdempotentResponse {
+                    status_code: 200,
+                    body: String::from(r#"{"id":"1"}"#),
+                },
+            },
+        ];
+        for record in records {
+            let json = StoredRecord::from(&record).to_json()?;
+            let restored: IdempotencyRecord = StoredRecord::from_json(&json)?.into();
+            assert_eq!(record, restored);
+        }
+
+        Ok(())
+    }
+}
@@ -1 +1,3 @@
+pub mod device;
+pub mod idempotency;
 pub mod token;
@@ -4,12 +4,18 @@ use deadpool_redis::{Connection as RedisConnection, Pool as RedisPool};
 use redis::AsyncCommands;
 use secrecy::{ExposeSecret as _, SecretString};
 use sha2::{Digest, Sha256};
+use time::{Duration, OffsetDateTime};
 
+use domain::models::primitives::EmailAddress;
 use domain::models::user::{UserId, UserPermissionCode};
-use domain::repositories::token::{TokenContent, TokenPairWithTtl, TokenRepository, TokenType};
+use domain::repositories::token::{
+    AccessToken, ActiveSession, PendingEmailChange, SessionToken, TokenContent, TokenPairWithTtl,
+    TokenRepository, TokenType,
+};
 use domain::{DomainError, DomainResult};
 
 /// Redisトークンリポジトリ
+#[derive(Debug, Clone)]
 pub struct RedisTokenRepository {
     /// Redis接続プール
     pool: RedisPool,
@@ -40,12 +46,33 @@ impl RedisTokenRepository {
             DomainError::Repository(anyhow!("{}", CONNECTION_ERROR))
         })
     }
+
+    /// RedisにPINGコマンドを送信して、Redisが応答するか確認する。
+    pub async fn ping(&self) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        redis::cmd("PING")
+            .query_async::<_, String>(&mut conn)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", PING_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", PING_ERROR))
+            })?;
+
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl TokenRepository for RedisTokenRepository {
     /// アクセストークンとリフレッシュトークンを登録する。
     ///
+    /// ユーザーに発行したトークンを無効にできるように、トークンのキーをユーザーごとのセットに
+    /// 追加する。セットの生存期間は、リフレッシュトークンの生存期間に合わせる。
+    ///
+    /// また、トークンペアをセッションとして取得できるように、トークンを発行した日時、トークンの
+    /// キー及びデバイスを、アクセストークンのキーの先頭から生成したセッションIDで保存して、
+    /// セッションのキーもユーザーごとのセットに追加する。
+    ///
     /// # 引数
     ///
     /// * `tokens` - トークンペア
@@ -55,9 +82,9 @@ impl TokenRepository for RedisTokenRepository {
         token_pair: TokenPairWithTtl<'a>,
         user_permission_code: UserPermissionCode,
     ) -> DomainResult<()> {
-        let access_key = generate_key(token_pair.access);
+        let access_key = generate_key(token_pair.access.secret());
         let access_value = generate_value(user_id, TokenType::Access, user_permission_code);
-        let refresh_key = generate_key(token_pair.refresh);
+        let refresh_key = generate_key(token_pair.refresh.secret());
         let refresh_value = generate_value(user_id, TokenType::Refresh, user_permission_code);
         let mut conn = self.connection().await?;
         store(&mut conn, &access_key, &access_value, token_pair.access_ttl).await?;
@@ -68,6 +95,33 @@ impl TokenRepository for RedisTokenRepository {
             token_pair.refresh_ttl,
         )
         .await?;
+        let session_key = generate_session_key(user_id, generate_session_id(&access_key));
+        let session_value = generate_session_value(
+            OffsetDateTime::now_utc(),
+            &access_key,
+            &refresh_key,
+            token_pair.device,
+        );
+        store(
+            &mut conn,
+            &session_key,
+            &session_value,
+            token_pair.refresh_ttl,
+        )
+        .await?;
+        let user_tokens_key = generate_user_tokens_key(user_id);
+        conn.sadd::<_, _, ()>(&user_tokens_key, &[&access_key, &refresh_key, &session_key])
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", STORE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", STORE_ERROR))
+            })?;
+        conn.expire::<_, ()>(&user_tokens_key, token_pair.refresh_ttl as i64)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", STORE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", STORE_ERROR))
+            })?;
 
         Ok(())
     }
@@ -99,6 +153,395 @@ impl TokenRepository for RedisTokenRepository {
             user_permission_code,
         }))
     }
+
+    /// Eメールアドレスを確認するトークンを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - Eメールアドレスを確認するユーザーのユーザーID
+    /// * `token` - Eメールアドレスを確認するトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_email_verification_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let key = generate_email_verification_key(token);
+
+        store(&mut conn, &key, &user_id.value.to_string(), ttl).await
+    }
+
+    /// Eメールアドレスを確認するトークンを消費する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - Eメールアドレスを確認するトークン
+    ///
+    /// # 戻り値
+    ///
+    /// Eメールアドレスを確認するユーザーのユーザーID
+    async fn consume_email_verification_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        let mut conn = self.connection().await?;
+        let key = generate_email_verification_key(token);
+
+        consume_user_id(&mut conn, &key).await
+    }
+
+    /// Eメールアドレスの変更を確定するトークンを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `email_change` - 保留しているEメールアドレスの変更
+    /// * `token` - Eメールアドレスの変更を確定するトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_email_change_token(
+        &self,
+        email_change: &PendingEmailChange,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let key = generate_email_change_key(token);
+        let value = format!(
+            "{}:{}",
+            email_change.user_id.value, email_change.email.value
+        );
+
+        store(&mut conn, &key, &value, ttl).await
+    }
+
+    /// Eメールアドレスの変更を確定するトークンを消費する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - Eメールアドレスの変更を確定するトークン
+    ///
+    /// # 戻り値
+    ///
+    /// 保留しているEメールアドレスの変更
+    async fn consume_email_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<PendingEmailChange>> {
+        let mut conn = self.connection().await?;
+        let key = generate_email_change_key(token);
+        let value: Option<String> = conn.get_del(&key).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", RETRIEVE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", RETRIEVE_ERROR))
+        })?;
+
+        value
+            .map(|value| split_email_change_value(&value))
+            .transpose()
+    }
+
+    /// 2要素認証を待機しているサインインを示すトークンを登録する。
+    ///
+    /// ユーザーに発行したトークンを無効にするときに、2要素認証を待機しているサインインも
+    /// 無効にできるように、キーをユーザーごとのセットに追加する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - サインインするユーザーのユーザーID
+    /// * `token` - 2要素認証を待機しているサインインを示すトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_mfa_pending_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let key = generate_mfa_pending_key(token);
+        store(&mut conn, &key, &user_id.value.to_string(), ttl).await?;
+        conn.sadd::<_, _, ()>(generate_user_tokens_key(user_id), &key)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", STORE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", STORE_ERROR))
+            })
+    }
+
+    /// 2要素認証を待機しているサインインを示すトークンを消費する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - 2要素認証を待機しているサインインを示すトークン
+    ///
+    /// # 戻り値
+    ///
+    /// サインインするユーザーのユーザーID
+    async fn consume_mfa_pending_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        let mut conn = self.connection().await?;
+        let key = generate_mfa_pending_key(token);
+
+        consume_user_id(&mut conn, &key).await
+    }
+
+    /// ユーザーの2要素認証を待機しているサインインを示すトークンをすべて無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    async fn revoke_mfa_pending_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let user_tokens_key = generate_user_tokens_key(user_id);
+        let keys: Vec<String> = conn.smembers(&user_tokens_key).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", RETRIEVE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", RETRIEVE_ERROR))
+        })?;
+        let keys = keys
+            .into_iter()
+            .filter(|key| key.starts_with(MFA_PENDING_KEY_PREFIX))
+            .collect::<Vec<_>>();
+        if keys.is_empty() {
+            return Ok(());
+        }
+        conn.del::<_, ()>(&keys).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", DELETE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", DELETE_ERROR))
+        })?;
+        conn.srem::<_, _, ()>(&user_tokens_key, &keys)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", DELETE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", DELETE_ERROR))
+            })
+    }
+
+    /// パスワードの変更を要求されているユーザーが、パスワードを変更するためのトークンを登録する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - パスワードを変更するユーザーのユーザーID
+    /// * `token` - パスワードを変更するためのトークン
+    /// * `ttl` - トークンの生存期間（秒）
+    async fn register_password_change_token(
+        &self,
+        user_id: UserId,
+        token: &SecretString,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let key = generate_password_change_key(token);
+
+        store(&mut conn, &key, &user_id.value.to_string(), ttl).await
+    }
+
+    /// パスワードを変更するためのトークンを消費する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - パスワードを変更するためのトークン
+    ///
+    /// # 戻り値
+    ///
+    /// パスワードを変更するユーザーのユーザーID
+    async fn consume_password_change_token(
+        &self,
+        token: &SecretString,
+    ) -> DomainResult<Option<UserId>> {
+        let mut conn = self.connection().await?;
+        let key = generate_password_change_key(token);
+
+        consume_user_id(&mut conn, &key).await
+    }
+
+    /// アクセストークンを発行したユーザーが、パスワードで再認証したことを登録する。
+    ///
+    /// ユーザーに発行したトークンを無効にするときに、再認証したことも無効にできるように、
+    /// キーをユーザーごとのセットに追加する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - 再認証したユーザーのユーザーID
+    /// * `access_token` - 再認証したユーザーのアクセストークン
+    /// * `ttl` - 再認証したことの生存期間（秒）
+    async fn register_reauthentication(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+        ttl: u64,
+    ) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let key = generate_reauthentication_key(access_token);
+        store(&mut conn, &key, &user_id.value.to_string(), ttl).await?;
+        conn.sadd::<_, _, ()>(generate_user_tokens_key(user_id), &key)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", STORE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", STORE_ERROR))
+            })
+    }
+
+    /// アクセストークンで再認証したユーザーのユーザーIDを取得する。
+    ///
+    /// # 引数
+    ///
+    /// * `access_token` - アクセストークン
+    ///
+    /// # 戻り値
+    ///
+    /// 再認証したユーザーのユーザーID
+    async fn reauthenticated_user(
+        &self,
+        access_token: &AccessToken,
+    ) -> DomainResult<Option<UserId>> {
+        let mut conn = self.connection().await?;
+        let key = generate_reauthentication_key(access_token);
+
+        retrieve(&mut conn, &key)
+            .await?
+            .map(|value| {
+                UserId::try_from(value.as_str()).map_err(|_| {
+                    tracing::error!("{} ({}:{})", USER_ID_CONSTRUCTION_FAILED, file!(), line!());
+                    DomainError::Unexpected(anyhow!("{}", USER_ID_CONSTRUCTION_FAILED))
+                })
+            })
+            .transpose()
+    }
+
+    /// ユーザーがサインインしているセッションを取得する。
+    ///
+    /// トークンの有効期限は、トークンのキーの生存期間から計算する。生存期間が過ぎたセッションの
+    /// キーは、ユーザーごとのセットから削除する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `current_access_token` - リクエストで送信されたアクセストークン
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザーがサインインしているセッション
+    async fn user_sessions(
+        &self,
+        user_id: UserId,
+        current_access_token: &AccessToken,
+    ) -> DomainResult<Vec<ActiveSession>> {
+        let mut conn = self.connection().await?;
+        let user_tokens_key = generate_user_tokens_key(user_id);
+        let keys: Vec<String> = conn.smembers(&user_tokens_key).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", RETRIEVE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", RETRIEVE_ERROR))
+        })?;
+        let current_access_key = generate_key(current_access_token.secret());
+        let current_session_id = generate_session_id(&current_access_key);
+        let session_key_prefix = generate_session_key(user_id, "");
+        let mut sessions = vec![];
+        let mut expired_keys = vec![];
+        for key in keys {
+            let Some(session_id) = key.strip_prefix(&session_key_prefix) else {
+                continue;
+            };
+            let Some(value) = retrieve(&mut conn, &key).await? else {
+                expired_keys.push(key);
+                continue;
+            };
+            let (issued_at, access_key, refresh_key, device) = split_session_value(&value)?;
+            let now = OffsetDateTime::now_utc();
+            let mut tokens = vec![];
+            for (token_type, token_key) in [
+                (TokenType::Access, access_key),
+                (TokenType::Refresh, refresh_key),
+            ] {
+                let ttl: i64 = conn.ttl(token_key).await.map_err(|e| {
+                    tracing::error!("{} {}({}:{})", RETRIEVE_ERROR, e, file!(), line!());
+                    DomainError::Repository(anyhow!("{}", RETRIEVE_ERROR))
+                })?;
+                // キーが存在しない場合、TTLコマンドは負の値を返す
+                if 0 < ttl {
+                    tokens.push(SessionToken {
+                        token_type,
+                        expiration: now + Duration::seconds(ttl),
+                    });
+                }
+            }
+            sessions.push(ActiveSession {
+                id: session_id.to_string(),
+                issued_at,
+                tokens,
+                device: device.map(String::from),
+                current: session_id == current_session_id,
+            });
+        }
+        if !expired_keys.is_empty() {
+            conn.srem::<_, _, ()>(&user_tokens_key, &expired_keys)
+                .await
+                .map_err(|e| {
+                    tracing::error!("{} {}({}:{})", DELETE_ERROR, e, file!(), line!());
+                    DomainError::Repository(anyhow!("{}", DELETE_ERROR))
+                })?;
+        }
+
+        Ok(sessions)
+    }
+
+    /// ユーザーがサインインしているセッションのアクセストークンとリフレッシュトークンを無効にする。
+    ///
+    /// アクセストークンで再認証したことも無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `session_id` - セッションID
+    ///
+    /// # 戻り値
+    ///
+    /// セッションを無効にした場合は`true`
+    async fn revoke_session(&self, user_id: UserId, session_id: &str) -> DomainResult<bool> {
+        let mut conn = self.connection().await?;
+        let session_key = generate_session_key(user_id, session_id);
+        let Some(value) = retrieve(&mut conn, &session_key).await? else {
+            return Ok(false);
+        };
+        let (_, access_key, refresh_key, _) = split_session_value(&value)?;
+        let keys = [
+            format!("{}:{}", REAUTHENTICATION_KEY_PREFIX, access_key),
+            access_key.to_string(),
+            refresh_key.to_string(),
+            session_key,
+        ];
+        conn.del::<_, ()>(&keys).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", DELETE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", DELETE_ERROR))
+        })?;
+        conn.srem::<_, _, ()>(generate_user_tokens_key(user_id), &keys)
+            .await
+            .map_err(|e| {
+                tracing::error!("{} {}({}:{})", DELETE_ERROR, e, file!(), line!());
+                DomainError::Repository(anyhow!("{}", DELETE_ERROR))
+            })?;
+
+        Ok(true)
+    }
+
+    /// ユーザーに発行したアクセストークンとリフレッシュトークンをすべて無効にする。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    async fn revoke_user_tokens(&self, user_id: UserId) -> DomainResult<()> {
+        let mut conn = self.connection().await?;
+        let user_tokens_key = generate_user_tokens_key(user_id);
+        let mut keys: Vec<String> = conn.smembers(&user_tokens_key).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", RETRIEVE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", RETRIEVE_ERROR))
+        })?;
+        keys.push(user_tokens_key);
+        conn.del::<_, ()>(&keys).await.map_err(|e| {
+            tracing::error!("{} {}({}:{})", DELETE_ERROR, e, file!(), line!());
+            DomainError::Repository(anyhow!("{}", DELETE_ERROR))
+        })
+    }
 }
 
 /// Redisに登録するキーを生成する。
@@ -117,6 +560,159 @@ fn generate_key(token: &SecretString) -> String {
     format!("{:x}", hasher.finalize())
 }
 
+/// Eメールアドレスを確認するトークンをRedisに登録するキーを生成する。
+///
+/// アクセストークン及びリフレッシュトークンのキーと区別するため、プレフィックスを付与する。
+///
+/// # 引数
+///
+/// * `token` - Eメールアドレスを確認するトークン
+///
+/// # 戻り値
+///
+/// プレフィックスを付与したトークンをハッシュ化した文字列
+fn generate_email_verification_key(token: &SecretString) -> String {
+    format!("{}:{}", EMAIL_VERIFICATION_KEY_PREFIX, generate_key(token))
+}
+
+/// Eメールアドレスの変更を確定するトークンをRedisに登録するキーを生成する。
+///
+/// # 引数
+///
+/// * `token` - Eメールアドレスの変更を確定するトークン
+///
+/// # 戻り値
+///
+/// プレフィックスを付与したトークンをハッシュ化した文字列
+fn generate_email_change_key(token: &SecretString) -> String {
+    format!("{}:{}", EMAIL_CHANGE_KEY_PREFIX, generate_key(token))
+}
+
+/// 2要素認証を待機しているサインインを示すトークンをRedisに登録するキーを生成する。
+///
+/// # 引数
+///
+/// * `token` - 2要素認証を待機しているサインインを示すトークン
+///
+/// # 戻り値
+///
+/// プレフィックスを付与したトークンをハッシュ化した文字列
+fn generate_mfa_pending_key(token: &SecretString) -> String {
+    format!("{}:{}", MFA_PENDING_KEY_PREFIX, generate_key(token))
+}
+
+/// パスワードを変更するためのトークンをRedisに登録するキーを生成する。
+///
+/// # 引数
+///
+/// * `token` - パスワードを変更するためのトークン
+///
+/// # 戻り値
+///
+/// プレフィックスを付与したトークンをハッシュ化した文字列
+fn generate_password_change_key(token: &SecretString) -> String {
+    format!("{}:{}", PASSWORD_CHANGE_KEY_PREFIX, generate_key(token))
+}
+
+/// アクセストークンで再認証したことをRedisに登録するキーを生成する。
+///
+/// # 引数
+///
+/// * `access_token` - 再認証したユーザーのアクセストークン
+///
+/// # 戻り値
+///
+/// プレフィックスを付与したアクセストークンをハッシュ化した文字列
+fn generate_reauthentication_key(access_token: &AccessToken) -> String {
+    format!(
+        "{}:{}",
+        REAUTHENTICATION_KEY_PREFIX,
+        generate_key(access_token.secret())
+    )
+}
+
+/// アクセストークンのキーから、セッションIDを生成する。
+///
+/// # 引数
+///
+/// * `access_key` - アクセストークンをハッシュ化した文字列
+///
+/// # 戻り値
+///
+/// アクセストークンをハッシュ化した文字列の先頭
+fn generate_session_id(access_key: &str) -> &str {
+    &access_key[..SESSION_ID_LENGTH]
+}
+
+/// セッションをRedisに登録するキーを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+/// * `session_id` - セッションID
+///
+/// # 戻り値
+///
+/// プレフィックスとユーザーIDを付与したセッションID
+fn generate_session_key(user_id: UserId, session_id: &str) -> String {
+    format!("{}:{}:{}", SESSION_KEY_PREFIX, user_id.value, session_id)
+}
+
+/// セッションとしてRedisに登録する値を生成する。
+///
+/// デバイスを識別する文字列には`:`が含まれる可能性があるため、最後に配置する。
+fn generate_session_value(
+    issued_at: OffsetDateTime,
+    access_key: &str,
+    refresh_key: &str,
+    device: Option<&str>,
+) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        issued_at.unix_timestamp(),
+        access_key,
+        refresh_key,
+        device.unwrap_or_default()
+    )
+}
+
+/// セッションの値を、トークンを発行した日時、アクセストークンのキー、リフレッシュトークンのキー
+/// 及びデバイスに分離する。
+fn split_session_value(value: &str) -> DomainResult<(OffsetDateTime, &str, &str, Option<&str>)> {
+    let values = value.splitn(4, ':').collect::<Vec<_>>();
+    if values.len() != 4 {
+        tracing::error!("{} ({}:{})", SESSION_CONSTRUCTION_FAILED, file!(), line!());
+        return Err(DomainError::Unexpected(anyhow!(
+            "{}",
+            SESSION_CONSTRUCTION_FAILED
+        )));
+    }
+    let issued_at = values[0]
+        .parse::<i64>()
+        .ok()
+        .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok())
+        .ok_or_else(|| {
+            tracing::error!("{} ({}:{})", SESSION_CONSTRUCTION_FAILED, file!(), line!());
+            DomainError::Unexpected(anyhow!("{}", SESSION_CONSTRUCTION_FAILED))
+        })?;
+    let device = Some(values[3]).filter(|device| !device.is_empty());
+
+    Ok((issued_at, values[1], values[2], device))
+}
+
+/// ユーザーに発行したトークンのキーを保存するセットのキーを生成する。
+///
+/// # 引数
+///
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// プレフィックスを付与したユーザーID
+fn generate_user_tokens_key(user_id: UserId) -> String {
+    format!("{}:{}", USER_TOKENS_KEY_PREFIX, user_id.value)
+}
+
 /// Redisに登録する値を生成する。
 fn generate_value(
     user_id: UserId,
@@ -151,6 +747,23 @@ async fn retrieve(conn: &mut RedisConnection, key: &str) -> DomainResult<Option<
     Ok(value)
 }
 
+/// Redisからキーで値を取得して削除した後、値をユーザーIDに変換する。
+async fn consume_user_id(conn: &mut RedisConnection, key: &str) -> DomainResult<Option<UserId>> {
+    let value: Option<String> = conn.get_del(key).await.map_err(|e| {
+        tracing::error!("{} {}({}:{})", RETRIEVE_ERROR, e, file!(), line!());
+        DomainError::Repository(anyhow!("{}", RETRIEVE_ERROR))
+    })?;
+
+    value
+        .map(|value| {
+            UserId::try_from(value.as_str()).map_err(|_| {
+                tracing::error!("{} ({}:{})", USER_ID_CONSTRUCTION_FAILED, file!(), line!());
+                DomainError::Unexpected(anyhow!("{}", USER_ID_CONSTRUCTION_FAILED))
+            })
+        })
+        .transpose()
+}
+
 /// 値をユーザーID、トークンの種類及びユーザーの権限に分離する。
 fn split_value(value: &str) -> DomainResult<(UserId, TokenType, UserPermissionCode)> {
     let mut values = value.split(':');
@@ -187,9 +800,46 @@ fn split_value(value: &str) -> DomainResult<(UserId, TokenType, UserPermissionCo
     Ok((user_id, token_type, user_permission_code))
 }
 
+/// Eメールアドレスの変更を確定するトークンの値を、ユーザーIDと変更後のEメールアドレスに分離する。
+fn split_email_change_value(value: &str) -> DomainResult<PendingEmailChange> {
+    let (user_id, email) = value.split_once(':').ok_or_else(|| {
+        tracing::error!("{} ({}:{})", EMAIL_NOT_FOUND, file!(), line!());
+        DomainError::Unexpected(anyhow!("{}", EMAIL_NOT_FOUND))
+    })?;
+    let user_id = UserId::try_from(user_id).map_err(|_| {
+        tracing::error!("{} ({}:{})", USER_ID_CONSTRUCTION_FAILED, file!(), line!());
+        DomainError::Unexpected(anyhow!("{}", USER_ID_CONSTRUCTION_FAILED))
+    })?;
+    let email = EmailAddress::new(email).map_err(|_| {
+        tracing::error!("{} ({}:{})", EMAIL_CONSTRUCTION_FAILED, file!(), line!());
+        DomainError::Unexpected(anyhow!("{}", EMAIL_CONSTRUCTION_FAILED))
+    })?;
+
+    Ok(PendingEmailChange { user_id, email })
+}
+
+/// Eメールアドレスを確認するトークンのキーのプレフィックス
+const EMAIL_VERIFICATION_KEY_PREFIX: &str = "email_verification";
+/// Eメールアドレスの変更を確定するトークンのキーのプレフィックス
+const EMAIL_CHANGE_KEY_PREFIX: &str = "email_change";
+/// 2要素認証を待機しているサインインを示すトークンのキーのプレフィックス
+const MFA_PENDING_KEY_PREFIX: &str = "mfa_pending";
+/// パスワードを変更するためのトークンのキーのプレフィックス
+const PASSWORD_CHANGE_KEY_PREFIX: &str = "password_change";
+/// アクセストークンで再認証したことを示すキーのプレフィックス
+const REAUTHENTICATION_KEY_PREFIX: &str = "reauthentication";
+/// セッションのキーのプレフィックス
+const SESSION_KEY_PREFIX: &str = "session";
+/// ユーザーに発行したトークンのキーを保存するセットのキーのプレフィックス
+const USER_TOKENS_KEY_PREFIX: &str = "tokens";
+/// セッションIDの文字数
+const SESSION_ID_LENGTH: usize = 16;
+
 const CONNECTION_ERROR: &str = "Redisに接続するときにエラーが発生しました。";
 const STORE_ERROR: &str = "Redisにキーと値を保存するときにエラーが発生しました。";
+const PING_ERROR: &str = "RedisにPINGコマンドを送信したときにエラーが発生しました。";
 const RETRIEVE_ERROR: &str = "Redisからキーで値を取得するときにエラーが発生しました。";
+const DELETE_ERROR: &str = "Redisからキーを削除するときにエラーが発生しました。";
 const USER_ID_NOT_FOUND: &str = "Redisに登録された値からユーザーIDを取得できませんでした。";
 const TOKEN_TYPE_NOT_FOUND: &str = "Redisに登録された値からトークンの種類を取得できませんでした。";
 const USER_PERMISSION_NOT_FOUND: &str =
@@ -200,6 +850,11 @@ const TOKEN_TYPE_CONSTRUCTION_FAILED: &str =
     "Redisに登録された値からトークンの種類を確認できませんでした。";
 const USER_PERMISSION_CONSTRUCTION_FAILED: &str =
     "Redisに登録された値からユーザー権限を確認できませんでした。";
+const EMAIL_NOT_FOUND: &str = "Redisに登録された値からEメールアドレスを取得できませんでした。";
+const EMAIL_CONSTRUCTION_FAILED: &str =
+    "Redisに登録された値からEメールアドレスを確認できませんでした。";
+const SESSION_CONSTRUCTION_FAILED: &str =
+    "Redisに登録された値からセッションを確認できませんでした。";
 
 #[cfg(test)]
 mod tests {
@@ -235,4 +890,48 @@ mod tests {
 
         Ok(())
     }
+
+    /// Eメールアドレスを確認するトークンのキーが、同じトークンのアクセストークンのキーと異なることを確認
+    #[test]
+    fn email_verification_key_differs_from_token_key() {
+        let token = SecretString::new(String::from("token"));
+        let key = generate_email_verification_key(&token);
+
+        assert_eq!(format!("email_verification:{}", generate_key(&token)), key);
+        assert_ne!(generate_key(&token), key);
+    }
+
+    /// Eメールアドレスの変更を確定するトークンの値を、ユーザーIDと変更後のEメールアドレスに分割できることを確認
+    #[test]
+    fn can_split_email_change_value() -> anyhow::Result<()> {
+        let user_id = UserId::default();
+        let input = format!("{}:foo@example.com", user_id);
+        let email_change = split_email_change_value(&input)?;
+        assert_eq!(user_id, email_change.user_id);
+        assert_eq!("foo@example.com", email_change.email.value);
+        assert!(split_email_change_value("foo@example.com").is_err());
+
+        Ok(())
+    }
+
+    /// セッションの値を、トークンを発行した日時、トークンのキー及びデバイスに分割できることを確認
+    #[test]
+    fn can_split_session_value() -> anyhow::Result<()> {
+        let issued_at = OffsetDateTime::from_unix_timestamp(1_700_000_000)?;
+        let device = "Mozilla/5.0 (X11; Linux x86_64) http://example.com";
+        let value = generate_session_value(issued_at, "access", "refresh", Some(device));
+        assert_eq!(
+            (issued_at, "access", "refresh", Some(device)),
+            split_session_value(&value)?
+        );
+
+        let value = generate_session_value(issued_at, "access", "refresh", None);
+        assert_eq!(
+            (issued_at, "access", "refresh", None),
+            split_session_value(&value)?
+        );
+        assert!(split_session_value("access:refresh").is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,261 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Instant;
+
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::HttpMessage as _;
+
+use configurations::settings::AccessLogSettings;
+use domain::models::user::UserId;
+
+use crate::routes::extractors::ClientIp;
+
+/// ルートにマッチしなかったリクエストを記録するときのルート名
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// ヘルスチェック及びレディネスチェックのパス
+const HEALTH_CHECK_PATHS: [&str; 2] = ["/health-check", "/health-check/ready"];
+
+/// リクエストごとにアクセスログを記録するミドルウェア
+///
+/// レスポンスを返すときに、リクエストのHTTPメソッド、マッチしたルートのパターン、レスポンスの
+/// ステータスコード、処理時間（ミリ秒）、レスポンスボディのサイズ、クライアントのIPアドレス、
+/// 認証されたリクエストの場合はユーザーIDを、1つのイベントとして記録する。
+/// ステータスコードが5xxの場合は`WARN`、それ以外の場合は`INFO`レベルで記録する。
+#[derive(Debug, Clone, Default)]
+pub struct RequestLogger {
+    /// ヘルスチェック及びレディネスチェックへのリクエストを記録しないか示すフラグ
+    exclude_health_check: bool,
+}
+
+impl RequestLogger {
+    /// アクセスログ設定から、アクセスログを記録するミドルウェアを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `settings` - アクセスログ設定
+    ///
+    /// # 戻り値
+    ///
+    /// アクセスログを記録するミドルウェア
+    pub fn new(settings: &AccessLogSettings) -> Self {
+        Self {
+            exclude_health_check: settings.exclude_health_check,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestLogger
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RequestLoggerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestLoggerMiddleware {
+            service: Rc::new(service),
+            exclude_health_check: self.exclude_health_check,
+        }))
+    }
+}
+
+pub struct RequestLoggerMiddleware<S> {
+    service: Rc<S>,
+    exclude_health_check: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestLoggerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let method = req.method().to_string();
+        let client_ip = ClientIp::resolve(req.request())
+            .map(|ClientIp(ip)| ip.to_string())
+            .unwrap_or_default();
+        let exclude_health_check = self.exclude_health_check;
+        let started_at = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            let route = res
+                .request()
+                .match_pattern()
+                .unwrap_or_else(|| String::from(UNMATCHED_ROUTE));
+            if exclude_health_check && is_health_check(&route) {
+                return Ok(res);
+            }
+            let latency_ms = started_at.elapsed().as_secs_f64() * 1000.0;
+            let status_code = res.status().as_u16();
+            let response_size = match res.response().body().size() {
+                BodySize::Sized(size) => Some(size),
+                BodySize::None => Some(0),
+                BodySize::Stream => None,
+            };
+            let user_id = res.request().extensions().get::<UserId>().copied();
+            let user_id = user_id.as_ref().map(tracing::field::display);
+            if res.status().is_server_error() {
+                tracing::warn!(
+                    http.method = %method,
+                    http.route = %route,
+                    http.status_code = status_code,
+                    latency_ms,
+                    response_size,
+                    client_ip = %client_ip,
+                    user_id,
+                    "access"
+                );
+            } else {
+                tracing::info!(
+                    http.method = %method,
+                    http.route = %route,
+                    http.status_code = status_code,
+                    latency_ms,
+                    response_size,
+                    client_ip = %client_ip,
+                    user_id,
+                    "access"
+                );
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// ヘルスチェックまたはレディネスチェックのルートであるか確認する。
+///
+/// ルートのパターンには、APIのパスのプレフィックスが含まれるため、パターンの末尾で判定する。
+fn is_health_check(route: &str) -> bool {
+    HEALTH_CHECK_PATHS.iter().any(|path| route.ends_with(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use actix_web::http::StatusCode;
+    use actix_web::{test, web, App, HttpRequest, HttpResponse};
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use super::*;
+
+    /// ログを記録するバッファ
+    #[derive(Clone, Default)]
+    struct BufferWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for BufferWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for BufferWriter {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// アクセスログを記録するミドルウェアを登録したアプリで、リクエストを処理して、記録したログを返す。
+    async fn call_with_logger(settings: AccessLogSettings, uri: &str) -> (StatusCode, String) {
+        let buffer = BufferWriter::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(buffer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestLogger::new(&settings))
+                .route(
+                    "/users/{user_id}",
+                    web::get().to(|request: HttpRequest| async move {
+                        // 認証済みのリクエストを模倣
+                        request.extensions_mut().insert(UserId::default());
+                        HttpResponse::Ok().body("ok")
+                    }),
+                )
+                .route(
+                    "/api/v1/health-check",
+                    web::get().to(|| async { HttpResponse::Ok().finish() }),
+                ),
+        )
+        .await;
+        let res = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        let status_code = res.status();
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+
+        (status_code, output)
+    }
+
+    /// 200 OKを返したリクエストのアクセスログに、期待したフィールドが記録されることを確認
+    #[actix_web::test]
+    async fn access_log_records_fields_for_ok_response() {
+        let (status_code, output) =
+            call_with_logger(AccessLogSettings::default(), "/users/123").await;
+
+        assert_eq!(StatusCode::OK, status_code);
+        assert_eq!(1, output.lines().count(), "{}", output);
+        assert!(output.contains(" INFO "), "{}", output);
+        assert!(output.contains("http.method=GET"), "{}", output);
+        assert!(output.contains("http.route=/users/{user_id}"), "{}", output);
+        assert!(output.contains("http.status_code=200"), "{}", output);
+        assert!(output.contains("latency_ms="), "{}", output);
+        assert!(output.contains("response_size=2"), "{}", output);
+        assert!(output.contains("client_ip="), "{}", output);
+        assert!(output.contains("user_id="), "{}", output);
+    }
+
+    /// 404 Not Foundを返したリクエストのアクセスログに、期待したフィールドが記録されることを確認
+    #[actix_web::test]
+    async fn access_log_records_fields_for_not_found_response() {
+        let (status_code, output) =
+            call_with_logger(AccessLogSettings::default(), "/not-found").await;
+
+        assert_eq!(StatusCode::NOT_FOUND, status_code);
+        assert_eq!(1, output.lines().count(), "{}", output);
+        assert!(output.contains(" INFO "), "{}", output);
+        assert!(output.contains("http.method=GET"), "{}", output);
+        assert!(output.contains("http.route=unmatched"), "{}", output);
+        assert!(output.contains("http.status_code=404"), "{}", output);
+        assert!(!output.contains("user_id="), "{}", output);
+    }
+
+    /// ヘルスチェックを除外する設定の場合は、ヘルスチェックへのリクエストを記録しないことを確認
+    #[actix_web::test]
+    async fn access_log_excludes_health_check_when_configured() {
+        let settings = AccessLogSettings {
+            exclude_health_check: true,
+        };
+        let (status_code, output) = call_with_logger(settings, "/api/v1/health-check").await;
+        assert_eq!(StatusCode::OK, status_code);
+        assert!(output.is_empty(), "{}", output);
+
+        let (_, output) =
+            call_with_logger(AccessLogSettings::default(), "/api/v1/health-check").await;
+        assert_eq!(1, output.lines().count(), "{}", output);
+    }
+}
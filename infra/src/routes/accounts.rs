@@ -1,90 +1,279 @@
 use actix_web::cookie::Cookie;
-use actix_web::{web, HttpResponse};
+use actix_web::http::header::{
+    CacheControl, CacheDirective, ContentDisposition, DispositionParam, DispositionType,
+    AUTHORIZATION, USER_AGENT,
+};
+use actix_web::http::{Method, StatusCode};
+use actix_web::web::Bytes;
+use actix_web::{web, HttpRequest, HttpResponse};
+use futures_util::{stream, StreamExt as _};
 use secrecy::{ExposeSecret, SecretString};
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use configurations::settings::HttpServerSettings;
+use domain::models::audit_log::AuditActor;
 use domain::models::primitives::*;
-use domain::models::user::{User, UserPermissionCode};
+use domain::models::query::{Pagination, SortDirection};
+use domain::models::user::{User, UserId, UserPermission, UserPermissionCode};
+use domain::repositories::token::{
+    AccessToken, ActiveSession, RefreshToken, SessionToken, TokenContent,
+};
+use domain::repositories::user::{LockedUser, UserListFilter, UserListOrder};
+use domain::DomainResult;
+use macros::MapFrom;
 use use_cases::accounts::{
-    SignInUseCaseInput, SignInUseCaseOutput, SignUpUseCaseInputBuilder, SignUpUseCaseOutput,
+    AccountActivity, BulkSignUpResult, BulkSignUpUseCaseInput, BulkSignUpUseCaseOutput,
+    ChangeRequiredPasswordUseCaseInput, CreateUserUseCaseInput, CreateUserUseCaseOutput,
+    DeleteUserRequester, DeleteUserUseCaseInput, MfaPendingUseCaseOutput,
+    PasswordChangeRequiredUseCaseOutput, Reauthentication, RequestEmailChangeUseCaseInput,
+    SignInResult, SignInUseCaseInput, SignInUseCaseOutput, SignInWithTotpUseCaseInput,
+    SignUpUseCaseInput, SignUpUseCaseInputBuilder, SignUpUseCaseOutput, TokenIntrospection,
+    TotpSetupUseCaseOutput, UpdateProfileUseCaseInput,
 };
-use use_cases::UseCaseError;
+use use_cases::messages::{MessageKey, UseCaseMessage};
+use use_cases::{UseCaseError, ERR_REGISTRATION_IS_DISABLED, ERR_USER_PERMISSION_IS_NOT_ALLOWED};
 
-use crate::routes::extractors::{AdminContext, UserOwnContext};
+use crate::routes::etags::{conditional_json_response, if_match_version, version_etag};
+use crate::routes::extractors::{
+    AdminContext, AdminOrOwnerContext, ClientIp, OptionalUserContext, RequestAccessToken,
+    UserContext,
+};
+use crate::routes::idempotency::idempotent;
+use crate::routes::ip_allowlist::IpAllowlistGuard;
 use crate::routes::{
-    ProcessRequestError, ProcessRequestResult, ACCESS_TOKEN_KEY, REFRESH_TOKEN_KEY,
+    json_config, method_not_allowed, parse_request_body, ErrorResponseBody, FieldMessage,
+    ProcessRequestError, ProcessRequestResult,
 };
 use crate::RequestContext;
 
 /// アカウントスコープを返却する。
-pub fn accounts_scope() -> actix_web::Scope {
+///
+/// # 引数
+///
+/// * `settings` - HTTPサーバー設定
+/// * `admin_ip_allowlist` - 管理者向けのエンドポイントに適用する、アクセス元のIPアドレスを確認するミドルウェア
+pub fn accounts_scope(
+    settings: &HttpServerSettings,
+    admin_ip_allowlist: &IpAllowlistGuard,
+) -> actix_web::Scope {
+    // 一括登録するユーザーの数に応じて、リクエストボディの最大サイズを大きくする
+    let import_payload_limit = settings
+        .max_json_payload_bytes
+        .saturating_mul(settings.max_import_users);
+
     web::scope("/accounts")
-        .service(web::resource("/sign-up").route(web::post().to(sign_up)))
-        .service(web::resource("/sign-in").route(web::post().to(sign_in)))
+        .service(
+            web::resource("/sign-up")
+                .route(web::post().to(sign_up))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/sign-in")
+                .route(web::post().to(sign_in))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/sign-in/2fa")
+                .route(web::post().to(sign_in_with_totp))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/password")
+                .route(web::post().to(change_required_password))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/session")
+                .route(web::get().to(session))
+                .default_service(method_not_allowed(&[Method::GET])),
+        )
+        .service(
+            web::resource("/2fa/setup")
+                .route(web::post().to(set_up_totp))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/2fa/enable")
+                .route(web::post().to(enable_totp))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/2fa/disable")
+                .route(web::post().to(disable_totp))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/verify-email")
+                .route(web::post().to(verify_email))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/verify-email/resend")
+                .route(web::post().to(resend_email_verification))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/sessions")
+                .route(web::get().to(list_sessions))
+                .default_service(method_not_allowed(&[Method::GET])),
+        )
+        .service(
+            web::resource("/sessions/{session_id}")
+                .route(web::delete().to(revoke_session))
+                .default_service(method_not_allowed(&[Method::DELETE])),
+        )
+        .service(
+            web::resource("/token/introspect")
+                .route(web::post().to(introspect_token))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/reauthenticate")
+                .route(web::post().to(reauthenticate))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/email-change")
+                .route(web::post().to(request_email_change))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/email-change/confirm")
+                .route(web::post().to(confirm_email_change))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
         .service(
             web::scope("/users")
-                .service(web::resource("").route(web::get().to(list_users)))
+                .wrap(admin_ip_allowlist.clone())
+                .service(
+                    web::resource("")
+                        .route(web::get().to(list_users))
+                        .route(web::post().to(create_user))
+                        .default_service(method_not_allowed(&[Method::GET, Method::POST])),
+                )
+                // `/{user_id}`に一致しないように、`/{user_id}`よりも先に登録
+                .service(
+                    web::resource("/locked")
+                        .route(web::get().to(list_locked_users))
+                        .default_service(method_not_allowed(&[Method::GET])),
+                )
+                .service(
+                    web::resource("/export")
+                        .route(web::get().to(export_users))
+                        .default_service(method_not_allowed(&[Method::GET])),
+                )
+                .service(
+                    web::resource("/import")
+                        .app_data(json_config(import_payload_limit))
+                        .route(web::post().to(import_users))
+                        .default_service(method_not_allowed(&[Method::POST])),
+                )
                 .service(
                     web::scope("/{user_id}")
-                        .service(web::resource("").route(web::get().to(user_detail))),
+                        .service(
+                            web::resource("")
+                                .route(web::get().to(user_detail))
+                                .route(web::patch().to(update_profile))
+                                .route(web::delete().to(delete_user))
+                                .default_service(method_not_allowed(&[
+                                    Method::GET,
+                                    Method::PATCH,
+                                    Method::DELETE,
+                                ])),
+                        )
+                        .service(
+                            web::resource("/permission")
+                                .route(web::put().to(change_user_permission))
+                                .default_service(method_not_allowed(&[Method::PUT])),
+                        )
+                        .service(
+                            web::resource("/activity")
+                                .route(web::get().to(account_activity))
+                                .default_service(method_not_allowed(&[Method::GET])),
+                        ),
                 ),
         )
 }
 
 /// サインアップ
+#[utoipa::path(
+    post,
+    path = "/accounts/sign-up",
+    tag = "accounts",
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "再送信したリクエストを冪等に処理する冪等キー"),
+    ),
+    request_body = SignUpReqBody,
+    responses(
+        (status = 200, description = "サインアップしたユーザー", body = SignUpResBody),
+        (status = 400, description = "リクエストの内容が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "サインアップが無効になっている、または管理者以外がサインアップで登録できないユーザー権限を指定した", body = ErrorResponseBody),
+        (status = 409, description = "同じ冪等キーのリクエストを処理している", body = ErrorResponseBody),
+        (status = 422, description = "冪等キーが異なるリクエストボディで使用されている", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "sign up handler",
+    skip(context, admin_context, request, request_body)
+)]
 pub async fn sign_up(
     context: web::Data<RequestContext>,
-    request_body: web::Json<SignUpReqBody>,
+    admin_context: Option<AdminContext>,
+    request: HttpRequest,
+    request_body: web::Json<serde_json::Value>,
 ) -> ProcessRequestResult<HttpResponse> {
-    let password_settings = &context.password_settings;
-    let user_repository = context.user_repository();
-    let input = request_body.0;
+    // サインアップが無効な場合は、ユーザーを登録しない
+    if !context.http_server_settings.registration_enabled {
+        return Err(ProcessRequestError::new(
+            StatusCode::FORBIDDEN,
+            Some(ERR_REGISTRATION_IS_DISABLED),
+            MessageKey::RegistrationIsDisabled,
+        ));
+    }
 
-    let email = EmailAddress::new(input.email).map_err(ProcessRequestError::from)?;
-    let user_permission_code = UserPermissionCode::try_from(input.user_permission_code)
-        .map_err(ProcessRequestError::from)?;
-    let password = RawPassword::new(input.password).map_err(ProcessRequestError::from)?;
-    let family_name = FamilyName::new(input.family_name).map_err(ProcessRequestError::from)?;
-    let given_name = GivenName::new(input.given_name).map_err(ProcessRequestError::from)?;
-    let postal_code = PostalCode::new(input.postal_code).map_err(ProcessRequestError::from)?;
-    let address = Address::new(input.address).map_err(ProcessRequestError::from)?;
-    let fixed_phone_number = OptionalFixedPhoneNumber::try_from(input.fixed_phone_number)
-        .map_err(ProcessRequestError::from)?;
-    let mobile_phone_number = OptionalMobilePhoneNumber::try_from(input.mobile_phone_number)
-        .map_err(ProcessRequestError::from)?;
-    let remarks = OptionalRemarks::try_from(input.remarks).map_err(ProcessRequestError::from)?;
-
-    let input = SignUpUseCaseInputBuilder::new()
-        .email(email)
-        .password(password)
-        .active(true)
-        .user_permission_code(user_permission_code)
-        .family_name(family_name)
-        .given_name(given_name)
-        .postal_code(postal_code)
-        .address(address)
-        .fixed_phone_number(fixed_phone_number)
-        .mobile_phone_number(mobile_phone_number)
-        .remarks(remarks)
-        .build()
-        .map_err(|e| UseCaseError::domain_rule(e.to_string()))?;
-
-    use_cases::accounts::sign_up(password_settings, user_repository, input)
-        .await
-        .map(|user| HttpResponse::Ok().json(SignUpResBody::from(user)))
-        .map_err(|e| e.into())
+    // 冪等キーを検証するためにリクエストボディのハッシュ値を計算できるように、JSONとして受け取る
+    let request_body = request_body.into_inner();
+    idempotent(&context, &request, SIGN_UP_SCOPE, &request_body, || async {
+        let request_body = parse_request_body::<SignUpReqBody>(
+            request_body.clone(),
+            context.http_server_settings.strict_request_bodies,
+        )?;
+        let input = request_body.validate_into_input(&context.password_settings.policy())?;
+        // 管理者以外は、設定で許可されたユーザー権限のユーザーのみ登録できる
+        if admin_context.is_none()
+            && !context
+                .http_server_settings
+                .allows_sign_up_with(input.user_permission_code)
+        {
+            return Err(ProcessRequestError::new(
+                StatusCode::FORBIDDEN,
+                Some(ERR_USER_PERMISSION_IS_NOT_ALLOWED),
+                MessageKey::UserPermissionIsNotAllowed,
+            ));
+        }
+
+        context
+            .accounts()
+            .sign_up(input)
+            .await
+            .map(|user| HttpResponse::Ok().json(SignUpResBody::from(user)))
+            .map_err(|e| e.into())
+    })
+    .await
 }
 
 /// サインアップリクエスト・ボディ
 /// ```
-#[derive(Debug, Clone, serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
 pub struct SignUpReqBody {
     /// Eメールアドレス
     pub email: String,
     /// 未加工なパスワード
+    #[schema(value_type = String, write_only)]
     pub password: SecretString,
     /// ユーザー権限コード
     pub user_permission_code: i16,
@@ -104,31 +293,186 @@ pub struct SignUpReqBody {
     pub remarks: Option<String>,
 }
 
+impl SignUpReqBody {
+    /// すべてのフィールドを検証して、アクティブなユーザーを登録するサインアップユースケース入力を構築する。
+    ///
+    /// 最初に検証に失敗したフィールドで中断せずに、すべてのフィールドを検証して、検証に失敗した
+    /// フィールドのエラーをまとめて返す。ただし、極端に長い文字列を含む場合は、ドメインプリミティブを
+    /// 構築する前に、長すぎるフィールドのエラーを返す。
+    ///
+    /// # 引数
+    ///
+    /// * `policy` - パスワードポリシー
+    ///
+    /// # 戻り値
+    ///
+    /// サインアップユースケース入力
+    pub fn validate_into_input(
+        self,
+        policy: &PasswordPolicy,
+    ) -> ProcessRequestResult<SignUpUseCaseInput> {
+        validate_string_lengths(&[
+            ("email", Some(&self.email)),
+            ("password", Some(self.password.expose_secret())),
+            ("familyName", Some(&self.family_name)),
+            ("givenName", Some(&self.given_name)),
+            ("postalCode", Some(&self.postal_code)),
+            ("address", Some(&self.address)),
+            ("fixedPhoneNumber", self.fixed_phone_number.as_deref()),
+            ("mobilePhoneNumber", self.mobile_phone_number.as_deref()),
+            ("remarks", self.remarks.as_deref()),
+        ])?;
+        let mut errors = vec![];
+        let email = validate_field(&mut errors, "email", EmailAddress::new(self.email));
+        let password = validate_field(
+            &mut errors,
+            "password",
+            RawPassword::with_policy(self.password, policy),
+        );
+        let user_permission_code = validate_field(
+            &mut errors,
+            "userPermissionCode",
+            UserPermissionCode::try_from(self.user_permission_code),
+        );
+        let family_name =
+            validate_field(&mut errors, "familyName", FamilyName::new(self.family_name));
+        let given_name = validate_field(&mut errors, "givenName", GivenName::new(self.given_name));
+        let postal_code =
+            validate_field(&mut errors, "postalCode", PostalCode::new(self.postal_code));
+        let address = validate_field(&mut errors, "address", Address::new(self.address));
+        let fixed_phone_number = validate_field(
+            &mut errors,
+            "fixedPhoneNumber",
+            OptionalFixedPhoneNumber::try_from(self.fixed_phone_number),
+        );
+        let mobile_phone_number = validate_field(
+            &mut errors,
+            "mobilePhoneNumber",
+            OptionalMobilePhoneNumber::try_from(self.mobile_phone_number),
+        );
+        let remarks = validate_field(
+            &mut errors,
+            "remarks",
+            OptionalRemarks::try_from(self.remarks),
+        );
+        if !errors.is_empty() {
+            return Err(ProcessRequestError::invalid_fields(errors));
+        }
+
+        // すべてのフィールドの検証に成功しているため、`unwrap`しても失敗しない
+        SignUpUseCaseInputBuilder::new()
+            .email(email.unwrap())
+            .password(password.unwrap())
+            .active(true)
+            .user_permission_code(user_permission_code.unwrap())
+            .family_name(family_name.unwrap())
+            .given_name(given_name.unwrap())
+            .postal_code(postal_code.unwrap())
+            .address(address.unwrap())
+            .fixed_phone_number(fixed_phone_number.unwrap())
+            .mobile_phone_number(mobile_phone_number.unwrap())
+            .remarks(remarks.unwrap())
+            .build()
+            .map_err(|e| UseCaseError::domain_rule(e.to_string()).into())
+    }
+}
+
+/// フィールドの検証結果から値を取り出す。
+///
+/// 検証に失敗した場合は、フィールドのエラーを追加して`None`を返す。
+///
+/// # 引数
+///
+/// * `errors` - 検証に失敗したフィールドのエラーを追加するベクタ
+/// * `field` - リクエスト・ボディのフィールド名
+/// * `result` - フィールドの検証結果
+///
+/// # 戻り値
+///
+/// 検証に成功した場合はフィールドの値、それ以外の場合は`None`
+fn validate_field<T>(
+    errors: &mut Vec<FieldMessage>,
+    field: &'static str,
+    result: DomainResult<T>,
+) -> Option<T> {
+    result
+        .map_err(|e| errors.push(FieldMessage::new(field, e.to_string())))
+        .ok()
+}
+
+/// リクエスト・ボディの文字列フィールドの最大バイト数
+///
+/// 極端に長い文字列を正規表現で検証したり、ハッシュ化したりしないように、ドメインプリミティブを
+/// 構築する前に拒否する。
+const MAX_REQUEST_STRING_BYTES: usize = 1024;
+
+/// リクエスト・ボディの文字列フィールドが、最大バイト数以下であるか確認する。
+///
+/// # 引数
+///
+/// * `fields` - リクエスト・ボディのフィールド名と値のスライス
+///
+/// # 戻り値
+///
+/// すべてのフィールドが最大バイト数以下の場合は`()`、それ以外の場合は長すぎるフィールドのエラー
+fn validate_string_lengths(fields: &[(&'static str, Option<&str>)]) -> ProcessRequestResult<()> {
+    let errors = fields
+        .iter()
+        .filter(|(_, value)| value.is_some_and(|value| MAX_REQUEST_STRING_BYTES < value.len()))
+        .map(|(field, _)| {
+            FieldMessage::new(
+                *field,
+                UseCaseMessage::from(MessageKey::StringIsTooLong)
+                    .with_param("max", MAX_REQUEST_STRING_BYTES),
+            )
+        })
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return Err(ProcessRequestError::invalid_fields(errors));
+    }
+
+    Ok(())
+}
+
 /// サインアップレスポンス・ボディ
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema, MapFrom)]
 #[serde(rename_all = "camelCase")]
+#[map_from(source = "SignUpUseCaseOutput")]
 pub struct SignUpResBody {
     /// ユーザーID
-    pub id: Uuid,
+    #[schema(value_type = Uuid)]
+    pub id: UserId,
     /// Eメールアドレス
+    #[map_from(value)]
     pub email: String,
     /// アクティブフラグ
     pub active: bool,
     /// ユーザー権限コード
+    #[map_from(rename = "user_permission.code", into)]
     pub user_permission_code: i16,
+    /// ユーザー権限名
+    #[map_from(rename = "user_permission.name", value)]
+    pub user_permission_name: String,
     /// 苗字
+    #[map_from(value)]
     pub family_name: String,
     /// 名前
+    #[map_from(value)]
     pub given_name: String,
     /// 郵便番号
+    #[map_from(value)]
     pub postal_code: String,
     /// 住所
+    #[map_from(value)]
     pub address: String,
     /// 固定電話番号
+    #[map_from(owned_value)]
     pub fixed_phone_number: Option<String>,
     /// 携帯電話番号
+    #[map_from(owned_value)]
     pub mobile_phone_number: Option<String>,
     /// 備考
+    #[map_from(owned_value)]
     pub remarks: Option<String>,
     /// 作成日時
     #[serde(with = "time::serde::rfc3339")]
@@ -138,120 +482,1035 @@ pub struct SignUpResBody {
     pub updated_at: OffsetDateTime,
 }
 
-impl From<SignUpUseCaseOutput> for SignUpResBody {
-    fn from(value: SignUpUseCaseOutput) -> Self {
-        Self {
-            id: value.id.value,
-            email: value.email.value,
-            active: value.active,
-            user_permission_code: value.user_permission_code as i16,
-            family_name: value.family_name.value,
-            given_name: value.given_name.value,
-            postal_code: value.postal_code.value,
-            address: value.address.value,
-            fixed_phone_number: value.fixed_phone_number.owned_value(),
-            mobile_phone_number: value.mobile_phone_number.owned_value(),
-            remarks: value.remarks.owned_value(),
-            created_at: value.created_at,
-            updated_at: value.updated_at,
+/// サインイン
+///
+/// ユーザーが2要素認証を有効にしている場合は、202 Acceptedで2要素認証を待機しているサインインを
+/// 示すトークンを返す。トークンとTOTPコードを`POST /accounts/sign-in/2fa`に送信して、サインインを完了する。
+///
+/// ユーザーがパスワードの変更を要求されている場合は、202 Acceptedでパスワードを変更するための
+/// トークンを返す。トークンと変更後のパスワードを`POST /accounts/password`に送信して、パスワードを
+/// 変更した後に、もう一度サインインする。
+///
+/// どのレスポンスを返したかは、レスポンス・ボディの`status`で判断する。
+#[utoipa::path(
+    post,
+    path = "/accounts/sign-in",
+    tag = "accounts",
+    request_body = SignInReqBody,
+    responses(
+        (status = 200, description = "アクセストークンとリフレッシュトークン", body = SignInResBody),
+        (status = 202, description = "2要素認証を待機しているサインインを示すトークン（`status`が`passwordChangeRequired`の場合は、PasswordChangeRequiredResBodyでパスワードを変更するためのトークン）", body = MfaPendingResBody),
+        (status = 400, description = "リクエストの内容が誤っている", body = ErrorResponseBody),
+        (status = 401, description = "Eメールアドレスまたはパスワードが誤っている、アカウントがロックされている、またはEメールアドレスを確認していない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+        (status = 503, description = "データベースが応答しないため、一時的にサインインできない", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "sign in handler", skip(context, request, request_body))]
+pub async fn sign_in(
+    context: web::Data<RequestContext>,
+    request: HttpRequest,
+    request_body: web::Json<serde_json::Value>,
+) -> ProcessRequestResult<HttpResponse> {
+    let request_body = parse_request_body::<SignInReqBody>(
+        request_body.into_inner(),
+        context.http_server_settings.strict_request_bodies,
+    )?;
+    validate_string_lengths(&[
+        ("email", Some(&request_body.email)),
+        ("password", Some(request_body.password.expose_secret())),
+    ])?;
+    let email = EmailAddress::new(request_body.email).map_err(ProcessRequestError::from)?;
+    let password =
+        RawPassword::with_policy(request_body.password, &context.password_settings.policy())
+            .map_err(ProcessRequestError::from)?;
+    let device = request_device(&request);
+    let input = SignInUseCaseInput {
+        email,
+        password,
+        device: device.clone(),
+        client_ip: ClientIp::resolve(&request).map(|ClientIp(ip)| ip),
+        user_agent: device,
+    };
+
+    let result = context
+        .accounts()
+        .sign_in(input)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    match result {
+        SignInResult::SignedIn(output) => Ok(signed_in_response(&context, &output)),
+        SignInResult::MfaPending(output) => {
+            Ok(HttpResponse::Accepted().json(MfaPendingResBody::from(&output)))
         }
+        SignInResult::PasswordChangeRequired(output) => Ok(HttpResponse::Accepted()
+            .insert_header(CacheControl(vec![CacheDirective::NoStore]))
+            .json(PasswordChangeRequiredResBody::from(&output))),
     }
 }
 
-/// サインイン
-pub async fn sign_in(
+/// 2要素認証サインイン
+///
+/// サインインで受け取った2要素認証を待機しているサインインを示すトークンと、認証アプリが生成した
+/// TOTPコードで、サインインを完了する。トークンは1回だけ使用できる。
+#[utoipa::path(
+    post,
+    path = "/accounts/sign-in/2fa",
+    tag = "accounts",
+    request_body = SignInWithTotpReqBody,
+    responses(
+        (status = 200, description = "アクセストークンとリフレッシュトークン", body = SignInResBody),
+        (status = 400, description = "リクエストの内容が誤っている", body = ErrorResponseBody),
+        (status = 401, description = "トークンが無効、有効期限が切れている、TOTPコードが誤っている、またはアカウントがロックされている", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "sign in with totp handler",
+    skip(context, request, request_body)
+)]
+pub async fn sign_in_with_totp(
     context: web::Data<RequestContext>,
-    request_body: web::Json<SignInReqBody>,
+    request: HttpRequest,
+    request_body: web::Json<SignInWithTotpReqBody>,
 ) -> ProcessRequestResult<HttpResponse> {
-    let http_server_settings = &context.http_server_settings;
-    let password_settings = &context.password_settings;
-    let authorization_settings = &context.authorization_settings;
-    let user_repository = context.user_repository();
-    let token_repository = context.token_repository();
-    let email = EmailAddress::new(request_body.0.email).map_err(ProcessRequestError::from)?;
-    let password = RawPassword::new(request_body.0.password).map_err(ProcessRequestError::from)?;
-    let input = SignInUseCaseInput { email, password };
-
-    let output = use_cases::accounts::sign_in(
-        password_settings,
-        authorization_settings,
-        user_repository,
-        token_repository,
-        input,
-    )
-    .await
-    .map_err(ProcessRequestError::from)?;
+    let device = request_device(&request);
+    let input = SignInWithTotpUseCaseInput {
+        token: request_body.0.token,
+        code: request_body.0.code,
+        device: device.clone(),
+        client_ip: ClientIp::resolve(&request).map(|ClientIp(ip)| ip),
+        user_agent: device,
+    };
+
+    let output = context
+        .accounts()
+        .sign_in_with_totp(input)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(signed_in_response(&context, &output))
+}
+
+/// `User-Agent`ヘッダの値で、サインインしたデバイスを識別する文字列を返す。
+fn request_device(request: &HttpRequest) -> Option<String> {
+    request
+        .headers()
+        .get(USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(String::from)
+}
 
+/// サインインが完了したときのレスポンスを構築する。
+///
+/// # 引数
+///
+/// * `context` - リクエストコンテキスト
+/// * `output` - アクセストークンとリフレッシュトークン
+///
+/// # 戻り値
+///
+/// クッキーにアクセス及びリフレッシュトークンを設定して、レスポンスボディにアクセス及び
+/// リフレッシュトークンを格納したレスポンス
+fn signed_in_response(context: &RequestContext, output: &SignInUseCaseOutput) -> HttpResponse {
     // レスポンスヘッダに、クッキーにアクセス及びリクエストトークンを設定する`Set-Cookie`を追加する。
     let access_cookie = generate_token_cookie(
-        ACCESS_TOKEN_KEY,
-        &output.access,
+        context,
+        CookieToken::Access(&output.access),
         output.access_expiration,
-        http_server_settings,
     );
     let refresh_cookie = generate_token_cookie(
-        REFRESH_TOKEN_KEY,
-        &output.access,
+        context,
+        CookieToken::Refresh(&output.refresh),
         output.refresh_expiration,
-        http_server_settings,
     );
     // レスポンスボディを構築
-    let body = SignInResBody::from(&output);
+    let body = SignInResBody::from(output);
 
-    Ok(HttpResponse::Ok()
+    HttpResponse::Ok()
         .cookie(access_cookie)
         .cookie(refresh_cookie)
-        .json(body))
+        .json(body)
+}
+
+/// クッキーに保存するトークン
+///
+/// クッキーの名前と有効期間をトークンの種類で決めて、アクセストークンとリフレッシュトークンを
+/// 取り違えたクッキーを生成しないようにする。
+enum CookieToken<'a> {
+    /// アクセストークン
+    Access(&'a AccessToken),
+    /// リフレッシュトークン
+    Refresh(&'a RefreshToken),
 }
 
+/// アクセス／リフレッシュトークンを保存するクッキーを生成する。
+///
+/// クライアントの時計がずれている場合に備えて、`Expires`属性に加えて`Max-Age`属性を付与する。
+/// `__Host-`プレフィックスを付与する場合は、Secure属性を付与して、Path属性を`/`にし、Domain属性を付与しない。
+///
+/// # 引数
+///
+/// * `context` - リクエストコンテキスト
+/// * `token` - アクセス／リフレッシュトークン
+/// * `expiration` - トークンの有効期限
+///
+/// # 戻り値
+///
+/// クッキー
 fn generate_token_cookie<'a>(
-    name: &'a str,
-    token: &'a SecretString,
+    context: &RequestContext,
+    token: CookieToken<'a>,
     expiration: OffsetDateTime,
-    http_settings: &HttpServerSettings,
 ) -> Cookie<'a> {
-    Cookie::build(name, token.expose_secret())
+    let http_settings = &context.http_server_settings;
+    let authorization_settings = &context.authorization_settings;
+    let (name, value, max_age) = match token {
+        CookieToken::Access(token) => (
+            context.access_token_cookie_name(),
+            token.expose(),
+            authorization_settings.access_token_seconds,
+        ),
+        CookieToken::Refresh(token) => (
+            context.refresh_token_cookie_name(),
+            token.expose(),
+            authorization_settings.refresh_token_seconds,
+        ),
+    };
+    let builder = Cookie::build(name, value)
         .same_site(http_settings.same_site)
-        .secure(http_settings.secure)
         .http_only(true)
         .expires(expiration)
-        .finish()
+        .max_age(Duration::seconds(max_age as i64));
+    if http_settings.use_host_prefix {
+        return builder.secure(true).path("/").finish();
+    }
+    let mut cookie = builder
+        .secure(http_settings.secure)
+        .path(http_settings.cookie_path.clone())
+        .finish();
+    if let Some(domain) = &http_settings.cookie_domain {
+        cookie.set_domain(domain.clone());
+    }
+
+    cookie
 }
 
 /// サインインリクエスト・ボディ
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
 pub struct SignInReqBody {
     /// Eメールアドレス
     pub email: String,
     /// パス話ワード
+    #[schema(value_type = String, write_only)]
     pub password: SecretString,
 }
 
+/// サインインの状態
+///
+/// サインインのレスポンス・ボディに含めて、クライアントがサインインを完了したか、または次に
+/// どの操作をする必要があるかを判断できるようにする。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum SignInStatus {
+    /// サインインが完了した
+    SignedIn,
+    /// 2要素認証を待機している
+    MfaPending,
+    /// パスワードの変更を要求されている
+    PasswordChangeRequired,
+}
+
 /// JWTトークンペア・レスポンス・ボディ
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
 pub struct SignInResBody {
+    /// サインインの状態（常に`signedIn`）
+    pub status: SignInStatus,
     /// アクセストークン
     pub access: String,
     /// リフレッシュトークン
     pub refresh: String,
+    /// 今回のサインインの前に最後にサインインした日時（はじめてサインインした場合は`null`）
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub previous_sign_in_at: Option<OffsetDateTime>,
 }
 
 impl From<&SignInUseCaseOutput> for SignInResBody {
     fn from(value: &SignInUseCaseOutput) -> Self {
         Self {
-            access: value.access.expose_secret().to_string(),
-            refresh: value.refresh.expose_secret().to_string(),
+            status: SignInStatus::SignedIn,
+            access: value.access.expose().to_string(),
+            refresh: value.refresh.expose().to_string(),
+            previous_sign_in_at: value.previous_sign_in_at,
+        }
+    }
+}
+
+/// 2要素認証を待機しているサインインのレスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MfaPendingResBody {
+    /// サインインの状態（常に`mfaPending`）
+    pub status: SignInStatus,
+    /// 2要素認証を待機しているサインインを示すトークン
+    pub mfa_token: String,
+    /// トークンの有効期限
+    #[serde(with = "time::serde::rfc3339")]
+    pub expiration: OffsetDateTime,
+}
+
+impl From<&MfaPendingUseCaseOutput> for MfaPendingResBody {
+    fn from(value: &MfaPendingUseCaseOutput) -> Self {
+        Self {
+            status: SignInStatus::MfaPending,
+            mfa_token: value.token.expose_secret().to_string(),
+            expiration: value.expiration,
+        }
+    }
+}
+
+/// パスワードの変更を要求されているサインインのレスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PasswordChangeRequiredResBody {
+    /// サインインの状態（常に`passwordChangeRequired`）
+    pub status: SignInStatus,
+    /// パスワードを変更するためのトークン
+    pub password_change_token: String,
+    /// トークンの有効期限
+    #[serde(with = "time::serde::rfc3339")]
+    pub expiration: OffsetDateTime,
+}
+
+impl From<&PasswordChangeRequiredUseCaseOutput> for PasswordChangeRequiredResBody {
+    fn from(value: &PasswordChangeRequiredUseCaseOutput) -> Self {
+        Self {
+            status: SignInStatus::PasswordChangeRequired,
+            password_change_token: value.token.expose_secret().to_string(),
+            expiration: value.expiration,
+        }
+    }
+}
+
+/// 2要素認証サインインリクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SignInWithTotpReqBody {
+    /// 2要素認証を待機しているサインインを示すトークン
+    #[schema(value_type = String, write_only)]
+    pub token: SecretString,
+    /// 認証アプリが生成したTOTPコード
+    pub code: String,
+}
+
+/// 要求されたパスワードの変更
+///
+/// サインインで受け取ったパスワードを変更するためのトークンで、パスワードを変更して、パスワードの
+/// 変更の要求を消去する。トークンは1回だけ使用できる。パスワードを変更した後は、変更後のパスワードで
+/// サインインする。
+#[utoipa::path(
+    post,
+    path = "/accounts/password",
+    tag = "accounts",
+    request_body = ChangeRequiredPasswordReqBody,
+    responses(
+        (status = 204, description = "パスワードを変更した"),
+        (status = 400, description = "変更後のパスワードが誤っている、または現在のパスワードと同じ", body = ErrorResponseBody),
+        (status = 401, description = "トークンが無効、または有効期限が切れている", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "change required password handler", skip(context, request_body))]
+pub async fn change_required_password(
+    context: web::Data<RequestContext>,
+    request_body: web::Json<ChangeRequiredPasswordReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let new_password = RawPassword::with_policy(
+        request_body.0.new_password,
+        &context.password_settings.policy(),
+    )
+    .map_err(ProcessRequestError::from)?;
+    let input = ChangeRequiredPasswordUseCaseInput {
+        token: request_body.0.token,
+        new_password,
+    };
+
+    context
+        .accounts()
+        .change_required_password(input)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 要求されたパスワードの変更リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeRequiredPasswordReqBody {
+    /// サインインで受け取ったパスワードを変更するためのトークン
+    #[schema(value_type = String, write_only)]
+    pub token: SecretString,
+    /// 変更後のパスワード
+    #[schema(value_type = String, write_only)]
+    pub new_password: SecretString,
+}
+
+/// セッション
+///
+/// アクセストークンを送信せずにリクエストした場合は、匿名のユーザーとして`authenticated`を`false`で
+/// 返す。無効または有効期限が切れたアクセストークンを送信した場合は、401 Unauthorizedを返す。
+#[utoipa::path(
+    get,
+    path = "/accounts/session",
+    tag = "accounts",
+    responses(
+        (status = 200, description = "セッションの状態", body = SessionResBody),
+        (status = 401, description = "アクセストークンが無効または有効期限が切れている", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "session handler", skip(user_context))]
+pub async fn session(user_context: OptionalUserContext) -> HttpResponse {
+    HttpResponse::Ok().json(SessionResBody::from(user_context.0))
+}
+
+/// セッションレスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionResBody {
+    /// 認証済みユーザーであるか示すフラグ
+    pub authenticated: bool,
+    /// 認証済みユーザーのユーザーID
+    #[schema(value_type = Option<Uuid>)]
+    pub user_id: Option<UserId>,
+    /// 認証済みユーザーのユーザー権限コード
+    pub user_permission_code: Option<i16>,
+}
+
+impl From<Option<TokenContent>> for SessionResBody {
+    fn from(value: Option<TokenContent>) -> Self {
+        Self {
+            authenticated: value.is_some(),
+            user_id: value.map(|content| content.user_id),
+            user_permission_code: value.map(|content| content.user_permission_code as i16),
+        }
+    }
+}
+
+/// サインインしているセッションのリスト
+///
+/// サインインで発行したアクセストークンとリフレッシュトークンの組ごとに、トークンを発行した日時、
+/// 有効なトークンの種類と有効期限、及びサインインしたデバイスを、発行した日時の新しい順に返す。
+/// リクエストで送信したアクセストークンのセッションは、`current`を`true`で返す。
+/// トークンそのものは返さない。
+#[utoipa::path(
+    get,
+    path = "/accounts/sessions",
+    tag = "accounts",
+    security(("access_token" = [])),
+    responses(
+        (status = 200, description = "サインインしているセッションのリスト", body = Vec<ActiveSessionResBody>),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "list sessions handler",
+    skip(context, user_context, access_token)
+)]
+pub async fn list_sessions(
+    context: web::Data<RequestContext>,
+    user_context: UserContext,
+    access_token: RequestAccessToken,
+) -> ProcessRequestResult<HttpResponse> {
+    let sessions = context
+        .accounts()
+        .list_sessions(user_context.0.user_id, &access_token.0)
+        .await?
+        .into_iter()
+        .map(ActiveSessionResBody::from)
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+/// サインインしているセッションレスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSessionResBody {
+    /// セッションID
+    pub id: String,
+    /// トークンを発行した日時
+    #[serde(with = "time::serde::rfc3339")]
+    pub issued_at: OffsetDateTime,
+    /// 有効なトークンの種類と有効期限
+    pub tokens: Vec<SessionTokenResBody>,
+    /// サインインしたデバイスを識別する文字列（`User-Agent`ヘッダの値）
+    pub device: Option<String>,
+    /// リクエストで送信したアクセストークンのセッションであるか示すフラグ
+    pub current: bool,
+}
+
+impl From<ActiveSession> for ActiveSessionResBody {
+    fn from(value: ActiveSession) -> Self {
+        Self {
+            id: value.id,
+            issued_at: value.issued_at,
+            tokens: value
+                .tokens
+                .into_iter()
+                .map(SessionTokenResBody::from)
+                .collect(),
+            device: value.device,
+            current: value.current,
+        }
+    }
+}
+
+/// セッションの有効なトークンレスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTokenResBody {
+    /// トークンの種類（`access`または`refresh`）
+    pub token_type: String,
+    /// トークンの有効期限
+    #[serde(with = "time::serde::rfc3339")]
+    pub expiration: OffsetDateTime,
+}
+
+impl From<SessionToken> for SessionTokenResBody {
+    fn from(value: SessionToken) -> Self {
+        Self {
+            token_type: value.token_type.to_string(),
+            expiration: value.expiration,
         }
     }
 }
 
+/// セッションの無効化
+///
+/// セッションのアクセストークンとリフレッシュトークンを無効にする。他のユーザーのセッションは
+/// 無効にできない。
+#[utoipa::path(
+    delete,
+    path = "/accounts/sessions/{session_id}",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(("session_id" = String, Path, description = "セッションID")),
+    responses(
+        (status = 204, description = "セッションを無効にした"),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 404, description = "セッションが見つからない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "revoke session handler", skip(context, user_context))]
+pub async fn revoke_session(
+    context: web::Data<RequestContext>,
+    user_context: UserContext,
+    session_id: web::Path<String>,
+) -> ProcessRequestResult<HttpResponse> {
+    context
+        .accounts()
+        .revoke_session(user_context.0.user_id, &session_id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// トークンの検査
+///
+/// 内部サービスが、このアプリケーションが発行したトークンを検証するときに使用する。内部サービスは、
+/// `Authorization`ヘッダに`Bearer`スキームで資格情報を指定する。RFC 7662に従い、トークンが無効な
+/// 場合、有効期限が切れている場合、または無効にされている場合は、`active`を`false`にして返す。
+/// JWTの形式でないトークンは、400 Bad Requestを返す。
+#[utoipa::path(
+    post,
+    path = "/accounts/token/introspect",
+    tag = "accounts",
+    security(("service_credential" = [])),
+    request_body = IntrospectTokenReqBody,
+    responses(
+        (status = 200, description = "トークンの検査結果", body = TokenIntrospectionResBody),
+        (status = 400, description = "トークンの形式が不正", body = ErrorResponseBody),
+        (status = 401, description = "内部サービスの資格情報が無効", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "introspect token handler",
+    skip(context, request, request_body)
+)]
+pub async fn introspect_token(
+    context: web::Data<RequestContext>,
+    request: HttpRequest,
+    request_body: web::Json<IntrospectTokenReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let credential = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|value| SecretString::new(value.to_string()));
+    let introspection = context
+        .accounts()
+        .introspect_token(credential.as_ref(), &request_body.token)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(TokenIntrospectionResBody::from(introspection)))
+}
+
+/// トークンの検査リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct IntrospectTokenReqBody {
+    /// 検査するトークン
+    #[schema(value_type = String, write_only)]
+    pub token: SecretString,
+}
+
+/// トークンの検査レスポンス・ボディ
+///
+/// RFC 7662のレスポンスの形式に従う。トークンが有効でない場合は、`active`のみを返す。
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct TokenIntrospectionResBody {
+    /// トークンが有効であるか示すフラグ
+    pub active: bool,
+    /// トークンを発行したユーザーのユーザーID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Uuid>)]
+    pub sub: Option<UserId>,
+    /// 有効期限を示すUNIXエポック秒
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    /// トークンの種類（`access`または`refresh`）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// トークンを発行したユーザーのユーザー権限コード
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permission: Option<i16>,
+}
+
+impl From<Option<TokenIntrospection>> for TokenIntrospectionResBody {
+    fn from(value: Option<TokenIntrospection>) -> Self {
+        Self {
+            active: value.is_some(),
+            sub: value.map(|introspection| introspection.user_id),
+            exp: value.map(|introspection| introspection.expiration),
+            token_type: value.map(|introspection| introspection.token_type.to_string()),
+            permission: value.map(|introspection| introspection.user_permission_code as i16),
+        }
+    }
+}
+
+/// 2要素認証の準備
+///
+/// TOTPの秘密鍵を生成して、認証アプリに登録する`otpauth`URIを返す。2要素認証は、
+/// `POST /accounts/2fa/enable`で認証アプリが生成したTOTPコードを送信するまで有効にならない。
+#[utoipa::path(
+    post,
+    path = "/accounts/2fa/setup",
+    tag = "accounts",
+    security(("access_token" = [])),
+    responses(
+        (status = 200, description = "認証アプリに登録する`otpauth`URIとTOTPの秘密鍵", body = TotpSetupResBody),
+        (status = 400, description = "2要素認証がすでに有効", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "set up totp handler", skip(context, user_context))]
+pub async fn set_up_totp(
+    context: web::Data<RequestContext>,
+    user_context: UserContext,
+) -> ProcessRequestResult<HttpResponse> {
+    let output = context
+        .accounts()
+        .set_up_totp(user_context.0.user_id)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::Ok().json(TotpSetupResBody::from(output)))
+}
+
+/// 2要素認証の準備レスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct TotpSetupResBody {
+    /// 認証アプリに登録する`otpauth`URI
+    pub uri: String,
+    /// 認証アプリに手動で入力するBase32形式のTOTPの秘密鍵
+    pub secret: String,
+}
+
+impl From<TotpSetupUseCaseOutput> for TotpSetupResBody {
+    fn from(value: TotpSetupUseCaseOutput) -> Self {
+        Self {
+            uri: value.uri,
+            secret: value.secret.expose_secret().to_string(),
+        }
+    }
+}
+
+/// 2要素認証の有効化
+#[utoipa::path(
+    post,
+    path = "/accounts/2fa/enable",
+    tag = "accounts",
+    security(("access_token" = [])),
+    request_body = TotpCodeReqBody,
+    responses(
+        (status = 204, description = "2要素認証を有効にした"),
+        (status = 400, description = "TOTPコードが誤っている、2要素認証を準備していない、または2要素認証がすでに有効", body = ErrorResponseBody),
+        (status = 401, description = "アカウントがロックされている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "enable totp handler",
+    skip(context, user_context, request_body)
+)]
+pub async fn enable_totp(
+    context: web::Data<RequestContext>,
+    user_context: UserContext,
+    request_body: web::Json<TotpCodeReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    context
+        .accounts()
+        .enable_totp(user_context.0.user_id, request_body.0.code)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// 2要素認証の無効化
+#[utoipa::path(
+    post,
+    path = "/accounts/2fa/disable",
+    tag = "accounts",
+    security(("access_token" = [])),
+    request_body = TotpCodeReqBody,
+    responses(
+        (status = 204, description = "2要素認証を無効にした"),
+        (status = 400, description = "TOTPコードが誤っている、または2要素認証が有効でない", body = ErrorResponseBody),
+        (status = 401, description = "アカウントがロックされている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "disable totp handler",
+    skip(context, user_context, request_body)
+)]
+pub async fn disable_totp(
+    context: web::Data<RequestContext>,
+    user_context: UserContext,
+    request_body: web::Json<TotpCodeReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    context
+        .accounts()
+        .disable_totp(user_context.0.user_id, request_body.0.code)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// TOTPコードリクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct TotpCodeReqBody {
+    /// 認証アプリが生成したTOTPコード
+    pub code: String,
+}
+
+/// Eメールアドレスの確認
+#[utoipa::path(
+    post,
+    path = "/accounts/verify-email",
+    tag = "accounts",
+    request_body = VerifyEmailReqBody,
+    responses(
+        (status = 204, description = "Eメールアドレスを確認した"),
+        (status = 400, description = "トークンが無効、または有効期限が切れている", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "verify email handler", skip(context, request_body))]
+pub async fn verify_email(
+    context: web::Data<RequestContext>,
+    request_body: web::Json<VerifyEmailReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    context
+        .accounts()
+        .verify_email(request_body.0.token)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Eメールアドレス確認リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct VerifyEmailReqBody {
+    /// Eメールアドレスを確認するトークン
+    #[schema(value_type = String, write_only)]
+    pub token: SecretString,
+}
+
+/// Eメールアドレスを確認するトークンの再送信
+///
+/// Eメールアドレスが登録されているか推測されないように、Eメールアドレスを持つユーザーが登録されて
+/// いない場合や、Eメールアドレスを確認済みの場合でも、204 No Contentを返す。
+#[utoipa::path(
+    post,
+    path = "/accounts/verify-email/resend",
+    tag = "accounts",
+    request_body = ResendEmailVerificationReqBody,
+    responses(
+        (status = 204, description = "Eメールアドレスを確認するトークンを再送信した"),
+        (status = 400, description = "リクエストの内容が誤っている", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "resend email verification handler",
+    skip(context, request_body)
+)]
+pub async fn resend_email_verification(
+    context: web::Data<RequestContext>,
+    request_body: web::Json<ResendEmailVerificationReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let email = EmailAddress::new(request_body.0.email).map_err(ProcessRequestError::from)?;
+    context
+        .accounts()
+        .resend_email_verification(email)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Eメールアドレスを確認するトークンの再送信リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct ResendEmailVerificationReqBody {
+    /// Eメールアドレス
+    pub email: String,
+}
+
+/// 再認証
+///
+/// パスワードを検証して、アクセストークンで再認証したことを登録する。再認証の有効期限までは、
+/// パスワードを送信せずに、アカウントの削除とEメールアドレスの変更を要求できる。
+/// パスワードが間違っている場合は、サインインと同様にサインイン失敗回数を記録する。
+#[utoipa::path(
+    post,
+    path = "/accounts/reauthenticate",
+    tag = "accounts",
+    security(("access_token" = [])),
+    request_body = ReauthenticateReqBody,
+    responses(
+        (status = 200, description = "再認証した", body = ReauthenticateResBody),
+        (status = 400, description = "リクエストの内容が誤っている", body = ErrorResponseBody),
+        (status = 401, description = "パスワードが間違っている、またはアカウントがロックされている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "reauthenticate handler",
+    skip(context, user_context, access_token, request_body)
+)]
+pub async fn reauthenticate(
+    context: web::Data<RequestContext>,
+    user_context: UserContext,
+    access_token: RequestAccessToken,
+    request_body: web::Json<ReauthenticateReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let password =
+        RawPassword::with_policy(request_body.0.password, &context.password_settings.policy())
+            .map_err(ProcessRequestError::from)?;
+    let output = context
+        .accounts()
+        .reauthenticate(user_context.0.user_id, &access_token.0, &password)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::Ok().json(ReauthenticateResBody {
+        expiration: output.expiration,
+    }))
+}
+
+/// 再認証リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct ReauthenticateReqBody {
+    /// 現在のパスワード
+    #[schema(value_type = String, write_only)]
+    pub password: SecretString,
+}
+
+/// 再認証レスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct ReauthenticateResBody {
+    /// 再認証の有効期限
+    #[serde(with = "time::serde::rfc3339")]
+    pub expiration: OffsetDateTime,
+}
+
+/// リクエストボディのパスワード、またはアクセストークンから、本人であることを再確認する方法を
+/// 決定する。
+///
+/// # 引数
+///
+/// * `password` - リクエストボディで送信されたパスワード
+/// * `access_token` - リクエストで送信されたアクセストークン
+/// * `policy` - パスワードポリシー
+///
+/// # 戻り値
+///
+/// パスワードが送信された場合はパスワード、それ以外の場合は再認証したアクセストークンで再確認する方法
+fn reauthentication(
+    password: Option<SecretString>,
+    access_token: RequestAccessToken,
+    policy: &PasswordPolicy,
+) -> ProcessRequestResult<Reauthentication> {
+    match password {
+        Some(password) => Ok(Reauthentication::Password(
+            RawPassword::with_policy(password, policy).map_err(ProcessRequestError::from)?,
+        )),
+        None => Ok(Reauthentication::AccessToken(access_token.0)),
+    }
+}
+
+/// Eメールアドレスの変更の要求
+///
+/// Eメールアドレスの変更を保留して、変更を確定するトークンを変更後のEメールアドレスに送信する。
+/// Eメールアドレスは、`POST /accounts/email-change/confirm`で変更を確定するまで変更しない。
+/// `POST /accounts/reauthenticate`で再認証した場合は、パスワードを送信する必要はない。
+#[utoipa::path(
+    post,
+    path = "/accounts/email-change",
+    tag = "accounts",
+    security(("access_token" = [])),
+    request_body = RequestEmailChangeReqBody,
+    responses(
+        (status = 204, description = "変更を確定するトークンを変更後のEメールアドレスに送信した"),
+        (status = 400, description = "リクエストの内容が誤っている、パスワードが送信されず再認証していない、または変更後のEメールアドレスを持つユーザーが登録されている", body = ErrorResponseBody),
+        (status = 401, description = "パスワードが間違っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "request email change handler",
+    skip(context, user_context, access_token, request_body)
+)]
+pub async fn request_email_change(
+    context: web::Data<RequestContext>,
+    user_context: UserContext,
+    access_token: RequestAccessToken,
+    request_body: web::Json<RequestEmailChangeReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let request_body = request_body.into_inner();
+    let reauthentication = reauthentication(
+        request_body.password,
+        access_token,
+        &context.password_settings.policy(),
+    )?;
+    let email = EmailAddress::new(request_body.email).map_err(ProcessRequestError::from)?;
+    context
+        .accounts()
+        .request_email_change(
+            user_context.0.user_id,
+            RequestEmailChangeUseCaseInput {
+                reauthentication,
+                email,
+            },
+        )
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Eメールアドレスの変更要求リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct RequestEmailChangeReqBody {
+    /// 現在のパスワード（再認証した場合は不要）
+    #[schema(value_type = Option<String>, write_only)]
+    pub password: Option<SecretString>,
+    /// 変更後のEメールアドレス
+    pub email: String,
+}
+
+/// Eメールアドレスの変更の確定
+///
+/// Eメールアドレスを変更した後、ユーザーに発行したアクセストークンとリフレッシュトークンを
+/// すべて無効にするため、ユーザーは変更後のEメールアドレスで再度サインインする必要がある。
+#[utoipa::path(
+    post,
+    path = "/accounts/email-change/confirm",
+    tag = "accounts",
+    request_body = ConfirmEmailChangeReqBody,
+    responses(
+        (status = 204, description = "Eメールアドレスを変更した"),
+        (status = 400, description = "トークンが無効、有効期限が切れている、または変更後のEメールアドレスを持つユーザーが登録されている", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "confirm email change handler", skip(context, request_body))]
+pub async fn confirm_email_change(
+    context: web::Data<RequestContext>,
+    request_body: web::Json<ConfirmEmailChangeReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    context
+        .accounts()
+        .confirm_email_change(request_body.0.token)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Eメールアドレスの変更確定リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct ConfirmEmailChangeReqBody {
+    /// Eメールアドレスの変更を確定するトークン
+    #[schema(value_type = String, write_only)]
+    pub token: SecretString,
+}
+
 /// ユーザーリスト
-async fn list_users(
+#[utoipa::path(
+    get,
+    path = "/accounts/users",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(UserListQuery),
+    responses(
+        (status = 200, description = "ユーザーのリスト", body = Vec<UserResBody>),
+        (status = 400, description = "絞り込み条件が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "list users handler",
+    skip(request_context, _admin_context, query)
+)]
+pub async fn list_users(
     request_context: web::Data<RequestContext>,
     _admin_context: AdminContext,
+    query: web::Query<UserListQuery>,
 ) -> ProcessRequestResult<HttpResponse> {
-    let repo = request_context.user_repository();
-    let users = use_cases::accounts::list_users(repo)
+    let query = query.into_inner();
+    let search = query.q.clone();
+    let filter = UserListFilter::try_from(query)?;
+    let users = request_context
+        .accounts()
+        .list_users(search.as_deref(), filter)
         .await?
         .into_iter()
         .map(UserResBody::from)
@@ -260,57 +1519,1019 @@ async fn list_users(
     Ok(HttpResponse::Ok().json(users))
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// ユーザーリストの絞り込み条件クエリ
+#[derive(Debug, Clone, serde::Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct UserListQuery {
+    /// 苗字、名前またはEメールアドレスの部分一致で検索する文字列（大文字と小文字を区別しない、
+    /// ユーザーリストのみ）
+    pub q: Option<String>,
+    /// アクティブフラグ
+    pub active: Option<bool>,
+    /// ユーザー権限コード
+    pub user_permission_code: Option<i16>,
+    /// 並べ替えるキー（`email`、`createdAt`または`lastSignInAt`、省略した場合は`createdAt`）
+    pub sort: Option<String>,
+    /// 並べ替える方向（`asc`または`desc`、省略した場合は`asc`）
+    pub order: Option<String>,
+    /// 取得するユーザーの最大数（1以上1000以下、省略した場合はすべて）
+    pub limit: Option<u32>,
+    /// 並び順で先頭から読み飛ばすユーザーの数（省略した場合は0）
+    pub offset: Option<u32>,
+}
+
+impl TryFrom<UserListQuery> for UserListFilter {
+    type Error = ProcessRequestError;
+
+    fn try_from(value: UserListQuery) -> Result<Self, Self::Error> {
+        let user_permission_code = value
+            .user_permission_code
+            .map(UserPermissionCode::try_from)
+            .transpose()
+            .map_err(ProcessRequestError::from)?;
+        let direction = value
+            .order
+            .as_deref()
+            .map(SortDirection::try_from)
+            .transpose()
+            .map_err(ProcessRequestError::from)?
+            .unwrap_or_default();
+        let order = UserListOrder::new(value.sort.as_deref().unwrap_or("createdAt"), direction)
+            .map_err(ProcessRequestError::from)?;
+
+        Ok(Self {
+            active: value.active,
+            user_permission_code,
+            order,
+            pagination: Pagination::new(value.limit, value.offset.unwrap_or_default()),
+        })
+    }
+}
+
+/// ロックされたアカウントのリスト
+///
+/// サインインに連続して失敗してアカウントがロックされたユーザーを、ロックされた日時の順に返す。
+#[utoipa::path(
+    get,
+    path = "/accounts/users/locked",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(LockedUserListQuery),
+    responses(
+        (status = 200, description = "アカウントがロックされたユーザーのリスト", body = Vec<LockedUserResBody>),
+        (status = 400, description = "取得する範囲が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "list locked users handler",
+    skip(request_context, _admin_context, query)
+)]
+pub async fn list_locked_users(
+    request_context: web::Data<RequestContext>,
+    _admin_context: AdminContext,
+    query: web::Query<LockedUserListQuery>,
+) -> ProcessRequestResult<HttpResponse> {
+    let LockedUserListQuery { limit, offset } = query.into_inner();
+    let users = request_context
+        .accounts()
+        .list_locked_users(Pagination::new(limit, offset.unwrap_or_default()))
+        .await?
+        .into_iter()
+        .map(LockedUserResBody::from)
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(users))
+}
+
+/// ロックされたアカウントのリストの取得範囲クエリ
+#[derive(Debug, Clone, serde::Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct LockedUserListQuery {
+    /// 取得するユーザーの最大数（1以上1000以下、省略した場合はすべて）
+    pub limit: Option<u32>,
+    /// ロックされた日時の順で先頭から読み飛ばすユーザーの数（省略した場合は0）
+    pub offset: Option<u32>,
+}
+
+/// アカウントがロックされたユーザーのレスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LockedUserResBody {
+    /// ユーザー
+    #[serde(flatten)]
+    pub user: UserResBody,
+    /// アカウントがロックされた日時
+    #[serde(with = "time::serde::rfc3339")]
+    pub locked_at: OffsetDateTime,
+    /// サインインに失敗した回数
+    pub number_of_sign_in_failures: i16,
+}
+
+impl From<LockedUser> for LockedUserResBody {
+    fn from(value: LockedUser) -> Self {
+        let number_of_sign_in_failures = value.user.number_of_sign_in_failures.value;
+
+        Self {
+            user: value.user.into(),
+            locked_at: value.locked_at,
+            number_of_sign_in_failures,
+        }
+    }
+}
+
+/// ユーザーのエクスポート
+///
+/// ユーザーのリストを、Excelで開けるようにBOMを付与したUTF-8のCSVで返す。
+/// すべてのユーザーをメモリに読み込まないように、ユーザーをデータベースから取得するたびにCSVの行を送信する。
+#[utoipa::path(
+    get,
+    path = "/accounts/users/export",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(UserListQuery),
+    responses(
+        (status = 200, description = "ユーザーのリストを記録したCSV", content_type = "text/csv; charset=utf-8", body = String),
+        (status = 400, description = "絞り込み条件が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "export users handler", skip(context, _admin_context, query))]
+pub async fn export_users(
+    context: web::Data<RequestContext>,
+    _admin_context: AdminContext,
+    query: web::Query<UserListQuery>,
+) -> ProcessRequestResult<HttpResponse> {
+    let filter = UserListFilter::try_from(query.into_inner())?;
+    let users = context.accounts().export_users(filter)?;
+    // BOMと見出し行を送信した後、ユーザーを取得するたびに行を送信
+    let header = stream::once(async { users_csv_header() });
+    let rows = users.map(|user| {
+        user.map_err(ProcessRequestError::from)
+            .and_then(|user| users_csv_row(UserCsvRecord::from(user)))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type(USERS_CSV_CONTENT_TYPE)
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(String::from(
+                USERS_CSV_FILE_NAME,
+            ))],
+        })
+        .streaming(header.chain(rows)))
+}
+
+/// ユーザーのCSVのBOMと見出し行を生成する。
+fn users_csv_header() -> ProcessRequestResult<Bytes> {
+    let mut writer = csv::Writer::from_writer(UTF8_BOM.as_bytes().to_vec());
+    writer.write_record(USERS_CSV_HEADERS).map_err(csv_error)?;
+
+    writer
+        .into_inner()
+        .map(Bytes::from)
+        .map_err(|e| csv_error(e.into_error()))
+}
+
+/// ユーザーのCSVの行を生成する。
+fn users_csv_row(record: UserCsvRecord) -> ProcessRequestResult<Bytes> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(vec![]);
+    writer.serialize(record).map_err(csv_error)?;
+
+    writer
+        .into_inner()
+        .map(Bytes::from)
+        .map_err(|e| csv_error(e.into_error()))
+}
+
+/// CSVを生成するときに発生したエラーを、リクエスト処理エラーに変換する。
+fn csv_error(e: impl std::fmt::Display) -> ProcessRequestError {
+    tracing::error!("{} ({}:{})", e, file!(), line!());
+    ProcessRequestError::without_error_code(StatusCode::INTERNAL_SERVER_ERROR, CSV_ERROR)
+}
+
+/// ユーザーのCSVの行
+///
+/// 列は`UserResBody`のフィールドと一致させて、日時はRFC3339形式で記録する。
+#[derive(Debug, Clone, serde::Serialize)]
+struct UserCsvRecord {
+    id: UserId,
+    email: String,
+    active: bool,
+    user_permission_code: i16,
+    user_permission_name: String,
+    family_name: String,
+    given_name: String,
+    postal_code: String,
+    address: String,
+    fixed_phone_number: Option<String>,
+    mobile_phone_number: Option<String>,
+    remarks: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    last_logged_in_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    updated_at: OffsetDateTime,
+    version: i32,
+}
+
+impl From<User> for UserCsvRecord {
+    fn from(value: User) -> Self {
+        let body = UserResBody::from(value);
+        Self {
+            id: body.id,
+            email: body.email,
+            active: body.active,
+            user_permission_code: body.user_permission.code,
+            user_permission_name: body.user_permission.name,
+            family_name: body.family_name,
+            given_name: body.given_name,
+            postal_code: body.postal_code,
+            address: body.address,
+            fixed_phone_number: body.fixed_phone_number,
+            mobile_phone_number: body.mobile_phone_number,
+            remarks: body.remarks,
+            last_logged_in_at: body.last_logged_in_at,
+            created_at: body.created_at,
+            updated_at: body.updated_at,
+            version: body.version,
+        }
+    }
+}
+
+/// ユーザーのCSVの見出し
+pub const USERS_CSV_HEADERS: [&str; 16] = [
+    "id",
+    "email",
+    "active",
+    "user_permission_code",
+    "user_permission_name",
+    "family_name",
+    "given_name",
+    "postal_code",
+    "address",
+    "fixed_phone_number",
+    "mobile_phone_number",
+    "remarks",
+    "last_logged_in_at",
+    "created_at",
+    "updated_at",
+    "version",
+];
+/// ユーザーのCSVのコンテンツタイプ
+pub const USERS_CSV_CONTENT_TYPE: &str = "text/csv; charset=utf-8";
+/// ユーザーのCSVのファイル名
+const USERS_CSV_FILE_NAME: &str = "users.csv";
+
+/// サインアップの冪等キーを区別するエンドポイントの名前
+const SIGN_UP_SCOPE: &str = "sign-up";
+/// UTF-8のBOM
+const UTF8_BOM: &str = "\u{feff}";
+const CSV_ERROR: &str = "CSVを生成するときにエラーが発生しました。";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema, MapFrom)]
+#[map_from(source = "User")]
 pub struct UserResBody {
-    pub id: Uuid,
+    #[schema(value_type = Uuid)]
+    pub id: UserId,
+    #[map_from(value)]
     pub email: String,
     pub active: bool,
+    #[map_from(into)]
     pub user_permission: UserPermissionBody,
+    #[map_from(value)]
     pub family_name: String,
+    #[map_from(value)]
     pub given_name: String,
+    #[map_from(value)]
     pub postal_code: String,
+    #[map_from(value)]
     pub address: String,
+    #[map_from(owned_value)]
     pub fixed_phone_number: Option<String>,
+    #[map_from(owned_value)]
     pub mobile_phone_number: Option<String>,
+    #[map_from(owned_value)]
     pub remarks: Option<String>,
+    #[schema(value_type = Option<Vec<i64>>)]
+    #[map_from(rename = "last_sign_in_at")]
     pub last_logged_in_at: Option<OffsetDateTime>,
+    #[schema(value_type = Vec<i64>)]
     pub created_at: OffsetDateTime,
+    #[schema(value_type = Vec<i64>)]
     pub updated_at: OffsetDateTime,
+    pub version: i32,
 }
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema, MapFrom)]
+#[map_from(source = "UserPermission")]
 pub struct UserPermissionBody {
+    #[map_from(into)]
     pub code: i16,
+    #[map_from(value)]
     pub name: String,
 }
 
-impl From<User> for UserResBody {
-    fn from(value: User) -> Self {
+/// プロフィールの更新
+///
+/// 他の操作による更新を上書きしないように、`If-Match`ヘッダに、ユーザーを取得したときの`ETag`を
+/// 指定する必要がある。ユーザーが他の操作で更新されている場合は、409 Conflictを返す。
+/// リクエスト・ボディで指定しなかったフィールドは更新しない。
+#[utoipa::path(
+    patch,
+    path = "/accounts/users/{user_id}",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(
+        ("user_id" = Uuid, Path, description = "ユーザーID"),
+        ("If-Match" = String, Header, description = "ユーザーを取得したときの`ETag`"),
+    ),
+    request_body = UpdateProfileReqBody,
+    responses(
+        (status = 200, description = "プロフィールを更新したユーザー", body = UserResBody,
+            headers(("ETag" = String, description = "更新後のユーザーのエンティティタグ"))),
+        (status = 400, description = "リクエストの内容が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 404, description = "ユーザーが見つからない", body = ErrorResponseBody),
+        (status = 409, description = "ユーザーが他の操作で更新されている", body = ErrorResponseBody),
+        (status = 428, description = "`If-Match`ヘッダが指定されていない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "update profile handler",
+    skip(context, user_context, request, request_body)
+)]
+pub async fn update_profile(
+    context: web::Data<RequestContext>,
+    user_context: AdminOrOwnerContext,
+    request: HttpRequest,
+    request_body: web::Json<UpdateProfileReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let version = if_match_version(&request)?;
+    let input = request_body
+        .0
+        .validate_into_input(user_context.target_user_id, version)?;
+
+    let user = context
+        .accounts()
+        .update_profile(input)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header(version_etag(user.version))
+        .json(UserResBody::from(user)))
+}
+
+/// プロフィール更新リクエスト・ボディ
+///
+/// 固定電話番号、携帯電話番号及び備考は、`null`を指定すると消去する。
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateProfileReqBody {
+    /// 苗字
+    pub family_name: Option<String>,
+    /// 名前
+    pub given_name: Option<String>,
+    /// 郵便番号
+    pub postal_code: Option<String>,
+    /// 住所
+    pub address: Option<String>,
+    /// 固定電話番号
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<String>)]
+    pub fixed_phone_number: Option<Option<String>>,
+    /// 携帯電話番号
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<String>)]
+    pub mobile_phone_number: Option<Option<String>>,
+    /// 備考
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<String>)]
+    pub remarks: Option<Option<String>>,
+}
+
+impl UpdateProfileReqBody {
+    /// 指定されたすべてのフィールドを検証して、プロフィール更新ユースケース入力を構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - プロフィールを更新するユーザーのユーザーID
+    /// * `version` - クライアントが取得したユーザーのバージョン
+    ///
+    /// # 戻り値
+    ///
+    /// プロフィール更新ユースケース入力
+    pub fn validate_into_input(
+        self,
+        user_id: UserId,
+        version: i32,
+    ) -> ProcessRequestResult<UpdateProfileUseCaseInput> {
+        let mut errors = vec![];
+        let family_name = self
+            .family_name
+            .map(|value| validate_field(&mut errors, "familyName", FamilyName::new(value)));
+        let given_name = self
+            .given_name
+            .map(|value| validate_field(&mut errors, "givenName", GivenName::new(value)));
+        let postal_code = self
+            .postal_code
+            .map(|value| validate_field(&mut errors, "postalCode", PostalCode::new(value)));
+        let address = self
+            .address
+            .map(|value| validate_field(&mut errors, "address", Address::new(value)));
+        let fixed_phone_number = self.fixed_phone_number.map(|value| {
+            validate_field(
+                &mut errors,
+                "fixedPhoneNumber",
+                OptionalFixedPhoneNumber::try_from(value),
+            )
+        });
+        let mobile_phone_number = self.mobile_phone_number.map(|value| {
+            validate_field(
+                &mut errors,
+                "mobilePhoneNumber",
+                OptionalMobilePhoneNumber::try_from(value),
+            )
+        });
+        let remarks = self
+            .remarks
+            .map(|value| validate_field(&mut errors, "remarks", OptionalRemarks::try_from(value)));
+        if !errors.is_empty() {
+            return Err(ProcessRequestError::invalid_fields(errors));
+        }
+
+        // すべてのフィールドの検証に成功しているため、`flatten`しても指定したフィールドは失われない
+        Ok(UpdateProfileUseCaseInput {
+            user_id,
+            version,
+            family_name: family_name.flatten(),
+            given_name: given_name.flatten(),
+            postal_code: postal_code.flatten(),
+            address: address.flatten(),
+            fixed_phone_number: fixed_phone_number.flatten(),
+            mobile_phone_number: mobile_phone_number.flatten(),
+            remarks: remarks.flatten(),
+        })
+    }
+}
+
+/// フィールドが存在する場合は、`null`であっても`Some`としてデシリアライズする。
+///
+/// `#[serde(default)]`と組み合わせて、フィールドが存在しない場合と`null`の場合を区別する。
+fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: serde::Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    serde::Deserialize::deserialize(deserializer).map(Some)
+}
+
+/// アカウントの削除
+///
+/// ユーザー自身がアカウントを削除する場合は、リクエストボディでパスワードを送信するか、
+/// `POST /accounts/reauthenticate`で再認証する必要がある。
+/// 管理者は、パスワードを送信せずに他のユーザーのアカウントを削除できる。
+/// アカウントを削除したユーザーのレコードは残すが、個人情報は消去する。
+#[utoipa::path(
+    delete,
+    path = "/accounts/users/{user_id}",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(("user_id" = Uuid, Path, description = "ユーザーID")),
+    request_body(content = DeleteUserReqBody, description = "ユーザー自身がアカウントを削除する場合に必要"),
+    responses(
+        (status = 204, description = "アカウントを削除した"),
+        (status = 400, description = "パスワードが送信されず再認証していない、または最後の管理者のアカウントを削除しようとした", body = ErrorResponseBody),
+        (status = 401, description = "パスワードが間違っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 404, description = "ユーザーが見つからない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "delete user handler",
+    skip(context, user_context, access_token, request_body)
+)]
+pub async fn delete_user(
+    context: web::Data<RequestContext>,
+    user_context: AdminOrOwnerContext,
+    access_token: RequestAccessToken,
+    request_body: Option<web::Json<DeleteUserReqBody>>,
+) -> ProcessRequestResult<HttpResponse> {
+    let user_id = user_context.target_user_id;
+    let requester = if user_context.is_owner() {
+        // ユーザー自身がアカウントを削除する場合は、パスワードまたは再認証で本人であることを再確認
+        let password = request_body.map(|body| body.0.password);
+        DeleteUserRequester::Owner(reauthentication(
+            password,
+            access_token,
+            &context.password_settings.policy(),
+        )?)
+    } else {
+        DeleteUserRequester::Admin(user_context.audit_actor())
+    };
+
+    context
+        .accounts()
+        .delete_user(DeleteUserUseCaseInput { user_id, requester })
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// アカウント削除リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+pub struct DeleteUserReqBody {
+    /// 現在のパスワード
+    #[schema(value_type = String, write_only)]
+    pub password: SecretString,
+}
+
+/// ユーザーの登録
+///
+/// 管理者が、ランダムに生成したパスワードでユーザーを登録する。
+///
+/// 生成したパスワードはレスポンス・ボディで1度だけ返すため、管理者はユーザーにパスワードを伝えて、
+/// ユーザーは最初にサインインした後にパスワードを変更しなければならない。
+/// Eメールアドレスを確認するトークンは送信しない。
+/// ユーザーを登録した管理者は、監査ログに記録する。
+#[utoipa::path(
+    post,
+    path = "/accounts/users",
+    tag = "accounts",
+    security(("access_token" = [])),
+    request_body = CreateUserReqBody,
+    responses(
+        (status = 201, description = "登録したユーザーと生成したパスワード", body = CreateUserResBody),
+        (status = 400, description = "リクエストの内容が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 409, description = "Eメールアドレスが既に登録されている", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "create user handler",
+    skip(context, admin_context, request_body)
+)]
+pub async fn create_user(
+    context: web::Data<RequestContext>,
+    admin_context: AdminContext,
+    request_body: web::Json<CreateUserReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let input = request_body
+        .into_inner()
+        .validate_into_input(admin_context.audit_actor())?;
+    let output = context
+        .accounts()
+        .create_user_with_generated_password(input)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    // レスポンス・ボディに生成したパスワードを含めるため、キャッシュさせない
+    Ok(HttpResponse::Created()
+        .insert_header(CacheControl(vec![CacheDirective::NoStore]))
+        .json(CreateUserResBody::from(output)))
+}
+
+/// ユーザー登録リクエスト・ボディ
+///
+/// パスワードはサーバーで生成するため、指定しない。
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateUserReqBody {
+    /// Eメールアドレス
+    pub email: String,
+    /// アクティブフラグ
+    ///
+    /// 省略した場合は、アクティブなユーザーを登録する。
+    pub active: Option<bool>,
+    /// ユーザー権限コード
+    pub user_permission_code: i16,
+    /// 苗字
+    pub family_name: String,
+    /// 名前
+    pub given_name: String,
+    /// 郵便番号
+    pub postal_code: String,
+    /// 住所
+    pub address: String,
+    /// 固定電話番号
+    pub fixed_phone_number: Option<String>,
+    /// 携帯電話番号
+    pub mobile_phone_number: Option<String>,
+    /// 備考
+    pub remarks: Option<String>,
+}
+
+impl CreateUserReqBody {
+    /// すべてのフィールドを検証して、ユーザー登録ユースケース入力を構築する。
+    ///
+    /// 最初に検証に失敗したフィールドで中断せずに、すべてのフィールドを検証して、検証に失敗した
+    /// フィールドのエラーをまとめて返す。
+    ///
+    /// # 引数
+    ///
+    /// * `actor` - ユーザーを登録する管理者
+    ///
+    /// # 戻り値
+    ///
+    /// ユーザー登録ユースケース入力
+    pub fn validate_into_input(
+        self,
+        actor: AuditActor,
+    ) -> ProcessRequestResult<CreateUserUseCaseInput> {
+        let mut errors = vec![];
+        let email = validate_field(&mut errors, "email", EmailAddress::new(self.email));
+        let user_permission_code = validate_field(
+            &mut errors,
+            "userPermissionCode",
+            UserPermissionCode::try_from(self.user_permission_code),
+        );
+        let family_name =
+            validate_field(&mut errors, "familyName", FamilyName::new(self.family_name));
+        let given_name = validate_field(&mut errors, "givenName", GivenName::new(self.given_name));
+        let postal_code =
+            validate_field(&mut errors, "postalCode", PostalCode::new(self.postal_code));
+        let address = validate_field(&mut errors, "address", Address::new(self.address));
+        let fixed_phone_number = validate_field(
+            &mut errors,
+            "fixedPhoneNumber",
+            OptionalFixedPhoneNumber::try_from(self.fixed_phone_number),
+        );
+        let mobile_phone_number = validate_field(
+            &mut errors,
+            "mobilePhoneNumber",
+            OptionalMobilePhoneNumber::try_from(self.mobile_phone_number),
+        );
+        let remarks = validate_field(
+            &mut errors,
+            "remarks",
+            OptionalRemarks::try_from(self.remarks),
+        );
+        if !errors.is_empty() {
+            return Err(ProcessRequestError::invalid_fields(errors));
+        }
+
+        // すべてのフィールドの検証に成功しているため、`unwrap`しても失敗しない
+        Ok(CreateUserUseCaseInput {
+            actor,
+            email: email.unwrap(),
+            active: self.active.unwrap_or(true),
+            user_permission_code: user_permission_code.unwrap(),
+            family_name: family_name.unwrap(),
+            given_name: given_name.unwrap(),
+            postal_code: postal_code.unwrap(),
+            address: address.unwrap(),
+            fixed_phone_number: fixed_phone_number.unwrap(),
+            mobile_phone_number: mobile_phone_number.unwrap(),
+            remarks: remarks.unwrap(),
+        })
+    }
+}
+
+/// ユーザー登録レスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateUserResBody {
+    /// 登録したユーザー
+    pub user: SignUpResBody,
+    /// 生成したパスワード
+    ///
+    /// このレスポンスでのみ返す。
+    pub password: String,
+    /// ユーザーが最初にサインインした後に、パスワードを変更しなければならないか示すフラグ
+    ///
+    /// 生成したパスワードで登録したユーザーは、常に`true`である。
+    pub must_change_password: bool,
+}
+
+impl From<CreateUserUseCaseOutput> for CreateUserResBody {
+    fn from(value: CreateUserUseCaseOutput) -> Self {
         Self {
-            id: value.id.value,
-            email: value.email.value,
-            active: value.active,
-            user_permission: UserPermissionBody {
-                code: value.user_permission.code as i16,
-                name: value.user_permission.name.value,
-            },
-            family_name: value.family_name.value,
-            given_name: value.given_name.value,
-            postal_code: value.postal_code.value,
-            address: value.address.value,
-            fixed_phone_number: value.fixed_phone_number.owned_value(),
-            mobile_phone_number: value.mobile_phone_number.owned_value(),
-            remarks: value.remarks.owned_value(),
-            last_logged_in_at: value.last_sign_in_at,
-            created_at: value.created_at,
-            updated_at: value.updated_at,
+            user: SignUpResBody::from(value.user),
+            password: value.password.value.expose_secret().to_string(),
+            must_change_password: true,
         }
     }
 }
 
-async fn user_detail(
-    _request_context: web::Data<RequestContext>,
-    user_own_context: UserOwnContext,
-) -> String {
-    format!("user_id: {}", user_own_context.user_id,)
+/// ユーザーの一括登録
+///
+/// 登録に失敗したユーザーがいても、他のユーザーの登録を継続して、リクエスト・ボディのインデックスごとに
+/// 結果を返す。`atomic`に`true`を指定した場合は、1人でも登録に失敗したユーザーがいれば、
+/// すべてのユーザーを登録しない。
+#[utoipa::path(
+    post,
+    path = "/accounts/users/import",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(ImportUsersQuery),
+    request_body = Vec<SignUpReqBody>,
+    responses(
+        (status = 200, description = "ユーザーごとの登録結果", body = ImportUsersResBody),
+        (status = 400, description = "一括登録するユーザーが多すぎる", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "import users handler",
+    skip(context, admin_context, query, request_body)
+)]
+pub async fn import_users(
+    context: web::Data<RequestContext>,
+    admin_context: AdminContext,
+    query: web::Query<ImportUsersQuery>,
+    request_body: web::Json<Vec<serde_json::Value>>,
+) -> ProcessRequestResult<HttpResponse> {
+    let strict = context.http_server_settings.strict_request_bodies;
+    let policy = context.password_settings.policy();
+    let request_body = request_body
+        .into_inner()
+        .into_iter()
+        .map(|user| parse_request_body::<SignUpReqBody>(user, strict))
+        .collect::<ProcessRequestResult<Vec<_>>>()?;
+    // 検証に失敗したフィールドのエラーをレスポンスに含めるため、ユーザーごとに保持
+    let mut field_errors = vec![];
+    let users = request_body
+        .into_iter()
+        .map(|user| match user.validate_into_input(&policy) {
+            Ok(input) => {
+                field_errors.push(None);
+                Ok(input)
+            }
+            Err(e) => {
+                field_errors.push(e.body.fields);
+                Err(UseCaseError::validation(e.message))
+            }
+        })
+        .collect();
+    let input = BulkSignUpUseCaseInput {
+        users,
+        atomic: query.atomic,
+        actor: admin_context.audit_actor(),
+    };
+    let output = context
+        .accounts()
+        .bulk_sign_up(context.http_server_settings.max_import_users, input)
+        .await
+        .map_err(ProcessRequestError::from)?;
+    let mut body = ImportUsersResBody::from(output);
+    for failure in body.failed.iter_mut() {
+        failure.error.fields = field_errors[failure.index].take();
+    }
+
+    Ok(HttpResponse::Ok().json(body))
+}
+
+/// ユーザー一括登録クエリ
+#[derive(Debug, Clone, serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportUsersQuery {
+    /// 1人でも登録に失敗した場合に、すべてのユーザーを登録しないか示すフラグ
+    #[serde(default)]
+    pub atomic: bool,
+}
+
+/// ユーザー一括登録レスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportUsersResBody {
+    /// ユーザーの登録を確定したか示すフラグ
+    ///
+    /// `atomic`に`true`を指定して、1人でも登録に失敗したユーザーがいる場合は`false`で、
+    /// `succeeded`は空になる。
+    pub committed: bool,
+    /// 登録したユーザー
+    pub succeeded: Vec<ImportedUserResBody>,
+    /// 登録に失敗したユーザー
+    pub failed: Vec<ImportFailureResBody>,
+}
+
+/// 一括登録したユーザー
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedUserResBody {
+    /// リクエスト・ボディのインデックス
+    pub index: usize,
+    /// 登録したユーザー
+    pub user: SignUpResBody,
+}
+
+/// 一括登録に失敗したユーザー
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportFailureResBody {
+    /// リクエスト・ボディのインデックス
+    pub index: usize,
+    /// 登録に失敗した理由
+    pub error: ErrorResponseBody,
+}
+
+impl From<BulkSignUpUseCaseOutput> for ImportUsersResBody {
+    fn from(value: BulkSignUpUseCaseOutput) -> Self {
+        let mut succeeded = vec![];
+        let mut failed = vec![];
+        for (index, result) in value.results.into_iter().enumerate() {
+            match result {
+                BulkSignUpResult::SignedUp(user) => succeeded.push(ImportedUserResBody {
+                    index,
+                    user: (*user).into(),
+                }),
+                BulkSignUpResult::Failed(e) => failed.push(ImportFailureResBody {
+                    index,
+                    error: ProcessRequestError::from(e).body,
+                }),
+                BulkSignUpResult::Skipped => {}
+            }
+        }
+
+        Self {
+            committed: value.committed,
+            succeeded,
+            failed,
+        }
+    }
+}
+
+/// ユーザー権限の変更
+///
+/// ユーザー権限を変更したユーザーに発行したアクセストークンとリフレッシュトークンをすべて無効にするため、
+/// ユーザーは再度サインインする必要がある。
+#[utoipa::path(
+    put,
+    path = "/accounts/users/{user_id}/permission",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(("user_id" = Uuid, Path, description = "ユーザーID")),
+    request_body = ChangeUserPermissionReqBody,
+    responses(
+        (status = 200, description = "ユーザー権限を変更したユーザー", body = UserResBody),
+        (status = 400, description = "ユーザー権限コードが範囲外、または最後の管理者のユーザー権限を変更しようとした", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 404, description = "ユーザーが見つからない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "change user permission handler",
+    skip(context, admin_context, request_body)
+)]
+pub async fn change_user_permission(
+    context: web::Data<RequestContext>,
+    admin_context: AdminContext,
+    path: web::Path<Uuid>,
+    request_body: web::Json<ChangeUserPermissionReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let user_permission_code = UserPermissionCode::try_from(request_body.0.user_permission_code)
+        .map_err(ProcessRequestError::from)?;
+    let user = context
+        .accounts()
+        .change_user_permission(
+            &admin_context.audit_actor(),
+            UserId::new(path.into_inner()),
+            user_permission_code,
+        )
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::Ok().json(UserResBody::from(user)))
+}
+
+/// ユーザー権限変更リクエスト・ボディ
+#[derive(Debug, Clone, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeUserPermissionReqBody {
+    /// 変更後のユーザー権限コード
+    pub user_permission_code: i16,
+}
+
+/// ユーザー詳細
+///
+/// ユーザー自身または管理者が取得できる。
+/// レスポンスの`ETag`ヘッダを`If-None-Match`ヘッダに指定した場合、ユーザーが更新されていなければ、
+/// ボディを含まない304 Not Modifiedを返す。
+#[utoipa::path(
+    get,
+    path = "/accounts/users/{user_id}",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(
+        ("user_id" = Uuid, Path, description = "ユーザーID"),
+        ("If-None-Match" = Option<String>, Header, description = "以前に取得したユーザーの`ETag`"),
+    ),
+    responses(
+        (status = 200, description = "ユーザー", body = UserResBody,
+            headers(("ETag" = String, description = "ユーザーのエンティティタグ"))),
+        (status = 304, description = "ユーザーが更新されていない"),
+        (status = 400, description = "ユーザーIDの形式が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 404, description = "ユーザーが見つからない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "user detail handler", skip(context, user_context, request))]
+pub async fn user_detail(
+    context: web::Data<RequestContext>,
+    user_context: AdminOrOwnerContext,
+    request: HttpRequest,
+) -> ProcessRequestResult<HttpResponse> {
+    let user = context
+        .accounts()
+        .user_detail(user_context.target_user_id)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(conditional_json_response(
+        &request,
+        version_etag(user.version),
+        UserResBody::from(user),
+    ))
+}
+
+/// アカウントの状況
+///
+/// ユーザー自身または管理者が取得できる。
+/// 最後にサインインした日時、サインインに失敗した履歴、アカウントのロック状態、及び有効なトークンを
+/// 持つセッションの数をまとめて返す。
+#[utoipa::path(
+    get,
+    path = "/accounts/users/{user_id}/activity",
+    tag = "accounts",
+    security(("access_token" = [])),
+    params(
+        ("user_id" = Uuid, Path, description = "ユーザーID"),
+    ),
+    responses(
+        (status = 200, description = "アカウントの状況", body = AccountActivityResBody),
+        (status = 400, description = "ユーザーIDの形式が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 404, description = "ユーザーが見つからない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "account activity handler",
+    skip(context, user_context, access_token)
+)]
+pub async fn account_activity(
+    context: web::Data<RequestContext>,
+    user_context: AdminOrOwnerContext,
+    access_token: RequestAccessToken,
+) -> ProcessRequestResult<HttpResponse> {
+    let activity = context
+        .accounts()
+        .account_activity(user_context.target_user_id, &access_token.0)
+        .await
+        .map_err(ProcessRequestError::from)?;
+
+    Ok(HttpResponse::Ok().json(AccountActivityResBody::from(activity)))
+}
+
+/// アカウントの状況レスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountActivityResBody {
+    /// ユーザーID
+    #[schema(value_type = Uuid)]
+    pub user_id: UserId,
+    /// 最後にサインインした日時
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_sign_in_at: Option<OffsetDateTime>,
+    /// 最初にサインインに失敗した日時
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub sign_in_attempted_at: Option<OffsetDateTime>,
+    /// 最初にサインインに失敗した日時から、サインインに失敗した回数
+    pub number_of_sign_in_failures: i16,
+    /// アクティブフラグ
+    pub active: bool,
+    /// アカウントをロックした日時
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub locked_at: Option<OffsetDateTime>,
+    /// 有効なトークンを持つセッションの数
+    pub active_sessions: usize,
+}
+
+impl From<AccountActivity> for AccountActivityResBody {
+    fn from(value: AccountActivity) -> Self {
+        Self {
+            user_id: value.user_id,
+            last_sign_in_at: value.last_sign_in_at,
+            sign_in_attempted_at: value.sign_in_attempted_at,
+            number_of_sign_in_failures: value.number_of_sign_in_failures.value,
+            active: value.active,
+            locked_at: value.locked_at,
+            active_sessions: value.active_sessions,
+        }
+    }
 }
@@ -0,0 +1,279 @@
+use actix_web::dev::HttpServiceFactory;
+use actix_web::http::{Method, StatusCode};
+use actix_web::{web, HttpResponse};
+use time::OffsetDateTime;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use domain::models::audit_log::AuditLog;
+use domain::models::user::UserId;
+use domain::repositories::audit_log::AuditLogFilter;
+use use_cases::accounts::PurgeExpiredRecordsUseCaseOutput;
+
+use crate::routes::extractors::AdminContext;
+use crate::routes::ip_allowlist::IpAllowlistGuard;
+use crate::routes::{method_not_allowed, ProcessRequestError, ProcessRequestResult};
+use crate::RequestContext;
+
+/// 管理スコープを返却する。
+///
+/// # 引数
+///
+/// * `ip_allowlist` - アクセス元のIPアドレスを確認するミドルウェア
+pub fn admin_scope(ip_allowlist: &IpAllowlistGuard) -> impl HttpServiceFactory {
+    web::scope("/admin")
+        .wrap(ip_allowlist.clone())
+        .service(
+            web::resource("/maintenance")
+                .route(web::get().to(maintenance))
+                .route(web::post().to(switch_maintenance))
+                .default_service(method_not_allowed(&[Method::GET, Method::POST])),
+        )
+        .service(
+            web::resource("/retention/run")
+                .route(web::post().to(run_retention))
+                .default_service(method_not_allowed(&[Method::POST])),
+        )
+        .service(
+            web::resource("/audit-logs")
+                .route(web::get().to(list_audit_logs))
+                .default_service(method_not_allowed(&[Method::GET])),
+        )
+}
+
+/// メンテナンスの状態
+///
+/// メンテナンス中であるか返す。
+#[utoipa::path(
+    get,
+    path = "/admin/maintenance",
+    tag = "admin",
+    security(("access_token" = [])),
+    responses(
+        (status = 200, description = "メンテナンスの状態", body = MaintenanceResBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "maintenance handler", skip(request_context, _admin_context))]
+pub async fn maintenance(
+    request_context: web::Data<RequestContext>,
+    _admin_context: AdminContext,
+) -> ProcessRequestResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(MaintenanceResBody {
+        enabled: request_context.is_under_maintenance(),
+    }))
+}
+
+/// メンテナンスの切り替え
+///
+/// メンテナンスを開始または終了する。
+///
+/// メンテナンス中は、ヘルスチェックとメンテナンスの切り替え以外のリクエストに、
+/// 503 Service Unavailableを返す。
+/// メンテナンスの状態はプロセスごとに保持するため、複数のインスタンスで共有されない。
+#[utoipa::path(
+    post,
+    path = "/admin/maintenance",
+    tag = "admin",
+    security(("access_token" = [])),
+    request_body = MaintenanceReqBody,
+    responses(
+        (status = 200, description = "切り替えた後のメンテナンスの状態", body = MaintenanceResBody),
+        (status = 400, description = "リクエストボディが誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "switch maintenance handler",
+    skip(request_context, _admin_context, request_body)
+)]
+pub async fn switch_maintenance(
+    request_context: web::Data<RequestContext>,
+    _admin_context: AdminContext,
+    request_body: web::Json<MaintenanceReqBody>,
+) -> ProcessRequestResult<HttpResponse> {
+    let enabled = request_body.enabled;
+    request_context.set_maintenance(enabled);
+
+    Ok(HttpResponse::Ok().json(MaintenanceResBody { enabled }))
+}
+
+/// メンテナンスの切り替えリクエスト・ボディ
+#[derive(Debug, Clone, Copy, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReqBody {
+    /// メンテナンスを開始する場合は`true`、終了する場合は`false`
+    pub enabled: bool,
+}
+
+/// メンテナンスの状態レスポンス・ボディ
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceResBody {
+    /// メンテナンス中の場合は`true`
+    pub enabled: bool,
+}
+
+/// 保持期間を経過したレコードの消去
+///
+/// 保持期間設定に従って、保持期間を経過したサインインに失敗した履歴と、削除したユーザーの
+/// レコードを消去して、消去したレコードの数を返す。
+#[utoipa::path(
+    post,
+    path = "/admin/retention/run",
+    tag = "admin",
+    security(("access_token" = [])),
+    responses(
+        (status = 200, description = "消去したレコードの数", body = RetentionRunResBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+        (status = 503, description = "データベースが応答しない", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(name = "run retention handler", skip(request_context, _admin_context))]
+pub async fn run_retention(
+    request_context: web::Data<RequestContext>,
+    _admin_context: AdminContext,
+) -> ProcessRequestResult<HttpResponse> {
+    let output = request_context
+        .accounts()
+        .purge_expired_records(&request_context.retention_settings)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(RetentionRunResBody::from(output)))
+}
+
+/// 保持期間を経過したレコードの消去レスポンス・ボディ
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionRunResBody {
+    /// サインインに失敗した履歴を消去したユーザーの数
+    pub purged_sign_in_histories: u64,
+    /// 物理的に削除した、削除したユーザーの数
+    pub purged_deleted_users: u64,
+}
+
+impl From<PurgeExpiredRecordsUseCaseOutput> for RetentionRunResBody {
+    fn from(value: PurgeExpiredRecordsUseCaseOutput) -> Self {
+        Self {
+            purged_sign_in_histories: value.sign_in_histories,
+            purged_deleted_users: value.deleted_users,
+        }
+    }
+}
+
+/// 監査ログリスト
+///
+/// 管理者が実行した操作を記録した監査ログを、操作を実行した日時の新しい順に返す。
+#[utoipa::path(
+    get,
+    path = "/admin/audit-logs",
+    tag = "admin",
+    security(("access_token" = [])),
+    params(AuditLogListQuery),
+    responses(
+        (status = 200, description = "監査ログのリスト", body = Vec<AuditLogResBody>),
+        (status = 400, description = "取得する範囲が誤っている", body = ErrorResponseBody),
+        (status = 403, description = "アクセスする権限がない", body = ErrorResponseBody),
+        (status = 500, description = "サーバー内部でエラーが発生した", body = ErrorResponseBody),
+    )
+)]
+#[tracing::instrument(
+    name = "list audit logs handler",
+    skip(request_context, _admin_context, query)
+)]
+pub async fn list_audit_logs(
+    request_context: web::Data<RequestContext>,
+    _admin_context: AdminContext,
+    query: web::Query<AuditLogListQuery>,
+) -> ProcessRequestResult<HttpResponse> {
+    let filter = AuditLogFilter::try_from(query.into_inner())?;
+    let audit_logs = request_context
+        .accounts()
+        .list_audit_logs(filter)
+        .await?
+        .into_iter()
+        .map(AuditLogResBody::from)
+        .collect::<Vec<_>>();
+
+    Ok(HttpResponse::Ok().json(audit_logs))
+}
+
+/// 監査ログリストの絞り込み条件クエリ
+#[derive(Debug, Clone, serde::Deserialize, IntoParams)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct AuditLogListQuery {
+    /// 操作を実行した管理者のユーザーID
+    pub actor_user_id: Option<Uuid>,
+    /// 操作の対象となったユーザーのユーザーID
+    pub target_user_id: Option<Uuid>,
+    /// 取得する監査ログの最大数（1以上1000以下、省略した場合はすべて）
+    pub limit: Option<u32>,
+    /// 操作を実行した日時の新しい順で先頭から読み飛ばす監査ログの数（省略した場合は0）
+    pub offset: Option<u32>,
+}
+
+impl TryFrom<AuditLogListQuery> for AuditLogFilter {
+    type Error = ProcessRequestError;
+
+    fn try_from(value: AuditLogListQuery) -> Result<Self, Self::Error> {
+        if value
+            .limit
+            .is_some_and(|limit| !(1..=MAX_AUDIT_LOG_LIST_LIMIT).contains(&limit))
+        {
+            return Err(ProcessRequestError::without_error_code(
+                StatusCode::BAD_REQUEST,
+                AUDIT_LOG_LIST_LIMIT_OUT_OF_RANGE,
+            ));
+        }
+
+        Ok(Self {
+            actor_user_id: value.actor_user_id.map(UserId::new),
+            target_user_id: value.target_user_id.map(UserId::new),
+            limit: value.limit,
+            offset: value.offset.unwrap_or_default(),
+        })
+    }
+}
+
+/// 監査ログのレスポンス・ボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogResBody {
+    /// 監査ログID
+    pub id: Uuid,
+    /// 操作を実行した管理者のユーザーID
+    pub actor_user_id: Uuid,
+    /// 操作（`change_user_permission`、`delete_user`、`import_user`、`create_user`または
+    /// `update_password`）
+    pub action: String,
+    /// 操作の対象となったユーザーのユーザーID
+    pub target_user_id: Option<Uuid>,
+    /// 操作を実行した日時
+    #[serde(with = "time::serde::rfc3339")]
+    pub occurred_at: OffsetDateTime,
+    /// 操作を要求したリクエストのリクエストID
+    pub request_id: Option<String>,
+    /// 操作ごとの詳細
+    #[schema(value_type = Object)]
+    pub details: serde_json::Value,
+}
+
+impl From<AuditLog> for AuditLogResBody {
+    fn from(value: AuditLog) -> Self {
+        Self {
+            id: value.id.value,
+            actor_user_id: value.actor_user_id.value,
+            action: value.action.to_string(),
+            target_user_id: value.target_user_id.map(|id| id.value),
+            occurred_at: value.occurred_at,
+            request_id: value.request_id,
+            details: value.details,
+        }
+    }
+}
+
+/// 1回のリクエストで取得できる監査ログの最大数
+const MAX_AUDIT_LOG_LIST_LIMIT: u32 = 1000;
+const AUDIT_LOG_LIST_LIMIT_OUT_OF_RANGE: &str = "取得する監査ログの数は、1以上1000以下です。";
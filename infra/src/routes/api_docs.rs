@@ -0,0 +1,229 @@
+use actix_web::http::{header, Method};
+use actix_web::{web, HttpResponse};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::openapi::Server;
+use utoipa::{Modify, OpenApi};
+
+use crate::routes::accounts::{
+    self, AccountActivityResBody, ActiveSessionResBody, ChangeRequiredPasswordReqBody,
+    ChangeUserPermissionReqBody, ConfirmEmailChangeReqBody, CreateUserReqBody, CreateUserResBody,
+    DeleteUserReqBody, ImportFailureResBody, ImportUsersResBody, ImportedUserResBody,
+    IntrospectTokenReqBody, LockedUserResBody, MfaPendingResBody, PasswordChangeRequiredResBody,
+    ReauthenticateReqBody, ReauthenticateResBody, RequestEmailChangeReqBody,
+    ResendEmailVerificationReqBody, SessionResBody, SessionTokenResBody, SignInReqBody,
+    SignInResBody, SignInStatus, SignInWithTotpReqBody, SignUpReqBody, SignUpResBody,
+    TokenIntrospectionResBody, TotpCodeReqBody, TotpSetupResBody, UpdateProfileReqBody,
+    UserPermissionBody, UserResBody, VerifyEmailReqBody,
+};
+use crate::routes::admin::{
+    self, AuditLogResBody, MaintenanceReqBody, MaintenanceResBody, RetentionRunResBody,
+};
+use crate::routes::{
+    self, method_not_allowed, DependencyStatus, ErrorResponseBody, FieldError, ReadinessResBody,
+    ACCESS_TOKEN_KEY,
+};
+use crate::RequestContext;
+
+/// OpenAPIドキュメント
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        routes::health_check,
+        routes::readiness_check,
+        accounts::sign_up,
+        accounts::sign_in,
+        accounts::sign_in_with_totp,
+        accounts::change_required_password,
+        accounts::session,
+        accounts::list_sessions,
+        accounts::revoke_session,
+        accounts::introspect_token,
+        accounts::set_up_totp,
+        accounts::enable_totp,
+        accounts::disable_totp,
+        accounts::verify_email,
+        accounts::resend_email_verification,
+        accounts::reauthenticate,
+        accounts::request_email_change,
+        accounts::confirm_email_change,
+        accounts::list_users,
+        accounts::list_locked_users,
+        accounts::export_users,
+        accounts::create_user,
+        accounts::import_users,
+        accounts::change_user_permission,
+        accounts::update_profile,
+        accounts::delete_user,
+        accounts::user_detail,
+        accounts::account_activity,
+        admin::maintenance,
+        admin::switch_maintenance,
+        admin::run_retention,
+        admin::list_audit_logs,
+    ),
+    components(schemas(
+        ErrorResponseBody,
+        FieldError,
+        DependencyStatus,
+        ReadinessResBody,
+        SignUpReqBody,
+        SignUpResBody,
+        SignInReqBody,
+        SignInStatus,
+        SignInResBody,
+        MfaPendingResBody,
+        PasswordChangeRequiredResBody,
+        SignInWithTotpReqBody,
+        ChangeRequiredPasswordReqBody,
+        SessionResBody,
+        ActiveSessionResBody,
+        SessionTokenResBody,
+        IntrospectTokenReqBody,
+        TokenIntrospectionResBody,
+        TotpSetupResBody,
+        TotpCodeReqBody,
+        VerifyEmailReqBody,
+        ResendEmailVerificationReqBody,
+        ReauthenticateReqBody,
+        ReauthenticateResBody,
+        RequestEmailChangeReqBody,
+        ConfirmEmailChangeReqBody,
+        UserResBody,
+        LockedUserResBody,
+        CreateUserReqBody,
+        CreateUserResBody,
+        ImportUsersResBody,
+        ImportedUserResBody,
+        ImportFailureResBody,
+        ChangeUserPermissionReqBody,
+        UpdateProfileReqBody,
+        DeleteUserReqBody,
+        UserPermissionBody,
+        AccountActivityResBody,
+        MaintenanceReqBody,
+        MaintenanceResBody,
+        RetentionRunResBody,
+        AuditLogResBody,
+    )),
+    modifiers(&AccessTokenSecurity, &ServiceCredentialSecurity),
+    tags(
+        (name = "health", description = "ヘルスチェック"),
+        (name = "accounts", description = "アカウント"),
+        (name = "admin", description = "管理"),
+    )
+)]
+pub struct ApiDoc;
+
+/// アクセストークンによる認証方法をOpenAPIドキュメントに追加する。
+///
+/// アクセストークンは、`Authorization`ヘッダまたはクッキーで送信する。
+struct AccessTokenSecurity;
+
+impl Modify for AccessTokenSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "access_token",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "access_token_cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(ACCESS_TOKEN_KEY))),
+        );
+    }
+}
+
+/// 内部サービスの資格情報による認証方法をOpenAPIドキュメントに追加する。
+///
+/// 内部サービスの資格情報は、`Authorization`ヘッダで送信する。
+struct ServiceCredentialSecurity;
+
+impl Modify for ServiceCredentialSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "service_credential",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+/// APIドキュメントスコープを返却する。
+///
+/// # 引数
+///
+/// * `swagger_ui` - Swagger UIを提供するか示すフラグ
+pub fn api_docs_scope(swagger_ui: bool) -> actix_web::Scope {
+    let scope = web::scope("/api-docs").service(
+        web::resource("/openapi.json")
+            .route(web::get().to(openapi_json))
+            .default_service(method_not_allowed(&[Method::GET])),
+    );
+    match swagger_ui {
+        true => scope.service(
+            web::resource("")
+                .route(web::get().to(swagger_ui_html))
+                .default_service(method_not_allowed(&[Method::GET])),
+        ),
+        false => scope,
+    }
+}
+
+/// OpenAPIドキュメントをJSON形式で返す。
+///
+/// APIのパスのプレフィックスを、OpenAPIドキュメントのサーバーのURLとして設定する。
+/// また、アクセストークンを保存するクッキーの名前を、HTTPサーバー設定に合わせて設定する。
+async fn openapi_json(context: web::Data<RequestContext>) -> HttpResponse {
+    let mut document = ApiDoc::openapi();
+    let url = match context.http_server_settings.api_prefix.as_str() {
+        "" => "/",
+        prefix => prefix,
+    };
+    document.servers = Some(vec![Server::new(url)]);
+    // クッキーの名前にプレフィックスが付与される場合があるため、リクエストコンテキストから取得
+    if let Some(components) = document.components.as_mut() {
+        components.add_security_scheme(
+            "access_token_cookie",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(
+                context.access_token_cookie_name(),
+            ))),
+        );
+    }
+
+    HttpResponse::Ok().json(document)
+}
+
+/// Swagger UIを返す。
+async fn swagger_ui_html() -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(header::ContentType::html())
+        .body(SWAGGER_UI_HTML)
+}
+
+/// Swagger UIのHTML
+const SWAGGER_UI_HTML: &str = r##"<!DOCTYPE html>
+<html lang="ja">
+<head>
+  <meta charset="utf-8" />
+  <title>actix-web-example API</title>
+  <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+  <script>
+    window.onload = () => {
+      window.ui = SwaggerUIBundle({
+        url: "/api-docs/openapi.json",
+        dom_id: "#swagger-ui",
+      });
+    };
+  </script>
+</body>
+</html>
+"##;
@@ -0,0 +1,179 @@
+use actix_web::http::header::{self, HeaderValue, IF_MATCH, IF_NONE_MATCH};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse};
+
+use use_cases::messages::MessageKey;
+
+use crate::routes::{ProcessRequestError, ProcessRequestResult};
+
+/// ユーザーのバージョンから、`ETag`ヘッダに設定するエンティティタグを生成する。
+///
+/// ユーザーのバージョンは、サインインの記録など、プロフィール以外の更新では増えないため、
+/// 弱いエンティティタグとする。
+///
+/// # 引数
+///
+/// * `version` - ユーザーのバージョン
+///
+/// # 戻り値
+///
+/// `W/"<version>"`形式のエンティティタグ
+pub fn version_etag(version: i32) -> (header::HeaderName, String) {
+    (header::ETAG, format!(r#"W/"{}""#, version))
+}
+
+/// `If-Match`ヘッダから、クライアントが取得したユーザーのバージョンを取得する。
+///
+/// このAPIは弱いエンティティタグのみを発行するため、`W/`の有無を区別せずに比較する。
+///
+/// # 引数
+///
+/// * `request` - HTTPリクエスト
+///
+/// # 戻り値
+///
+/// クライアントが取得したユーザーのバージョン
+///
+/// `If-Match`ヘッダがない、`*`が指定されている、またはバージョンを取得できない場合は、
+/// 428 Precondition Requiredのリクエスト処理エラー
+pub fn if_match_version(request: &HttpRequest) -> ProcessRequestResult<i32> {
+    request
+        .headers()
+        .get(IF_MATCH)
+        .and_then(parse_version_etag)
+        .ok_or_else(|| {
+            ProcessRequestError::without_error_code(
+                StatusCode::PRECONDITION_REQUIRED,
+                MessageKey::IfMatchIsRequired,
+            )
+        })
+}
+
+/// エンティティタグを付与して、リソースをJSONで返すレスポンスを構築する。
+///
+/// リクエストの`If-None-Match`ヘッダのエンティティタグのいずれかが、リソースのエンティティタグと
+/// 一致する場合は、ボディを含まない304 Not Modifiedを返す。エンティティタグは、`W/`の有無を
+/// 区別しない弱い比較で比較する。
+///
+/// # 引数
+///
+/// * `request` - HTTPリクエスト
+/// * `etag` - `ETag`ヘッダとエンティティタグ
+/// * `body` - リソース
+///
+/// # 戻り値
+///
+/// HTTPレスポンス
+pub fn conditional_json_response(
+    request: &HttpRequest,
+    etag: (header::HeaderName, String),
+    body: impl serde::Serialize,
+) -> HttpResponse {
+    let not_modified = request
+        .headers()
+        .get_all(IF_NONE_MATCH)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|tag| tag.trim() == "*" || weak_eq(tag.trim(), &etag.1));
+    if not_modified {
+        return HttpResponse::NotModified().insert_header(etag).finish();
+    }
+
+    HttpResponse::Ok().insert_header(etag).json(body)
+}
+
+/// 2つのエンティティタグを、`W/`の有無を区別せずに比較する。
+fn weak_eq(a: &str, b: &str) -> bool {
+    let a = a.strip_prefix("W/").unwrap_or(a);
+    let b = b.strip_prefix("W/").unwrap_or(b);
+
+    a == b
+}
+
+/// エンティティタグからユーザーのバージョンを取得する。
+fn parse_version_etag(value: &HeaderValue) -> Option<i32> {
+    let value = value.to_str().ok()?.trim();
+    let value = value.strip_prefix("W/").unwrap_or(value);
+
+    value.strip_prefix('"')?.strip_suffix('"')?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    /// ユーザーのバージョンから弱いエンティティタグを生成できることを確認
+    #[test]
+    fn can_generate_weak_etag_from_version() {
+        let (name, value) = version_etag(3);
+
+        assert_eq!(header::ETAG, name);
+        assert_eq!(r#"W/"3""#, value);
+    }
+
+    /// `If-Match`ヘッダから、弱いまたは強いエンティティタグのバージョンを取得できることを確認
+    #[test]
+    fn can_retrieve_version_from_if_match() {
+        for value in [r#"W/"3""#, r#""3""#, r#" W/"3" "#] {
+            let request = TestRequest::default()
+                .insert_header((IF_MATCH, value))
+                .to_http_request();
+
+            assert_eq!(3, if_match_version(&request).unwrap(), "{}", value);
+        }
+    }
+
+    /// `If-None-Match`ヘッダのエンティティタグが一致する場合は、304を返すことを確認
+    #[test]
+    fn matching_if_none_match_is_not_modified() {
+        for value in [r#"W/"3""#, r#""3""#, r#"W/"1", W/"3""#, "*"] {
+            let request = TestRequest::default()
+                .insert_header((IF_NONE_MATCH, value))
+                .to_http_request();
+
+            let response = conditional_json_response(&request, version_etag(3), "body");
+
+            assert_eq!(StatusCode::NOT_MODIFIED, response.status(), "{}", value);
+            assert_eq!(r#"W/"3""#, response.headers().get(header::ETAG).unwrap());
+        }
+    }
+
+    /// `If-None-Match`ヘッダがない、またはエンティティタグが一致しない場合は、200を返すことを確認
+    #[test]
+    fn missing_or_unmatched_if_none_match_is_ok() {
+        let request = TestRequest::default().to_http_request();
+        let response = conditional_json_response(&request, version_etag(3), "body");
+        assert_eq!(StatusCode::OK, response.status());
+        assert_eq!(r#"W/"3""#, response.headers().get(header::ETAG).unwrap());
+
+        let request = TestRequest::default()
+            .insert_header((IF_NONE_MATCH, r#"W/"2""#))
+            .to_http_request();
+        let response = conditional_json_response(&request, version_etag(3), "body");
+        assert_eq!(StatusCode::OK, response.status());
+    }
+
+    /// `If-Match`ヘッダがない、またはバージョンを取得できない場合は、428を返すことを確認
+    #[test]
+    fn missing_or_malformed_if_match_is_precondition_required() {
+        let request = TestRequest::default().to_http_request();
+        let e = if_match_version(&request).unwrap_err();
+        assert_eq!(StatusCode::PRECONDITION_REQUIRED, e.status_code);
+
+        for value in ["*", "3", r#"W/"abc""#, r#""3"#] {
+            let request = TestRequest::default()
+                .insert_header((IF_MATCH, value))
+                .to_http_request();
+            let e = if_match_version(&request).unwrap_err();
+
+            assert_eq!(
+                StatusCode::PRECONDITION_REQUIRED,
+                e.status_code,
+                "{}",
+                value
+            );
+        }
+    }
+}
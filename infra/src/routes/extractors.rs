@@ -1,19 +1,23 @@
-use std::future::Future;
+use std::future::{ready, Future, Ready};
+use std::net::{IpAddr, SocketAddr};
 use std::pin::Pin;
 use std::str::FromStr as _;
 
+use actix_web::http::header::{HeaderMap, HeaderName};
 use actix_web::http::{header, StatusCode};
-use actix_web::{web, FromRequest, HttpRequest};
+use actix_web::{web, FromRequest, HttpMessage as _, HttpRequest};
+use ipnet::IpNet;
 use secrecy::SecretString;
+use tracing_actix_web::RequestId;
 use uuid::Uuid;
 
+use domain::models::audit_log::AuditActor;
 use domain::models::user::{UserId, UserPermissionCode};
-use domain::repositories::token::{TokenContent, TokenRepository, TokenType};
+use domain::repositories::token::{AccessToken, TokenContent, TokenRepository, TokenType};
+use use_cases::jwt::{looks_like_jwt, retrieve_claim_from_token};
+use use_cases::messages::MessageKey;
 
-use crate::repositories::redis::token::RedisTokenRepository;
-use crate::routes::{
-    ErrorResponseBody, ProcessRequestError, ProcessRequestResult, ACCESS_TOKEN_KEY,
-};
+use crate::routes::{ProcessRequestError, ProcessRequestResult, ACCESS_TOKEN_KEY};
 use crate::RequestContext;
 
 /// 認証済みユーザーのみがアクセス可能なコンテキスト
@@ -35,9 +39,83 @@ impl FromRequest for UserContext {
     }
 }
 
+/// 匿名のユーザーと認証済みユーザーの両方がアクセス可能なコンテキスト
+///
+/// アクセストークンが送信されていない場合は`None`になる。アクセストークンが送信されたが、
+/// 無効または有効期限が切れている場合は、壊れたクライアントを見逃さないように、
+/// 匿名のユーザーとして扱わずに401 Unauthorizedを返す。
+pub struct OptionalUserContext(pub Option<TokenContent>);
+
+impl FromRequest for OptionalUserContext {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let request = req.clone();
+
+        Box::pin(async move {
+            // リクエストヘッダからアクセストークンを取得
+            let token = retrieve_access_token(&request)?;
+            if token.is_none() {
+                return Ok(Self(None));
+            }
+            let token = token.unwrap();
+            // アクセストークンに紐付いたトークンの内容を取得
+            let content = resolve_token_content(&request, &token)
+                .await?
+                .ok_or_else(|| {
+                    ProcessRequestError::without_error_code(
+                        StatusCode::UNAUTHORIZED,
+                        MessageKey::InvalidAccessToken,
+                    )
+                })?;
+
+            Ok(Self(Some(content)))
+        })
+    }
+}
+
+/// リクエストで送信されたアクセストークン
+///
+/// アクセストークンで再認証したことを確認するときに使用する。アクセストークンが有効であるかは
+/// 確認しないため、`UserContext`などのコンテキストと一緒に使用する。
+pub struct RequestAccessToken(pub AccessToken);
+
+impl FromRequest for RequestAccessToken {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let request = req.clone();
+
+        Box::pin(async move {
+            // リクエストヘッダからアクセストークンを取得
+            let token = retrieve_access_token(&request)?;
+
+            token
+                .map(|token| Self(AccessToken::new(token)))
+                .ok_or_else(forbidden_actix_error)
+        })
+    }
+}
+
 /// 管理権限を持つユーザーのアクセス可能なコンテキスト
 pub struct AdminContext {
     pub user_id: UserId,
+    /// リクエストID
+    ///
+    /// `TracingLogger`ミドルウェアを登録していない場合は`None`
+    pub request_id: Option<String>,
+}
+
+impl AdminContext {
+    /// 管理者が実行した操作を監査ログに記録する操作者を返す。
+    pub fn audit_actor(&self) -> AuditActor {
+        AuditActor {
+            user_id: self.user_id,
+            request_id: self.request_id.clone(),
+        }
+    }
 }
 
 impl FromRequest for AdminContext {
@@ -56,6 +134,7 @@ impl FromRequest for AdminContext {
 
             Ok(Self {
                 user_id: content.user_id,
+                request_id: request_id(&request),
             })
         })
     }
@@ -78,23 +157,10 @@ impl FromRequest for UserOwnContext {
         Box::pin(async move {
             // リクエストヘッダからアクセストークンを取得してトークンコンテンツを取得
             let content = retrieve_token_content(&request).await?;
-            // リクエストURIからユーザーIDを文字列で取得
-            let user_id = request.match_info().get("user_id").ok_or_else(|| {
-                ProcessRequestError::without_error_code(
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "リクエストURIに`{user_id}`パスが必要です。",
-                )
-            })?;
-            // 文字列で表現されたユーザーIDを変換
-            let user_id = Uuid::from_str(user_id).map_err(|_| {
-                ProcessRequestError::without_error_code(
-                    StatusCode::BAD_REQUEST,
-                    "リクエストURIで指定されたユーザーIDをUUIDに変換できません。",
-                )
-            })?;
+            // リクエストURIで指定されたユーザーIDを取得
+            let user_id = path_user_id(&request)?;
             // リクエストURIで指定されたユーザーIDと、アクセストークンに紐付いたユーザーIDを比較して、
             // リクエストしたユーザー自身の情報をリクエストしているか確認
-            let user_id = UserId::new(user_id);
             if content.user_id != user_id {
                 return Err(forbidden_actix_error());
             }
@@ -104,8 +170,279 @@ impl FromRequest for UserOwnContext {
     }
 }
 
+/// ユーザー自身または管理者がアクセス可能なコンテキスト
+///
+/// パスに`{user_id}`を含み、それがユーザーIDであること。
+pub struct AdminOrOwnerContext {
+    /// リクエストしたユーザーのユーザーID
+    pub acting_user_id: UserId,
+    /// リクエストURIで指定されたユーザーID
+    pub target_user_id: UserId,
+    /// リクエストしたユーザーが管理者であるか示すフラグ
+    pub is_admin: bool,
+    /// リクエストID
+    pub request_id: Option<String>,
+}
+
+impl AdminOrOwnerContext {
+    /// トークンの内容とリクエストURIで指定されたユーザーIDから、コンテキストを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `content` - アクセストークンに紐付いたトークンの内容
+    /// * `target_user_id` - リクエストURIで指定されたユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// リクエストしたユーザーが管理者、またはリクエストURIで指定されたユーザー自身の場合は
+    /// コンテキスト、それ以外の場合は403 Forbiddenのリクエスト処理エラー
+    fn authorize(content: &TokenContent, target_user_id: UserId) -> ProcessRequestResult<Self> {
+        let is_admin = content.user_permission_code == UserPermissionCode::Admin;
+        if !is_admin && content.user_id != target_user_id {
+            return Err(forbidden_error());
+        }
+
+        Ok(Self {
+            acting_user_id: content.user_id,
+            target_user_id,
+            is_admin,
+            request_id: None,
+        })
+    }
+
+    /// リクエストしたユーザーが、リクエストURIで指定されたユーザー自身であるか確認する。
+    pub fn is_owner(&self) -> bool {
+        self.acting_user_id == self.target_user_id
+    }
+
+    /// リクエストしたユーザーを、操作を監査ログに記録する操作者として返す。
+    pub fn audit_actor(&self) -> AuditActor {
+        AuditActor {
+            user_id: self.acting_user_id,
+            request_id: self.request_id.clone(),
+        }
+    }
+}
+
+impl FromRequest for AdminOrOwnerContext {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        let request = req.clone();
+
+        Box::pin(async move {
+            // リクエストヘッダからアクセストークンを取得してトークンコンテンツを取得
+            let content = retrieve_token_content(&request).await?;
+            // リクエストURIで指定されたユーザーIDを取得
+            let target_user_id = path_user_id(&request)?;
+
+            let mut context = Self::authorize(&content, target_user_id)?;
+            context.request_id = request_id(&request);
+
+            Ok(context)
+        })
+    }
+}
+
+/// `TracingLogger`ミドルウェアがリクエストに付与したリクエストIDを取得する。
+///
+/// # 引数
+///
+/// * `request` - HTTPリクエスト
+///
+/// # 戻り値
+///
+/// リクエストID
+fn request_id(request: &HttpRequest) -> Option<String> {
+    request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.to_string())
+}
+
+/// リクエストのアクセス元のIPアドレス
+///
+/// 接続元が信頼するリバースプロキシの場合は、`X-Forwarded-For`ヘッダ、または`X-Forwarded-For`
+/// ヘッダがない場合は`Forwarded`ヘッダに記録されたIPアドレスを末尾からたどり、信頼するプロキシ
+/// 以外の最初のIPアドレスをアクセス元とする。接続元が信頼するプロキシでない場合は、偽装されている
+/// 可能性があるため、これらのヘッダを無視して接続元のIPアドレスをアクセス元とする。
+///
+/// 判定したIPアドレスはリクエストの拡張に記録するため、ミドルウェアやハンドラで何度取得しても、
+/// ヘッダを1回だけ解析する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// リクエストのアクセス元のIPアドレスを判定する。
+    ///
+    /// リクエストコンテキストを取得できない場合は、信頼するプロキシがないものとして判定する。
+    ///
+    /// # 引数
+    ///
+    /// * `request` - HTTPリクエスト
+    ///
+    /// # 戻り値
+    ///
+    /// アクセス元のIPアドレス、接続元のIPアドレスを取得できない場合は`None`
+    pub fn resolve(request: &HttpRequest) -> Option<Self> {
+        if let Some(client_ip) = request.extensions().get::<Self>() {
+            return Some(*client_ip);
+        }
+        let trusted_proxies = request
+            .app_data::<web::Data<RequestContext>>()
+            .map(|context| context.trusted_proxies())
+            .unwrap_or_default();
+        let peer_ip = request.peer_addr().map(|addr| addr.ip());
+        let client_ip = resolve_client_ip(peer_ip, request.headers(), trusted_proxies).map(Self)?;
+        request.extensions_mut().insert(client_ip);
+
+        Some(client_ip)
+    }
+}
+
+impl FromRequest for ClientIp {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &actix_web::HttpRequest, _: &mut actix_web::dev::Payload) -> Self::Future {
+        ready(Self::resolve(req).ok_or_else(|| {
+            ProcessRequestError::without_error_code(
+                StatusCode::BAD_REQUEST,
+                MessageKey::ClientIpIsUnknown,
+            )
+            .into()
+        }))
+    }
+}
+
+/// `X-Forwarded-For`ヘッダ
+const X_FORWARDED_FOR: HeaderName = HeaderName::from_static("x-forwarded-for");
+
+/// 接続元のIPアドレスとリクエストヘッダから、アクセス元のIPアドレスを判定する。
+///
+/// IPv4射影IPv6アドレスは、IPv4アドレスに変換する。
+///
+/// # 引数
+///
+/// * `peer_ip` - 接続元のIPアドレス
+/// * `headers` - リクエストヘッダ
+/// * `trusted_proxies` - 信頼するリバースプロキシのIPアドレスの範囲
+///
+/// # 戻り値
+///
+/// アクセス元のIPアドレス、接続元のIPアドレスがない場合は`None`
+fn resolve_client_ip(
+    peer_ip: Option<IpAddr>,
+    headers: &HeaderMap,
+    trusted_proxies: &[IpNet],
+) -> Option<IpAddr> {
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|proxy| proxy.contains(ip));
+    let mut client_ip = peer_ip?.to_canonical();
+    if !is_trusted(&client_ip) {
+        return Some(client_ip);
+    }
+    // 信頼するプロキシが追記したIPアドレスを末尾からたどり、信頼するプロキシ以外のIPアドレスを探す
+    for address in forwarded_addresses(headers).into_iter().rev() {
+        // 解析できないIPアドレスより前は信頼できないため、最後にたどったプロキシをアクセス元とする
+        let Some(address) = address else {
+            break;
+        };
+        client_ip = address;
+        if !is_trusted(&client_ip) {
+            break;
+        }
+    }
+
+    Some(client_ip)
+}
+
+/// リクエストヘッダから、プロキシが記録したIPアドレスを、記録された順に取得する。
+///
+/// `X-Forwarded-For`ヘッダがある場合は`X-Forwarded-For`ヘッダから、ない場合は`Forwarded`ヘッダの
+/// `for`パラメーターから取得する。
+///
+/// # 引数
+///
+/// * `headers` - リクエストヘッダ
+///
+/// # 戻り値
+///
+/// IPアドレスのリスト、IPアドレスとして解析できない値は`None`
+fn forwarded_addresses(headers: &HeaderMap) -> Vec<Option<IpAddr>> {
+    if headers.contains_key(X_FORWARDED_FOR) {
+        return headers
+            .get_all(X_FORWARDED_FOR)
+            .flat_map(|value| {
+                value
+                    .to_str()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(|address| parse_forwarded_address(address.trim()))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+    }
+
+    headers
+        .get_all(header::FORWARDED)
+        .flat_map(|value| {
+            value
+                .to_str()
+                .unwrap_or_default()
+                .split(',')
+                .map(|element| {
+                    element
+                        .split(';')
+                        .filter_map(|pair| pair.trim().split_once('='))
+                        .find(|(name, _)| name.eq_ignore_ascii_case("for"))
+                        .and_then(|(_, value)| parse_forwarded_address(value.trim_matches('"')))
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// プロキシが記録したIPアドレスを解析する。
+///
+/// ポート番号を付与したIPアドレス（`192.0.2.1:8080`や`[2001:db8::1]:8080`）と、角括弧で囲まれた
+/// IPv6アドレスも解析する。
+fn parse_forwarded_address(value: &str) -> Option<IpAddr> {
+    value
+        .parse::<IpAddr>()
+        .ok()
+        .or_else(|| value.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+        .or_else(|| {
+            value
+                .strip_prefix('[')
+                .and_then(|value| value.strip_suffix(']'))
+                .and_then(|value| value.parse().ok())
+        })
+        .map(|ip: IpAddr| ip.to_canonical())
+}
+
+/// リクエストURIの`{user_id}`パスから、ユーザーIDを取得する。
+fn path_user_id(request: &HttpRequest) -> ProcessRequestResult<UserId> {
+    // リクエストURIからユーザーIDを文字列で取得
+    let user_id = request.match_info().get("user_id").ok_or_else(|| {
+        ProcessRequestError::without_error_code(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            MessageKey::UserIdPathIsRequired,
+        )
+    })?;
+    // 文字列で表現されたユーザーIDを変換
+    let user_id = Uuid::from_str(user_id).map_err(|_| {
+        ProcessRequestError::without_error_code(
+            StatusCode::BAD_REQUEST,
+            MessageKey::InvalidUserIdPath,
+        )
+    })?;
+
+    Ok(UserId::new(user_id))
+}
+
 pub fn forbidden_error() -> ProcessRequestError {
-    ProcessRequestError::without_error_code(StatusCode::FORBIDDEN, "アクセスする権限がありません。")
+    ProcessRequestError::without_error_code(StatusCode::FORBIDDEN, MessageKey::Forbidden)
 }
 pub fn forbidden_actix_error() -> actix_web::Error {
     actix_web::Error::from(forbidden_error())
@@ -119,61 +456,130 @@ async fn retrieve_token_content(request: &HttpRequest) -> actix_web::Result<Toke
     }
     let token = token.unwrap();
 
-    // Redisからアクセストークンに紐付いたトークンの内容を取得
-    let content = token_content_from_redis(request, &token).await?;
+    // アクセストークンに紐付いたトークンの内容を取得
+    let content = resolve_token_content(request, &token).await?;
     if content.is_none() {
         return Err(forbidden_actix_error());
     }
+
+    Ok(content.unwrap())
+}
+
+/// アクセストークンに紐付いたトークンの内容を取得する。
+///
+/// # 引数
+///
+/// * `request` - HTTPリクエスト
+/// * `token` - アクセストークン
+///
+/// # 戻り値
+///
+/// トークンの内容、アクセストークンが無効または有効期限が切れている場合は`None`
+///
+/// JWTのクレイムに記録されたトークンの種類がアクセストークンでない場合は、Redisに問い合わせずに
+/// 拒否する。トークンが無効にされていないかは、Redisに記録されたトークンの内容で判断する。
+async fn resolve_token_content(
+    request: &HttpRequest,
+    token: &SecretString,
+) -> actix_web::Result<Option<TokenContent>> {
+    // 同じリクエストで取得済みの場合は、Redisに問い合わせずにトークンの内容を返す
+    let cached = request.extensions().get::<TokenContent>().copied();
+    if cached.is_some() {
+        return Ok(cached);
+    }
+
+    // アクセストークンの署名を検証して、クレイムを取得
+    let context = context_from_request(request)?;
+    let claim = retrieve_claim_from_token(token, &context.jwt_keys);
+    if claim.is_err() {
+        return Ok(None);
+    }
+    let claim = claim.unwrap();
+    // クレイムに記録されたトークンの種類が、アクセストークンであるか確認
+    if claim
+        .token_type
+        .is_some_and(|token_type| token_type != TokenType::Access)
+    {
+        return Err(refresh_token_sent_error());
+    }
+
+    // トークンリポジトリからアクセストークンに紐付いたトークンの内容を取得
+    let content = token_content_from_repository(context, token).await?;
+    if content.is_none() {
+        return Ok(None);
+    }
     let content = content.unwrap();
 
     // アクセストークンが、本当にアクセストークンであるか確認
+    // トークンの種類をクレイムに記録する前に発行したトークンは、Redisに記録されたトークンの種類で確認
     if content.token_type != TokenType::Access {
-        return Err(ProcessRequestError::without_error_code(
-            StatusCode::BAD_REQUEST,
-            "リフレッシュトークンが送信されました。",
-        )
-        .into());
+        return Err(refresh_token_sent_error());
+    }
+    // クレイムとRedisに記録されたユーザーID及びユーザー権限コードが一致するか確認
+    // ユーザー権限を変更するとトークンを無効にするため、一致しない場合はトークンが改ざんされているか、
+    // 古いトークンであると判断
+    if content.user_id != claim.user_id
+        || claim
+            .user_permission_code
+            .is_some_and(|code| code != content.user_permission_code)
+    {
+        return Ok(None);
     }
 
-    Ok(content)
+    // アクセスログに記録するため、リクエストの拡張にユーザーIDを保存
+    // また、同じリクエストで複数の抽出器がRedisに問い合わせないように、トークンの内容を保存
+    request.extensions_mut().insert(content.user_id);
+    request.extensions_mut().insert(content);
+
+    Ok(Some(content))
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, thiserror::Error)]
-enum ParseError {
-    #[error("Authorizationヘッダの内容が誤っています。")]
-    Invalid,
-    #[error("Authorizationヘッダの内容が`Bearer`から始まっていません。")]
-    MissingSchema,
-    #[error("Authorizationヘッダの内容にアクセストークンが含まれていません。")]
-    MissingToken,
+fn refresh_token_sent_error() -> actix_web::Error {
+    ProcessRequestError::without_error_code(StatusCode::BAD_REQUEST, MessageKey::RefreshTokenSent)
+        .into()
 }
 
 // リクエストヘッダからアクセストークンを取得する。
+//
+// JWTの形式でないアクセストークンは、署名の検証やトークンリポジトリへの問い合わせをせずに、
+// 400 Bad Requestで拒否する。
 fn retrieve_access_token(request: &HttpRequest) -> ProcessRequestResult<Option<SecretString>> {
     // クッキーからアクセストークンを取得
-    let token = access_token_from_cookie(request);
-    if token.is_some() {
-        return Ok(token);
+    let token = match access_token_from_cookie(request) {
+        Some(token) => Some(token),
+        // `Authorization`ヘッダからアクセストークンを取得
+        None => access_token_from_auth_header(request)
+            .map_err(|key| ProcessRequestError::without_error_code(StatusCode::BAD_REQUEST, key))?,
+    };
+    if token.as_ref().is_some_and(|token| !looks_like_jwt(token)) {
+        return Err(ProcessRequestError::without_error_code(
+            StatusCode::BAD_REQUEST,
+            MessageKey::MalformedToken,
+        ));
     }
-    // `Authorization`ヘッダからアクセストークンを取得
-    let token = access_token_from_auth_header(request).map_err(|e| {
-        ProcessRequestError::without_error_code(StatusCode::BAD_REQUEST, format!("{}", e))
-    })?;
 
     Ok(token)
 }
 
 /// クッキーからアクセストークンを取得する。
+///
+/// クッキーの名前には、HTTPサーバー設定のクッキーの名前のプレフィックスを付与する。
 fn access_token_from_cookie(request: &HttpRequest) -> Option<SecretString> {
+    let name = request
+        .app_data::<web::Data<RequestContext>>()
+        .map(|context| context.access_token_cookie_name())
+        .unwrap_or_else(|| String::from(ACCESS_TOKEN_KEY));
     request
-        .cookie(ACCESS_TOKEN_KEY)
+        .cookie(&name)
         .map(|c| SecretString::new(c.value().to_string()))
 }
 
 /// リクエストの`Authorization`ヘッダーからアクセストークンを取得する。
+///
+/// ヘッダーの内容が誤っている場合は、エラーメッセージのキーを返す。
 fn access_token_from_auth_header(
     request: &HttpRequest,
-) -> Result<Option<SecretString>, ParseError> {
+) -> Result<Option<SecretString>, MessageKey> {
     let header_value = request.headers().get(header::AUTHORIZATION);
     if header_value.is_none() {
         return Ok(None);
@@ -182,50 +588,625 @@ fn access_token_from_auth_header(
     // "Bearer *"
     //  12345678
     if header_value.len() < 8 {
-        return Err(ParseError::Invalid);
+        return Err(MessageKey::InvalidAuthorizationHeader);
     }
     let mut parts = header_value
         .to_str()
-        .map_err(|_| ParseError::Invalid)?
+        .map_err(|_| MessageKey::InvalidAuthorizationHeader)?
         .splitn(2, ' ');
     if parts.next() != Some("Bearer") {
-        return Err(ParseError::MissingSchema);
+        return Err(MessageKey::AuthorizationSchemeIsNotBearer);
     }
-    let token = parts.next().ok_or(ParseError::MissingToken)?;
+    let token = parts.next().ok_or(MessageKey::AccessTokenIsMissing)?;
 
     Ok(Some(SecretString::new(token.to_string())))
 }
 
-// Redisからアクセストークンに紐付いたトークンの内容を取得する。
-async fn token_content_from_redis(
-    request: &HttpRequest,
-    token: &SecretString,
-) -> ProcessRequestResult<Option<TokenContent>> {
-    let context = request
+/// HTTPリクエストからリクエストコンテキストを取得する。
+fn context_from_request(request: &HttpRequest) -> ProcessRequestResult<&web::Data<RequestContext>> {
+    request
         .app_data::<web::Data<RequestContext>>()
         .ok_or_else(|| {
             tracing::error!(
-                "can not retrieve the pool of redis ({}:{})",
+                "can not retrieve the request context ({}:{})",
                 file!(),
                 line!()
             );
-            ProcessRequestError {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body: ErrorResponseBody {
-                    error_code: None,
-                    message: "Redis接続プールを取得できませんでした。".into(),
-                },
-            }
-        })?;
-    let repo = RedisTokenRepository::new(context.redis_pool.clone());
-    repo.retrieve_token_content(token).await.map_err(move |e| {
-        tracing::error!("{} ({}:{})", e, file!(), line!());
-        ProcessRequestError {
-            status_code: StatusCode::INTERNAL_SERVER_ERROR,
-            body: ErrorResponseBody {
-                error_code: None,
-                message: "Redis接続プールを取得できませんでした。".into(),
-            },
-        }
-    })
+            ProcessRequestError::without_error_code(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                MessageKey::RequestContextIsUnavailable,
+            )
+        })
+}
+
+/// リクエストコンテキストのトークンリポジトリから、アクセストークンに紐付いたトークンの内容を取得する。
+async fn token_content_from_repository(
+    context: &RequestContext,
+    token: &SecretString,
+) -> ProcessRequestResult<Option<TokenContent>> {
+    context
+        .token_repository()
+        .retrieve_token_content(token)
+        .await
+        .map_err(move |e| {
+            tracing::error!("{} ({}:{})", e, file!(), line!());
+            ProcessRequestError::without_error_code(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                MessageKey::TokenContentIsUnavailable,
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use actix_web::test::TestRequest;
+    use async_trait::async_trait;
+    use time::{Duration, OffsetDateTime};
+
+    use domain::models::user::UserId;
+    use domain::repositories::memory::token::InMemoryTokenRepository;
+    use domain::repositories::token::{ActiveSession, PendingEmailChange, TokenPairWithTtl};
+    use domain::DomainResult;
+    use use_cases::jwt::{generate_token_pair, TokenPair};
+    use use_cases::messages::UseCaseMessage;
+
+    use super::*;
+    use crate::tests::{request_context, request_context_with_trusted_proxies};
+
+    /// トークンの内容を取得した回数を数えるトークンリポジトリ
+    #[derive(Debug)]
+    struct CountingTokenRepository {
+        content: TokenContent,
+        retrieved: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl TokenRepository for CountingTokenRepository {
+        async fn register_token_pair<'a>(
+            &self,
+            _user_id: UserId,
+            _tokens: TokenPairWithTtl<'a>,
+            _user_permission_code: UserPermissionCode,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn retrieve_token_content(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<TokenContent>> {
+            self.retrieved.fetch_add(1, Ordering::SeqCst);
+            Ok(Some(self.content))
+        }
+
+        async fn register_email_verification_token(
+            &self,
+            _user_id: UserId,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_email_verification_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn register_email_change_token(
+            &self,
+            _email_change: &PendingEmailChange,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_email_change_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<PendingEmailChange>> {
+            unimplemented!()
+        }
+
+        async fn register_mfa_pending_token(
+            &self,
+            _user_id: UserId,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_mfa_pending_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn revoke_mfa_pending_tokens(&self, _user_id: UserId) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn register_password_change_token(
+            &self,
+            _user_id: UserId,
+            _token: &SecretString,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn consume_password_change_token(
+            &self,
+            _token: &SecretString,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn register_reauthentication(
+            &self,
+            _user_id: UserId,
+            _access_token: &AccessToken,
+            _ttl: u64,
+        ) -> DomainResult<()> {
+            unimplemented!()
+        }
+
+        async fn reauthenticated_user(
+            &self,
+            _access_token: &AccessToken,
+        ) -> DomainResult<Option<UserId>> {
+            unimplemented!()
+        }
+
+        async fn user_sessions(
+            &self,
+            _user_id: UserId,
+            _current_access_token: &AccessToken,
+        ) -> DomainResult<Vec<ActiveSession>> {
+            unimplemented!()
+        }
+
+        async fn revoke_session(&self, _user_id: UserId, _session_id: &str) -> DomainResult<bool> {
+            unimplemented!()
+        }
+
+        async fn revoke_user_tokens(&self, _user_id: UserId) -> DomainResult<()> {
+            unimplemented!()
+        }
+    }
+
+    /// テスト用のリクエストコンテキストの秘密鍵で、アクセストークンとリフレッシュトークンを生成する。
+    fn token_pair(user_id: UserId, user_permission_code: UserPermissionCode) -> TokenPair {
+        let context = request_context();
+        let expiration = OffsetDateTime::now_utc() + Duration::seconds(60);
+
+        generate_token_pair(
+            user_id,
+            user_permission_code,
+            expiration,
+            expiration,
+            &context.jwt_keys,
+        )
+        .unwrap()
+    }
+
+    /// `Authorization`ヘッダに設定する値を生成する。
+    fn bearer(token: &str) -> String {
+        format!("Bearer {}", token)
+    }
+
+    /// 1つのリクエストで複数の抽出器を使用しても、トークンの内容を1回だけ取得することを確認
+    #[actix_web::test]
+    async fn token_content_is_retrieved_once_per_request() {
+        let user_id = UserId::default();
+        let tokens = token_pair(user_id, UserPermissionCode::Admin);
+        let repo = Arc::new(CountingTokenRepository {
+            content: token_content(user_id, UserPermissionCode::Admin),
+            retrieved: AtomicUsize::new(0),
+        });
+        let context = request_context().with_token_repository(repo.clone());
+        let (request, mut payload) = TestRequest::default()
+            .app_data(web::Data::new(context))
+            .insert_header((header::AUTHORIZATION, bearer(tokens.access.expose())))
+            .to_http_parts();
+
+        let user = UserContext::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+        let admin = AdminContext::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(user.0.user_id, admin.user_id);
+        assert_eq!(1, repo.retrieved.load(Ordering::SeqCst));
+    }
+
+    /// アクセストークンとリフレッシュトークンを登録したメモリトークンリポジトリを設定した、
+    /// リクエストコンテキストを構築する。
+    async fn request_context_with_tokens(
+        user_id: UserId,
+        tokens: &TokenPair,
+        user_permission_code: UserPermissionCode,
+    ) -> RequestContext {
+        let repo = InMemoryTokenRepository::new();
+        let token_with_ttls = TokenPairWithTtl {
+            access: &tokens.access,
+            access_ttl: 60,
+            refresh: &tokens.refresh,
+            refresh_ttl: 60,
+            device: None,
+        };
+        repo.register_token_pair(user_id, token_with_ttls, user_permission_code)
+            .await
+            .unwrap();
+
+        request_context().with_token_repository(Arc::new(repo))
+    }
+
+    /// 登録されたアクセストークンの場合は、認証済みユーザーのコンテキストを取得できることを確認
+    #[actix_web::test]
+    async fn user_context_can_be_extracted_with_registered_access_token() {
+        let user_id = UserId::default();
+        let tokens = token_pair(user_id, UserPermissionCode::General);
+        let context =
+            request_context_with_tokens(user_id, &tokens, UserPermissionCode::General).await;
+        let (request, mut payload) = TestRequest::default()
+            .app_data(web::Data::new(context))
+            .insert_header((header::AUTHORIZATION, bearer(tokens.access.expose())))
+            .to_http_parts();
+
+        let user = UserContext::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(user_id, user.0.user_id);
+        assert_eq!(Some(user_id), request.extensions().get::<UserId>().copied());
+    }
+
+    /// 登録されていないアクセストークンの場合は403、リフレッシュトークンまたはJWTの形式でない
+    /// トークンの場合は400を返すことを確認
+    #[actix_web::test]
+    async fn user_context_rejects_unregistered_or_refresh_token() {
+        let user_id = UserId::default();
+        let tokens = token_pair(user_id, UserPermissionCode::General);
+        let context = web::Data::new(
+            request_context_with_tokens(user_id, &tokens, UserPermissionCode::General).await,
+        );
+        let unregistered = token_pair(UserId::default(), UserPermissionCode::General);
+        for (token, expected) in [
+            (bearer(unregistered.access.expose()), StatusCode::FORBIDDEN),
+            (
+                String::from("Bearer unknown-token"),
+                StatusCode::BAD_REQUEST,
+            ),
+            (bearer(tokens.refresh.expose()), StatusCode::BAD_REQUEST),
+        ] {
+            let (request, mut payload) = TestRequest::default()
+                .app_data(context.clone())
+                .insert_header((header::AUTHORIZATION, token))
+                .to_http_parts();
+
+            let result = UserContext::from_request(&request, &mut payload).await;
+
+            let e = result.err().unwrap();
+            assert_eq!(expected, e.as_response_error().status_code());
+        }
+    }
+
+    /// クレイムにリフレッシュトークンと記録されたトークンは、トークンの内容を取得せずに400を返すことを確認
+    #[actix_web::test]
+    async fn refresh_token_claim_is_rejected_before_retrieving_token_content() {
+        let user_id = UserId::default();
+        let tokens = token_pair(user_id, UserPermissionCode::General);
+        let repo = Arc::new(CountingTokenRepository {
+            content: token_content(user_id, UserPermissionCode::General),
+            retrieved: AtomicUsize::new(0),
+        });
+        let context = request_context().with_token_repository(repo.clone());
+        let (request, mut payload) = TestRequest::default()
+            .app_data(web::Data::new(context))
+            .insert_header((header::AUTHORIZATION, bearer(tokens.refresh.expose())))
+            .to_http_parts();
+
+        let result = UserContext::from_request(&request, &mut payload).await;
+
+        let e = result.err().unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, e.as_response_error().status_code());
+        assert_eq!(
+            UseCaseMessage::from(MessageKey::RefreshTokenSent),
+            e.as_error::<ProcessRequestError>().unwrap().message
+        );
+        assert_eq!(0, repo.retrieved.load(Ordering::SeqCst));
+    }
+
+    /// JWTの形式でないトークンは、クッキーで送信された場合も、トークンの内容を取得せずに400を
+    /// 返すことを確認
+    #[actix_web::test]
+    async fn malformed_token_is_rejected_before_retrieving_token_content() {
+        let user_id = UserId::default();
+        let repo = Arc::new(CountingTokenRepository {
+            content: token_content(user_id, UserPermissionCode::General),
+            retrieved: AtomicUsize::new(0),
+        });
+        let context = web::Data::new(request_context().with_token_repository(repo.clone()));
+        let cookie_name = context.access_token_cookie_name();
+        for request in [
+            TestRequest::default().insert_header((header::AUTHORIZATION, "Bearer a.b")),
+            TestRequest::default().insert_header((header::AUTHORIZATION, "Bearer a.b.c d")),
+            TestRequest::default().cookie(actix_web::cookie::Cookie::new(
+                cookie_name.clone(),
+                "not-a-jwt",
+            )),
+        ] {
+            let (request, mut payload) = request.app_data(context.clone()).to_http_parts();
+
+            let result = UserContext::from_request(&request, &mut payload).await;
+
+            let e = result.err().unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, e.as_response_error().status_code());
+        }
+        assert_eq!(0, repo.retrieved.load(Ordering::SeqCst));
+    }
+
+    /// `Authorization`ヘッダの内容が誤っている場合は、メッセージカタログのメッセージで400を返すことを確認
+    #[actix_web::test]
+    async fn invalid_authorization_header_is_rejected_with_catalog_message() {
+        let context = web::Data::new(request_context());
+        let cases = [
+            ("Bearer", MessageKey::InvalidAuthorizationHeader),
+            (
+                "Basic dXNlcjpwYXNz",
+                MessageKey::AuthorizationSchemeIsNotBearer,
+            ),
+        ];
+        for (value, expected) in cases {
+            let (request, mut payload) = TestRequest::default()
+                .app_data(context.clone())
+                .insert_header((header::AUTHORIZATION, value))
+                .to_http_parts();
+
+            let result = UserContext::from_request(&request, &mut payload).await;
+
+            let e = result.err().unwrap();
+            assert_eq!(StatusCode::BAD_REQUEST, e.as_response_error().status_code());
+            assert_eq!(
+                UseCaseMessage::from(expected),
+                e.as_error::<ProcessRequestError>().unwrap().message,
+                "{}",
+                value
+            );
+        }
+    }
+
+    /// クレイムとトークンの内容のユーザー権限コードが一致しない場合は、403を返すことを確認
+    #[actix_web::test]
+    async fn user_context_rejects_token_with_mismatched_permission() {
+        let user_id = UserId::default();
+        // 一般ユーザーとして発行したアクセストークンを、管理者として登録
+        let tokens = token_pair(user_id, UserPermissionCode::General);
+        let context =
+            request_context_with_tokens(user_id, &tokens, UserPermissionCode::Admin).await;
+        let (request, mut payload) = TestRequest::default()
+            .app_data(web::Data::new(context))
+            .insert_header((header::AUTHORIZATION, bearer(tokens.access.expose())))
+            .to_http_parts();
+
+        let result = UserContext::from_request(&request, &mut payload).await;
+
+        let e = result.err().unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, e.as_response_error().status_code());
+    }
+
+    /// 有効なアクセストークンの場合は認証済み、無効なアクセストークンの場合は401を返すことを確認
+    #[actix_web::test]
+    async fn optional_user_context_distinguishes_valid_and_invalid_token() {
+        let user_id = UserId::default();
+        let tokens = token_pair(user_id, UserPermissionCode::General);
+        let context = web::Data::new(
+            request_context_with_tokens(user_id, &tokens, UserPermissionCode::General).await,
+        );
+
+        let (request, mut payload) = TestRequest::default()
+            .app_data(context.clone())
+            .insert_header((header::AUTHORIZATION, bearer(tokens.access.expose())))
+            .to_http_parts();
+        let valid = OptionalUserContext::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+        assert_eq!(Some(user_id), valid.0.map(|content| content.user_id));
+
+        let unregistered = token_pair(UserId::default(), UserPermissionCode::General);
+        let (request, mut payload) = TestRequest::default()
+            .app_data(context)
+            .insert_header((header::AUTHORIZATION, bearer(unregistered.access.expose())))
+            .to_http_parts();
+        let result = OptionalUserContext::from_request(&request, &mut payload).await;
+        let e = result.err().unwrap();
+        assert_eq!(
+            StatusCode::UNAUTHORIZED,
+            e.as_response_error().status_code()
+        );
+    }
+
+    fn token_content(user_id: UserId, user_permission_code: UserPermissionCode) -> TokenContent {
+        TokenContent {
+            user_id,
+            token_type: TokenType::Access,
+            user_permission_code,
+        }
+    }
+
+    /// アクセストークンが送信されていない場合は、匿名のユーザーとして扱うことを確認
+    #[actix_web::test]
+    async fn optional_user_context_is_none_without_access_token() {
+        let (request, mut payload) = TestRequest::default().to_http_parts();
+
+        let context = OptionalUserContext::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+
+        assert!(context.0.is_none());
+    }
+
+    /// `Authorization`ヘッダの内容が誤っている場合は、匿名のユーザーとして扱わないことを確認
+    #[actix_web::test]
+    async fn optional_user_context_rejects_malformed_authorization_header() {
+        let (request, mut payload) = TestRequest::default()
+            .insert_header((header::AUTHORIZATION, "Basic dXNlcjpwYXNz"))
+            .to_http_parts();
+
+        let result = OptionalUserContext::from_request(&request, &mut payload).await;
+
+        let e = result.err().unwrap();
+        assert_eq!(StatusCode::BAD_REQUEST, e.as_response_error().status_code());
+    }
+
+    /// 管理者は、自分自身にも他のユーザーにもアクセスできることを確認
+    #[test]
+    fn admin_can_access_own_and_other_user() {
+        let admin_id = UserId::default();
+        let other_id = UserId::default();
+        let content = token_content(admin_id, UserPermissionCode::Admin);
+
+        let own = AdminOrOwnerContext::authorize(&content, admin_id).unwrap();
+        assert_eq!(admin_id, own.acting_user_id);
+        assert_eq!(admin_id, own.target_user_id);
+        assert!(own.is_admin);
+        assert!(own.is_owner());
+
+        let other = AdminOrOwnerContext::authorize(&content, other_id).unwrap();
+        assert_eq!(admin_id, other.acting_user_id);
+        assert_eq!(other_id, other.target_user_id);
+        assert!(other.is_admin);
+        assert!(!other.is_owner());
+    }
+
+    /// 一般ユーザーは、自分自身にアクセスできるが、他のユーザーにはアクセスできないことを確認
+    #[test]
+    fn general_user_can_access_only_own_user() {
+        let user_id = UserId::default();
+        let content = token_content(user_id, UserPermissionCode::General);
+
+        let own = AdminOrOwnerContext::authorize(&content, user_id).unwrap();
+        assert_eq!(user_id, own.acting_user_id);
+        assert_eq!(user_id, own.target_user_id);
+        assert!(!own.is_admin);
+        assert!(own.is_owner());
+
+        let e = AdminOrOwnerContext::authorize(&content, UserId::default())
+            .err()
+            .unwrap();
+        assert_eq!(StatusCode::FORBIDDEN, e.status_code);
+    }
+
+    /// IPアドレスの範囲のリストを解析する。
+    fn ip_ranges(ranges: &[&str]) -> Vec<IpNet> {
+        ranges.iter().map(|range| range.parse().unwrap()).collect()
+    }
+
+    /// `X-Forwarded-For`ヘッダを指定したリクエストヘッダを構築する。
+    fn forwarded_for(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_FORWARDED_FOR, value.parse().unwrap());
+
+        headers
+    }
+
+    /// 接続元が信頼するプロキシでない場合は、偽装された`X-Forwarded-For`ヘッダを無視することを確認
+    #[test]
+    fn spoofed_forwarded_for_from_untrusted_peer_is_ignored() {
+        let trusted_proxies = ip_ranges(&["127.0.0.1/32"]);
+        let headers = forwarded_for("10.1.2.3");
+
+        let client_ip = resolve_client_ip(
+            Some("203.0.113.5".parse().unwrap()),
+            &headers,
+            &trusted_proxies,
+        );
+        assert_eq!(Some("203.0.113.5".parse().unwrap()), client_ip);
+
+        // 信頼するプロキシがない場合も無視
+        let client_ip = resolve_client_ip(Some("127.0.0.1".parse().unwrap()), &headers, &[]);
+        assert_eq!(Some("127.0.0.1".parse().unwrap()), client_ip);
+    }
+
+    /// 複数のプロキシを経由した場合は、信頼するプロキシを末尾から読み飛ばして、アクセス元を判定することを確認
+    #[test]
+    fn client_ip_is_resolved_from_multi_hop_chain() {
+        let trusted_proxies = ip_ranges(&["127.0.0.1/32", "10.0.0.0/8"]);
+        let peer_ip = Some("127.0.0.1".parse().unwrap());
+
+        // クライアントが先頭に偽装したIPアドレスは、信頼するプロキシ以外の最初のIPアドレスより前のため無視
+        let headers = forwarded_for("192.0.2.99, 198.51.100.7, 10.0.0.2");
+        let client_ip = resolve_client_ip(peer_ip, &headers, &trusted_proxies);
+        assert_eq!(Some("198.51.100.7".parse().unwrap()), client_ip);
+
+        // 複数の`X-Forwarded-For`ヘッダは、順に連結して扱う
+        let mut headers = HeaderMap::new();
+        headers.append(X_FORWARDED_FOR, "198.51.100.7".parse().unwrap());
+        headers.append(X_FORWARDED_FOR, "10.0.0.3, 10.0.0.2".parse().unwrap());
+        let client_ip = resolve_client_ip(peer_ip, &headers, &trusted_proxies);
+        assert_eq!(Some("198.51.100.7".parse().unwrap()), client_ip);
+
+        // すべて信頼するプロキシの場合は、先頭のIPアドレス
+        let headers = forwarded_for("10.0.0.4, 10.0.0.2");
+        let client_ip = resolve_client_ip(peer_ip, &headers, &trusted_proxies);
+        assert_eq!(Some("10.0.0.4".parse().unwrap()), client_ip);
+
+        // 解析できないIPアドレスより前はたどらない
+        let headers = forwarded_for("198.51.100.7, unknown, 10.0.0.2");
+        let client_ip = resolve_client_ip(peer_ip, &headers, &trusted_proxies);
+        assert_eq!(Some("10.0.0.2".parse().unwrap()), client_ip);
+    }
+
+    /// `X-Forwarded-For`ヘッダがない場合は、`Forwarded`ヘッダからアクセス元を判定することを確認
+    #[test]
+    fn client_ip_is_resolved_from_forwarded_header() {
+        let trusted_proxies = ip_ranges(&["127.0.0.1/32"]);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::FORWARDED,
+            r#"for=192.0.2.60;proto=http, for="[2001:db8:cafe::17]:4711";by=127.0.0.1"#
+                .parse()
+                .unwrap(),
+        );
+
+        let client_ip = resolve_client_ip(
+            Some("127.0.0.1".parse().unwrap()),
+            &headers,
+            &trusted_proxies,
+        );
+
+        assert_eq!(Some("2001:db8:cafe::17".parse().unwrap()), client_ip);
+    }
+
+    /// 抽出器が、リクエストコンテキストの信頼するプロキシに従ってアクセス元を判定して、
+    /// リクエストの拡張に記録することを確認
+    #[actix_web::test]
+    async fn client_ip_can_be_extracted() {
+        let context = request_context_with_trusted_proxies(&["127.0.0.1"]);
+        let (request, mut payload) = TestRequest::default()
+            .app_data(web::Data::new(context))
+            .peer_addr("127.0.0.1:50000".parse().unwrap())
+            .insert_header((X_FORWARDED_FOR, "198.51.100.7"))
+            .to_http_parts();
+
+        let client_ip = ClientIp::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(ClientIp("198.51.100.7".parse().unwrap()), client_ip);
+        assert_eq!(
+            Some(client_ip),
+            request.extensions().get::<ClientIp>().copied()
+        );
+    }
 }
@@ -0,0 +1,227 @@
+use std::future::Future;
+
+use actix_web::body::to_bytes;
+use actix_web::http::header::HeaderName;
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse};
+use sha2::{Digest, Sha256};
+
+use domain::repositories::idempotency::{
+    IdempotencyRecord, IdempotencyRepository, IdempotentResponse,
+};
+use use_cases::messages::MessageKey;
+
+use crate::routes::{ProcessRequestError, ProcessRequestResult};
+use crate::RequestContext;
+
+/// クライアントがリクエストに付与する冪等キーのヘッダ
+pub const IDEMPOTENCY_KEY: HeaderName = HeaderName::from_static("idempotency-key");
+
+/// 記録したレスポンスを返したことを示すヘッダ
+pub const IDEMPOTENT_REPLAYED: HeaderName = HeaderName::from_static("idempotent-replayed");
+
+/// `Idempotency-Key`ヘッダに指定できる最大文字数
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 255;
+
+/// `Idempotency-Key`ヘッダを指定したリクエストを、冪等に処理する。
+///
+/// `Idempotency-Key`ヘッダを指定していない場合は、リクエストをそのまま処理する。
+/// 指定した場合は、冪等キーを予約してからリクエストを処理して、成功したレスポンスを記録する。
+/// 同じ冪等キーで再送信されたリクエストには、リクエストを処理せずに記録したレスポンスを返す。
+///
+/// * 同じ冪等キーで異なるリクエストボディを送信した場合は、422 Unprocessable Entityを返す。
+/// * 同じ冪等キーのリクエストを処理している場合は、409 Conflictを返す。
+/// * リクエストの処理に失敗した場合は、レスポンスを記録せずに冪等キーを解放して、
+///   同じ冪等キーで再送信できるようにする。
+///
+/// # 引数
+///
+/// * `context` - リクエストコンテキスト
+/// * `request` - HTTPリクエスト
+/// * `scope` - 冪等キーを区別するエンドポイントの名前
+/// * `body` - リクエストボディ
+/// * `handler` - リクエストを処理する関数
+///
+/// # 戻り値
+///
+/// HTTPレスポンス
+pub async fn idempotent<F, Fut>(
+    context: &RequestContext,
+    request: &HttpRequest,
+    scope: &str,
+    body: &serde_json::Value,
+    handler: F,
+) -> ProcessRequestResult<HttpResponse>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = ProcessRequestResult<HttpResponse>>,
+{
+    let Some(key) = idempotency_key(request)? else {
+        return handler().await;
+    };
+    let key = format!("{}:{}", scope, key);
+    let request_hash = request_hash(body);
+    let repository = context.idempotency_repository();
+    let ttl = context.http_server_settings.idempotency_key_seconds;
+    match repository.reserve(&key, &request_hash, ttl).await? {
+        None => {}
+        Some(record) if record.request_hash() != request_hash => {
+            return Err(ProcessRequestError::without_error_code(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                MessageKey::IdempotencyKeyIsReused,
+            ));
+        }
+        Some(IdempotencyRecord::Processing { .. }) => {
+            return Err(ProcessRequestError::without_error_code(
+                StatusCode::CONFLICT,
+                MessageKey::IdempotentRequestIsInProgress,
+            ));
+        }
+        Some(IdempotencyRecord::Completed { response, .. }) => {
+            let mut res = json_response(&response);
+            res.headers_mut()
+                .insert(IDEMPOTENT_REPLAYED, "true".parse().unwrap());
+            return Ok(res);
+        }
+    }
+
+    let result = handler().await;
+    let res = match result {
+        Ok(res) if res.status().is_success() => res,
+        result => {
+            // 冪等キーを解放できなくても、記録は期限切れで削除されるため、処理した結果を返す
+            if let Err(e) = repository.release(&key).await {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+            }
+            return result;
+        }
+    };
+    let status_code = res.status().as_u16();
+    let body = to_bytes(res.into_body()).await.map_err(|e| {
+        tracing::error!("{} ({}:{})", e, file!(), line!());
+        ProcessRequestError::without_error_code(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            RESPONSE_BODY_ERROR,
+        )
+    })?;
+    let response = IdempotentResponse {
+        status_code,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    // レスポンスを記録できなくても、リクエストは処理済みのため、処理した結果を返す
+    if let Err(e) = repository
+        .complete(&key, &request_hash, &response, ttl)
+        .await
+    {
+        tracing::error!("{} ({}:{})", e, file!(), line!());
+    }
+
+    Ok(json_response(&response))
+}
+
+/// `Idempotency-Key`ヘッダから冪等キーを取得する。
+///
+/// # 引数
+///
+/// * `request` - HTTPリクエスト
+///
+/// # 戻り値
+///
+/// 冪等キー
+///
+/// `Idempotency-Key`ヘッダがない場合は`None`、空、255文字を超える、または表示可能なASCII文字
+/// 以外を含む場合は400 Bad Requestのリクエスト処理エラー
+fn idempotency_key(request: &HttpRequest) -> ProcessRequestResult<Option<&str>> {
+    let Some(value) = request.headers().get(IDEMPOTENCY_KEY) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .filter(|key| is_valid_idempotency_key(key))
+        .map(Some)
+        .ok_or_else(|| {
+            ProcessRequestError::without_error_code(
+                StatusCode::BAD_REQUEST,
+                MessageKey::InvalidIdempotencyKey,
+            )
+        })
+}
+
+/// 冪等キーが、1文字以上255文字以下の表示可能なASCII文字であるか確認する。
+fn is_valid_idempotency_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.len() <= MAX_IDEMPOTENCY_KEY_LENGTH
+        && key.chars().all(|c| c.is_ascii_graphic())
+}
+
+/// リクエストボディのハッシュ値を計算する。
+///
+/// JSONオブジェクトのキーは辞書順に並べてシリアライズされるため、キーの順番や空白のみが異なる
+/// リクエストボディは、同じハッシュ値になる。
+///
+/// # 引数
+///
+/// * `body` - リクエストボディ
+///
+/// # 戻り値
+///
+/// 16進数で表現したSHA-256ハッシュ値
+fn request_hash(body: &serde_json::Value) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(body.to_string().as_bytes());
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// 記録したレスポンスから、JSON形式のHTTPレスポンスを構築する。
+fn json_response(response: &IdempotentResponse) -> HttpResponse {
+    let status_code =
+        StatusCode::from_u16(response.status_code).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+
+    HttpResponse::build(status_code)
+        .content_type(mime::APPLICATION_JSON)
+        .body(response.body.clone())
+}
+
+const RESPONSE_BODY_ERROR: &str = "レスポンスボディを読み込むときにエラーが発生しました。";
+
+#[cfg(test)]
+mod tests {
+    use actix_web::test::TestRequest;
+
+    use super::*;
+
+    /// `Idempotency-Key`ヘッダを検証することを確認
+    #[test]
+    fn idempotency_key_is_validated() {
+        let request = TestRequest::default().to_http_request();
+        assert_eq!(None, idempotency_key(&request).unwrap());
+
+        let request = TestRequest::default()
+            .insert_header((IDEMPOTENCY_KEY, "3f6c1a9e-key"))
+            .to_http_request();
+        assert_eq!(Some("3f6c1a9e-key"), idempotency_key(&request).unwrap());
+
+        for invalid in [String::from("has space"), "a".repeat(256)] {
+            let request = TestRequest::default()
+                .insert_header((IDEMPOTENCY_KEY, invalid.as_str()))
+                .to_http_request();
+            let err = idempotency_key(&request).unwrap_err();
+            assert_eq!(StatusCode::BAD_REQUEST, err.status_code);
+        }
+    }
+
+    /// キーの順番と空白のみが異なるリクエストボディは、同じハッシュ値になることを確認
+    #[test]
+    fn request_hash_ignores_key_order_and_whitespace() -> anyhow::Result<()> {
+        let a: serde_json::Value = serde_json::from_str(r#"{"a": 1, "b": {"c": 2, "d": 3}}"#)?;
+        let b: serde_json::Value = serde_json::from_str(r#"{"b":{"d":3,"c":2},"a":1}"#)?;
+        let c: serde_json::Value = serde_json::from_str(r#"{"a": 1, "b": {"c": 2, "d": 4}}"#)?;
+
+        assert_eq!(request_hash(&a), request_hash(&b));
+        assert_ne!(request_hash(&a), request_hash(&c));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::HttpResponse;
+use ipnet::IpNet;
+
+use configurations::settings::HttpServerSettings;
+
+use crate::routes::extractors::{forbidden_error, ClientIp};
+
+/// アクセスを許可したIPアドレスの範囲外からのリクエストに、403 Forbiddenを返すミドルウェア
+///
+/// 管理者向けのエンドポイントを登録したスコープに適用する。
+/// アクセス元のIPアドレスは、信頼するリバースプロキシの設定に従って[`ClientIp`]で判定する。
+/// アクセスを許可するIPアドレスの範囲が空の場合は、アクセス元のIPアドレスを確認しない。
+#[derive(Debug, Clone, Default)]
+pub struct IpAllowlistGuard {
+    /// アクセスを許可するIPアドレスの範囲
+    ranges: Arc<Vec<IpNet>>,
+}
+
+impl IpAllowlistGuard {
+    /// HTTPサーバー設定から、アクセス元のIPアドレスを確認するミドルウェアを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `settings` - HTTPサーバー設定
+    ///
+    /// # 戻り値
+    ///
+    /// アクセス元のIPアドレスを確認するミドルウェア、アクセスを許可するIPアドレスの範囲が
+    /// CIDR表記として妥当ではない場合はエラー
+    pub fn new(settings: &HttpServerSettings) -> anyhow::Result<Self> {
+        Ok(Self {
+            ranges: Arc::new(settings.admin_ip_ranges()?),
+        })
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IpAllowlistGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = IpAllowlistGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(IpAllowlistGuardMiddleware {
+            service: Rc::new(service),
+            ranges: self.ranges.clone(),
+        }))
+    }
+}
+
+pub struct IpAllowlistGuardMiddleware<S> {
+    service: Rc<S>,
+    ranges: Arc<Vec<IpNet>>,
+}
+
+impl<S, B> Service<ServiceRequest> for IpAllowlistGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !self.ranges.is_empty() {
+            let allowed = ClientIp::resolve(req.request())
+                .map(|ClientIp(ip)| self.ranges.iter().any(|range| range.contains(&ip)))
+                .unwrap_or(false);
+            if !allowed {
+                let res = HttpResponse::from_error(forbidden_error());
+                let res = req.into_response(res).map_into_right_body();
+
+                return Box::pin(async move { Ok(res) });
+            }
+        }
+        let fut = self.service.call(req);
+
+        Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::body::to_bytes;
+    use actix_web::http::StatusCode;
+    use actix_web::{test, web, App};
+
+    use crate::routes::ErrorResponseBody;
+
+    use super::*;
+
+    /// アクセスを許可するIPアドレスの範囲を指定したミドルウェアを適用したスコープに、リクエストを送信する。
+    async fn call_guarded_scope(
+        ranges: &[&str],
+        peer_addr: &str,
+        forwarded_for: Option<&str>,
+    ) -> (StatusCode, String) {
+        let guard = IpAllowlistGuard {
+            ranges: Arc::new(ranges.iter().map(|range| range.parse().unwrap()).collect()),
+        };
+        let app = test::init_service(
+            App::new().service(
+                web::scope("/admin")
+                    .wrap(guard)
+                    .route("", web::get().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+        let mut req = test::TestRequest::get()
+            .uri("/admin")
+            .peer_addr(peer_addr.parse().unwrap());
+        if let Some(forwarded_for) = forwarded_for {
+            req = req.insert_header(("x-forwarded-for", forwarded_for));
+        }
+        let res = test::call_service(&app, req.to_request()).await;
+        let status_code = res.status();
+        let body = to_bytes(res.into_body()).await.unwrap();
+
+        (status_code, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    /// 範囲内のIPアドレスからのリクエストを処理することを確認
+    #[actix_web::test]
+    async fn request_from_allowed_address_is_processed() {
+        let (status_code, _) = call_guarded_scope(&["10.0.0.0/8"], "10.1.2.3:50000", None).await;
+        assert_eq!(StatusCode::OK, status_code);
+
+        let (status_code, _) =
+            call_guarded_scope(&["10.0.0.0/8"], "[::ffff:10.1.2.3]:50000", None).await;
+        assert_eq!(StatusCode::OK, status_code);
+    }
+
+    /// 範囲外のIPアドレスからのリクエストに、403 ForbiddenをJSONで返すことを確認
+    #[actix_web::test]
+    async fn request_from_denied_address_is_forbidden() {
+        let (status_code, body) =
+            call_guarded_scope(&["10.0.0.0/8"], "192.168.1.1:50000", None).await;
+        let body: ErrorResponseBody = serde_json::from_str(&body).unwrap();
+
+        assert_eq!(StatusCode::FORBIDDEN, status_code);
+        assert_eq!("アクセスする権限がありません。", body.message);
+    }
+
+    /// アクセスを許可するIPアドレスの範囲が空の場合は、アクセス元のIPアドレスを確認しないことを確認
+    #[actix_web::test]
+    async fn request_is_processed_when_allowlist_is_empty() {
+        let (status_code, _) = call_guarded_scope(&[], "192.168.1.1:50000", None).await;
+
+        assert_eq!(StatusCode::OK, status_code);
+    }
+
+    /// 信頼するプロキシを経由していないリクエストの`X-Forwarded-For`ヘッダで、範囲内のIPアドレスを
+    /// 偽装できないことを確認
+    #[actix_web::test]
+    async fn spoofed_forwarded_for_header_is_ignored() {
+        let (status_code, _) =
+            call_guarded_scope(&["10.0.0.0/8"], "192.0.2.1:50000", Some("10.1.2.3")).await;
+
+        assert_eq!(StatusCode::FORBIDDEN, status_code);
+    }
+}
@@ -0,0 +1,93 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header;
+
+use use_cases::messages::Language;
+
+use crate::routes::ProcessRequestError;
+
+/// エラーレスポンスのメッセージを、リクエストの`Accept-Language`ヘッダに応じた言語に変換するミドルウェア
+///
+/// メッセージカタログに登録されたメッセージを持つ`ProcessRequestError`のみを変換する。
+/// `Accept-Language`ヘッダが指定されていない場合や、サポートしていない言語の場合は、
+/// デフォルトの言語（日本語）のメッセージを返す。
+pub struct Localization;
+
+impl<S, B> Transform<S, ServiceRequest> for Localization
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = LocalizationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LocalizationMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct LocalizationMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for LocalizationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let language = request_language(&req);
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            if language == Language::default() {
+                return Ok(res.map_into_left_body());
+            }
+            let localized = res
+                .response()
+                .error()
+                .and_then(|e| e.as_error::<ProcessRequestError>())
+                .filter(|e| e.message.is_localizable())
+                .map(|e| e.localized_response(language));
+
+            match localized {
+                Some(mut localized) => {
+                    // `Retry-After`ヘッダなど、エラーレスポンスに設定されたヘッダを引き継ぐ
+                    for (name, value) in res.headers() {
+                        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+                            localized.headers_mut().append(name.clone(), value.clone());
+                        }
+                    }
+                    let (req, _) = res.into_parts();
+                    Ok(ServiceResponse::new(req, localized).map_into_right_body())
+                }
+                None => Ok(res.map_into_left_body()),
+            }
+        })
+    }
+}
+
+/// リクエストの`Accept-Language`ヘッダから、レスポンスに使用する言語を決定する。
+fn request_language(req: &ServiceRequest) -> Language {
+    req.headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(Language::from_accept_language)
+        .unwrap_or_default()
+}
@@ -0,0 +1,105 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, StatusCode};
+use actix_web::{web, HttpResponse};
+
+use use_cases::messages::MessageKey;
+
+use crate::routes::ProcessRequestError;
+use crate::RequestContext;
+
+/// メンテナンス中に、ヘルスチェックとメンテナンスの切り替え以外のリクエストに、
+/// 503 Service Unavailableを返すミドルウェア
+///
+/// レスポンスには、HTTPサーバー設定の`maintenance_retry_after_seconds`を`Retry-After`ヘッダに設定する。
+/// ロードバランサーがインスタンスを切り離さないように、ヘルスチェックは通常どおり処理する。
+pub struct Maintenance;
+
+impl<S, B> Transform<S, ServiceRequest> for Maintenance
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Transform = MaintenanceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let retry_after = req
+            .app_data::<web::Data<RequestContext>>()
+            .filter(|context| context.is_under_maintenance())
+            .filter(|context| !is_available_in_maintenance(context, req.path()))
+            .map(|context| context.http_server_settings.maintenance_retry_after_seconds);
+        if let Some(retry_after) = retry_after {
+            let error = ProcessRequestError::without_error_code(
+                StatusCode::SERVICE_UNAVAILABLE,
+                MessageKey::UnderMaintenance,
+            );
+            let mut res = HttpResponse::from_error(error);
+            res.headers_mut()
+                .insert(header::RETRY_AFTER, retry_after.into());
+            let res = req.into_response(res).map_into_right_body();
+
+            return Box::pin(async move { Ok(res) });
+        }
+        let fut = self.service.call(req);
+
+        Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+    }
+}
+
+/// メンテナンス中も処理するパスであるか確認する。
+///
+/// ヘルスチェック、レディネスチェック及びメンテナンスの切り替えは、メンテナンス中も処理する。
+///
+/// # 引数
+///
+/// * `context` - リクエストコンテキスト
+/// * `path` - リクエストのパス
+///
+/// # 戻り値
+///
+/// メンテナンス中も処理する場合は`true`、それ以外の場合は`false`
+fn is_available_in_maintenance(context: &RequestContext, path: &str) -> bool {
+    let Some(path) = path.strip_prefix(context.http_server_settings.api_prefix.as_str()) else {
+        return false;
+    };
+
+    path == HEALTH_CHECK_PATH
+        || path.starts_with(&format!("{}/", HEALTH_CHECK_PATH))
+        || path == MAINTENANCE_PATH
+}
+
+/// ヘルスチェックのパス
+const HEALTH_CHECK_PATH: &str = "/health-check";
+
+/// メンテナンスを切り替えるパス
+const MAINTENANCE_PATH: &str = "/admin/maintenance";
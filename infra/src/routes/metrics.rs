@@ -0,0 +1,117 @@
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::time::Instant;
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::StatusCode;
+use actix_web::{web, HttpRequest, HttpResponse};
+
+use crate::routes::extractors::{forbidden_error, ClientIp};
+use crate::routes::{ProcessRequestError, ProcessRequestResult};
+use crate::RequestContext;
+
+/// Prometheusのテキスト形式のコンテンツタイプ
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// ルートにマッチしなかったリクエストを記録するときのルート名
+const UNMATCHED_ROUTE: &str = "unmatched";
+
+/// HTTPリクエストのメトリクスを記録するミドルウェア
+///
+/// リクエストのHTTPメソッド、マッチしたルートのパターン及びレスポンスのステータスコードごとに、
+/// リクエスト数と処理時間を記録する。
+pub struct RequestMetrics;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestMetrics
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RequestMetricsMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestMetricsMiddleware {
+            service: Rc::new(service),
+        }))
+    }
+}
+
+pub struct RequestMetricsMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestMetricsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let metrics = req
+            .app_data::<web::Data<RequestContext>>()
+            .map(|context| context.metrics.clone());
+        let method = req.method().to_string();
+        let started_at = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            if let Some(metrics) = metrics {
+                let route = res
+                    .request()
+                    .match_pattern()
+                    .unwrap_or_else(|| String::from(UNMATCHED_ROUTE));
+                metrics.observe_http_request(
+                    &method,
+                    &route,
+                    res.status().as_u16(),
+                    started_at.elapsed().as_secs_f64(),
+                );
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// メトリクス
+///
+/// メトリクス設定でアクセスを許可されたIPアドレスからのリクエストにのみ、
+/// Prometheusのテキスト形式でメトリクスを返す。
+pub async fn metrics(
+    request: HttpRequest,
+    context: web::Data<RequestContext>,
+) -> ProcessRequestResult<HttpResponse> {
+    let settings = &context.http_server_settings.metrics;
+    let allowed = ClientIp::resolve(&request)
+        .map(|ClientIp(ip)| settings.is_allowed(ip))
+        .unwrap_or(false);
+    if !allowed {
+        return Err(forbidden_error());
+    }
+
+    context
+        .metrics
+        .observe_pools(&context.pg_pool, &context.redis_pool);
+    let body = context.metrics.render().map_err(|e| {
+        tracing::error!("{} ({}:{})", e, file!(), line!());
+        ProcessRequestError::without_error_code(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "メトリクスを出力できませんでした。",
+        )
+    })?;
+
+    Ok(HttpResponse::Ok()
+        .content_type(PROMETHEUS_CONTENT_TYPE)
+        .body(body))
+}
@@ -1,17 +1,40 @@
+pub mod access_log;
 pub mod accounts;
+pub mod admin;
+pub mod api_docs;
+pub mod etags;
 pub mod extractors;
+pub mod idempotency;
+pub mod ip_allowlist;
+pub mod localization;
+pub mod maintenance;
+pub mod metrics;
+pub mod telemetry;
 
+use std::time::Duration;
 use std::{borrow::Cow, str::FromStr as _};
 
+use actix_web::body::{to_bytes, BodySize, MessageBody};
 use actix_web::dev::ServiceResponse;
+use actix_web::error::JsonPayloadError;
 use actix_web::http::header::{self, HeaderMap, TryIntoHeaderValue as _};
-use actix_web::http::StatusCode;
+use actix_web::http::{Method, StatusCode};
 use actix_web::middleware::ErrorHandlerResponse;
-use actix_web::{HttpResponse, Responder, ResponseError};
+use actix_web::rt::time::timeout;
+use actix_web::web::Bytes;
+use actix_web::{web, HttpRequest, HttpResponse, Responder, ResponseError, Route};
+use deadpool_redis::Pool as RedisPool;
 use mime::Mime;
+use sqlx::PgPool;
+use utoipa::ToSchema;
 
+use configurations::settings::HttpServerSettings;
 use domain::DomainError;
-use use_cases::{UseCaseError, UseCaseErrorKind};
+use use_cases::messages::{Language, MessageKey, UseCaseMessage};
+use use_cases::{UseCaseError, UseCaseErrorCode, UseCaseErrorKind};
+
+use crate::repositories::redis::token::RedisTokenRepository;
+use crate::RequestContext;
 
 /// リクエスト処理結果
 pub type ProcessRequestResult<T> = Result<T, ProcessRequestError>;
@@ -20,6 +43,30 @@ pub type ProcessRequestResult<T> = Result<T, ProcessRequestError>;
 pub const ACCESS_TOKEN_KEY: &str = "access";
 pub const REFRESH_TOKEN_KEY: &str = "refresh";
 
+/// アクセス／リフレッシュトークンを保存するクッキーの名前を返す。
+///
+/// # 引数
+///
+/// * `settings` - HTTPサーバー設定
+/// * `key` - アクセス／リフレッシュトークンキー
+///
+/// # 戻り値
+///
+/// HTTPサーバー設定のクッキーの名前のプレフィックスを付与したクッキーの名前
+/// `__Host-`プレフィックスを付与する場合は、さらに`__Host-`を先頭に付与したクッキーの名前
+pub fn token_cookie_name(settings: &HttpServerSettings, key: &str) -> String {
+    match settings.use_host_prefix {
+        true => format!(
+            "{}{}{}",
+            HOST_COOKIE_PREFIX, settings.cookie_name_prefix, key
+        ),
+        false => format!("{}{}", settings.cookie_name_prefix, key),
+    }
+}
+
+/// `__Host-`クッキー・プレフィックス
+pub const HOST_COOKIE_PREFIX: &str = "__Host-";
+
 /// リクエスト処理エラー
 ///
 /// * ドメイン層で発生したエラーは、`DomainError` -> `ProcessRequestError`に変換する。
@@ -31,7 +78,13 @@ pub struct ProcessRequestError {
     /// HTTPステータスコード
     pub status_code: StatusCode,
     /// レスポンスボディ
+    ///
+    /// レスポンスボディのメッセージは、デフォルトの言語で解決したメッセージである。
     pub body: ErrorResponseBody,
+    /// 言語に応じて解決するメッセージ
+    pub message: UseCaseMessage,
+    /// 言語に応じて解決する、検証に失敗したフィールドのメッセージ
+    pub field_messages: Vec<FieldMessage>,
 }
 
 /// リクエスト処理エラーを、`actix-web`のエラーレスポンスとして扱えるように`ResponseError`を実装する。
@@ -71,34 +124,84 @@ impl ProcessRequestError {
     pub fn new(
         status_code: StatusCode,
         error_code: Option<u32>,
-        message: impl Into<Cow<'static, str>>,
+        message: impl Into<UseCaseMessage>,
     ) -> Self {
+        let message = message.into();
         Self {
             status_code,
             body: ErrorResponseBody {
                 error_code,
-                message: message.into(),
+                message: message.resolve(Language::default()),
+                fields: None,
             },
+            message,
+            field_messages: vec![],
         }
     }
 
-    pub fn without_error_code(
-        status_code: StatusCode,
-        message: impl Into<Cow<'static, str>>,
-    ) -> Self {
+    pub fn without_error_code(status_code: StatusCode, message: impl Into<UseCaseMessage>) -> Self {
+        Self::new(status_code, None, message)
+    }
+
+    /// 複数のフィールドの検証に失敗したことを示す、400 Bad Requestのリクエスト処理エラーを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `fields` - 検証に失敗したフィールドのエラー
+    ///
+    /// # 戻り値
+    ///
+    /// リクエスト処理エラー
+    pub fn invalid_fields(fields: Vec<FieldMessage>) -> Self {
+        let mut error = Self::new(
+            StatusCode::BAD_REQUEST,
+            Some(UseCaseErrorCode::Validation as u32),
+            MessageKey::InvalidFields,
+        );
+        error.body.fields = Some(
+            fields
+                .iter()
+                .map(|field| field.resolve(Language::default()))
+                .collect(),
+        );
+        error.field_messages = fields;
+
+        error
+    }
+
+    /// 言語に応じてメッセージを解決したエラーレスポンスを返す。
+    ///
+    /// # 引数
+    ///
+    /// * `language` - 言語
+    ///
+    /// # 戻り値
+    ///
+    /// エラーレスポンス
+    pub fn localized_response(&self, language: Language) -> HttpResponse {
+        let body = ErrorResponseBody {
+            error_code: self.body.error_code,
+            message: self.message.resolve(language),
+            fields: self.body.fields.as_ref().map(|_| {
+                self.field_messages
+                    .iter()
+                    .map(|field| field.resolve(language))
+                    .collect()
+            }),
+        };
         Self {
-            status_code,
-            body: ErrorResponseBody {
-                error_code: None,
-                message: message.into(),
-            },
+            status_code: self.status_code,
+            body,
+            message: self.message.clone(),
+            field_messages: self.field_messages.clone(),
         }
+        .error_response()
     }
 }
 /// エラーレスポンス・ボディ
 ///
 /// アプリケーションから返されるエラーレスポンスのボディを表現する。
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ErrorResponseBody {
     /// アプリ独自のエラーコード
@@ -107,9 +210,74 @@ pub struct ErrorResponseBody {
     pub error_code: Option<u32>,
 
     /// エラーメッセージ
+    #[schema(value_type = String)]
+    pub message: Cow<'static, str>,
+
+    /// 検証に失敗したフィールドのエラー
+    ///
+    /// 複数のフィールドの検証に失敗した場合のみ設定する。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<FieldError>>,
+}
+
+/// 検証に失敗したフィールドのエラー
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct FieldError {
+    /// リクエスト・ボディのフィールド名
+    pub field: Cow<'static, str>,
+    /// エラーメッセージ
+    #[schema(value_type = String)]
     pub message: Cow<'static, str>,
 }
 
+impl FieldError {
+    pub fn new<F, M>(field: F, message: M) -> Self
+    where
+        F: Into<Cow<'static, str>>,
+        M: Into<Cow<'static, str>>,
+    {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// 言語に応じて解決する、検証に失敗したフィールドのメッセージ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMessage {
+    /// リクエスト・ボディのフィールド名
+    pub field: Cow<'static, str>,
+    /// 言語に応じて解決するエラーメッセージ
+    pub message: UseCaseMessage,
+}
+
+impl FieldMessage {
+    pub fn new<F, M>(field: F, message: M) -> Self
+    where
+        F: Into<Cow<'static, str>>,
+        M: Into<UseCaseMessage>,
+    {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    /// 言語に応じてメッセージを解決した、フィールドのエラーを返す。
+    ///
+    /// # 引数
+    ///
+    /// * `language` - 言語
+    ///
+    /// # 戻り値
+    ///
+    /// フィールドのエラー
+    pub fn resolve(&self, language: Language) -> FieldError {
+        FieldError::new(self.field.clone(), self.message.resolve(language))
+    }
+}
+
 impl std::fmt::Display for ErrorResponseBody {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self.error_code {
@@ -135,6 +303,7 @@ impl ErrorResponseBody {
         Self {
             error_code,
             message: message.into(),
+            fields: None,
         }
     }
 }
@@ -142,45 +311,29 @@ impl ErrorResponseBody {
 impl From<DomainError> for ProcessRequestError {
     fn from(value: DomainError) -> Self {
         let status_code = match value {
+            DomainError::Repository(_) if value.is_timeout() => StatusCode::SERVICE_UNAVAILABLE,
             DomainError::Unexpected(_) | DomainError::Repository(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
             _ => StatusCode::BAD_REQUEST,
         };
-        Self {
-            status_code,
-            body: ErrorResponseBody {
-                error_code: None,
-                message: value.to_string().into(),
-            },
-        }
+        Self::without_error_code(status_code, value.to_string())
     }
 }
 
 impl From<UseCaseError> for ProcessRequestError {
     fn from(value: UseCaseError) -> Self {
-        let body = ErrorResponseBody {
-            error_code: Some(value.error_code),
-            message: value.message,
+        let status_code = match value.kind {
+            UseCaseErrorKind::Unexpected | UseCaseErrorKind::Repository => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            UseCaseErrorKind::Validation | UseCaseErrorKind::DomainRule => StatusCode::BAD_REQUEST,
+            UseCaseErrorKind::NotFound => StatusCode::NOT_FOUND,
+            UseCaseErrorKind::Unauthorized => StatusCode::UNAUTHORIZED,
+            UseCaseErrorKind::Conflict => StatusCode::CONFLICT,
+            UseCaseErrorKind::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
         };
-        match value.kind {
-            UseCaseErrorKind::Unexpected | UseCaseErrorKind::Repository => Self {
-                status_code: StatusCode::INTERNAL_SERVER_ERROR,
-                body,
-            },
-            UseCaseErrorKind::Validation | UseCaseErrorKind::DomainRule => Self {
-                status_code: StatusCode::BAD_REQUEST,
-                body,
-            },
-            UseCaseErrorKind::NotFound => Self {
-                status_code: StatusCode::NOT_FOUND,
-                body,
-            },
-            UseCaseErrorKind::Unauthorized => Self {
-                status_code: StatusCode::UNAUTHORIZED,
-                body,
-            },
-        }
+        Self::new(status_code, Some(value.error_code), value.message)
     }
 }
 
@@ -197,47 +350,612 @@ impl From<UseCaseError> for ProcessRequestError {
 fn retrieve_content_type(headers: &HeaderMap) -> Option<Mime> {
     let content_type = headers.get(header::CONTENT_TYPE)?;
     let content_type = content_type.to_str().ok()?;
-    match Mime::from_str(content_type) {
-        Ok(mime) => Some(mime),
-        Err(_) => None,
-    }
+    Mime::from_str(content_type).ok()
 }
 
+/// Content-Typeが`application/json`であるか確認する。
+///
+/// `charset`などのパラメーターは無視する。
+///
+/// # 引数
+///
+/// * `mime` - Content-Type
+///
+/// # 戻り値
+///
+/// `application/json`の場合は`true`、それ以外の場合は`false`
+fn is_application_json(mime: &Mime) -> bool {
+    mime.type_() == mime::APPLICATION && mime.subtype() == mime::JSON
+}
+
+/// デフォルト・エラー・ハンドラがエラーレスポンスボディとして解析するレスポンスボディの最大サイズ（バイト）
+///
+/// これより大きいレスポンスボディは読み込まずに、エラーレスポンスボディを生成して置き換える。
+const MAX_ERROR_BODY_BYTES: u64 = 64 * 1024;
+
+/// エラーレスポンスボディをシリアライズできなかった場合に返すレスポンスボディ
+const FALLBACK_ERROR_BODY: &str = r#"{"errorCode":null,"message":"Unexpected error raised"}"#;
+
 /// カスタムデフォルト・エラー・ハンドラ
+///
+/// Content-Typeが`application/json`でない場合、レスポンスボディをJSON形式のエラーレスポンスボディに書き換える。
+/// ただし、レスポンスボディがエラーレスポンスボディとして解析できる場合は、レスポンスボディをそのまま返す。
+/// ストリーミングするレスポンスボディは、書き換えずにそのまま返す。また、最大サイズを超えるレスポンス
+/// ボディは、読み込まずに書き換える。
+/// ワーカーをパニックさせないように、レスポンスボディの生成に失敗した場合は、固定のエラーレスポンス
+/// ボディを返す。
 pub fn default_error_handler<B>(
     res: ServiceResponse<B>,
-) -> actix_web::Result<ErrorHandlerResponse<B>> {
+) -> actix_web::Result<ErrorHandlerResponse<B>>
+where
+    B: MessageBody + 'static,
+{
     // コンテンツタイプがapplication/jsonの場合はそのまま返す
     let content_type = retrieve_content_type(res.headers());
-    if content_type.is_some() && content_type.unwrap() == mime::APPLICATION_JSON {
+    if content_type.as_ref().is_some_and(is_application_json) {
         return Ok(ErrorHandlerResponse::Response(res.map_into_left_body()));
     }
-    // レスポンスボディを生成
-    let message = res
-        .status()
-        .canonical_reason()
-        .unwrap_or("Unexpected error raised");
-    let body = ErrorResponseBody::new(None, message);
-    let body = serde_json::to_string(&body).unwrap();
+    // ストリーミングするレスポンスボディは、すべて読み込むまで待機しないように、そのまま返す
+    let size = res.response().body().size();
+    if matches!(size, BodySize::Stream) {
+        return Ok(ErrorHandlerResponse::Response(res.map_into_left_body()));
+    }
+    let oversized = matches!(size, BodySize::Sized(size) if MAX_ERROR_BODY_BYTES < size);
     let (req, res) = res.into_parts();
-    let mut res = res.set_body(body);
-    // レスポンスのヘッダを`application/json`に設定
-    res.headers_mut().insert(
-        header::CONTENT_TYPE,
-        header::HeaderValue::from_str(mime::APPLICATION_JSON.as_ref()).unwrap(),
-    );
-    // レスポンスを構築
-    let res = ServiceResponse::new(req, res)
-        .map_into_boxed_body()
-        .map_into_right_body();
+    let (res, body) = res.into_parts();
 
-    Ok(ErrorHandlerResponse::Response(res))
+    Ok(ErrorHandlerResponse::Future(Box::pin(async move {
+        // レスポンスボディがエラーレスポンスボディの場合はそのまま使用して、それ以外の場合はレスポンスボディを生成
+        let body = match oversized {
+            true => Bytes::new(),
+            false => to_bytes(body).await.unwrap_or_default(),
+        };
+        let body = match serde_json::from_slice::<ErrorResponseBody>(&body) {
+            Ok(_) => body,
+            Err(_) => {
+                let message = res
+                    .status()
+                    .canonical_reason()
+                    .unwrap_or("Unexpected error raised");
+                let body = ErrorResponseBody::new(None, message);
+                serde_json::to_vec(&body)
+                    .map(Bytes::from)
+                    .unwrap_or_else(|_| Bytes::from_static(FALLBACK_ERROR_BODY.as_bytes()))
+            }
+        };
+        let mut res = res.set_body(body);
+        // レスポンスのヘッダを`application/json`に設定
+        res.headers_mut().insert(
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_static("application/json"),
+        );
+        // レスポンスを構築
+        let res = ServiceResponse::new(req, res)
+            .map_into_boxed_body()
+            .map_into_right_body();
+
+        Ok(res)
+    })))
+}
+
+/// ルーティングできないパスへのリクエストに、404 Not Foundを返す。
+///
+/// `App`のデフォルトサービスとして登録する。
+pub async fn not_found() -> ProcessRequestResult<HttpResponse> {
+    Err(ProcessRequestError::without_error_code(
+        StatusCode::NOT_FOUND,
+        StatusCode::NOT_FOUND.canonical_reason().unwrap(),
+    ))
+}
+
+/// リソースが許可していないHTTPメソッドでのリクエストに、405 Method Not Allowedを返すルートを生成する。
+///
+/// レスポンスには、リソースが許可しているHTTPメソッドを`Allow`ヘッダに設定する。
+/// リソースのデフォルトサービスとして登録する。
+///
+/// # 引数
+///
+/// * `allowed` - リソースが許可しているHTTPメソッド
+///
+/// # 戻り値
+///
+/// ルート
+pub fn method_not_allowed(allowed: &'static [Method]) -> Route {
+    web::to(move || async move {
+        let error = ProcessRequestError::without_error_code(
+            StatusCode::METHOD_NOT_ALLOWED,
+            StatusCode::METHOD_NOT_ALLOWED.canonical_reason().unwrap(),
+        );
+        let mut res = error.error_response();
+        res.headers_mut().insert(
+            header::ALLOW,
+            header::Allow(allowed.to_vec()).try_into_value().unwrap(),
+        );
+
+        res
+    })
+}
+
+/// JSON形式のリクエストボディの最大サイズを設定したJSON抽出器の設定を生成する。
+///
+/// リクエストボディが最大サイズを超える場合は、413 Payload Too LargeをJSON形式のエラーレスポンスで返す。
+///
+/// # 引数
+///
+/// * `limit` - JSON形式のリクエストボディの最大サイズ（バイト）
+///
+/// # 戻り値
+///
+/// JSON抽出器の設定
+pub fn json_config(limit: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit)
+        .error_handler(|e, _| match e {
+            JsonPayloadError::Overflow { limit }
+            | JsonPayloadError::OverflowKnownLength { limit, .. } => {
+                ProcessRequestError::without_error_code(
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    UseCaseMessage::from(MessageKey::PayloadTooLarge).with_param("max", limit),
+                )
+                .into()
+            }
+            JsonPayloadError::Deserialize(e) => match unknown_field(&e) {
+                Some(field) => unknown_field_error(field).into(),
+                None => JsonPayloadError::Deserialize(e).into(),
+            },
+            e => e.into(),
+        })
+}
+
+/// JSON形式のリクエストボディを、リクエスト・ボディ型にデシリアライズする。
+///
+/// リクエスト・ボディ型は`#[serde(deny_unknown_fields)]`を付与して、未知のフィールドを拒否する。
+/// 未知のフィールドを含む場合は、そのフィールドを示す400 Bad Requestを返す。ただし、厳密に検証しない
+/// 場合は、リクエストボディの最上位にある未知のフィールドを取り除いてデシリアライズする。
+///
+/// # 引数
+///
+/// * `value` - JSON形式のリクエストボディ
+/// * `strict` - 未知のフィールドを拒否する場合は`true`
+///
+/// # 戻り値
+///
+/// リクエスト・ボディ
+pub fn parse_request_body<T>(mut value: serde_json::Value, strict: bool) -> ProcessRequestResult<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    loop {
+        let e = match T::deserialize(&value) {
+            Ok(body) => return Ok(body),
+            Err(e) => e,
+        };
+        let field = match unknown_field(&e) {
+            Some(field) => field,
+            None => {
+                return Err(ProcessRequestError::without_error_code(
+                    StatusCode::BAD_REQUEST,
+                    StatusCode::BAD_REQUEST.canonical_reason().unwrap(),
+                ))
+            }
+        };
+        let removed = match (strict, value.as_object_mut()) {
+            (false, Some(object)) => object.remove(&field).is_some(),
+            _ => false,
+        };
+        if !removed {
+            return Err(unknown_field_error(field));
+        }
+        tracing::warn!("Ignored unknown field in request body: {}", field);
+    }
+}
+
+/// デシリアライズ・エラーが未知のフィールドを示す場合に、そのフィールド名を返す。
+///
+/// # 引数
+///
+/// * `e` - デシリアライズ・エラー
+///
+/// # 戻り値
+///
+/// 未知のフィールド名
+fn unknown_field(e: &serde_json::Error) -> Option<String> {
+    let message = e.to_string();
+    let rest = message.strip_prefix("unknown field `")?;
+    let end = rest.find('`')?;
+
+    Some(String::from(&rest[..end]))
+}
+
+/// 未知のフィールドを含むことを示す、400 Bad Requestのリクエスト処理エラーを構築する。
+///
+/// # 引数
+///
+/// * `field` - 未知のフィールド名
+///
+/// # 戻り値
+///
+/// リクエスト処理エラー
+fn unknown_field_error(field: String) -> ProcessRequestError {
+    ProcessRequestError::invalid_fields(vec![FieldMessage::new(field, MessageKey::UnknownField)])
 }
 
 /// ヘルスチェック
+#[utoipa::path(
+    get,
+    path = "/health-check",
+    tag = "health",
+    responses((status = 200, description = "HTTPサーバーが動作している"))
+)]
 #[tracing::instrument(name = "health check")]
 pub async fn health_check() -> impl Responder {
     HttpResponse::Ok()
         .insert_header(header::ContentType(mime::APPLICATION_JSON))
         .body(r#"{"message": "It works!"}"#)
 }
+
+/// JWTの署名を検証する公開鍵のJWK Setを返す。
+///
+/// 非対称鍵でJWTを署名する場合に、JWTを検証するサービスが公開鍵を取得できるように公開する。
+/// 共有する秘密鍵でJWTを署名する場合は、404 Not Foundを返す。
+#[tracing::instrument(name = "jwks", skip(context))]
+pub async fn jwks(context: web::Data<RequestContext>) -> ProcessRequestResult<HttpResponse> {
+    match context.jwt_keys.jwks() {
+        Some(jwks) => Ok(HttpResponse::Ok().json(jwks)),
+        None => not_found().await,
+    }
+}
+
+/// APIのパスにプレフィックスを付与する前のパス
+pub const UNPREFIXED_PATHS: [&str; 2] = ["/health-check", "/accounts"];
+
+/// プレフィックスを付与していない以前のパスへのリクエストを、プレフィックスを付与したパスに
+/// リダイレクトするサービスを登録する。
+///
+/// # 引数
+///
+/// * `config` - サービス設定
+pub fn redirect_unprefixed_paths(config: &mut web::ServiceConfig) {
+    for path in UNPREFIXED_PATHS {
+        config.service(web::scope(path).default_service(web::to(redirect_to_prefixed_path)));
+    }
+}
+
+/// リクエストされたパスにプレフィックスを付与したパスに、308 Permanent Redirectでリダイレクトする。
+///
+/// リダイレクト先のパスには、リクエストされたパスのクエリ文字列を引き継ぐ。
+async fn redirect_to_prefixed_path(
+    request: HttpRequest,
+    context: web::Data<RequestContext>,
+) -> HttpResponse {
+    let path_and_query = request
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or_else(|| request.path());
+    let location = format!(
+        "{}{}",
+        context.http_server_settings.api_prefix, path_and_query
+    );
+
+    HttpResponse::PermanentRedirect()
+        .insert_header((header::LOCATION, location))
+        .finish()
+}
+
+/// レディネスチェックで、依存するサービスの応答を待機する時間（秒）
+pub const READINESS_CHECK_TIMEOUT_SECONDS: u64 = 2;
+
+/// 依存するサービスの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyStatus {
+    /// 応答あり
+    Up,
+    /// 応答なし
+    Down,
+}
+
+/// レディネスチェック・レスポンスボディ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ToSchema)]
+pub struct ReadinessResBody {
+    /// PostgreSQLの状態
+    pub postgres: DependencyStatus,
+    /// Redisの状態
+    pub redis: DependencyStatus,
+    /// 応答しなかったサービスの名前のリスト
+    pub failures: Vec<String>,
+}
+
+/// レディネスチェック
+///
+/// PostgreSQLとRedisが応答するか確認して、すべてが応答した場合は200 OK、
+/// 応答しないサービスがある場合は503 Service Unavailableを返す。
+#[utoipa::path(
+    get,
+    path = "/health-check/ready",
+    tag = "health",
+    responses(
+        (status = 200, description = "すべてのサービスが応答した", body = ReadinessResBody),
+        (status = 503, description = "応答しないサービスがある", body = ReadinessResBody),
+    )
+)]
+#[tracing::instrument(name = "readiness check", skip(context))]
+pub async fn readiness_check(context: web::Data<RequestContext>) -> impl Responder {
+    let postgres = check_postgres(&context.pg_pool).await;
+    let redis = check_redis(&context.redis_pool).await;
+    let failures = [("postgres", postgres), ("redis", redis)]
+        .into_iter()
+        .filter(|(_, status)| *status == DependencyStatus::Down)
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+    let mut res = match failures.is_empty() {
+        true => HttpResponse::Ok(),
+        false => HttpResponse::ServiceUnavailable(),
+    };
+
+    res.json(ReadinessResBody {
+        postgres,
+        redis,
+        failures,
+    })
+}
+
+/// PostgreSQL接続プールから接続を取得して、`SELECT 1`を実行できるか確認する。
+async fn check_postgres(pool: &PgPool) -> DependencyStatus {
+    let query = sqlx::query("SELECT 1").execute(pool);
+    match timeout(Duration::from_secs(READINESS_CHECK_TIMEOUT_SECONDS), query).await {
+        Ok(Ok(_)) => DependencyStatus::Up,
+        Ok(Err(e)) => {
+            tracing::error!("{} ({}:{})", e, file!(), line!());
+            DependencyStatus::Down
+        }
+        Err(_) => {
+            tracing::error!("{} ({}:{})", POSTGRES_TIMEOUT, file!(), line!());
+            DependencyStatus::Down
+        }
+    }
+}
+
+/// RedisにPINGコマンドを送信して、Redisが応答するか確認する。
+async fn check_redis(pool: &RedisPool) -> DependencyStatus {
+    let repo = RedisTokenRepository::new(pool.clone());
+    match timeout(
+        Duration::from_secs(READINESS_CHECK_TIMEOUT_SECONDS),
+        repo.ping(),
+    )
+    .await
+    {
+        Ok(Ok(_)) => DependencyStatus::Up,
+        // エラーはリポジトリで記録済み
+        Ok(Err(_)) => DependencyStatus::Down,
+        Err(_) => {
+            tracing::error!("{} ({}:{})", REDIS_TIMEOUT, file!(), line!());
+            DependencyStatus::Down
+        }
+    }
+}
+
+const POSTGRES_TIMEOUT: &str = "PostgreSQLが時間内に応答しませんでした。";
+const REDIS_TIMEOUT: &str = "Redisが時間内に応答しませんでした。";
+
+#[cfg(test)]
+mod tests {
+    use actix_web::middleware::ErrorHandlers;
+    use actix_web::{test, App};
+
+    use super::*;
+
+    /// 指定したContent-Typeとボディで400 Bad Requestを返すハンドラを登録したアプリで、リクエストを処理する。
+    async fn call_bad_request(
+        content_type: Option<&'static str>,
+        body: &'static str,
+    ) -> (Option<String>, String) {
+        let app = test::init_service(
+            App::new()
+                .wrap(ErrorHandlers::new().default_handler(default_error_handler))
+                .route(
+                    "/",
+                    web::get().to(move || async move {
+                        let mut builder = HttpResponse::BadRequest();
+                        if let Some(content_type) = content_type {
+                            builder.insert_header((header::CONTENT_TYPE, content_type));
+                        }
+                        builder.body(body)
+                    }),
+                ),
+        )
+        .await;
+        let res = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let content_type = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = test::read_body(res).await;
+
+        (content_type, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    /// Content-Typeに`charset`パラメーターが付与されたJSONのボディを、そのまま返すことを確認
+    #[actix_web::test]
+    async fn default_error_handler_keeps_json_body_with_charset() {
+        let body = r#"{"errorCode":1,"message":"エラー"}"#;
+
+        let (content_type, actual) =
+            call_bad_request(Some("application/json; charset=utf-8"), body).await;
+
+        assert_eq!(
+            Some("application/json; charset=utf-8"),
+            content_type.as_deref()
+        );
+        assert_eq!(body, actual);
+    }
+
+    /// Content-Typeが設定されていないが、ボディがエラーレスポンスボディの場合は、ボディをそのまま返すことを確認
+    #[actix_web::test]
+    async fn default_error_handler_passes_through_error_response_body() {
+        let body = r#"{"errorCode":1,"message":"エラー"}"#;
+
+        let (content_type, actual) = call_bad_request(None, body).await;
+
+        assert_eq!(Some("application/json"), content_type.as_deref());
+        assert_eq!(body, actual);
+    }
+
+    /// ボディがエラーレスポンスボディでない場合は、ボディを書き換えることを確認
+    #[actix_web::test]
+    async fn default_error_handler_rewrites_non_json_body() {
+        let (content_type, actual) = call_bad_request(Some("text/plain"), "bad request").await;
+        let actual: ErrorResponseBody = serde_json::from_str(&actual).unwrap();
+
+        assert_eq!(Some("application/json"), content_type.as_deref());
+        assert!(actual.error_code.is_none());
+        assert_eq!("Bad Request", actual.message);
+    }
+
+    /// レスポンスから構築したサービスレスポンスで、デフォルト・エラー・ハンドラを直接呼び出す。
+    async fn handle_error<B>(res: HttpResponse<B>) -> (Option<String>, Bytes)
+    where
+        B: MessageBody + 'static,
+    {
+        let res = test::TestRequest::default().to_srv_response(res);
+        let res = match default_error_handler(res).unwrap() {
+            ErrorHandlerResponse::Response(res) => res,
+            ErrorHandlerResponse::Future(future) => future.await.unwrap(),
+        };
+        let content_type = res
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap().to_string());
+        let body = to_bytes(res.into_body()).await.ok().unwrap();
+
+        (content_type, body)
+    }
+
+    /// エラーレスポンスボディを返すサービスレスポンスは、書き換えないことを確認
+    #[actix_web::test]
+    async fn default_error_handler_keeps_synthetic_json_error() {
+        let body = r#"{"errorCode":1,"message":"エラー"}"#;
+        let res = HttpResponse::BadRequest()
+            .insert_header(header::ContentType(mime::APPLICATION_JSON))
+            .body(body);
+
+        let (content_type, actual) = handle_error(res).await;
+
+        assert_eq!(Some("application/json"), content_type.as_deref());
+        assert_eq!(body.as_bytes(), actual);
+    }
+
+    /// プレーンテキストのエラーを返すサービスレスポンスは、エラーレスポンスボディに書き換えることを確認
+    #[actix_web::test]
+    async fn default_error_handler_rewrites_synthetic_plain_text_error() {
+        let res = HttpResponse::NotFound()
+            .insert_header(header::ContentType(mime::TEXT_PLAIN))
+            .body("not found");
+
+        let (content_type, actual) = handle_error(res).await;
+        let actual: ErrorResponseBody = serde_json::from_slice(&actual).unwrap();
+
+        assert_eq!(Some("application/json"), content_type.as_deref());
+        assert!(actual.error_code.is_none());
+        assert_eq!("Not Found", actual.message);
+    }
+
+    /// Content-Typeをメディアタイプとして解析できない場合も、パニックせずにエラーレスポンスボディに
+    /// 書き換えることを確認
+    #[actix_web::test]
+    async fn default_error_handler_rewrites_error_with_malformed_content_type() {
+        let res = HttpResponse::BadRequest()
+            .insert_header((header::CONTENT_TYPE, "application//json;;="))
+            .body("bad request");
+
+        let (content_type, actual) = handle_error(res).await;
+        let actual: ErrorResponseBody = serde_json::from_slice(&actual).unwrap();
+
+        assert_eq!(Some("application/json"), content_type.as_deref());
+        assert_eq!("Bad Request", actual.message);
+    }
+
+    /// 最大サイズを超えるレスポンスボディは、読み込まずにエラーレスポンスボディに書き換えることを確認
+    #[actix_web::test]
+    async fn default_error_handler_replaces_oversized_body() {
+        let body = "a".repeat(MAX_ERROR_BODY_BYTES as usize + 1);
+        let res = HttpResponse::InternalServerError()
+            .insert_header(header::ContentType(mime::TEXT_PLAIN))
+            .body(body);
+
+        let (_, actual) = handle_error(res).await;
+        let actual: ErrorResponseBody = serde_json::from_slice(&actual).unwrap();
+
+        assert_eq!("Internal Server Error", actual.message);
+    }
+
+    /// ストリーミングするレスポンスボディは、書き換えないことを確認
+    #[actix_web::test]
+    async fn default_error_handler_skips_streaming_body() {
+        let stream = futures_util::stream::once(async {
+            Ok::<_, actix_web::Error>(Bytes::from_static(b"streaming"))
+        });
+        let res = HttpResponse::BadRequest()
+            .insert_header(header::ContentType(mime::TEXT_PLAIN))
+            .streaming(stream);
+
+        let (content_type, actual) = handle_error(res).await;
+
+        assert_eq!(Some("text/plain"), content_type.as_deref());
+        assert_eq!(Bytes::from_static(b"streaming"), actual);
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[serde(rename_all = "camelCase", deny_unknown_fields)]
+    struct PhoneReqBody {
+        mobile_phone_number: Option<String>,
+    }
+
+    /// 未知のフィールドを含むリクエストボディは、厳密に検証する場合にそのフィールドを示すエラーを返し、
+    /// 厳密に検証しない場合はフィールドを無視することを確認
+    #[actix_web::test]
+    async fn parse_request_body_rejects_or_ignores_unknown_fields() {
+        let value = serde_json::json!({ "mobliePhoneNumber": "090-1234-5678" });
+
+        let e = parse_request_body::<PhoneReqBody>(value.clone(), true).unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, e.status_code);
+        assert_eq!(
+            Some(vec![FieldError::new(
+                "mobliePhoneNumber",
+                MessageKey::UnknownField.template(Language::Ja)
+            )]),
+            e.body.fields
+        );
+
+        let body = parse_request_body::<PhoneReqBody>(value, false).unwrap();
+        assert!(body.mobile_phone_number.is_none());
+
+        let e = parse_request_body::<PhoneReqBody>(serde_json::json!([]), false).unwrap_err();
+        assert_eq!(StatusCode::BAD_REQUEST, e.status_code);
+        assert!(e.body.fields.is_none());
+    }
+
+    /// 複数のフィールドの検証に失敗したエラーを言語に応じて解決しても、フィールドのエラーを維持して、
+    /// メッセージカタログに登録されたフィールドのメッセージを言語に応じて解決することを確認
+    #[actix_web::test]
+    async fn localized_invalid_fields_response_keeps_field_errors() {
+        let error = ProcessRequestError::invalid_fields(vec![
+            FieldMessage::new("email", "Eメールアドレスの形式が間違っています。"),
+            FieldMessage::new("mobliePhoneNumber", MessageKey::UnknownField),
+        ]);
+        let fields = vec![
+            FieldError::new("email", "Eメールアドレスの形式が間違っています。"),
+            FieldError::new("mobliePhoneNumber", "The field is unknown."),
+        ];
+
+        let res = error.localized_response(Language::En);
+        let body = to_bytes(res.into_body()).await.unwrap();
+        let body: ErrorResponseBody = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(StatusCode::BAD_REQUEST, error.status_code);
+        assert_eq!(Some(UseCaseErrorCode::Validation as u32), body.error_code);
+        assert_eq!("Some fields are invalid.", body.message);
+        assert_eq!(Some(fields), body.fields);
+    }
+}
@@ -0,0 +1,87 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::HeaderMap;
+use opentelemetry::propagation::{Extractor, TextMapPropagator as _};
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use tracing::Span;
+use tracing_actix_web::{root_span, DefaultRootSpanBuilder, RootSpanBuilder};
+use tracing_opentelemetry::OpenTelemetrySpanExt as _;
+
+/// リクエストのスパンを構築するビルダー
+///
+/// `tracing-actix-web`の`TracingLogger`ミドルウェアに指定して使用する。
+/// リクエストのスパンには、`http.method`、`http.route`及び`http.status_code`を記録する。
+/// また、リクエストに`traceparent`ヘッダ（W3C Trace Context）がある場合は、ヘッダが示すスパンを
+/// リクエストのスパンの親に設定する。
+pub struct RequestSpanBuilder;
+
+impl RootSpanBuilder for RequestSpanBuilder {
+    fn on_request_start(request: &ServiceRequest) -> Span {
+        let span = root_span!(request);
+        // `traceparent`ヘッダから、リクエストを送信したサービスのトレースのコンテキストを取得
+        let parent = TraceContextPropagator::new().extract(&HeaderExtractor(request.headers()));
+        span.set_parent(parent);
+
+        span
+    }
+
+    fn on_request_end<B: MessageBody>(
+        span: Span,
+        outcome: &Result<ServiceResponse<B>, actix_web::Error>,
+    ) {
+        // ステータスコードとエラーを記録
+        DefaultRootSpanBuilder::on_request_end(span, outcome);
+    }
+}
+
+/// HTTPヘッダからトレースのコンテキストを取り出すエクストラクター
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::header::{HeaderName, HeaderValue};
+    use opentelemetry::trace::TraceContextExt as _;
+
+    use super::*;
+
+    /// `traceparent`ヘッダからトレースのコンテキストを取り出せることを確認
+    #[test]
+    fn can_extract_trace_context_from_traceparent_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            HeaderName::from_static("traceparent"),
+            HeaderValue::from_static("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"),
+        );
+
+        let context = TraceContextPropagator::new().extract(&HeaderExtractor(&headers));
+        let span_context = context.span().span_context().clone();
+
+        assert!(span_context.is_valid());
+        assert!(span_context.is_remote());
+        assert_eq!(
+            "4bf92f3577b34da6a3ce929d0e0e4736",
+            span_context.trace_id().to_string()
+        );
+        assert_eq!("00f067aa0ba902b7", span_context.span_id().to_string());
+    }
+
+    /// `traceparent`ヘッダがない場合は、トレースのコンテキストを取り出さないことを確認
+    #[test]
+    fn no_trace_context_without_traceparent_header() {
+        let headers = HeaderMap::new();
+
+        let context = TraceContextPropagator::new().extract(&HeaderExtractor(&headers));
+
+        assert!(!context.span().span_context().is_valid());
+    }
+}
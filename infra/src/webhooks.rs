@@ -0,0 +1,327 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use secrecy::ExposeSecret as _;
+use sha2::Sha256;
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
+
+use configurations::settings::WebhookSettings;
+use domain::events::{DomainEvent, EventHandler};
+use domain::DomainResult;
+
+/// Webhook送信者
+///
+/// 購読しているWebhookの送信先に、発行されたドメインイベントをJSON形式で通知するイベントハンドラー。
+/// リクエストボディは、Webhook設定の秘密鍵を使用したHMAC-SHA256で署名して、その値を
+/// `X-Webhook-Signature`ヘッダに`sha256=<16進数>`の形式で設定する。
+/// HTTPハンドラーを待機させないように、Webhookはバックグラウンドで送信する。送信先が2xx以外の
+/// ステータスコードを返した場合、または送信先に接続できなかった場合は、待機時間を2倍にしながら
+/// 最大の送信回数まで再送信して、すべて失敗した場合は、イベントの内容とともにエラーを記録する。
+#[derive(Debug, Clone)]
+pub struct WebhookSender {
+    /// HTTPクライアント
+    client: reqwest::Client,
+    /// Webhook設定のリスト
+    webhooks: Arc<Vec<WebhookSettings>>,
+}
+
+impl WebhookSender {
+    /// Webhook送信者を構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `webhooks` - Webhook設定のリスト
+    ///
+    /// # 戻り値
+    ///
+    /// Webhook送信者
+    pub fn new(webhooks: Vec<WebhookSettings>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            webhooks: Arc::new(webhooks),
+        }
+    }
+
+    /// ドメインイベントを購読しているWebhookの送信先に、イベントを通知する。
+    ///
+    /// # 引数
+    ///
+    /// * `event` - ドメインイベント
+    ///
+    /// # 戻り値
+    ///
+    /// 送信先ごとに、Webhookを送信するタスクのハンドル
+    pub fn send(&self, event: &DomainEvent) -> Vec<JoinHandle<()>> {
+        let envelope = WebhookEnvelope::new(event, OffsetDateTime::now_utc());
+        let body = match serde_json::to_string(&envelope) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                return vec![];
+            }
+        };
+
+        self.webhooks
+            .iter()
+            .filter(|webhook| webhook.subscribes(event.name()))
+            .map(|webhook| {
+                let client = self.client.clone();
+                let webhook = webhook.clone();
+                let body = body.clone();
+                tokio::spawn(async move { deliver(&client, &webhook, body).await })
+            })
+            .collect()
+    }
+}
+
+#[async_trait]
+impl EventHandler for WebhookSender {
+    async fn handle(&self, event: &DomainEvent) -> DomainResult<()> {
+        let _ = self.send(event);
+
+        Ok(())
+    }
+}
+
+/// Webhookの送信先に、イベントを通知するリクエストを送信する。
+///
+/// 送信に失敗した場合は、最大の送信回数まで再送信する。
+///
+/// # 引数
+///
+/// * `client` - HTTPクライアント
+/// * `webhook` - Webhook設定
+/// * `body` - リクエストボディ
+async fn deliver(client: &reqwest::Client, webhook: &WebhookSettings, body: String) {
+    let signature = sign(webhook.secret.expose_secret(), body.as_bytes());
+    let mut backoff = Duration::from_millis(webhook.initial_backoff_millis);
+    for attempt in 1..=webhook.max_attempts {
+        let result = client
+            .post(&webhook.url)
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                mime::APPLICATION_JSON.as_ref(),
+            )
+            .header(WEBHOOK_SIGNATURE_HEADER, &signature)
+            .timeout(Duration::from_secs(webhook.timeout_seconds))
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => return,
+            Err(e) if attempt < webhook.max_attempts => {
+                tracing::warn!(
+                    url = %webhook.url,
+                    attempt,
+                    "Failed to deliver webhook, retrying in {:?}: {}",
+                    backoff,
+                    e
+                );
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(e) => {
+                tracing::error!(
+                    url = %webhook.url,
+                    attempts = webhook.max_attempts,
+                    payload = %body,
+                    "Failed to deliver webhook: {}",
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// リクエストボディをHMAC-SHA256で署名する。
+///
+/// # 引数
+///
+/// * `secret` - 秘密鍵
+/// * `body` - リクエストボディ
+///
+/// # 戻り値
+///
+/// `sha256=`に、署名を16進数で表現した文字列を連結した文字列
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    // HMACは任意の長さの鍵を受け付けるため、失敗しない
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    let signature = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    format!("sha256={}", signature)
+}
+
+/// Webhookで送信するイベントのエンベロープ
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookEnvelope {
+    /// イベントの種類を示す名前
+    pub event_type: String,
+    /// イベントが発生した日時
+    #[serde(with = "time::serde::rfc3339")]
+    pub occurred_at: OffsetDateTime,
+    /// イベントの対象となったユーザーのユーザーID
+    pub user_id: String,
+    /// イベントの種類ごとの内容
+    pub payload: serde_json::Value,
+}
+
+impl WebhookEnvelope {
+    /// ドメインイベントから、Webhookで送信するイベントのエンベロープを構築する。
+    ///
+    /// 送信先に必要以上の個人情報を渡さないように、内容にはEメールアドレスのみを含める。
+    ///
+    /// # 引数
+    ///
+    /// * `event` - ドメインイベント
+    /// * `occurred_at` - イベントが発生した日時
+    ///
+    /// # 戻り値
+    ///
+    /// イベントのエンベロープ
+    pub fn new(event: &DomainEvent, occurred_at: OffsetDateTime) -> Self {
+        let payload = match event {
+            DomainEvent::UserRegistered { email, .. }
+            | DomainEvent::AccountLocked { email, .. } => {
+                serde_json::json!({ "email": email.value })
+            }
+            DomainEvent::UserSignedIn { .. } | DomainEvent::PasswordChanged { .. } => {
+                serde_json::json!({})
+            }
+        };
+
+        Self {
+            event_type: String::from(event.name()),
+            occurred_at,
+            user_id: event.user_id().to_string(),
+            payload,
+        }
+    }
+}
+
+/// リクエストボディの署名を設定するヘッダ
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Webhook-Signature";
+
+#[cfg(test)]
+mod tests {
+    use secrecy::SecretString;
+    use wiremock::matchers::{header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use domain::models::primitives::EmailAddress;
+    use domain::models::user::UserId;
+
+    use super::*;
+
+    fn webhook_settings(url: String, events: &[&str]) -> WebhookSettings {
+        WebhookSettings {
+            url,
+            secret: SecretString::new(String::from("webhook-secret")),
+            events: events.iter().map(|event| event.to_string()).collect(),
+            max_attempts: 3,
+            initial_backoff_millis: 10,
+            timeout_seconds: 5,
+        }
+    }
+
+    fn user_registered() -> DomainEvent {
+        DomainEvent::UserRegistered {
+            user_id: UserId::default(),
+            email: EmailAddress::new("foo@example.com").unwrap(),
+        }
+    }
+
+    /// 送信先が最初に500を返した場合は再送信して、リクエストボディをHMAC-SHA256で署名した
+    /// イベントのエンベロープを送信することを確認
+    #[tokio::test]
+    async fn send_retries_on_server_error_and_signs_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hooks"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/hooks"))
+            .and(header_exists(WEBHOOK_SIGNATURE_HEADER))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let url = format!("{}/hooks", server.uri());
+        let sender = WebhookSender::new(vec![webhook_settings(url, &["user_registered"])]);
+        let event = user_registered();
+
+        for handle in sender.send(&event) {
+            handle.await.unwrap();
+        }
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(2, requests.len());
+        for request in requests {
+            let signature = request
+                .headers
+                .get(WEBHOOK_SIGNATURE_HEADER)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            assert_eq!(sign("webhook-secret", &request.body), signature);
+            let envelope: WebhookEnvelope = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!("user_registered", envelope.event_type);
+            assert_eq!(event.user_id().to_string(), envelope.user_id);
+            assert_eq!("foo@example.com", envelope.payload["email"]);
+        }
+    }
+
+    /// 最大の送信回数まで送信に失敗した場合は、送信を諦めることを確認
+    #[tokio::test]
+    async fn send_gives_up_after_max_attempts() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&server)
+            .await;
+        let sender = WebhookSender::new(vec![webhook_settings(server.uri(), &["user_registered"])]);
+
+        for handle in sender.send(&user_registered()) {
+            handle.await.unwrap();
+        }
+    }
+
+    /// 購読していないイベントは送信しないことを確認
+    #[tokio::test]
+    async fn send_skips_unsubscribed_webhooks() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(0)
+            .mount(&server)
+            .await;
+        let sender = WebhookSender::new(vec![webhook_settings(server.uri(), &["account_locked"])]);
+
+        assert!(sender.send(&user_registered()).is_empty());
+    }
+
+    /// 署名がHMAC-SHA256の既知の値と一致することを確認
+    #[test]
+    fn sign_matches_known_hmac_sha256() {
+        // RFC 4231 Test Case 2
+        assert_eq!(
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            sign("Jefe", b"what do ya want for nothing?")
+        );
+    }
+}
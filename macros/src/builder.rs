@@ -2,12 +2,13 @@ use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 use syn::spanned::Spanned;
 use syn::{
-    AngleBracketedGenericArguments, Attribute, Data, DataStruct, DeriveInput, Fields, FieldsNamed,
-    GenericArgument, Ident, Lit, Path, PathArguments, PathSegment, Type, TypePath, Visibility,
+    AngleBracketedGenericArguments, Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput,
+    Fields, FieldsNamed, GenericArgument, Ident, LitStr, Path, PathArguments, PathSegment, Type,
+    TypePath, Visibility,
 };
 
 use crate::types::CommaPunctuatedFields;
-use crate::utils::retrieve_name_values_list;
+use crate::utils::filter_attrs;
 
 pub(crate) fn impl_builder(input: DeriveInput) -> syn::Result<TokenStream2> {
     if let Data::Struct(DataStruct {
@@ -25,14 +26,14 @@ pub(crate) fn impl_builder(input: DeriveInput) -> syn::Result<TokenStream2> {
         let builder_struct = impl_builder_struct(&vis, &builder_ident, &fields);
         // ビルダーの`new`メソッドを実装
         let builder_new_method = impl_builder_new_method(&vis, &fields);
-        // ビルダーを構築する構造体のフィールドの`builder`フィールドに付与された名前`each`の値を取得
-        let each_values = retrieve_each_name_value(&named)?;
+        // ビルダーを構築する構造体のフィールドに付与された`builder`属性を取得
+        let field_attrs = retrieve_builder_field_attrs(&named)?;
         // ビルダーのsetterメソッドを実装
-        let builder_setter_methods = impl_builder_setter_methods(&vis, &fields, &each_values);
+        let builder_setter_methods = impl_builder_setter_methods(&vis, &fields, &field_attrs);
         // ビルダーの`build`メソッドを実装
-        let func_ident = retrieve_builder_validation_func(&input.attrs)?;
+        let validation = retrieve_builder_validation(&input.attrs)?;
         let builder_build_method =
-            impl_builder_build_method(&vis, &struct_ident, &fields, func_ident);
+            impl_builder_build_method(&vis, &struct_ident, &fields, &field_attrs, validation);
 
         Ok(quote! {
             #builder_struct
@@ -46,119 +47,147 @@ pub(crate) fn impl_builder(input: DeriveInput) -> syn::Result<TokenStream2> {
             }
         })
     } else {
-        Err(syn::Error::new(input.span(), "only struct supported"))
+        // 列挙型と共用体の場合はキーワード、タプル構造体の場合はフィールドを指すエラー
+        let span = match &input.data {
+            Data::Struct(DataStruct {
+                fields: Fields::Unit,
+                ..
+            }) => input.ident.span(),
+            Data::Struct(DataStruct { fields, .. }) => fields.span(),
+            Data::Enum(DataEnum { enum_token, .. }) => enum_token.span,
+            Data::Union(DataUnion { union_token, .. }) => union_token.span,
+        };
+        Err(syn::Error::new(
+            span,
+            "only struct with named fields supported",
+        ))
     }
 }
 
-/// ビルダーが構築するプリミティブを検証する関数の識別子を取得する。
+/// ビルダーを構築する構造体に付与された`builder_validation`属性
+#[derive(Default)]
+struct BuilderValidation {
+    /// `validator::Validate`で検証するか
+    validate: bool,
+    /// 構築した構造体を検証するメソッドの識別子
+    func: Option<Ident>,
+}
+
+/// ビルダーが構築する構造体の検証方法を取得する。
 ///
 /// ```text
 /// #[derive(Builder)]
-/// #[builder_validation(func = "func_name")
+/// #[builder_validation(validate, func = "func_name")]
 /// struct Foo { ... }
 /// ```
-/// 上記`func_name`を取得する。
-fn retrieve_builder_validation_func(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
-    let name_values_list = retrieve_name_values_list(attrs, "builder_validation")?;
+///
+/// 上記`validate`が指定されていることと、`func_name`を取得する。
+fn retrieve_builder_validation(attrs: &[Attribute]) -> syn::Result<BuilderValidation> {
+    let validation_attrs = filter_attrs(attrs, "builder_validation");
+    let mut validation = BuilderValidation::default();
 
     // builder_validation属性が指定されていない場合は検証しない
-    if name_values_list.is_empty() {
-        return Ok(None);
+    if validation_attrs.is_empty() {
+        return Ok(validation);
     }
     // builder_validation属性が2つ以上指定されている場合はエラー
-    if name_values_list.len() > 1 {
-        return Err(syn::Error::new(
-            attrs[0].span(),
+    if validation_attrs.len() > 1 {
+        return Err(syn::Error::new_spanned(
+            validation_attrs[1],
             "only one builder_validation can be specified",
         ));
     }
-    // builder_validation属性にfuncのみ指定されているか確認
-    let name_values = &name_values_list[0];
-    if 1 < name_values.keys().len() {
-        return Err(syn::Error::new(
-            attrs[0].span(),
-            "builder_validation must have only one `func` name value",
-        ));
-    }
 
-    // builder_validation属性にfuncが複数指定されている場合はエラー
-    let func_list = name_values
-        .get(&format_ident!("func"))
-        .ok_or(syn::Error::new(
-            attrs[0].span(),
-            "builder_validation must have only one `func` name value",
-        ))?;
-    if 1 < func_list.len() {
-        return Err(syn::Error::new(
-            attrs[0].span(),
-            "only one `func` can be specified",
+    // `validate`は値を持たないため、名前と値のリストとしてではなく、メタ情報として解析
+    validation_attrs[0].parse_nested_meta(|meta| {
+        if meta.path.is_ident("validate") {
+            validation.validate = true;
+            Ok(())
+        } else if meta.path.is_ident("func") {
+            // builder_validation属性にfuncが複数指定されている場合はエラー
+            if validation.func.is_some() {
+                return Err(meta.error("only one `func` can be specified"));
+            }
+            let func: LitStr = meta.value()?.parse().map_err(|err| {
+                syn::Error::new(err.span(), "func must have a function name string")
+            })?;
+            validation.func = Some(format_ident!("{}", func.value()));
+            Ok(())
+        } else {
+            Err(meta.error("builder_validation supports only `validate` and `func`"))
+        }
+    })?;
+
+    // builder_validation属性にvalidateもfuncも指定されていない場合はエラー
+    if !validation.validate && validation.func.is_none() {
+        return Err(syn::Error::new_spanned(
+            validation_attrs[0],
+            "builder_validation must have `validate` or `func`",
         ));
     }
 
-    match &func_list[0] {
-        Lit::Str(s) => Ok(Some(format_ident!("{}", s.value()))),
-        _ => Err(syn::Error::new(
-            attrs[0].span(),
-            "func must have a function name string",
-        )),
-    }
+    Ok(validation)
+}
+
+/// ビルダーを構築する構造体のフィールドに付与された`builder`属性
+#[derive(Default)]
+struct BuilderFieldAttr {
+    /// 要素を1つずつ追加するsetterメソッドの名前
+    each: Option<Ident>,
+    /// 値を設定しなかった場合に、フィールドの型の`Default`の値を使用するか
+    default: bool,
 }
 
-/// ビルダーを構築する構造体のフィールドに付与された`builder`属性の`each`を取得する。
+/// ビルダーを構築する構造体のフィールドに付与された`builder`属性を取得する。
 ///
 /// ```text
 /// #[derive(Builder)]
 /// struct Foo {
 ///     #[builder(each = "each_name")]
-///     a: String,
+///     a: Vec<String>,
+///     #[builder(default)]
+///     b: OptionalRemarks,
 /// }
 /// ```
 ///
-/// 上記`each_name`を取得する。
-fn retrieve_builder_each(attrs: &[Attribute]) -> syn::Result<Option<Ident>> {
-    let name_values_list = retrieve_name_values_list(attrs, "builder")?;
+/// 上記`each_name`と、`b`フィールドに`default`が指定されていることを取得する。
+fn retrieve_builder_field_attr(attrs: &[Attribute]) -> syn::Result<BuilderFieldAttr> {
+    let builder_attrs = filter_attrs(attrs, "builder");
+    let mut field_attr = BuilderFieldAttr::default();
 
     // builder属性が指定されていない場合
-    if name_values_list.is_empty() {
-        return Ok(None);
+    if builder_attrs.is_empty() {
+        return Ok(field_attr);
     }
     // builder属性が2つ以上指定されている場合はエラー
-    if name_values_list.len() > 1 {
-        return Err(syn::Error::new(
-            attrs[0].span(),
+    if builder_attrs.len() > 1 {
+        return Err(syn::Error::new_spanned(
+            builder_attrs[1],
             "only one builder can be specified",
         ));
     }
-    // builder属性にeachのみ指定されているか確認
-    let name_values = &name_values_list[0];
-    if 1 < name_values.keys().len() {
-        return Err(syn::Error::new(
-            attrs[0].span(),
-            "builder must have only one `each` name value",
-        ));
-    }
 
-    // builder属性にeachが複数指定されている場合はエラー
-    let each_list = name_values
-        .get(&format_ident!("each"))
-        .ok_or(syn::Error::new(
-            attrs[0].span(),
-            "builder must have only one `each` name value",
-        ))?;
-    if 1 < each_list.len() {
-        return Err(syn::Error::new(
-            attrs[0].span(),
-            "only one each can be specified",
-        ));
-    }
+    // `default`は値を持たないため、名前と値のリストとしてではなく、メタ情報として解析
+    builder_attrs[0].parse_nested_meta(|meta| {
+        if meta.path.is_ident("each") {
+            // builder属性にeachが複数指定されている場合はエラー
+            if field_attr.each.is_some() {
+                return Err(meta.error("only one each can be specified"));
+            }
+            let each: LitStr = meta.value()?.parse().map_err(|err| {
+                syn::Error::new(err.span(), "each must have a method name string")
+            })?;
+            field_attr.each = Some(format_ident!("{}", each.value()));
+            Ok(())
+        } else if meta.path.is_ident("default") {
+            field_attr.default = true;
+            Ok(())
+        } else {
+            Err(meta.error("builder supports only `each` and `default`"))
+        }
+    })?;
 
-    match &each_list[0] {
-        Lit::Str(s) => Ok(Some(format_ident!("{}", s.value()))),
-        _ => Err(syn::Error::new(
-            attrs[0].span(),
-            "each must have a method name string",
-        )),
-    }
+    Ok(field_attr)
 }
 
 /// ビルダーを構築する構造体のフィールド情報
@@ -172,7 +201,7 @@ struct FieldInfo<'a> {
 /// ビルダーを構築する構造体のフィールドの識別子と型を取得する。
 fn retrieve_struct_field_ident_and_type_pairs(
     named_fields: &CommaPunctuatedFields,
-) -> syn::Result<Vec<FieldInfo>> {
+) -> syn::Result<Vec<FieldInfo<'_>>> {
     let mut fields = vec![];
     for named_field in named_fields {
         let ident = named_field.ident.as_ref();
@@ -235,15 +264,17 @@ fn impl_builder_new_method(vis: &Visibility, fields: &[FieldInfo]) -> TokenStrea
     }
 }
 
-/// ビルダーを構築する構造体のフィールドに付与された`builder`フィールドの`each`の値を取得する。
+/// ビルダーを構築する構造体のフィールドに付与された`builder`属性を取得する。
 ///
 /// # 戻り値
 ///
-/// フィールドの`builder`属性に`each`が存在する場合は`each`の値、存在しない場合は`None`を格納したベクタ
-fn retrieve_each_name_value(fields: &CommaPunctuatedFields) -> syn::Result<Vec<Option<Ident>>> {
+/// フィールドの順に`builder`属性を格納したベクタ
+fn retrieve_builder_field_attrs(
+    fields: &CommaPunctuatedFields,
+) -> syn::Result<Vec<BuilderFieldAttr>> {
     fields
         .iter()
-        .map(|f| retrieve_builder_each(&f.attrs))
+        .map(|f| retrieve_builder_field_attr(&f.attrs))
         .collect::<syn::Result<Vec<_>>>()
 }
 
@@ -251,13 +282,13 @@ fn retrieve_each_name_value(fields: &CommaPunctuatedFields) -> syn::Result<Vec<O
 fn impl_builder_setter_methods(
     vis: &Visibility,
     fields: &[FieldInfo],
-    each_attrs: &[Option<Ident>],
+    field_attrs: &[BuilderFieldAttr],
 ) -> TokenStream2 {
     let setters =
         fields
             .iter()
-            .zip(each_attrs)
-            .map(|(FieldInfo { ident, ty }, maybe_each)| {
+            .zip(field_attrs)
+            .map(|(FieldInfo { ident, ty }, BuilderFieldAttr { each: maybe_each, .. })| {
                 let has_each = maybe_each.is_some();
                 match field_type(ty) {
                     FieldType::Option(inner_ty) => {
@@ -300,15 +331,20 @@ fn impl_builder_setter_methods(
 /// * `vis` - `build`メソッドの可視性
 /// * `struct_ident` - ビルダーを構築する構造体の識別子
 /// * `field` - ビルダーを構築する構造体のフィールド
-/// * `func_ident` - ビルダーを構築する構造体を検証するメソッドの識別子
+/// * `field_attrs` - ビルダーを構築する構造体のフィールドに付与された`builder`属性
+/// * `validation` - ビルダーを構築する構造体の検証方法
 fn impl_builder_build_method(
     vis: &Visibility,
     struct_ident: &Ident,
     fields: &[FieldInfo],
-    func: Option<Ident>,
+    field_attrs: &[BuilderFieldAttr],
+    validation: BuilderValidation,
 ) -> TokenStream2 {
-    let field_tokens = fields.iter().map(|FieldInfo{ident, ty}|
+    let field_tokens = fields.iter().zip(field_attrs).map(|(FieldInfo{ident, ty}, field_attr)|
     match field_type(ty) {
+        FieldType::Raw if field_attr.default => quote! {
+            #ident: self.#ident.take().unwrap_or_default()
+        },
         FieldType::Option(_) => quote! {
             #ident: match self.#ident {
                 ::core::option::Option::Some(_) => ::core::option::Option::Some(self.#ident.take().unwrap()),
@@ -323,13 +359,17 @@ fn impl_builder_build_method(
     });
 
     let instance = format_ident!("{}", "instance");
-    let validator = match func {
-        Some(func) => {
-            let func_ident = format_ident!("{}", func);
-            quote!(
-                #instance.#func_ident()?;
-            )
-        }
+    // `validator::Validate`で検証した後、検証するメソッドを呼び出す
+    let validate = match validation.validate {
+        true => quote!(
+            ::validator::Validate::validate(&#instance)?;
+        ),
+        false => quote!(),
+    };
+    let func = match validation.func {
+        Some(func_ident) => quote!(
+            #instance.#func_ident()?;
+        ),
         None => quote!(),
     };
 
@@ -343,7 +383,8 @@ fn impl_builder_build_method(
                     #(#field_tokens),*
                 };
 
-                #validator
+                #validate
+                #func
 
                 Ok(#instance)
             }
@@ -10,6 +10,8 @@ mod optional_string_primitive;
 use optional_string_primitive::impl_optional_string_primitive;
 mod builder;
 use builder::impl_builder;
+mod map_from;
+use map_from::impl_map_from;
 
 /// `PrimitiveDisplay`導出マクロ
 ///
@@ -35,6 +37,10 @@ pub fn derive_primitive_display(input: TokenStream) -> TokenStream {
 /// `primitive`属性の`name`には、プリミティブの名前を指定する。
 /// `primitive`属性の`message`には、プリミティブの検証に失敗したときのメッセージを指定する。
 ///
+/// また、プリミティブの名前を表す`PRIMITIVE_NAME`定数と、`value`フィールドに付与された
+/// `validate`属性の`length`から`MIN_LEN`及び`MAX_LEN`定数を実装する。`metadata`関数は、
+/// これらと`regex`の正規表現を設定した`PrimitiveMetadata`を返す。
+///
 /// ```text
 /// #[derive(Validator, StringPrimitive)]
 /// #[primitive(
@@ -66,6 +72,9 @@ pub fn derive_string_primitive(input: TokenStream) -> TokenStream {
 ///
 /// `primitive`属性の`name`には、プリミティブの名前を指定する。
 ///
+/// また、プリミティブの名前を表す`PRIMITIVE_NAME`定数と、`validate`属性の`range`から`MIN`及び
+/// `MAX`定数を実装する。`metadata`関数は、これらを設定した`PrimitiveMetadata`を返す。
+///
 /// ```text
 /// #[derive(Validator, IntegerPrimitive)]
 /// #[primitive(name = "数量")]
@@ -92,6 +101,13 @@ pub fn derive_integer_primitive(input: TokenStream) -> TokenStream {
 /// `primitive`属性の`regex`には、格納する文字列がマッチする正規表現を指定する。
 /// `primitive`属性の`min`と`max`には、格納する文字列の最小及び最大長さを指定する。
 ///
+/// 格納している文字列を参照する`value`と`as_deref`、インスタンスを消費して文字列を返す
+/// `owned_value`と`unwrap_or_default`、及び文字列の参照を関数に渡す`map`を実装する。
+/// また、文字列を格納していないインスタンスを返す`Default`を実装する。
+///
+/// プリミティブの名前を表す`PRIMITIVE_NAME`定数と、指定された制約を表す`MIN_LEN`、`MAX_LEN`
+/// 及び`PATTERN`定数を実装する。`metadata`関数は、これらを設定した`PrimitiveMetadata`を返す。
+///
 /// ```text
 /// /// 携帯電話番号
 /// #[derive(Debug, Clone, PartialEq, Eq, Hash, OptionalStringPrimitive)]
@@ -123,6 +139,15 @@ pub fn derive_optional_string_primitive(input: TokenStream) -> TokenStream {
 ///
 /// 構造体のビルダーを実装する。
 ///
+/// `builder`属性の`each`を指定した`Vec`型のフィールドには、要素を1つずつ追加するsetterメソッドを
+/// 実装する。`builder`属性に`default`を指定したフィールドは、値を設定しなかった場合に、
+/// フィールドの型の`Default`の値で構築する。
+///
+/// 構造体に`builder_validation`属性を付与すると、`build`メソッドは構築した構造体を検証する。
+/// `validate`を指定した場合は`validator::Validate`で検証し、`func`を指定した場合は、指定した
+/// 名前のメソッドを呼び出して検証する。両方を指定した場合は、`validator::Validate`で検証した後、
+/// メソッドを呼び出す。
+///
 /// ```text
 /// #[derive(Builder)]
 /// pub struct Command {
@@ -131,6 +156,8 @@ pub fn derive_optional_string_primitive(input: TokenStream) -> TokenStream {
 ///     args: Vec<String>,
 ///     current_dir: Option<String>,
 ///     value: Option<u8>,
+///     #[builder(default)]
+///     verbose: bool,
 /// }
 ///
 /// let command = CommandBuilder::new()
@@ -142,6 +169,7 @@ pub fn derive_optional_string_primitive(input: TokenStream) -> TokenStream {
 ///     .build()
 ///     .unwrap();
 /// assert_eq!(command.executable, "cargo");
+/// assert!(!command.verbose);
 /// ```
 #[proc_macro_derive(Builder, attributes(builder_validation, builder))]
 pub fn derive_builder(input: TokenStream) -> TokenStream {
@@ -152,3 +180,42 @@ pub fn derive_builder(input: TokenStream) -> TokenStream {
         Err(err) => TokenStream::from(err.into_compile_error()),
     }
 }
+
+/// `MapFrom`導出マクロ
+///
+/// `map_from`属性の`source`に指定した型から、構造体への`From`トレイトを実装する。
+///
+/// 構造体の各フィールドには、変換元の同じ名前のフィールドの値を移動する。フィールドに付与した
+/// `map_from`属性で、変換元の値の取り出し方を指定できる。
+///
+/// * `rename` - 変換元のフィールドの名前、`user_permission.name`のようにドットで区切ったパスも指定可能
+/// * `value` - 変換元のフィールドの`value`フィールドを取り出す
+/// * `owned_value` - 変換元のフィールドの`owned_value`メソッドを呼び出す
+/// * `into` - 取り出した値を`Into`で変換する
+///
+/// 変換元に存在しないフィールドがある場合は、そのフィールドを指すコンパイルエラーになる。
+///
+/// ```text
+/// #[derive(MapFrom)]
+/// #[map_from(source = "User")]
+/// pub struct UserResBody {
+///     pub id: UserId,
+///     #[map_from(value)]
+///     pub email: String,
+///     #[map_from(owned_value)]
+///     pub remarks: Option<String>,
+///     #[map_from(rename = "last_sign_in_at")]
+///     pub last_logged_in_at: Option<OffsetDateTime>,
+///     #[map_from(rename = "user_permission.code", into)]
+///     pub user_permission_code: i16,
+/// }
+/// ```
+#[proc_macro_derive(MapFrom, attributes(map_from))]
+pub fn derive_map_from(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input as DeriveInput);
+
+    match impl_map_from(input) {
+        Ok(token_stream) => TokenStream::from(token_stream),
+        Err(err) => TokenStream::from(err.into_compile_error()),
+    }
+}
@@ -0,0 +1,204 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Data, DataStruct, DeriveInput, Fields, FieldsNamed, Ident, LitStr, Path, Token,
+};
+
+use crate::types::CommaPunctuatedFields;
+use crate::utils::filter_attrs;
+
+pub(crate) fn impl_map_from(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(DataStruct {
+        fields: Fields::Named(FieldsNamed { named, .. }),
+        ..
+    }) = &input.data
+    else {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "MapFrom supports only structs with named fields",
+        ));
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    // 変換元の型を取得
+    let source = retrieve_map_from_source(ident, &input.attrs)?;
+    // フィールドごとに、変換元の値を取得する式を生成
+    let value = quote!(value);
+    let field_tokens = impl_field_tokens(named, &value)?;
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#source> for #ident #ty_generics #where_clause {
+            fn from(#value: #source) -> Self {
+                Self {
+                    #(#field_tokens),*
+                }
+            }
+        }
+    })
+}
+
+/// 構造体に付与された`map_from`属性の`source`から、変換元の型を取得する。
+///
+/// ```text
+/// #[derive(MapFrom)]
+/// #[map_from(source = "User")]
+/// struct UserResBody { ... }
+/// ```
+///
+/// 上記`User`を取得する。
+fn retrieve_map_from_source(ident: &Ident, attrs: &[Attribute]) -> syn::Result<Path> {
+    let map_from_attrs = filter_attrs(attrs, "map_from");
+
+    // map_from属性が指定されていない場合はエラー
+    if map_from_attrs.is_empty() {
+        return Err(syn::Error::new(
+            ident.span(),
+            "MapFrom requires `#[map_from(source = \"...\")]`",
+        ));
+    }
+    // map_from属性が2つ以上指定されている場合はエラー
+    if map_from_attrs.len() > 1 {
+        return Err(syn::Error::new_spanned(
+            map_from_attrs[1],
+            "only one map_from can be specified",
+        ));
+    }
+
+    let mut source: Option<Path> = None;
+    map_from_attrs[0].parse_nested_meta(|meta| {
+        if !meta.path.is_ident("source") {
+            return Err(meta.error("map_from of struct supports only `source`"));
+        }
+        if source.is_some() {
+            return Err(meta.error("only one source can be specified"));
+        }
+        let lit: LitStr = meta.value()?.parse()?;
+        source = Some(lit.parse()?);
+        Ok(())
+    })?;
+
+    source.ok_or_else(|| {
+        syn::Error::new_spanned(map_from_attrs[0], "map_from must have `source` name value")
+    })
+}
+
+/// フィールドに付与された`map_from`属性
+#[derive(Default)]
+struct MapFromFieldAttr {
+    /// 変換元のフィールドのパス
+    ///
+    /// `None`の場合は、フィールドと同じ名前の変換元のフィールドを使用する。
+    rename: Option<Vec<Ident>>,
+    /// 変換元のフィールドの`value`フィールドを取り出すか
+    value: bool,
+    /// 変換元のフィールドの`owned_value`メソッドを呼び出すか
+    owned_value: bool,
+    /// 取り出した値を`Into`で変換するか
+    into: bool,
+}
+
+/// フィールドに付与された`map_from`属性を取得する。
+///
+/// ```text
+/// #[derive(MapFrom)]
+/// #[map_from(source = "User")]
+/// struct UserResBody {
+///     #[map_from(rename = "user_permission.name", value)]
+///     user_permission_name: String,
+///     #[map_from(owned_value)]
+///     remarks: Option<String>,
+///     #[map_from(rename = "last_sign_in_at")]
+///     last_logged_in_at: Option<OffsetDateTime>,
+///     #[map_from(into)]
+///     user_permission: UserPermissionBody,
+/// }
+/// ```
+fn retrieve_map_from_field_attr(attrs: &[Attribute]) -> syn::Result<MapFromFieldAttr> {
+    let map_from_attrs = filter_attrs(attrs, "map_from");
+    let mut field_attr = MapFromFieldAttr::default();
+
+    // map_from属性が指定されていない場合
+    if map_from_attrs.is_empty() {
+        return Ok(field_attr);
+    }
+    // map_from属性が2つ以上指定されている場合はエラー
+    if map_from_attrs.len() > 1 {
+        return Err(syn::Error::new_spanned(
+            map_from_attrs[1],
+            "only one map_from can be specified",
+        ));
+    }
+
+    map_from_attrs[0].parse_nested_meta(|meta| {
+        if meta.path.is_ident("rename") {
+            if field_attr.rename.is_some() {
+                return Err(meta.error("only one rename can be specified"));
+            }
+            // `user_permission.name`のように、ドットで区切られたフィールドのパスを解析
+            let lit: LitStr = meta.value()?.parse()?;
+            let segments = lit.parse_with(
+                syn::punctuated::Punctuated::<Ident, Token![.]>::parse_separated_nonempty,
+            )?;
+            field_attr.rename = Some(segments.into_iter().collect());
+        } else if meta.path.is_ident("value") {
+            field_attr.value = true;
+        } else if meta.path.is_ident("owned_value") {
+            field_attr.owned_value = true;
+        } else if meta.path.is_ident("into") {
+            field_attr.into = true;
+        } else {
+            return Err(meta.error(
+                "map_from of field supports only `rename`, `value`, `owned_value` and `into`",
+            ));
+        }
+        Ok(())
+    })?;
+
+    // valueとowned_valueは、どちらか一方のみ指定できる
+    if field_attr.value && field_attr.owned_value {
+        return Err(syn::Error::new_spanned(
+            map_from_attrs[0],
+            "value and owned_value can not be specified together",
+        ));
+    }
+
+    Ok(field_attr)
+}
+
+/// フィールドごとに、変換元の値からフィールドの値を取得する式を生成する。
+///
+/// 変換元にフィールドが存在しない場合のコンパイルエラーが、変換先のフィールドを指すように、
+/// 式に変換先のフィールドのスパンを付与する。
+///
+/// # 引数
+///
+/// * `fields` - 変換先の構造体のフィールド
+/// * `value` - 変換元の値を示す識別子
+fn impl_field_tokens(
+    fields: &CommaPunctuatedFields,
+    value: &TokenStream2,
+) -> syn::Result<Vec<TokenStream2>> {
+    fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            let span = field.span();
+            let attr = retrieve_map_from_field_attr(&field.attrs)?;
+            let path = attr.rename.unwrap_or_else(|| vec![ident.clone()]);
+            let mut expr = quote_spanned!(span=> #value #(.#path)*);
+            if attr.value {
+                expr = quote_spanned!(span=> #expr.value);
+            }
+            if attr.owned_value {
+                expr = quote_spanned!(span=> #expr.owned_value());
+            }
+            if attr.into {
+                expr = quote_spanned!(span=> ::core::convert::Into::into(#expr));
+            }
+
+            Ok(quote! { #ident: #expr })
+        })
+        .collect()
+}
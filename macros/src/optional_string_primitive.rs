@@ -1,8 +1,10 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
-use syn::{spanned::Spanned, DeriveInput, Lit};
+use syn::{DeriveInput, Fields, Lit};
 
-use crate::utils::{is_data_struct, retrieve_name_values_list};
+use crate::utils::{
+    filter_attrs, impl_primitive_metadata, is_data_struct, retrieve_name_values_list,
+};
 
 pub(crate) fn impl_optional_string_primitive(input: DeriveInput) -> syn::Result<TokenStream2> {
     let ident = &input.ident;
@@ -10,37 +12,90 @@ pub(crate) fn impl_optional_string_primitive(input: DeriveInput) -> syn::Result<
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
 
     // フィールドを持つ構造体であることを確認
-    is_data_struct(&input, "TupleOptionalStringPrimitive")?;
+    let data_struct = is_data_struct(&input, "OptionalStringPrimitive")?;
+    // `Option<String>`を1つだけ持つタプル構造体であることを確認
+    match &data_struct.fields {
+        Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {}
+        Fields::Unit => {
+            return Err(syn::Error::new(
+                ident.span(),
+                "OptionalStringPrimitive is expected a tuple struct with one `Option<String>`",
+            ))
+        }
+        fields => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "OptionalStringPrimitive is expected a tuple struct with one `Option<String>`",
+            ))
+        }
+    }
     // 検証属性を取得
     let primitive_attr = retrieve_primitive_attr(&input)?;
     // try_from_strメソッドを実装
     let try_from_str = impl_try_from_str_method(&primitive_attr);
+    // 制約を表現する定数とメタデータを実装
+    let metadata = impl_metadata(&primitive_attr);
 
     Ok(quote! {
         impl #impl_generics #ident #ty_generics #where_clause {
+            #metadata
+
             # try_from_str
 
+            /// 格納している文字列の参照を返す。
             pub fn value(&self) -> ::core::option::Option<&::std::primitive::str> {
                 self.0.as_deref()
             }
 
-            pub fn owned_value(&self) -> ::core::option::Option<::std::string::String> {
-                self.0.as_ref().map(|v| v.clone())
+            /// 格納している文字列の参照を返す。
+            ///
+            /// `Option<String>`の`as_deref`と同様に使用できるように、`value`と同じ値を返す。
+            pub fn as_deref(&self) -> ::core::option::Option<&::std::primitive::str> {
+                self.0.as_deref()
+            }
+
+            /// インスタンスを消費して、格納している文字列を返す。
+            pub fn owned_value(self) -> ::core::option::Option<::std::string::String> {
+                self.0
+            }
+
+            /// 文字列を格納している場合は、文字列の参照を関数に渡した結果を返す。
+            pub fn map<U, F>(&self, f: F) -> ::core::option::Option<U>
+            where
+                F: ::core::ops::FnOnce(&::std::primitive::str) -> U,
+            {
+                self.0.as_deref().map(f)
             }
 
+            /// インスタンスを消費して、格納している文字列を返す。
+            ///
+            /// 文字列を格納していない場合は、空文字を返す。
+            pub fn unwrap_or_default(self) -> ::std::string::String {
+                self.0.unwrap_or_default()
+            }
+
+            /// 文字列を格納していないインスタンスを返す。
             pub fn none() -> Self {
                 Self(::core::option::Option::None)
             }
 
+            /// 文字列を格納している場合は`true`を返す。
             pub fn is_some(&self) -> ::core::primitive::bool {
                 self.0.is_some()
             }
 
+            /// 文字列を格納していない場合は`true`を返す。
             pub fn is_none(&self) -> ::core::primitive::bool {
                 self.0.is_none()
             }
         }
 
+        impl ::core::default::Default for #ident {
+            fn default() -> Self {
+                Self::none()
+            }
+        }
+
         impl ::core::fmt::Display for #ident {
             fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 match &self.0 {
@@ -90,6 +145,40 @@ pub(crate) fn impl_optional_string_primitive(input: DeriveInput) -> syn::Result<
     })
 }
 
+/// `primitive`属性に指定された制約を表現する定数と、メタデータを返す`metadata`関数を実装する。
+fn impl_metadata(primitive_attr: &PrimitiveAttr) -> TokenStream2 {
+    let mut consts = vec![];
+    let mut metadata_fields = vec![];
+    if let Some(min) = primitive_attr.min {
+        consts.push(quote! {
+            /// 文字列の最小文字数
+            pub const MIN_LEN: ::core::primitive::usize = #min;
+        });
+        metadata_fields.push(quote!(min_len: ::core::option::Option::Some(Self::MIN_LEN)));
+    }
+    if let Some(max) = primitive_attr.max {
+        consts.push(quote! {
+            /// 文字列の最大文字数
+            pub const MAX_LEN: ::core::primitive::usize = #max;
+        });
+        metadata_fields.push(quote!(max_len: ::core::option::Option::Some(Self::MAX_LEN)));
+    }
+    if let Some(regex) = &primitive_attr.regex {
+        consts.push(quote! {
+            /// 文字列がマッチする正規表現
+            pub const PATTERN: &::std::primitive::str = #regex;
+        });
+        metadata_fields.push(quote!(pattern: ::core::option::Option::Some(Self::PATTERN)));
+    }
+    let metadata = impl_primitive_metadata(&primitive_attr.name, &metadata_fields);
+
+    quote! {
+        #metadata
+
+        #(#consts)*
+    }
+}
+
 fn impl_try_from_str_method(primitive_attr: &PrimitiveAttr) -> TokenStream2 {
     let mut validation_tokens: Vec<TokenStream2> = vec![];
     let name = &primitive_attr.name;
@@ -162,18 +251,25 @@ fn retrieve_primitive_attr(input: &DeriveInput) -> syn::Result<PrimitiveAttr> {
     let mut min: Option<usize> = None;
     let mut max: Option<usize> = None;
 
-    // primitive属性の名前と値を取得
-    let name_values_list = retrieve_name_values_list(&input.attrs, "primitive")?;
-
-    // primitive属性が2つ以上指定されている場合はエラー
-    if name_values_list.len() > 1 {
+    let primitive_attrs = filter_attrs(&input.attrs, "primitive");
+    // primitive属性が指定されていない場合はエラー
+    if primitive_attrs.is_empty() {
         return Err(syn::Error::new(
-            input.attrs[1].span(),
+            input.ident.span(),
+            "OptionalStringPrimitive must have the `primitive` attribute",
+        ));
+    }
+    // primitive属性が2つ以上指定されている場合は、2つ目の属性を指すエラー
+    if primitive_attrs.len() > 1 {
+        return Err(syn::Error::new_spanned(
+            primitive_attrs[1],
             "only one primitive can be specified",
         ));
     }
 
-    let name_values = &name_values_list[0];
+    // primitive属性の名前と値を取得
+    let name_values_list = retrieve_name_values_list(&input.attrs, "primitive")?;
+    let name_values = name_values_list.first().cloned().unwrap_or_default();
     // すべてのキーが1つだけ指定されているか確認
     for (ident, values) in name_values.iter() {
         if values.len() > 1 {
@@ -211,8 +307,8 @@ fn retrieve_primitive_attr(input: &DeriveInput) -> syn::Result<PrimitiveAttr> {
 
     // nameが指定されていない場合はエラー
     if name.is_none() {
-        return Err(syn::Error::new(
-            input.ident.span(),
+        return Err(syn::Error::new_spanned(
+            primitive_attrs[0],
             "primitive attribute must have `name`",
         ));
     }
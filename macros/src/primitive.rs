@@ -1,12 +1,14 @@
 use proc_macro2::TokenStream as TokenStream2;
 use quote::{format_ident, quote};
 use syn::{
-    punctuated::Punctuated, Attribute, DataStruct, DeriveInput, Expr, Field, Fields, FieldsNamed,
-    Ident, Lit, MetaNameValue,
+    punctuated::Punctuated, spanned::Spanned, Attribute, DataStruct, DeriveInput, Expr, Field,
+    Fields, FieldsNamed, Ident, Lit, Meta, MetaNameValue, Token,
 };
 
 use crate::types::{CommaPunctuatedMetaList, CommaPunctuatedNameValues};
-use crate::utils::{is_data_struct, retrieve_name_values_list};
+use crate::utils::{
+    filter_attrs, impl_primitive_metadata, is_data_struct, retrieve_name_values_list,
+};
 
 pub(crate) fn impl_primitive_display(input: DeriveInput) -> syn::Result<TokenStream2> {
     let ident = &input.ident;
@@ -20,7 +22,7 @@ pub(crate) fn impl_primitive_display(input: DeriveInput) -> syn::Result<TokenStr
     let fields = retrieve_named_fields(ident, data_struct, "PrimitiveDisplay")?;
 
     // 構造体が`value`フィールドを持つか確認
-    if !has_value_field(fields) {
+    if find_value_field(fields).is_none() {
         return Err(syn::Error::new(
             ident.span(),
             "PrimitiveDisplay must have the `value` field",
@@ -37,20 +39,21 @@ pub(crate) fn impl_primitive_display(input: DeriveInput) -> syn::Result<TokenStr
 }
 
 /// 構造体の名前付きフィールドを取得する。
+///
+/// タプル構造体の場合のエラーはフィールドを、ユニット構造体の場合のエラーは構造体の識別子を指す。
 fn retrieve_named_fields<'a>(
     ident: &'a Ident,
     data_struct: &'a DataStruct,
     macro_name: &str,
 ) -> syn::Result<&'a FieldsNamed> {
+    let message = format!(
+        "{} is expected a struct contain the `value` field",
+        macro_name
+    );
     match &data_struct.fields {
         Fields::Named(fields) => Ok(fields),
-        _ => Err(syn::Error::new(
-            ident.span(),
-            format!(
-                "{} is expected a struct contain the `value` field",
-                macro_name
-            ),
-        )),
+        Fields::Unnamed(fields) => Err(syn::Error::new_spanned(fields, message)),
+        Fields::Unit => Err(syn::Error::new(ident.span(), message)),
     }
 }
 
@@ -70,15 +73,42 @@ pub(crate) fn impl_string_primitive(input: DeriveInput) -> syn::Result<TokenStre
 
     // 構造体が`value`フィールドを持つか確認
     // FIXME: `value`フィールドが`String`型であることを確認する実装
-    if !has_value_field(fields) {
+    let Some(field) = find_value_field(fields) else {
         return Err(syn::Error::new(
             ident.span(),
             "StringPrimitive must have the `value` field of type `String`",
         ));
+    };
+    // `value`フィールドに付与された`validate`属性から、文字数と正規表現の制約を取得
+    let constraints = retrieve_validate_string_attrs(field)?;
+    let mut consts = vec![];
+    let mut metadata_fields = vec![];
+    if let Some(min_len) = &constraints.min_len {
+        consts.push(quote! {
+            /// 文字列の最小文字数
+            pub const MIN_LEN: ::core::primitive::usize = (#min_len) as ::core::primitive::usize;
+        });
+        metadata_fields.push(quote!(min_len: ::core::option::Option::Some(Self::MIN_LEN)));
+    }
+    if let Some(max_len) = &constraints.max_len {
+        consts.push(quote! {
+            /// 文字列の最大文字数
+            pub const MAX_LEN: ::core::primitive::usize = (#max_len) as ::core::primitive::usize;
+        });
+        metadata_fields.push(quote!(max_len: ::core::option::Option::Some(Self::MAX_LEN)));
+    }
+    // 正規表現は静的変数に格納されているため定数にできず、メタデータのみに設定
+    if let Some(regex_path) = &constraints.regex_path {
+        metadata_fields.push(quote!(pattern: ::core::option::Option::Some((#regex_path).as_str())));
     }
+    let metadata = impl_primitive_metadata(&name, &metadata_fields);
 
     Ok(quote! {
         impl #impl_generics #ident #ty_generics #where_clause {
+            #metadata
+
+            #(#consts)*
+
             pub fn new<T: ::std::string::ToString>(value: T) -> DomainResult<Self> {
                 let value = value.to_string().trim().to_string();
                 if value.is_empty() {
@@ -100,12 +130,83 @@ pub(crate) fn impl_string_primitive(input: DeriveInput) -> syn::Result<TokenStre
     })
 }
 
-/// 構造体が`value`フィールドを持つか確認する。
-fn has_value_field(fields: &FieldsNamed) -> bool {
+/// 構造体の`value`フィールドを取得する。
+fn find_value_field(fields: &FieldsNamed) -> Option<&Field> {
     fields
         .named
         .iter()
-        .any(|f| *f.ident.as_ref().unwrap() == "value")
+        .find(|f| *f.ident.as_ref().unwrap() == "value")
+}
+
+/// `value`フィールドに付与された`validate`属性の、文字列の制約
+#[derive(Default)]
+struct ValidateString {
+    /// `length`属性の`min`、または`equal`
+    min_len: Option<Expr>,
+    /// `length`属性の`max`、または`equal`
+    max_len: Option<Expr>,
+    /// `regex`属性の`path`
+    regex_path: Option<Expr>,
+}
+
+/// `validate`属性内の`length`属性の`min`と`max`、及び`regex`属性の`path`を取得する。
+///
+/// `#[validate(length(min = 1, max = 40))]`
+///                          ^        ^^
+/// `#[validate(regex(path = "*POSTAL_CODE_EXPRESSION"))]`
+///                           ^^^^^^^^^^^^^^^^^^^^^^^
+fn retrieve_validate_string_attrs(field: &Field) -> syn::Result<ValidateString> {
+    let mut constraints = ValidateString::default();
+
+    for validate_attr in filter_attrs(&field.attrs, "validate") {
+        // `email`のように値を持たない検証も指定されるため、メタ情報のリストとして解析
+        let metas: Punctuated<Meta, Token![,]> = validate_attr
+            .parse_args_with(Punctuated::parse_terminated)
+            .map_err(|err| {
+                syn::Error::new_spanned(
+                    validate_attr,
+                    format!("failed to parse attribute: {}", err),
+                )
+            })?;
+        for meta in metas.iter() {
+            let Meta::List(meta_list) = meta else {
+                continue;
+            };
+            let is_length = meta_list.path.is_ident("length");
+            if !is_length && !meta_list.path.is_ident("regex") {
+                continue;
+            }
+            let name_values: CommaPunctuatedNameValues = meta_list
+                .parse_args_with(Punctuated::parse_terminated)
+                .map_err(|err| {
+                    syn::Error::new_spanned(
+                        meta_list,
+                        format!("failed to parse attribute: {}", err),
+                    )
+                })?;
+            for nv in name_values.iter() {
+                if is_length {
+                    if nv.path.is_ident("min") || nv.path.is_ident("equal") {
+                        constraints.min_len = Some(nv.value.clone());
+                    }
+                    if nv.path.is_ident("max") || nv.path.is_ident("equal") {
+                        constraints.max_len = Some(nv.value.clone());
+                    }
+                } else if nv.path.is_ident("path") {
+                    // `path = "*POSTAL_CODE_EXPRESSION"`のように、文字列で指定された式を解析
+                    constraints.regex_path = Some(match &nv.value {
+                        Expr::Lit(syn::ExprLit {
+                            lit: Lit::Str(lit_str),
+                            ..
+                        }) => lit_str.parse()?,
+                        expr => expr.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    Ok(constraints)
 }
 
 struct StringPrimitiveAttr {
@@ -126,29 +227,32 @@ fn retrieve_primitive_info(ident: &Ident, attrs: &[Attribute]) -> syn::Result<St
     let mut name: Option<String> = None;
     let mut message: Option<String> = None;
 
-    let name_value_list = retrieve_name_values_list(attrs, "primitive")?;
+    let primitive_attrs = filter_attrs(attrs, "primitive");
     // primitive属性が付与されていない場合はエラー
-    if name_value_list.is_empty() {
+    if primitive_attrs.is_empty() {
         return Err(syn::Error::new(
             ident.span(),
             "domain primitive must have the `primitive` attribute",
         ));
     }
-    // primitive属性が1つより多く付与されている場合はエラー
-    if 1 < name_value_list.len() {
-        return Err(syn::Error::new(
-            ident.span(),
+    // primitive属性が1つより多く付与されている場合は、2つ目の属性を指すエラー
+    if 1 < primitive_attrs.len() {
+        return Err(syn::Error::new_spanned(
+            primitive_attrs[1],
             "domain primitive only have one `primitive` attribute",
         ));
     }
+    let primitive_attr = primitive_attrs[0];
+    let name_value_list = retrieve_name_values_list(attrs, "primitive")?;
     // primitive属性の名前と値の組みは2つのみ
-    let name_values = &name_value_list[0];
-    if name_values.len() != 2 {
-        return Err(syn::Error::new(
-            ident.span(),
+    let name_values = name_value_list.first();
+    if name_values.map_or(0, |name_values| name_values.len()) != 2 {
+        return Err(syn::Error::new_spanned(
+            primitive_attr,
             "`primitive` attributes must have `name` and `message`",
         ));
     }
+    let name_values = name_values.unwrap();
 
     // nameの値を取得
     if let Some(lits) = name_values.get(&format_ident!("name")) {
@@ -164,14 +268,14 @@ fn retrieve_primitive_info(ident: &Ident, attrs: &[Attribute]) -> syn::Result<St
     }
 
     if name.is_none() {
-        return Err(syn::Error::new(
-            ident.span(),
+        return Err(syn::Error::new_spanned(
+            primitive_attr,
             "`primitive` must have `name`",
         ));
     }
     if message.is_none() {
-        return Err(syn::Error::new(
-            ident.span(),
+        return Err(syn::Error::new_spanned(
+            primitive_attr,
             "`primitive` must have `message`",
         ));
     }
@@ -190,20 +294,20 @@ pub(crate) fn impl_integer_primitive(input: DeriveInput) -> syn::Result<TokenStr
     // フィールドを持つ構造体であることを確認
     let data_struct = is_data_struct(&input, "IntegerPrimitive")?;
     // データ構造に付与された`primitive`属性の`name`の値を取得
-    let name_values_list = retrieve_name_values_list(&input.attrs, "primitive")?;
-    if name_values_list.is_empty() {
-        return Err(syn::Error::new_spanned(
-            &input,
+    let primitive_attrs = filter_attrs(&input.attrs, "primitive");
+    if primitive_attrs.is_empty() {
+        return Err(syn::Error::new(
+            ident.span(),
             "PrimitiveInteger must have the `primitive` attribute",
         ));
     }
+    let name_values_list = retrieve_name_values_list(&input.attrs, "primitive")?;
     let name_values = name_values_list
         .first()
-        .unwrap()
-        .get(&format_ident!("name"));
+        .and_then(|name_values| name_values.get(&format_ident!("name")));
     if name_values.is_none() {
         return Err(syn::Error::new_spanned(
-            &input,
+            primitive_attrs[0],
             "`primitive` attribute must have the `name`",
         ));
     }
@@ -218,15 +322,18 @@ pub(crate) fn impl_integer_primitive(input: DeriveInput) -> syn::Result<TokenStr
     // タプル構造体でないことを確認
     if data_struct.fields.iter().any(|f| f.ident.is_none()) {
         return Err(syn::Error::new_spanned(
-            &input,
+            &data_struct.fields,
             "PrimitiveInteger must be struct",
         ));
     }
 
     // `value`フィールドを持つか確認
     if data_struct.fields.len() != 1 {
-        return Err(syn::Error::new_spanned(
-            &input,
+        return Err(syn::Error::new(
+            match data_struct.fields.is_empty() {
+                true => ident.span(),
+                false => data_struct.fields.span(),
+            },
             "PrimitiveInteger can have at least one `value` field",
         ));
     }
@@ -236,7 +343,7 @@ pub(crate) fn impl_integer_primitive(input: DeriveInput) -> syn::Result<TokenStr
         .iter()
         .find(|f| f.ident.as_ref().unwrap() == "value")
         .ok_or(syn::Error::new_spanned(
-            &input,
+            &data_struct.fields,
             "PrimitiveInteger must have one `value` field",
         ))?;
     // `value`フィールドの型を取得
@@ -254,6 +361,25 @@ pub(crate) fn impl_integer_primitive(input: DeriveInput) -> syn::Result<TokenStr
         },
         _ => quote! {},
     };
+    let mut consts = vec![];
+    let mut metadata_fields = vec![];
+    if let Some(min) = range.min {
+        consts.push(quote! {
+            /// 最小値
+            pub const MIN: #ty = #min as #ty;
+        });
+        metadata_fields
+            .push(quote!(min: ::core::option::Option::Some(Self::MIN as ::core::primitive::i64)));
+    }
+    if let Some(max) = range.max {
+        consts.push(quote! {
+            /// 最大値
+            pub const MAX: #ty = #max as #ty;
+        });
+        metadata_fields
+            .push(quote!(max: ::core::option::Option::Some(Self::MAX as ::core::primitive::i64)));
+    }
+    let metadata = impl_primitive_metadata(&name.value(), &metadata_fields);
     let max_token = match range.max {
         Some(max) => quote! {
             if value > #max as #ty {
@@ -267,6 +393,10 @@ pub(crate) fn impl_integer_primitive(input: DeriveInput) -> syn::Result<TokenStr
 
     Ok(quote! {
         impl #impl_generics #ident #ty_generics #where_clause {
+            #metadata
+
+            #(#consts)*
+
             pub fn new(value: #ty) -> DomainResult<Self> {
                 #min_token
                 #max_token
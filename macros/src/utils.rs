@@ -1,23 +1,77 @@
 use std::collections::HashMap;
 
-use syn::{punctuated::Punctuated, Attribute, Data, DataStruct, DeriveInput, Expr, Ident, Lit};
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    punctuated::Punctuated, Attribute, Data, DataEnum, DataStruct, DataUnion, DeriveInput, Expr,
+    Ident, Lit,
+};
 
 use crate::types::CommaPunctuatedNameValues;
 
 /// フィールドを持つ構造体であることを確認する。
+///
+/// 構造体でない場合のエラーは、`enum`または`union`キーワードを指す。
 pub(crate) fn is_data_struct<'a>(
     input: &'a DeriveInput,
     macro_name: &str,
 ) -> syn::Result<&'a DataStruct> {
-    match &input.data {
-        Data::Struct(data_struct) => Ok(data_struct),
-        _ => Err(syn::Error::new(
-            input.ident.span(),
-            format!("{} is expected a struct", macro_name),
-        )),
+    let span = match &input.data {
+        Data::Struct(data_struct) => return Ok(data_struct),
+        Data::Enum(DataEnum { enum_token, .. }) => enum_token.span,
+        Data::Union(DataUnion { union_token, .. }) => union_token.span,
+    };
+
+    Err(syn::Error::new(
+        span,
+        format!("{} is expected a struct", macro_name),
+    ))
+}
+
+/// ドメインプリミティブの名前を表現する定数と、メタデータを返す`metadata`関数を実装する。
+///
+/// `metadata`関数が返す`PrimitiveMetadata`のうち、`fields`で設定しないフィールドは`None`になる。
+///
+/// # 引数
+///
+/// * `name` - ドメインプリミティブの名前
+/// * `fields` - 制約が判明している`PrimitiveMetadata`のフィールドに値を設定するトークン
+///
+/// # 戻り値
+///
+/// 定数と`metadata`関数のトークンストリーム
+pub(crate) fn impl_primitive_metadata(name: &str, fields: &[TokenStream2]) -> TokenStream2 {
+    quote! {
+        /// ドメインプリミティブの名前
+        pub const PRIMITIVE_NAME: &::std::primitive::str = #name;
+
+        /// ドメインプリミティブの名前と制約を表現するメタデータを返す。
+        pub fn metadata() -> PrimitiveMetadata {
+            PrimitiveMetadata {
+                #(#fields,)*
+                ..PrimitiveMetadata::new(Self::PRIMITIVE_NAME)
+            }
+        }
     }
 }
 
+/// 識別子に付与された属性のうち、指定された名前の属性をすべて取得する。
+///
+/// # 引数
+///
+/// * `attrs` - 識別子に付けられた属性のスライス
+/// * `attr_name` - 属性の名前
+///
+/// # 戻り値
+///
+/// 指定された名前の属性を格納したベクタ
+pub(crate) fn filter_attrs<'a>(attrs: &'a [Attribute], attr_name: &str) -> Vec<&'a Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident(attr_name))
+        .collect()
+}
+
 /// 識別子に付与された属性について、それらの名前と値をすべて取得する。
 ///
 /// ```text
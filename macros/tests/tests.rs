@@ -1,7 +1,12 @@
 use validator::Validate;
 
+use domain::models::primitives::{
+    EmailAddress, OptionalMobilePhoneNumber, OptionalRemarks, PostalCode, PrimitiveMetadata,
+};
 use domain::{DomainError, DomainResult};
-use macros::{IntegerPrimitive, OptionalStringPrimitive, PrimitiveDisplay, StringPrimitive};
+use macros::{
+    Builder, IntegerPrimitive, MapFrom, OptionalStringPrimitive, PrimitiveDisplay, StringPrimitive,
+};
 
 /// `Display`トレイトを実装したドメインプリミティブを実装できることを確認
 #[test]
@@ -26,6 +31,7 @@ struct TestStringPrimitive {
     value: String,
 }
 
+#[allow(dead_code)]
 #[derive(Validate, IntegerPrimitive)]
 #[primitive(name = "整数プリミティブ")]
 struct TestIntegerPrimitive {
@@ -111,6 +117,25 @@ fn mobile_phone_number_can_be_constructed_from_a_valid_string() {
     assert_eq!(Some("090-1234-5678"), phone_number.value())
 }
 
+/// オプショナル文字列プリミティブが格納している文字列を、参照または所有権とともに取得できることを確認
+#[test]
+fn optional_string_primitive_accessors() {
+    let some = OptionalMobileNumber::try_from("090-1234-5678").unwrap();
+    let none = OptionalMobileNumber::none();
+
+    assert_eq!(Some("090-1234-5678"), some.as_deref());
+    assert_eq!(None, none.as_deref());
+    assert_eq!(Some(13), some.map(str::len));
+    assert_eq!(None, none.map(str::len));
+    assert_eq!(
+        Some(String::from("090-1234-5678")),
+        some.clone().owned_value()
+    );
+    assert_eq!(None, none.clone().owned_value());
+    assert_eq!("090-1234-5678", some.unwrap_or_default());
+    assert_eq!("", none.unwrap_or_default());
+}
+
 /// 携帯電話番号の形式として妥当でない文字列から携帯電話番号を構築できることを確認
 #[test]
 fn mobile_phone_number_can_not_be_constructed_from_an_invalid_string() {
@@ -124,6 +149,67 @@ fn mobile_phone_number_can_not_be_constructed_from_an_invalid_string() {
     );
 }
 
+/// 文字列プリミティブが、`primitive`属性と`validate`属性から名前と制約を表現する定数とメタデータを持つことを確認
+#[test]
+fn string_primitive_metadata() {
+    assert_eq!("Eメールアドレス", EmailAddress::PRIMITIVE_NAME);
+    assert_eq!(6, EmailAddress::MIN_LEN);
+    assert_eq!(254, EmailAddress::MAX_LEN);
+    assert_eq!(
+        PrimitiveMetadata {
+            min_len: Some(6),
+            max_len: Some(254),
+            ..PrimitiveMetadata::new("Eメールアドレス")
+        },
+        EmailAddress::metadata()
+    );
+    assert_eq!(
+        PrimitiveMetadata {
+            pattern: Some("^[0-9]{3}-[0-9]{4}$"),
+            ..PrimitiveMetadata::new("郵便番号")
+        },
+        PostalCode::metadata()
+    );
+}
+
+/// オプショナル文字列プリミティブが、`primitive`属性から名前と制約を表現する定数とメタデータを持つことを確認
+#[test]
+fn optional_string_primitive_metadata() {
+    assert_eq!("備考", OptionalRemarks::PRIMITIVE_NAME);
+    assert_eq!(400, OptionalRemarks::MAX_LEN);
+    assert_eq!(
+        PrimitiveMetadata {
+            max_len: Some(400),
+            ..PrimitiveMetadata::new("備考")
+        },
+        OptionalRemarks::metadata()
+    );
+    assert_eq!(
+        r"^0[789]0-[0-9]{4}-[0-9]{4}$",
+        OptionalMobilePhoneNumber::PATTERN
+    );
+    assert_eq!(
+        Some(OptionalMobilePhoneNumber::PATTERN),
+        OptionalMobilePhoneNumber::metadata().pattern
+    );
+}
+
+/// 整数プリミティブが、`primitive`属性と`validate`属性から名前と制約を表現する定数とメタデータを持つことを確認
+#[test]
+fn integer_primitive_metadata() {
+    assert_eq!("整数プリミティブ", TestIntegerPrimitive::PRIMITIVE_NAME);
+    assert_eq!(0, TestIntegerPrimitive::MIN);
+    assert_eq!(10, TestIntegerPrimitive::MAX);
+    assert_eq!(
+        PrimitiveMetadata {
+            min: Some(0),
+            max: Some(10),
+            ..PrimitiveMetadata::new("整数プリミティブ")
+        },
+        TestIntegerPrimitive::metadata()
+    );
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, OptionalStringPrimitive)]
 #[primitive(name = "オプショナル文字列", max = 10)]
 pub struct MaxLengthOptionalString(Option<String>);
@@ -275,3 +361,187 @@ fn optional_string_can_not_be_constructed_from_non_matching_string() {
         );
     }
 }
+
+/// オプショナル文字列プリミティブの`Default`は、文字列を格納していないことを確認
+#[test]
+fn optional_string_primitive_default_is_none() {
+    assert!(OptionalMobileNumber::default().is_none());
+    assert_eq!(
+        OptionalMobileNumber::none(),
+        OptionalMobileNumber::default()
+    );
+}
+
+#[derive(Debug, Builder)]
+pub struct Contact {
+    name: String,
+    #[builder(default)]
+    mobile_phone_number: OptionalMobileNumber,
+    #[builder(default)]
+    retries: u8,
+}
+
+/// `builder`属性に`default`を指定したフィールドは、値を設定しなかった場合に`Default`の値で構築され、
+/// 値を設定した場合はその値で構築されることを確認
+#[test]
+fn builder_uses_default_for_fields_with_default_attribute() {
+    let contact = ContactBuilder::new()
+        .name(String::from("foo"))
+        .build()
+        .unwrap();
+    assert_eq!("foo", contact.name);
+    assert!(contact.mobile_phone_number.is_none());
+    assert_eq!(0, contact.retries);
+
+    let contact = ContactBuilder::new()
+        .name(String::from("foo"))
+        .mobile_phone_number(OptionalMobileNumber::try_from("090-1234-5678").unwrap())
+        .retries(3)
+        .build()
+        .unwrap();
+    assert_eq!(Some("090-1234-5678"), contact.mobile_phone_number.value());
+    assert_eq!(3, contact.retries);
+
+    // `default`を指定していないフィールドは、値を設定しなければならない
+    assert!(ContactBuilder::new().build().is_err());
+}
+
+/// 変換元の名前
+pub struct SourceName {
+    value: String,
+}
+
+/// 変換元の権限
+pub struct SourcePermission {
+    code: u8,
+    name: SourceName,
+}
+
+/// 変換元
+pub struct Source {
+    id: u32,
+    name: SourceName,
+    mobile_phone_number: OptionalMobileNumber,
+    permission: SourcePermission,
+    signed_in_at: Option<u64>,
+}
+
+#[derive(Debug, MapFrom)]
+#[map_from(source = "Source")]
+pub struct Target {
+    id: u32,
+    #[map_from(value)]
+    name: String,
+    #[map_from(owned_value)]
+    mobile_phone_number: Option<String>,
+    #[map_from(rename = "permission.code", into)]
+    permission_code: u16,
+    #[map_from(rename = "permission.name", value)]
+    permission_name: String,
+    #[map_from(rename = "signed_in_at")]
+    last_signed_in_at: Option<u64>,
+}
+
+/// 変換元のフィールドを、名前の変更、プリミティブの値の取り出し、及び`Option`のまま移動して、
+/// 変換先を構築できることを確認
+#[test]
+fn map_from_converts_source_fields() {
+    let source = |signed_in_at| Source {
+        id: 1,
+        name: SourceName {
+            value: String::from("foo"),
+        },
+        mobile_phone_number: OptionalMobileNumber::try_from("090-1234-5678").unwrap(),
+        permission: SourcePermission {
+            code: 2,
+            name: SourceName {
+                value: String::from("general"),
+            },
+        },
+        signed_in_at,
+    };
+
+    let target = Target::from(source(Some(42)));
+    assert_eq!(1, target.id);
+    assert_eq!("foo", target.name);
+    assert_eq!(
+        Some(String::from("090-1234-5678")),
+        target.mobile_phone_number
+    );
+    assert_eq!(2, target.permission_code);
+    assert_eq!("general", target.permission_name);
+    assert_eq!(Some(42), target.last_signed_in_at);
+
+    let target = Target::from(source(None));
+    assert_eq!(None, target.last_signed_in_at);
+}
+
+#[derive(Debug, Validate, Builder)]
+#[builder_validation(validate)]
+pub struct Account {
+    #[validate(length(min = 1, max = 10, message = "名前は1文字以上10文字以下です。"))]
+    name: String,
+    #[validate(length(max = 20, message = "備考は20文字以下です。"))]
+    remarks: String,
+}
+
+/// `builder_validation`属性に`validate`を指定した場合、`validator::Validate`で検証して、
+/// フィールドごとのメッセージをエラーで返すことを確認
+#[test]
+fn builder_validates_with_validator() {
+    let account = AccountBuilder::new()
+        .name(String::from("foo"))
+        .remarks(String::new())
+        .build()
+        .unwrap();
+    assert_eq!("foo", account.name);
+
+    let err = AccountBuilder::new()
+        .name(String::new())
+        .remarks("a".repeat(21))
+        .build()
+        .unwrap_err();
+    let errors = err.downcast_ref::<validator::ValidationErrors>().unwrap();
+    let field_errors = errors.field_errors();
+    assert_eq!(
+        Some("名前は1文字以上10文字以下です。"),
+        field_errors["name"][0].message.as_deref()
+    );
+    assert_eq!(
+        Some("備考は20文字以下です。"),
+        field_errors["remarks"][0].message.as_deref()
+    );
+}
+
+#[derive(Debug, Validate, Builder)]
+#[builder_validation(validate, func = "validate_range")]
+pub struct Range {
+    #[validate(range(max = 100, message = "開始は100以下です。"))]
+    start: u32,
+    end: u32,
+}
+
+impl Range {
+    fn validate_range(&self) -> Result<(), String> {
+        match self.start <= self.end {
+            true => Ok(()),
+            false => Err(String::from("開始は終了以下です。")),
+        }
+    }
+}
+
+/// `builder_validation`属性に`validate`と`func`を指定した場合、`validator::Validate`で検証した後、
+/// 指定したメソッドで検証することを確認
+#[test]
+fn builder_validates_with_validator_and_then_func() {
+    let range = RangeBuilder::new().start(1).end(2).build().unwrap();
+    assert_eq!((1, 2), (range.start, range.end));
+
+    // `validator::Validate`による検証が先に失敗
+    let err = RangeBuilder::new().start(101).end(0).build().unwrap_err();
+    assert!(err.downcast_ref::<validator::ValidationErrors>().is_some());
+
+    // `validator::Validate`による検証に成功した後、メソッドによる検証が失敗
+    let err = RangeBuilder::new().start(2).end(1).build().unwrap_err();
+    assert_eq!("開始は終了以下です。", err.to_string());
+}
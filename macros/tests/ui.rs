@@ -0,0 +1,8 @@
+/// マクロの誤用がコンパイルエラーになり、エラーが原因となったトークンを指すことを確認
+///
+/// `.stderr`を更新する場合は、`TRYBUILD=overwrite cargo test -p macros --test ui`を実行する。
+#[test]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
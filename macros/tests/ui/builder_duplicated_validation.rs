@@ -0,0 +1,10 @@
+use macros::Builder;
+
+#[derive(Builder)]
+#[builder_validation(validate)]
+#[builder_validation(func = "check")]
+struct Account {
+    name: String,
+}
+
+fn main() {}
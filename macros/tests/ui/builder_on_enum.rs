@@ -0,0 +1,9 @@
+use macros::Builder;
+
+#[derive(Builder)]
+enum Shape {
+    Circle,
+    Square,
+}
+
+fn main() {}
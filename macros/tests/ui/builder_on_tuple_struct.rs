@@ -0,0 +1,6 @@
+use macros::Builder;
+
+#[derive(Builder)]
+struct Point(i32, i32);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use macros::Builder;
+
+#[derive(Builder)]
+struct Account {
+    name: String,
+    #[builder(skip)]
+    remarks: Option<String>,
+}
+
+fn main() {}
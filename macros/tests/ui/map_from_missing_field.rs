@@ -0,0 +1,16 @@
+use macros::MapFrom;
+
+struct User {
+    id: u32,
+    name: String,
+}
+
+#[derive(MapFrom)]
+#[map_from(source = "User")]
+struct UserResBody {
+    id: u32,
+    name: String,
+    email: String,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use macros::OptionalStringPrimitive;
+
+#[derive(OptionalStringPrimitive)]
+#[primitive(name = "remarks", max = 400)]
+struct Remarks { value: Option<String> }
+
+fn main() {}
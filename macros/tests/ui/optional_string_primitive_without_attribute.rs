@@ -0,0 +1,6 @@
+use macros::OptionalStringPrimitive;
+
+#[derive(OptionalStringPrimitive)]
+struct Remarks(Option<String>);
+
+fn main() {}
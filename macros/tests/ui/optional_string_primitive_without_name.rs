@@ -0,0 +1,7 @@
+use macros::OptionalStringPrimitive;
+
+#[derive(OptionalStringPrimitive)]
+#[primitive(min = 1, max = 400)]
+struct Remarks(Option<String>);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use macros::StringPrimitive;
+
+#[derive(StringPrimitive)]
+#[primitive(name = "code", message = "invalid code")]
+#[primitive(name = "code", message = "invalid code")]
+struct Code {
+    value: String,
+}
+
+fn main() {}
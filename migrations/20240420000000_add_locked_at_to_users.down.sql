@@ -0,0 +1,3 @@
+-- ユーザー・テーブルから、ユーザーのアカウントをロックした日時を削除
+ALTER TABLE users
+DROP COLUMN IF EXISTS locked_at;
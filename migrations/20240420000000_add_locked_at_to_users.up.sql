@@ -0,0 +1,3 @@
+-- ユーザー・テーブルに、ユーザーのアカウントをロックした日時を追加
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS locked_at TIMESTAMP WITH TIME ZONE;
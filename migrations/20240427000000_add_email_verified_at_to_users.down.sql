@@ -0,0 +1,3 @@
+-- ユーザー・テーブルから、ユーザーのEメールアドレスを確認した日時を削除
+ALTER TABLE users
+DROP COLUMN IF EXISTS email_verified_at;
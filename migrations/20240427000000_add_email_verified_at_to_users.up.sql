@@ -0,0 +1,3 @@
+-- ユーザー・テーブルに、ユーザーのEメールアドレスを確認した日時を追加
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS email_verified_at TIMESTAMP WITH TIME ZONE;
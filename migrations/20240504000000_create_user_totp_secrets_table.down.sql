@@ -0,0 +1,2 @@
+-- ユーザーTOTP秘密鍵テーブルを削除
+DROP TABLE IF EXISTS user_totp_secrets;
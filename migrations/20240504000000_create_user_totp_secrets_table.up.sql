@@ -0,0 +1,11 @@
+-- ユーザーTOTP秘密鍵テーブルを作成
+CREATE TABLE IF NOT EXISTS user_totp_secrets (
+    user_id UUID NOT NULL,
+    encrypted_secret BYTEA NOT NULL,
+    enabled_at TIMESTAMP WITH TIME ZONE,
+    last_used_step BIGINT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    CONSTRAINT pk_user_totp_secrets PRIMARY KEY (user_id),
+    CONSTRAINT fk_user_totp_secrets_user FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE
+);
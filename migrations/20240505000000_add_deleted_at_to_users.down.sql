@@ -0,0 +1,22 @@
+-- 個人情報を消去したユーザーは制約を満たさないため削除
+DELETE FROM users
+WHERE deleted_at IS NOT NULL;
+
+-- 個人情報を記録する列の値を必須にする制約を元に戻す
+ALTER TABLE users
+DROP CONSTRAINT IF EXISTS ck_users_personal_information_must_be_not_null,
+DROP CONSTRAINT IF EXISTS ck_users_either_phone_numbers_must_be_not_null,
+ADD CONSTRAINT ck_users_either_phone_numbers_must_be_not_null CHECK (
+    fixed_phone_number IS NOT NULL
+    OR mobile_phone_number IS NOT NULL
+);
+
+ALTER TABLE users
+ALTER COLUMN family_name SET NOT NULL,
+ALTER COLUMN given_name SET NOT NULL,
+ALTER COLUMN postal_code SET NOT NULL,
+ALTER COLUMN address SET NOT NULL;
+
+-- ユーザー・テーブルから、ユーザーのアカウントを削除した日時を削除
+ALTER TABLE users
+DROP COLUMN IF EXISTS deleted_at;
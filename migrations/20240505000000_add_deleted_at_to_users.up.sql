@@ -0,0 +1,28 @@
+-- ユーザー・テーブルに、ユーザーのアカウントを削除した日時を追加
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMP WITH TIME ZONE;
+
+-- 削除したユーザーの個人情報を消去できるように、個人情報を記録する列のNOT NULL制約を削除
+ALTER TABLE users
+ALTER COLUMN family_name DROP NOT NULL,
+ALTER COLUMN given_name DROP NOT NULL,
+ALTER COLUMN postal_code DROP NOT NULL,
+ALTER COLUMN address DROP NOT NULL;
+
+-- 削除していないユーザーのみ、個人情報を記録する列の値を必須にする
+ALTER TABLE users
+DROP CONSTRAINT IF EXISTS ck_users_either_phone_numbers_must_be_not_null,
+ADD CONSTRAINT ck_users_either_phone_numbers_must_be_not_null CHECK (
+    deleted_at IS NOT NULL
+    OR fixed_phone_number IS NOT NULL
+    OR mobile_phone_number IS NOT NULL
+),
+ADD CONSTRAINT ck_users_personal_information_must_be_not_null CHECK (
+    deleted_at IS NOT NULL
+    OR (
+        family_name IS NOT NULL
+        AND given_name IS NOT NULL
+        AND postal_code IS NOT NULL
+        AND address IS NOT NULL
+    )
+);
@@ -0,0 +1,3 @@
+-- ユーザー・テーブルから、バージョンを削除
+ALTER TABLE users
+DROP COLUMN IF EXISTS version;
@@ -0,0 +1,3 @@
+-- ユーザー・テーブルに、楽観的ロックでユーザーを更新するためのバージョンを追加
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,6 @@
+-- ユーザーを検索するときに使用するインデックスを削除
+DROP INDEX IF EXISTS ix_users_email;
+DROP INDEX IF EXISTS ix_users_given_name;
+DROP INDEX IF EXISTS ix_users_family_name;
+
+DROP EXTENSION IF EXISTS pg_trgm;
@@ -0,0 +1,7 @@
+-- ユーザーを苗字、名前及びEメールアドレスの部分一致で検索できるように、トライグラムを有効化
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+-- 大文字と小文字を区別しない部分一致検索（ILIKE）で使用するインデックスを作成
+CREATE INDEX IF NOT EXISTS ix_users_family_name ON users USING GIN (family_name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS ix_users_given_name ON users USING GIN (given_name gin_trgm_ops);
+CREATE INDEX IF NOT EXISTS ix_users_email ON users USING GIN (email gin_trgm_ops);
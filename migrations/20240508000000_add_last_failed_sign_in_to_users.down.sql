@@ -0,0 +1,4 @@
+-- ユーザー・テーブルから、最後にサインインに失敗したアクセス元のIPアドレスとユーザーエージェントを削除
+ALTER TABLE users
+DROP COLUMN IF EXISTS last_failed_sign_in_user_agent,
+DROP COLUMN IF EXISTS last_failed_sign_in_ip_address;
@@ -0,0 +1,4 @@
+-- ユーザー・テーブルに、最後にサインインに失敗したアクセス元のIPアドレスとユーザーエージェントを追加
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS last_failed_sign_in_ip_address VARCHAR(45),
+ADD COLUMN IF NOT EXISTS last_failed_sign_in_user_agent TEXT;
@@ -0,0 +1,3 @@
+-- ユーザー・テーブルから、ユーザーがパスワードを変更しなければならないかを示すフラグを削除
+ALTER TABLE users
+DROP COLUMN IF EXISTS password_change_required;
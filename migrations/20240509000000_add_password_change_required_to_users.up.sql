@@ -0,0 +1,3 @@
+-- ユーザー・テーブルに、ユーザーがパスワードを変更しなければならないかを示すフラグを追加
+ALTER TABLE users
+ADD COLUMN IF NOT EXISTS password_change_required BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- 管理者監査ログテーブルを削除
+DROP TABLE IF EXISTS admin_audit_logs;
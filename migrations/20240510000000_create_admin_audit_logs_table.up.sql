@@ -0,0 +1,19 @@
+-- 管理者監査ログテーブルを作成
+-- 操作を実行した管理者や、操作の対象となったユーザーのレコードを物理的に削除しても監査ログを
+-- 残すため、ユーザーテーブルへの外部キー制約は設定しない
+CREATE TABLE IF NOT EXISTS admin_audit_logs (
+    id UUID NOT NULL,
+    actor_user_id UUID NOT NULL,
+    action VARCHAR(50) NOT NULL,
+    target_user_id UUID,
+    occurred_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    request_id VARCHAR(100),
+    details JSONB NOT NULL DEFAULT '{}'::JSONB,
+    CONSTRAINT pk_admin_audit_logs PRIMARY KEY (id)
+);
+
+-- 操作を実行した管理者または操作の対象となったユーザーで絞り込み、操作を実行した日時の新しい順に
+-- 並べるときに使用するインデックスを作成
+CREATE INDEX IF NOT EXISTS ix_admin_audit_logs_occurred_at ON admin_audit_logs (occurred_at DESC);
+CREATE INDEX IF NOT EXISTS ix_admin_audit_logs_actor_user_id ON admin_audit_logs (actor_user_id, occurred_at DESC);
+CREATE INDEX IF NOT EXISTS ix_admin_audit_logs_target_user_id ON admin_audit_logs (target_user_id, occurred_at DESC);
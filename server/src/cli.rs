@@ -0,0 +1,283 @@
+use std::io::BufRead;
+
+use anyhow::anyhow;
+use clap::{Args, Parser, Subcommand};
+use secrecy::{ExposeSecret as _, SecretString};
+
+use configurations::settings::{AppSettings, InitialAdminSettings};
+use domain::models::primitives::RawPassword;
+use use_cases::accounts::SignUpUseCaseOutput;
+use use_cases::passwords::generate_phc_string;
+use use_cases::settings::PasswordSettings;
+
+use crate::startup::{
+    connection_pools, migrate_database, request_context, seed_initial_admin, Application,
+};
+
+/// サーバーのコマンドライン引数
+#[derive(Debug, Parser)]
+#[command(version, about = "actix-webのサンプル・アプリケーション")]
+pub struct Cli {
+    /// サブコマンド
+    ///
+    /// 省略した場合は`serve`を実行する。
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// 実行するサブコマンドを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// サブコマンド、省略された場合は`serve`
+    pub fn command(self) -> Command {
+        self.command.unwrap_or(Command::Serve)
+    }
+}
+
+/// サブコマンド
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// HTTPサーバーを起動して、シャットダウンシグナルを受信するまで実行する。
+    Serve,
+    /// データベースをマイグレートして終了する。
+    Migrate,
+    /// 管理者を登録して終了する。
+    CreateAdmin(CreateAdminArgs),
+    /// 標準入力から読み込んだパスワードをハッシュ化したPHC文字列を出力する。
+    HashPassword,
+}
+
+/// 管理者を登録するサブコマンドの引数
+#[derive(Debug, Clone, Args)]
+pub struct CreateAdminArgs {
+    /// Eメールアドレス
+    #[arg(long)]
+    pub email: String,
+    /// パスワード
+    #[arg(long)]
+    pub password: SecretString,
+    /// 苗字
+    #[arg(long)]
+    pub family_name: String,
+    /// 名前
+    #[arg(long)]
+    pub given_name: String,
+    /// 郵便番号
+    #[arg(long)]
+    pub postal_code: String,
+    /// 住所
+    #[arg(long)]
+    pub address: String,
+    /// 固定電話番号
+    #[arg(long)]
+    pub fixed_phone_number: Option<String>,
+    /// 携帯電話番号
+    #[arg(long)]
+    pub mobile_phone_number: Option<String>,
+}
+
+impl From<CreateAdminArgs> for InitialAdminSettings {
+    fn from(value: CreateAdminArgs) -> Self {
+        Self {
+            email: value.email,
+            password: value.password,
+            family_name: value.family_name,
+            given_name: value.given_name,
+            postal_code: value.postal_code,
+            address: value.address,
+            fixed_phone_number: value.fixed_phone_number,
+            mobile_phone_number: value.mobile_phone_number,
+        }
+    }
+}
+
+/// HTTPサーバーを起動して、シャットダウンシグナルを受信するまで実行する。
+///
+/// # 引数
+///
+/// * `settings` - アプリケーション設定
+pub async fn serve(settings: AppSettings) -> anyhow::Result<()> {
+    Application::build(settings)
+        .await?
+        .run_until_stopped()
+        .await
+}
+
+/// データベースをマイグレートする。
+///
+/// # 引数
+///
+/// * `settings` - アプリケーション設定
+pub async fn migrate(settings: &AppSettings) -> anyhow::Result<()> {
+    let pg_pool = settings.database.connection_pool();
+    let result = migrate_database(&pg_pool).await;
+    pg_pool.close().await;
+
+    result
+}
+
+/// 管理者を登録する。
+///
+/// 初期管理者と同様に、ドメインプリミティブで値を検証して、パスワードをハッシュ化して登録する。
+/// 同じEメールアドレスを持つユーザーが登録されている場合はエラーを返す。
+///
+/// # 引数
+///
+/// * `settings` - アプリケーション設定
+/// * `args` - 管理者を登録するサブコマンドの引数
+///
+/// # 戻り値
+///
+/// 登録した管理者
+pub async fn create_admin(
+    settings: &AppSettings,
+    args: CreateAdminArgs,
+) -> anyhow::Result<SignUpUseCaseOutput> {
+    let admin = InitialAdminSettings::from(args);
+    admin.validate()?;
+    let (pg_pool, redis_pool) = connection_pools(&settings.database, &settings.redis).await?;
+    let context = request_context(settings, pg_pool, redis_pool)?;
+    let result = seed_initial_admin(&admin, &context).await;
+    context.close().await;
+
+    result?.ok_or_else(|| {
+        anyhow!(
+            "Eメールアドレス`{}`を持つユーザーが既に登録されています。",
+            admin.email
+        )
+    })
+}
+
+/// 読み込んだパスワードをハッシュ化したPHC文字列を生成する。
+///
+/// 最初の1行をパスワードとして読み込み、パスワードポリシーを満たしているか検証した後、
+/// パスワード設定に従ってハッシュ化する。
+///
+/// # 引数
+///
+/// * `settings` - パスワード設定
+/// * `reader` - パスワードを読み込むリーダー
+///
+/// # 戻り値
+///
+/// PHC文字列
+pub fn hash_password(settings: &PasswordSettings, reader: impl BufRead) -> anyhow::Result<String> {
+    let line = reader
+        .lines()
+        .next()
+        .ok_or_else(|| anyhow!("パスワードを入力してください。"))??;
+    let raw_password = RawPassword::with_policy(SecretString::new(line), &settings.policy())
+        .map_err(|e| anyhow!("パスワードが不正です。{}", e))?;
+    let phc_password = generate_phc_string(&raw_password, settings)
+        .map_err(|e| anyhow!("パスワードをハッシュ化できませんでした。{}", e))?;
+
+    Ok(phc_password.value.expose_secret().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// サブコマンドを省略した場合は、`serve`を実行することを確認
+    #[test]
+    fn serve_is_default_command() {
+        let cli = Cli::try_parse_from(["server"]).unwrap();
+
+        assert!(matches!(cli.command(), Command::Serve));
+    }
+
+    /// サブコマンドを解析できることを確認
+    #[test]
+    fn parse_commands() {
+        for (arg, expected) in [
+            ("serve", "serve"),
+            ("migrate", "migrate"),
+            ("hash-password", "hash-password"),
+        ] {
+            let command = Cli::try_parse_from(["server", arg]).unwrap().command();
+            let actual = match command {
+                Command::Serve => "serve",
+                Command::Migrate => "migrate",
+                Command::HashPassword => "hash-password",
+                Command::CreateAdmin(_) => "create-admin",
+            };
+            assert_eq!(expected, actual);
+        }
+        assert!(Cli::try_parse_from(["server", "unknown"]).is_err());
+    }
+
+    /// 管理者を登録するサブコマンドの引数を解析できることを確認
+    #[test]
+    fn parse_create_admin_args() {
+        let cli = Cli::try_parse_from([
+            "server",
+            "create-admin",
+            "--email",
+            "admin@example.com",
+            "--password",
+            "Az3#Za3@",
+            "--family-name",
+            "山田",
+            "--given-name",
+            "太郎",
+            "--postal-code",
+            "012-3456",
+            "--address",
+            "東京都新宿区西新宿2-8-1",
+            "--mobile-phone-number",
+            "090-1234-5678",
+        ])
+        .unwrap();
+
+        let Command::CreateAdmin(args) = cli.command() else {
+            panic!("create-admin must be parsed");
+        };
+        assert_eq!("admin@example.com", args.email);
+        assert_eq!("Az3#Za3@", args.password.expose_secret());
+        assert_eq!("山田", args.family_name);
+        assert_eq!(None, args.fixed_phone_number);
+        assert_eq!(
+            Some(String::from("090-1234-5678")),
+            args.mobile_phone_number
+        );
+    }
+
+    /// 管理者を登録するサブコマンドで、必須の引数を省略できないことを確認
+    #[test]
+    fn create_admin_requires_email_and_password() {
+        let result = Cli::try_parse_from([
+            "server",
+            "create-admin",
+            "--family-name",
+            "山田",
+            "--given-name",
+            "太郎",
+            "--postal-code",
+            "012-3456",
+            "--address",
+            "東京都新宿区西新宿2-8-1",
+        ]);
+
+        assert!(result.is_err());
+    }
+
+    /// 読み込んだパスワードをハッシュ化したPHC文字列を出力することを確認
+    #[test]
+    fn hash_password_generates_phc_string() {
+        let settings = PasswordSettings {
+            pepper: SecretString::new(String::from("pepper")),
+            hash_memory: 12288,
+            hash_iterations: 3,
+            hash_parallelism: 1,
+            max_length: domain::models::primitives::PASSWORD_MAX_LENGTH,
+        };
+
+        let phc = hash_password(&settings, "Az3#Za3@\n".as_bytes()).unwrap();
+
+        assert!(phc.starts_with("$argon2id$"), "{}", phc);
+        assert!(hash_password(&settings, "short\n".as_bytes()).is_err());
+        assert!(hash_password(&settings, "".as_bytes()).is_err());
+    }
+}
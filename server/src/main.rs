@@ -1,63 +1,87 @@
-use std::net::TcpListener;
+use std::io::Write as _;
 use std::path::Path;
 
 use anyhow::anyhow;
+use clap::Parser as _;
 
 use configurations::settings::{
     read_app_settings, AppEnvironment, ENV_APP_ENVIRONMENT, ENV_APP_ENVIRONMENT_DEFAULT,
     SETTINGS_DIR_NAME,
 };
-use infra::RequestContext;
-use server::startup::build_http_server;
-use server::telemetry::{generate_log_subscriber, init_log_subscriber, LOG_SUBSCRIBER_NAME};
+use server::cli::{create_admin, hash_password, migrate, serve, Cli, Command};
+use server::telemetry::{
+    generate_log_subscriber, init_log_subscriber, otlp_tracer, shutdown_tracer_provider,
+    LOG_SUBSCRIBER_NAME,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    // コマンドライン引数を解析
+    let command = Cli::parse().command();
+
     // 環境変数を設定
     dotenvx::dotenv()?;
 
     // 環境変数からアプリケーションの動作環境を取得
-    let app_env: AppEnvironment = std::env::var(ENV_APP_ENVIRONMENT)
-        .unwrap_or_else(|_| String::from(ENV_APP_ENVIRONMENT_DEFAULT))
-        .into();
+    let app_env = AppEnvironment::try_from(
+        std::env::var(ENV_APP_ENVIRONMENT)
+            .unwrap_or_else(|_| String::from(ENV_APP_ENVIRONMENT_DEFAULT)),
+    )
+    .map_err(|e| anyhow!("アプリケーションの起動を中止しました。{}", e))?;
 
     // アプリケーション設定を取得
     let settings_dir = Path::new(SETTINGS_DIR_NAME);
     let app_settings = read_app_settings(app_env, settings_dir)?;
-    // 認証設定を検証
-    app_settings.authorization.validate()?;
+    // アプリケーション設定を検証
+    app_settings.validate()?;
+
+    // OTLPコレクターのエンドポイントが指定されている場合は、トレースをエクスポートするトレーサーを構築
+    let tracer = app_settings
+        .tracing
+        .otlp_endpoint
+        .as_deref()
+        .map(|endpoint| otlp_tracer(LOG_SUBSCRIBER_NAME, endpoint))
+        .transpose()?;
 
     // サブスクライバを初期化
     let subscriber = generate_log_subscriber(
         LOG_SUBSCRIBER_NAME.into(),
         app_settings.logging.level,
+        app_settings.logging.format,
+        tracer,
         std::io::stdout,
     );
     init_log_subscriber(subscriber);
 
-    // HTTPサーバーがリクエストを待ち受けるアドレス
-    let address = format!("localhost:{}", app_settings.http_server.port);
-
-    // PostgreSQL接続プールを取得
-    let pg_pool = app_settings.database.connection_pool();
-    // Redis接続プールを取得
-    let redis_pool = app_settings.redis.connection_pool()?;
-
-    // リクエストコンテキストを構築
-    let context = RequestContext::new(
-        app_settings.http_server,
-        app_settings.password,
-        app_settings.authorization,
-        pg_pool,
-        redis_pool,
-    );
+    // サブコマンドを実行
+    let otlp_enabled = app_settings.tracing.otlp_endpoint.is_some();
+    let result = match command {
+        Command::Serve => serve(app_settings).await,
+        Command::Migrate => migrate(&app_settings).await,
+        Command::CreateAdmin(args) => create_admin(&app_settings, args)
+            .await
+            .and_then(|user| write_output(user.id)),
+        Command::HashPassword => {
+            hash_password(&app_settings.password, std::io::stdin().lock()).and_then(write_output)
+        }
+    };
+
+    // エクスポートしていないトレースをエクスポート
+    if otlp_enabled {
+        shutdown_tracer_provider();
+    }
+
+    result
+}
 
-    // Httpサーバーがリクエストを待ち受けるアドレスをバインド
-    let listener = TcpListener::bind(&address).map_err(|e| anyhow!(e))?;
-    tracing::info!("Http server is listening on `{}`", &address);
+/// サブコマンドの実行結果を標準出力に出力する。
+///
+/// # 引数
+///
+/// * `value` - サブコマンドの実行結果
+fn write_output(value: impl std::fmt::Display) -> anyhow::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", value)?;
 
-    // HTTPサーバーを起動
-    build_http_server(listener, context)?
-        .await
-        .map_err(|e| e.into())
+    Ok(())
 }
@@ -1,16 +1,224 @@
-use std::net::TcpListener;
+use std::fs::File;
+use std::future::Future;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::time::Duration;
 
-use actix_web::dev::Server;
-use actix_web::middleware::ErrorHandlers;
+use actix_web::dev::{Server, ServerHandle};
+use actix_web::http::{KeepAlive, Method};
+use actix_web::middleware::{Compress, Condition, ErrorHandlers};
 use actix_web::{web, App, HttpServer};
+use anyhow::{anyhow, Context as _};
+use deadpool_redis::Pool as RedisPool;
+use rustls::ServerConfig;
+use sqlx::migrate::Migrate as _;
+use sqlx::PgPool;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
 use tracing_actix_web::TracingLogger;
 
+use configurations::settings::{
+    AppSettings, DatabaseSettings, InitialAdminSettings, RedisSettings, TlsSettings,
+};
+use domain::models::primitives::*;
+use domain::models::user::UserPermissionCode;
+use domain::DomainError;
+use infra::emails::SmtpEmailSender;
+use infra::repositories::redis::token::RedisTokenRepository;
+use infra::routes::access_log::RequestLogger;
 use infra::routes::accounts::accounts_scope;
-use infra::routes::{default_error_handler, health_check};
+use infra::routes::admin::admin_scope;
+use infra::routes::api_docs::api_docs_scope;
+use infra::routes::ip_allowlist::IpAllowlistGuard;
+use infra::routes::localization::Localization;
+use infra::routes::maintenance::Maintenance;
+use infra::routes::metrics::{metrics, RequestMetrics};
+use infra::routes::telemetry::RequestSpanBuilder;
+use infra::routes::{
+    default_error_handler, health_check, json_config, jwks, method_not_allowed, not_found,
+    readiness_check, redirect_unprefixed_paths,
+};
+use infra::webhooks::WebhookSender;
 use infra::RequestContext;
+use use_cases::accounts::{SignUpUseCaseInputBuilder, SignUpUseCaseOutput};
+
+/// アプリケーション
+///
+/// 接続プールとリクエストコンテキストを構築して、リクエストを待ち受けるアドレスをバインドした
+/// HTTPサーバーを保持する。`main`関数と統合テストは、同じ手順でアプリケーションを起動する。
+pub struct Application {
+    /// HTTPサーバーがリクエストを待ち受けるアドレス
+    local_addr: SocketAddr,
+    /// HTTPサーバー
+    server: Server,
+    /// リクエストコンテキスト
+    context: RequestContext,
+}
+
+impl Application {
+    /// アプリケーション設定に従って、アプリケーションを構築する。
+    ///
+    /// PostgreSQL接続プールとRedis接続プールを構築して、データベース設定の`migrate_on_startup`が
+    /// `true`の場合はデータベースをマイグレートした後、[`Application::build_with_context`]で
+    /// アプリケーションを構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `settings` - アプリケーション設定
+    ///
+    /// # 戻り値
+    ///
+    /// アプリケーション
+    pub async fn build(settings: AppSettings) -> anyhow::Result<Self> {
+        // `fail_fast`が有効な場合は、リクエストを待ち受ける前に接続を確認
+        let (pg_pool, redis_pool) = connection_pools(&settings.database, &settings.redis).await?;
+        if settings.database.migrate_on_startup {
+            migrate_database(&pg_pool).await?;
+        }
+        let context = request_context(&settings, pg_pool, redis_pool)?;
+
+        Self::build_with_context(&settings, context).await
+    }
+
+    /// 構築済みのリクエストコンテキストを使用して、アプリケーションを構築する。
+    ///
+    /// ユーザー権限をキャッシュして、初期管理者を登録して、バックグラウンド・タスクを起動した後、
+    /// HTTPサーバー設定のアドレスをバインドしたHTTPサーバーを構築する。ポートに`0`を指定した
+    /// 場合は、OSが決定したポートをバインドする。
+    ///
+    /// # 引数
+    ///
+    /// * `settings` - アプリケーション設定
+    /// * `context` - リクエストコンテキスト
+    ///
+    /// # 戻り値
+    ///
+    /// アプリケーション
+    pub async fn build_with_context(
+        settings: &AppSettings,
+        context: RequestContext,
+    ) -> anyhow::Result<Self> {
+        // ユーザー権限をキャッシュ
+        // 読み込めなかった場合は、最初にユーザー権限を使用するときに読み込む
+        if let Err(e) = context.refresh_user_permissions().await {
+            tracing::warn!(
+                "Failed to cache user permissions, they will be loaded on first use: {}",
+                e
+            );
+        }
+
+        // 初期管理者を登録
+        if let Some(initial_admin) = &settings.initial_admin {
+            seed_initial_admin(initial_admin, &context).await?;
+        }
+
+        // ロックしたアカウントを定期的にアンロックするバックグラウンド・タスクを起動
+        let _ = spawn_account_unlocker(context.clone());
+        // 保持期間を経過したレコードを定期的に消去するバックグラウンド・タスクを起動
+        let _ = spawn_retention_purger(context.clone());
+
+        // HTTPサーバーがリクエストを待ち受けるアドレスをバインド
+        let address = settings.http_server.address();
+        let listener = TcpListener::bind(&address)
+            .with_context(|| format!("failed to bind the address `{}`", address))?;
+        let local_addr = listener.local_addr()?;
+        let server = build_http_server(listener, context.clone())?;
+        tracing::info!(
+            tls = settings.http_server.enabled_tls().is_some(),
+            workers = settings.http_server.workers,
+            "Http server is listening on `{}`",
+            local_addr
+        );
+
+        Ok(Self {
+            local_addr,
+            server,
+            context,
+        })
+    }
+
+    /// HTTPサーバーがリクエストを待ち受けるアドレスを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// HTTPサーバーがリクエストを待ち受けるアドレス
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// HTTPサーバーがリクエストを待ち受けるポートを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// HTTPサーバーがリクエストを待ち受けるポート
+    pub fn port(&self) -> u16 {
+        self.local_addr.port()
+    }
+
+    /// HTTPサーバーを停止するためのハンドルを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// HTTPサーバーのハンドル
+    pub fn handle(&self) -> ServerHandle {
+        self.server.handle()
+    }
+
+    /// HTTPサーバーを起動して、シャットダウンシグナルを受信するか、ハンドルで停止するまで実行する。
+    ///
+    /// HTTPサーバーが停止した後、接続プールを閉じる。
+    pub async fn run_until_stopped(self) -> anyhow::Result<()> {
+        run_until_shutdown(self.server, shutdown_signal(), self.context).await
+    }
+}
+
+/// アプリケーション設定に従って、リクエストコンテキストを構築する。
+///
+/// Eメール設定が指定されている場合はSMTPサーバーを介してEメールを送信して、Webhook設定が
+/// 指定されている場合は購読しているイベントをWebhookで通知する。
+///
+/// # 引数
+///
+/// * `settings` - アプリケーション設定
+/// * `pg_pool` - PostgreSQL接続プール
+/// * `redis_pool` - Redis接続プール
+///
+/// # 戻り値
+///
+/// リクエストコンテキスト
+pub fn request_context(
+    settings: &AppSettings,
+    pg_pool: PgPool,
+    redis_pool: RedisPool,
+) -> anyhow::Result<RequestContext> {
+    let mut context = RequestContext::new(
+        settings.http_server.clone(),
+        settings.password.clone(),
+        settings.authorization.clone(),
+        pg_pool,
+        redis_pool,
+    )?;
+    // Eメール設定が指定されている場合は、SMTPサーバーを介してEメールを送信
+    if let Some(email) = &settings.email {
+        context = context.with_email_sender(Arc::new(SmtpEmailSender::new(email)?));
+    }
+    // Webhook設定が指定されている場合は、購読しているイベントをWebhookで通知
+    if !settings.webhooks.is_empty() {
+        context =
+            context.with_event_handler(Arc::new(WebhookSender::new(settings.webhooks.clone())));
+    }
+
+    // ユーザーリポジトリがSQLステートメントの完了を待機する時間と、保持期間設定を設定
+    Ok(context
+        .with_query_timeout(settings.database.query_timeout())
+        .with_retention_settings(settings.retention.clone()))
+}
 
 /// HTTPサーバーを構築する。
 ///
+/// HTTPサーバー設定でTLSが有効になっている場合は、TLSでリクエストを待ち受ける。
+///
 /// # 引数
 ///
 /// * `listener` - HTTPサーバーがリッスンするポートをバインドしたリスナー
@@ -20,15 +228,522 @@ use infra::RequestContext;
 ///
 /// HTTPサーバー
 pub fn build_http_server(listener: TcpListener, context: RequestContext) -> anyhow::Result<Server> {
+    // TLSが有効な場合は、サーバー証明書と秘密鍵を読み込み
+    let tls_config = match context.http_server_settings.enabled_tls() {
+        Some(tls) => Some(load_rustls_config(tls)?),
+        None => None,
+    };
+
     // HttpServerを構築
-    Ok(HttpServer::new(move || {
+    let settings = context.http_server_settings.clone();
+    let keep_alive = match settings.keep_alive_seconds {
+        0 => KeepAlive::Disabled,
+        seconds => KeepAlive::Timeout(Duration::from_secs(seconds)),
+    };
+    let metrics_enabled = settings.metrics.enabled;
+    let swagger_ui = settings.api_docs.swagger_ui;
+    let access_log = settings.access_log.clone();
+    let api_prefix = settings.api_prefix.clone();
+    let max_json_payload_bytes = settings.max_json_payload_bytes;
+    let compression = settings.compression;
+    let admin_ip_allowlist = IpAllowlistGuard::new(&settings)?;
+    // プレフィックスを付与しない場合は、以前のパスとプレフィックスを付与したパスが同じになるため、
+    // リダイレクトしない
+    let redirect = settings.redirect_unprefixed_paths && !api_prefix.is_empty();
+    let server = HttpServer::new(move || {
         App::new()
-            .wrap(TracingLogger::default())
+            .wrap(Maintenance)
+            .wrap(Localization)
+            .wrap(RequestMetrics)
+            .wrap(RequestLogger::new(&access_log))
+            .wrap(TracingLogger::<RequestSpanBuilder>::new())
             .wrap(ErrorHandlers::new().default_handler(default_error_handler))
-            .route("/health-check", web::get().to(health_check))
-            .service(accounts_scope())
+            // エラー・ハンドラが圧縮する前のレスポンスボディを検査及び書き換えできるように、最後に登録
+            .wrap(Condition::new(compression, Compress::default()))
+            .configure(|config| {
+                if metrics_enabled {
+                    config.service(
+                        web::resource("/metrics")
+                            .route(web::get().to(metrics))
+                            .default_service(method_not_allowed(&[Method::GET])),
+                    );
+                }
+                if redirect {
+                    redirect_unprefixed_paths(config);
+                }
+            })
+            .service(
+                web::resource("/.well-known/jwks.json")
+                    .route(web::get().to(jwks))
+                    .default_service(method_not_allowed(&[Method::GET])),
+            )
+            .service(api_docs_scope(swagger_ui))
+            // プレフィックスが空文字の場合、スコープがすべてのパスに一致するため、最後に登録
+            .service(
+                web::scope(&api_prefix)
+                    .service(
+                        web::resource("/health-check")
+                            .route(web::get().to(health_check))
+                            .default_service(method_not_allowed(&[Method::GET])),
+                    )
+                    .service(
+                        web::resource("/health-check/ready")
+                            .route(web::get().to(readiness_check))
+                            .default_service(method_not_allowed(&[Method::GET])),
+                    )
+                    .service(accounts_scope(
+                        &context.http_server_settings,
+                        &admin_ip_allowlist,
+                    ))
+                    .service(admin_scope(&admin_ip_allowlist)),
+            )
+            .default_service(web::to(not_found))
+            .app_data(json_config(max_json_payload_bytes))
+            .app_data(web::PayloadConfig::new(max_json_payload_bytes))
             .app_data(web::Data::new(context.clone()))
     })
-    .listen(listener)?
-    .run())
+    .workers(settings.workers)
+    .keep_alive(keep_alive)
+    .client_request_timeout(Duration::from_secs(settings.client_request_timeout_seconds))
+    .shutdown_timeout(settings.shutdown_timeout_seconds)
+    // シグナルは`run_until_shutdown`関数で処理
+    .disable_signals();
+    let server = match tls_config {
+        Some(config) => server.listen_rustls_0_22(listener, config)?,
+        None => server.listen(listener)?,
+    };
+
+    Ok(server.run())
+}
+
+/// HTTPサーバーを起動して、シャットダウンシグナルを受信するまで実行する。
+///
+/// シャットダウンシグナルを受信した場合、HTTPサーバーが新しい接続を受け付けることを停止して、
+/// HTTPサーバー設定の`shutdown_timeout_seconds`を上限に、処理中のリクエストが完了するまで待機する。
+/// HTTPサーバーが停止した後、PostgreSQL接続プールとRedis接続プールを閉じる。
+///
+/// # 引数
+///
+/// * `server` - HTTPサーバー
+/// * `signal` - シャットダウンシグナルを受信したときに完了するフューチャー
+/// * `context` - リクエストコンテキスト
+pub async fn run_until_shutdown<S>(
+    server: Server,
+    signal: S,
+    context: RequestContext,
+) -> anyhow::Result<()>
+where
+    S: Future<Output = ()>,
+{
+    let handle = server.handle();
+    let mut server_task = tokio::spawn(server);
+
+    tokio::select! {
+        result = &mut server_task => {
+            result??;
+            tracing::info!("Http server stopped");
+        }
+        _ = signal => {
+            tracing::info!("Shutdown signal received, stopping the http server gracefully");
+            handle.stop(true).await;
+            server_task.await??;
+            tracing::info!("Http server stopped after draining connections");
+        }
+    }
+
+    // 接続プールを閉じる
+    context.close().await;
+    tracing::info!("Shutdown completed");
+
+    Ok(())
+}
+
+/// ロックしたユーザーのアカウントをアンロックする間隔の上限（秒）
+pub const ACCOUNT_UNLOCK_INTERVAL_SECONDS: u64 = 60;
+
+/// ロックしてから、自動的にアンロックする時間が経過したユーザーのアカウントを、定期的にアンロックする
+/// バックグラウンド・タスクを起動する。
+///
+/// アンロックする間隔は、認証設定の`lockout_seconds`と`ACCOUNT_UNLOCK_INTERVAL_SECONDS`の短い方である。
+/// リクエストコンテキストの接続プールを閉じた場合、バックグラウンド・タスクは終了する。
+///
+/// # 引数
+///
+/// * `context` - リクエストコンテキスト
+///
+/// # 戻り値
+///
+/// バックグラウンド・タスクのハンドル、`lockout_seconds`が`0`の場合は`None`
+pub fn spawn_account_unlocker(context: RequestContext) -> Option<JoinHandle<()>> {
+    let lockout_seconds = context.authorization_settings.lockout_seconds;
+    if lockout_seconds == 0 {
+        return None;
+    }
+    let period = Duration::from_secs(lockout_seconds.min(ACCOUNT_UNLOCK_INTERVAL_SECONDS));
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if context.is_closed() {
+                break;
+            }
+            // エラーはリポジトリで記録済み
+            let result = context.accounts().unlock_expired_accounts().await;
+            if let Ok(count @ 1..) = result {
+                tracing::info!("Unlocked {} expired account(s)", count);
+            }
+        }
+    }))
+}
+
+/// 保持期間を経過したサインインに失敗した履歴と、削除したユーザーのレコードを、定期的に消去する
+/// バックグラウンド・タスクを起動する。
+///
+/// 消去する間隔は、保持期間設定の`interval_seconds`である。消去に失敗した場合は、警告を記録して
+/// 次の間隔で再度消去する。リクエストコンテキストの接続プールを閉じた場合、バックグラウンド・
+/// タスクは終了する。
+///
+/// # 引数
+///
+/// * `context` - リクエストコンテキスト
+///
+/// # 戻り値
+///
+/// バックグラウンド・タスクのハンドル、保持期間または`interval_seconds`が`0`の場合は`None`
+pub fn spawn_retention_purger(context: RequestContext) -> Option<JoinHandle<()>> {
+    let settings = &context.retention_settings;
+    if !settings.is_enabled() || settings.interval_seconds == 0 {
+        return None;
+    }
+    let period = Duration::from_secs(settings.interval_seconds);
+
+    Some(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            if context.is_closed() {
+                break;
+            }
+            let result = context
+                .accounts()
+                .purge_expired_records(&context.retention_settings)
+                .await;
+            match result {
+                Ok(output) => tracing::info!(
+                    "Purged sign-in history of {} user(s) and {} deleted user(s)",
+                    output.sign_in_histories,
+                    output.deleted_users
+                ),
+                Err(e) => tracing::warn!("Failed to purge expired records: {}", e),
+            }
+        }
+    }))
+}
+
+/// PostgreSQL接続プールとRedis接続プールを構築する。
+///
+/// データベース設定またはRedis設定の`fail_fast`が`true`の場合は、HTTPサーバーがリクエストを
+/// 待ち受ける前に接続を確認して、接続できない場合は設定を確認するように促すエラーを返す。
+/// `false`の場合は、接続を確認せず、最初に接続を使用するときに接続する。
+///
+/// # 引数
+///
+/// * `database` - データベース設定
+/// * `redis` - Redis設定
+///
+/// # 戻り値
+///
+/// PostgreSQL接続プールとRedis接続プール
+pub async fn connection_pools(
+    database: &DatabaseSettings,
+    redis: &RedisSettings,
+) -> anyhow::Result<(PgPool, RedisPool)> {
+    let pg_pool = database.connection_pool();
+    match database.fail_fast {
+        true => warm_up_pg_pool(&pg_pool, database).await?,
+        false => tracing::info!(
+            "PostgreSQL connection pool was created, connections will be established on first use"
+        ),
+    }
+
+    let redis_pool = redis.connection_pool()?;
+    match redis.fail_fast {
+        true => verify_redis_pool(&redis_pool, redis).await?,
+        false => tracing::info!(
+            "Redis connection pool was created, connections will be established on first use"
+        ),
+    }
+
+    Ok((pg_pool, redis_pool))
+}
+
+/// PostgreSQL接続プールに、接続プールが保持する最小接続数（最低1つ）の接続を作成する。
+///
+/// 作成したそれぞれの接続で`SELECT 1`を実行して、データベースが応答するか確認する。
+///
+/// # 引数
+///
+/// * `pool` - PostgreSQL接続プール
+/// * `settings` - データベース設定
+async fn warm_up_pg_pool(pool: &PgPool, settings: &DatabaseSettings) -> anyhow::Result<()> {
+    let error = |e: sqlx::Error| {
+        anyhow!(
+            "PostgreSQLに接続できないため、アプリケーションの起動を中止しました。ホスト、ポート番号、ユーザー名、パスワード及びデータベース名の設定を確認してください。(host: {}, port: {}, user: {}, name: {}): {}",
+            settings.host,
+            settings.port,
+            settings.user,
+            settings.name,
+            e
+        )
+    };
+
+    // 接続を保持したまま次の接続を取得することで、最小接続数の接続を作成
+    let mut connections = Vec::with_capacity(settings.min_connections.max(1) as usize);
+    for _ in 0..settings.min_connections.max(1) {
+        let mut connection = pool.acquire().await.map_err(error)?;
+        sqlx::query("SELECT 1")
+            .execute(&mut *connection)
+            .await
+            .map_err(error)?;
+        connections.push(connection);
+    }
+    tracing::info!(
+        "PostgreSQL connection pool is ready with {} connection(s)",
+        connections.len()
+    );
+
+    Ok(())
+}
+
+/// Redis接続プールから接続を取得して、`PING`コマンドを送信できるか確認する。
+///
+/// # 引数
+///
+/// * `pool` - Redis接続プール
+/// * `settings` - Redis設定
+async fn verify_redis_pool(pool: &RedisPool, settings: &RedisSettings) -> anyhow::Result<()> {
+    RedisTokenRepository::new(pool.clone())
+        .ping()
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Redisに接続できないため、アプリケーションの起動を中止しました。ホスト、ポート番号、データベース番号、TLS及びパスワードの設定を確認してください。(host: {}, port: {}, db: {}, tls: {}): {}",
+                settings.host,
+                settings.port,
+                settings.db,
+                settings.tls,
+                e
+            )
+        })?;
+    tracing::info!("Redis connection pool is ready");
+
+    Ok(())
+}
+
+/// データベースをマイグレートする。
+///
+/// 適用していないマイグレーションをすべて適用して、適用したマイグレーションのバージョンを
+/// ログに記録する。
+///
+/// # 引数
+///
+/// * `pool` - PostgreSQL接続プール
+pub async fn migrate_database(pool: &PgPool) -> anyhow::Result<()> {
+    let migrator = sqlx::migrate!("../migrations");
+
+    // 適用済みのマイグレーションのバージョンを取得
+    let mut connection = pool
+        .acquire()
+        .await
+        .map_err(|e| anyhow!("データベースのマイグレーションに失敗しました。{}", e))?;
+    connection
+        .ensure_migrations_table()
+        .await
+        .map_err(|e| anyhow!("データベースのマイグレーションに失敗しました。{}", e))?;
+    let applied_versions = connection
+        .list_applied_migrations()
+        .await
+        .map_err(|e| anyhow!("データベースのマイグレーションに失敗しました。{}", e))?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect::<Vec<_>>();
+    drop(connection);
+
+    // マイグレーションを適用
+    migrator
+        .run(pool)
+        .await
+        .map_err(|e| anyhow!("データベースのマイグレーションに失敗しました。{}", e))?;
+    let applied = migrator
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .filter(|migration| !applied_versions.contains(&migration.version))
+        .collect::<Vec<_>>();
+    if applied.is_empty() {
+        tracing::info!("Database is up to date, no migrations were applied");
+    }
+    for migration in applied {
+        tracing::info!(
+            "Applied migration {} ({})",
+            migration.version,
+            migration.description
+        );
+    }
+
+    Ok(())
+}
+
+/// 初期管理者を登録する。
+///
+/// 初期管理者と同じEメールアドレスを持つユーザーが登録されていない場合のみ登録するため、
+/// アプリケーションを再起動しても、ユーザーを重複して登録しない。
+///
+/// # 引数
+///
+/// * `settings` - 初期管理者設定
+/// * `context` - リクエストコンテキスト
+///
+/// # 戻り値
+///
+/// 初期管理者を登録した場合は登録したユーザー、既に登録されていた場合は`None`
+pub async fn seed_initial_admin(
+    settings: &InitialAdminSettings,
+    context: &RequestContext,
+) -> anyhow::Result<Option<SignUpUseCaseOutput>> {
+    let invalid = |e: DomainError| anyhow!("初期管理者設定が不正です。{}", e);
+    let input = SignUpUseCaseInputBuilder::new()
+        .email(EmailAddress::new(settings.email.clone()).map_err(invalid)?)
+        .password(
+            RawPassword::with_policy(
+                settings.password.clone(),
+                &context.password_settings.policy(),
+            )
+            .map_err(invalid)?,
+        )
+        .active(true)
+        .user_permission_code(UserPermissionCode::Admin)
+        .family_name(FamilyName::new(settings.family_name.clone()).map_err(invalid)?)
+        .given_name(GivenName::new(settings.given_name.clone()).map_err(invalid)?)
+        .postal_code(PostalCode::new(settings.postal_code.clone()).map_err(invalid)?)
+        .address(Address::new(settings.address.clone()).map_err(invalid)?)
+        .fixed_phone_number(
+            OptionalFixedPhoneNumber::try_from(settings.fixed_phone_number.clone())
+                .map_err(invalid)?,
+        )
+        .mobile_phone_number(
+            OptionalMobilePhoneNumber::try_from(settings.mobile_phone_number.clone())
+                .map_err(invalid)?,
+        )
+        .build()
+        .map_err(|e| anyhow!("初期管理者設定が不正です。{}", e))?;
+
+    let output = context
+        .accounts()
+        .seed_initial_admin(input)
+        .await
+        .map_err(|e| anyhow!("初期管理者を登録できませんでした。{}", e))?;
+    match &output {
+        Some(user) => tracing::info!("Initial admin user `{}` was created", user.email),
+        None => tracing::info!(
+            "Initial admin user `{}` already exists, skipped seeding",
+            settings.email
+        ),
+    }
+
+    Ok(output)
+}
+
+/// SIGTERMまたはSIGINTを受信するまで待機する。
+///
+/// シグナルハンドラの登録に失敗した場合は、そのシグナルを待機しない。
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("{} ({}:{})", e, file!(), line!());
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(e) => {
+                tracing::error!("{} ({}:{})", e, file!(), line!());
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => tracing::info!("SIGINT received"),
+        _ = terminate => tracing::info!("SIGTERM received"),
+    }
+}
+
+/// TLS設定に従って、サーバー証明書と秘密鍵を読み込んだ`rustls`のサーバー設定を構築する。
+///
+/// # 引数
+///
+/// * `tls` - TLS設定
+///
+/// # 戻り値
+///
+/// `rustls`のサーバー設定
+pub fn load_rustls_config(tls: &TlsSettings) -> anyhow::Result<ServerConfig> {
+    // サーバー証明書チェーンを読み込み
+    let cert_file = File::open(&tls.cert_path).with_context(|| {
+        format!(
+            "failed to open the certificate file `{}`",
+            tls.cert_path.display()
+        )
+    })?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(cert_file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| {
+            format!(
+                "failed to read the certificate file `{}`",
+                tls.cert_path.display()
+            )
+        })?;
+    if certs.is_empty() {
+        return Err(anyhow!(
+            "the certificate file `{}` does not contain any certificates",
+            tls.cert_path.display()
+        ));
+    }
+
+    // 秘密鍵を読み込み
+    let key_file = File::open(&tls.key_path).with_context(|| {
+        format!(
+            "failed to open the private key file `{}`",
+            tls.key_path.display()
+        )
+    })?;
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key_file))
+        .with_context(|| {
+            format!(
+                "failed to read the private key file `{}`",
+                tls.key_path.display()
+            )
+        })?
+        .ok_or_else(|| {
+            anyhow!(
+                "the private key file `{}` does not contain any private keys",
+                tls.key_path.display()
+            )
+        })?;
+
+    ServerConfig::builder()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("failed to build the TLS configuration from the certificate and private key")
 }
@@ -1,20 +1,41 @@
+use std::fmt;
+
+use opentelemetry::trace::TraceError;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig as _;
+use opentelemetry_sdk::trace::{self as sdktrace, Tracer};
+use opentelemetry_sdk::{runtime, Resource};
+use serde_json::{Map, Value};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tracing::field::{Field, Visit};
 use tracing::subscriber::set_global_default;
-use tracing::Subscriber;
+use tracing::{Event, Subscriber};
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
-use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::fmt::format::{FmtSpan, JsonFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter};
 use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::{EnvFilter, Registry};
 
+use configurations::settings::LogFormat;
+
 /// ログサブスクライバ名
 pub const LOG_SUBSCRIBER_NAME: &str = "actix_web_example";
 
 /// ログを購読するサブスクライバを生成する。
 ///
+/// ログの出力形式が`pretty`の場合はBunyan形式、`json`の場合は1行に1つのイベントを出力する
+/// JSON形式でログを出力する。
+/// トレーサーを指定した場合は、スパンをOpenTelemetryのトレースとして記録するレイヤーを追加する。
+///
 /// # 引数
 ///
 /// * `name` - ログを購読するサブスクライバの名前
 /// * `default_level` - デフォルトのログレベル
+/// * `format` - ログの出力形式
+/// * `tracer` - スパンを記録するOpenTelemetryのトレーサー
 /// * `sink` - ログの出力先
 ///
 /// # 戻り値
@@ -23,6 +44,8 @@ pub const LOG_SUBSCRIBER_NAME: &str = "actix_web_example";
 pub fn generate_log_subscriber<Sink>(
     name: String,
     default_level: log::Level,
+    format: LogFormat,
+    tracer: Option<Tracer>,
     sink: Sink,
 ) -> impl Subscriber + Sync + Send
 where
@@ -32,12 +55,64 @@ where
     let env_filter = EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| EnvFilter::new(default_level.as_str()));
 
+    // ログの出力形式に応じたレイヤーを構築
+    let (bunyan_layer, json_layer) = match format {
+        LogFormat::Pretty => (Some(BunyanFormattingLayer::new(name, sink)), None),
+        LogFormat::Json => (
+            None,
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_span_events(FmtSpan::CLOSE)
+                    .fmt_fields(JsonFields::new())
+                    .event_format(JsonLogFormat::new(name))
+                    .with_writer(sink),
+            ),
+        ),
+    };
+
+    // スパンをOpenTelemetryのトレースとして記録するレイヤーを構築
+    let otel_layer = tracer.map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+
     // ログを購読するサブスクライバを構築
-    let formatting_layer = BunyanFormattingLayer::new(name, sink);
     Registry::default()
         .with(env_filter)
-        .with(JsonStorageLayer)
-        .with(formatting_layer)
+        .with(bunyan_layer.is_some().then_some(JsonStorageLayer))
+        .with(bunyan_layer)
+        .with(json_layer)
+        .with(otel_layer)
+}
+
+/// トレースをOTLP（gRPC）でエクスポートするトレーサーを構築する。
+///
+/// スパンはバッチでエクスポートするため、Tokioランタイム内で呼び出す必要がある。
+/// 構築したトレーサーのプロバイダーは、グローバルなトレーサー・プロバイダーとして登録する。
+///
+/// # 引数
+///
+/// * `name` - トレースに記録するサービス名
+/// * `endpoint` - トレースをエクスポートするOTLPコレクターのエンドポイント
+///
+/// # 戻り値
+///
+/// トレーサー
+pub fn otlp_tracer(name: &str, endpoint: &str) -> Result<Tracer, TraceError> {
+    let exporter = opentelemetry_otlp::new_exporter()
+        .tonic()
+        .with_endpoint(endpoint);
+    let resource = Resource::new([KeyValue::new("service.name", name.to_string())]);
+
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(exporter)
+        .with_trace_config(sdktrace::config().with_resource(resource))
+        .install_batch(runtime::Tokio)
+}
+
+/// グローバルなトレーサー・プロバイダーを停止する。
+///
+/// エクスポートしていないスパンをエクスポートしてから停止する。
+pub fn shutdown_tracer_provider() {
+    opentelemetry::global::shutdown_tracer_provider();
 }
 
 /// ログを購読するサブスクライバを初期化する。
@@ -51,3 +126,186 @@ pub fn init_log_subscriber(subscriber: impl Subscriber + Send + Sync) {
     // 上記サブスクライバをデフォルトに設定
     set_global_default(subscriber).expect("failed to set subscriber");
 }
+
+/// 1行に1つのイベントをJSON形式で出力するフォーマッター
+///
+/// イベントの発生日時（`time`）、ログレベル（`level`）、メッセージ（`msg`）、サービス名（`name`）、
+/// ターゲット（`target`）、イベントを囲むスパンの名前（`span`）を出力する。
+/// また、イベントのフィールドと、イベントを囲むスパンのフィールドをフラットに出力する。
+/// スパンとイベントのフィールドの名前が重複した場合は、イベントのフィールドを優先する。
+struct JsonLogFormat {
+    /// サービス名
+    name: String,
+}
+
+impl JsonLogFormat {
+    fn new(name: String) -> Self {
+        Self { name }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonLogFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let mut fields = Map::new();
+
+        // イベントを囲むスパンのフィールドを、外側のスパンから順に記録
+        let mut span_name = None;
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                span_name = Some(span.name());
+                let extensions = span.extensions();
+                let Some(formatted) = extensions.get::<FormattedFields<N>>() else {
+                    continue;
+                };
+                if let Ok(Value::Object(span_fields)) =
+                    serde_json::from_str::<Value>(formatted.as_str())
+                {
+                    fields.extend(span_fields);
+                }
+            }
+        }
+
+        // イベントのフィールドを記録
+        let mut visitor = JsonVisitor(&mut fields);
+        event.record(&mut visitor);
+        let message = fields.remove("message").unwrap_or(Value::from(""));
+
+        let mut line = Map::new();
+        let now = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default();
+        line.insert(String::from("time"), Value::from(now));
+        line.insert(
+            String::from("level"),
+            Value::from(metadata.level().as_str()),
+        );
+        line.insert(String::from("msg"), message);
+        line.insert(String::from("name"), Value::from(self.name.as_str()));
+        line.insert(String::from("target"), Value::from(metadata.target()));
+        if let Some(span_name) = span_name {
+            line.insert(String::from("span"), Value::from(span_name));
+        }
+        for (key, value) in fields {
+            line.entry(key).or_insert(value);
+        }
+
+        let line = serde_json::to_string(&line).map_err(|_| fmt::Error)?;
+        writeln!(writer, "{}", line)
+    }
+}
+
+/// イベントのフィールドをJSONのマップに記録するビジター
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(
+            field.name().to_string(),
+            Value::from(format!("{:?}", value)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::Value;
+    use tracing_subscriber::fmt::MakeWriter;
+
+    use configurations::settings::LogFormat;
+
+    use super::generate_log_subscriber;
+
+    /// ログを記録するバッファ
+    #[derive(Clone, Default)]
+    struct BufferWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for BufferWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl<'a> MakeWriter<'a> for BufferWriter {
+        type Writer = Self;
+
+        fn make_writer(&'a self) -> Self::Writer {
+            self.clone()
+        }
+    }
+
+    /// JSON形式を指定した場合に、1行に1つの妥当なJSONでログを出力することを確認
+    #[test]
+    fn json_format_emits_valid_json_lines() {
+        let buffer = BufferWriter::default();
+        let subscriber = generate_log_subscriber(
+            String::from("test-service"),
+            log::Level::Info,
+            LogFormat::Json,
+            None,
+            buffer.clone(),
+        );
+
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("request", request_id = "abc");
+            let _guard = span.enter();
+            tracing::info!(user_id = 42, "user signed in");
+            tracing::debug!("filtered out");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines = output
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        // イベントとスパンを閉じたときのイベント
+        assert_eq!(2, lines.len(), "{}", output);
+        let event = &lines[0];
+        assert_eq!("INFO", event["level"]);
+        assert_eq!("user signed in", event["msg"]);
+        assert_eq!("test-service", event["name"]);
+        assert_eq!("request", event["span"]);
+        assert_eq!("abc", event["request_id"]);
+        assert_eq!(42, event["user_id"]);
+        assert!(event.get("time").is_some());
+        let close = &lines[1];
+        assert_eq!("close", close["msg"]);
+        assert!(close.get("time.busy").is_some());
+    }
+}
@@ -1,4 +1,16 @@
-use crate::helpers::{app_settings, spawn_test_app, split_response};
+use std::net::TcpListener;
+use std::time::Duration;
+
+use secrecy::SecretString;
+use tokio::io::{AsyncReadExt as _, AsyncWriteExt as _};
+use uuid::Uuid;
+
+use configurations::settings::TlsSettings;
+use infra::routes::{DependencyStatus, ReadinessResBody};
+use infra::RequestContext;
+use server::startup::{build_http_server, run_until_shutdown, Application};
+
+use crate::helpers::{app_settings, spawn_test_app, split_response, ADMIN_USER_RAW_PASSWORD};
 
 /// ヘルスチェック・ハンドラ
 #[tokio::test]
@@ -11,7 +23,7 @@ async fn health_check_works() -> anyhow::Result<()> {
 
     // 実行
     let response = client
-        .get(&format!("{}/health-check", app.root_uri))
+        .get(app.api_uri("/health-check"))
         .send()
         .await
         .expect("Failed to execute request.");
@@ -31,6 +43,208 @@ async fn health_check_works() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// PostgreSQLとRedisが応答する場合に、レディネスチェックが成功することを確認
+#[tokio::test]
+#[ignore]
+async fn readiness_check_works() -> anyhow::Result<()> {
+    // 準備
+    let settings = app_settings()?;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::new();
+
+    // 実行
+    let response = client
+        .get(app.api_uri("/health-check/ready"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = split_response(response).await?;
+    let body: ReadinessResBody = serde_json::from_str(&response.body)?;
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::OK, response.status_code);
+    assert_eq!(DependencyStatus::Up, body.postgres);
+    assert_eq!(DependencyStatus::Up, body.redis);
+    assert!(body.failures.is_empty());
+
+    Ok(())
+}
+
+/// OpenAPIドキュメントを取得できて、サインアップのパスとエラーレスポンスのスキーマが含まれていることを確認
+#[tokio::test]
+#[ignore]
+async fn openapi_document_contains_sign_up_path_and_error_schema() -> anyhow::Result<()> {
+    // 準備
+    let settings = app_settings()?;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::new();
+
+    // 実行
+    let response = client
+        .get(format!("{}/api-docs/openapi.json", app.root_uri))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = split_response(response).await?;
+    let document: serde_json::Value = serde_json::from_str(&response.body)?;
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::OK, response.status_code);
+    let sign_up = &document["paths"]["/accounts/sign-up"]["post"];
+    assert!(sign_up.is_object());
+    assert_eq!(
+        "#/components/schemas/ErrorResponseBody",
+        sign_up["responses"]["400"]["content"]["application/json"]["schema"]["$ref"]
+    );
+    assert!(document["components"]["schemas"]["ErrorResponseBody"].is_object());
+    let password = &document["components"]["schemas"]["SignUpReqBody"]["properties"]["password"];
+    assert_eq!(serde_json::Value::Bool(true), password["writeOnly"]);
+
+    Ok(())
+}
+
+/// Swagger UIを無効にした場合、Swagger UIを公開しないことを確認
+#[tokio::test]
+#[ignore]
+async fn swagger_ui_is_not_served_when_disabled() -> anyhow::Result<()> {
+    // 準備
+    let mut settings = app_settings()?;
+    settings.http_server.api_docs.swagger_ui = false;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::new();
+
+    // 実行
+    let response = client
+        .get(format!("{}/api-docs", app.root_uri))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::NOT_FOUND, response.status());
+
+    Ok(())
+}
+
+/// Redisが応答しない場合に、レディネスチェックがRedisを失敗したサービスとして報告することを確認
+#[tokio::test]
+#[ignore]
+async fn readiness_check_reports_redis_down() -> anyhow::Result<()> {
+    // 準備
+    let mut settings = app_settings()?;
+    // 接続できないRedisを指定
+    settings.redis.port = 1;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::new();
+
+    // 実行
+    let response = client
+        .get(app.api_uri("/health-check/ready"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = split_response(response).await?;
+    let body: ReadinessResBody = serde_json::from_str(&response.body)?;
+
+    // 検証
+    assert_eq!(
+        reqwest::StatusCode::SERVICE_UNAVAILABLE,
+        response.status_code
+    );
+    assert_eq!(DependencyStatus::Up, body.postgres);
+    assert_eq!(DependencyStatus::Down, body.redis);
+    assert_eq!(vec![String::from("redis")], body.failures);
+
+    Ok(())
+}
+
+/// リクエストとサインインを記録したメトリクスを取得できることを確認
+#[tokio::test]
+#[ignore]
+async fn metrics_endpoint_exposes_request_and_sign_in_counters() -> anyhow::Result<()> {
+    // 準備
+    let settings = app_settings()?;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::new();
+    for _ in 0..2 {
+        client.get(app.api_uri("/health-check")).send().await?;
+    }
+    // 登録されていないユーザーでサインイン
+    let response = app
+        .sign_in(
+            String::from("unknown@example.com"),
+            SecretString::new(String::from(ADMIN_USER_RAW_PASSWORD)),
+        )
+        .await?;
+    assert_eq!(reqwest::StatusCode::UNAUTHORIZED, response.status());
+
+    // 実行
+    let response = client
+        .get(format!("{}/metrics", app.root_uri))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = split_response(response).await?;
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::OK, response.status_code);
+    assert!(response.body.contains(
+        r#"http_requests_total{method="GET",route="/api/v1/health-check",status="200"} 2"#
+    ));
+    assert!(response.body.contains(
+        r#"http_requests_total{method="POST",route="/api/v1/accounts/sign-in",status="401"} 1"#
+    ));
+    assert!(response
+        .body
+        .contains(r#"sign_ins_total{result="failure"} 1"#));
+    assert!(response.body.contains("pg_pool_connections"));
+    assert!(response.body.contains("redis_pool_max_connections"));
+
+    Ok(())
+}
+
+/// アクセスを許可されていないIPアドレスからメトリクスを取得できないことを確認
+#[tokio::test]
+#[ignore]
+async fn metrics_endpoint_rejects_requests_from_disallowed_addresses() -> anyhow::Result<()> {
+    // 準備
+    let mut settings = app_settings()?;
+    settings.http_server.metrics.allowed_ips = vec![];
+    let app = spawn_test_app(settings).await?;
+
+    // 実行
+    let response = reqwest::Client::new()
+        .get(format!("{}/metrics", app.root_uri))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::FORBIDDEN, response.status());
+
+    Ok(())
+}
+
+/// 共有する秘密鍵でJWTを署名する場合は、JWK Setを公開しないことを確認
+#[tokio::test]
+#[ignore]
+async fn jwks_is_not_published_for_symmetric_signing() -> anyhow::Result<()> {
+    // 準備
+    let app = spawn_test_app(app_settings()?).await?;
+
+    // 実行
+    let response = reqwest::Client::new()
+        .get(format!("{}/.well-known/jwks.json", app.root_uri))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::NOT_FOUND, response.status());
+
+    Ok(())
+}
+
 /// 存在しないURIにアクセスしたときに、正しいレスポンスが得られるか確認
 #[tokio::test]
 #[ignore]
@@ -42,7 +256,7 @@ async fn not_found_works() -> anyhow::Result<()> {
 
     // 実行
     let response = client
-        .get(&format!("{}/non-existent-uri", app.root_uri))
+        .get(format!("{}/non-existent-uri", app.root_uri))
         .send()
         .await
         .expect("Failed to execute request.");
@@ -61,3 +275,334 @@ async fn not_found_works() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// リソースが許可していないHTTPメソッドでアクセスしたときに、正しいレスポンスが得られるか確認
+#[tokio::test]
+#[ignore]
+async fn method_not_allowed_works() -> anyhow::Result<()> {
+    // 準備
+    let settings = app_settings()?;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::new();
+
+    // 実行
+    let response = client
+        .delete(app.api_uri("/accounts/sign-up"))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = split_response(response).await?;
+    let content_type = response.headers.get(reqwest::header::CONTENT_TYPE);
+    let allow = response.headers.get(reqwest::header::ALLOW);
+    let body: serde_json::Value = serde_json::from_str(&response.body)?;
+
+    // 検証
+    assert_eq!(
+        reqwest::StatusCode::METHOD_NOT_ALLOWED,
+        response.status_code
+    );
+    assert_eq!(
+        mime::APPLICATION_JSON.to_string(),
+        content_type.unwrap().to_str().unwrap()
+    );
+    assert_eq!("POST", allow.unwrap().to_str().unwrap());
+    assert_eq!(body["message"], serde_json::json!("Method Not Allowed"));
+    assert!(body["errorCode"].is_null());
+
+    Ok(())
+}
+
+/// プレフィックスを付与していない以前のパスにアクセスしたときに、JSON形式の404 Not Foundが返されることを確認
+#[tokio::test]
+#[ignore]
+async fn unprefixed_path_returns_not_found() -> anyhow::Result<()> {
+    // 準備
+    let settings = app_settings()?;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::new();
+
+    // 実行
+    let response = client
+        .get(format!("{}/health-check", app.root_uri))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = split_response(response).await?;
+    let body: serde_json::Value = serde_json::from_str(&response.body)?;
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::NOT_FOUND, response.status_code);
+    assert_eq!(
+        mime::APPLICATION_JSON.to_string(),
+        response
+            .headers
+            .get(reqwest::header::CONTENT_TYPE)
+            .unwrap()
+            .to_str()?
+    );
+    assert_eq!(body["message"], serde_json::json!("Not Found"));
+
+    Ok(())
+}
+
+/// リダイレクトを有効にした場合、プレフィックスを付与していない以前のパスへのリクエストが、
+/// プレフィックスを付与したパスにリダイレクトされることを確認
+#[tokio::test]
+#[ignore]
+async fn unprefixed_path_is_redirected_when_enabled() -> anyhow::Result<()> {
+    // 準備
+    let mut settings = app_settings()?;
+    settings.http_server.redirect_unprefixed_paths = true;
+    let app = spawn_test_app(settings).await?;
+    let client = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+
+    // 実行
+    let response = client
+        .get(format!("{}/accounts/users?page=2", app.root_uri))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::PERMANENT_REDIRECT, response.status());
+    assert_eq!(
+        format!(
+            "{}/accounts/users?page=2",
+            app.settings.http_server.api_prefix
+        ),
+        response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .unwrap()
+            .to_str()?
+    );
+
+    Ok(())
+}
+
+/// すべてのネットワークインターフェースにバインドしたHTTPサーバーが、リクエストを処理できることを確認
+#[tokio::test]
+#[ignore]
+async fn server_can_bind_to_all_interfaces() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    let mut settings = app_settings()?;
+    settings.http_server.host = String::from("0.0.0.0");
+    settings.http_server.port = 0;
+    settings.http_server.validate()?;
+    let context = RequestContext::new(
+        settings.http_server.clone(),
+        settings.password.clone(),
+        settings.authorization.clone(),
+        settings.database.connection_pool(),
+        settings.redis.connection_pool()?,
+    )?;
+
+    // 実行
+    let listener = TcpListener::bind(settings.http_server.address())?;
+    let address = listener.local_addr()?;
+    tokio::spawn(build_http_server(listener, context)?);
+    let response = reqwest::Client::new()
+        .get(format!(
+            "http://127.0.0.1:{}{}/health-check",
+            address.port(),
+            settings.http_server.api_prefix
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // 検証
+    assert!(address.ip().is_unspecified());
+    assert_eq!(reqwest::StatusCode::OK, response.status());
+
+    Ok(())
+}
+
+/// ポート0を指定して構築したアプリケーションが、OSが決定したポートでリクエストを処理して、
+/// ハンドルで停止できることを確認
+#[tokio::test]
+#[ignore]
+async fn application_can_be_built_on_port_zero() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    let mut settings = app_settings()?;
+    settings.http_server.port = 0;
+    settings.database.migrate_on_startup = false;
+    settings.initial_admin = None;
+
+    // 実行
+    let application = Application::build(settings.clone()).await?;
+    let port = application.port();
+    let handle = application.handle();
+    let running = tokio::spawn(application.run_until_stopped());
+    let response = reqwest::Client::new()
+        .get(format!(
+            "http://127.0.0.1:{}{}/health-check",
+            port, settings.http_server.api_prefix
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    handle.stop(true).await;
+
+    // 検証
+    assert_ne!(0, port);
+    assert_eq!(reqwest::StatusCode::OK, response.status());
+    running.await??;
+
+    Ok(())
+}
+
+/// TLSを有効にしたHTTPサーバーが、HTTPSでリクエストを処理できることを確認
+#[tokio::test]
+#[ignore]
+async fn server_can_serve_https_requests() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    // 自己署名証明書を生成して、一時ディレクトリに保存
+    let cert = rcgen::generate_simple_self_signed(vec![String::from("localhost")])?;
+    let cert_pem = cert.serialize_pem()?;
+    let dir = std::env::temp_dir().join(format!("awe_tls_{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir)?;
+    let cert_path = dir.join("cert.pem");
+    let key_path = dir.join("key.pem");
+    std::fs::write(&cert_path, &cert_pem)?;
+    std::fs::write(&key_path, cert.serialize_private_key_pem())?;
+
+    let mut settings = app_settings()?;
+    settings.http_server.tls = Some(TlsSettings {
+        enabled: true,
+        cert_path,
+        key_path,
+    });
+    let context = RequestContext::new(
+        settings.http_server.clone(),
+        settings.password.clone(),
+        settings.authorization.clone(),
+        settings.database.connection_pool(),
+        settings.redis.connection_pool()?,
+    )?;
+    let listener = TcpListener::bind("localhost:0")?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(build_http_server(listener, context)?);
+    // 自己署名証明書を信頼するクライアントを構築
+    let client = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(cert_pem.as_bytes())?)
+        .build()?;
+
+    // 実行
+    let response = client
+        .get(format!(
+            "https://localhost:{}{}/health-check",
+            port, settings.http_server.api_prefix
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+    let response = split_response(response).await?;
+    let body: serde_json::Value = serde_json::from_str(&response.body)?;
+    std::fs::remove_dir_all(&dir)?;
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::OK, response.status_code);
+    assert_eq!(serde_json::json!("It works!"), body["message"]);
+
+    Ok(())
+}
+
+/// ワーカースレッド数を1にしたHTTPサーバーが、リクエストを処理できることを確認
+#[tokio::test]
+#[ignore]
+async fn server_with_single_worker_works() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    let mut settings = app_settings()?;
+    settings.http_server.workers = 1;
+    settings.http_server.keep_alive_seconds = 0;
+    settings.http_server.client_request_timeout_seconds = 1;
+    settings.http_server.shutdown_timeout_seconds = 1;
+    settings.http_server.validate()?;
+    let context = RequestContext::new(
+        settings.http_server.clone(),
+        settings.password.clone(),
+        settings.authorization.clone(),
+        settings.database.connection_pool(),
+        settings.redis.connection_pool()?,
+    )?;
+    let listener = TcpListener::bind("localhost:0")?;
+    let port = listener.local_addr()?.port();
+    tokio::spawn(build_http_server(listener, context)?);
+
+    // 実行
+    let response = reqwest::Client::new()
+        .get(format!(
+            "http://localhost:{}{}/health-check",
+            port, settings.http_server.api_prefix
+        ))
+        .send()
+        .await
+        .expect("Failed to execute request.");
+
+    // 検証
+    assert_eq!(reqwest::StatusCode::OK, response.status());
+
+    Ok(())
+}
+
+/// シャットダウンシグナルを受信したHTTPサーバーが、処理中のリクエストを完了してから停止することを確認
+#[tokio::test]
+#[ignore]
+async fn server_shuts_down_gracefully() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    let mut settings = app_settings()?;
+    settings.http_server.shutdown_timeout_seconds = 10;
+    let context = RequestContext::new(
+        settings.http_server.clone(),
+        settings.password.clone(),
+        settings.authorization.clone(),
+        settings.database.connection_pool(),
+        settings.redis.connection_pool()?,
+    )?;
+    let listener = TcpListener::bind("localhost:0")?;
+    let address = listener.local_addr()?;
+    let server = build_http_server(listener, context.clone())?;
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let signal = async {
+        let _ = rx.await;
+    };
+    let running = tokio::spawn(run_until_shutdown(server, signal, context));
+
+    // 実行
+    // リクエストボディの一部のみを送信して、処理中のリクエストを作成
+    let mut stream = tokio::net::TcpStream::connect(address).await?;
+    let request = format!(
+        "POST {}/accounts/sign-in HTTP/1.1\r\n\
+         Host: localhost\r\n\
+         Content-Type: application/json\r\n\
+         Content-Length: 2\r\n\
+         Connection: close\r\n\r\n{{",
+        settings.http_server.api_prefix
+    );
+    stream.write_all(request.as_bytes()).await?;
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    // シャットダウンシグナルを送信
+    tx.send(()).unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let stopped_before_completion = running.is_finished();
+    // リクエストボディの残りを送信して、レスポンスを受信
+    stream.write_all(b"}").await?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    let result = tokio::time::timeout(Duration::from_secs(5), running).await??;
+
+    // 検証
+    assert!(!stopped_before_completion);
+    assert!(response.starts_with("HTTP/1.1 400"), "{}", response);
+    assert!(result.is_ok());
+
+    Ok(())
+}
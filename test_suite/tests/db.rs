@@ -1,6 +1,18 @@
-use infra::repositories::postgres::{IsolationLevel, PgRepository};
+use futures_util::StreamExt as _;
+use secrecy::SecretString;
 
-use crate::helpers::{app_settings, spawn_test_app};
+use domain::models::audit_log::AuditAction;
+use domain::models::primitives::EmailAddress;
+use domain::models::user::{UserId, UserPermissionCode};
+use domain::repositories::audit_log::AuditLogInput;
+use domain::repositories::user::{GuardedChange, SignUpInput, UserListFilter, UserRepository as _};
+use domain::DomainError;
+use infra::repositories::postgres::user::{insert_user_query, PgUserRepository};
+use infra::repositories::postgres::{AccessMode, IsolationLevel, PgRepository, PgTransaction};
+use infra::routes::accounts::SignUpReqBody;
+use server::startup::{connection_pools, migrate_database};
+
+use crate::helpers::{app_settings, general_user_sign_up_body, sign_up_input, spawn_test_app};
 
 /// トランザクションを開始して、コミットできるか確認
 #[tokio::test]
@@ -28,3 +40,393 @@ async fn transaction_works() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// トランザクション内で、トランザクションの分離レベル、アクセスモード及びデフェラブルを取得する。
+async fn transaction_characteristics(
+    tx: &mut PgTransaction<'_>,
+) -> anyhow::Result<(String, String, String)> {
+    let row = sqlx::query_as::<_, (String, String, String)>(
+        r#"
+        SELECT
+            current_setting('transaction_isolation'),
+            current_setting('transaction_read_only'),
+            current_setting('transaction_deferrable')
+        "#,
+    )
+    .fetch_one(&mut **tx)
+    .await?;
+
+    Ok(row)
+}
+
+/// 指定したトランザクション分離レベルとアクセスモードが、トランザクション内で有効になっていることを確認
+#[tokio::test]
+#[ignore]
+async fn transaction_characteristics_take_effect() -> anyhow::Result<()> {
+    // 準備
+    let settings = app_settings()?;
+    let app = spawn_test_app(settings).await?;
+    let repo = PgRepository::<i32>::new(app.pg_pool.clone());
+
+    // デフォルト
+    {
+        let mut tx = repo.begin().await?;
+        let (isolation_level, read_only, _) = transaction_characteristics(&mut tx).await?;
+        assert_eq!("read committed", isolation_level);
+        assert_eq!("off", read_only);
+    }
+
+    // リピータブルリード
+    {
+        let mut tx = repo
+            .begin_with_level(IsolationLevel::RepeatableRead)
+            .await?;
+        let (isolation_level, read_only, _) = transaction_characteristics(&mut tx).await?;
+        assert_eq!("repeatable read", isolation_level);
+        assert_eq!("off", read_only);
+    }
+
+    // シリアライザブルかつ読み込み専用
+    {
+        let mut tx = repo
+            .begin_with_mode(IsolationLevel::Serializable, AccessMode::ReadOnly)
+            .await?;
+        let (isolation_level, read_only, deferrable) = transaction_characteristics(&mut tx).await?;
+        assert_eq!("serializable", isolation_level);
+        assert_eq!("on", read_only);
+        assert_eq!("off", deferrable);
+    }
+
+    // リードコミットかつ読み書き、デフェラブルでない
+    {
+        let mut tx = repo
+            .begin_with_full(IsolationLevel::ReadCommit, AccessMode::ReadWrite, false)
+            .await?;
+        let (isolation_level, read_only, deferrable) = transaction_characteristics(&mut tx).await?;
+        assert_eq!("read committed", isolation_level);
+        assert_eq!("off", read_only);
+        assert_eq!("off", deferrable);
+    }
+
+    Ok(())
+}
+
+/// シリアライザブルかつ読み込み専用のデフェラブルなトランザクションを開始できることを確認
+#[tokio::test]
+#[ignore]
+async fn serializable_read_only_deferrable_transaction_works() -> anyhow::Result<()> {
+    // 準備
+    let settings = app_settings()?;
+    let app = spawn_test_app(settings).await?;
+    let repo = PgRepository::<i32>::new(app.pg_pool.clone());
+
+    // 実行
+    let mut tx = repo
+        .begin_with_full(IsolationLevel::Serializable, AccessMode::ReadOnly, true)
+        .await?;
+
+    // 検証
+    let (isolation_level, read_only, deferrable) = transaction_characteristics(&mut tx).await?;
+    assert_eq!("serializable", isolation_level);
+    assert_eq!("on", read_only);
+    assert_eq!("on", deferrable);
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// クロージャーがエラーを返した場合は、トランザクション内で登録したユーザーがロールバックされる
+/// ことを確認
+#[tokio::test]
+#[ignore]
+async fn with_transaction_rolls_back_when_closure_fails() -> anyhow::Result<()> {
+    // 準備
+    let app = spawn_test_app(app_settings()?).await?;
+    let repo = PgUserRepository::new(app.pg_pool.clone());
+    let input = sign_up_input(general_user_sign_up_body(), &app.settings.password);
+    let user_id = input.id;
+
+    // 実行
+    let result = repo
+        .with_transaction(move |tx| {
+            Box::pin(async move {
+                insert_user_query(input)
+                    .fetch_one(&mut **tx)
+                    .await
+                    .map_err(|e| DomainError::Repository(e.into()))?;
+                Err::<(), _>(DomainError::Validation("登録後に失敗".into()))
+            })
+        })
+        .await;
+
+    // 検証
+    assert!(matches!(result, Err(DomainError::Validation(_))));
+    assert!(repo.by_id(user_id).await?.is_none());
+
+    Ok(())
+}
+
+/// クロージャーが成功した場合は、トランザクション内で登録したユーザーがコミットされることを確認
+#[tokio::test]
+#[ignore]
+async fn with_transaction_level_commits_when_closure_succeeds() -> anyhow::Result<()> {
+    // 準備
+    let app = spawn_test_app(app_settings()?).await?;
+    let repo = PgUserRepository::new(app.pg_pool.clone());
+    let input = sign_up_input(general_user_sign_up_body(), &app.settings.password);
+    let user_id = input.id;
+
+    // 実行
+    let isolation_level = repo
+        .with_transaction_level(IsolationLevel::Serializable, move |tx| {
+            Box::pin(async move {
+                insert_user_query(input)
+                    .fetch_one(&mut **tx)
+                    .await
+                    .map_err(|e| DomainError::Repository(e.into()))?;
+                sqlx::query_scalar::<_, String>("SELECT current_setting('transaction_isolation')")
+                    .fetch_one(&mut **tx)
+                    .await
+                    .map_err(|e| DomainError::Repository(e.into()))
+            })
+        })
+        .await?;
+
+    // 検証
+    assert_eq!("serializable", isolation_level);
+    assert!(repo.by_id(user_id).await?.is_some());
+
+    Ok(())
+}
+
+/// 数百人のユーザーを、ストリームで小さなバッファに分けて、すべて取得できることを確認
+#[tokio::test]
+#[ignore]
+async fn users_can_be_streamed_in_small_buffers() -> anyhow::Result<()> {
+    // 準備
+    const NUMBER_OF_USERS: usize = 300;
+    let app = spawn_test_app(app_settings()?).await?;
+    let repo = PgUserRepository::new(app.pg_pool.clone());
+    // パスワードのハッシュ化に時間がかかるため、1人分の入力を複製して登録
+    let base = sign_up_input(general_user_sign_up_body(), &app.settings.password);
+    let mut tx = app.pg_pool.begin().await?;
+    for i in 0..NUMBER_OF_USERS {
+        let input = SignUpInput {
+            id: UserId::default(),
+            email: EmailAddress::new(format!("user{:03}@example.com", i))?,
+            ..base.clone()
+        };
+        insert_user_query(input).fetch_one(&mut *tx).await?;
+    }
+    tx.commit().await?;
+
+    // 実行
+    let mut chunks = repo.stream(UserListFilter::default()).chunks(16);
+    let mut number_of_users = 0;
+    let mut largest_chunk = 0;
+    while let Some(chunk) = chunks.next().await {
+        largest_chunk = largest_chunk.max(chunk.len());
+        for user in chunk {
+            user?;
+            number_of_users += 1;
+        }
+    }
+
+    // 検証
+    assert_eq!(NUMBER_OF_USERS, number_of_users);
+    assert!(largest_chunk <= 16);
+
+    Ok(())
+}
+
+/// 削除したユーザーは、最後にサインインした日時を更新しないことを確認
+#[tokio::test]
+#[ignore]
+async fn last_sign_in_of_deleted_user_is_not_updated() -> anyhow::Result<()> {
+    // 準備
+    let app = spawn_test_app(app_settings()?).await?;
+    let repo = PgUserRepository::new(app.pg_pool.clone());
+    let user = app
+        .register_user(sign_up_input(
+            general_user_sign_up_body(),
+            &app.settings.password,
+        ))
+        .await?;
+    repo.soft_delete(user.id, None).await?;
+
+    // 実行
+    let record = repo.update_last_sign_in(user.id).await?;
+
+    // 検証
+    assert!(record.is_none());
+    let last_sign_in_at = sqlx::query_scalar::<_, Option<time::OffsetDateTime>>(
+        "SELECT last_sign_in_at FROM users WHERE id = $1",
+    )
+    .bind(user.id)
+    .fetch_one(&app.pg_pool)
+    .await?;
+    assert!(last_sign_in_at.is_none());
+
+    Ok(())
+}
+
+/// 2人の管理者のアカウントを同時に削除した場合に、一方のアカウントのみ削除されることを確認
+#[tokio::test]
+#[ignore]
+async fn last_admin_is_kept_when_admins_are_deleted_concurrently() -> anyhow::Result<()> {
+    // 準備
+    let app = spawn_test_app(app_settings()?).await?;
+    let repo = PgUserRepository::new(app.pg_pool.clone());
+    let mut admins = vec![];
+    for email in ["admin1@example.com", "admin2@example.com"] {
+        let body = SignUpReqBody {
+            email: String::from(email),
+            user_permission_code: UserPermissionCode::Admin as i16,
+            ..general_user_sign_up_body()
+        };
+        admins.push(
+            app.register_user(sign_up_input(body, &app.settings.password))
+                .await?,
+        );
+    }
+
+    // 実行
+    let (first, second) = tokio::join!(
+        repo.soft_delete(admins[0].id, None),
+        repo.soft_delete(admins[1].id, None)
+    );
+
+    // 検証
+    let results = [first?, second?];
+    assert!(results.contains(&GuardedChange::Changed));
+    assert!(results.contains(&GuardedChange::LastAdmin));
+    assert_eq!(1, repo.count_admins().await?);
+
+    Ok(())
+}
+
+/// 2人の管理者を同時に一般ユーザーに変更した場合に、一方のユーザー権限のみ変更されることを確認
+#[tokio::test]
+#[ignore]
+async fn last_admin_is_kept_when_admins_are_demoted_concurrently() -> anyhow::Result<()> {
+    // 準備
+    let app = spawn_test_app(app_settings()?).await?;
+    let repo = PgUserRepository::new(app.pg_pool.clone());
+    let mut admins = vec![];
+    for email in ["admin1@example.com", "admin2@example.com"] {
+        let body = SignUpReqBody {
+            email: String::from(email),
+            user_permission_code: UserPermissionCode::Admin as i16,
+            ..general_user_sign_up_body()
+        };
+        admins.push(
+            app.register_user(sign_up_input(body, &app.settings.password))
+                .await?,
+        );
+    }
+    let audit = |user_id: UserId| AuditLogInput {
+        actor_user_id: user_id,
+        action: AuditAction::ChangeUserPermission,
+        target_user_id: Some(user_id),
+        request_id: None,
+        details: serde_json::json!({}),
+    };
+
+    // 実行
+    let (first, second) = tokio::join!(
+        repo.update_permission(
+            admins[0].id,
+            UserPermissionCode::General,
+            audit(admins[0].id)
+        ),
+        repo.update_permission(
+            admins[1].id,
+            UserPermissionCode::General,
+            audit(admins[1].id)
+        )
+    );
+
+    // 検証
+    let results = [first?, second?];
+    assert!(results.contains(&GuardedChange::Changed));
+    assert!(results.contains(&GuardedChange::LastAdmin));
+    assert_eq!(1, repo.count_admins().await?);
+
+    Ok(())
+}
+
+/// アプリケーションの起動時にデータベースをマイグレートする設定で起動した場合に、
+/// マイグレートしていないデータベースに`users`テーブルが作成されることを確認
+#[tokio::test]
+#[ignore]
+async fn database_is_migrated_on_startup() -> anyhow::Result<()> {
+    // 準備
+    let mut settings = app_settings()?;
+    settings.database.migrate_on_startup = true;
+
+    // 実行
+    let app = spawn_test_app(settings).await?;
+
+    // 検証
+    let exists = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT FROM information_schema.tables
+            WHERE table_schema = 'public' AND table_name = 'users'
+        )
+        "#,
+    )
+    .fetch_one(&app.pg_pool)
+    .await?;
+    assert!(exists);
+    // すでにマイグレートしたデータベースを再度マイグレートしてもエラーにならない
+    migrate_database(&app.pg_pool).await?;
+
+    Ok(())
+}
+
+/// データベースの接続を確認する設定で、誤ったパスワードを設定して起動した場合に、
+/// HTTPサーバーがリクエストを待ち受ける前にエラーになることを確認
+#[tokio::test]
+#[ignore]
+async fn startup_fails_fast_when_database_password_is_wrong() -> anyhow::Result<()> {
+    // 準備
+    let mut settings = app_settings()?;
+    settings.database.password = SecretString::new(String::from("wrong-database-pass"));
+    settings.database.fail_fast = true;
+
+    // 実行
+    let result = connection_pools(&settings.database, &settings.redis).await;
+
+    // 検証
+    let error = result.expect_err("connection pools should not be created");
+    assert!(
+        error
+            .to_string()
+            .contains("PostgreSQLに接続できないため、アプリケーションの起動を中止しました。"),
+        "{}",
+        error
+    );
+    assert!(!error.to_string().contains("wrong-database-pass"));
+
+    Ok(())
+}
+
+/// データベースの接続を確認しない設定で、誤ったパスワードを設定して起動した場合に、
+/// 接続プールを構築できることを確認
+#[tokio::test]
+#[ignore]
+async fn startup_does_not_connect_to_database_when_fail_fast_is_disabled() -> anyhow::Result<()> {
+    // 準備
+    let mut settings = app_settings()?;
+    settings.database.password = SecretString::new(String::from("wrong-database-pass"));
+    settings.database.fail_fast = false;
+
+    // 実行
+    let result = connection_pools(&settings.database, &settings.redis).await;
+
+    // 検証
+    assert!(result.is_ok());
+
+    Ok(())
+}
@@ -1,11 +1,11 @@
-use std::net::TcpListener;
 use std::path::Path;
+use std::sync::{Arc, Mutex};
 
-use anyhow::Context as _;
+use async_trait::async_trait;
 use deadpool_redis::Pool as RedisPool;
 use infra::repositories::redis::token::RedisTokenRepository;
 use once_cell::sync::Lazy;
-use reqwest::header::{HeaderValue, CONTENT_TYPE};
+use reqwest::header::{HeaderValue, ACCEPT_LANGUAGE, CONTENT_TYPE, IF_MATCH, IF_NONE_MATCH};
 use secrecy::{ExposeSecret, SecretString};
 use sqlx::{Connection as _, Executor as _, PgConnection, PgPool};
 use use_cases::accounts::SignInUseCaseInput;
@@ -19,14 +19,23 @@ use domain::models::primitives::*;
 use domain::models::user::{UserId, UserPermission, UserPermissionCode, UserPermissionName};
 use domain::repositories::token::TokenContent;
 use domain::repositories::token::TokenRepository;
+use domain::repositories::totp::TotpRepository;
 use domain::repositories::user::{SignUpInput, SignUpInputBuilder, SignUpOutput, UserRepository};
+use infra::repositories::postgres::totp::PgTotpRepository;
 use infra::repositories::postgres::user::{insert_user_query, InsertedUserRow, PgUserRepository};
 use infra::routes::accounts::SignUpReqBody;
+use infra::routes::{token_cookie_name, ACCESS_TOKEN_KEY};
 use infra::RequestContext;
-use server::startup::build_http_server;
+use server::startup::{migrate_database, request_context, Application};
 use server::telemetry::{generate_log_subscriber, init_log_subscriber};
+use use_cases::notifications::{
+    EmailSender, EMAIL_CHANGE_SUBJECT, EMAIL_CHANGE_TOKEN_LABEL, EMAIL_VERIFICATION_SUBJECT,
+    EMAIL_VERIFICATION_TOKEN_LABEL,
+};
 use use_cases::passwords::generate_phc_string;
 use use_cases::settings::PasswordSettings;
+use use_cases::totp::decrypt_totp_secret;
+use use_cases::UseCaseResult;
 
 /// 分解したレスポンス
 pub struct ResponseParts {
@@ -51,12 +60,23 @@ pub async fn split_response(response: reqwest::Response) -> anyhow::Result<Respo
 static TRACING: Lazy<()> = Lazy::new(|| {
     let default_level = log::Level::Info;
     let subscriber_name = String::from("test");
+    // ログの出力形式は、アプリケーション設定に従う
+    let format = app_settings()
+        .map(|settings| settings.logging.format)
+        .unwrap_or_default();
 
     if std::env::var("TEST_LOG").is_ok() {
-        let subscriber = generate_log_subscriber(subscriber_name, default_level, std::io::stdout);
+        let subscriber = generate_log_subscriber(
+            subscriber_name,
+            default_level,
+            format,
+            None,
+            std::io::stdout,
+        );
         init_log_subscriber(subscriber);
     } else {
-        let subscriber = generate_log_subscriber(subscriber_name, default_level, std::io::sink);
+        let subscriber =
+            generate_log_subscriber(subscriber_name, default_level, format, None, std::io::sink);
         init_log_subscriber(subscriber);
     }
 });
@@ -73,40 +93,401 @@ pub struct TestApp {
     pub pg_pool: PgPool,
     /// Redis接続プール
     pub redis_pool: RedisPool,
+    /// アプリが送信したEメールを記録するEメール送信者
+    pub email_sender: Arc<RecordingEmailSender>,
+    /// アプリと共有するリクエストコンテキスト
+    pub context: RequestContext,
 }
 
 impl TestApp {
+    /// APIのパスのプレフィックスを付与したURIを返す。
+    ///
+    /// # 引数
+    ///
+    /// * `path` - プレフィックスを付与するパス
+    ///
+    /// # 戻り値
+    ///
+    /// URI
+    pub fn api_uri(&self, path: &str) -> String {
+        format!(
+            "{}{}{}",
+            self.root_uri, self.settings.http_server.api_prefix, path
+        )
+    }
+
     pub async fn sign_up(&self, body: String) -> anyhow::Result<reqwest::Response> {
-        let client = reqwest::Client::new();
-        client
-            .post(&format!("{}/accounts/sign-up", self.root_uri))
-            .body(body)
-            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+        self.sign_up_request(body)
             .send()
             .await
             .map_err(|e| e.into())
     }
 
+    /// `Accept-Language`ヘッダを指定して、サインアップをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `body` - リクエストボディ
+    /// * `accept_language` - `Accept-Language`ヘッダの値
+    pub async fn sign_up_with_accept_language(
+        &self,
+        body: String,
+        accept_language: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        self.sign_up_request(body)
+            .header(ACCEPT_LANGUAGE, accept_language)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// `Idempotency-Key`ヘッダを指定して、サインアップをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `body` - リクエストボディ
+    /// * `key` - 冪等キー
+    pub async fn sign_up_with_idempotency_key(
+        &self,
+        body: String,
+        key: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        self.sign_up_request(body)
+            .header("Idempotency-Key", key)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// アクセストークンを指定して、サインアップをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `body` - リクエストボディ
+    /// * `token` - アクセストークン
+    pub async fn sign_up_with_access_token(
+        &self,
+        body: String,
+        token: &SecretString,
+    ) -> anyhow::Result<reqwest::Response> {
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(
+            self.sign_up_request(body),
+            token.clone(),
+            true,
+            &cookie_name,
+        )
+        .send()
+        .await
+        .map_err(|e| e.into())
+    }
+
+    fn sign_up_request(&self, body: String) -> reqwest::RequestBuilder {
+        reqwest::Client::new()
+            .post(self.api_uri("/accounts/sign-up"))
+            .body(body)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+    }
+
     pub async fn sign_in(
         &self,
         email: String,
         password: SecretString,
     ) -> anyhow::Result<reqwest::Response> {
-        let client = reqwest::Client::new();
-        let body = format!(
-            r#"{{"email": "{}", "password": "{}" }}"#,
-            email,
-            password.expose_secret()
-        );
-        client
-            .post(&format!("{}/accounts/sign-in", self.root_uri))
-            .body(body)
+        // 生成したパスワードは`"`や`\`を含む場合があるため、JSONとしてエスケープ
+        let body = serde_json::json!({
+            "email": email,
+            "password": password.expose_secret(),
+        });
+        reqwest::Client::new()
+            .post(self.api_uri("/accounts/sign-in"))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Eメールアドレスの確認をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - Eメールアドレスを確認するトークン
+    pub async fn verify_email(&self, token: &str) -> anyhow::Result<reqwest::Response> {
+        reqwest::Client::new()
+            .post(self.api_uri("/accounts/verify-email"))
+            .body(format!(r#"{{"token": "{}"}}"#, token))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Eメールアドレスを確認するトークンの再送信をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `email` - Eメールアドレス
+    pub async fn resend_email_verification(
+        &self,
+        email: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        reqwest::Client::new()
+            .post(self.api_uri("/accounts/verify-email/resend"))
+            .body(format!(r#"{{"email": "{}"}}"#, email))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// パスワードで再認証する。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `password` - 現在のパスワード
+    pub async fn reauthenticate(
+        &self,
+        token: &SecretString,
+        password: &SecretString,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .post(self.api_uri("/accounts/reauthenticate"))
+            .body(format!(r#"{{"password": "{}"}}"#, password.expose_secret()))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Eメールアドレスの変更をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `password` - 現在のパスワード
+    /// * `email` - 変更後のEメールアドレス
+    pub async fn request_email_change(
+        &self,
+        token: &SecretString,
+        password: &SecretString,
+        email: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .post(self.api_uri("/accounts/email-change"))
+            .body(format!(
+                r#"{{"password": "{}", "email": "{}"}}"#,
+                password.expose_secret(),
+                email
+            ))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// Eメールアドレスの変更の確定をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - Eメールアドレスの変更を確定するトークン
+    pub async fn confirm_email_change(&self, token: &str) -> anyhow::Result<reqwest::Response> {
+        reqwest::Client::new()
+            .post(self.api_uri("/accounts/email-change/confirm"))
+            .body(format!(r#"{{"token": "{}"}}"#, token))
             .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
             .send()
             .await
             .map_err(|e| e.into())
     }
 
+    /// 2要素認証サインインをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `mfa_token` - 2要素認証を待機しているサインインを示すトークン
+    /// * `code` - TOTPコード
+    pub async fn sign_in_with_totp(
+        &self,
+        mfa_token: &str,
+        code: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        reqwest::Client::new()
+            .post(self.api_uri("/accounts/sign-in/2fa"))
+            .body(format!(
+                r#"{{"token": "{}", "code": "{}"}}"#,
+                mfa_token, code
+            ))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// 要求されたパスワードの変更をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - サインインで受け取ったパスワードを変更するためのトークン
+    /// * `new_password` - 変更後のパスワード
+    pub async fn change_required_password(
+        &self,
+        token: &str,
+        new_password: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        reqwest::Client::new()
+            .post(self.api_uri("/accounts/password"))
+            .json(&serde_json::json!({ "token": token, "newPassword": new_password }))
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// 2要素認証の準備をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    pub async fn set_up_totp(&self, token: &SecretString) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new().post(self.api_uri("/accounts/2fa/setup"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// 2要素認証の有効化をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `code` - TOTPコード
+    pub async fn enable_totp(
+        &self,
+        token: &SecretString,
+        code: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        self.totp_code_request("/accounts/2fa/enable", token, code)
+            .await
+    }
+
+    /// 2要素認証の無効化をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `code` - TOTPコード
+    pub async fn disable_totp(
+        &self,
+        token: &SecretString,
+        code: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        self.totp_code_request("/accounts/2fa/disable", token, code)
+            .await
+    }
+
+    async fn totp_code_request(
+        &self,
+        path: &str,
+        token: &SecretString,
+        code: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .post(self.api_uri(path))
+            .body(format!(r#"{{"code": "{}"}}"#, code))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// ユーザーが登録したTOTPの秘密鍵を復号して返す。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    ///
+    /// # 戻り値
+    ///
+    /// TOTPの秘密鍵
+    pub async fn totp_secret(&self, user_id: UserId) -> anyhow::Result<Vec<u8>> {
+        let secret = PgTotpRepository::new(self.pg_pool.clone())
+            .totp_secret(user_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("totp secret was not registered"))?;
+
+        decrypt_totp_secret(
+            &self.settings.authorization.totp_secret_key,
+            &secret.encrypted_secret,
+        )
+        .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    /// アプリが最後に送信した、Eメールアドレスを確認するトークンを記載したEメールから、
+    /// トークンを取得する。
+    ///
+    /// Eメールはバックグラウンドで送信されるため、Eメールを送信するまで待機する。
+    ///
+    /// # 引数
+    ///
+    /// * `email` - 宛先のEメールアドレス
+    ///
+    /// # 戻り値
+    ///
+    /// Eメールアドレスを確認するトークン
+    pub async fn email_verification_token(&self, email: &str) -> Option<String> {
+        self.sent_token(
+            email,
+            EMAIL_VERIFICATION_SUBJECT,
+            EMAIL_VERIFICATION_TOKEN_LABEL,
+        )
+        .await
+    }
+
+    /// アプリが最後に送信した、Eメールアドレスの変更を確定するトークンを記載したEメールから、
+    /// トークンを取得する。
+    ///
+    /// Eメールはバックグラウンドで送信されるため、Eメールを送信するまで待機する。
+    ///
+    /// # 引数
+    ///
+    /// * `email` - 宛先の変更後のEメールアドレス
+    ///
+    /// # 戻り値
+    ///
+    /// Eメールアドレスの変更を確定するトークン
+    pub async fn email_change_token(&self, email: &str) -> Option<String> {
+        self.sent_token(email, EMAIL_CHANGE_SUBJECT, EMAIL_CHANGE_TOKEN_LABEL)
+            .await
+    }
+
+    /// アプリが最後に送信したEメールの本文から、ラベルに続くトークンを取得する。
+    async fn sent_token(&self, email: &str, subject: &str, label: &str) -> Option<String> {
+        for _ in 0..50 {
+            let token = self
+                .email_sender
+                .find_last(email, subject)
+                .and_then(|body| {
+                    body.lines()
+                        .find_map(|line| line.strip_prefix(label))
+                        .map(String::from)
+                });
+            if token.is_some() {
+                return token;
+            }
+            tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        }
+
+        None
+    }
+
     /// ユーザーの一覧をリクエストする。
     ///
     /// # 引数
@@ -124,13 +505,375 @@ impl TestApp {
         auth_header: Option<bool>,
     ) -> anyhow::Result<reqwest::Response> {
         let client = reqwest::Client::new();
-        let mut builder = client.get(format!("{}/accounts/users", self.root_uri));
-        if token.is_some() {
-            builder = append_access_token(builder, token.unwrap(), auth_header.unwrap());
+        let mut builder = client.get(self.api_uri("/accounts/users"));
+        if let Some(token) = token {
+            let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+            builder = append_access_token(builder, token, auth_header.unwrap(), &cookie_name);
         }
         builder.send().await.map_err(|e| e.into())
     }
 
+    /// クエリ文字列を指定して、ユーザーのリストをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `query` - 絞り込み条件、並び順及びページを指定するクエリ文字列
+    pub async fn list_users_with_query(
+        &self,
+        token: &SecretString,
+        query: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder =
+            reqwest::Client::new().get(self.api_uri(&format!("/accounts/users{}", query)));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// アカウントがロックされたユーザーのリストをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `query` - ページを指定するクエリ文字列
+    pub async fn list_locked_users(
+        &self,
+        token: &SecretString,
+        query: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder =
+            reqwest::Client::new().get(self.api_uri(&format!("/accounts/users/locked{}", query)));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// メンテナンスの切り替えをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `enabled` - メンテナンスを開始する場合は`true`、終了する場合は`false`
+    pub async fn switch_maintenance(
+        &self,
+        token: &SecretString,
+        enabled: bool,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .post(self.api_uri("/admin/maintenance"))
+            .json(&serde_json::json!({ "enabled": enabled }));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// 保持期間を経過したレコードの消去をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    pub async fn run_retention(&self, token: &SecretString) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new().post(self.api_uri("/admin/retention/run"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// 監査ログリストをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `target_user_id` - 操作の対象となったユーザーのユーザーIDで絞り込む場合に指定
+    pub async fn list_audit_logs(
+        &self,
+        token: &SecretString,
+        target_user_id: Option<UserId>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut builder = reqwest::Client::new().get(self.api_uri("/admin/audit-logs"));
+        if let Some(target_user_id) = target_user_id {
+            builder = builder.query(&[("targetUserId", target_user_id.to_string())]);
+        }
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// セッションの状態をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン、匿名のユーザーとしてリクエストする場合は`None`
+    pub async fn session(&self, token: Option<SecretString>) -> anyhow::Result<reqwest::Response> {
+        let mut builder = reqwest::Client::new().get(self.api_uri("/accounts/session"));
+        if let Some(token) = token {
+            let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+            builder = append_access_token(builder, token, true, &cookie_name);
+        }
+        builder.send().await.map_err(|e| e.into())
+    }
+
+    /// サインインしているセッションのリストをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    pub async fn list_sessions(&self, token: &SecretString) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new().get(self.api_uri("/accounts/sessions"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// セッションの無効化をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `session_id` - セッションID
+    pub async fn revoke_session(
+        &self,
+        token: &SecretString,
+        session_id: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .delete(self.api_uri(&format!("/accounts/sessions/{}", session_id)));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// トークンの検査をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `credential` - 内部サービスの資格情報
+    /// * `token` - 検査するトークン
+    pub async fn introspect_token(
+        &self,
+        credential: Option<&str>,
+        token: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut builder = reqwest::Client::new()
+            .post(self.api_uri("/accounts/token/introspect"))
+            .body(format!(r#"{{"token": "{}"}}"#, token))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(credential) = credential {
+            builder = builder.bearer_auth(credential);
+        }
+
+        builder.send().await.map_err(|e| e.into())
+    }
+
+    /// ユーザーのエクスポートをリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `query` - 絞り込み条件を指定するクエリ文字列
+    pub async fn export_users(
+        &self,
+        token: &SecretString,
+        query: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder =
+            reqwest::Client::new().get(self.api_uri(&format!("/accounts/users/export{}", query)));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// 管理者によるユーザーの登録をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `body` - 登録するユーザーを記録したリクエストボディ
+    pub async fn create_user(
+        &self,
+        token: &SecretString,
+        body: String,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .post(self.api_uri("/accounts/users"))
+            .body(body)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// ユーザーの一括登録をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `body` - 登録するユーザーの配列を記録したリクエストボディ
+    /// * `query` - 一括登録の方法を指定するクエリ文字列
+    pub async fn import_users(
+        &self,
+        token: &SecretString,
+        body: String,
+        query: &str,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .post(self.api_uri(&format!("/accounts/users/import{}", query)))
+            .body(body)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// ユーザー権限の変更をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `user_id` - ユーザー権限を変更するユーザーのユーザーID
+    /// * `user_permission_code` - 変更後のユーザー権限コード
+    pub async fn change_user_permission(
+        &self,
+        token: &SecretString,
+        user_id: UserId,
+        user_permission_code: i16,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .put(self.api_uri(&format!("/accounts/users/{}/permission", user_id)))
+            .body(format!(
+                r#"{{"userPermissionCode": {}}}"#,
+                user_permission_code
+            ))
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// ユーザー詳細をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `user_id` - 取得するユーザーのユーザーID
+    /// * `if_none_match` - `If-None-Match`ヘッダに設定するエンティティタグ
+    pub async fn user_detail(
+        &self,
+        token: &SecretString,
+        user_id: UserId,
+        if_none_match: Option<&str>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut builder =
+            reqwest::Client::new().get(self.api_uri(&format!("/accounts/users/{}", user_id)));
+        if let Some(if_none_match) = if_none_match {
+            builder = builder.header(IF_NONE_MATCH, if_none_match);
+        }
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// アカウントの状況をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `user_id` - アカウントの状況を取得するユーザーのユーザーID
+    pub async fn account_activity(
+        &self,
+        token: &SecretString,
+        user_id: UserId,
+    ) -> anyhow::Result<reqwest::Response> {
+        let builder = reqwest::Client::new()
+            .get(self.api_uri(&format!("/accounts/users/{}/activity", user_id)));
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// プロフィールの更新をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `user_id` - プロフィールを更新するユーザーのユーザーID
+    /// * `body` - リクエスト・ボディ
+    /// * `if_match` - `If-Match`ヘッダに設定するエンティティタグ
+    pub async fn update_profile(
+        &self,
+        token: &SecretString,
+        user_id: UserId,
+        body: String,
+        if_match: Option<&str>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut builder = reqwest::Client::new()
+            .patch(self.api_uri(&format!("/accounts/users/{}", user_id)))
+            .body(body)
+            .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        if let Some(if_match) = if_match {
+            builder = builder.header(IF_MATCH, if_match);
+        }
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
+    /// アカウントの削除をリクエストする。
+    ///
+    /// # 引数
+    ///
+    /// * `token` - アクセストークン
+    /// * `user_id` - アカウントを削除するユーザーのユーザーID
+    /// * `password` - ユーザー自身がアカウントを削除する場合のパスワード
+    pub async fn delete_user(
+        &self,
+        token: &SecretString,
+        user_id: UserId,
+        password: Option<&SecretString>,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut builder =
+            reqwest::Client::new().delete(self.api_uri(&format!("/accounts/users/{}", user_id)));
+        if let Some(password) = password {
+            builder = builder
+                .body(format!(r#"{{"password": "{}"}}"#, password.expose_secret()))
+                .header(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        }
+        let cookie_name = token_cookie_name(&self.settings.http_server, ACCESS_TOKEN_KEY);
+        append_access_token(builder, token.clone(), true, &cookie_name)
+            .send()
+            .await
+            .map_err(|e| e.into())
+    }
+
     pub async fn register_user(&self, input: SignUpInput) -> anyhow::Result<SignUpOutput> {
         let repo = PgUserRepository::new(self.pg_pool.clone());
 
@@ -146,9 +889,9 @@ impl TestApp {
 
 pub fn app_settings() -> anyhow::Result<AppSettings> {
     // 環境変数からアプリケーションの動作環境を取得
-    let app_env: AppEnvironment = std::env::var(ENV_APP_ENVIRONMENT)
-        .unwrap_or_else(|_| String::from("development"))
-        .into();
+    let app_env = AppEnvironment::try_from(
+        std::env::var(ENV_APP_ENVIRONMENT).unwrap_or_else(|_| String::from("development")),
+    )?;
     // 環境変数や設定ファイルからアプリケーション設定を読み込み
     let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
     let settings_dir = dir.join("..").join(SETTINGS_DIR_NAME);
@@ -172,30 +915,33 @@ pub async fn spawn_test_app(mut settings: AppSettings) -> anyhow::Result<TestApp
     settings.database.name = format!("awe_test_{}", Uuid::new_v4()).replace('-', "_");
     // テスト用のデータベースを作成して、接続及び構成
     let pg_pool = configure_database(&settings.database).await?;
+    // アプリケーションの起動時にデータベースをマイグレートする場合は、サーバーと同様にマイグレート
+    if settings.database.migrate_on_startup {
+        migrate_database(&pg_pool).await?;
+    }
     // Redis接続プールを構築
     let redis_pool = settings.redis.connection_pool()?;
-    // テスト用のデータベースに接続するリポジトリのコンテナを構築
-    let context = RequestContext::new(
-        settings.http_server.clone(),
-        settings.password.clone(),
-        settings.authorization.clone(),
-        pg_pool.clone(),
-        redis_pool.clone(),
-    );
+    // テスト用のデータベースに接続するリクエストコンテキストを構築
+    // アプリが送信したEメールは、Eメールを送信せずに記録する
+    let email_sender = Arc::new(RecordingEmailSender::default());
+    let context = request_context(&settings, pg_pool.clone(), redis_pool.clone())?
+        .with_email_sender(email_sender.clone());
 
     // ポート0を指定してTCPソケットにバインドすることで、OSにポート番号の決定を委譲
-    let listener = TcpListener::bind("localhost:0").context("failed to bind random port")?;
-    let port = listener.local_addr().unwrap().port();
-    let server = build_http_server(listener, context)?;
+    settings.http_server.port = 0;
+    let application = Application::build_with_context(&settings, context.clone()).await?;
+    let port = application.port();
     // 統合テストが終了すると、HTTPサーバーがリッスンするポートが閉じられる。
     // すると、actix-webが提供する`Server`が終了して、ここで生み出したスレッドが終了する。
-    tokio::spawn(server);
+    tokio::spawn(application.run_until_stopped());
 
     Ok(TestApp {
         root_uri: format!("http://localhost:{}", port),
         settings,
         pg_pool,
         redis_pool,
+        email_sender,
+        context,
     })
 }
 
@@ -221,9 +967,10 @@ pub async fn configure_database(settings: &DatabaseSettings) -> anyhow::Result<P
     // データベースに接続
     let pool = PgPool::connect_with(settings.with_db()).await?;
     // データベースをマイグレート
+    // アプリケーションの起動時にデータベースをマイグレートする場合は、ここではマイグレートしない
     let crate_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
     let migrations_dir = crate_dir.join("..").join("migrations");
-    if migrations_dir.is_dir() {
+    if migrations_dir.is_dir() && !settings.migrate_on_startup {
         sqlx::migrate!("../migrations").run(&pool).await?;
     }
 
@@ -290,12 +1037,24 @@ pub fn generate_optional_remarks() -> OptionalRemarks {
 }
 
 pub fn admin_user_sign_up_body_json() -> String {
+    sign_up_body_json(UserPermissionCode::Admin)
+}
+
+/// 管理者と同じ個人情報で、ユーザー権限を指定したサインアップのリクエストボディを返す。
+///
+/// 管理者としてアクセスしないサインアップでは一般ユーザーのみ登録できるため、サインアップを
+/// リクエストする場合は一般ユーザーを指定する。
+///
+/// # 引数
+///
+/// * `user_permission_code` - ユーザー権限コード
+pub fn sign_up_body_json(user_permission_code: UserPermissionCode) -> String {
     format!(
         r#"
         {{
             "email": "{}",
             "password": "{}",
-            "userPermissionCode": 1,
+            "userPermissionCode": {},
             "familyName": "山田",
             "givenName": "太郎",
             "postalCode": "899-7103",
@@ -305,11 +1064,11 @@ pub fn admin_user_sign_up_body_json() -> String {
             "remarks": "日本に実際に存在するややこしい地名です。"
         }}
         "#,
-        ADMIN_USER_EMAIL_ADDRESS, ADMIN_USER_RAW_PASSWORD
+        ADMIN_USER_EMAIL_ADDRESS, ADMIN_USER_RAW_PASSWORD, user_permission_code as i16
     )
 }
 
-pub fn admin_user_sign_up_body(body: &str) -> SignUpReqBody {
+pub fn sign_up_body(body: &str) -> SignUpReqBody {
     serde_json::from_str::<SignUpReqBody>(body).unwrap()
 }
 
@@ -331,7 +1090,7 @@ pub fn general_user_sign_up_body() -> SignUpReqBody {
 pub fn sign_up_input(body: SignUpReqBody, settings: &PasswordSettings) -> SignUpInput {
     let email = EmailAddress::new(body.email).unwrap();
     let user_permission_code = UserPermissionCode::try_from(body.user_permission_code).unwrap();
-    let password = RawPassword::new(body.password).unwrap();
+    let password = RawPassword::with_policy(body.password, &settings.policy()).unwrap();
     let password = generate_phc_string(&password, settings).unwrap();
     let family_name = FamilyName::new(body.family_name).unwrap();
     let given_name = GivenName::new(body.given_name).unwrap();
@@ -364,6 +1123,9 @@ pub fn admin_user_sign_in_use_case_input() -> SignInUseCaseInput {
         email: EmailAddress::new(String::from(ADMIN_USER_EMAIL_ADDRESS)).unwrap(),
         password: RawPassword::new(SecretString::new(String::from(ADMIN_USER_RAW_PASSWORD)))
             .unwrap(),
+        device: None,
+        client_ip: None,
+        user_agent: None,
     }
 }
 
@@ -372,6 +1134,9 @@ pub fn general_user_sign_in_use_case_input() -> SignInUseCaseInput {
         email: EmailAddress::new(String::from(GENERAL_USER_EMAIL_ADDRESS)).unwrap(),
         password: RawPassword::new(SecretString::new(String::from(GENERAL_USER_RAW_PASSWORD)))
             .unwrap(),
+        device: None,
+        client_ip: None,
+        user_agent: None,
     }
 }
 
@@ -379,6 +1144,7 @@ fn append_access_token(
     mut builder: reqwest::RequestBuilder,
     token: SecretString,
     auth_header: bool,
+    cookie_name: &str,
 ) -> reqwest::RequestBuilder {
     if auth_header {
         builder = builder.header(
@@ -388,7 +1154,7 @@ fn append_access_token(
     } else {
         builder = builder.header(
             reqwest::header::COOKIE,
-            format!("access={}", token.expose_secret()),
+            format!("{}={}", cookie_name, token.expose_secret()),
         );
     }
 
@@ -410,7 +1176,7 @@ pub async fn register_admin_user(
     repo: &PgUserRepository,
 ) -> anyhow::Result<InsertedUserRow> {
     let json = admin_user_sign_up_body_json();
-    let body = admin_user_sign_up_body(&json);
+    let body = sign_up_body(&json);
     let input = sign_up_input(body, password_settings);
 
     let mut tx = repo.begin().await?;
@@ -433,3 +1199,47 @@ pub async fn register_general_user(
 
     Ok(user)
 }
+
+/// 送信したEメールを記録するEメール送信者
+#[derive(Debug, Default)]
+pub struct RecordingEmailSender {
+    /// 送信したEメールの宛先と件名
+    pub sent: Mutex<Vec<(String, String)>>,
+    /// 送信したEメールの本文
+    pub bodies: Mutex<Vec<String>>,
+}
+
+impl RecordingEmailSender {
+    /// 宛先と件名が一致する、最後に送信したEメールの本文を返す。
+    ///
+    /// # 引数
+    ///
+    /// * `to` - 宛先のEメールアドレス
+    /// * `subject` - 件名
+    ///
+    /// # 戻り値
+    ///
+    /// Eメールの本文
+    pub fn find_last(&self, to: &str, subject: &str) -> Option<String> {
+        let sent = self.sent.lock().unwrap();
+        let bodies = self.bodies.lock().unwrap();
+        sent.iter()
+            .zip(bodies.iter())
+            .rev()
+            .find(|((sent_to, sent_subject), _)| sent_to == to && sent_subject == subject)
+            .map(|(_, body)| body.clone())
+    }
+}
+
+#[async_trait]
+impl EmailSender for RecordingEmailSender {
+    async fn send(&self, to: EmailAddress, subject: String, body: String) -> UseCaseResult<()> {
+        // 宛先と件名、本文の順番が一致するように、両方のロックを取得してから記録
+        let mut sent = self.sent.lock().unwrap();
+        let mut bodies = self.bodies.lock().unwrap();
+        sent.push((to.value, subject));
+        bodies.push(body);
+
+        Ok(())
+    }
+}
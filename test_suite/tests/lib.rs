@@ -3,3 +3,5 @@ mod helpers;
 
 mod accounts;
 mod db;
+mod redis;
+mod telemetry;
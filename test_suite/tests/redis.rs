@@ -0,0 +1,82 @@
+use deadpool_redis::redis;
+use secrecy::SecretString;
+
+use server::startup::connection_pools;
+
+use crate::helpers::app_settings;
+
+/// パスワードで保護されたRedisに、設定したパスワードで接続できることを確認
+///
+/// `docker-compose.yml`の`redis_auth`サービスが起動していることを想定している。
+#[tokio::test]
+#[ignore]
+async fn can_connect_to_password_protected_redis() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    let mut settings = app_settings()?.redis;
+    settings.port = std::env::var("REDIS_AUTH_PORT")?.parse()?;
+    settings.password = Some(SecretString::new(std::env::var("REDIS_AUTH_PASSWORD")?));
+    settings.db = 1;
+    let pool = settings.connection_pool()?;
+
+    // 実行
+    let mut connection = pool.get().await?;
+    let pong: String = redis::cmd("PING").query_async(&mut connection).await?;
+
+    // 検証
+    assert_eq!("PONG", pong);
+
+    Ok(())
+}
+
+/// パスワードで保護されたRedisに、誤ったパスワードで接続できないことを確認
+///
+/// `docker-compose.yml`の`redis_auth`サービスが起動していることを想定している。
+#[tokio::test]
+#[ignore]
+async fn cannot_connect_to_password_protected_redis_with_wrong_password() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    let mut settings = app_settings()?.redis;
+    settings.port = std::env::var("REDIS_AUTH_PORT")?.parse()?;
+    settings.password = Some(SecretString::new(String::from("wrong-redis-pass")));
+    let pool = settings.connection_pool()?;
+
+    // 実行
+    let result = pool.get().await;
+
+    // 検証
+    assert!(result.is_err());
+
+    Ok(())
+}
+
+/// Redisの接続を確認する設定で、パスワードで保護されたRedisに誤ったパスワードを設定して
+/// 起動した場合に、HTTPサーバーがリクエストを待ち受ける前にエラーになることを確認
+///
+/// `docker-compose.yml`の`redis_auth`サービスが起動していることを想定している。
+#[tokio::test]
+#[ignore]
+async fn startup_fails_fast_when_redis_password_is_wrong() -> anyhow::Result<()> {
+    // 準備
+    dotenvx::dotenv()?;
+    let mut settings = app_settings()?;
+    settings.redis.port = std::env::var("REDIS_AUTH_PORT")?.parse()?;
+    settings.redis.password = Some(SecretString::new(String::from("wrong-redis-pass")));
+    settings.redis.fail_fast = true;
+
+    // 実行
+    let result = connection_pools(&settings.database, &settings.redis).await;
+
+    // 検証
+    let error = result.expect_err("connection pools should not be created");
+    assert!(
+        error
+            .to_string()
+            .contains("Redisに接続できないため、アプリケーションの起動を中止しました。"),
+        "{}",
+        error
+    );
+
+    Ok(())
+}
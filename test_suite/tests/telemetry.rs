@@ -0,0 +1,138 @@
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use opentelemetry::trace::{SpanId, TraceId, TracerProvider as _};
+use opentelemetry_sdk::export::trace::SpanData;
+use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+use opentelemetry_sdk::trace::TracerProvider;
+use tracing_actix_web::TracingLogger;
+
+use configurations::settings::LogFormat;
+use domain::models::user::UserPermissionCode;
+use infra::routes::accounts::accounts_scope;
+use infra::routes::ip_allowlist::IpAllowlistGuard;
+use infra::routes::telemetry::RequestSpanBuilder;
+use infra::RequestContext;
+use server::telemetry::generate_log_subscriber;
+
+use crate::helpers::{
+    app_settings, sign_up_body_json, spawn_test_app, CONTENT_TYPE_APPLICATION_JSON,
+};
+
+/// リクエストを送信したサービスのトレースID
+const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+/// リクエストを送信したサービスのスパンID
+const PARENT_SPAN_ID: &str = "00f067aa0ba902b7";
+
+/// エクスポートしたスパンから、名前が一致するスパンを取得する。
+fn find_span<'a>(spans: &'a [SpanData], name: &str) -> &'a SpanData {
+    spans
+        .iter()
+        .find(|span| span.name == name)
+        .unwrap_or_else(|| panic!("span `{}` was not exported", name))
+}
+
+/// スパンの属性の値を文字列で取得する。
+fn span_attribute(span: &SpanData, key: &str) -> Option<String> {
+    span.attributes
+        .iter()
+        .find(|kv| kv.key.as_str() == key)
+        .map(|kv| kv.value.to_string())
+}
+
+/// サインアップしたとき、リクエスト、ハンドラ及びユースケースのスパンが入れ子になったトレースを
+/// 記録することを確認
+#[actix_web::test]
+#[ignore]
+async fn sign_up_request_produces_nested_spans() -> anyhow::Result<()> {
+    // 準備
+    let app = spawn_test_app(app_settings()?).await?;
+    let settings = &app.settings;
+    let context = RequestContext::new(
+        settings.http_server.clone(),
+        settings.password.clone(),
+        settings.authorization.clone(),
+        app.pg_pool.clone(),
+        app.redis_pool.clone(),
+    )?;
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder()
+        .with_simple_exporter(exporter.clone())
+        .build();
+    let subscriber = generate_log_subscriber(
+        String::from("test"),
+        log::Level::Info,
+        LogFormat::default(),
+        Some(provider.tracer("test")),
+        std::io::sink,
+    );
+    let _guard = tracing::subscriber::set_default(subscriber);
+    let api_prefix = settings.http_server.api_prefix.clone();
+    let service = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<RequestSpanBuilder>::new())
+            .service(web::scope(&api_prefix).service(accounts_scope(
+                &settings.http_server,
+                &IpAllowlistGuard::default(),
+            )))
+            .app_data(web::Data::new(context)),
+    )
+    .await;
+    let request = test::TestRequest::post()
+        .uri(&format!("{}/accounts/sign-up", api_prefix))
+        .insert_header((CONTENT_TYPE, CONTENT_TYPE_APPLICATION_JSON))
+        .insert_header((
+            "traceparent",
+            format!("00-{}-{}-01", TRACE_ID, PARENT_SPAN_ID),
+        ))
+        .set_payload(sign_up_body_json(UserPermissionCode::General))
+        .to_request();
+
+    // 実行
+    let response = test::call_service(&service, request).await;
+    let status_code = response.status();
+    // リクエストのスパンは、レスポンスボディを読み込んだ後に閉じる
+    test::read_body(response).await;
+    provider.force_flush();
+
+    // 検証
+    assert_eq!(StatusCode::OK, status_code);
+    let spans = exporter.get_finished_spans()?;
+    let route = format!("{}/accounts/sign-up", api_prefix);
+    let request_span = find_span(&spans, &format!("POST {}", route));
+    let handler_span = find_span(&spans, "sign up handler");
+    let use_case_span = find_span(&spans, "sign up use case");
+    // すべてのスパンが、リクエストを送信したサービスのトレースに属する
+    let trace_id = TraceId::from_hex(TRACE_ID)?;
+    for span in [request_span, handler_span, use_case_span] {
+        assert_eq!(trace_id, span.span_context.trace_id());
+    }
+    // リクエスト -> ハンドラ -> ユースケースの順に入れ子になっている
+    assert_eq!(
+        SpanId::from_hex(PARENT_SPAN_ID)?,
+        request_span.parent_span_id
+    );
+    assert_eq!(
+        request_span.span_context.span_id(),
+        handler_span.parent_span_id
+    );
+    assert_eq!(
+        handler_span.span_context.span_id(),
+        use_case_span.parent_span_id
+    );
+    // リクエストのスパンに標準の属性を記録している
+    assert_eq!(
+        Some("POST"),
+        span_attribute(request_span, "http.method").as_deref()
+    );
+    assert_eq!(
+        Some(route.as_str()),
+        span_attribute(request_span, "http.route").as_deref()
+    );
+    assert_eq!(
+        Some("200"),
+        span_attribute(request_span, "http.status_code").as_deref()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use domain::models::audit_log::AuditLog;
+use domain::repositories::audit_log::{AuditLogFilter, AuditLogRepository};
+
+use crate::{UseCaseError, UseCaseResult};
+
+/// 管理者が実行した操作を記録した監査ログのリストを、操作を実行した日時の新しい順に取得する。
+///
+/// # 引数
+///
+/// * `repository` - 監査ログリポジトリ
+/// * `filter` - 監査ログリストの絞り込み条件
+///
+/// # 戻り値
+///
+/// * 監査ログを格納したベクタ
+#[tracing::instrument(name = "list audit logs use case", skip(repository))]
+pub async fn list_audit_logs(
+    repository: impl AuditLogRepository,
+    filter: AuditLogFilter,
+) -> UseCaseResult<Vec<AuditLog>> {
+    repository.list(filter).await.map_err(UseCaseError::from)
+}
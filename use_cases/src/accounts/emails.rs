@@ -0,0 +1,262 @@
+use std::sync::Arc;
+
+use secrecy::SecretString;
+
+use domain::clock::Clock;
+use domain::models::primitives::*;
+use domain::models::user::UserId;
+use domain::repositories::token::{PendingEmailChange, TokenRepository};
+use domain::repositories::user::UserRepository;
+
+use super::generate_one_time_token;
+use super::reauthentication::{confirm_reauthentication, Reauthentication};
+use super::sign_up::same_email_address_is_registered_error;
+use crate::messages::MessageKey;
+use crate::notifications::{send_email_change_confirmation, send_email_verification, EmailSender};
+use crate::settings::{AuthorizationSettings, PasswordSettings};
+use crate::{
+    UseCaseError, UseCaseErrorKind, UseCaseResult, ERR_INVALID_EMAIL_CHANGE_TOKEN,
+    ERR_INVALID_EMAIL_VERIFICATION_TOKEN,
+};
+
+/// Eメールアドレスを確認するトークンを生成して、リポジトリに登録した後、ユーザーにEメールで送信する。
+pub(super) async fn issue_email_verification_token(
+    authorization_settings: &AuthorizationSettings,
+    token_repository: &impl TokenRepository,
+    email_sender: Arc<dyn EmailSender>,
+    user_id: UserId,
+    email: EmailAddress,
+) -> UseCaseResult<()> {
+    let token = generate_one_time_token();
+    let ttl = authorization_settings.email_verification_token_seconds;
+    token_repository
+        .register_email_verification_token(user_id, &token, ttl)
+        .await
+        .map_err(UseCaseError::from)?;
+    send_email_verification(email_sender, email, token, ttl);
+
+    Ok(())
+}
+
+/// ユーザーのEメールアドレスを確認する。
+///
+/// # 引数
+///
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `token` - Eメールアドレスを確認するトークン
+#[tracing::instrument(
+    name = "verify email use case",
+    skip(user_repository, token_repository, token)
+)]
+pub async fn verify_email(
+    user_repository: impl UserRepository,
+    token_repository: impl TokenRepository,
+    token: SecretString,
+) -> UseCaseResult<()> {
+    let invalid_token_error = UseCaseError::new(
+        UseCaseErrorKind::Validation,
+        ERR_INVALID_EMAIL_VERIFICATION_TOKEN,
+        MessageKey::InvalidEmailVerificationToken,
+    );
+    // トークンを消費して、Eメールアドレスを確認するユーザーを取得
+    let user_id = token_repository
+        .consume_email_verification_token(&token)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| invalid_token_error.clone())?;
+    // ユーザーが削除されている場合は、トークンが無効であるとみなす
+    if !user_repository
+        .verify_email(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+    {
+        return Err(invalid_token_error);
+    }
+
+    Ok(())
+}
+
+/// ユーザーのEメールアドレスを確認するトークンを再送信する。
+///
+/// Eメールアドレスを持つユーザーが登録されていない場合、またはユーザーがEメールアドレスを確認済み
+/// の場合は、何もしない。Eメールアドレスが登録されているか推測されないように、いずれの場合でも
+/// 成功として扱う。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `email_sender` - Eメール送信者
+/// * `email` - Eメールアドレス
+#[tracing::instrument(
+    name = "resend email verification use case",
+    skip(authorization_settings, user_repository, token_repository, email_sender),
+    fields(user.email = %email)
+)]
+pub async fn resend_email_verification(
+    authorization_settings: &AuthorizationSettings,
+    user_repository: impl UserRepository,
+    token_repository: impl TokenRepository,
+    email_sender: Arc<dyn EmailSender>,
+    email: EmailAddress,
+) -> UseCaseResult<()> {
+    let credential = user_repository
+        .user_credential(email)
+        .await
+        .map_err(UseCaseError::from)?;
+    match credential {
+        Some(credential) if credential.email_verified_at.is_none() => {
+            issue_email_verification_token(
+                authorization_settings,
+                &token_repository,
+                email_sender,
+                credential.user_id,
+                credential.email,
+            )
+            .await
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Eメールアドレスの変更を要求する。
+///
+/// 本人であることを再確認した後、Eメールアドレスの変更を保留して、変更を確定するトークンを
+/// 変更後のEメールアドレスにEメールで送信する。Eメールアドレスは、変更後のEメールアドレスで
+/// トークンを受け取り、変更を確定するまで変更しない。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `email_sender` - Eメール送信者
+/// * `clock` - 時計
+/// * `user_id` - Eメールアドレスを変更するユーザーのユーザーID
+/// * `input` - Eメールアドレス変更要求ユースケース入力
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(
+    name = "request email change use case",
+    skip(password_settings, authorization_settings, user_repository, token_repository, email_sender, clock, input),
+    fields(user.id = %user_id, user.new_email = %input.email)
+)]
+pub async fn request_email_change(
+    password_settings: &PasswordSettings,
+    authorization_settings: &AuthorizationSettings,
+    user_repository: impl UserRepository,
+    token_repository: impl TokenRepository,
+    email_sender: Arc<dyn EmailSender>,
+    clock: &impl Clock,
+    user_id: UserId,
+    input: RequestEmailChangeUseCaseInput,
+) -> UseCaseResult<()> {
+    confirm_reauthentication(
+        password_settings,
+        authorization_settings,
+        &user_repository,
+        &token_repository,
+        clock,
+        user_id,
+        &input.reauthentication,
+    )
+    .await?;
+    // 変更後のEメールアドレスを持つユーザーが登録されていないか確認
+    if user_repository
+        .by_email(input.email.clone())
+        .await
+        .map_err(UseCaseError::from)?
+        .is_some()
+    {
+        return Err(same_email_address_is_registered_error());
+    }
+
+    // Eメールアドレスの変更を保留して、変更を確定するトークンを変更後のEメールアドレスに送信
+    let token = generate_one_time_token();
+    let ttl = authorization_settings.email_change_token_seconds;
+    let email_change = PendingEmailChange {
+        user_id,
+        email: input.email,
+    };
+    token_repository
+        .register_email_change_token(&email_change, &token, ttl)
+        .await
+        .map_err(UseCaseError::from)?;
+    send_email_change_confirmation(email_sender, email_change.email, token, ttl);
+
+    Ok(())
+}
+
+/// Eメールアドレス変更要求ユースケース入力
+pub struct RequestEmailChangeUseCaseInput {
+    /// 本人であることを再確認する方法
+    pub reauthentication: Reauthentication,
+    /// 変更後のEメールアドレス
+    pub email: EmailAddress,
+}
+
+/// Eメールアドレスの変更を確定する。
+///
+/// トークンを消費して、保留しているEメールアドレスの変更を反映した後、ユーザーに発行したアクセス
+/// トークンとリフレッシュトークンをすべて無効にする。
+/// 変更を要求してから確定するまでの間に、変更後のEメールアドレスを持つユーザーが登録された場合は、
+/// Eメールアドレスを変更しない。
+///
+/// # 引数
+///
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `token` - Eメールアドレスの変更を確定するトークン
+#[tracing::instrument(
+    name = "confirm email change use case",
+    skip(user_repository, token_repository, token)
+)]
+pub async fn confirm_email_change(
+    user_repository: impl UserRepository,
+    token_repository: impl TokenRepository,
+    token: SecretString,
+) -> UseCaseResult<()> {
+    let invalid_token_error = UseCaseError::new(
+        UseCaseErrorKind::Validation,
+        ERR_INVALID_EMAIL_CHANGE_TOKEN,
+        MessageKey::InvalidEmailChangeToken,
+    );
+    // トークンを消費して、保留しているEメールアドレスの変更を取得
+    let email_change = token_repository
+        .consume_email_change_token(&token)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| invalid_token_error.clone())?;
+    // 変更を要求した後に、変更後のEメールアドレスを持つユーザーが登録されていないか確認
+    if user_repository
+        .by_email(email_change.email.clone())
+        .await
+        .map_err(UseCaseError::from)?
+        .is_some()
+    {
+        return Err(same_email_address_is_registered_error());
+    }
+    // Eメールアドレスを変更
+    // 上記の確認の後に、同じEメールアドレスを持つユーザーが登録された場合は、一意制約違反になる
+    let updated = match user_repository
+        .update_email(email_change.user_id, email_change.email)
+        .await
+    {
+        Ok(updated) => updated,
+        Err(e) if e.to_string().contains("ak_users_email") => {
+            return Err(same_email_address_is_registered_error());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    // ユーザーが削除されている場合は、トークンが無効であるとみなす
+    if !updated {
+        return Err(invalid_token_error);
+    }
+    // 変更前のEメールアドレスで発行したトークンを無効にする
+    token_repository
+        .revoke_user_tokens(email_change.user_id)
+        .await
+        .map_err(UseCaseError::from)
+}
@@ -0,0 +1,635 @@
+mod audit_logs;
+mod emails;
+mod reauthentication;
+mod retention;
+mod sessions;
+mod sign_in;
+mod sign_up;
+mod two_factor;
+mod users;
+
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use rand::RngCore as _;
+use secrecy::SecretString;
+
+use domain::clock::Clock;
+use domain::events::{EventPublisher, NoopEventPublisher};
+use domain::models::audit_log::{AuditActor, AuditLog};
+use domain::models::primitives::*;
+use domain::models::query::Pagination;
+use domain::models::user::{User, UserId, UserPermissionCode};
+use domain::repositories::audit_log::{AuditLogFilter, AuditLogRepository};
+use domain::repositories::device::DeviceRepository;
+use domain::repositories::token::{AccessToken, ActiveSession, TokenRepository};
+use domain::repositories::totp::TotpRepository;
+use domain::repositories::user::{LockedUser, UserListFilter, UserRepository, UserStream};
+use domain::repositories::user_permission::UserPermissionRepository;
+
+use crate::jwt::JwtKeys;
+use crate::metrics::SignInMetrics;
+use crate::notifications::{EmailSender, SignInNotifier};
+use crate::settings::{AuthorizationSettings, PasswordSettings, RetentionSettings};
+use crate::UseCaseResult;
+
+pub use audit_logs::*;
+pub use emails::*;
+pub use reauthentication::*;
+pub use retention::*;
+pub use sessions::*;
+pub use sign_in::*;
+pub use sign_up::*;
+pub use two_factor::*;
+pub use users::*;
+
+/// アカウントユースケースが使用するリポジトリ
+#[derive(Debug, Clone)]
+pub struct AccountsRepositories {
+    /// ユーザーリポジトリ
+    pub user: Arc<dyn UserRepository>,
+    /// ユーザー権限リポジトリ
+    pub user_permission: Arc<dyn UserPermissionRepository>,
+    /// トークンリポジトリ
+    pub token: Arc<dyn TokenRepository>,
+    /// TOTPリポジトリ
+    pub totp: Arc<dyn TotpRepository>,
+    /// デバイスリポジトリ
+    pub device: Arc<dyn DeviceRepository>,
+    /// 監査ログリポジトリ
+    pub audit_log: Arc<dyn AuditLogRepository>,
+}
+
+/// アカウントユースケース
+///
+/// アカウントに関するユースケースが使用する設定とリポジトリを保持する。ハンドラーは、設定と
+/// リポジトリを引数で渡す代わりに、このユースケースのメソッドを呼び出す。テストでは、
+/// モックのリポジトリを注入して構築できる。
+#[derive(Debug, Clone)]
+pub struct AccountsUseCase {
+    /// パスワード設定
+    password_settings: PasswordSettings,
+    /// 認証設定
+    authorization_settings: AuthorizationSettings,
+    /// JWTを署名及び検証する鍵
+    jwt_keys: JwtKeys,
+    /// リポジトリ
+    repositories: AccountsRepositories,
+    /// Eメール送信者
+    email_sender: Arc<dyn EmailSender>,
+    /// サインインメトリクス
+    metrics: Arc<dyn SignInMetrics>,
+    /// イベント発行者
+    events: Arc<dyn EventPublisher>,
+    /// 時計
+    clock: Arc<dyn Clock>,
+}
+
+impl AccountsUseCase {
+    /// アカウントユースケースを構築する。
+    ///
+    /// イベント発行者には、イベントを発行しないイベント発行者を設定する。イベントを発行する場合は、
+    /// `with_event_publisher`メソッドでイベント発行者を設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `password_settings` - パスワード設定
+    /// * `authorization_settings` - 認証設定
+    /// * `jwt_keys` - JWTを署名及び検証する鍵
+    /// * `repositories` - リポジトリ
+    /// * `email_sender` - Eメール送信者
+    /// * `metrics` - サインインメトリクス
+    /// * `clock` - 時計
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントユースケース
+    pub fn new(
+        password_settings: PasswordSettings,
+        authorization_settings: AuthorizationSettings,
+        jwt_keys: JwtKeys,
+        repositories: AccountsRepositories,
+        email_sender: Arc<dyn EmailSender>,
+        metrics: Arc<dyn SignInMetrics>,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        Self {
+            password_settings,
+            authorization_settings,
+            jwt_keys,
+            repositories,
+            email_sender,
+            metrics,
+            events: Arc::new(NoopEventPublisher),
+            clock,
+        }
+    }
+
+    /// Eメール送信者を設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `email_sender` - Eメール送信者
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントユースケース
+    pub fn with_email_sender(mut self, email_sender: Arc<dyn EmailSender>) -> Self {
+        self.email_sender = email_sender;
+        self
+    }
+
+    /// イベント発行者を設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `events` - イベント発行者
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントユースケース
+    pub fn with_event_publisher(mut self, events: Arc<dyn EventPublisher>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// ユーザーリポジトリを設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `user_repository` - ユーザーリポジトリ
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントユースケース
+    pub fn with_user_repository(mut self, user_repository: Arc<dyn UserRepository>) -> Self {
+        self.repositories.user = user_repository;
+        self
+    }
+
+    /// トークンリポジトリを設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `token_repository` - トークンリポジトリ
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントユースケース
+    pub fn with_token_repository(mut self, token_repository: Arc<dyn TokenRepository>) -> Self {
+        self.repositories.token = token_repository;
+        self
+    }
+
+    /// 時計を設定する。
+    ///
+    /// # 引数
+    ///
+    /// * `clock` - 時計
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントユースケース
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// サインイン通知者を構築する。
+    fn sign_in_notifier(&self) -> SignInNotifier<Arc<dyn DeviceRepository>> {
+        SignInNotifier::new(self.repositories.device.clone(), self.email_sender.clone())
+    }
+
+    /// ユーザーをサインアップする。
+    ///
+    /// 詳細は[`sign_up`]を参照すること。
+    pub async fn sign_up(&self, input: SignUpUseCaseInput) -> UseCaseResult<SignUpUseCaseOutput> {
+        sign_up(
+            &self.password_settings,
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.user_permission.clone(),
+            self.repositories.token.clone(),
+            self.email_sender.clone(),
+            &self.events,
+            input,
+        )
+        .await
+    }
+
+    /// 初期管理者を登録する。
+    ///
+    /// 詳細は[`seed_initial_admin`]を参照すること。
+    pub async fn seed_initial_admin(
+        &self,
+        input: SignUpUseCaseInput,
+    ) -> UseCaseResult<Option<SignUpUseCaseOutput>> {
+        seed_initial_admin(
+            &self.password_settings,
+            self.repositories.user.clone(),
+            self.repositories.user_permission.clone(),
+            input,
+        )
+        .await
+    }
+
+    /// 管理者が、ランダムなパスワードを生成してユーザーを登録する。
+    ///
+    /// 詳細は[`create_user_with_generated_password`]を参照すること。
+    pub async fn create_user_with_generated_password(
+        &self,
+        input: CreateUserUseCaseInput,
+    ) -> UseCaseResult<CreateUserUseCaseOutput> {
+        create_user_with_generated_password(
+            &self.password_settings,
+            self.repositories.user.clone(),
+            self.repositories.user_permission.clone(),
+            &self.events,
+            input,
+        )
+        .await
+    }
+
+    /// 複数のユーザーを一括でサインアップする。
+    ///
+    /// 詳細は[`bulk_sign_up`]を参照すること。
+    pub async fn bulk_sign_up(
+        &self,
+        max_users: usize,
+        input: BulkSignUpUseCaseInput,
+    ) -> UseCaseResult<BulkSignUpUseCaseOutput> {
+        bulk_sign_up(
+            &self.password_settings,
+            self.repositories.user.clone(),
+            self.repositories.user_permission.clone(),
+            &self.events,
+            max_users,
+            input,
+        )
+        .await
+    }
+
+    /// ユーザーのEメールアドレスを確認する。
+    ///
+    /// 詳細は[`verify_email`]を参照すること。
+    pub async fn verify_email(&self, token: SecretString) -> UseCaseResult<()> {
+        verify_email(
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            token,
+        )
+        .await
+    }
+
+    /// Eメールアドレスを確認するEメールを再送信する。
+    ///
+    /// 詳細は[`resend_email_verification`]を参照すること。
+    pub async fn resend_email_verification(&self, email: EmailAddress) -> UseCaseResult<()> {
+        resend_email_verification(
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            self.email_sender.clone(),
+            email,
+        )
+        .await
+    }
+
+    /// ユーザーのEメールアドレスの変更を要求する。
+    ///
+    /// 詳細は[`request_email_change`]を参照すること。
+    pub async fn request_email_change(
+        &self,
+        user_id: UserId,
+        input: RequestEmailChangeUseCaseInput,
+    ) -> UseCaseResult<()> {
+        request_email_change(
+            &self.password_settings,
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            self.email_sender.clone(),
+            &self.clock,
+            user_id,
+            input,
+        )
+        .await
+    }
+
+    /// ユーザーのEメールアドレスの変更を確定する。
+    ///
+    /// 詳細は[`confirm_email_change`]を参照すること。
+    pub async fn confirm_email_change(&self, token: SecretString) -> UseCaseResult<()> {
+        confirm_email_change(
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            token,
+        )
+        .await
+    }
+
+    /// パスワードでユーザーを再認証する。
+    ///
+    /// 詳細は[`reauthenticate`]を参照すること。
+    pub async fn reauthenticate(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+        password: &RawPassword,
+    ) -> UseCaseResult<ReauthenticateUseCaseOutput> {
+        reauthenticate(
+            &self.password_settings,
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            &self.clock,
+            user_id,
+            access_token,
+            password,
+        )
+        .await
+    }
+
+    /// ユーザーがサインインする。
+    ///
+    /// 詳細は[`sign_in`]を参照すること。
+    pub async fn sign_in(&self, input: SignInUseCaseInput) -> UseCaseResult<SignInResult> {
+        sign_in(
+            &self.password_settings,
+            &self.authorization_settings,
+            &self.jwt_keys,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            self.repositories.totp.clone(),
+            &self.metrics,
+            self.sign_in_notifier(),
+            &self.events,
+            &self.clock,
+            input,
+        )
+        .await
+    }
+
+    /// TOTPコードで2要素認証を完了して、ユーザーがサインインする。
+    ///
+    /// 詳細は[`sign_in_with_totp`]を参照すること。
+    pub async fn sign_in_with_totp(
+        &self,
+        input: SignInWithTotpUseCaseInput,
+    ) -> UseCaseResult<SignInUseCaseOutput> {
+        sign_in_with_totp(
+            &self.authorization_settings,
+            &self.jwt_keys,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            self.repositories.totp.clone(),
+            &self.metrics,
+            self.sign_in_notifier(),
+            &self.events,
+            &self.clock,
+            input,
+        )
+        .await
+    }
+
+    /// 自動的にアンロックする時間が経過したユーザーのアカウントをアンロックする。
+    ///
+    /// 詳細は[`unlock_expired_accounts`]を参照すること。
+    pub async fn unlock_expired_accounts(&self) -> UseCaseResult<u64> {
+        unlock_expired_accounts(
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            &self.clock,
+        )
+        .await
+    }
+
+    /// 保持期間を経過したサインインに失敗した履歴と、削除したユーザーのレコードを消去する。
+    ///
+    /// 詳細は[`purge_expired_records`]を参照すること。
+    pub async fn purge_expired_records(
+        &self,
+        retention_settings: &RetentionSettings,
+    ) -> UseCaseResult<PurgeExpiredRecordsUseCaseOutput> {
+        purge_expired_records(
+            retention_settings,
+            self.repositories.user.clone(),
+            &self.clock,
+        )
+        .await
+    }
+
+    /// ユーザーがサインインしているセッションを取得する。
+    ///
+    /// 詳細は[`list_sessions`]を参照すること。
+    pub async fn list_sessions(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+    ) -> UseCaseResult<Vec<ActiveSession>> {
+        list_sessions(self.repositories.token.clone(), user_id, access_token).await
+    }
+
+    /// ユーザーのセッションを無効にする。
+    ///
+    /// 詳細は[`revoke_session`]を参照すること。
+    pub async fn revoke_session(&self, user_id: UserId, session_id: &str) -> UseCaseResult<()> {
+        revoke_session(self.repositories.token.clone(), user_id, session_id).await
+    }
+
+    /// トークンを検査する。
+    ///
+    /// 詳細は[`introspect_token`]を参照すること。
+    pub async fn introspect_token(
+        &self,
+        credential: Option<&SecretString>,
+        token: &SecretString,
+    ) -> UseCaseResult<Option<TokenIntrospection>> {
+        introspect_token(
+            &self.authorization_settings,
+            &self.jwt_keys,
+            self.repositories.token.clone(),
+            &self.clock,
+            credential,
+            token,
+        )
+        .await
+    }
+
+    /// TOTPの秘密鍵を生成して登録する。
+    ///
+    /// 詳細は[`set_up_totp`]を参照すること。
+    pub async fn set_up_totp(&self, user_id: UserId) -> UseCaseResult<TotpSetupUseCaseOutput> {
+        set_up_totp(
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.totp.clone(),
+            user_id,
+        )
+        .await
+    }
+
+    /// TOTPコードを検証して、2要素認証を有効にする。
+    ///
+    /// 詳細は[`enable_totp`]を参照すること。
+    pub async fn enable_totp(&self, user_id: UserId, code: String) -> UseCaseResult<()> {
+        enable_totp(
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            self.repositories.totp.clone(),
+            &self.clock,
+            user_id,
+            code,
+        )
+        .await
+    }
+
+    /// TOTPコードを検証して、2要素認証を無効にする。
+    ///
+    /// 詳細は[`disable_totp`]を参照すること。
+    pub async fn disable_totp(&self, user_id: UserId, code: String) -> UseCaseResult<()> {
+        disable_totp(
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            self.repositories.totp.clone(),
+            &self.clock,
+            user_id,
+            code,
+        )
+        .await
+    }
+
+    /// ユーザーのリストを取得する。
+    ///
+    /// 詳細は[`list_users`]を参照すること。
+    pub async fn list_users(
+        &self,
+        search: Option<&str>,
+        filter: UserListFilter,
+    ) -> UseCaseResult<Vec<User>> {
+        list_users(self.repositories.user.clone(), search, filter).await
+    }
+
+    /// ロックされているユーザーのリストを取得する。
+    ///
+    /// 詳細は[`list_locked_users`]を参照すること。
+    pub async fn list_locked_users(
+        &self,
+        pagination: Pagination,
+    ) -> UseCaseResult<Vec<LockedUser>> {
+        list_locked_users(self.repositories.user.clone(), pagination).await
+    }
+
+    /// ユーザーを1件ずつ返すストリームを返す。
+    ///
+    /// 詳細は[`export_users`]を参照すること。
+    pub fn export_users(&self, filter: UserListFilter) -> UseCaseResult<UserStream> {
+        export_users(self.repositories.user.clone(), filter)
+    }
+
+    /// ユーザーを取得する。
+    ///
+    /// 詳細は[`user_detail`]を参照すること。
+    pub async fn user_detail(&self, user_id: UserId) -> UseCaseResult<User> {
+        user_detail(self.repositories.user.clone(), user_id).await
+    }
+
+    /// ユーザーのアカウントの状況を取得する。
+    ///
+    /// 詳細は[`account_activity`]を参照すること。
+    pub async fn account_activity(
+        &self,
+        user_id: UserId,
+        access_token: &AccessToken,
+    ) -> UseCaseResult<AccountActivity> {
+        account_activity(
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            user_id,
+            access_token,
+        )
+        .await
+    }
+
+    /// ユーザーを削除する。
+    ///
+    /// 詳細は[`delete_user`]を参照すること。
+    pub async fn delete_user(&self, input: DeleteUserUseCaseInput) -> UseCaseResult<()> {
+        delete_user(
+            &self.password_settings,
+            &self.authorization_settings,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            &self.clock,
+            input,
+        )
+        .await
+    }
+
+    /// ユーザーの権限を変更する。
+    ///
+    /// 詳細は[`change_user_permission`]を参照すること。
+    pub async fn change_user_permission(
+        &self,
+        actor: &AuditActor,
+        user_id: UserId,
+        user_permission_code: UserPermissionCode,
+    ) -> UseCaseResult<User> {
+        change_user_permission(
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            actor,
+            user_id,
+            user_permission_code,
+        )
+        .await
+    }
+
+    /// 監査ログのリストを取得する。
+    ///
+    /// 詳細は[`list_audit_logs`]を参照すること。
+    pub async fn list_audit_logs(&self, filter: AuditLogFilter) -> UseCaseResult<Vec<AuditLog>> {
+        list_audit_logs(self.repositories.audit_log.clone(), filter).await
+    }
+
+    /// ユーザーのプロフィールを更新する。
+    ///
+    /// 詳細は[`update_profile`]を参照すること。
+    pub async fn update_profile(&self, input: UpdateProfileUseCaseInput) -> UseCaseResult<User> {
+        update_profile(self.repositories.user.clone(), input).await
+    }
+
+    /// パスワードの変更を要求されたユーザーのパスワードを変更する。
+    ///
+    /// 詳細は[`change_required_password`]を参照すること。
+    pub async fn change_required_password(
+        &self,
+        input: ChangeRequiredPasswordUseCaseInput,
+    ) -> UseCaseResult<()> {
+        change_required_password(
+            &self.password_settings,
+            self.repositories.user.clone(),
+            self.repositories.token.clone(),
+            &self.events,
+            input,
+        )
+        .await
+    }
+}
+
+/// Eメールアドレスの確認など、1度しか使用できないトークンを生成する。
+///
+/// # 戻り値
+///
+/// 32バイトの乱数を16進数で表現したトークン
+fn generate_one_time_token() -> SecretString {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    SecretString::new(token)
+}
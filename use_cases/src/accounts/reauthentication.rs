@@ -0,0 +1,200 @@
+use time::{Duration, OffsetDateTime};
+
+use domain::clock::Clock;
+use domain::models::primitives::*;
+use domain::models::user::UserId;
+use domain::repositories::token::{AccessToken, TokenRepository};
+use domain::repositories::user::{SignInAttempt, UserRepository};
+
+use super::sign_in::record_sign_in_failure;
+use crate::messages::MessageKey;
+use crate::passwords::verify_password;
+use crate::settings::{AuthorizationSettings, PasswordSettings};
+use crate::{UseCaseError, UseCaseResult};
+
+/// パスワードでユーザーを再認証する。
+///
+/// アクセストークンを盗まれた場合に備えて、アカウントの削除やEメールアドレスの変更の前に、
+/// ユーザーにパスワードを再入力させる。パスワードを検証した後、アクセストークンで再認証したことを
+/// 認証設定の`reauthentication_seconds`の間登録するため、その間はパスワードを送信せずに、
+/// それらの操作ができる。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `authorization_settings` - 認証設定
+/// * `user_repo` - ユーザーリポジトリ
+/// * `token_repo` - トークンリポジトリ
+/// * `clock` - 時計
+/// * `user_id` - 再認証するユーザーのユーザーID
+/// * `access_token` - 再認証するユーザーのアクセストークン
+/// * `password` - 加工していないパスワード
+///
+/// # 戻り値
+///
+/// 再認証ユースケース出力
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(
+    name = "reauthenticate use case",
+    skip(password_settings, authorization_settings, user_repo, token_repo, clock, access_token, password),
+    fields(user.id = %user_id)
+)]
+pub async fn reauthenticate(
+    password_settings: &PasswordSettings,
+    authorization_settings: &AuthorizationSettings,
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    clock: &impl Clock,
+    user_id: UserId,
+    access_token: &AccessToken,
+    password: &RawPassword,
+) -> UseCaseResult<ReauthenticateUseCaseOutput> {
+    verify_password_with_lockout(
+        password_settings,
+        authorization_settings,
+        &user_repo,
+        &token_repo,
+        clock,
+        user_id,
+        password,
+    )
+    .await?;
+
+    let ttl = authorization_settings.reauthentication_seconds;
+    token_repo
+        .register_reauthentication(user_id, access_token, ttl)
+        .await
+        .map_err(UseCaseError::from)?;
+
+    Ok(ReauthenticateUseCaseOutput {
+        expiration: clock.now_utc() + Duration::seconds(ttl as i64),
+    })
+}
+
+/// 再認証ユースケース出力
+#[derive(Debug, Clone)]
+pub struct ReauthenticateUseCaseOutput {
+    /// 再認証の有効期限
+    pub expiration: OffsetDateTime,
+}
+
+/// 本人であることを再確認する方法
+pub enum Reauthentication {
+    /// 加工していないパスワードで再確認する。
+    Password(RawPassword),
+    /// `reauthenticate`で再認証したアクセストークンで再確認する。
+    AccessToken(AccessToken),
+}
+
+/// 本人であることを再確認する。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `authorization_settings` - 認証設定
+/// * `user_repo` - ユーザーリポジトリ
+/// * `token_repo` - トークンリポジトリ
+/// * `clock` - 時計
+/// * `user_id` - 本人であることを再確認するユーザーのユーザーID
+/// * `reauthentication` - 本人であることを再確認する方法
+pub(super) async fn confirm_reauthentication(
+    password_settings: &PasswordSettings,
+    authorization_settings: &AuthorizationSettings,
+    user_repo: &impl UserRepository,
+    token_repo: &impl TokenRepository,
+    clock: &impl Clock,
+    user_id: UserId,
+    reauthentication: &Reauthentication,
+) -> UseCaseResult<()> {
+    match reauthentication {
+        Reauthentication::Password(password) => {
+            verify_password_with_lockout(
+                password_settings,
+                authorization_settings,
+                user_repo,
+                token_repo,
+                clock,
+                user_id,
+                password,
+            )
+            .await
+        }
+        Reauthentication::AccessToken(access_token) => {
+            let reauthenticated_user = token_repo
+                .reauthenticated_user(access_token)
+                .await
+                .map_err(UseCaseError::from)?;
+            if reauthenticated_user != Some(user_id) {
+                return Err(UseCaseError::validation(MessageKey::PasswordIsRequired));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// ユーザーのパスワードを検証する。
+///
+/// パスワードが間違っている場合は、サインインと同様にサインイン失敗回数を記録する。
+/// その結果アカウントをロックした場合は、アクセストークンを盗んだ者がパスワードを推測し続けられない
+/// ように、ユーザーに発行したトークンをすべて無効にする。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `authorization_settings` - 認証設定
+/// * `user_repo` - ユーザーリポジトリ
+/// * `token_repo` - トークンリポジトリ
+/// * `clock` - 時計
+/// * `user_id` - ユーザーID
+/// * `password` - 加工していないパスワード
+async fn verify_password_with_lockout(
+    password_settings: &PasswordSettings,
+    authorization_settings: &AuthorizationSettings,
+    user_repo: &impl UserRepository,
+    token_repo: &impl TokenRepository,
+    clock: &impl Clock,
+    user_id: UserId,
+    password: &RawPassword,
+) -> UseCaseResult<()> {
+    let now_dt = clock.now_utc();
+    let wrong_password_error = UseCaseError::unauthorized(MessageKey::WrongPassword);
+
+    // ユーザーのクレデンシャルを取得して、パスワードを検証
+    let credential = user_repo
+        .credential_by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| wrong_password_error.clone())?;
+    if !credential.active {
+        return Err(UseCaseError::unauthorized(MessageKey::UserAccountIsLocked));
+    }
+    if !verify_password(password, &password_settings.pepper, &credential.password)? {
+        // パスワードの検証は、アクセス元を受け取らないため記録しない
+        let attempt = SignInAttempt::default();
+        if record_sign_in_failure(
+            authorization_settings,
+            user_repo,
+            &credential,
+            &attempt,
+            now_dt,
+        )
+        .await?
+        {
+            token_repo
+                .revoke_user_tokens(user_id)
+                .await
+                .map_err(UseCaseError::from)?;
+        }
+        return Err(wrong_password_error);
+    }
+    // 本人であることを確認できたため、サインインに失敗した履歴を消去
+    if credential.attempted_at.is_some() {
+        user_repo
+            .clear_sign_in_failed_history(user_id)
+            .await
+            .map_err(UseCaseError::from)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,59 @@
+use time::Duration;
+
+use domain::clock::Clock;
+use domain::repositories::user::UserRepository;
+
+use crate::settings::RetentionSettings;
+use crate::{UseCaseError, UseCaseResult};
+
+/// 保持期間を経過したサインインに失敗した履歴と、削除したユーザーのレコードを消去する。
+///
+/// 保持期間に`0`を設定したレコードは消去しない。
+///
+/// # 引数
+///
+/// * `retention_settings` - 保持期間設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `clock` - 時計
+///
+/// # 戻り値
+///
+/// 消去したレコードの数
+#[tracing::instrument(
+    name = "purge expired records use case",
+    skip(retention_settings, user_repository, clock)
+)]
+pub async fn purge_expired_records(
+    retention_settings: &RetentionSettings,
+    user_repository: impl UserRepository,
+    clock: &impl Clock,
+) -> UseCaseResult<PurgeExpiredRecordsUseCaseOutput> {
+    let now = clock.now_utc();
+
+    let mut output = PurgeExpiredRecordsUseCaseOutput::default();
+    if 0 < retention_settings.history_days {
+        let before = now - Duration::days(retention_settings.history_days.into());
+        output.sign_in_histories = user_repository
+            .purge_sign_in_history_older_than(before)
+            .await
+            .map_err(UseCaseError::from)?;
+    }
+    if 0 < retention_settings.deleted_user_days {
+        let before = now - Duration::days(retention_settings.deleted_user_days.into());
+        output.deleted_users = user_repository
+            .purge_deleted_before(before)
+            .await
+            .map_err(UseCaseError::from)?;
+    }
+
+    Ok(output)
+}
+
+/// 保持期間を経過したレコードの消去ユースケース出力
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PurgeExpiredRecordsUseCaseOutput {
+    /// サインインに失敗した履歴を消去したユーザーの数
+    pub sign_in_histories: u64,
+    /// 物理的に削除した、削除したユーザーの数
+    pub deleted_users: u64,
+}
@@ -0,0 +1,162 @@
+use std::cmp::Reverse;
+
+use secrecy::{ExposeSecret as _, SecretString};
+
+use domain::clock::Clock;
+use domain::models::user::{UserId, UserPermissionCode};
+use domain::repositories::token::{AccessToken, ActiveSession, TokenRepository, TokenType};
+
+use crate::jwt::{looks_like_jwt, retrieve_claim_from_token, JwtKeys};
+use crate::messages::MessageKey;
+use crate::settings::AuthorizationSettings;
+use crate::totp::constant_time_eq;
+use crate::{UseCaseError, UseCaseResult};
+
+/// ユーザーがサインインしているセッションを、トークンを発行した日時の新しい順に取得する。
+///
+/// # 引数
+///
+/// * `token_repo` - トークンリポジトリ
+/// * `user_id` - ユーザーID
+/// * `access_token` - リクエストで送信されたアクセストークン
+///
+/// # 戻り値
+///
+/// ユーザーがサインインしているセッション
+#[tracing::instrument(
+    name = "list sessions use case",
+    skip(token_repo, access_token),
+    fields(user.id = %user_id)
+)]
+pub async fn list_sessions(
+    token_repo: impl TokenRepository,
+    user_id: UserId,
+    access_token: &AccessToken,
+) -> UseCaseResult<Vec<ActiveSession>> {
+    let mut sessions = token_repo
+        .user_sessions(user_id, access_token)
+        .await
+        .map_err(UseCaseError::from)?;
+    sessions.sort_by_key(|session| Reverse(session.issued_at));
+
+    Ok(sessions)
+}
+
+/// ユーザーがサインインしているセッションを無効にする。
+///
+/// # 引数
+///
+/// * `token_repo` - トークンリポジトリ
+/// * `user_id` - ユーザーID
+/// * `session_id` - セッションID
+#[tracing::instrument(
+    name = "revoke session use case",
+    skip(token_repo),
+    fields(user.id = %user_id)
+)]
+pub async fn revoke_session(
+    token_repo: impl TokenRepository,
+    user_id: UserId,
+    session_id: &str,
+) -> UseCaseResult<()> {
+    if !token_repo
+        .revoke_session(user_id, session_id)
+        .await
+        .map_err(UseCaseError::from)?
+    {
+        return Err(UseCaseError::not_found(MessageKey::SessionNotFound));
+    }
+
+    Ok(())
+}
+
+/// 内部サービスから送信されたトークンを検査する。
+///
+/// RFC 7662に従い、トークンの署名と有効期限を検証して、トークンが無効にされていないか
+/// トークンリポジトリで確認する。トークンが有効でない場合はエラーにせず、`None`を返す。
+/// ただし、JWTの形式でないトークンは、署名の検証やトークンリポジトリへの問い合わせをせずに、
+/// 検証エラーを返す。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `jwt_keys` - JWTを署名及び検証する鍵
+/// * `token_repo` - トークンリポジトリ
+/// * `clock` - 時計
+/// * `credential` - 内部サービスが送信した資格情報
+/// * `token` - 検査するトークン
+///
+/// # 戻り値
+///
+/// トークンが有効な場合はトークンの検査結果、それ以外の場合は`None`
+#[tracing::instrument(
+    name = "introspect token use case",
+    skip(authorization_settings, jwt_keys, token_repo, clock, credential, token)
+)]
+pub async fn introspect_token(
+    authorization_settings: &AuthorizationSettings,
+    jwt_keys: &JwtKeys,
+    token_repo: impl TokenRepository,
+    clock: &impl Clock,
+    credential: Option<&SecretString>,
+    token: &SecretString,
+) -> UseCaseResult<Option<TokenIntrospection>> {
+    // 内部サービスの資格情報を確認
+    let authenticated = match (&authorization_settings.introspection_credential, credential) {
+        (Some(expected), Some(credential)) => constant_time_eq(
+            expected.expose_secret().as_bytes(),
+            credential.expose_secret().as_bytes(),
+        ),
+        _ => false,
+    };
+    if !authenticated {
+        return Err(UseCaseError::unauthorized(
+            MessageKey::InvalidServiceCredential,
+        ));
+    }
+
+    // 明らかに形式が誤っているトークンを拒否
+    if !looks_like_jwt(token) {
+        return Err(UseCaseError::validation(MessageKey::MalformedToken));
+    }
+    // トークンの署名と有効期限を検証
+    let Ok(claim) = retrieve_claim_from_token(token, jwt_keys) else {
+        return Ok(None);
+    };
+    if claim.expiration <= clock.now_utc().unix_timestamp() as u64 {
+        return Ok(None);
+    }
+    // トークンが無効にされていないか確認
+    let content = token_repo
+        .retrieve_token_content(token)
+        .await
+        .map_err(UseCaseError::from)?;
+
+    // クレイムにトークンの種類が記録されている場合は、リポジトリに記録されたトークンの種類と一致するか確認
+    Ok(content
+        .filter(|content| content.user_id == claim.user_id)
+        .filter(|content| {
+            claim
+                .token_type
+                .is_none_or(|token_type| token_type == content.token_type)
+        })
+        .map(|content| TokenIntrospection {
+            user_id: content.user_id,
+            expiration: claim.expiration,
+            token_type: content.token_type,
+            user_permission_code: content.user_permission_code,
+        }))
+}
+
+/// トークンの検査結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenIntrospection {
+    /// トークンを発行したユーザーのユーザーID
+    pub user_id: UserId,
+    /// 有効期限を示すUNIXエポック秒
+    pub expiration: u64,
+    /// トークンの種類
+    pub token_type: TokenType,
+    /// トークンを発行したユーザーのユーザー権限コード
+    pub user_permission_code: UserPermissionCode,
+}
@@ -0,0 +1,628 @@
+use std::net::IpAddr;
+
+use secrecy::SecretString;
+use time::{Duration, OffsetDateTime};
+
+use domain::clock::Clock;
+use domain::events::{DomainEvent, EventPublisher};
+use domain::models::primitives::*;
+use domain::models::user::UserId;
+use domain::repositories::device::DeviceRepository;
+use domain::repositories::token::{AccessToken, RefreshToken, TokenPairWithTtl, TokenRepository};
+use domain::repositories::totp::TotpRepository;
+use domain::repositories::user::{SignInAttempt, SignInRecord, UserCredential, UserRepository};
+use domain::DomainError;
+
+use super::generate_one_time_token;
+use super::two_factor::{invalid_totp_code_error, verify_totp};
+use crate::jwt::{generate_token_pair, JwtKeys};
+use crate::messages::MessageKey;
+use crate::metrics::SignInMetrics;
+use crate::notifications::SignInNotifier;
+use crate::passwords::verify_password;
+use crate::settings::{AuthorizationSettings, PasswordSettings};
+use crate::{
+    UseCaseError, UseCaseErrorKind, UseCaseResult, ERR_EMAIL_ADDRESS_IS_NOT_VERIFIED,
+    ERR_INVALID_MFA_PENDING_TOKEN,
+};
+
+/// ユーザーがサインインする。
+///
+/// ユーザーが最初にサインインに失敗した日時: last_failed_at
+/// 現在の日時: now_dt
+/// ユーザーのアカウントをロックするサインイン失敗回数: number_of_failures
+/// 上記サインイン失敗回数をカウントする期間（秒）: attempting_seconds
+///
+/// 最初にサインインに失敗した日時か記録されていない場合、または最初にサインインに失敗した日時に失敗回数を
+/// カウントする期間を足した日時が、現在の日時よりも過去の場合は、最初のサインインの失敗として記録
+///
+/// * last_failed_at.is_none()
+/// * last_failed_at + attempting_seconds < now_dt
+///
+/// 最初にサインインに失敗した日時に失敗回数をカウントする期間を足した日時が、現在の日時より未来の場合は、
+/// サインイン失敗回数をインクリメント
+///
+/// * last_failed_at + attempting_seconds >= now_dt
+///
+/// 上記の結果、サインイン失敗回数がユーザーのアカウントをロックするサインイン失敗回数に達した場合は、
+/// ユーザーのアカウントをロック
+///
+/// * サインイン失敗回数 >= number_of_failures
+///
+/// 認証設定でEメールアドレスの確認を要求している場合、Eメールアドレスを確認していないユーザーの
+/// サインインを拒否する。
+///
+/// ユーザーがパスワードの変更を要求されている場合は、アクセストークンとリフレッシュトークンを発行せずに、
+/// パスワードを変更するためのトークンを返す。
+///
+/// ユーザーが2要素認証を有効にしている場合は、アクセストークンとリフレッシュトークンを発行せずに、
+/// 2要素認証を待機しているサインインを示すトークンを返す。
+///
+/// ユーザーのアカウントをロックした場合、またはユーザーがはじめてサインインしたデバイスから
+/// サインインに成功した場合は、ユーザーにEメールで通知する。また、ユーザーのアカウントをロックした
+/// 場合はアカウントをロックしたイベント、サインインに成功した場合はユーザーがサインインした
+/// イベントを発行する。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `authorization_settings` - 認証設定
+/// * `jwt_keys` - JWTを署名及び検証する鍵
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `totp_repository` - TOTPリポジトリ
+/// * `metrics` - サインインメトリクス
+/// * `notifier` - サインイン通知者
+/// * `events` - イベント発行者
+/// * `clock` - 時計
+/// * `input` - サインインユースケース入力
+///
+/// # 戻り値
+///
+/// * アクセストークンとリフレッシュトークン、パスワードを変更するためのトークン、または2要素認証を
+///   待機しているサインインを示すトークン
+#[allow(clippy::too_many_arguments)]
+pub async fn sign_in(
+    password_settings: &PasswordSettings,
+    authorization_settings: &AuthorizationSettings,
+    jwt_keys: &JwtKeys,
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    totp_repo: impl TotpRepository,
+    metrics: &impl SignInMetrics,
+    notifier: SignInNotifier<impl DeviceRepository + 'static>,
+    events: &impl EventPublisher,
+    clock: &impl Clock,
+    input: SignInUseCaseInput,
+) -> UseCaseResult<SignInResult> {
+    // 現在の日時
+    let now_dt = clock.now_utc();
+    // 不許可／未認証エラー
+    let unauthorized_error = UseCaseError::unauthorized(MessageKey::WrongEmailAddressOrPassword);
+
+    // ユーザーのクレデンシャルを取得
+    let credential = user_repo
+        .user_credential(input.email)
+        .await
+        .map_err(UseCaseError::from)?;
+    if credential.is_none() {
+        metrics.sign_in_failed();
+        return Err(unauthorized_error);
+    }
+    let mut credential = credential.unwrap();
+    // アカウントをロックしてから、自動的にアンロックする時間が経過している場合はアンロック
+    // バックグラウンド・タスクがアンロックする前でも、サインインできるようにする
+    if !credential.active && authorization_settings.is_lockout_expired(credential.locked_at, now_dt)
+    {
+        user_repo
+            .unlock_user_account(credential.user_id)
+            .await
+            .map_err(UseCaseError::from)?;
+        // クレデンシャルを取得した後にユーザーが削除された場合は、ユーザーが存在しない場合と同様に扱う
+        let Some(cleared) = user_repo
+            .clear_sign_in_failed_history(credential.user_id)
+            .await
+            .map_err(UseCaseError::from)?
+        else {
+            metrics.sign_in_failed();
+            return Err(unauthorized_error);
+        };
+        credential = cleared;
+    }
+    // アカウントがアクティブか確認
+    if !credential.active {
+        metrics.sign_in_failed();
+        return Err(UseCaseError::unauthorized(MessageKey::UserAccountIsLocked));
+    }
+    // パスワードを検証
+    if !verify_password(
+        &input.password,
+        &password_settings.pepper,
+        &credential.password,
+    )? {
+        metrics.sign_in_failed();
+        let attempt = SignInAttempt {
+            client_ip: input.client_ip,
+            user_agent: input.user_agent.clone(),
+        };
+        if record_sign_in_failure(
+            authorization_settings,
+            &user_repo,
+            &credential,
+            &attempt,
+            now_dt,
+        )
+        .await?
+        {
+            // ロックする前にパスワードを知られていた場合でも、2要素認証でサインインを完了できないようにする
+            token_repo
+                .revoke_mfa_pending_tokens(credential.user_id)
+                .await
+                .map_err(UseCaseError::from)?;
+            metrics.account_locked();
+            notifier.account_locked(credential.email.clone(), attempt.clone());
+            events
+                .publish(DomainEvent::AccountLocked {
+                    user_id: credential.user_id,
+                    email: credential.email,
+                    attempt,
+                })
+                .await;
+        }
+
+        return Err(unauthorized_error);
+    }
+    // Eメールアドレスを確認していることを要求する場合は、確認していないユーザーのサインインを拒否
+    if authorization_settings.require_verified_email && credential.email_verified_at.is_none() {
+        metrics.sign_in_failed();
+        return Err(UseCaseError::new(
+            UseCaseErrorKind::Unauthorized,
+            ERR_EMAIL_ADDRESS_IS_NOT_VERIFIED,
+            MessageKey::EmailAddressIsNotVerified,
+        ));
+    }
+    // パスワードの変更を要求されている場合は、パスワードを変更するまでトークンを発行しない
+    if credential.password_change_required {
+        let token = generate_one_time_token();
+        let ttl = authorization_settings.password_change_token_seconds;
+        token_repo
+            .register_password_change_token(credential.user_id, &token, ttl)
+            .await
+            .map_err(UseCaseError::from)?;
+
+        return Ok(SignInResult::PasswordChangeRequired(
+            PasswordChangeRequiredUseCaseOutput {
+                token,
+                expiration: now_dt + Duration::seconds(ttl as i64),
+            },
+        ));
+    }
+    // 2要素認証を有効にしている場合は、TOTPコードを検証するまでトークンを発行しない
+    if totp_repo
+        .totp_secret(credential.user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .is_some_and(|secret| secret.is_enabled())
+    {
+        let token = generate_one_time_token();
+        let ttl = authorization_settings.mfa_pending_token_seconds;
+        token_repo
+            .register_mfa_pending_token(credential.user_id, &token, ttl)
+            .await
+            .map_err(UseCaseError::from)?;
+
+        return Ok(SignInResult::MfaPending(MfaPendingUseCaseOutput {
+            token,
+            expiration: now_dt + Duration::seconds(ttl as i64),
+        }));
+    }
+
+    let (output, credential) = issue_token_pair(
+        authorization_settings,
+        jwt_keys,
+        &user_repo,
+        &token_repo,
+        clock,
+        credential.user_id,
+        input.device.as_deref(),
+    )
+    .await?;
+    metrics.sign_in_succeeded();
+    events
+        .publish(DomainEvent::UserSignedIn {
+            user_id: credential.user_id,
+        })
+        .await;
+    notifier.signed_in(credential.user_id, credential.email, input.device);
+
+    Ok(SignInResult::SignedIn(output))
+}
+
+/// サインインに失敗したことを記録して、サインイン失敗回数がアカウントをロックする失敗回数に達した
+/// 場合は、ユーザーのアカウントをロックする。
+///
+/// 記録する方法とロックするかどうかは[`UserCredential::next_failure_action`]で決定して、
+/// その結果をユーザーリポジトリに反映する。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `user_repo` - ユーザーリポジトリ
+/// * `credential` - サインインに失敗したユーザーのクレデンシャル
+/// * `attempt` - サインインに失敗したアクセス元
+/// * `now_dt` - 現在の日時
+///
+/// # 戻り値
+///
+/// ユーザーのアカウントをロックした場合は`true`
+pub(super) async fn record_sign_in_failure(
+    authorization_settings: &AuthorizationSettings,
+    user_repo: &impl UserRepository,
+    credential: &UserCredential,
+    attempt: &SignInAttempt,
+    now_dt: OffsetDateTime,
+) -> UseCaseResult<bool> {
+    // サイン履歴保存エラー
+    // リポジトリの処理がタイムアウトした場合は、一時的に利用不可であることを示すエラーを返す
+    let history_record_error = UseCaseError::repository(MessageKey::SignInHistoryNotRecorded);
+    let map_history_record_error = |e: DomainError| match e.is_timeout() {
+        true => UseCaseError::from(e),
+        false => history_record_error.clone(),
+    };
+
+    let action = credential.next_failure_action(
+        now_dt,
+        Duration::seconds(authorization_settings.attempting_seconds.into()),
+        authorization_settings.number_of_failures,
+    );
+    let latest_credential = if action.records_first() {
+        user_repo
+            .record_first_sign_in_failed(credential.user_id, attempt)
+            .await
+    } else {
+        user_repo
+            .increment_number_of_sign_in_failures(credential.user_id, attempt)
+            .await
+    };
+    latest_credential
+        .map_err(map_history_record_error)?
+        .ok_or_else(|| history_record_error.clone())?;
+    if !action.locks() {
+        return Ok(false);
+    }
+    user_repo
+        .lock_user_account(credential.user_id)
+        .await
+        .map_err(map_history_record_error)?;
+
+    Ok(true)
+}
+
+/// 最後にサインインした日時を更新して、アクセストークン及びリフレッシュトークンを発行する。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `jwt_keys` - JWTを署名及び検証する鍵
+/// * `user_repo` - ユーザーリポジトリ
+/// * `token_repo` - トークンリポジトリ
+/// * `clock` - 時計
+/// * `user_id` - サインインするユーザーのユーザーID
+/// * `device` - サインインしたデバイスを識別する文字列
+///
+/// # 戻り値
+///
+/// アクセストークンとリフレッシュトークン、及び更新後のユーザーのクレデンシャル
+pub(super) async fn issue_token_pair(
+    authorization_settings: &AuthorizationSettings,
+    jwt_keys: &JwtKeys,
+    user_repo: &impl UserRepository,
+    token_repo: &impl TokenRepository,
+    clock: &impl Clock,
+    user_id: UserId,
+    device: Option<&str>,
+) -> UseCaseResult<(SignInUseCaseOutput, UserCredential)> {
+    // 最後にサインインした日時を更新
+    // サインインの途中でユーザーが削除された場合は、トークンを発行しない
+    let SignInRecord {
+        credential,
+        previous_sign_in_at,
+    } = user_repo
+        .update_last_sign_in(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::unauthorized(MessageKey::WrongEmailAddressOrPassword))?;
+
+    // アクセストークン及びリフレッシュトークンを生成
+    let dt = clock.now_utc();
+    let access_expiration =
+        dt + Duration::seconds(authorization_settings.access_token_seconds as i64);
+    let refresh_expiration =
+        dt + Duration::seconds(authorization_settings.refresh_token_seconds as i64);
+    let tokens = generate_token_pair(
+        credential.user_id,
+        credential.user_permission_code,
+        access_expiration,
+        refresh_expiration,
+        jwt_keys,
+    )?;
+
+    // アクセストークン及びリフレッシュトークンをリポジトリに保存
+    let token_with_ttls = TokenPairWithTtl {
+        access: &tokens.access,
+        access_ttl: authorization_settings.access_token_seconds,
+        refresh: &tokens.refresh,
+        refresh_ttl: authorization_settings.refresh_token_seconds,
+        device,
+    };
+    token_repo
+        .register_token_pair(
+            credential.user_id,
+            token_with_ttls,
+            credential.user_permission_code,
+        )
+        .await?;
+
+    Ok((
+        SignInUseCaseOutput {
+            access: tokens.access,
+            access_expiration,
+            refresh: tokens.refresh,
+            refresh_expiration,
+            previous_sign_in_at,
+        },
+        credential,
+    ))
+}
+
+/// 2要素認証を待機しているサインインを、TOTPコードで完了する。
+///
+/// 2要素認証を待機しているサインインを示すトークンは、TOTPコードが間違っている場合でも消費する。
+/// したがって、TOTPコードを間違えた場合は、もう一度サインインする必要がある。
+/// TOTPコードが間違っている場合は、パスワードが間違っている場合と同様にサインイン失敗回数を
+/// 記録して、失敗回数に達した場合はユーザーのアカウントをロックする。
+/// トークンを発行する前にクレデンシャルを取得し直して、削除されたユーザー、またはアカウントを
+/// ロックされたユーザーのサインインを拒否する。
+/// サインインに成功した場合は、ユーザーがサインインしたイベントを発行する。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `jwt_keys` - JWTを署名及び検証する鍵
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `totp_repository` - TOTPリポジトリ
+/// * `metrics` - サインインメトリクス
+/// * `notifier` - サインイン通知者
+/// * `events` - イベント発行者
+/// * `clock` - 時計
+/// * `input` - 2要素認証サインインユースケース入力
+///
+/// # 戻り値
+///
+/// * アクセストークンとリフレッシュトークン
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(
+    name = "sign in with totp use case",
+    skip(
+        authorization_settings,
+        jwt_keys,
+        user_repo,
+        token_repo,
+        totp_repo,
+        metrics,
+        notifier,
+        events,
+        clock,
+        input
+    )
+)]
+pub async fn sign_in_with_totp(
+    authorization_settings: &AuthorizationSettings,
+    jwt_keys: &JwtKeys,
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    totp_repo: impl TotpRepository,
+    metrics: &impl SignInMetrics,
+    notifier: SignInNotifier<impl DeviceRepository + 'static>,
+    events: &impl EventPublisher,
+    clock: &impl Clock,
+    input: SignInWithTotpUseCaseInput,
+) -> UseCaseResult<SignInUseCaseOutput> {
+    let invalid_token_error = UseCaseError::new(
+        UseCaseErrorKind::Unauthorized,
+        ERR_INVALID_MFA_PENDING_TOKEN,
+        MessageKey::InvalidMfaPendingToken,
+    );
+    // トークンを消費して、サインインするユーザーを取得
+    let user_id = token_repo
+        .consume_mfa_pending_token(&input.token)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| invalid_token_error.clone())?;
+    // サインインした後にユーザーが削除された場合は、トークンが無効であるとみなす
+    let credential = user_repo
+        .credential_by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| invalid_token_error.clone())?;
+    // サインインした後にアカウントがロックされた場合は、トークンを発行しない
+    if !credential.active {
+        metrics.sign_in_failed();
+        return Err(UseCaseError::unauthorized(MessageKey::UserAccountIsLocked));
+    }
+    // サインインした後に2要素認証を無効にした場合は、トークンが無効であるとみなす
+    let secret = totp_repo
+        .totp_secret(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .filter(|secret| secret.is_enabled())
+        .ok_or(invalid_token_error)?;
+    // TOTPコードを検証
+    if !verify_totp(
+        authorization_settings,
+        &totp_repo,
+        clock,
+        &secret,
+        &input.code,
+    )
+    .await?
+    {
+        metrics.sign_in_failed();
+        let attempt = SignInAttempt {
+            client_ip: input.client_ip,
+            user_agent: input.user_agent.clone(),
+        };
+        if record_sign_in_failure(
+            authorization_settings,
+            &user_repo,
+            &credential,
+            &attempt,
+            clock.now_utc(),
+        )
+        .await?
+        {
+            token_repo
+                .revoke_mfa_pending_tokens(credential.user_id)
+                .await
+                .map_err(UseCaseError::from)?;
+            metrics.account_locked();
+            notifier.account_locked(credential.email.clone(), attempt.clone());
+            events
+                .publish(DomainEvent::AccountLocked {
+                    user_id: credential.user_id,
+                    email: credential.email,
+                    attempt,
+                })
+                .await;
+        }
+
+        return Err(invalid_totp_code_error(UseCaseErrorKind::Unauthorized));
+    }
+
+    let (output, credential) = issue_token_pair(
+        authorization_settings,
+        jwt_keys,
+        &user_repo,
+        &token_repo,
+        clock,
+        user_id,
+        input.device.as_deref(),
+    )
+    .await?;
+    metrics.sign_in_succeeded();
+    events
+        .publish(DomainEvent::UserSignedIn {
+            user_id: credential.user_id,
+        })
+        .await;
+    notifier.signed_in(credential.user_id, credential.email, input.device);
+
+    Ok(output)
+}
+
+/// ロックしてから、自動的にアンロックする時間が経過したユーザーのアカウントをアンロックする。
+///
+/// アンロックしたアカウントは、サインインに失敗した履歴を消去する。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `clock` - 時計
+///
+/// # 戻り値
+///
+/// アンロックしたアカウントの数
+#[tracing::instrument(
+    name = "unlock expired accounts use case",
+    skip(authorization_settings, user_repository, clock)
+)]
+pub async fn unlock_expired_accounts(
+    authorization_settings: &AuthorizationSettings,
+    user_repository: impl UserRepository,
+    clock: &impl Clock,
+) -> UseCaseResult<u64> {
+    // 自動的にアンロックしない場合は何もしない
+    if authorization_settings.lockout_seconds == 0 {
+        return Ok(0);
+    }
+    let before = clock.now_utc() - Duration::seconds(authorization_settings.lockout_seconds as i64);
+
+    user_repository
+        .unlock_expired_accounts(before)
+        .await
+        .map_err(UseCaseError::from)
+}
+
+/// サインインユースケース入力
+pub struct SignInUseCaseInput {
+    /// Eメールアドレス
+    pub email: EmailAddress,
+    /// 加工していないパスワード
+    pub password: RawPassword,
+    /// サインインしたデバイスを識別する文字列
+    pub device: Option<String>,
+    /// アクセス元のIPアドレス
+    ///
+    /// HTTPリクエストを介さずにサインインする場合など、判定できない場合は`None`
+    pub client_ip: Option<IpAddr>,
+    /// アクセス元のユーザーエージェント
+    pub user_agent: Option<String>,
+}
+
+/// サインインユースケースの結果
+pub enum SignInResult {
+    /// サインインが完了した
+    SignedIn(SignInUseCaseOutput),
+    /// 2要素認証を待機している
+    MfaPending(MfaPendingUseCaseOutput),
+    /// パスワードの変更を要求されている
+    PasswordChangeRequired(PasswordChangeRequiredUseCaseOutput),
+}
+
+/// 2要素認証を待機しているサインインのユースケース出力
+pub struct MfaPendingUseCaseOutput {
+    /// 2要素認証を待機しているサインインを示すトークン
+    pub token: SecretString,
+    /// トークンの有効期限
+    pub expiration: OffsetDateTime,
+}
+
+/// パスワードの変更を要求されているサインインのユースケース出力
+pub struct PasswordChangeRequiredUseCaseOutput {
+    /// パスワードを変更するためのトークン
+    pub token: SecretString,
+    /// トークンの有効期限
+    pub expiration: OffsetDateTime,
+}
+
+/// 2要素認証サインインユースケース入力
+pub struct SignInWithTotpUseCaseInput {
+    /// 2要素認証を待機しているサインインを示すトークン
+    pub token: SecretString,
+    /// 認証アプリが生成したTOTPコード
+    pub code: String,
+    /// サインインしたデバイスを識別する文字列
+    pub device: Option<String>,
+    /// アクセス元のIPアドレス
+    ///
+    /// HTTPリクエストを介さずにサインインする場合など、判定できない場合は`None`
+    pub client_ip: Option<IpAddr>,
+    /// アクセス元のユーザーエージェント
+    pub user_agent: Option<String>,
+}
+
+/// サインインユースケース出力
+pub struct SignInUseCaseOutput {
+    /// アクセストークン
+    pub access: AccessToken,
+    /// アクセストークンの有効期限
+    pub access_expiration: OffsetDateTime,
+    /// リフレッシュトークン
+    pub refresh: RefreshToken,
+    /// リフレッシュトークンの有効期限
+    pub refresh_expiration: OffsetDateTime,
+    /// 今回のサインインの前に、ユーザーが最後にサインインした日時
+    pub previous_sign_in_at: Option<OffsetDateTime>,
+}
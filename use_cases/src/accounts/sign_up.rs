@@ -0,0 +1,596 @@
+use std::sync::Arc;
+
+use time::OffsetDateTime;
+
+use domain::events::{DomainEvent, EventPublisher};
+use domain::models::audit_log::{AuditAction, AuditActor};
+use domain::models::primitives::*;
+use domain::models::user::{UserId, UserPermission, UserPermissionCode};
+use domain::repositories::token::TokenRepository;
+use domain::repositories::user::{SignUpInput, SignUpInputBuilder, SignUpOutput, UserRepository};
+use domain::repositories::user_permission::UserPermissionRepository;
+use domain::DomainError;
+use macros::Builder;
+
+use super::emails::issue_email_verification_token;
+use crate::messages::{MessageKey, UseCaseMessage};
+use crate::notifications::EmailSender;
+use crate::passwords::{generate_phc_string, generate_random_password};
+use crate::settings::{AuthorizationSettings, PasswordSettings};
+use crate::{
+    UseCaseError, UseCaseErrorKind, UseCaseResult, ERR_SAME_EMAIL_ADDRESS_IS_REGISTERED,
+    ERR_SPECIFY_FIXED_OR_MOBILE_NUMBER, ERR_TOO_MANY_USERS_TO_IMPORT,
+};
+
+/// サインアップユースケース入力
+#[derive(Debug, Clone, Builder)]
+pub struct SignUpUseCaseInput {
+    /// Eメールアドレス
+    pub email: EmailAddress,
+    /// パスワード
+    pub password: RawPassword,
+    /// アクティブフラグ
+    pub active: bool,
+    /// ユーザー権限コード
+    pub user_permission_code: UserPermissionCode,
+    /// 苗字
+    pub family_name: FamilyName,
+    /// 名前
+    pub given_name: GivenName,
+    /// 郵便番号
+    pub postal_code: PostalCode,
+    /// 住所
+    pub address: Address,
+    /// 固定電話番号
+    #[builder(default)]
+    pub fixed_phone_number: OptionalFixedPhoneNumber,
+    /// 携帯電話番号
+    #[builder(default)]
+    pub mobile_phone_number: OptionalMobilePhoneNumber,
+    /// 備考
+    #[builder(default)]
+    pub remarks: OptionalRemarks,
+}
+
+/// サインアップユースケース出力
+pub struct SignUpUseCaseOutput {
+    /// ユーザーID
+    pub id: UserId,
+    /// Eメールアドレス
+    pub email: EmailAddress,
+    /// アクティブフラグ
+    pub active: bool,
+    /// ユーザー権限
+    pub user_permission: UserPermission,
+    /// 苗字
+    pub family_name: FamilyName,
+    /// 名前
+    pub given_name: GivenName,
+    /// 郵便番号
+    pub postal_code: PostalCode,
+    /// 住所
+    pub address: Address,
+    /// 固定電話番号
+    pub fixed_phone_number: OptionalFixedPhoneNumber,
+    /// 携帯電話番号
+    pub mobile_phone_number: OptionalMobilePhoneNumber,
+    /// 備考
+    pub remarks: OptionalRemarks,
+    /// 作成日時
+    pub created_at: OffsetDateTime,
+    /// 更新日時
+    pub updated_at: OffsetDateTime,
+}
+
+impl SignUpUseCaseOutput {
+    /// リポジトリに登録したユーザーと、ユーザー権限からサインアップユースケース出力を構築する。
+    fn new(value: SignUpOutput, user_permission: UserPermission) -> Self {
+        Self {
+            id: value.id,
+            email: value.email,
+            active: value.active,
+            user_permission,
+            family_name: value.family_name,
+            given_name: value.given_name,
+            postal_code: value.postal_code,
+            address: value.address,
+            fixed_phone_number: value.fixed_phone_number,
+            mobile_phone_number: value.mobile_phone_number,
+            remarks: value.remarks,
+            created_at: value.created_at,
+            updated_at: value.updated_at,
+        }
+    }
+}
+
+/// ユーザーを登録する。
+///
+/// ユーザーを登録した後、ユーザーを登録したイベントを発行して、Eメールアドレスを確認するトークンを
+/// 生成して、ユーザーにEメールで送信する。
+/// トークンの登録に失敗した場合は、エラーを記録して、ユーザーの登録は成功として扱う。ユーザーは
+/// Eメールアドレスを確認するトークンを再送信できる。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `user_permission_repository` - ユーザー権限リポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `email_sender` - Eメール送信者
+/// * `events` - イベント発行者
+/// * `input` - サインアップユースケース入力
+///
+/// # 戻り値
+///
+/// * 登録したユーザー
+#[allow(clippy::too_many_arguments)]
+#[tracing::instrument(
+    name = "sign up use case",
+    skip(
+        password_settings,
+        authorization_settings,
+        user_repository,
+        user_permission_repository,
+        token_repository,
+        email_sender,
+        events,
+        input
+    ),
+    fields(user.email = %input.email)
+)]
+pub async fn sign_up(
+    password_settings: &PasswordSettings,
+    authorization_settings: &AuthorizationSettings,
+    user_repository: impl UserRepository,
+    user_permission_repository: impl UserPermissionRepository,
+    token_repository: impl TokenRepository,
+    email_sender: Arc<dyn EmailSender>,
+    events: &impl EventPublisher,
+    input: SignUpUseCaseInput,
+) -> UseCaseResult<SignUpUseCaseOutput> {
+    let user = create_user(
+        password_settings,
+        &user_repository,
+        &user_permission_repository,
+        input,
+    )
+    .await?;
+    events.publish(user_registered(&user)).await;
+    if let Err(e) = issue_email_verification_token(
+        authorization_settings,
+        &token_repository,
+        email_sender,
+        user.id,
+        user.email.clone(),
+    )
+    .await
+    {
+        tracing::error!("{} ({}:{})", e, file!(), line!());
+    }
+
+    Ok(user)
+}
+
+/// ユーザーをリポジトリに登録する。
+pub(super) async fn create_user(
+    password_settings: &PasswordSettings,
+    user_repository: &impl UserRepository,
+    user_permission_repository: &impl UserPermissionRepository,
+    input: SignUpUseCaseInput,
+) -> UseCaseResult<SignUpUseCaseOutput> {
+    let input = sign_up_input(password_settings, input)?;
+
+    // ユーザーを登録
+    let user = user_repository
+        .create(input)
+        .await
+        .map_err(create_user_error)?;
+    let user_permission =
+        user_permission(user_permission_repository, user.user_permission_code).await?;
+
+    Ok(SignUpUseCaseOutput::new(user, user_permission))
+}
+
+/// 登録したユーザーから、ユーザーを登録したイベントを構築する。
+fn user_registered(user: &SignUpUseCaseOutput) -> DomainEvent {
+    DomainEvent::UserRegistered {
+        user_id: user.id,
+        email: user.email.clone(),
+    }
+}
+
+/// ユーザー権限リポジトリから、ユーザー権限コードに対応するユーザー権限を取得する。
+///
+/// # 引数
+///
+/// * `user_permission_repository` - ユーザー権限リポジトリ
+/// * `code` - ユーザー権限コード
+///
+/// # 戻り値
+///
+/// ユーザー権限
+async fn user_permission(
+    user_permission_repository: &impl UserPermissionRepository,
+    code: UserPermissionCode,
+) -> UseCaseResult<UserPermission> {
+    // ユーザーのユーザー権限コードは外部キーで制約されているため、実際には見つからないことはない
+    user_permission_repository
+        .by_code(code)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::validation(MessageKey::UserPermissionCodeOutOfRange))
+}
+
+/// サインアップユースケース入力から、パスワードをハッシュ化してリポジトリに登録するユーザーを構築する。
+fn sign_up_input(
+    password_settings: &PasswordSettings,
+    input: SignUpUseCaseInput,
+) -> UseCaseResult<SignUpInput> {
+    let id = UserId::default();
+    let password = generate_phc_string(&input.password, password_settings)?;
+
+    SignUpInputBuilder::new()
+        .id(id)
+        .email(input.email)
+        .password(password)
+        .active(input.active)
+        .user_permission_code(input.user_permission_code)
+        .family_name(input.family_name)
+        .given_name(input.given_name)
+        .postal_code(input.postal_code)
+        .address(input.address)
+        .fixed_phone_number(input.fixed_phone_number)
+        .mobile_phone_number(input.mobile_phone_number)
+        .remarks(input.remarks)
+        .build()
+        .map_err(|e| UseCaseError::domain_rule(e.to_string()))
+}
+
+/// ユーザーをリポジトリに登録するときに発生したエラーを、ユースケースエラーに変換する。
+fn create_user_error(e: DomainError) -> UseCaseError {
+    let message = e.to_string();
+    if message.contains("ak_users_email") {
+        same_email_address_is_registered_error()
+    } else if message.contains("fk_users_permission") {
+        UseCaseError::validation(MessageKey::UserPermissionCodeOutOfRange)
+    } else if message.contains("ck_users_either_phone_numbers_must_be_not_null") {
+        // インフラストラクチャ層で検証されるため、実際にはここは実行されない
+        UseCaseError::new(
+            UseCaseErrorKind::DomainRule,
+            ERR_SPECIFY_FIXED_OR_MOBILE_NUMBER,
+            MessageKey::SpecifyFixedOrMobileNumber,
+        )
+    } else {
+        UseCaseError::repository(message)
+    }
+}
+
+/// 同じEメールアドレスを持つユーザーが登録されていることを示すユースケースエラーを構築する。
+pub(super) fn same_email_address_is_registered_error() -> UseCaseError {
+    UseCaseError::new(
+        UseCaseErrorKind::DomainRule,
+        ERR_SAME_EMAIL_ADDRESS_IS_REGISTERED,
+        MessageKey::SameEmailAddressIsRegistered,
+    )
+}
+
+/// 初期管理者を登録する。
+///
+/// 初期管理者と同じEメールアドレスを持つユーザーが登録されていない場合のみ、アクティブな管理者として
+/// ユーザーを登録する。したがって、何度呼び出しても、ユーザーを重複して登録しない。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `user_permission_repository` - ユーザー権限リポジトリ
+/// * `input` - サインアップユースケース入力
+///
+/// # 戻り値
+///
+/// 登録した初期管理者、すでにユーザーが登録されている場合は`None`
+#[tracing::instrument(
+    name = "seed initial admin use case",
+    skip(password_settings, user_repository, user_permission_repository, input),
+    fields(user.email = %input.email)
+)]
+pub async fn seed_initial_admin(
+    password_settings: &PasswordSettings,
+    user_repository: impl UserRepository,
+    user_permission_repository: impl UserPermissionRepository,
+    mut input: SignUpUseCaseInput,
+) -> UseCaseResult<Option<SignUpUseCaseOutput>> {
+    // 同じEメールアドレスを持つユーザーが登録されている場合は、何もしない
+    if user_repository
+        .by_email(input.email.clone())
+        .await?
+        .is_some()
+    {
+        return Ok(None);
+    }
+
+    input.active = true;
+    input.user_permission_code = UserPermissionCode::Admin;
+    let user = create_user(
+        password_settings,
+        &user_repository,
+        &user_permission_repository,
+        input,
+    )
+    .await?;
+    // 初期管理者のEメールアドレスは確認済みとする
+    user_repository
+        .verify_email(user.id)
+        .await
+        .map_err(UseCaseError::from)?;
+
+    Ok(Some(user))
+}
+
+/// 管理者が、ランダムに生成したパスワードでユーザーを登録する。
+///
+/// 管理者がパスワードを考えなくても済むように、パスワードポリシーを満たすパスワードを生成して登録する。
+/// 生成したパスワードはハッシュ化して保存するため、ユースケース出力で1度だけ返す。
+/// 生成したパスワードは管理者も知っているため、ユーザーが最初にサインインしたときにパスワードの変更を
+/// 要求する。
+/// なお、一括登録と同様に、Eメールアドレスを確認するトークンは送信しない。
+/// ユーザーを登録した管理者を記録するため、ユーザーと同じトランザクションで監査ログを記録する。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `user_permission_repository` - ユーザー権限リポジトリ
+/// * `events` - イベント発行者
+/// * `input` - ユーザー登録ユースケース入力
+///
+/// # 戻り値
+///
+/// 登録したユーザーと生成したパスワード
+#[tracing::instrument(
+    name = "create user use case",
+    skip(password_settings, user_repository, user_permission_repository, events, input),
+    fields(user.email = %input.email, actor.id = %input.actor.user_id)
+)]
+pub async fn create_user_with_generated_password(
+    password_settings: &PasswordSettings,
+    user_repository: impl UserRepository,
+    user_permission_repository: impl UserPermissionRepository,
+    events: &impl EventPublisher,
+    input: CreateUserUseCaseInput,
+) -> UseCaseResult<CreateUserUseCaseOutput> {
+    let password = generate_random_password(&password_settings.policy())?;
+    let audit = input.actor.entry(
+        AuditAction::CreateUser,
+        None,
+        serde_json::json!({
+            "active": input.active,
+            "userPermissionCode": input.user_permission_code as i16,
+        }),
+    );
+    let input = SignUpUseCaseInput {
+        email: input.email,
+        password: password.clone(),
+        active: input.active,
+        user_permission_code: input.user_permission_code,
+        family_name: input.family_name,
+        given_name: input.given_name,
+        postal_code: input.postal_code,
+        address: input.address,
+        fixed_phone_number: input.fixed_phone_number,
+        mobile_phone_number: input.mobile_phone_number,
+        remarks: input.remarks,
+    };
+    // 生成したパスワードは管理者から通知されるため、ユーザーを登録するときに、
+    // 最初のサインインでパスワードの変更を要求するように設定
+    let input = SignUpInput {
+        password_change_required: true,
+        ..sign_up_input(password_settings, input)?
+    };
+
+    // 監査ログを記録するため、1人のユーザーを一括登録するメソッドで登録
+    let user = user_repository
+        .create_many(vec![input], true, audit)
+        .await
+        .map_err(UseCaseError::from)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| UseCaseError::unexpected("登録したユーザーを取得できませんでした。"))?
+        .map_err(create_user_error)?;
+    let user_permission =
+        user_permission(&user_permission_repository, user.user_permission_code).await?;
+    let user = SignUpUseCaseOutput::new(user, user_permission);
+    events.publish(user_registered(&user)).await;
+
+    Ok(CreateUserUseCaseOutput { user, password })
+}
+
+/// パスワードを生成してユーザーを登録するユースケース入力
+#[derive(Debug, Clone)]
+pub struct CreateUserUseCaseInput {
+    /// ユーザーを登録する管理者
+    pub actor: AuditActor,
+    /// Eメールアドレス
+    pub email: EmailAddress,
+    /// アクティブフラグ
+    pub active: bool,
+    /// ユーザー権限コード
+    pub user_permission_code: UserPermissionCode,
+    /// 苗字
+    pub family_name: FamilyName,
+    /// 名前
+    pub given_name: GivenName,
+    /// 郵便番号
+    pub postal_code: PostalCode,
+    /// 住所
+    pub address: Address,
+    /// 固定電話番号
+    pub fixed_phone_number: OptionalFixedPhoneNumber,
+    /// 携帯電話番号
+    pub mobile_phone_number: OptionalMobilePhoneNumber,
+    /// 備考
+    pub remarks: OptionalRemarks,
+}
+
+/// パスワードを生成してユーザーを登録するユースケース出力
+pub struct CreateUserUseCaseOutput {
+    /// 登録したユーザー
+    pub user: SignUpUseCaseOutput,
+    /// 生成したパスワード
+    ///
+    /// ユーザーは、最初にサインインした後にパスワードを変更しなければならない。
+    pub password: RawPassword,
+}
+
+/// ユーザー一括登録ユースケース入力
+#[derive(Debug, Clone)]
+pub struct BulkSignUpUseCaseInput {
+    /// 登録するユーザーのリスト
+    ///
+    /// リクエストの内容からサインアップユースケース入力を構築できなかったユーザーには、そのエラーを格納する。
+    pub users: Vec<UseCaseResult<SignUpUseCaseInput>>,
+    /// 1人でも登録に失敗した場合に、すべてのユーザーを登録しないか示すフラグ
+    pub atomic: bool,
+    /// ユーザーを一括して登録する管理者
+    pub actor: AuditActor,
+}
+
+/// ユーザー一括登録ユースケース出力
+pub struct BulkSignUpUseCaseOutput {
+    /// ユーザーの登録を確定したか示すフラグ
+    ///
+    /// `atomic`が`true`で、1人でも登録に失敗したユーザーがいる場合は`false`である。
+    pub committed: bool,
+    /// 入力と同じ順番で、ユーザーごとの登録結果を格納したリスト
+    pub results: Vec<BulkSignUpResult>,
+}
+
+/// ユーザーごとの一括登録の結果
+pub enum BulkSignUpResult {
+    /// ユーザーを登録した。
+    SignedUp(Box<SignUpUseCaseOutput>),
+    /// ユーザーの登録に失敗した。
+    Failed(UseCaseError),
+    /// 他のユーザーの登録に失敗したため、ユーザーを登録しなかった。
+    Skipped,
+}
+
+/// 複数のユーザーを一括して登録する。
+///
+/// ユーザーの登録に失敗しても、他のユーザーの登録を継続して、ユーザーごとに結果を返す。
+/// `atomic`が`true`の場合は、1人でも登録に失敗したユーザーがいれば、すべてのユーザーを登録しない。
+/// なお、一括して登録したユーザーには、Eメールアドレスを確認するトークンを送信しない。
+/// 登録したユーザーごとに、ユーザーと同じトランザクションで監査ログを記録する。
+/// 登録を確定した場合は、登録したユーザーごとに、ユーザーを登録したイベントを発行する。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `user_permission_repository` - ユーザー権限リポジトリ
+/// * `events` - イベント発行者
+/// * `max_users` - 一括して登録できるユーザーの最大数
+/// * `input` - ユーザー一括登録ユースケース入力
+///
+/// # 戻り値
+///
+/// ユーザー一括登録ユースケース出力
+#[tracing::instrument(
+    name = "bulk sign up use case",
+    skip(password_settings, user_repository, user_permission_repository, events, input),
+    fields(
+        users = input.users.len(),
+        atomic = input.atomic,
+        actor.id = %input.actor.user_id
+    )
+)]
+pub async fn bulk_sign_up(
+    password_settings: &PasswordSettings,
+    user_repository: impl UserRepository,
+    user_permission_repository: impl UserPermissionRepository,
+    events: &impl EventPublisher,
+    max_users: usize,
+    input: BulkSignUpUseCaseInput,
+) -> UseCaseResult<BulkSignUpUseCaseOutput> {
+    if max_users < input.users.len() {
+        return Err(UseCaseError::new(
+            UseCaseErrorKind::Validation,
+            ERR_TOO_MANY_USERS_TO_IMPORT,
+            UseCaseMessage::from(MessageKey::TooManyUsersToImport).with_param("max", max_users),
+        ));
+    }
+
+    // ユーザーを検証して、リポジトリに登録するユーザーを構築
+    let mut errors = Vec::with_capacity(input.users.len());
+    let mut users = vec![];
+    for user in input.users {
+        match user.and_then(|user| sign_up_input(password_settings, user)) {
+            Ok(user) => {
+                users.push(user);
+                errors.push(None);
+            }
+            Err(e) => errors.push(Some(e)),
+        }
+    }
+
+    // すべてのユーザーを登録しない場合で、検証に失敗したユーザーがいる場合は、リポジトリに登録しない
+    let mut created = if input.atomic && users.len() < errors.len() {
+        vec![]
+    } else {
+        let audit = input.actor.entry(
+            AuditAction::ImportUser,
+            None,
+            serde_json::json!({ "atomic": input.atomic }),
+        );
+        user_repository
+            .create_many(users, input.atomic, audit)
+            .await
+            .map_err(UseCaseError::from)?
+    }
+    .into_iter();
+    let user_permissions = user_permission_repository
+        .list()
+        .await
+        .map_err(UseCaseError::from)?;
+    let mut results = errors
+        .into_iter()
+        .map(|error| match error {
+            Some(e) => BulkSignUpResult::Failed(e),
+            None => match created.next() {
+                Some(Ok(user)) => match user_permissions
+                    .iter()
+                    .find(|permission| permission.code == user.user_permission_code)
+                {
+                    Some(permission) => BulkSignUpResult::SignedUp(Box::new(
+                        SignUpUseCaseOutput::new(user, permission.clone()),
+                    )),
+                    None => BulkSignUpResult::Failed(UseCaseError::validation(
+                        MessageKey::UserPermissionCodeOutOfRange,
+                    )),
+                },
+                Some(Err(e)) => BulkSignUpResult::Failed(create_user_error(e)),
+                None => BulkSignUpResult::Skipped,
+            },
+        })
+        .collect::<Vec<_>>();
+    let committed = !input.atomic
+        || results
+            .iter()
+            .all(|result| matches!(result, BulkSignUpResult::SignedUp(_)));
+    // 登録を確定していない場合、登録に成功したユーザーはリポジトリに存在しない
+    if !committed {
+        for result in results.iter_mut() {
+            if matches!(result, BulkSignUpResult::SignedUp(_)) {
+                *result = BulkSignUpResult::Skipped;
+            }
+        }
+    }
+    for result in results.iter() {
+        if let BulkSignUpResult::SignedUp(user) = result {
+            events.publish(user_registered(user)).await;
+        }
+    }
+
+    Ok(BulkSignUpUseCaseOutput { committed, results })
+}
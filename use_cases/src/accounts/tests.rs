@@ -0,0 +1,1360 @@
+use std::str::FromStr as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use secrecy::{ExposeSecret as _, SecretString};
+use time::{Duration, OffsetDateTime};
+
+use domain::clock::{Clock, FixedClock, SteppingClock, SystemClock};
+use domain::events::{DomainEvent, NoopEventPublisher, RecordingEventPublisher};
+use domain::models::audit_log::{AuditAction, AuditActor};
+use domain::models::primitives::*;
+use domain::models::query::Pagination;
+use domain::models::user::{UserId, UserPermissionCode};
+use domain::repositories::audit_log::AuditLogFilter;
+use domain::repositories::device::DeviceRepository;
+use domain::repositories::memory::token::InMemoryTokenRepository;
+use domain::repositories::memory::user::InMemoryUserRepository;
+use domain::repositories::memory::user_permission::InMemoryUserPermissionRepository;
+use domain::repositories::token::{
+    AccessToken, RefreshToken, TokenPairWithTtl, TokenRepository, TokenType,
+};
+use domain::repositories::totp::{TotpRepository, UserTotpSecret};
+use domain::repositories::user::{SignInAttempt, UserCredential, UserListFilter, UserRepository};
+use domain::DomainResult;
+
+use super::sign_up::create_user;
+use super::*;
+use crate::jwt::{generate_token_pair, JwtKeys};
+use crate::messages::{MessageKey, UseCaseMessage};
+use crate::metrics::SignInMetrics;
+use crate::notifications::{NoopEmailSender, SignInNotifier};
+use crate::passwords::generate_phc_string;
+use crate::settings::{AuthorizationSettings, PasswordSettings, RetentionSettings};
+use crate::{
+    UseCaseErrorKind, UseCaseResult, ERR_INVALID_PASSWORD_CHANGE_TOKEN,
+    ERR_NEW_PASSWORD_MUST_DIFFER,
+};
+
+/// ユーザーのパスワード
+const PASSWORD: &str = "Az3#Za3@";
+/// ユーザーのパスワードと異なるパスワード
+const WRONG_PASSWORD: &str = "Bz4#Zb4@";
+/// 変更後のユーザーのパスワード
+const NEW_PASSWORD: &str = "Cz5#Zc5@";
+/// ユーザーのEメールアドレス
+const EMAIL: &str = "foo@example.com";
+
+/// TOTPの秘密鍵を登録していないTOTPリポジトリ
+#[derive(Debug)]
+struct EmptyTotpRepository;
+
+#[async_trait]
+impl TotpRepository for EmptyTotpRepository {
+    async fn totp_secret(&self, _user_id: UserId) -> DomainResult<Option<UserTotpSecret>> {
+        Ok(None)
+    }
+
+    async fn register_totp_secret(
+        &self,
+        _user_id: UserId,
+        _encrypted_secret: Vec<u8>,
+    ) -> DomainResult<()> {
+        Ok(())
+    }
+
+    async fn enable_totp(&self, _user_id: UserId) -> DomainResult<bool> {
+        Ok(false)
+    }
+
+    async fn delete_totp_secret(&self, _user_id: UserId) -> DomainResult<bool> {
+        Ok(false)
+    }
+
+    async fn record_used_totp_step(&self, _user_id: UserId, _step: u64) -> DomainResult<bool> {
+        Ok(true)
+    }
+}
+
+/// すべてのデバイスを、はじめてサインインしたデバイスとするデバイスリポジトリ
+#[derive(Debug)]
+struct NewDeviceRepository;
+
+#[async_trait]
+impl DeviceRepository for NewDeviceRepository {
+    async fn register_device(&self, _user_id: UserId, _device: &str) -> DomainResult<bool> {
+        Ok(true)
+    }
+}
+
+/// サインインの結果を数えるサインインメトリクス
+#[derive(Debug, Default)]
+struct CountingSignInMetrics {
+    succeeded: AtomicUsize,
+    failed: AtomicUsize,
+    locked: AtomicUsize,
+}
+
+impl SignInMetrics for CountingSignInMetrics {
+    fn sign_in_succeeded(&self) {
+        self.succeeded.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn sign_in_failed(&self) {
+        self.failed.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn account_locked(&self) {
+        self.locked.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn password_settings() -> PasswordSettings {
+    PasswordSettings {
+        pepper: SecretString::new(String::from("pepper")),
+        hash_memory: 12288,
+        hash_iterations: 3,
+        hash_parallelism: 1,
+        max_length: PASSWORD_MAX_LENGTH,
+    }
+}
+
+/// 10分間に3回サインインに失敗するとロックして、1時間後に自動的にアンロックする認証設定
+fn authorization_settings() -> AuthorizationSettings {
+    AuthorizationSettings {
+        attempting_seconds: 600,
+        number_of_failures: 3,
+        lockout_seconds: 3600,
+        jwt_token_secret: SecretString::new(String::from("jwt-token-secret")),
+        jwt_signing: Default::default(),
+        totp_secret_key: SecretString::new(String::from("totp-secret-key")),
+        access_token_seconds: 300,
+        refresh_token_seconds: 3600,
+        require_verified_email: false,
+        email_verification_token_seconds: 86400,
+        email_change_token_seconds: 3600,
+        mfa_pending_token_seconds: 300,
+        password_change_token_seconds: 600,
+        reauthentication_seconds: 300,
+        introspection_credential: Some(SecretString::new(String::from("service-credential"))),
+    }
+}
+
+/// ユーザーを登録したメモリユーザーリポジトリを返す。
+async fn user_repository() -> (InMemoryUserRepository, UserId) {
+    let repo = InMemoryUserRepository::new();
+    let user = create_user(
+        &password_settings(),
+        &repo,
+        &InMemoryUserPermissionRepository::new(),
+        sign_up_use_case_input(),
+    )
+    .await
+    .unwrap();
+
+    (repo, user.id)
+}
+
+/// ユーザーを登録するサインアップユースケース入力を構築する。
+fn sign_up_use_case_input() -> SignUpUseCaseInput {
+    SignUpUseCaseInputBuilder::new()
+        .email(EmailAddress::new(EMAIL).unwrap())
+        .password(RawPassword::new(SecretString::from_str(PASSWORD).unwrap()).unwrap())
+        .active(true)
+        .user_permission_code(UserPermissionCode::General)
+        .family_name(FamilyName::new("foo").unwrap())
+        .given_name(GivenName::new("bar").unwrap())
+        .postal_code(PostalCode::new("012-3456").unwrap())
+        .address(Address::new("foo bar baz qux").unwrap())
+        .fixed_phone_number(OptionalFixedPhoneNumber::try_from("03-1234-5678").unwrap())
+        .build()
+        .unwrap()
+}
+
+/// メモリリポジトリと、イベントを記録するイベント発行者を注入したアカウントユースケースを構築する。
+fn accounts_use_case(
+    repo: InMemoryUserRepository,
+    token_repo: Arc<InMemoryTokenRepository>,
+    events: Arc<RecordingEventPublisher>,
+) -> AccountsUseCase {
+    let settings = authorization_settings();
+    AccountsUseCase::new(
+        password_settings(),
+        settings.clone(),
+        JwtKeys::new(&settings).unwrap(),
+        AccountsRepositories {
+            audit_log: Arc::new(repo.audit_log_repository()),
+            user: Arc::new(repo),
+            user_permission: Arc::new(InMemoryUserPermissionRepository::new()),
+            token: token_repo,
+            totp: Arc::new(EmptyTotpRepository),
+            device: Arc::new(NewDeviceRepository),
+        },
+        Arc::new(NoopEmailSender),
+        Arc::new(CountingSignInMetrics::default()),
+        Arc::new(SystemClock),
+    )
+    .with_event_publisher(events)
+}
+
+/// パスワードを指定して、メモリユーザーリポジトリに登録したユーザーでサインインする。
+async fn sign_in_with(
+    settings: &AuthorizationSettings,
+    repo: &InMemoryUserRepository,
+    metrics: &CountingSignInMetrics,
+    password: &str,
+) -> UseCaseResult<SignInResult> {
+    sign_in_at(settings, repo, metrics, &SystemClock, password).await
+}
+
+/// 時計とパスワードを指定して、メモリユーザーリポジトリに登録したユーザーでサインインする。
+async fn sign_in_at(
+    settings: &AuthorizationSettings,
+    repo: &InMemoryUserRepository,
+    metrics: &CountingSignInMetrics,
+    clock: &impl Clock,
+    password: &str,
+) -> UseCaseResult<SignInResult> {
+    sign_in_input_at(settings, repo, metrics, clock, sign_in_input(password)).await
+}
+
+/// パスワードを指定して、アクセス元を判定できないサインインユースケース入力を構築する。
+fn sign_in_input(password: &str) -> SignInUseCaseInput {
+    SignInUseCaseInput {
+        email: EmailAddress::new(EMAIL).unwrap(),
+        password: RawPassword::new(SecretString::from_str(password).unwrap()).unwrap(),
+        device: None,
+        client_ip: None,
+        user_agent: None,
+    }
+}
+
+/// 時計とサインインユースケース入力を指定して、メモリユーザーリポジトリに登録したユーザーで
+/// サインインする。
+async fn sign_in_input_at(
+    settings: &AuthorizationSettings,
+    repo: &InMemoryUserRepository,
+    metrics: &CountingSignInMetrics,
+    clock: &impl Clock,
+    input: SignInUseCaseInput,
+) -> UseCaseResult<SignInResult> {
+    sign_in(
+        &password_settings(),
+        settings,
+        &JwtKeys::new(settings).unwrap(),
+        repo.clone(),
+        InMemoryTokenRepository::new(),
+        EmptyTotpRepository,
+        metrics,
+        SignInNotifier::new(NewDeviceRepository, Arc::new(NoopEmailSender)),
+        &NoopEventPublisher,
+        clock,
+        input,
+    )
+    .await
+}
+
+/// ユーザーのクレデンシャルを取得する。
+async fn credential(repo: &InMemoryUserRepository) -> UserCredential {
+    repo.user_credential(EmailAddress::new(EMAIL).unwrap())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+fn error_message(result: UseCaseResult<SignInResult>) -> UseCaseMessage {
+    match result {
+        Ok(_) => panic!("sign in must fail"),
+        Err(e) => e.message,
+    }
+}
+
+/// 生成したパスワードでユーザーを登録すると、サインインしたときにパスワードの変更を要求して、
+/// パスワードを変更した後は、変更後のパスワードでサインインできることを確認
+#[tokio::test]
+async fn user_created_with_generated_password_must_change_password() {
+    let repo = InMemoryUserRepository::new();
+    let input = CreateUserUseCaseInput {
+        actor: audit_actor(),
+        email: EmailAddress::new(EMAIL).unwrap(),
+        active: true,
+        user_permission_code: UserPermissionCode::General,
+        family_name: FamilyName::new("foo").unwrap(),
+        given_name: GivenName::new("bar").unwrap(),
+        postal_code: PostalCode::new("012-3456").unwrap(),
+        address: Address::new("foo bar baz qux").unwrap(),
+        fixed_phone_number: OptionalFixedPhoneNumber::try_from("03-1234-5678").unwrap(),
+        mobile_phone_number: OptionalMobilePhoneNumber::none(),
+        remarks: OptionalRemarks::none(),
+    };
+
+    let output = create_user_with_generated_password(
+        &password_settings(),
+        repo.clone(),
+        InMemoryUserPermissionRepository::new(),
+        &NoopEventPublisher,
+        input,
+    )
+    .await
+    .unwrap();
+
+    assert!(output.user.active);
+    assert!(credential(&repo).await.password_change_required);
+    let settings = authorization_settings();
+    let token_repo = Arc::new(InMemoryTokenRepository::new());
+    let password = output.password.value.expose_secret();
+    let result = sign_in(
+        &password_settings(),
+        &settings,
+        &JwtKeys::new(&settings).unwrap(),
+        repo.clone(),
+        token_repo.clone(),
+        EmptyTotpRepository,
+        &CountingSignInMetrics::default(),
+        SignInNotifier::new(NewDeviceRepository, Arc::new(NoopEmailSender)),
+        &NoopEventPublisher,
+        &SystemClock,
+        sign_in_input(password),
+    )
+    .await;
+    let Ok(SignInResult::PasswordChangeRequired(required)) = result else {
+        panic!("sign in must require password change");
+    };
+    // 現在のパスワードと同じパスワードには変更できない
+    let same_password = ChangeRequiredPasswordUseCaseInput {
+        token: required.token.clone(),
+        new_password: output.password.clone(),
+    };
+    let result = change_required_password(
+        &password_settings(),
+        repo.clone(),
+        token_repo.clone(),
+        &NoopEventPublisher,
+        same_password,
+    )
+    .await;
+    assert_eq!(
+        Some(ERR_NEW_PASSWORD_MUST_DIFFER),
+        result.err().map(|e| e.error_code)
+    );
+    // トークンは1度しか使用できない
+    let input = ChangeRequiredPasswordUseCaseInput {
+        token: required.token,
+        new_password: RawPassword::new(SecretString::from_str(NEW_PASSWORD).unwrap()).unwrap(),
+    };
+    let result = change_required_password(
+        &password_settings(),
+        repo.clone(),
+        token_repo,
+        &NoopEventPublisher,
+        input,
+    )
+    .await;
+    assert_eq!(
+        Some(ERR_INVALID_PASSWORD_CHANGE_TOKEN),
+        result.err().map(|e| e.error_code)
+    );
+
+    let token = generate_one_time_token();
+    let token_repo = InMemoryTokenRepository::new();
+    token_repo
+        .register_password_change_token(output.user.id, &token, 60)
+        .await
+        .unwrap();
+    let input = ChangeRequiredPasswordUseCaseInput {
+        token,
+        new_password: RawPassword::new(SecretString::from_str(NEW_PASSWORD).unwrap()).unwrap(),
+    };
+    change_required_password(
+        &password_settings(),
+        repo.clone(),
+        token_repo,
+        &NoopEventPublisher,
+        input,
+    )
+    .await
+    .unwrap();
+
+    assert!(!credential(&repo).await.password_change_required);
+    let result = sign_in_with(
+        &settings,
+        &repo,
+        &CountingSignInMetrics::default(),
+        NEW_PASSWORD,
+    )
+    .await;
+    assert!(matches!(result, Ok(SignInResult::SignedIn(_))));
+}
+
+/// パスワードを変更すると、変更後のパスワードでのみサインインでき、サインインに失敗した履歴を
+/// 消去して、監査ログを記録することを確認
+#[tokio::test]
+async fn password_is_updated_and_sign_in_failures_are_cleared() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (repo, user_id) = user_repository().await;
+    let _ = sign_in_with(&settings, &repo, &metrics, WRONG_PASSWORD).await;
+    let new_password = RawPassword::new(SecretString::from_str(NEW_PASSWORD).unwrap()).unwrap();
+    let phc_password = generate_phc_string(&new_password, &password_settings()).unwrap();
+    let actor = audit_actor();
+
+    update_password(&repo, &actor, user_id, phc_password)
+        .await
+        .unwrap();
+
+    let logs = repo
+        .audit_log_repository()
+        .list(AuditLogFilter {
+            target_user_id: Some(user_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(1, logs.len());
+    assert_eq!(AuditAction::UpdatePassword, logs[0].action);
+    assert_eq!(actor.user_id, logs[0].actor_user_id);
+    assert_eq!(actor.request_id, logs[0].request_id);
+
+    assert_eq!(0, credential(&repo).await.number_of_failures.value);
+    assert!(sign_in_with(&settings, &repo, &metrics, PASSWORD)
+        .await
+        .is_err());
+    assert!(sign_in_with(&settings, &repo, &metrics, NEW_PASSWORD)
+        .await
+        .is_ok());
+}
+
+/// サインインに失敗した後に、アカウントの状況でサインインの失敗とアクティブであることを
+/// 取得できることを確認
+#[tokio::test]
+async fn account_activity_reports_sign_in_failure() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (repo, user_id) = user_repository().await;
+    let _ = sign_in_with(&settings, &repo, &metrics, WRONG_PASSWORD).await;
+
+    let activity = account_activity(
+        repo.clone(),
+        InMemoryTokenRepository::new(),
+        user_id,
+        &AccessToken::new(SecretString::new(String::from("access"))),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(user_id, activity.user_id);
+    assert!(activity.active);
+    assert!(activity.last_sign_in_at.is_none());
+    assert!(activity.sign_in_attempted_at.is_some());
+    assert_eq!(1, activity.number_of_sign_in_failures.value);
+    assert!(activity.locked_at.is_none());
+    assert_eq!(0, activity.active_sessions);
+}
+
+/// 失敗回数をカウントする期間内にサインインに失敗すると、最初に失敗した日時を維持して
+/// 失敗回数を増やし、失敗回数に達したときにアカウントをロックすることを確認
+#[tokio::test]
+async fn account_is_locked_after_failures_within_attempting_period() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (repo, _) = user_repository().await;
+    // 失敗回数をカウントする期間の最後まで、サインインするたびに日時を進める
+    let step = Duration::seconds(settings.attempting_seconds as i64 / 2);
+    let clock = SteppingClock::new(OffsetDateTime::now_utc(), step);
+
+    let result = sign_in_at(&settings, &repo, &metrics, &clock, WRONG_PASSWORD).await;
+    assert_eq!(
+        UseCaseMessage::from(MessageKey::WrongEmailAddressOrPassword),
+        error_message(result)
+    );
+    let first = credential(&repo).await;
+    assert_eq!(1, first.number_of_failures.value);
+    assert!(first.attempted_at.is_some());
+
+    let _ = sign_in_at(&settings, &repo, &metrics, &clock, WRONG_PASSWORD).await;
+    let second = credential(&repo).await;
+    assert_eq!(2, second.number_of_failures.value);
+    assert_eq!(first.attempted_at, second.attempted_at);
+    assert!(second.active);
+
+    let _ = sign_in_at(&settings, &repo, &metrics, &clock, WRONG_PASSWORD).await;
+    let locked = credential(&repo).await;
+    assert_eq!(3, locked.number_of_failures.value);
+    assert!(!locked.active);
+    assert!(locked.locked_at.is_some());
+    assert_eq!(1, metrics.locked.load(Ordering::SeqCst));
+
+    // ロックしたアカウントは、正しいパスワードでもサインインできない
+    let result = sign_in_at(&settings, &repo, &metrics, &clock, PASSWORD).await;
+    assert_eq!(
+        UseCaseMessage::from(MessageKey::UserAccountIsLocked),
+        error_message(result)
+    );
+    assert_eq!(4, metrics.failed.load(Ordering::SeqCst));
+    assert_eq!(0, metrics.succeeded.load(Ordering::SeqCst));
+}
+
+/// 失敗回数をカウントする期間が経過した後にサインインに失敗すると、最初の失敗として記録し直すことを確認
+#[tokio::test]
+async fn failure_after_attempting_period_is_recorded_as_first_failure() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (repo, _) = user_repository().await;
+    let clock = SteppingClock::new(OffsetDateTime::now_utc(), Duration::ZERO);
+    for _ in 0..2 {
+        let _ = sign_in_at(&settings, &repo, &metrics, &clock, WRONG_PASSWORD).await;
+    }
+
+    clock.advance(Duration::seconds(settings.attempting_seconds as i64 + 1));
+    let _ = sign_in_at(&settings, &repo, &metrics, &clock, WRONG_PASSWORD).await;
+
+    let credential = credential(&repo).await;
+    assert_eq!(1, credential.number_of_failures.value);
+    assert!(credential.active);
+    assert_eq!(0, metrics.locked.load(Ordering::SeqCst));
+}
+
+/// サインインに失敗したアクセス元を記録して、アクセス元を判定できないサインインの失敗では
+/// 記録を消去することを確認
+#[tokio::test]
+async fn sign_in_failure_records_client_ip_and_user_agent() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (repo, user_id) = user_repository().await;
+    let input = SignInUseCaseInput {
+        client_ip: Some("192.0.2.1".parse().unwrap()),
+        user_agent: Some(String::from("Mozilla/5.0")),
+        ..sign_in_input(WRONG_PASSWORD)
+    };
+
+    let _ = sign_in_input_at(&settings, &repo, &metrics, &SystemClock, input).await;
+    let attempt = repo.last_failed_sign_in_attempt(user_id).await.unwrap();
+    assert_eq!(Some("192.0.2.1".parse().unwrap()), attempt.client_ip);
+    assert_eq!(Some("Mozilla/5.0"), attempt.user_agent.as_deref());
+
+    let _ = sign_in_with(&settings, &repo, &metrics, WRONG_PASSWORD).await;
+    let attempt = repo.last_failed_sign_in_attempt(user_id).await.unwrap();
+    assert_eq!(SignInAttempt::default(), attempt);
+}
+
+/// サインインに成功すると、サインインに失敗した履歴を消去することを確認
+#[tokio::test]
+async fn successful_sign_in_clears_sign_in_failures() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (repo, user_id) = user_repository().await;
+    for _ in 0..2 {
+        let _ = sign_in_with(&settings, &repo, &metrics, WRONG_PASSWORD).await;
+    }
+
+    let result = sign_in_with(&settings, &repo, &metrics, PASSWORD).await;
+
+    assert!(matches!(result, Ok(SignInResult::SignedIn(_))));
+    let credential = credential(&repo).await;
+    assert_eq!(0, credential.number_of_failures.value);
+    assert!(credential.attempted_at.is_none());
+    let user = repo.by_id(user_id).await.unwrap().unwrap();
+    assert!(user.last_sign_in_at.is_some());
+    assert_eq!(1, metrics.succeeded.load(Ordering::SeqCst));
+}
+
+/// アカウントを自動的にアンロックするまでの時間が経過した場合のみ、ロックしたアカウントで
+/// サインインできることを確認
+#[tokio::test]
+async fn locked_account_can_sign_in_only_after_lockout_expires() {
+    let (repo, user_id) = user_repository().await;
+    repo.lock_user_account(user_id).await.unwrap();
+    let locked_at = OffsetDateTime::now_utc();
+    let metrics = CountingSignInMetrics::default();
+    let settings = authorization_settings();
+    let lockout = Duration::seconds(settings.lockout_seconds as i64);
+
+    // 自動的にアンロックしない設定では、サインインできない
+    let mut never_unlock = authorization_settings();
+    never_unlock.lockout_seconds = 0;
+    let clock = FixedClock(locked_at + lockout + Duration::seconds(1));
+    let result = sign_in_at(&never_unlock, &repo, &metrics, &clock, PASSWORD).await;
+    assert_eq!(
+        UseCaseMessage::from(MessageKey::UserAccountIsLocked),
+        error_message(result)
+    );
+
+    // アンロックするまでの時間が経過する前は、サインインできない
+    let clock = FixedClock(locked_at + lockout - Duration::seconds(1));
+    let result = sign_in_at(&settings, &repo, &metrics, &clock, PASSWORD).await;
+    assert_eq!(
+        UseCaseMessage::from(MessageKey::UserAccountIsLocked),
+        error_message(result)
+    );
+
+    let clock = FixedClock(locked_at + lockout + Duration::seconds(1));
+    let result = sign_in_at(&settings, &repo, &metrics, &clock, PASSWORD).await;
+    assert!(matches!(result, Ok(SignInResult::SignedIn(_))));
+    let credential = credential(&repo).await;
+    assert!(credential.active);
+    assert!(credential.locked_at.is_none());
+}
+
+/// サインインの途中でユーザーが削除された場合は、パニックせずにトークンの発行を拒否することを確認
+#[tokio::test]
+async fn token_pair_is_not_issued_to_user_deleted_during_sign_in() {
+    let settings = authorization_settings();
+    let (repo, user_id) = user_repository().await;
+    repo.soft_delete(user_id, None).await.unwrap();
+
+    let result = super::sign_in::issue_token_pair(
+        &settings,
+        &JwtKeys::new(&settings).unwrap(),
+        &repo,
+        &InMemoryTokenRepository::new(),
+        &SystemClock,
+        user_id,
+        None,
+    )
+    .await;
+
+    let e = result.err().expect("token pair must not be issued");
+    assert_eq!(UseCaseErrorKind::Unauthorized, e.kind);
+    assert_eq!(
+        UseCaseMessage::from(MessageKey::WrongEmailAddressOrPassword),
+        e.message
+    );
+}
+
+/// アカウントを自動的にアンロックするまでの時間が経過したアカウントのみをアンロックすることを確認
+#[tokio::test]
+async fn unlock_expired_accounts_uses_clock() {
+    let (repo, user_id) = user_repository().await;
+    repo.lock_user_account(user_id).await.unwrap();
+    let locked_at = OffsetDateTime::now_utc();
+    let settings = authorization_settings();
+    let lockout = Duration::seconds(settings.lockout_seconds as i64);
+
+    let clock = FixedClock(locked_at + lockout - Duration::seconds(1));
+    let count = unlock_expired_accounts(&settings, repo.clone(), &clock)
+        .await
+        .unwrap();
+    assert_eq!(0, count);
+
+    let clock = FixedClock(locked_at + lockout + Duration::seconds(1));
+    let count = unlock_expired_accounts(&settings, repo.clone(), &clock)
+        .await
+        .unwrap();
+    assert_eq!(1, count);
+    assert!(credential(&repo).await.active);
+}
+
+/// 時計の現在の日時から保持期間を経過したレコードのみを消去し、保持期間に`0`を設定した
+/// レコードは消去しないことを確認
+#[tokio::test]
+async fn purge_expired_records_uses_clock_and_retention_days() {
+    let (repo, user_id) = user_repository().await;
+    repo.record_first_sign_in_failed(user_id, &SignInAttempt::default())
+        .await
+        .unwrap();
+    let attempted_at = OffsetDateTime::now_utc();
+    let settings = RetentionSettings {
+        history_days: 7,
+        deleted_user_days: 0,
+        ..Default::default()
+    };
+
+    let clock = FixedClock(attempted_at + Duration::days(7) - Duration::seconds(1));
+    let output = purge_expired_records(&settings, repo.clone(), &clock)
+        .await
+        .unwrap();
+    assert_eq!(PurgeExpiredRecordsUseCaseOutput::default(), output);
+
+    let clock = FixedClock(attempted_at + Duration::days(7) + Duration::seconds(1));
+    repo.soft_delete(user_id, None).await.unwrap();
+    let output = purge_expired_records(&settings, repo.clone(), &clock)
+        .await
+        .unwrap();
+    assert_eq!(1, output.sign_in_histories);
+    assert_eq!(0, output.deleted_users);
+
+    let settings = RetentionSettings {
+        history_days: 0,
+        deleted_user_days: 1,
+        ..Default::default()
+    };
+    let clock = FixedClock(OffsetDateTime::now_utc() + Duration::days(2));
+    let output = purge_expired_records(&settings, repo.clone(), &clock)
+        .await
+        .unwrap();
+    assert_eq!(0, output.sign_in_histories);
+    assert_eq!(1, output.deleted_users);
+}
+
+/// パスワードを指定して、メモリユーザーリポジトリに登録したユーザーを再認証する。
+async fn reauthenticate_with(
+    settings: &AuthorizationSettings,
+    user_repo: &InMemoryUserRepository,
+    token_repo: &Arc<InMemoryTokenRepository>,
+    user_id: UserId,
+    access_token: &AccessToken,
+    password: &str,
+) -> UseCaseResult<ReauthenticateUseCaseOutput> {
+    let password = RawPassword::new(SecretString::from_str(password).unwrap()).unwrap();
+
+    reauthenticate(
+        &password_settings(),
+        settings,
+        user_repo.clone(),
+        token_repo.clone(),
+        &SystemClock,
+        user_id,
+        access_token,
+        &password,
+    )
+    .await
+}
+
+/// 再認証したアクセストークンで、再認証の有効期限まで本人であることを再確認できることを確認
+#[tokio::test]
+async fn reauthentication_is_valid_until_it_expires() {
+    let mut settings = authorization_settings();
+    let (user_repo, user_id) = user_repository().await;
+    let token_repo = Arc::new(InMemoryTokenRepository::new());
+    let access_token = AccessToken::new(SecretString::new(String::from("access")));
+    let confirm = |settings: AuthorizationSettings, access_token: &str| {
+        let user_repo = user_repo.clone();
+        let token_repo = &token_repo;
+        let reauthentication = Reauthentication::AccessToken(AccessToken::new(SecretString::new(
+            String::from(access_token),
+        )));
+        async move {
+            confirm_reauthentication(
+                &password_settings(),
+                &settings,
+                &user_repo,
+                token_repo,
+                &SystemClock,
+                user_id,
+                &reauthentication,
+            )
+            .await
+        }
+    };
+
+    // 再認証していないアクセストークンでは、本人であることを再確認できない
+    let e = confirm(settings.clone(), "access").await.unwrap_err();
+    assert_eq!(
+        UseCaseMessage::from(MessageKey::PasswordIsRequired),
+        e.message
+    );
+
+    let output = reauthenticate_with(
+        &settings,
+        &user_repo,
+        &token_repo,
+        user_id,
+        &access_token,
+        PASSWORD,
+    )
+    .await
+    .unwrap();
+    assert!(OffsetDateTime::now_utc() < output.expiration);
+    assert!(confirm(settings.clone(), "access").await.is_ok());
+    // 再認証は、有効期限まで何度でも使用できる
+    assert!(confirm(settings.clone(), "access").await.is_ok());
+    // 他のアクセストークンでは、本人であることを再確認できない
+    assert!(confirm(settings.clone(), "other").await.is_err());
+
+    // 有効期限が切れた再認証では、本人であることを再確認できない
+    settings.reauthentication_seconds = 0;
+    reauthenticate_with(
+        &settings,
+        &user_repo,
+        &token_repo,
+        user_id,
+        &access_token,
+        PASSWORD,
+    )
+    .await
+    .unwrap();
+    assert!(confirm(settings, "access").await.is_err());
+}
+
+/// 再認証の失敗は、サインインの失敗と一緒に数えて、アカウントをロックした場合はトークンを
+/// すべて無効にすることを確認
+#[tokio::test]
+async fn failed_reauthentication_counts_toward_lockout() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (user_repo, user_id) = user_repository().await;
+    let token_repo = Arc::new(InMemoryTokenRepository::new());
+    let (access, refresh) = (
+        AccessToken::new(SecretString::new(String::from("access"))),
+        RefreshToken::new(SecretString::new(String::from("refresh"))),
+    );
+    let tokens = TokenPairWithTtl {
+        access: &access,
+        access_ttl: 60,
+        refresh: &refresh,
+        refresh_ttl: 60,
+        device: None,
+    };
+    token_repo
+        .register_token_pair(user_id, tokens, UserPermissionCode::General)
+        .await
+        .unwrap();
+    let _ = sign_in_with(&settings, &user_repo, &metrics, WRONG_PASSWORD).await;
+
+    let e = reauthenticate_with(
+        &settings,
+        &user_repo,
+        &token_repo,
+        user_id,
+        &access,
+        WRONG_PASSWORD,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(UseCaseMessage::from(MessageKey::WrongPassword), e.message);
+    let credential = credential(&user_repo).await;
+    assert_eq!(2, credential.number_of_failures.value);
+    assert!(credential.active);
+    assert!(token_repo
+        .retrieve_token_content(access.secret())
+        .await
+        .unwrap()
+        .is_some());
+
+    let _ = reauthenticate_with(
+        &settings,
+        &user_repo,
+        &token_repo,
+        user_id,
+        &access,
+        WRONG_PASSWORD,
+    )
+    .await;
+    let locked = user_repo.credential_by_id(user_id).await.unwrap().unwrap();
+    assert_eq!(3, locked.number_of_failures.value);
+    assert!(!locked.active);
+    assert!(token_repo
+        .retrieve_token_content(access.secret())
+        .await
+        .unwrap()
+        .is_none());
+    assert!(token_repo
+        .retrieve_token_content(refresh.secret())
+        .await
+        .unwrap()
+        .is_none());
+
+    // ロックしたアカウントは、正しいパスワードでも再認証できない
+    let e = reauthenticate_with(
+        &settings,
+        &user_repo,
+        &token_repo,
+        user_id,
+        &access,
+        PASSWORD,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        UseCaseMessage::from(MessageKey::UserAccountIsLocked),
+        e.message
+    );
+    assert!(token_repo
+        .reauthenticated_user(&access)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// 再認証に成功すると、サインインに失敗した履歴を消去することを確認
+#[tokio::test]
+async fn successful_reauthentication_clears_sign_in_failures() {
+    let settings = authorization_settings();
+    let metrics = CountingSignInMetrics::default();
+    let (user_repo, user_id) = user_repository().await;
+    let token_repo = Arc::new(InMemoryTokenRepository::new());
+    let access_token = AccessToken::new(SecretString::new(String::from("access")));
+    for _ in 0..2 {
+        let _ = sign_in_with(&settings, &user_repo, &metrics, WRONG_PASSWORD).await;
+    }
+
+    reauthenticate_with(
+        &settings,
+        &user_repo,
+        &token_repo,
+        user_id,
+        &access_token,
+        PASSWORD,
+    )
+    .await
+    .unwrap();
+
+    let credential = credential(&user_repo).await;
+    assert_eq!(0, credential.number_of_failures.value);
+    assert!(credential.attempted_at.is_none());
+    assert_eq!(
+        Some(user_id),
+        token_repo
+            .reauthenticated_user(&access_token)
+            .await
+            .unwrap()
+    );
+}
+
+/// 内部サービスの資格情報を指定して、トークンを検査する。
+async fn introspect_with(
+    settings: &AuthorizationSettings,
+    token_repo: &Arc<InMemoryTokenRepository>,
+    now: OffsetDateTime,
+    credential: &str,
+    token: &SecretString,
+) -> UseCaseResult<Option<TokenIntrospection>> {
+    let credential = SecretString::new(String::from(credential));
+
+    introspect_token(
+        settings,
+        &JwtKeys::new(settings).unwrap(),
+        token_repo.clone(),
+        &FixedClock(now),
+        Some(&credential),
+        token,
+    )
+    .await
+}
+
+/// 有効なトークンの検査結果を返し、有効期限が切れたトークン、署名が不正なトークン及び無効にした
+/// トークンは有効でないと判断することを確認
+#[tokio::test]
+async fn introspect_token_reports_only_active_tokens() {
+    let settings = authorization_settings();
+    let token_repo = Arc::new(InMemoryTokenRepository::new());
+    let user_id = UserId::default();
+    let now = OffsetDateTime::now_utc();
+    let access_expiration = now + Duration::seconds(settings.access_token_seconds as i64);
+    let tokens = generate_token_pair(
+        user_id,
+        UserPermissionCode::General,
+        access_expiration,
+        now + Duration::seconds(settings.refresh_token_seconds as i64),
+        &JwtKeys::new(&settings).unwrap(),
+    )
+    .unwrap();
+    let token_with_ttls = TokenPairWithTtl {
+        access: &tokens.access,
+        access_ttl: settings.access_token_seconds,
+        refresh: &tokens.refresh,
+        refresh_ttl: settings.refresh_token_seconds,
+        device: None,
+    };
+    token_repo
+        .register_token_pair(user_id, token_with_ttls, UserPermissionCode::Admin)
+        .await
+        .unwrap();
+    let credential = "service-credential";
+
+    let introspection = introspect_with(
+        &settings,
+        &token_repo,
+        now,
+        credential,
+        tokens.access.secret(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(user_id, introspection.user_id);
+    assert_eq!(
+        access_expiration.unix_timestamp() as u64,
+        introspection.expiration
+    );
+    assert_eq!(TokenType::Access, introspection.token_type);
+    assert_eq!(
+        UserPermissionCode::Admin,
+        introspection.user_permission_code
+    );
+    let introspection = introspect_with(
+        &settings,
+        &token_repo,
+        now,
+        credential,
+        tokens.refresh.secret(),
+    )
+    .await
+    .unwrap()
+    .unwrap();
+    assert_eq!(TokenType::Refresh, introspection.token_type);
+    // 有効期限が切れたトークン
+    assert!(introspect_with(
+        &settings,
+        &token_repo,
+        access_expiration,
+        credential,
+        tokens.access.secret()
+    )
+    .await
+    .unwrap()
+    .is_none());
+    // 署名が不正なトークン
+    let invalid = SecretString::new(format!("{}x", tokens.access.expose()));
+    assert!(
+        introspect_with(&settings, &token_repo, now, credential, &invalid)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    // 無効にしたトークン
+    token_repo.revoke_user_tokens(user_id).await.unwrap();
+    assert!(introspect_with(
+        &settings,
+        &token_repo,
+        now,
+        credential,
+        tokens.access.secret()
+    )
+    .await
+    .unwrap()
+    .is_none());
+}
+
+/// 内部サービスの資格情報が一致しない場合、または資格情報を設定していない場合は、
+/// トークンを検査できないことを確認
+#[tokio::test]
+async fn introspect_token_rejects_invalid_service_credential() {
+    let mut settings = authorization_settings();
+    let token_repo = Arc::new(InMemoryTokenRepository::new());
+    let now = OffsetDateTime::now_utc();
+    let token = SecretString::new(String::from("token"));
+
+    let e = introspect_with(&settings, &token_repo, now, "wrong-credential", &token)
+        .await
+        .unwrap_err();
+    assert_eq!(UseCaseErrorKind::Unauthorized, e.kind);
+    let e = introspect_token(
+        &settings,
+        &JwtKeys::new(&settings).unwrap(),
+        token_repo.clone(),
+        &FixedClock(now),
+        None,
+        &token,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(UseCaseErrorKind::Unauthorized, e.kind);
+    settings.introspection_credential = None;
+    let e = introspect_with(&settings, &token_repo, now, "service-credential", &token)
+        .await
+        .unwrap_err();
+    assert_eq!(UseCaseErrorKind::Unauthorized, e.kind);
+}
+
+/// アカウントユースケースが、注入したリポジトリ及びメトリクスを使用することを確認
+#[tokio::test]
+async fn accounts_use_case_uses_injected_dependencies() {
+    let settings = authorization_settings();
+    let (repo, user_id) = user_repository().await;
+    let metrics = Arc::new(CountingSignInMetrics::default());
+    let accounts = AccountsUseCase::new(
+        password_settings(),
+        settings.clone(),
+        JwtKeys::new(&settings).unwrap(),
+        AccountsRepositories {
+            audit_log: Arc::new(repo.audit_log_repository()),
+            user: Arc::new(repo),
+            user_permission: Arc::new(InMemoryUserPermissionRepository::new()),
+            token: Arc::new(InMemoryTokenRepository::new()),
+            totp: Arc::new(EmptyTotpRepository),
+            device: Arc::new(NewDeviceRepository),
+        },
+        Arc::new(NoopEmailSender),
+        metrics.clone(),
+        Arc::new(SystemClock),
+    );
+
+    let Ok(SignInResult::SignedIn(output)) = accounts.sign_in(sign_in_input(PASSWORD)).await else {
+        panic!("user must sign in");
+    };
+    let sessions = accounts
+        .list_sessions(user_id, &output.access)
+        .await
+        .unwrap();
+
+    assert_eq!(1, metrics.succeeded.load(Ordering::SeqCst));
+    assert_eq!(1, sessions.len());
+    assert!(sessions[0].current);
+}
+
+/// ユーザーを登録すると、ユーザーを登録したイベントのみを発行することを確認
+#[tokio::test]
+async fn sign_up_publishes_user_registered_event() {
+    let events = Arc::new(RecordingEventPublisher::default());
+    let accounts = accounts_use_case(
+        InMemoryUserRepository::new(),
+        Arc::new(InMemoryTokenRepository::new()),
+        events.clone(),
+    );
+
+    let user = accounts.sign_up(sign_up_use_case_input()).await.unwrap();
+
+    assert_eq!(
+        vec![DomainEvent::UserRegistered {
+            user_id: user.id,
+            email: user.email
+        }],
+        events.events()
+    );
+}
+
+/// サインインに成功するとユーザーがサインインしたイベント、アカウントをロックするとアカウントを
+/// ロックしたイベントのみを発行して、サインインに失敗しただけではイベントを発行しないことを確認
+#[tokio::test]
+async fn sign_in_publishes_signed_in_and_account_locked_events() {
+    let (repo, user_id) = user_repository().await;
+    let events = Arc::new(RecordingEventPublisher::default());
+    let accounts = accounts_use_case(
+        repo,
+        Arc::new(InMemoryTokenRepository::new()),
+        events.clone(),
+    );
+
+    let result = accounts.sign_in(sign_in_input(PASSWORD)).await;
+    assert!(matches!(result, Ok(SignInResult::SignedIn(_))));
+    assert_eq!(vec![DomainEvent::UserSignedIn { user_id }], events.events());
+
+    let number_of_failures = authorization_settings().number_of_failures;
+    for _ in 0..number_of_failures - 1 {
+        assert!(accounts
+            .sign_in(sign_in_input(WRONG_PASSWORD))
+            .await
+            .is_err());
+    }
+    assert_eq!(1, events.events().len());
+    assert!(accounts
+        .sign_in(sign_in_input(WRONG_PASSWORD))
+        .await
+        .is_err());
+    assert_eq!(
+        vec![
+            DomainEvent::UserSignedIn { user_id },
+            DomainEvent::AccountLocked {
+                user_id,
+                email: EmailAddress::new(EMAIL).unwrap(),
+                attempt: SignInAttempt::default(),
+            },
+        ],
+        events.events()
+    );
+}
+
+/// 管理者がユーザーを登録するとユーザーを登録したイベント、ユーザーが要求されたパスワードを
+/// 変更するとパスワードを変更したイベントを発行することを確認
+#[tokio::test]
+async fn password_change_publishes_password_changed_event() {
+    let token_repo = Arc::new(InMemoryTokenRepository::new());
+    let events = Arc::new(RecordingEventPublisher::default());
+    let accounts = accounts_use_case(
+        InMemoryUserRepository::new(),
+        token_repo.clone(),
+        events.clone(),
+    );
+    let input = sign_up_use_case_input();
+    let output = accounts
+        .create_user_with_generated_password(CreateUserUseCaseInput {
+            actor: audit_actor(),
+            email: input.email,
+            active: true,
+            user_permission_code: input.user_permission_code,
+            family_name: input.family_name,
+            given_name: input.given_name,
+            postal_code: input.postal_code,
+            address: input.address,
+            fixed_phone_number: input.fixed_phone_number,
+            mobile_phone_number: input.mobile_phone_number,
+            remarks: input.remarks,
+        })
+        .await
+        .unwrap();
+    let user_id = output.user.id;
+    let token = generate_one_time_token();
+    token_repo
+        .register_password_change_token(user_id, &token, 60)
+        .await
+        .unwrap();
+
+    accounts
+        .change_required_password(ChangeRequiredPasswordUseCaseInput {
+            token,
+            new_password: RawPassword::new(SecretString::from_str(NEW_PASSWORD).unwrap()).unwrap(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        vec![
+            DomainEvent::UserRegistered {
+                user_id,
+                email: output.user.email,
+            },
+            DomainEvent::PasswordChanged { user_id },
+        ],
+        events.events()
+    );
+}
+
+/// 監査ログに記録する操作者を構築する。
+fn audit_actor() -> AuditActor {
+    AuditActor {
+        user_id: UserId::default(),
+        request_id: Some(String::from("request-id")),
+    }
+}
+
+/// 管理者がユーザー権限を変更した場合は、変更前後のユーザー権限コードを含む監査ログを記録することを確認
+#[tokio::test]
+async fn change_user_permission_records_audit_log() {
+    let (repo, user_id) = user_repository().await;
+    let audit_log_repo = repo.audit_log_repository();
+    let accounts = accounts_use_case(
+        repo,
+        Arc::new(InMemoryTokenRepository::new()),
+        Arc::new(RecordingEventPublisher::default()),
+    );
+    let actor = audit_actor();
+
+    accounts
+        .change_user_permission(&actor, user_id, UserPermissionCode::Admin)
+        .await
+        .unwrap();
+
+    let logs = audit_log_repo
+        .list(AuditLogFilter {
+            target_user_id: Some(user_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(1, logs.len());
+    assert_eq!(AuditAction::ChangeUserPermission, logs[0].action);
+    assert_eq!(actor.user_id, logs[0].actor_user_id);
+    assert_eq!(actor.request_id, logs[0].request_id);
+    assert_eq!(serde_json::json!({ "from": 2, "to": 1 }), logs[0].details);
+}
+
+/// 管理者がアカウントを削除した場合のみ、監査ログを記録することを確認
+#[tokio::test]
+async fn only_admin_delete_user_records_audit_log() {
+    let (repo, user_id) = user_repository().await;
+    let audit_log_repo = repo.audit_log_repository();
+    let accounts = accounts_use_case(
+        repo,
+        Arc::new(InMemoryTokenRepository::new()),
+        Arc::new(RecordingEventPublisher::default()),
+    );
+    let actor = audit_actor();
+
+    accounts
+        .delete_user(DeleteUserUseCaseInput {
+            user_id,
+            requester: DeleteUserRequester::Admin(actor.clone()),
+        })
+        .await
+        .unwrap();
+
+    let logs = audit_log_repo
+        .list(AuditLogFilter {
+            actor_user_id: Some(actor.user_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(1, logs.len());
+    assert_eq!(AuditAction::DeleteUser, logs[0].action);
+    assert_eq!(Some(user_id), logs[0].target_user_id);
+}
+
+/// 管理者がパスワードを生成してユーザーを登録した場合は、登録したユーザーを対象とした監査ログを
+/// 記録して、ユーザーを登録したイベントを発行することを確認
+#[tokio::test]
+async fn create_user_with_generated_password_records_audit_log() {
+    let repo = InMemoryUserRepository::new();
+    let audit_log_repo = repo.audit_log_repository();
+    let events = Arc::new(RecordingEventPublisher::default());
+    let accounts = accounts_use_case(
+        repo,
+        Arc::new(InMemoryTokenRepository::new()),
+        events.clone(),
+    );
+    let actor = audit_actor();
+    let input = sign_up_use_case_input();
+
+    let output = accounts
+        .create_user_with_generated_password(CreateUserUseCaseInput {
+            actor: actor.clone(),
+            email: input.email,
+            active: false,
+            user_permission_code: input.user_permission_code,
+            family_name: input.family_name,
+            given_name: input.given_name,
+            postal_code: input.postal_code,
+            address: input.address,
+            fixed_phone_number: input.fixed_phone_number,
+            mobile_phone_number: input.mobile_phone_number,
+            remarks: input.remarks,
+        })
+        .await
+        .unwrap();
+
+    assert!(!output.user.active);
+    let logs = audit_log_repo
+        .list(AuditLogFilter {
+            actor_user_id: Some(actor.user_id),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(1, logs.len());
+    assert_eq!(AuditAction::CreateUser, logs[0].action);
+    assert_eq!(Some(output.user.id), logs[0].target_user_id);
+    assert_eq!(actor.request_id, logs[0].request_id);
+    assert_eq!(
+        vec![DomainEvent::UserRegistered {
+            user_id: output.user.id,
+            email: output.user.email.clone(),
+        }],
+        events.events()
+    );
+}
+
+/// 取得するユーザーの最大数が範囲外の場合は、ユーザーのリストを取得するユースケースが検証エラーを
+/// 返すことを確認
+#[tokio::test]
+async fn user_list_limit_out_of_range_is_validation_error() {
+    let accounts = accounts_use_case(
+        InMemoryUserRepository::new(),
+        Arc::new(InMemoryTokenRepository::new()),
+        Arc::new(RecordingEventPublisher::default()),
+    );
+    let pagination = Pagination::new(Some(MAX_USER_LIST_LIMIT + 1), 0);
+
+    let filter = UserListFilter {
+        pagination,
+        ..Default::default()
+    };
+    let err = accounts.list_users(None, filter).await.unwrap_err();
+    assert_eq!(UseCaseErrorKind::Validation, err.kind);
+    let Err(err) = accounts.export_users(filter) else {
+        panic!("export users must be rejected");
+    };
+    assert_eq!(UseCaseErrorKind::Validation, err.kind);
+    let err = accounts
+        .list_locked_users(Pagination::new(Some(0), 0))
+        .await
+        .unwrap_err();
+    assert_eq!(UseCaseErrorKind::Validation, err.kind);
+
+    let filter = UserListFilter {
+        pagination: Pagination::new(Some(MAX_USER_LIST_LIMIT), 0),
+        ..Default::default()
+    };
+    assert!(accounts.list_users(None, filter).await.unwrap().is_empty());
+}
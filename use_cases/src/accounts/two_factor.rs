@@ -0,0 +1,310 @@
+use secrecy::SecretString;
+
+use domain::clock::Clock;
+use domain::models::user::UserId;
+use domain::repositories::token::TokenRepository;
+use domain::repositories::totp::{TotpRepository, UserTotpSecret};
+use domain::repositories::user::{SignInAttempt, UserRepository};
+
+use super::sign_in::record_sign_in_failure;
+use crate::messages::MessageKey;
+use crate::settings::AuthorizationSettings;
+use crate::totp::{
+    decrypt_totp_secret, encode_totp_secret, encrypt_totp_secret, generate_totp_secret, totp_uri,
+    verify_totp_code,
+};
+use crate::{
+    UseCaseError, UseCaseErrorKind, UseCaseResult, ERR_INVALID_TOTP_CODE,
+    ERR_TWO_FACTOR_IS_ALREADY_ENABLED, ERR_TWO_FACTOR_IS_NOT_SET_UP,
+};
+
+/// TOTPコードを検証して、TOTPコードを使用したタイムステップを記録する。
+///
+/// 同じTOTPコードを再利用できないように、最後に使用したタイムステップ以前のタイムステップで
+/// 一致したTOTPコードは無効とする。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `totp_repository` - TOTPリポジトリ
+/// * `clock` - 時計
+/// * `secret` - ユーザーのTOTPの秘密鍵
+/// * `code` - 検証するTOTPコード
+///
+/// # 戻り値
+///
+/// TOTPコードが有効な場合は`true`
+pub(super) async fn verify_totp(
+    authorization_settings: &AuthorizationSettings,
+    totp_repo: &impl TotpRepository,
+    clock: &impl Clock,
+    secret: &UserTotpSecret,
+    code: &str,
+) -> UseCaseResult<bool> {
+    let secret_key = decrypt_totp_secret(
+        &authorization_settings.totp_secret_key,
+        &secret.encrypted_secret,
+    )?;
+    let Some(step) = verify_totp_code(&secret_key, code, clock) else {
+        return Ok(false);
+    };
+
+    totp_repo
+        .record_used_totp_step(secret.user_id, step)
+        .await
+        .map_err(UseCaseError::from)
+}
+
+/// TOTPコードが無効であることを示すユースケースエラーを構築する。
+///
+/// # 引数
+///
+/// * `kind` - ユースケースエラー分類
+pub(super) fn invalid_totp_code_error(kind: UseCaseErrorKind) -> UseCaseError {
+    UseCaseError::new(kind, ERR_INVALID_TOTP_CODE, MessageKey::InvalidTotpCode)
+}
+
+/// サインインしているユーザーのTOTPコードを検証する。
+///
+/// 2要素認証を有効または無効にするときに、アクセストークンを盗んだ者がTOTPコードを推測し続け
+/// られないように、アカウントがロックされている場合はTOTPコードを検証しない。また、TOTPコードが
+/// 間違っている場合は、サインインと同様にサインイン失敗回数を記録して、その結果アカウントを
+/// ロックした場合は、ユーザーに発行したトークンをすべて無効にする。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `totp_repository` - TOTPリポジトリ
+/// * `clock` - 時計
+/// * `secret` - ユーザーのTOTPの秘密鍵
+/// * `code` - 検証するTOTPコード
+async fn verify_totp_with_lockout(
+    authorization_settings: &AuthorizationSettings,
+    user_repo: &impl UserRepository,
+    token_repo: &impl TokenRepository,
+    totp_repo: &impl TotpRepository,
+    clock: &impl Clock,
+    secret: &UserTotpSecret,
+    code: &str,
+) -> UseCaseResult<()> {
+    let credential = user_repo
+        .credential_by_id(secret.user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::unauthorized(MessageKey::Forbidden))?;
+    if !credential.active {
+        return Err(UseCaseError::unauthorized(MessageKey::UserAccountIsLocked));
+    }
+    if verify_totp(authorization_settings, totp_repo, clock, secret, code).await? {
+        return Ok(());
+    }
+    // TOTPコードの検証は、アクセス元を受け取らないため記録しない
+    let attempt = SignInAttempt::default();
+    if record_sign_in_failure(
+        authorization_settings,
+        user_repo,
+        &credential,
+        &attempt,
+        clock.now_utc(),
+    )
+    .await?
+    {
+        token_repo
+            .revoke_user_tokens(credential.user_id)
+            .await
+            .map_err(UseCaseError::from)?;
+    }
+
+    Err(invalid_totp_code_error(UseCaseErrorKind::Validation))
+}
+
+/// 2要素認証を準備する。
+///
+/// TOTPの秘密鍵を生成して、暗号化して登録する。2要素認証は、認証アプリが生成したTOTPコードで
+/// 有効にするまで有効にならない。すでに準備した秘密鍵がある場合は、秘密鍵を置き換える。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `totp_repository` - TOTPリポジトリ
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// 認証アプリに登録する`otpauth`URIとTOTPの秘密鍵
+#[tracing::instrument(
+    name = "set up totp use case",
+    skip(authorization_settings, user_repo, totp_repo),
+    fields(user.id = %user_id)
+)]
+pub async fn set_up_totp(
+    authorization_settings: &AuthorizationSettings,
+    user_repo: impl UserRepository,
+    totp_repo: impl TotpRepository,
+    user_id: UserId,
+) -> UseCaseResult<TotpSetupUseCaseOutput> {
+    // 2要素認証をすでに有効にしている場合は、秘密鍵を置き換えない
+    if totp_repo
+        .totp_secret(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .is_some_and(|secret| secret.is_enabled())
+    {
+        return Err(two_factor_is_already_enabled_error());
+    }
+    let user = user_repo
+        .by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::unauthorized(MessageKey::Forbidden))?;
+
+    // TOTPの秘密鍵を生成して、暗号化して登録
+    let secret = generate_totp_secret();
+    let encrypted_secret = encrypt_totp_secret(&authorization_settings.totp_secret_key, &secret)?;
+    totp_repo
+        .register_totp_secret(user_id, encrypted_secret)
+        .await
+        .map_err(UseCaseError::from)?;
+
+    Ok(TotpSetupUseCaseOutput {
+        uri: totp_uri(&user.email.value, &secret),
+        secret: SecretString::new(encode_totp_secret(&secret)),
+    })
+}
+
+/// 2要素認証準備ユースケース出力
+pub struct TotpSetupUseCaseOutput {
+    /// 認証アプリに登録する`otpauth`URI
+    pub uri: String,
+    /// 認証アプリに手動で入力するBase32形式のTOTPの秘密鍵
+    pub secret: SecretString,
+}
+
+/// 2要素認証を有効にする。
+///
+/// TOTPコードが間違っている場合は、サインインと同様にサインイン失敗回数を記録する。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `totp_repository` - TOTPリポジトリ
+/// * `clock` - 時計
+/// * `user_id` - ユーザーID
+/// * `code` - 認証アプリが生成したTOTPコード
+#[tracing::instrument(
+    name = "enable totp use case",
+    skip(authorization_settings, user_repo, token_repo, totp_repo, clock, code),
+    fields(user.id = %user_id)
+)]
+pub async fn enable_totp(
+    authorization_settings: &AuthorizationSettings,
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    totp_repo: impl TotpRepository,
+    clock: &impl Clock,
+    user_id: UserId,
+    code: String,
+) -> UseCaseResult<()> {
+    let secret = totp_repo
+        .totp_secret(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(two_factor_is_not_set_up_error)?;
+    if secret.is_enabled() {
+        return Err(two_factor_is_already_enabled_error());
+    }
+    verify_totp_with_lockout(
+        authorization_settings,
+        &user_repo,
+        &token_repo,
+        &totp_repo,
+        clock,
+        &secret,
+        &code,
+    )
+    .await?;
+    if !totp_repo
+        .enable_totp(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+    {
+        return Err(two_factor_is_not_set_up_error());
+    }
+
+    Ok(())
+}
+
+/// 2要素認証を無効にする。
+///
+/// 2要素認証を無効にするため、認証アプリが生成したTOTPコードを要求する。TOTPコードが間違っている
+/// 場合は、サインインと同様にサインイン失敗回数を記録する。
+///
+/// # 引数
+///
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `totp_repository` - TOTPリポジトリ
+/// * `clock` - 時計
+/// * `user_id` - ユーザーID
+/// * `code` - 認証アプリが生成したTOTPコード
+#[tracing::instrument(
+    name = "disable totp use case",
+    skip(authorization_settings, user_repo, token_repo, totp_repo, clock, code),
+    fields(user.id = %user_id)
+)]
+pub async fn disable_totp(
+    authorization_settings: &AuthorizationSettings,
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    totp_repo: impl TotpRepository,
+    clock: &impl Clock,
+    user_id: UserId,
+    code: String,
+) -> UseCaseResult<()> {
+    let secret = totp_repo
+        .totp_secret(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .filter(|secret| secret.is_enabled())
+        .ok_or_else(two_factor_is_not_set_up_error)?;
+    verify_totp_with_lockout(
+        authorization_settings,
+        &user_repo,
+        &token_repo,
+        &totp_repo,
+        clock,
+        &secret,
+        &code,
+    )
+    .await?;
+    totp_repo
+        .delete_totp_secret(user_id)
+        .await
+        .map_err(UseCaseError::from)?;
+
+    Ok(())
+}
+
+/// 2要素認証がすでに有効であることを示すユースケースエラーを構築する。
+fn two_factor_is_already_enabled_error() -> UseCaseError {
+    UseCaseError::new(
+        UseCaseErrorKind::DomainRule,
+        ERR_TWO_FACTOR_IS_ALREADY_ENABLED,
+        MessageKey::TwoFactorIsAlreadyEnabled,
+    )
+}
+
+/// 2要素認証の準備がされていないことを示すユースケースエラーを構築する。
+fn two_factor_is_not_set_up_error() -> UseCaseError {
+    UseCaseError::new(
+        UseCaseErrorKind::DomainRule,
+        ERR_TWO_FACTOR_IS_NOT_SET_UP,
+        MessageKey::TwoFactorIsNotSetUp,
+    )
+}
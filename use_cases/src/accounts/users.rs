@@ -0,0 +1,577 @@
+use secrecy::SecretString;
+use time::OffsetDateTime;
+
+use domain::clock::Clock;
+use domain::events::{DomainEvent, EventPublisher};
+use domain::models::audit_log::{AuditAction, AuditActor};
+use domain::models::primitives::*;
+use domain::models::query::Pagination;
+use domain::models::user::{User, UserId, UserPermissionCode};
+use domain::repositories::token::{AccessToken, TokenRepository};
+use domain::repositories::user::{
+    GuardedChange, LockedUser, UpdateProfileInputBuilder, UserListFilter, UserRepository,
+    UserStream,
+};
+
+use super::reauthentication::{confirm_reauthentication, Reauthentication};
+use crate::messages::MessageKey;
+use crate::passwords::{generate_phc_string, verify_password};
+use crate::settings::{AuthorizationSettings, PasswordSettings};
+use crate::{
+    UseCaseError, UseCaseErrorKind, UseCaseResult, ERR_INVALID_PASSWORD_CHANGE_TOKEN,
+    ERR_LAST_ADMIN_CAN_NOT_BE_DELETED, ERR_LAST_ADMIN_CAN_NOT_BE_DEMOTED,
+    ERR_NEW_PASSWORD_MUST_DIFFER, ERR_USER_WAS_MODIFIED,
+};
+
+/// 1回で取得できるユーザーの最大数
+pub const MAX_USER_LIST_LIMIT: u32 = 1000;
+
+/// ユーザーのリストを取得する。
+///
+/// 取得するユーザーの最大数が、1以上`MAX_USER_LIST_LIMIT`以下でない場合は検証エラーを返す。
+/// 検索文字列の前後の空白を取り除いた結果が空の場合は、検索文字列を指定しなかったものとする。
+///
+/// # 引数
+///
+/// * `repository` - ユーザーリポジトリ
+/// * `search` - 苗字、名前またはEメールアドレスの部分一致で検索する文字列
+/// * `filter` - ユーザーリストの絞り込み条件
+///
+/// # 戻り値
+///
+/// * ユーザーを格納したベクタ
+#[tracing::instrument(name = "list users use case", skip(repository))]
+pub async fn list_users(
+    repository: impl UserRepository,
+    search: Option<&str>,
+    filter: UserListFilter,
+) -> UseCaseResult<Vec<User>> {
+    filter.pagination.validate(MAX_USER_LIST_LIMIT)?;
+    let result = match search.map(str::trim).filter(|search| !search.is_empty()) {
+        Some(search) => repository.search(search, filter).await,
+        None => repository.list(filter).await,
+    };
+
+    result.map_err(|e| UseCaseError::repository(e.to_string()))
+}
+
+/// アカウントをロックしたユーザーのリストを取得する。
+///
+/// 取得するユーザーの最大数が、1以上`MAX_USER_LIST_LIMIT`以下でない場合は検証エラーを返す。
+///
+/// # 引数
+///
+/// * `repository` - ユーザーリポジトリ
+/// * `pagination` - アカウントをロックした日時の順で取得するページ
+///
+/// # 戻り値
+///
+/// * アカウントをロックしたユーザーを格納したベクタ
+#[tracing::instrument(name = "list locked users use case", skip(repository))]
+pub async fn list_locked_users(
+    repository: impl UserRepository,
+    pagination: Pagination,
+) -> UseCaseResult<Vec<LockedUser>> {
+    let pagination = pagination.validate(MAX_USER_LIST_LIMIT)?;
+    repository
+        .list_locked(pagination)
+        .await
+        .map_err(|e| UseCaseError::repository(e.to_string()))
+}
+
+/// ユーザーをエクスポートするため、ユーザーを1人ずつ取得するストリームを返す。
+///
+/// 取得するユーザーの最大数が、1以上`MAX_USER_LIST_LIMIT`以下でない場合は検証エラーを返す。
+///
+/// # 引数
+///
+/// * `repository` - ユーザーリポジトリ
+/// * `filter` - ユーザーリストの絞り込み条件
+///
+/// # 戻り値
+///
+/// * ユーザーを1人ずつ返すストリーム
+#[tracing::instrument(name = "export users use case", skip(repository))]
+pub fn export_users(
+    repository: impl UserRepository,
+    filter: UserListFilter,
+) -> UseCaseResult<UserStream> {
+    filter.pagination.validate(MAX_USER_LIST_LIMIT)?;
+
+    Ok(repository.stream(filter))
+}
+
+/// ユーザーを取得する。
+///
+/// # 引数
+///
+/// * `repository` - ユーザーリポジトリ
+/// * `user_id` - ユーザーID
+///
+/// # 戻り値
+///
+/// ユーザー
+#[tracing::instrument(name = "user detail use case", skip(repository))]
+pub async fn user_detail(repository: impl UserRepository, user_id: UserId) -> UseCaseResult<User> {
+    repository
+        .by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::not_found(MessageKey::UserNotFound))
+}
+
+/// ユーザーのアカウントの状況を取得する。
+///
+/// 最後にサインインした日時、サインインに失敗した履歴、アカウントのロック状態、及び有効なトークンを
+/// 持つセッションの数をまとめて返す。
+///
+/// # 引数
+///
+/// * `user_repo` - ユーザーリポジトリ
+/// * `token_repo` - トークンリポジトリ
+/// * `user_id` - アカウントの状況を取得するユーザーのユーザーID
+/// * `access_token` - リクエストで送信されたアクセストークン
+///
+/// # 戻り値
+///
+/// ユーザーのアカウントの状況
+#[tracing::instrument(
+    name = "account activity use case",
+    skip(user_repo, token_repo, access_token),
+    fields(user.id = %user_id)
+)]
+pub async fn account_activity(
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    user_id: UserId,
+    access_token: &AccessToken,
+) -> UseCaseResult<AccountActivity> {
+    let not_found_error = UseCaseError::not_found(MessageKey::UserNotFound);
+    let user = user_repo
+        .by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| not_found_error.clone())?;
+    // ロックした日時は`User`に含まれないため、クレデンシャルから取得
+    let credential = user_repo
+        .credential_by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or(not_found_error)?;
+    // 有効なトークンがないセッションは数えない
+    let active_sessions = token_repo
+        .user_sessions(user_id, access_token)
+        .await
+        .map_err(UseCaseError::from)?
+        .iter()
+        .filter(|session| !session.tokens.is_empty())
+        .count();
+
+    Ok(AccountActivity {
+        user_id,
+        last_sign_in_at: user.last_sign_in_at,
+        sign_in_attempted_at: user.sign_in_attempted_at,
+        number_of_sign_in_failures: user.number_of_sign_in_failures,
+        active: user.active,
+        locked_at: credential.locked_at,
+        active_sessions,
+    })
+}
+
+/// ユーザーのアカウントの状況
+#[derive(Debug, Clone)]
+pub struct AccountActivity {
+    /// ユーザーID
+    pub user_id: UserId,
+    /// 最後にサインインした日時
+    pub last_sign_in_at: Option<OffsetDateTime>,
+    /// 最初にサインインに失敗した日時
+    pub sign_in_attempted_at: Option<OffsetDateTime>,
+    /// 最初にサインインに失敗した日時から、サインインに失敗した回数
+    pub number_of_sign_in_failures: NumberOfSignInFailures,
+    /// アクティブフラグ
+    pub active: bool,
+    /// アカウントをロックした日時
+    pub locked_at: Option<OffsetDateTime>,
+    /// 有効なトークンを持つセッションの数
+    pub active_sessions: usize,
+}
+
+/// ユーザーのアカウントを削除する。
+///
+/// ユーザーのレコードは残して、個人情報を消去する。ユーザー自身がアカウントを削除する場合は、
+/// 本人であることを再確認する。管理者がいなくならないように、最後の管理者のアカウントは削除できない。
+/// 管理者がアカウントを削除する場合は、アカウントの削除と同じトランザクションで監査ログを記録する。
+/// アカウントを削除した後、ユーザーに発行したアクセストークンとリフレッシュトークンをすべて無効にする。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `authorization_settings` - 認証設定
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `clock` - 時計
+/// * `input` - アカウント削除ユースケース入力
+#[tracing::instrument(
+    name = "delete user use case",
+    skip(password_settings, authorization_settings, user_repo, token_repo, clock, input),
+    fields(user.id = %input.user_id)
+)]
+pub async fn delete_user(
+    password_settings: &PasswordSettings,
+    authorization_settings: &AuthorizationSettings,
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    clock: &impl Clock,
+    input: DeleteUserUseCaseInput,
+) -> UseCaseResult<()> {
+    if let DeleteUserRequester::Owner(reauthentication) = &input.requester {
+        confirm_reauthentication(
+            password_settings,
+            authorization_settings,
+            &user_repo,
+            &token_repo,
+            clock,
+            input.user_id,
+            reauthentication,
+        )
+        .await?;
+    }
+    let user = user_repo
+        .by_id(input.user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::not_found(MessageKey::UserNotFound))?;
+
+    let audit = match &input.requester {
+        DeleteUserRequester::Owner(_) => None,
+        DeleteUserRequester::Admin(actor) => Some(actor.entry(
+            AuditAction::DeleteUser,
+            Some(input.user_id),
+            serde_json::json!({ "userPermissionCode": user.user_permission.code as i16 }),
+        )),
+    };
+    match user_repo
+        .soft_delete(input.user_id, audit)
+        .await
+        .map_err(UseCaseError::from)?
+    {
+        GuardedChange::Changed => {}
+        GuardedChange::NotFound => {
+            return Err(UseCaseError::not_found(MessageKey::UserNotFound));
+        }
+        // 最後の管理者のアカウントを削除しない
+        GuardedChange::LastAdmin => {
+            return Err(UseCaseError::new(
+                UseCaseErrorKind::DomainRule,
+                ERR_LAST_ADMIN_CAN_NOT_BE_DELETED,
+                MessageKey::LastAdminCanNotBeDeleted,
+            ));
+        }
+    }
+    token_repo
+        .revoke_user_tokens(input.user_id)
+        .await
+        .map_err(UseCaseError::from)?;
+
+    Ok(())
+}
+
+/// アカウントを削除するユーザー
+pub enum DeleteUserRequester {
+    /// アカウントを所有するユーザー自身
+    ///
+    /// アカウントを削除する前に、本人であることを再確認する。
+    Owner(Reauthentication),
+    /// 管理者
+    ///
+    /// 監査ログに、アカウントを削除した管理者として記録する。
+    Admin(AuditActor),
+}
+
+/// アカウント削除ユースケース入力
+pub struct DeleteUserUseCaseInput {
+    /// アカウントを削除するユーザーのユーザーID
+    pub user_id: UserId,
+    /// アカウントを削除するユーザー
+    pub requester: DeleteUserRequester,
+}
+
+/// ユーザーのユーザー権限を変更する。
+///
+/// 管理者がいなくならないように、最後の管理者のユーザー権限は変更できない。
+/// ユーザー権限を変更した場合は、同じトランザクションで、変更前後のユーザー権限コードを含む監査ログを
+/// 記録する。ユーザー権限を変更した後、ユーザーに発行したアクセストークンとリフレッシュトークンをすべて無効にする。
+/// Redisに保存したトークンの内容には、トークンを発行したときのユーザー権限が含まれているためである。
+///
+/// # 引数
+///
+/// * `user_repository` - ユーザーリポジトリ
+/// * `token_repository` - トークンリポジトリ
+/// * `actor` - ユーザー権限を変更する管理者
+/// * `user_id` - ユーザー権限を変更するユーザーのユーザーID
+/// * `user_permission_code` - 変更後のユーザー権限コード
+///
+/// # 戻り値
+///
+/// ユーザー権限を変更したユーザー
+#[tracing::instrument(
+    name = "change user permission use case",
+    skip(user_repo, token_repo, actor),
+    fields(user.id = %user_id, actor.id = %actor.user_id)
+)]
+pub async fn change_user_permission(
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    actor: &AuditActor,
+    user_id: UserId,
+    user_permission_code: UserPermissionCode,
+) -> UseCaseResult<User> {
+    let user = user_repo
+        .by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::not_found(MessageKey::UserNotFound))?;
+    if user.user_permission.code == user_permission_code {
+        return Ok(user);
+    }
+
+    let audit = actor.entry(
+        AuditAction::ChangeUserPermission,
+        Some(user_id),
+        serde_json::json!({
+            "from": user.user_permission.code as i16,
+            "to": user_permission_code as i16,
+        }),
+    );
+    match user_repo
+        .update_permission(user_id, user_permission_code, audit)
+        .await
+        .map_err(UseCaseError::from)?
+    {
+        GuardedChange::Changed => {}
+        GuardedChange::NotFound => {
+            return Err(UseCaseError::not_found(MessageKey::UserNotFound));
+        }
+        // 最後の管理者を管理者以外に変更しない
+        GuardedChange::LastAdmin => {
+            return Err(UseCaseError::new(
+                UseCaseErrorKind::DomainRule,
+                ERR_LAST_ADMIN_CAN_NOT_BE_DEMOTED,
+                MessageKey::LastAdminCanNotBeDemoted,
+            ));
+        }
+    }
+    // 変更前のユーザー権限を含むトークンを無効化
+    token_repo
+        .revoke_user_tokens(user_id)
+        .await
+        .map_err(UseCaseError::from)?;
+
+    user_repo
+        .by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::not_found(MessageKey::UserNotFound))
+}
+
+/// ユーザーのプロフィールを更新する。
+///
+/// 他の操作による更新を上書きしないように、クライアントが取得したユーザーのバージョンと、
+/// 登録されているユーザーのバージョンが一致する場合のみ更新する。
+///
+/// # 引数
+///
+/// * `user_repository` - ユーザーリポジトリ
+/// * `input` - プロフィール更新ユースケース入力
+///
+/// # 戻り値
+///
+/// プロフィールを更新したユーザー
+#[tracing::instrument(
+    name = "update profile use case",
+    skip(user_repo, input),
+    fields(user.id = %input.user_id)
+)]
+pub async fn update_profile(
+    user_repo: impl UserRepository,
+    input: UpdateProfileUseCaseInput,
+) -> UseCaseResult<User> {
+    let user = user_repo
+        .by_id(input.user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| UseCaseError::not_found(MessageKey::UserNotFound))?;
+    if user.version != input.version {
+        return Err(user_was_modified_error());
+    }
+
+    // 指定されていない項目は、登録されているプロフィールを維持
+    let profile = UpdateProfileInputBuilder::new()
+        .family_name(input.family_name.unwrap_or(user.family_name))
+        .given_name(input.given_name.unwrap_or(user.given_name))
+        .postal_code(input.postal_code.unwrap_or(user.postal_code))
+        .address(input.address.unwrap_or(user.address))
+        .fixed_phone_number(input.fixed_phone_number.unwrap_or(user.fixed_phone_number))
+        .mobile_phone_number(
+            input
+                .mobile_phone_number
+                .unwrap_or(user.mobile_phone_number),
+        )
+        .remarks(input.remarks.unwrap_or(user.remarks))
+        .build()
+        .map_err(|e| UseCaseError::domain_rule(e.to_string()))?;
+
+    user_repo
+        .update_profile(input.user_id, input.version, profile)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(user_was_modified_error)
+}
+
+/// ユーザーのパスワードを変更したことを記録する。
+///
+/// パスワードの変更、再設定及び再ハッシュなど、ユーザーのパスワードを変更する処理は、この関数を介して
+/// パスワードを保存して、パスワードの保存と同じトランザクションで監査ログを記録する。
+///
+/// # 引数
+///
+/// * `user_repo` - ユーザーリポジトリ
+/// * `actor` - パスワードを変更する操作者
+/// * `user_id` - ユーザーID
+/// * `password` - 変更後のパスワードをハッシュ化したPHC文字列
+#[tracing::instrument(
+    name = "update password use case",
+    skip(user_repo, actor, password),
+    fields(actor.id = %actor.user_id)
+)]
+pub async fn update_password(
+    user_repo: &impl UserRepository,
+    actor: &AuditActor,
+    user_id: UserId,
+    password: PhcPassword,
+) -> UseCaseResult<()> {
+    let audit = actor.entry(
+        AuditAction::UpdatePassword,
+        Some(user_id),
+        serde_json::json!({}),
+    );
+    user_repo
+        .update_password(user_id, password, audit)
+        .await
+        .map_err(UseCaseError::from)?;
+    tracing::info!(user.id = %user_id, "user password was updated");
+
+    Ok(())
+}
+
+/// パスワードの変更を要求されているユーザーが、サインインで取得したトークンでパスワードを変更する。
+///
+/// トークンは1度しか使用できないため、変更後のパスワードが現在のパスワードと同じ場合も、ユーザーは
+/// もう一度サインインしてトークンを取得する必要がある。
+/// パスワードを変更すると、パスワードの変更を要求していることを消去して、ユーザーがパスワードを
+/// 変更したイベントを発行する。
+///
+/// # 引数
+///
+/// * `password_settings` - パスワード設定
+/// * `user_repo` - ユーザーリポジトリ
+/// * `token_repo` - トークンリポジトリ
+/// * `events` - イベント発行者
+/// * `input` - 要求されたパスワード変更ユースケース入力
+#[tracing::instrument(
+    name = "change required password use case",
+    skip(password_settings, user_repo, token_repo, events, input)
+)]
+pub async fn change_required_password(
+    password_settings: &PasswordSettings,
+    user_repo: impl UserRepository,
+    token_repo: impl TokenRepository,
+    events: &impl EventPublisher,
+    input: ChangeRequiredPasswordUseCaseInput,
+) -> UseCaseResult<()> {
+    let invalid_token_error = UseCaseError::new(
+        UseCaseErrorKind::Unauthorized,
+        ERR_INVALID_PASSWORD_CHANGE_TOKEN,
+        MessageKey::InvalidPasswordChangeToken,
+    );
+    // トークンを消費して、パスワードを変更するユーザーを取得
+    let user_id = token_repo
+        .consume_password_change_token(&input.token)
+        .await
+        .map_err(UseCaseError::from)?
+        .ok_or_else(|| invalid_token_error.clone())?;
+    // トークンを発行した後に、ユーザーが削除された場合、またはパスワードを変更した場合は、
+    // トークンが無効であるとみなす
+    let credential = user_repo
+        .credential_by_id(user_id)
+        .await
+        .map_err(UseCaseError::from)?
+        .filter(|credential| credential.password_change_required)
+        .ok_or(invalid_token_error)?;
+    if verify_password(
+        &input.new_password,
+        &password_settings.pepper,
+        &credential.password,
+    )? {
+        return Err(UseCaseError::new(
+            UseCaseErrorKind::Validation,
+            ERR_NEW_PASSWORD_MUST_DIFFER,
+            MessageKey::NewPasswordMustDiffer,
+        ));
+    }
+
+    let password = generate_phc_string(&input.new_password, password_settings)?;
+    // ユーザー自身がパスワードを変更したことを記録
+    let actor = AuditActor {
+        user_id,
+        request_id: None,
+    };
+    update_password(&user_repo, &actor, user_id, password).await?;
+    events
+        .publish(DomainEvent::PasswordChanged { user_id })
+        .await;
+
+    Ok(())
+}
+
+/// 要求されたパスワード変更ユースケース入力
+pub struct ChangeRequiredPasswordUseCaseInput {
+    /// サインインで取得したパスワードを変更するためのトークン
+    pub token: SecretString,
+    /// 変更後のパスワード
+    pub new_password: RawPassword,
+}
+
+/// ユーザーが他の操作で更新されたことを示すユースケースエラーを構築する。
+fn user_was_modified_error() -> UseCaseError {
+    UseCaseError::new(
+        UseCaseErrorKind::Conflict,
+        ERR_USER_WAS_MODIFIED,
+        MessageKey::UserWasModified,
+    )
+}
+
+/// プロフィール更新ユースケース入力
+///
+/// `None`の項目は更新しない。
+pub struct UpdateProfileUseCaseInput {
+    /// プロフィールを更新するユーザーのユーザーID
+    pub user_id: UserId,
+    /// クライアントが取得したユーザーのバージョン
+    pub version: i32,
+    /// 苗字
+    pub family_name: Option<FamilyName>,
+    /// 名前
+    pub given_name: Option<GivenName>,
+    /// 郵便番号
+    pub postal_code: Option<PostalCode>,
+    /// 住所
+    pub address: Option<Address>,
+    /// 固定電話番号
+    pub fixed_phone_number: Option<OptionalFixedPhoneNumber>,
+    /// 携帯電話番号
+    pub mobile_phone_number: Option<OptionalMobilePhoneNumber>,
+    /// 備考
+    pub remarks: Option<OptionalRemarks>,
+}
@@ -1,13 +1,17 @@
 pub mod accounts;
 pub mod jwt;
+pub mod messages;
+pub mod metrics;
+pub mod notifications;
 pub mod passwords;
 pub mod settings;
-
-use std::borrow::Cow;
+pub mod totp;
 
 use domain::DomainError;
 use enum_display::EnumDisplay;
 
+use crate::messages::{MessageKey, UseCaseMessage};
+
 pub type UseCaseResult<T> = Result<T, UseCaseError>;
 
 /// ユースケースエラーコード
@@ -20,6 +24,8 @@ pub enum UseCaseErrorCode {
     Repository = 3,
     NotFound = 4,
     Unauthorized = 5,
+    Conflict = 6,
+    Unavailable = 7,
 }
 
 /// ユースケースエラー分類
@@ -43,6 +49,14 @@ pub enum UseCaseErrorKind {
 
     /// 不許可／未認証
     Unauthorized,
+
+    /// 競合
+    Conflict,
+
+    /// 一時的に利用不可
+    ///
+    /// データベースが応答しないなど、再試行すると成功する可能性があることを表現する。
+    Unavailable,
 }
 
 /// ユースケースエラー
@@ -51,6 +65,9 @@ pub enum UseCaseErrorKind {
 /// 別のメソッドを呼び出して、ユースケースエラーを構築する。
 ///
 /// ユースケースで特殊なエラーの場合は、`new`メソッドを呼び出してユースケース・エラーを構築する。
+///
+/// メッセージにメッセージカタログのキーを指定した場合、メッセージはレスポンスを返すときに
+/// リクエストされた言語に応じて解決される。
 #[derive(Debug, Clone, thiserror::Error)]
 #[error("{message}")]
 pub struct UseCaseError {
@@ -59,7 +76,7 @@ pub struct UseCaseError {
     /// ユースケースエラー・コード
     pub error_code: u32,
     /// メッセージ
-    pub message: Cow<'static, str>,
+    pub message: UseCaseMessage,
 }
 
 impl UseCaseError {
@@ -77,7 +94,7 @@ impl UseCaseError {
     pub fn new(
         kind: UseCaseErrorKind,
         error_code: u32,
-        message: impl Into<Cow<'static, str>>,
+        message: impl Into<UseCaseMessage>,
     ) -> Self {
         Self {
             kind,
@@ -86,7 +103,7 @@ impl UseCaseError {
         }
     }
 
-    pub fn unexpected(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn unexpected(message: impl Into<UseCaseMessage>) -> Self {
         Self {
             kind: UseCaseErrorKind::Unexpected,
             error_code: UseCaseErrorCode::Unexpected as u32,
@@ -94,7 +111,7 @@ impl UseCaseError {
         }
     }
 
-    pub fn validation(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn validation(message: impl Into<UseCaseMessage>) -> Self {
         Self {
             kind: UseCaseErrorKind::Validation,
             error_code: UseCaseErrorCode::Validation as u32,
@@ -102,7 +119,7 @@ impl UseCaseError {
         }
     }
 
-    pub fn domain_rule(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn domain_rule(message: impl Into<UseCaseMessage>) -> Self {
         Self {
             kind: UseCaseErrorKind::DomainRule,
             error_code: UseCaseErrorCode::DomainRule as u32,
@@ -110,7 +127,7 @@ impl UseCaseError {
         }
     }
 
-    pub fn repository(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn repository(message: impl Into<UseCaseMessage>) -> Self {
         Self {
             kind: UseCaseErrorKind::Repository,
             error_code: UseCaseErrorCode::Repository as u32,
@@ -118,7 +135,7 @@ impl UseCaseError {
         }
     }
 
-    pub fn not_found(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn not_found(message: impl Into<UseCaseMessage>) -> Self {
         Self {
             kind: UseCaseErrorKind::NotFound,
             error_code: UseCaseErrorCode::NotFound as u32,
@@ -126,17 +143,40 @@ impl UseCaseError {
         }
     }
 
-    pub fn unauthorized(message: impl Into<Cow<'static, str>>) -> Self {
+    pub fn unauthorized(message: impl Into<UseCaseMessage>) -> Self {
         Self {
             kind: UseCaseErrorKind::Unauthorized,
             error_code: UseCaseErrorCode::Unauthorized as u32,
             message: message.into(),
         }
     }
+
+    pub fn conflict(message: impl Into<UseCaseMessage>) -> Self {
+        Self {
+            kind: UseCaseErrorKind::Conflict,
+            error_code: UseCaseErrorCode::Conflict as u32,
+            message: message.into(),
+        }
+    }
+
+    pub fn unavailable(message: impl Into<UseCaseMessage>) -> Self {
+        Self {
+            kind: UseCaseErrorKind::Unavailable,
+            error_code: UseCaseErrorCode::Unavailable as u32,
+            message: message.into(),
+        }
+    }
 }
 
 impl From<DomainError> for UseCaseError {
+    /// ドメインエラーをユースケースエラーに変換する。
+    ///
+    /// リポジトリの処理がタイムアウトした場合は、ロードバランサーが他のサーバーで再試行できるように、
+    /// 一時的に利用不可であることを示すユースケースエラーに変換する。
     fn from(value: DomainError) -> Self {
+        if value.is_timeout() {
+            return Self::unavailable(MessageKey::ServiceIsUnavailable);
+        }
         match value {
             DomainError::Unexpected(error) => Self::unexpected(error.to_string()),
             DomainError::Validation(message) => Self::validation(message),
@@ -149,3 +189,46 @@ impl From<DomainError> for UseCaseError {
 /// サインアップ
 pub const ERR_SAME_EMAIL_ADDRESS_IS_REGISTERED: u32 = 1000;
 pub const ERR_SPECIFY_FIXED_OR_MOBILE_NUMBER: u32 = 1001;
+pub const ERR_REGISTRATION_IS_DISABLED: u32 = 1002;
+pub const ERR_USER_PERMISSION_IS_NOT_ALLOWED: u32 = 1003;
+/// サインイン
+pub const ERR_EMAIL_ADDRESS_IS_NOT_VERIFIED: u32 = 1100;
+/// Eメールアドレスの確認
+pub const ERR_INVALID_EMAIL_VERIFICATION_TOKEN: u32 = 1200;
+/// Eメールアドレスの変更
+pub const ERR_INVALID_EMAIL_CHANGE_TOKEN: u32 = 1300;
+/// 2要素認証
+pub const ERR_INVALID_TOTP_CODE: u32 = 1400;
+pub const ERR_INVALID_MFA_PENDING_TOKEN: u32 = 1401;
+pub const ERR_TWO_FACTOR_IS_ALREADY_ENABLED: u32 = 1402;
+pub const ERR_TWO_FACTOR_IS_NOT_SET_UP: u32 = 1403;
+/// ユーザー権限の変更
+pub const ERR_LAST_ADMIN_CAN_NOT_BE_DEMOTED: u32 = 1500;
+/// アカウントの削除
+pub const ERR_LAST_ADMIN_CAN_NOT_BE_DELETED: u32 = 1600;
+/// ユーザーの一括登録
+pub const ERR_TOO_MANY_USERS_TO_IMPORT: u32 = 1700;
+/// プロフィールの更新
+pub const ERR_USER_WAS_MODIFIED: u32 = 1800;
+/// パスワードの変更
+pub const ERR_INVALID_PASSWORD_CHANGE_TOKEN: u32 = 1900;
+pub const ERR_NEW_PASSWORD_MUST_DIFFER: u32 = 1901;
+
+#[cfg(test)]
+mod tests {
+    use domain::RepositoryTimeout;
+
+    use super::*;
+
+    /// リポジトリの処理がタイムアウトした場合は、一時的に利用不可であることを示すユースケースエラーに
+    /// 変換することを確認
+    #[test]
+    fn repository_timeout_is_converted_into_unavailable_error() {
+        let error = UseCaseError::from(DomainError::Repository(RepositoryTimeout.into()));
+        assert_eq!(UseCaseErrorKind::Unavailable, error.kind);
+        assert_eq!(UseCaseErrorCode::Unavailable as u32, error.error_code);
+
+        let error = UseCaseError::from(DomainError::Repository(anyhow::anyhow!("error")));
+        assert_eq!(UseCaseErrorKind::Repository, error.kind);
+    }
+}
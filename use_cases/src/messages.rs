@@ -0,0 +1,583 @@
+use std::borrow::Cow;
+
+/// メッセージの言語
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    /// 日本語
+    #[default]
+    Ja,
+    /// 英語
+    En,
+}
+
+impl Language {
+    /// 言語タグから言語を取得する。
+    ///
+    /// 言語タグのプライマリ・サブタグのみで判定する。
+    ///
+    /// # 引数
+    ///
+    /// * `tag` - 言語タグ（例: `en-US`）
+    ///
+    /// # 戻り値
+    ///
+    /// 言語、サポートしていない言語の場合は`None`
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split('-').next()?.trim();
+        if primary.eq_ignore_ascii_case("ja") {
+            Some(Self::Ja)
+        } else if primary.eq_ignore_ascii_case("en") {
+            Some(Self::En)
+        } else {
+            None
+        }
+    }
+
+    /// `Accept-Language`ヘッダの値から、レスポンスに使用する言語を決定する。
+    ///
+    /// 品質値（`q`）が大きい順に、サポートしている言語を探す。サポートしている言語がない場合は、
+    /// 日本語を返す。
+    ///
+    /// # 引数
+    ///
+    /// * `value` - `Accept-Language`ヘッダの値
+    ///
+    /// # 戻り値
+    ///
+    /// 言語
+    pub fn from_accept_language(value: &str) -> Self {
+        let mut candidates = value
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (0.0 < quality).then_some((tag, quality))
+            })
+            .collect::<Vec<_>>();
+        // 品質値が同じ場合は、指定された順番を維持
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        candidates
+            .into_iter()
+            .find_map(|(tag, _)| Self::from_tag(tag))
+            .unwrap_or_default()
+    }
+}
+
+/// メッセージカタログのキー
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MessageKey {
+    /// 同じEメールアドレスを持つユーザーが登録されている
+    SameEmailAddressIsRegistered,
+    /// ユーザー権限区分コードが範囲外
+    UserPermissionCodeOutOfRange,
+    /// 固定電話番号または携帯電話番号が必要
+    SpecifyFixedOrMobileNumber,
+    /// サインアップが無効
+    RegistrationIsDisabled,
+    /// サインアップで登録できないユーザー権限
+    UserPermissionIsNotAllowed,
+    /// Eメールアドレスまたはパスワードが間違っている
+    WrongEmailAddressOrPassword,
+    /// ユーザーのアカウントがロックされている
+    UserAccountIsLocked,
+    /// サインイン履歴の保存に失敗した
+    SignInHistoryNotRecorded,
+    /// アクセスする権限がない
+    Forbidden,
+    /// ユーザーがEメールアドレスを確認していない
+    EmailAddressIsNotVerified,
+    /// Eメールアドレスを確認するトークンが無効
+    InvalidEmailVerificationToken,
+    /// パスワードが間違っている
+    WrongPassword,
+    /// Eメールアドレスの変更を確定するトークンが無効
+    InvalidEmailChangeToken,
+    /// TOTPコードが無効
+    InvalidTotpCode,
+    /// 2要素認証を待機しているサインインを示すトークンが無効
+    InvalidMfaPendingToken,
+    /// パスワードを変更するためのトークンが無効
+    InvalidPasswordChangeToken,
+    /// 変更後のパスワードが現在のパスワードと同じ
+    NewPasswordMustDiffer,
+    /// 2要素認証がすでに有効
+    TwoFactorIsAlreadyEnabled,
+    /// 2要素認証の準備がされていない
+    TwoFactorIsNotSetUp,
+    /// ユーザーが見つからない
+    UserNotFound,
+    /// 最後の管理者のユーザー権限は変更できない
+    LastAdminCanNotBeDemoted,
+    /// 最後の管理者のアカウントは削除できない
+    LastAdminCanNotBeDeleted,
+    /// パスワードを送信するか、再認証する必要がある
+    PasswordIsRequired,
+    /// 一括登録するユーザーが多すぎる
+    TooManyUsersToImport,
+    /// 複数のフィールドの検証に失敗した
+    InvalidFields,
+    /// ユーザーが他の操作で更新された
+    UserWasModified,
+    /// `If-Match`ヘッダが必要
+    IfMatchIsRequired,
+    /// アクセストークンが無効または期限切れ
+    InvalidAccessToken,
+    /// トークンがJWTの形式でない
+    MalformedToken,
+    /// アクセストークンの代わりにリフレッシュトークンが送信された
+    RefreshTokenSent,
+    /// セッションが見つからない
+    SessionNotFound,
+    /// 内部サービスの資格情報が無効
+    InvalidServiceCredential,
+    /// リクエストボディが大きすぎる
+    PayloadTooLarge,
+    /// `Idempotency-Key`ヘッダの値が不正
+    InvalidIdempotencyKey,
+    /// 同じ冪等キーのリクエストを処理している
+    IdempotentRequestIsInProgress,
+    /// 冪等キーが異なるリクエストボディで使用されている
+    IdempotencyKeyIsReused,
+    /// メンテナンス中
+    UnderMaintenance,
+    /// データベースが応答しないため、一時的にサービスを利用できない
+    ServiceIsUnavailable,
+    /// アクセス元のIPアドレスを判定できない
+    ClientIpIsUnknown,
+    /// リクエストURIに`{user_id}`パスがない
+    UserIdPathIsRequired,
+    /// リクエストURIで指定されたユーザーIDがUUIDでない
+    InvalidUserIdPath,
+    /// `Authorization`ヘッダの内容が不正
+    InvalidAuthorizationHeader,
+    /// `Authorization`ヘッダの内容が`Bearer`から始まっていない
+    AuthorizationSchemeIsNotBearer,
+    /// `Authorization`ヘッダにアクセストークンが含まれていない
+    AccessTokenIsMissing,
+    /// リクエストコンテキストを取得できない
+    RequestContextIsUnavailable,
+    /// トークンの内容を取得できない
+    TokenContentIsUnavailable,
+    /// 未知のフィールド
+    UnknownField,
+    /// 文字列が長すぎる
+    StringIsTooLong,
+}
+
+impl MessageKey {
+    /// メッセージカタログから、言語に対応するメッセージのテンプレートを取得する。
+    ///
+    /// テンプレートに含まれる`{name}`は、メッセージ・パラメーターで置き換える。
+    ///
+    /// # 引数
+    ///
+    /// * `language` - 言語
+    ///
+    /// # 戻り値
+    ///
+    /// メッセージのテンプレート
+    pub fn template(&self, language: Language) -> &'static str {
+        match (self, language) {
+            (Self::SameEmailAddressIsRegistered, Language::Ja) => {
+                "同じEメールアドレスを持つユーザーが、すでに登録されています。"
+            }
+            (Self::SameEmailAddressIsRegistered, Language::En) => {
+                "A user with the same email address is already registered."
+            }
+            (Self::UserPermissionCodeOutOfRange, Language::Ja) => {
+                "ユーザー権限区分コードが範囲外です。"
+            }
+            (Self::UserPermissionCodeOutOfRange, Language::En) => {
+                "The user permission code is out of range."
+            }
+            (Self::SpecifyFixedOrMobileNumber, Language::Ja) => {
+                "固定電話番号または携帯電話番号を指定する必要があります。"
+            }
+            (Self::SpecifyFixedOrMobileNumber, Language::En) => {
+                "Either a fixed phone number or a mobile phone number must be specified."
+            }
+            (Self::RegistrationIsDisabled, Language::Ja) => {
+                "ユーザーのサインアップは受け付けていません。"
+            }
+            (Self::RegistrationIsDisabled, Language::En) => "User sign-up is disabled.",
+            (Self::UserPermissionIsNotAllowed, Language::Ja) => {
+                "指定されたユーザー権限でサインアップすることはできません。"
+            }
+            (Self::UserPermissionIsNotAllowed, Language::En) => {
+                "Signing up with the specified user permission is not allowed."
+            }
+            (Self::WrongEmailAddressOrPassword, Language::Ja) => {
+                "Eメールアドレスまたはパスワードが間違っています。"
+            }
+            (Self::WrongEmailAddressOrPassword, Language::En) => {
+                "The email address or password is incorrect."
+            }
+            (Self::UserAccountIsLocked, Language::Ja) => {
+                "ユーザーのアカウントがロックされています。"
+            }
+            (Self::UserAccountIsLocked, Language::En) => "The user account is locked.",
+            (Self::SignInHistoryNotRecorded, Language::Ja) => {
+                "ユーザーのサインイン履歴の保存に失敗しました。"
+            }
+            (Self::SignInHistoryNotRecorded, Language::En) => {
+                "Failed to record the sign-in history of the user."
+            }
+            (Self::Forbidden, Language::Ja) => "アクセスする権限がありません。",
+            (Self::Forbidden, Language::En) => {
+                "You do not have permission to access this resource."
+            }
+            (Self::EmailAddressIsNotVerified, Language::Ja) => {
+                "Eメールアドレスが確認されていません。"
+            }
+            (Self::EmailAddressIsNotVerified, Language::En) => {
+                "The email address has not been verified."
+            }
+            (Self::InvalidEmailVerificationToken, Language::Ja) => {
+                "Eメールアドレスを確認するトークンが無効、または有効期限が切れています。"
+            }
+            (Self::InvalidEmailVerificationToken, Language::En) => {
+                "The email verification token is invalid or has expired."
+            }
+            (Self::WrongPassword, Language::Ja) => "パスワードが間違っています。",
+            (Self::WrongPassword, Language::En) => "The password is incorrect.",
+            (Self::InvalidEmailChangeToken, Language::Ja) => {
+                "Eメールアドレスの変更を確定するトークンが無効、または有効期限が切れています。"
+            }
+            (Self::InvalidEmailChangeToken, Language::En) => {
+                "The email change token is invalid or has expired."
+            }
+            (Self::InvalidTotpCode, Language::Ja) => {
+                "認証コードが間違っている、またはすでに使用されています。"
+            }
+            (Self::InvalidTotpCode, Language::En) => {
+                "The authentication code is incorrect or has already been used."
+            }
+            (Self::InvalidMfaPendingToken, Language::Ja) => {
+                "2要素認証のトークンが無効、または有効期限が切れています。もう一度サインインしてください。"
+            }
+            (Self::InvalidMfaPendingToken, Language::En) => {
+                "The two-factor authentication token is invalid or has expired. Please sign in again."
+            }
+            (Self::InvalidPasswordChangeToken, Language::Ja) => {
+                "パスワードを変更するトークンが無効、または有効期限が切れています。もう一度サインインしてください。"
+            }
+            (Self::InvalidPasswordChangeToken, Language::En) => {
+                "The password change token is invalid or has expired. Please sign in again."
+            }
+            (Self::NewPasswordMustDiffer, Language::Ja) => {
+                "変更後のパスワードは、現在のパスワードと異なるパスワードを指定してください。"
+            }
+            (Self::NewPasswordMustDiffer, Language::En) => {
+                "The new password must differ from the current password."
+            }
+            (Self::TwoFactorIsAlreadyEnabled, Language::Ja) => {
+                "2要素認証はすでに有効です。"
+            }
+            (Self::TwoFactorIsAlreadyEnabled, Language::En) => {
+                "Two-factor authentication is already enabled."
+            }
+            (Self::TwoFactorIsNotSetUp, Language::Ja) => {
+                "2要素認証の準備がされていません。"
+            }
+            (Self::TwoFactorIsNotSetUp, Language::En) => {
+                "Two-factor authentication has not been set up."
+            }
+            (Self::UserNotFound, Language::Ja) => "ユーザーが見つかりません。",
+            (Self::UserNotFound, Language::En) => "The user was not found.",
+            (Self::LastAdminCanNotBeDemoted, Language::Ja) => {
+                "最後の管理者のユーザー権限は変更できません。"
+            }
+            (Self::LastAdminCanNotBeDemoted, Language::En) => {
+                "The permission of the last administrator cannot be changed."
+            }
+            (Self::LastAdminCanNotBeDeleted, Language::Ja) => {
+                "最後の管理者のアカウントは削除できません。"
+            }
+            (Self::LastAdminCanNotBeDeleted, Language::En) => {
+                "The account of the last administrator cannot be deleted."
+            }
+            (Self::PasswordIsRequired, Language::Ja) => {
+                "パスワードを送信するか、再認証してください。"
+            }
+            (Self::PasswordIsRequired, Language::En) => {
+                "Send the password, or reauthenticate first."
+            }
+            (Self::TooManyUsersToImport, Language::Ja) => {
+                "一括登録できるユーザーは{max}人までです。"
+            }
+            (Self::TooManyUsersToImport, Language::En) => {
+                "Up to {max} users can be imported at once."
+            }
+            (Self::InvalidFields, Language::Ja) => "入力内容に誤りがあります。",
+            (Self::InvalidFields, Language::En) => "Some fields are invalid.",
+            (Self::UserWasModified, Language::Ja) => {
+                "ユーザーが他の操作で更新されました。最新のユーザーを取得してから更新してください。"
+            }
+            (Self::UserWasModified, Language::En) => {
+                "The user was modified by another operation. Fetch the latest user and try again."
+            }
+            (Self::IfMatchIsRequired, Language::Ja) => {
+                "If-Matchヘッダにユーザーのバージョンを指定してください。"
+            }
+            (Self::IfMatchIsRequired, Language::En) => {
+                "Specify the version of the user in the If-Match header."
+            }
+            (Self::InvalidAccessToken, Language::Ja) => {
+                "アクセストークンが無効か、有効期限が切れています。"
+            }
+            (Self::InvalidAccessToken, Language::En) => "The access token is invalid or has expired.",
+            (Self::MalformedToken, Language::Ja) => "トークンの形式が不正です。",
+            (Self::MalformedToken, Language::En) => "The token is malformed.",
+            (Self::RefreshTokenSent, Language::Ja) => "リフレッシュトークンが送信されました。",
+            (Self::RefreshTokenSent, Language::En) => {
+                "A refresh token was sent instead of an access token."
+            }
+            (Self::SessionNotFound, Language::Ja) => "セッションが見つかりません。",
+            (Self::SessionNotFound, Language::En) => "The session was not found.",
+            (Self::InvalidServiceCredential, Language::Ja) => {
+                "内部サービスの資格情報が無効です。"
+            }
+            (Self::InvalidServiceCredential, Language::En) => {
+                "The service credential is invalid."
+            }
+            (Self::PayloadTooLarge, Language::Ja) => {
+                "リクエストボディは{max}バイト以下にしてください。"
+            }
+            (Self::PayloadTooLarge, Language::En) => {
+                "The request body must be {max} bytes or less."
+            }
+            (Self::InvalidIdempotencyKey, Language::Ja) => {
+                "Idempotency-Keyヘッダには、255文字以下の表示可能なASCII文字を指定してください。"
+            }
+            (Self::InvalidIdempotencyKey, Language::En) => {
+                "The Idempotency-Key header must be up to 255 printable ASCII characters."
+            }
+            (Self::IdempotentRequestIsInProgress, Language::Ja) => {
+                "同じ冪等キーのリクエストを処理しています。しばらくしてから再送信してください。"
+            }
+            (Self::IdempotentRequestIsInProgress, Language::En) => {
+                "A request with the same idempotency key is being processed. Retry later."
+            }
+            (Self::IdempotencyKeyIsReused, Language::Ja) => {
+                "冪等キーは、異なるリクエストボディですでに使用されています。"
+            }
+            (Self::IdempotencyKeyIsReused, Language::En) => {
+                "The idempotency key has already been used with a different request body."
+            }
+            (Self::UnderMaintenance, Language::Ja) => {
+                "メンテナンス中です。しばらくしてから再度アクセスしてください。"
+            }
+            (Self::UnderMaintenance, Language::En) => {
+                "The service is under maintenance. Please try again later."
+            }
+            (Self::ServiceIsUnavailable, Language::Ja) => {
+                "一時的にサービスを利用できません。しばらくしてから再度アクセスしてください。"
+            }
+            (Self::ServiceIsUnavailable, Language::En) => {
+                "The service is temporarily unavailable. Please try again later."
+            }
+            (Self::ClientIpIsUnknown, Language::Ja) => {
+                "アクセス元のIPアドレスを判定できませんでした。"
+            }
+            (Self::ClientIpIsUnknown, Language::En) => {
+                "The IP address of the client could not be determined."
+            }
+            (Self::UserIdPathIsRequired, Language::Ja) => {
+                "リクエストURIに`{user_id}`パスが必要です。"
+            }
+            (Self::UserIdPathIsRequired, Language::En) => {
+                "The request URI must contain the `{user_id}` path."
+            }
+            (Self::InvalidUserIdPath, Language::Ja) => {
+                "リクエストURIで指定されたユーザーIDをUUIDに変換できません。"
+            }
+            (Self::InvalidUserIdPath, Language::En) => {
+                "The user ID in the request URI is not a valid UUID."
+            }
+            (Self::InvalidAuthorizationHeader, Language::Ja) => {
+                "Authorizationヘッダの内容が誤っています。"
+            }
+            (Self::InvalidAuthorizationHeader, Language::En) => {
+                "The Authorization header is invalid."
+            }
+            (Self::AuthorizationSchemeIsNotBearer, Language::Ja) => {
+                "Authorizationヘッダの内容が`Bearer`から始まっていません。"
+            }
+            (Self::AuthorizationSchemeIsNotBearer, Language::En) => {
+                "The Authorization header does not start with `Bearer`."
+            }
+            (Self::AccessTokenIsMissing, Language::Ja) => {
+                "Authorizationヘッダの内容にアクセストークンが含まれていません。"
+            }
+            (Self::AccessTokenIsMissing, Language::En) => {
+                "The Authorization header does not contain an access token."
+            }
+            (Self::RequestContextIsUnavailable, Language::Ja) => {
+                "リクエストコンテキストを取得できませんでした。"
+            }
+            (Self::RequestContextIsUnavailable, Language::En) => {
+                "The request context could not be retrieved."
+            }
+            (Self::TokenContentIsUnavailable, Language::Ja) => {
+                "トークンの内容を取得できませんでした。"
+            }
+            (Self::TokenContentIsUnavailable, Language::En) => {
+                "The token content could not be retrieved."
+            }
+            (Self::UnknownField, Language::Ja) => "未知のフィールドです。",
+            (Self::UnknownField, Language::En) => "The field is unknown.",
+            (Self::StringIsTooLong, Language::Ja) => "{max}バイト以下で指定してください。",
+            (Self::StringIsTooLong, Language::En) => "The value must be {max} bytes or less.",
+        }
+    }
+}
+
+/// ユースケースメッセージ
+///
+/// メッセージカタログに登録されたメッセージは、レスポンスを返すときに言語に応じて解決する。
+/// ドメイン層から受け取ったエラーメッセージなど、メッセージカタログに登録されていないメッセージは、
+/// そのまま返す。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UseCaseMessage {
+    /// メッセージカタログに登録されたメッセージ
+    Catalog {
+        /// メッセージカタログのキー
+        key: MessageKey,
+        /// メッセージ・パラメーター
+        params: Vec<(&'static str, String)>,
+    },
+    /// メッセージカタログに登録されていないメッセージ
+    Raw(Cow<'static, str>),
+}
+
+impl UseCaseMessage {
+    /// メッセージ・パラメーターを追加する。
+    ///
+    /// メッセージカタログに登録されていないメッセージの場合は、何もしない。
+    ///
+    /// # 引数
+    ///
+    /// * `name` - パラメーター名
+    /// * `value` - パラメーターの値
+    ///
+    /// # 戻り値
+    ///
+    /// ユースケースメッセージ
+    pub fn with_param(mut self, name: &'static str, value: impl ToString) -> Self {
+        if let Self::Catalog { params, .. } = &mut self {
+            params.push((name, value.to_string()));
+        }
+
+        self
+    }
+
+    /// メッセージカタログに登録されたメッセージであるか確認する。
+    pub fn is_localizable(&self) -> bool {
+        matches!(self, Self::Catalog { .. })
+    }
+
+    /// 言語に応じてメッセージを解決する。
+    ///
+    /// # 引数
+    ///
+    /// * `language` - 言語
+    ///
+    /// # 戻り値
+    ///
+    /// メッセージ
+    pub fn resolve(&self, language: Language) -> Cow<'static, str> {
+        match self {
+            Self::Catalog { key, params } if params.is_empty() => key.template(language).into(),
+            Self::Catalog { key, params } => {
+                let mut message = key.template(language).to_string();
+                for (name, value) in params {
+                    message = message.replace(&format!("{{{}}}", name), value);
+                }
+                message.into()
+            }
+            Self::Raw(message) => message.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for UseCaseMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.resolve(Language::default()))
+    }
+}
+
+impl From<MessageKey> for UseCaseMessage {
+    fn from(value: MessageKey) -> Self {
+        Self::Catalog {
+            key: value,
+            params: vec![],
+        }
+    }
+}
+
+impl From<&'static str> for UseCaseMessage {
+    fn from(value: &'static str) -> Self {
+        Self::Raw(value.into())
+    }
+}
+
+impl From<String> for UseCaseMessage {
+    fn from(value: String) -> Self {
+        Self::Raw(value.into())
+    }
+}
+
+impl From<Cow<'static, str>> for UseCaseMessage {
+    fn from(value: Cow<'static, str>) -> Self {
+        Self::Raw(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Accept-Language`ヘッダの値から、品質値が大きいサポートしている言語を決定できることを確認
+    #[test]
+    fn language_is_negotiated_from_accept_language() {
+        let cases = [
+            ("en", Language::En),
+            ("en-US,en;q=0.9", Language::En),
+            ("ja-JP", Language::Ja),
+            ("fr-FR, en;q=0.8, ja;q=0.5", Language::En),
+            ("en;q=0.3, ja;q=0.7", Language::Ja),
+            ("en;q=0, fr", Language::Ja),
+            ("fr, de", Language::Ja),
+            ("", Language::Ja),
+            ("*", Language::Ja),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(expected, Language::from_accept_language(value), "{}", value);
+        }
+    }
+
+    /// メッセージカタログに登録されたメッセージを言語に応じて解決して、
+    /// 登録されていないメッセージはそのまま返すことを確認
+    #[test]
+    fn messages_are_resolved_by_language() {
+        let message = UseCaseMessage::from(MessageKey::SameEmailAddressIsRegistered);
+        assert_eq!(
+            "同じEメールアドレスを持つユーザーが、すでに登録されています。",
+            message.resolve(Language::Ja)
+        );
+        assert_eq!(
+            "A user with the same email address is already registered.",
+            message.resolve(Language::En)
+        );
+        assert_eq!(message.resolve(Language::Ja), message.to_string());
+
+        let message = UseCaseMessage::from("ドメインエラー");
+        assert!(!message.is_localizable());
+        assert_eq!("ドメインエラー", message.resolve(Language::En));
+    }
+}
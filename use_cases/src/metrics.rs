@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+/// サインインメトリクス
+///
+/// サインインユースケースが、サインインの成功、失敗及びアカウントのロックを記録するために使用する。
+pub trait SignInMetrics: std::fmt::Debug + Sync + Send {
+    /// サインインに成功したことを記録する。
+    fn sign_in_succeeded(&self);
+
+    /// サインインに失敗したことを記録する。
+    fn sign_in_failed(&self);
+
+    /// ユーザーのアカウントをロックしたことを記録する。
+    fn account_locked(&self);
+}
+
+impl<T: SignInMetrics + ?Sized> SignInMetrics for Arc<T> {
+    fn sign_in_succeeded(&self) {
+        (**self).sign_in_succeeded()
+    }
+
+    fn sign_in_failed(&self) {
+        (**self).sign_in_failed()
+    }
+
+    fn account_locked(&self) {
+        (**self).account_locked()
+    }
+}
@@ -0,0 +1,460 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use secrecy::{ExposeSecret as _, SecretString};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::task::JoinHandle;
+
+use domain::models::primitives::EmailAddress;
+use domain::models::user::UserId;
+use domain::repositories::device::DeviceRepository;
+use domain::repositories::user::SignInAttempt;
+
+use crate::UseCaseResult;
+
+/// Eメール送信者
+#[async_trait]
+pub trait EmailSender: std::fmt::Debug + Sync + Send {
+    /// Eメールを送信する。
+    ///
+    /// # 引数
+    ///
+    /// * `to` - 宛先のEメールアドレス
+    /// * `subject` - 件名
+    /// * `body` - 本文
+    async fn send(&self, to: EmailAddress, subject: String, body: String) -> UseCaseResult<()>;
+}
+
+/// Eメールを送信しないEメール送信者
+///
+/// 開発環境など、Eメールを送信しない環境で使用する。送信するEメールはデバッグレベルで記録する。
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoopEmailSender;
+
+#[async_trait]
+impl EmailSender for NoopEmailSender {
+    async fn send(&self, to: EmailAddress, subject: String, _body: String) -> UseCaseResult<()> {
+        tracing::debug!(to = %to.value, subject = %subject, "email was not sent");
+
+        Ok(())
+    }
+}
+
+/// サインイン通知者
+///
+/// サインインユースケースが、アカウントのロック及び新しいデバイスからのサインインを、ユーザーに
+/// Eメールで通知するために使用する。
+/// Eメールはバックグラウンドで送信するため、レスポンスを返すことを妨げない。また、Eメールの
+/// 送信に失敗した場合は、エラーを記録して無視する。
+pub struct SignInNotifier<R>
+where
+    R: DeviceRepository + 'static,
+{
+    /// デバイスリポジトリ
+    device_repository: R,
+    /// Eメール送信者
+    email_sender: Arc<dyn EmailSender>,
+}
+
+impl<R> SignInNotifier<R>
+where
+    R: DeviceRepository + 'static,
+{
+    /// サインイン通知者を構築する。
+    ///
+    /// # 引数
+    ///
+    /// * `device_repository` - デバイスリポジトリ
+    /// * `email_sender` - Eメール送信者
+    ///
+    /// # 戻り値
+    ///
+    /// サインイン通知者
+    pub fn new(device_repository: R, email_sender: Arc<dyn EmailSender>) -> Self {
+        Self {
+            device_repository,
+            email_sender,
+        }
+    }
+
+    /// ユーザーのアカウントをロックしたことを通知する。
+    ///
+    /// 最後にサインインに失敗したアクセス元のうち、判定できた項目を本文に記載する。
+    ///
+    /// # 引数
+    ///
+    /// * `email` - ユーザーのEメールアドレス
+    /// * `attempt` - 最後にサインインに失敗したアクセス元
+    ///
+    /// # 戻り値
+    ///
+    /// Eメールを送信するタスクのハンドル
+    pub fn account_locked(&self, email: EmailAddress, attempt: SignInAttempt) -> JoinHandle<()> {
+        let email_sender = self.email_sender.clone();
+        tokio::spawn(async move {
+            let mut body = String::from(ACCOUNT_LOCKED_BODY);
+            if attempt.client_ip.is_some() || attempt.user_agent.is_some() {
+                body.push('\n');
+            }
+            if let Some(client_ip) = attempt.client_ip {
+                body.push_str(&format!("\nIPアドレス: {}", client_ip));
+            }
+            if let Some(user_agent) = attempt.user_agent {
+                body.push_str(&format!("\nユーザーエージェント: {}", user_agent));
+            }
+            send_and_log(email_sender.as_ref(), email, ACCOUNT_LOCKED_SUBJECT, body).await;
+        })
+    }
+
+    /// ユーザーがサインインしたことを通知する。
+    ///
+    /// デバイスを識別する文字列を登録して、ユーザーがはじめてサインインしたデバイスの場合のみ
+    /// 通知する。デバイスを識別できない場合は通知しない。
+    ///
+    /// # 引数
+    ///
+    /// * `user_id` - ユーザーID
+    /// * `email` - ユーザーのEメールアドレス
+    /// * `device` - デバイスを識別する文字列
+    ///
+    /// # 戻り値
+    ///
+    /// Eメールを送信するタスクのハンドル
+    pub fn signed_in(
+        self,
+        user_id: UserId,
+        email: EmailAddress,
+        device: Option<String>,
+    ) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let Some(device) = device else {
+                return;
+            };
+            match self
+                .device_repository
+                .register_device(user_id, &device)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => return,
+                Err(e) => {
+                    tracing::error!("{} ({}:{})", e, file!(), line!());
+                    return;
+                }
+            }
+            let signed_in_at = OffsetDateTime::now_utc()
+                .format(&Rfc3339)
+                .unwrap_or_default();
+            let body = format!(
+                "{}\n\n日時: {}\nデバイス: {}",
+                NEW_SIGN_IN_BODY, signed_in_at, device
+            );
+            send_and_log(self.email_sender.as_ref(), email, NEW_SIGN_IN_SUBJECT, body).await;
+        })
+    }
+}
+
+/// Eメールアドレスを確認するトークンを、ユーザーにEメールで送信する。
+///
+/// Eメールはバックグラウンドで送信して、送信に失敗した場合は、エラーを記録して無視する。
+///
+/// # 引数
+///
+/// * `email_sender` - Eメール送信者
+/// * `email` - 確認するEメールアドレス
+/// * `token` - Eメールアドレスを確認するトークン
+/// * `ttl` - トークンの有効期限（秒）
+///
+/// # 戻り値
+///
+/// Eメールを送信するタスクのハンドル
+pub fn send_email_verification(
+    email_sender: Arc<dyn EmailSender>,
+    email: EmailAddress,
+    token: SecretString,
+    ttl: u64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let body = format!(
+            "{}\n\n{}{}\n\n有効期限: {}分",
+            EMAIL_VERIFICATION_BODY,
+            EMAIL_VERIFICATION_TOKEN_LABEL,
+            token.expose_secret(),
+            ttl / 60
+        );
+        send_and_log(
+            email_sender.as_ref(),
+            email,
+            EMAIL_VERIFICATION_SUBJECT,
+            body,
+        )
+        .await;
+    })
+}
+
+/// Eメールアドレスの変更を確定するトークンを、変更後のEメールアドレスにEメールで送信する。
+///
+/// Eメールはバックグラウンドで送信して、送信に失敗した場合は、エラーを記録して無視する。
+///
+/// # 引数
+///
+/// * `email_sender` - Eメール送信者
+/// * `email` - 変更後のEメールアドレス
+/// * `token` - Eメールアドレスの変更を確定するトークン
+/// * `ttl` - トークンの有効期限（秒）
+///
+/// # 戻り値
+///
+/// Eメールを送信するタスクのハンドル
+pub fn send_email_change_confirmation(
+    email_sender: Arc<dyn EmailSender>,
+    email: EmailAddress,
+    token: SecretString,
+    ttl: u64,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let body = format!(
+            "{}\n\n{}{}\n\n有効期限: {}分",
+            EMAIL_CHANGE_BODY,
+            EMAIL_CHANGE_TOKEN_LABEL,
+            token.expose_secret(),
+            ttl / 60
+        );
+        send_and_log(email_sender.as_ref(), email, EMAIL_CHANGE_SUBJECT, body).await;
+    })
+}
+
+/// Eメールを送信して、送信に失敗した場合はエラーを記録する。
+async fn send_and_log(
+    email_sender: &dyn EmailSender,
+    to: EmailAddress,
+    subject: &str,
+    body: String,
+) {
+    if let Err(e) = email_sender.send(to, String::from(subject), body).await {
+        tracing::error!("{} ({}:{})", e, file!(), line!());
+    }
+}
+
+/// アカウントのロックを通知するEメールの件名
+pub const ACCOUNT_LOCKED_SUBJECT: &str = "アカウントがロックされました";
+/// アカウントのロックを通知するEメールの本文
+const ACCOUNT_LOCKED_BODY: &str =
+    "サインインに連続して失敗したため、アカウントをロックしました。心当たりがない場合は、管理者に連絡してください。";
+/// 新しいデバイスからのサインインを通知するEメールの件名
+pub const NEW_SIGN_IN_SUBJECT: &str = "新しいデバイスからサインインしました";
+/// 新しいデバイスからのサインインを通知するEメールの本文
+const NEW_SIGN_IN_BODY: &str =
+    "新しいデバイスからアカウントにサインインしました。心当たりがない場合は、パスワードを変更してください。";
+/// Eメールアドレスの確認を依頼するEメールの件名
+pub const EMAIL_VERIFICATION_SUBJECT: &str = "Eメールアドレスを確認してください";
+/// Eメールアドレスの確認を依頼するEメールの本文
+const EMAIL_VERIFICATION_BODY: &str =
+    "Eメールアドレスを確認するため、次の確認トークンを`POST /accounts/verify-email`に送信してください。";
+/// Eメールアドレスの確認を依頼するEメールの本文で、確認トークンの前に記載するラベル
+pub const EMAIL_VERIFICATION_TOKEN_LABEL: &str = "確認トークン: ";
+/// Eメールアドレスの変更の確定を依頼するEメールの件名
+pub const EMAIL_CHANGE_SUBJECT: &str = "Eメールアドレスの変更を確定してください";
+/// Eメールアドレスの変更の確定を依頼するEメールの本文
+const EMAIL_CHANGE_BODY: &str =
+    "Eメールアドレスの変更を確定するため、次の確定トークンを`POST /accounts/email-change/confirm`に送信してください。心当たりがない場合は、このEメールを無視してください。";
+/// Eメールアドレスの変更の確定を依頼するEメールの本文で、確定トークンの前に記載するラベル
+pub const EMAIL_CHANGE_TOKEN_LABEL: &str = "確定トークン: ";
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+
+    use domain::DomainResult;
+
+    use super::*;
+    use crate::UseCaseError;
+
+    /// 送信したEメールを記録するEメール送信者
+    #[derive(Debug, Default)]
+    struct RecordingEmailSender {
+        /// 送信したEメールの宛先と件名
+        sent: Mutex<Vec<(String, String)>>,
+        /// 送信したEメールの本文
+        bodies: Mutex<Vec<String>>,
+        /// 送信に失敗するか示すフラグ
+        fails: bool,
+    }
+
+    #[async_trait]
+    impl EmailSender for RecordingEmailSender {
+        async fn send(&self, to: EmailAddress, subject: String, body: String) -> UseCaseResult<()> {
+            if self.fails {
+                return Err(UseCaseError::unexpected("failed to send email"));
+            }
+            self.sent.lock().unwrap().push((to.value, subject));
+            self.bodies.lock().unwrap().push(body);
+
+            Ok(())
+        }
+    }
+
+    /// デバイスをメモリに記録するデバイスリポジトリ
+    #[derive(Debug, Clone, Default)]
+    struct InMemoryDeviceRepository {
+        devices: Arc<Mutex<HashSet<(UserId, String)>>>,
+    }
+
+    #[async_trait]
+    impl DeviceRepository for InMemoryDeviceRepository {
+        async fn register_device(&self, user_id: UserId, device: &str) -> DomainResult<bool> {
+            Ok(self
+                .devices
+                .lock()
+                .unwrap()
+                .insert((user_id, device.to_string())))
+        }
+    }
+
+    fn email() -> EmailAddress {
+        EmailAddress::new("foo@example.com").unwrap()
+    }
+
+    /// アカウントのロックを通知するEメールを送信することを確認
+    #[tokio::test]
+    async fn account_locked_sends_email() {
+        let sender = Arc::new(RecordingEmailSender::default());
+        let notifier = SignInNotifier::new(InMemoryDeviceRepository::default(), sender.clone());
+
+        notifier
+            .account_locked(email(), SignInAttempt::default())
+            .await
+            .unwrap();
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(
+            vec![(
+                String::from("foo@example.com"),
+                String::from(ACCOUNT_LOCKED_SUBJECT)
+            )],
+            *sent
+        );
+        assert_eq!(
+            vec![String::from(ACCOUNT_LOCKED_BODY)],
+            *sender.bodies.lock().unwrap()
+        );
+    }
+
+    /// アカウントのロックを通知するEメールに、サインインに失敗したアクセス元を記載することを確認
+    #[tokio::test]
+    async fn account_locked_email_contains_sign_in_attempt() {
+        let sender = Arc::new(RecordingEmailSender::default());
+        let notifier = SignInNotifier::new(InMemoryDeviceRepository::default(), sender.clone());
+        let attempt = SignInAttempt {
+            client_ip: Some("192.0.2.1".parse().unwrap()),
+            user_agent: Some(String::from("Mozilla/5.0")),
+        };
+
+        notifier.account_locked(email(), attempt).await.unwrap();
+
+        let bodies = sender.bodies.lock().unwrap();
+        assert_eq!(
+            format!(
+                "{}\n\nIPアドレス: 192.0.2.1\nユーザーエージェント: Mozilla/5.0",
+                ACCOUNT_LOCKED_BODY
+            ),
+            bodies[0]
+        );
+    }
+
+    /// はじめてサインインしたデバイスの場合のみ、サインインを通知するEメールを送信することを確認
+    #[tokio::test]
+    async fn signed_in_sends_email_only_for_new_device() {
+        let sender = Arc::new(RecordingEmailSender::default());
+        let repository = InMemoryDeviceRepository::default();
+        let user_id = UserId::default();
+        let device = Some(String::from("Mozilla/5.0"));
+
+        for device in [device.clone(), device, None] {
+            SignInNotifier::new(repository.clone(), sender.clone())
+                .signed_in(user_id, email(), device)
+                .await
+                .unwrap();
+        }
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(
+            vec![(
+                String::from("foo@example.com"),
+                String::from(NEW_SIGN_IN_SUBJECT)
+            )],
+            *sent
+        );
+    }
+
+    /// Eメールの送信に失敗しても、パニックしないことを確認
+    #[tokio::test]
+    async fn send_failure_is_swallowed() {
+        let sender = Arc::new(RecordingEmailSender {
+            fails: true,
+            ..Default::default()
+        });
+        let notifier = SignInNotifier::new(InMemoryDeviceRepository::default(), sender.clone());
+
+        assert!(notifier
+            .account_locked(email(), SignInAttempt::default())
+            .await
+            .is_ok());
+        assert!(sender.sent.lock().unwrap().is_empty());
+    }
+
+    /// Eメールアドレスを確認するトークンを記載したEメールを送信することを確認
+    #[tokio::test]
+    async fn send_email_verification_sends_token() {
+        let sender = Arc::new(RecordingEmailSender::default());
+
+        send_email_verification(
+            sender.clone(),
+            email(),
+            SecretString::new(String::from("abc123")),
+            3600,
+        )
+        .await
+        .unwrap();
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(
+            vec![(
+                String::from("foo@example.com"),
+                String::from(EMAIL_VERIFICATION_SUBJECT)
+            )],
+            *sent
+        );
+        let bodies = sender.bodies.lock().unwrap();
+        assert!(bodies[0].contains("確認トークン: abc123"), "{}", bodies[0]);
+        assert!(bodies[0].contains("有効期限: 60分"), "{}", bodies[0]);
+    }
+
+    /// Eメールアドレスの変更を確定するトークンを記載したEメールを送信することを確認
+    #[tokio::test]
+    async fn send_email_change_confirmation_sends_token() {
+        let sender = Arc::new(RecordingEmailSender::default());
+
+        send_email_change_confirmation(
+            sender.clone(),
+            email(),
+            SecretString::new(String::from("def456")),
+            3600,
+        )
+        .await
+        .unwrap();
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(
+            vec![(
+                String::from("foo@example.com"),
+                String::from(EMAIL_CHANGE_SUBJECT)
+            )],
+            *sent
+        );
+        let bodies = sender.bodies.lock().unwrap();
+        assert!(bodies[0].contains("確定トークン: def456"), "{}", bodies[0]);
+    }
+}
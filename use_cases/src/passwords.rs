@@ -1,6 +1,9 @@
+use std::collections::HashMap;
+
 use argon2::password_hash::SaltString;
 use argon2::{Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
-use domain::models::primitives::{PhcPassword, RawPassword};
+use domain::models::primitives::{PasswordPolicy, PhcPassword, RawPassword};
+use rand::seq::SliceRandom as _;
 use secrecy::{ExposeSecret as _, SecretString};
 
 use crate::settings::PasswordSettings;
@@ -82,6 +85,68 @@ pub fn verify_password(
         .is_ok())
 }
 
+/// パスワードポリシーを満たす、ランダムなパスワードを生成する。
+///
+/// 暗号論的に安全な乱数生成器を使用して、大文字、小文字のアルファベット、数字及び記号をそれぞれ
+/// 1文字以上含み、同じ文字を許容する最大数より多く含まないパスワードを生成する。
+/// パスワードの文字数は、パスワードの最小文字数と[`GENERATED_PASSWORD_LENGTH`]の大きい方とする。
+///
+/// # 引数
+///
+/// * `policy` - パスワードポリシー
+///
+/// # 戻り値
+///
+/// 未加工なパスワード、パスワードポリシーを満たすパスワードを生成できない場合はエラー
+pub fn generate_random_password(policy: &PasswordPolicy) -> UseCaseResult<RawPassword> {
+    let mut rng = rand::thread_rng();
+    let length = policy.min_length.max(GENERATED_PASSWORD_LENGTH);
+    let symbols = policy.symbols.chars().collect::<Vec<_>>();
+    let classes = [
+        ('A'..='Z').collect::<Vec<_>>(),
+        ('a'..='z').collect::<Vec<_>>(),
+        ('0'..='9').collect::<Vec<_>>(),
+        symbols,
+    ];
+    let candidates = classes.concat();
+    // 同じ文字を含められる数が0、または文字数が最大文字数を超える場合は、パスワードポリシーを
+    // 満たすパスワードを生成できず、文字を選択し続けてしまうため拒否
+    let capacity = candidates.len() as u64 * policy.max_char_appearances;
+    if policy.max_char_appearances == 0 || policy.max_length < length || capacity < length as u64 {
+        return Err(UseCaseError::unexpected(
+            "パスワードポリシーを満たすパスワードを生成できません。",
+        ));
+    }
+
+    // 文字の種類ごとに1文字ずつ選択した後、残りの文字をすべての候補から選択
+    let mut password = classes
+        .iter()
+        .map(|class| *class.choose(&mut rng).unwrap())
+        .collect::<Vec<_>>();
+    let mut appearances: HashMap<char, u64> = HashMap::new();
+    for ch in &password {
+        *appearances.entry(*ch).or_insert(0) += 1;
+    }
+    while password.len() < length {
+        let ch = *candidates.choose(&mut rng).unwrap();
+        let count = appearances.entry(ch).or_insert(0);
+        if policy.max_char_appearances <= *count {
+            continue;
+        }
+        *count += 1;
+        password.push(ch);
+    }
+    // 文字の種類ごとに選択した文字の位置を推測できないように、文字の順番を入れ替え
+    password.shuffle(&mut rng);
+
+    Ok(RawPassword {
+        value: SecretString::new(password.into_iter().collect()),
+    })
+}
+
+/// ランダムに生成するパスワードの文字数
+pub const GENERATED_PASSWORD_LENGTH: usize = 16;
+
 /// パスワードにコショウを振りかける。
 fn sprinkle_pepper_on_password(raw_password: &RawPassword, pepper: &SecretString) -> SecretString {
     let mut password = raw_password.value.expose_secret().to_string();
@@ -94,6 +159,7 @@ fn sprinkle_pepper_on_password(raw_password: &RawPassword, pepper: &SecretString
 pub mod tests {
     use std::str::FromStr as _;
 
+    use domain::models::primitives::{PASSWORD_MAX_LENGTH, PASSWORD_POLICY};
     use domain::DomainError;
 
     use super::*;
@@ -198,6 +264,46 @@ pub mod tests {
         }
     }
 
+    /// 同じ文字を3つまで含む、指定した文字数のパスワードを生成する。
+    fn long_password(length: usize) -> String {
+        let candidates = ('A'..='Z')
+            .chain('a'..='z')
+            .chain('0'..='9')
+            .chain(PASSWORD_POLICY.symbols.chars())
+            .collect::<Vec<_>>();
+
+        candidates.repeat(3).into_iter().take(length).collect()
+    }
+
+    /// 最大文字数の文字列から未加工なパスワードを構築でき、最大文字数を超える文字列から構築できないことを確認
+    #[test]
+    fn raw_password_length_is_limited_to_max_length() {
+        let secret = SecretString::new(long_password(PASSWORD_POLICY.max_length));
+        assert!(RawPassword::new(secret).is_ok());
+
+        let secret = SecretString::new(long_password(PASSWORD_POLICY.max_length + 1));
+        match RawPassword::new(secret) {
+            Err(DomainError::DomainRule(message)) => {
+                assert_eq!("パスワードは128文字以下で指定してください。", message);
+            }
+            _ => panic!("DomainError::DomainRule should be returned when construct raw password from too long string"),
+        }
+    }
+
+    /// パスワードポリシーで指定した最大文字数で、未加工なパスワードの文字数を制限することを確認
+    #[test]
+    fn raw_password_length_is_limited_by_policy() {
+        let policy = PasswordPolicy {
+            max_length: 64,
+            ..PASSWORD_POLICY
+        };
+
+        let secret = SecretString::new(long_password(64));
+        assert!(RawPassword::with_policy(secret, &policy).is_ok());
+        let secret = SecretString::new(long_password(65));
+        assert!(RawPassword::with_policy(secret, &policy).is_err());
+    }
+
     /// 同じ文字が指定した数より多く含まれている文字列から、未加工なパスワードを構築できないことを確認
     #[test]
     fn can_not_construct_raw_password_containing_same_character_more_than_specified_times() {
@@ -227,6 +333,7 @@ pub mod tests {
             hash_memory: 12288,
             hash_iterations: 3,
             hash_parallelism: 1,
+            max_length: PASSWORD_MAX_LENGTH,
         }
     }
 
@@ -256,4 +363,64 @@ pub mod tests {
             RawPassword::new(SecretString::new(String::from(different_password))).unwrap();
         assert!(!verify_password(&different_password, &settings.pepper, &phc_string).unwrap());
     }
+
+    /// ランダムに生成したパスワードが、すべてパスワードポリシーを満たすことを確認
+    #[test]
+    fn generated_passwords_satisfy_password_policy() {
+        for _ in 0..500 {
+            let password = generate_random_password(&PASSWORD_POLICY).unwrap();
+            let value = password.value.expose_secret();
+            assert_eq!(GENERATED_PASSWORD_LENGTH, value.chars().count());
+            assert!(
+                RawPassword::new(password.value.clone()).is_ok(),
+                "`{}`",
+                value
+            );
+        }
+    }
+
+    /// パスワードの最小文字数が、ランダムに生成するパスワードの文字数より長い場合は、最小文字数の
+    /// パスワードを生成することを確認
+    #[test]
+    fn generated_password_has_at_least_min_length() {
+        let policy = PasswordPolicy {
+            min_length: GENERATED_PASSWORD_LENGTH + 8,
+            ..PASSWORD_POLICY
+        };
+
+        let password = generate_random_password(&policy).unwrap();
+
+        assert_eq!(
+            GENERATED_PASSWORD_LENGTH + 8,
+            password.value.expose_secret().chars().count()
+        );
+    }
+
+    /// ランダムに生成したパスワードが、毎回異なることを確認
+    #[test]
+    fn generated_passwords_are_different() {
+        let first = generate_random_password(&PASSWORD_POLICY).unwrap();
+        let second = generate_random_password(&PASSWORD_POLICY).unwrap();
+
+        assert_ne!(first.value.expose_secret(), second.value.expose_secret());
+    }
+
+    /// パスワードポリシーを満たすパスワードを生成できない場合は、エラーを返すことを確認
+    #[test]
+    fn can_not_generate_password_violating_password_policy() {
+        let policies = [
+            PasswordPolicy {
+                max_char_appearances: 0,
+                ..PASSWORD_POLICY
+            },
+            PasswordPolicy {
+                max_length: GENERATED_PASSWORD_LENGTH - 1,
+                ..PASSWORD_POLICY
+            },
+        ];
+
+        for policy in policies {
+            assert!(generate_random_password(&policy).is_err());
+        }
+    }
 }
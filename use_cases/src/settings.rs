@@ -1,6 +1,12 @@
-use secrecy::SecretString;
+use std::collections::HashSet;
+use std::path::PathBuf;
 
-use crate::{UseCaseError, UseCaseResult};
+use secrecy::{ExposeSecret as _, SecretString};
+use time::{Duration, OffsetDateTime};
+
+use domain::models::primitives::{PasswordPolicy, PASSWORD_MAX_LENGTH, PASSWORD_POLICY};
+
+use crate::passwords::GENERATED_PASSWORD_LENGTH;
 
 /// パスワード設定
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -13,6 +19,15 @@ pub struct PasswordSettings {
     pub hash_iterations: u32,
     /// パスワードをハッシュ化するときの並列度
     pub hash_parallelism: u32,
+    /// パスワードの最大文字数
+    ///
+    /// デフォルトは`128`で、最大文字数を超えるパスワードは、ハッシュ化する前に拒否する。
+    #[serde(default = "default_password_max_length")]
+    pub max_length: usize,
+}
+
+fn default_password_max_length() -> usize {
+    PASSWORD_MAX_LENGTH
 }
 
 /// 認証設定
@@ -21,29 +36,444 @@ pub struct AuthorizationSettings {
     /// ユーザーのサインインの試行を許可する期間（秒）
     pub attempting_seconds: u32,
     /// ユーザーのアカウントをロックするまでのサインイン失敗回数
-    pub number_of_failures: u16,
+    pub number_of_failures: i16,
+    /// ロックしたユーザーのアカウントを自動的にアンロックするまでの時間（秒）
+    ///
+    /// `0`の場合は、アカウントを自動的にアンロックしない。
+    #[serde(default)]
+    pub lockout_seconds: u64,
     /// JWTトークンを生成するときの秘密鍵
+    ///
+    /// 署名アルゴリズムが`hs256`の場合に、JWTを署名及び検証するときに使用する。
     pub jwt_token_secret: SecretString,
+    /// JWTの署名設定
+    #[serde(default)]
+    pub jwt_signing: JwtSigningSettings,
+    /// 2要素認証に使用するTOTPの秘密鍵を暗号化するときの暗号鍵
+    pub totp_secret_key: SecretString,
     /// アクセストークンの有効期限（秒）
     pub access_token_seconds: u64,
     /// リフレッシュトークンの有効期限（秒）
     pub refresh_token_seconds: u64,
+    /// サインインするときに、ユーザーがEメールアドレスを確認していることを要求するか示すフラグ
+    #[serde(default)]
+    pub require_verified_email: bool,
+    /// Eメールアドレスを確認するトークンの有効期限（秒）
+    #[serde(default = "default_email_verification_token_seconds")]
+    pub email_verification_token_seconds: u64,
+    /// Eメールアドレスの変更を確定するトークンの有効期限（秒）
+    #[serde(default = "default_email_change_token_seconds")]
+    pub email_change_token_seconds: u64,
+    /// 2要素認証を待機しているサインインを示すトークンの有効期限（秒）
+    #[serde(default = "default_mfa_pending_token_seconds")]
+    pub mfa_pending_token_seconds: u64,
+    /// パスワードの変更を要求されているユーザーが、パスワードを変更するためのトークンの有効期限（秒）
+    #[serde(default = "default_password_change_token_seconds")]
+    pub password_change_token_seconds: u64,
+    /// パスワードで再認証してから、パスワードを送信せずにアカウントの削除などの操作ができる期間（秒）
+    #[serde(default = "default_reauthentication_seconds")]
+    pub reauthentication_seconds: u64,
+    /// トークンを検査する内部サービスを認証する資格情報
+    ///
+    /// 指定しない場合は、トークンの検査を許可しない。
+    #[serde(default)]
+    pub introspection_credential: Option<SecretString>,
+}
+
+/// JWTの署名アルゴリズム
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JwtAlgorithm {
+    /// 共有する秘密鍵を使用するHMAC SHA-256
+    #[default]
+    Hs256,
+    /// RSA PKCS#1 v1.5 SHA-256
+    Rs256,
+    /// Ed25519
+    EdDsa,
+}
+
+impl JwtAlgorithm {
+    /// 公開鍵で署名を検証する非対称鍵のアルゴリズムであるか確認する。
+    pub fn is_asymmetric(&self) -> bool {
+        *self != Self::Hs256
+    }
+}
+
+/// JWTの署名設定
+///
+/// 非対称鍵で署名する場合、JWTを検証するサービスは公開鍵のみを持つため、JWTを生成できない。
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct JwtSigningSettings {
+    /// 署名アルゴリズム
+    #[serde(default)]
+    pub algorithm: JwtAlgorithm,
+    /// JWTを署名する鍵の鍵ID
+    ///
+    /// 生成したJWTのヘッダの`kid`に記録する。非対称鍵で署名する場合は必須。
+    #[serde(default)]
+    pub kid: Option<String>,
+    /// PEM形式のJWTを署名する秘密鍵ファイルのパス
+    ///
+    /// 非対称鍵で署名する場合は必須。
+    #[serde(default)]
+    pub private_key_path: Option<PathBuf>,
+    /// JWTの署名を検証する公開鍵
+    ///
+    /// 鍵をローテーションした後も以前の鍵で署名したJWTを検証できるように、署名する鍵の公開鍵に
+    /// 加えて、以前の鍵の公開鍵を指定する。
+    #[serde(default)]
+    pub public_keys: Vec<JwtPublicKeySettings>,
+}
+
+/// JWTの署名を検証する公開鍵の設定
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct JwtPublicKeySettings {
+    /// 鍵ID
+    pub kid: String,
+    /// PEM形式の公開鍵ファイルのパス
+    pub path: PathBuf,
+}
+
+impl JwtSigningSettings {
+    /// JWTの署名設定を検証する。
+    ///
+    /// 非対称鍵で署名する場合は、鍵ID及び秘密鍵ファイルのパスを指定しているか、署名する鍵の公開鍵を
+    /// 指定しているか確認する。また、公開鍵の鍵IDが重複していないか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if self.kid.as_ref().is_some_and(|kid| kid.is_empty()) {
+            error.push("kid", EMPTY_JWT_KID);
+        }
+        if self.algorithm.is_asymmetric() {
+            if self.kid.is_none() {
+                error.push("kid", EMPTY_JWT_KID);
+            }
+            if self.private_key_path.is_none() {
+                error.push("private_key_path", MISSING_JWT_PRIVATE_KEY);
+            }
+            if self
+                .kid
+                .as_ref()
+                .is_some_and(|kid| self.public_keys.iter().all(|key| &key.kid != kid))
+            {
+                error.push("public_keys", MISSING_JWT_SIGNING_PUBLIC_KEY);
+            }
+        }
+        let mut kids = HashSet::new();
+        if self
+            .public_keys
+            .iter()
+            .any(|key| key.kid.is_empty() || !kids.insert(key.kid.as_str()))
+        {
+            error.push("public_keys", INVALID_JWT_PUBLIC_KEY_IDS);
+        }
+
+        error.into_result()
+    }
+}
+
+/// 保持期間設定
+///
+/// サインインに失敗した履歴と、削除したユーザーのレコードを保持する期間を設定する。
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct RetentionSettings {
+    /// 最初にサインインに失敗してから、サインインに失敗した履歴を保持する日数
+    ///
+    /// `0`の場合は、サインインに失敗した履歴を消去しない。
+    #[serde(default)]
+    pub history_days: u32,
+    /// ユーザーのアカウントを削除してから、ユーザーのレコードを保持する日数
+    ///
+    /// `0`の場合は、削除したユーザーのレコードを物理的に削除しない。
+    #[serde(default)]
+    pub deleted_user_days: u32,
+    /// 保持期間を経過したレコードを定期的に消去する間隔（秒）
+    ///
+    /// `0`の場合は、定期的に消去しない。
+    #[serde(default = "default_retention_interval_seconds")]
+    pub interval_seconds: u64,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            history_days: 0,
+            deleted_user_days: 0,
+            interval_seconds: default_retention_interval_seconds(),
+        }
+    }
+}
+
+impl RetentionSettings {
+    /// 保持期間を経過したレコードを消去するか確認する。
+    ///
+    /// # 戻り値
+    ///
+    /// サインインに失敗した履歴または削除したユーザーの保持期間を設定している場合は`true`
+    pub fn is_enabled(&self) -> bool {
+        0 < self.history_days || 0 < self.deleted_user_days
+    }
+}
+
+/// 保持期間を経過したレコードを定期的に消去するデフォルトの間隔（秒）
+pub const DEFAULT_RETENTION_INTERVAL_SECONDS: u64 = 3600;
+
+fn default_retention_interval_seconds() -> u64 {
+    DEFAULT_RETENTION_INTERVAL_SECONDS
+}
+
+/// Eメールアドレスを確認するトークンのデフォルトの有効期限（秒）
+pub const DEFAULT_EMAIL_VERIFICATION_TOKEN_SECONDS: u64 = 86400;
+
+fn default_email_verification_token_seconds() -> u64 {
+    DEFAULT_EMAIL_VERIFICATION_TOKEN_SECONDS
+}
+
+/// Eメールアドレスの変更を確定するトークンのデフォルトの有効期限（秒）
+pub const DEFAULT_EMAIL_CHANGE_TOKEN_SECONDS: u64 = 3600;
+
+fn default_email_change_token_seconds() -> u64 {
+    DEFAULT_EMAIL_CHANGE_TOKEN_SECONDS
+}
+
+/// 2要素認証を待機しているサインインを示すトークンのデフォルトの有効期限（秒）
+pub const DEFAULT_MFA_PENDING_TOKEN_SECONDS: u64 = 300;
+
+fn default_mfa_pending_token_seconds() -> u64 {
+    DEFAULT_MFA_PENDING_TOKEN_SECONDS
+}
+
+/// パスワードを変更するためのトークンのデフォルトの有効期限（秒）
+pub const DEFAULT_PASSWORD_CHANGE_TOKEN_SECONDS: u64 = 600;
+
+fn default_password_change_token_seconds() -> u64 {
+    DEFAULT_PASSWORD_CHANGE_TOKEN_SECONDS
+}
+
+/// パスワードで再認証してから、パスワードを送信せずに操作ができるデフォルトの期間（秒）
+pub const DEFAULT_REAUTHENTICATION_SECONDS: u64 = 300;
+
+fn default_reauthentication_seconds() -> u64 {
+    DEFAULT_REAUTHENTICATION_SECONDS
+}
+
+impl PasswordSettings {
+    /// パスワード設定を検証する。
+    ///
+    /// ペッパーが空でないか、パスワードをハッシュ化するときのメモリサイズ、反復回数及び並列度が
+    /// 1以上であるか、パスワードの最大文字数が最小文字数及びランダムに生成するパスワードの文字数
+    /// 以上であるか、同じ文字を含められる数が1以上であるか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if self.pepper.expose_secret().is_empty() {
+            error.push("pepper", EMPTY_PEPPER);
+        }
+        if self.hash_memory == 0 {
+            error.push("hash_memory", MUST_BE_POSITIVE);
+        }
+        if self.hash_iterations == 0 {
+            error.push("hash_iterations", MUST_BE_POSITIVE);
+        }
+        if self.hash_parallelism == 0 {
+            error.push("hash_parallelism", MUST_BE_POSITIVE);
+        }
+        let policy = self.policy();
+        let min_max_length = policy.min_length.max(GENERATED_PASSWORD_LENGTH);
+        if policy.max_length < min_max_length {
+            error.push(
+                "max_length",
+                format!("パスワードの最大文字数には{min_max_length}以上を指定してください。"),
+            );
+        }
+        if policy.max_char_appearances == 0 {
+            error.push("max_char_appearances", MUST_BE_POSITIVE);
+        }
+
+        error.into_result()
+    }
+
+    /// 未加工なパスワードを構築するときに検証するパスワードポリシーを返す。
+    ///
+    /// # 戻り値
+    ///
+    /// パスワードの最大文字数を、設定した最大文字数にしたパスワードポリシー
+    pub fn policy(&self) -> PasswordPolicy {
+        PasswordPolicy {
+            max_length: self.max_length,
+            ..PASSWORD_POLICY
+        }
+    }
 }
 
 impl AuthorizationSettings {
     /// 認証設定を検証する。
-    pub fn validate(&self) -> UseCaseResult<()> {
+    ///
+    /// サインインの試行を許可する期間、アカウントをロックするまでのサインイン失敗回数、
+    /// アクセストークン、Eメールアドレスを確認するトークン、Eメールアドレスの変更を確定するトークン
+    /// 及び2要素認証を待機しているサインインを示すトークンの有効期限と、再認証してから操作ができる
+    /// 期間が1以上であるか、JWTトークンを
+    /// 生成するときの秘密鍵、TOTPの秘密鍵を暗号化するときの暗号鍵及び指定したトークンを検査する
+    /// 内部サービスの資格情報が空でないか、リフレッシュトークンの
+    /// 有効期限がアクセストークンの有効期限よりも長いか、JWTの署名設定が適切であるか確認する。
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        let mut error = SettingsError::default();
+        if self.attempting_seconds == 0 {
+            error.push("attempting_seconds", MUST_BE_POSITIVE);
+        }
+        if self.number_of_failures < 1 {
+            error.push("number_of_failures", MUST_BE_POSITIVE);
+        }
+        if self.jwt_token_secret.expose_secret().is_empty() {
+            error.push("jwt_token_secret", EMPTY_JWT_TOKEN_SECRET);
+        }
+        if self.totp_secret_key.expose_secret().is_empty() {
+            error.push("totp_secret_key", EMPTY_TOTP_SECRET_KEY);
+        }
+        if self.access_token_seconds == 0 {
+            error.push("access_token_seconds", MUST_BE_POSITIVE);
+        }
         if self.refresh_token_seconds <= self.access_token_seconds {
-            tracing::error!("{} ({}:{})", INVALID_TOKEN_EXPIRATIONS, file!(), line!());
-            return Err(UseCaseError::unexpected(INVALID_TOKEN_EXPIRATIONS));
+            error.push("refresh_token_seconds", INVALID_TOKEN_EXPIRATIONS);
+        }
+        if self.email_verification_token_seconds == 0 {
+            error.push("email_verification_token_seconds", MUST_BE_POSITIVE);
+        }
+        if self.email_change_token_seconds == 0 {
+            error.push("email_change_token_seconds", MUST_BE_POSITIVE);
+        }
+        if self.mfa_pending_token_seconds == 0 {
+            error.push("mfa_pending_token_seconds", MUST_BE_POSITIVE);
+        }
+        if self.password_change_token_seconds == 0 {
+            error.push("password_change_token_seconds", MUST_BE_POSITIVE);
+        }
+        if self.reauthentication_seconds == 0 {
+            error.push("reauthentication_seconds", MUST_BE_POSITIVE);
+        }
+        if self
+            .introspection_credential
+            .as_ref()
+            .is_some_and(|credential| credential.expose_secret().is_empty())
+        {
+            error.push("introspection_credential", EMPTY_INTROSPECTION_CREDENTIAL);
+        }
+        error.merge("jwt_signing", self.jwt_signing.validate());
+
+        error.into_result()
+    }
+
+    /// ロックしたユーザーのアカウントを、自動的にアンロックする時間が経過したか確認する。
+    ///
+    /// # 引数
+    ///
+    /// * `locked_at` - ユーザーのアカウントをロックした日時
+    /// * `now` - 現在の日時
+    ///
+    /// # 戻り値
+    ///
+    /// アカウントをロックした日時から`lockout_seconds`が経過した場合は`true`、自動的にアンロックしない
+    /// 場合、またはアカウントをロックした日時が記録されていない場合は`false`
+    pub fn is_lockout_expired(
+        &self,
+        locked_at: Option<OffsetDateTime>,
+        now: OffsetDateTime,
+    ) -> bool {
+        match locked_at {
+            Some(locked_at) if 0 < self.lockout_seconds => {
+                locked_at + Duration::seconds(self.lockout_seconds as i64) <= now
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 設定の違反
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsViolation {
+    /// 違反した設定のキー
+    pub field: String,
+    /// 違反の内容
+    pub message: String,
+}
+
+/// 設定エラー
+///
+/// 設定を検証したときに見つかったすべての違反を保持する。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsError {
+    /// 設定の違反
+    pub violations: Vec<SettingsViolation>,
+}
+
+impl SettingsError {
+    /// 設定の違反を追加する。
+    ///
+    /// # 引数
+    ///
+    /// * `field` - 違反した設定のキー
+    /// * `message` - 違反の内容
+    pub fn push(&mut self, field: impl Into<String>, message: impl Into<String>) {
+        self.violations.push(SettingsViolation {
+            field: field.into(),
+            message: message.into(),
+        });
+    }
+
+    /// 設定の検証結果を追加する。
+    ///
+    /// 違反した設定のキーには、設定のセクションを付与する。
+    ///
+    /// # 引数
+    ///
+    /// * `section` - 設定のセクション
+    /// * `result` - 設定の検証結果
+    pub fn merge(&mut self, section: &str, result: Result<(), SettingsError>) {
+        if let Err(error) = result {
+            for violation in error.violations {
+                self.push(
+                    format!("{}.{}", section, violation.field),
+                    violation.message,
+                );
+            }
+        }
+    }
+
+    /// 設定の違反がない場合は`Ok`、それ以外の場合は`Err`を返す。
+    pub fn into_result(self) -> Result<(), SettingsError> {
+        match self.violations.is_empty() {
+            true => Ok(()),
+            false => Err(self),
+        }
+    }
+}
+
+impl std::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "設定が不正です。")?;
+        for violation in &self.violations {
+            write!(f, "\n  * `{}`: {}", violation.field, violation.message)?;
         }
 
         Ok(())
     }
 }
 
+impl std::error::Error for SettingsError {}
+
 const INVALID_TOKEN_EXPIRATIONS: &str =
     "リフレッシュトークンの有効期限は、アクセストークンの有効期限よりも長くなければなりません。";
+const MUST_BE_POSITIVE: &str = "1以上を指定してください。";
+const EMPTY_PEPPER: &str = "ペッパーを指定してください。";
+const EMPTY_JWT_TOKEN_SECRET: &str = "JWTトークンを生成するときの秘密鍵を指定してください。";
+const EMPTY_TOTP_SECRET_KEY: &str = "TOTPの秘密鍵を暗号化するときの暗号鍵を指定してください。";
+const EMPTY_INTROSPECTION_CREDENTIAL: &str =
+    "トークンを検査する内部サービスの資格情報を指定してください。";
+const EMPTY_JWT_KID: &str = "JWTを署名する鍵の鍵IDを指定してください。";
+const MISSING_JWT_PRIVATE_KEY: &str = "JWTを署名する秘密鍵ファイルのパスを指定してください。";
+const MISSING_JWT_SIGNING_PUBLIC_KEY: &str =
+    "JWTを署名する鍵の鍵IDと同じ鍵IDの公開鍵を指定してください。";
+const INVALID_JWT_PUBLIC_KEY_IDS: &str = "公開鍵の鍵IDは、空でなく重複しない値を指定してください。";
 
 #[cfg(test)]
 pub mod tests {
@@ -55,12 +485,37 @@ pub mod tests {
         AuthorizationSettings {
             attempting_seconds: 300,
             number_of_failures: 5,
+            lockout_seconds: 1800,
             jwt_token_secret: SecretString::new(String::from("asdf")),
+            jwt_signing: JwtSigningSettings::default(),
+            totp_secret_key: SecretString::new(String::from("qwer")),
             access_token_seconds: 300,
             refresh_token_seconds: 400,
+            require_verified_email: false,
+            email_verification_token_seconds: DEFAULT_EMAIL_VERIFICATION_TOKEN_SECONDS,
+            email_change_token_seconds: DEFAULT_EMAIL_CHANGE_TOKEN_SECONDS,
+            mfa_pending_token_seconds: DEFAULT_MFA_PENDING_TOKEN_SECONDS,
+            password_change_token_seconds: DEFAULT_PASSWORD_CHANGE_TOKEN_SECONDS,
+            reauthentication_seconds: DEFAULT_REAUTHENTICATION_SECONDS,
+            introspection_credential: None,
         }
     }
 
+    /// アカウントをロックした日時から、自動的にアンロックする時間が経過したか確認できることを確認
+    #[test]
+    fn lockout_is_expired_after_lockout_seconds() {
+        let mut settings = authorization_settings();
+        let now = OffsetDateTime::now_utc();
+        let lockout = Duration::seconds(settings.lockout_seconds as i64);
+
+        assert!(!settings.is_lockout_expired(None, now));
+        assert!(!settings.is_lockout_expired(Some(now - lockout + Duration::seconds(1)), now));
+        assert!(settings.is_lockout_expired(Some(now - lockout), now));
+        // 自動的にアンロックしない場合
+        settings.lockout_seconds = 0;
+        assert!(!settings.is_lockout_expired(Some(now - lockout), now));
+    }
+
     /// 認証設定が適切であることを検証できるか確認
     #[test]
     fn authorization_settings_is_valid() {
@@ -77,4 +532,150 @@ pub mod tests {
         settings.refresh_token_seconds = 300;
         assert!(settings.validate().is_err());
     }
+
+    /// アカウントをロックするまでのサインイン失敗回数が1未満の場合は、認証設定が適切でないことを確認
+    #[test]
+    fn authorization_settings_with_non_positive_number_of_failures_is_invalid() {
+        for number_of_failures in [0, -1] {
+            let mut settings = authorization_settings();
+            settings.number_of_failures = number_of_failures;
+            let error = settings.validate().unwrap_err();
+            let fields = error
+                .violations
+                .iter()
+                .map(|v| v.field.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(vec!["number_of_failures"], fields);
+        }
+    }
+
+    /// 認証設定の違反をすべて報告することを確認
+    #[test]
+    fn authorization_settings_reports_all_violations() {
+        let mut settings = authorization_settings();
+        settings.attempting_seconds = 0;
+        settings.jwt_token_secret = SecretString::new(String::new());
+        settings.refresh_token_seconds = 300;
+        settings.email_verification_token_seconds = 0;
+        settings.email_change_token_seconds = 0;
+        settings.mfa_pending_token_seconds = 0;
+        settings.password_change_token_seconds = 0;
+        settings.reauthentication_seconds = 0;
+        settings.introspection_credential = Some(SecretString::new(String::new()));
+
+        let error = settings.validate().unwrap_err();
+
+        let fields = error
+            .violations
+            .iter()
+            .map(|v| v.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                "attempting_seconds",
+                "jwt_token_secret",
+                "refresh_token_seconds",
+                "email_verification_token_seconds",
+                "email_change_token_seconds",
+                "mfa_pending_token_seconds",
+                "password_change_token_seconds",
+                "reauthentication_seconds",
+                "introspection_credential",
+            ],
+            fields
+        );
+    }
+
+    /// 非対称鍵で署名する場合に、鍵ID、秘密鍵ファイル及び署名する鍵の公開鍵を要求することを確認
+    #[test]
+    fn jwt_signing_settings_require_keys_for_asymmetric_algorithm() {
+        let public_key = |kid: &str| JwtPublicKeySettings {
+            kid: String::from(kid),
+            path: PathBuf::from("public.pem"),
+        };
+        let mut settings = JwtSigningSettings {
+            algorithm: JwtAlgorithm::Rs256,
+            ..Default::default()
+        };
+        let error = settings.validate().unwrap_err();
+        let fields = error
+            .violations
+            .iter()
+            .map(|v| v.field.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["kid", "private_key_path"], fields);
+
+        settings.kid = Some(String::from("2024-05"));
+        settings.private_key_path = Some(PathBuf::from("private.pem"));
+        settings.public_keys = vec![public_key("2024-01")];
+        let error = settings.validate().unwrap_err();
+        assert_eq!("public_keys", error.violations[0].field);
+
+        settings.public_keys = vec![public_key("2024-05"), public_key("2024-01")];
+        assert!(settings.validate().is_ok());
+
+        settings.public_keys.push(public_key("2024-01"));
+        assert!(settings.validate().is_err());
+    }
+
+    /// パスワード設定の違反を報告することを確認
+    #[test]
+    fn password_settings_reports_violations() {
+        let mut settings = PasswordSettings {
+            pepper: SecretString::new(String::from("pepper")),
+            hash_memory: 12288,
+            hash_iterations: 3,
+            hash_parallelism: 1,
+            max_length: PASSWORD_MAX_LENGTH,
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.pepper = SecretString::new(String::new());
+        settings.hash_parallelism = 0;
+        settings.max_length = 7;
+        let error = settings.validate().unwrap_err();
+
+        assert_eq!(3, error.violations.len());
+        assert_eq!("pepper", error.violations[0].field);
+        assert_eq!("hash_parallelism", error.violations[1].field);
+        assert_eq!("max_length", error.violations[2].field);
+    }
+
+    /// パスワードの最大文字数が、ランダムに生成するパスワードの文字数より短い設定を拒否することを確認
+    #[test]
+    fn password_settings_rejects_max_length_shorter_than_generated_password() {
+        let mut settings = PasswordSettings {
+            pepper: SecretString::new(String::from("pepper")),
+            hash_memory: 12288,
+            hash_iterations: 3,
+            hash_parallelism: 1,
+            max_length: GENERATED_PASSWORD_LENGTH,
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.max_length = GENERATED_PASSWORD_LENGTH - 1;
+        let error = settings.validate().unwrap_err();
+
+        assert_eq!(1, error.violations.len());
+        assert_eq!("max_length", error.violations[0].field);
+    }
+
+    /// サインインに失敗した履歴または削除したユーザーの保持期間を設定した場合のみ、保持期間を
+    /// 経過したレコードを消去することを確認
+    #[test]
+    fn retention_is_enabled_when_any_retention_days_is_set() {
+        let mut settings = RetentionSettings::default();
+        assert!(!settings.is_enabled());
+        assert_eq!(
+            DEFAULT_RETENTION_INTERVAL_SECONDS,
+            settings.interval_seconds
+        );
+
+        settings.history_days = 90;
+        assert!(settings.is_enabled());
+
+        settings.history_days = 0;
+        settings.deleted_user_days = 30;
+        assert!(settings.is_enabled());
+    }
 }
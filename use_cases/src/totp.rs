@@ -0,0 +1,289 @@
+use aes_gcm::aead::Aead as _;
+use aes_gcm::{Aes256Gcm, KeyInit as _, Nonce};
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use rand::RngCore as _;
+use secrecy::{ExposeSecret as _, SecretString};
+use sha1::Sha1;
+use sha2::{Digest as _, Sha256};
+use time::OffsetDateTime;
+
+use domain::clock::Clock;
+
+use crate::{UseCaseError, UseCaseResult};
+
+/// TOTPの秘密鍵を生成する。
+///
+/// # 戻り値
+///
+/// 20バイトの乱数
+pub fn generate_totp_secret() -> Vec<u8> {
+    let mut secret = vec![0u8; TOTP_SECRET_LEN];
+    rand::thread_rng().fill_bytes(&mut secret);
+
+    secret
+}
+
+/// 日時が含まれるTOTPのタイムステップを返す。
+///
+/// # 引数
+///
+/// * `dt` - 日時
+///
+/// # 戻り値
+///
+/// UNIXエポックからの経過秒数をタイムステップの長さで割った値
+pub fn totp_step(dt: OffsetDateTime) -> u64 {
+    dt.unix_timestamp().max(0) as u64 / TOTP_STEP_SECONDS
+}
+
+/// タイムステップのTOTPコードを生成する。
+///
+/// RFC 6238に従って、HMAC-SHA1で6桁のコードを生成する。
+///
+/// # 引数
+///
+/// * `secret` - TOTPの秘密鍵
+/// * `step` - タイムステップ
+///
+/// # 戻り値
+///
+/// 6桁のTOTPコード
+pub fn totp_code(secret: &[u8], step: u64) -> String {
+    // HMACは任意の長さの鍵を受け付けるため、失敗しない
+    let mut mac = <Hmac<Sha1> as Mac>::new_from_slice(secret).unwrap();
+    mac.update(&step.to_be_bytes());
+    let hash = mac.finalize().into_bytes();
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(TOTP_DIGITS as u32),
+        width = TOTP_DIGITS
+    )
+}
+
+/// TOTPコードを検証する。
+///
+/// 時計のずれを許容するため、現在のタイムステップの前後1ステップのコードも有効とする。
+///
+/// # 引数
+///
+/// * `secret` - TOTPの秘密鍵
+/// * `code` - 検証するTOTPコード
+/// * `clock` - 時計
+///
+/// # 戻り値
+///
+/// コードが有効な場合はコードが一致したタイムステップ、それ以外の場合は`None`
+pub fn verify_totp_code(secret: &[u8], code: &str, clock: &impl Clock) -> Option<u64> {
+    if code.len() != TOTP_DIGITS || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let current = totp_step(clock.now_utc());
+
+    (current.saturating_sub(TOTP_ALLOWED_DRIFT)..=current + TOTP_ALLOWED_DRIFT)
+        .find(|step| constant_time_eq(totp_code(secret, *step).as_bytes(), code.as_bytes()))
+}
+
+/// 2つのバイト列が等しいか、比較にかかる時間がバイト列の内容に依存しない方法で確認する。
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// 認証アプリに登録する`otpauth`URIを生成する。
+///
+/// # 引数
+///
+/// * `account` - アカウント名（ユーザーのEメールアドレス）
+/// * `secret` - TOTPの秘密鍵
+///
+/// # 戻り値
+///
+/// `otpauth`URI
+pub fn totp_uri(account: &str, secret: &[u8]) -> String {
+    format!(
+        "otpauth://totp/{issuer}:{account}?secret={secret}&issuer={issuer}&algorithm=SHA1&digits={digits}&period={period}",
+        issuer = TOTP_ISSUER,
+        account = account.replace('@', "%40"),
+        secret = encode_totp_secret(secret),
+        digits = TOTP_DIGITS,
+        period = TOTP_STEP_SECONDS,
+    )
+}
+
+/// TOTPの秘密鍵を、認証アプリに手動で入力できるBase32文字列に変換する。
+///
+/// # 引数
+///
+/// * `secret` - TOTPの秘密鍵
+///
+/// # 戻り値
+///
+/// パディングを除いたBase32文字列
+pub fn encode_totp_secret(secret: &[u8]) -> String {
+    BASE32_NOPAD.encode(secret)
+}
+
+/// TOTPの秘密鍵を暗号化する。
+///
+/// 秘密鍵は、TOTP暗号鍵を`Sha256`でハッシュ化した値を鍵として`AES-256-GCM`で暗号化する。
+/// 暗号文の先頭には、暗号化するたびに生成するノンスを付与する。
+///
+/// # 引数
+///
+/// * `key` - TOTP暗号鍵
+/// * `secret` - TOTPの秘密鍵
+///
+/// # 戻り値
+///
+/// ノンスを付与した暗号文
+pub fn encrypt_totp_secret(key: &SecretString, secret: &[u8]) -> UseCaseResult<Vec<u8>> {
+    let cipher = totp_cipher(key);
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let encrypted = cipher
+        .encrypt(Nonce::from_slice(&nonce), secret)
+        .map_err(|e| {
+            tracing::error!("{} {} ({}:{})", ENCRYPTION_ERROR, e, file!(), line!());
+            UseCaseError::unexpected(ENCRYPTION_ERROR)
+        })?;
+
+    Ok([nonce.as_slice(), &encrypted].concat())
+}
+
+/// 暗号化したTOTPの秘密鍵を復号する。
+///
+/// # 引数
+///
+/// * `key` - TOTP暗号鍵
+/// * `encrypted` - ノンスを付与した暗号文
+///
+/// # 戻り値
+///
+/// TOTPの秘密鍵
+pub fn decrypt_totp_secret(key: &SecretString, encrypted: &[u8]) -> UseCaseResult<Vec<u8>> {
+    if encrypted.len() < NONCE_LEN {
+        tracing::error!("{} ({}:{})", DECRYPTION_ERROR, file!(), line!());
+        return Err(UseCaseError::unexpected(DECRYPTION_ERROR));
+    }
+    let (nonce, encrypted) = encrypted.split_at(NONCE_LEN);
+
+    totp_cipher(key)
+        .decrypt(Nonce::from_slice(nonce), encrypted)
+        .map_err(|e| {
+            tracing::error!("{} {} ({}:{})", DECRYPTION_ERROR, e, file!(), line!());
+            UseCaseError::unexpected(DECRYPTION_ERROR)
+        })
+}
+
+/// TOTP暗号鍵から、TOTPの秘密鍵を暗号化及び復号する暗号を構築する。
+fn totp_cipher(key: &SecretString) -> Aes256Gcm {
+    let key = Sha256::digest(key.expose_secret().as_bytes());
+
+    Aes256Gcm::new(&key)
+}
+
+/// `otpauth`URIに記録する発行者
+pub const TOTP_ISSUER: &str = "actix-web-example";
+/// TOTPのタイムステップの長さ（秒）
+pub const TOTP_STEP_SECONDS: u64 = 30;
+/// TOTPコードの桁数
+pub const TOTP_DIGITS: usize = 6;
+/// 時計のずれを許容するタイムステップの数
+const TOTP_ALLOWED_DRIFT: u64 = 1;
+/// TOTPの秘密鍵の長さ（バイト）
+const TOTP_SECRET_LEN: usize = 20;
+/// `AES-256-GCM`のノンスの長さ（バイト）
+const NONCE_LEN: usize = 12;
+
+const ENCRYPTION_ERROR: &str = "TOTPの秘密鍵を暗号化するときにエラーが発生しました。";
+const DECRYPTION_ERROR: &str = "TOTPの秘密鍵を復号するときにエラーが発生しました。";
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use domain::clock::FixedClock;
+
+    use super::*;
+
+    /// RFC 6238のテストで使用する秘密鍵
+    const RFC_SECRET: &[u8] = b"12345678901234567890";
+
+    /// RFC 6238のテストベクタと同じTOTPコードを生成することを確認
+    ///
+    /// RFC 6238のテストベクタは8桁であるため、下位6桁を比較する。
+    #[test]
+    fn totp_code_matches_rfc_6238_test_vectors() {
+        let vectors = [
+            (datetime!(1970-01-01 00:00:59 UTC), "287082"),
+            (datetime!(2005-03-18 01:58:29 UTC), "081804"),
+            (datetime!(2009-02-13 23:31:30 UTC), "005924"),
+            (datetime!(2033-05-18 03:33:20 UTC), "279037"),
+        ];
+        for (dt, expected) in vectors {
+            assert_eq!(expected, totp_code(RFC_SECRET, totp_step(dt)), "{}", dt);
+        }
+    }
+
+    /// 前後1ステップまでのTOTPコードを有効とすることを確認
+    #[test]
+    fn verify_totp_code_allows_one_step_drift() {
+        let now = datetime!(2024-05-01 12:00:15 UTC);
+        let clock = FixedClock(now);
+        let current = totp_step(now);
+
+        for step in [current - 1, current, current + 1] {
+            let code = totp_code(RFC_SECRET, step);
+            assert_eq!(Some(step), verify_totp_code(RFC_SECRET, &code, &clock));
+        }
+        for step in [current - 2, current + 2] {
+            let code = totp_code(RFC_SECRET, step);
+            assert_eq!(None, verify_totp_code(RFC_SECRET, &code, &clock));
+        }
+    }
+
+    /// 6桁の数字でないTOTPコードを無効とすることを確認
+    #[test]
+    fn verify_totp_code_rejects_malformed_code() {
+        let clock = FixedClock(datetime!(2024-05-01 12:00:15 UTC));
+        for code in ["", "12345", "1234567", "12345a", " 12345"] {
+            assert_eq!(None, verify_totp_code(RFC_SECRET, code, &clock), "{}", code);
+        }
+    }
+
+    /// 認証アプリに登録する`otpauth`URIを生成できることを確認
+    #[test]
+    fn can_generate_totp_uri() {
+        let uri = totp_uri("foo@example.com", RFC_SECRET);
+
+        assert_eq!(
+            "otpauth://totp/actix-web-example:foo%40example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=actix-web-example&algorithm=SHA1&digits=6&period=30",
+            uri
+        );
+    }
+
+    /// 暗号化したTOTPの秘密鍵を、同じTOTP暗号鍵でのみ復号できることを確認
+    #[test]
+    fn encrypted_totp_secret_can_be_decrypted_only_with_same_key() -> anyhow::Result<()> {
+        let key = SecretString::new(String::from("totp-secret-key"));
+        let secret = generate_totp_secret();
+
+        let encrypted = encrypt_totp_secret(&key, &secret)?;
+
+        assert_ne!(secret, encrypted);
+        assert_eq!(secret, decrypt_totp_secret(&key, &encrypted)?);
+        let other_key = SecretString::new(String::from("other-key"));
+        assert!(decrypt_totp_secret(&other_key, &encrypted).is_err());
+        assert!(decrypt_totp_secret(&key, &encrypted[..4]).is_err());
+
+        Ok(())
+    }
+}